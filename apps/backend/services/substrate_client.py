@@ -640,7 +640,7 @@ class SubstrateClient:
                 call_function='vote',
                 call_params={
                     'proposal_id': proposal_id,
-                    'in_favor': in_favor
+                    'vote': 'Aye' if in_favor else 'Nay'
                 }
             )
             
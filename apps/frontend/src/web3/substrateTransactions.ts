@@ -273,7 +273,7 @@ export async function voteOnProposal(
     const injector = await web3FromAddress(account.address);
     
     // Create the extrinsic
-    const tx = api.tx.dao.vote(proposalId, inFavor);
+    const tx = api.tx.dao.vote(proposalId, inFavor ? 'Aye' : 'Nay');
     
     return new Promise((resolve, reject) => {
       tx.signAndSend(
@@ -0,0 +1,94 @@
+//! Typed errors for TidyGen RPC calls
+
+use jsonrpsee::{
+    core::Error as JsonRpseeError,
+    types::error::{CallError, ErrorObjectOwned, INVALID_PARAMS_CODE, METHOD_NOT_FOUND_CODE},
+};
+use std::fmt;
+
+/// Error code the pallet RPC servers use for failed runtime API calls
+pub const RUNTIME_ERROR: i32 = pallet_did_rpc::RUNTIME_ERROR;
+
+/// Errors returned by [`crate::TidygenClient`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TidygenRpcError {
+    /// An account argument is not a valid SS58 address
+    InvalidAddress(String),
+    /// A hash argument is not a 32-byte hex string
+    InvalidHash(String),
+    /// The runtime API call failed on the node
+    Runtime {
+        /// Debug output of the runtime error, if the node sent it
+        detail: Option<String>,
+    },
+    /// The node does not expose the method (pallet RPC not installed)
+    MethodNotFound(String),
+    /// The node rejected the request parameters
+    InvalidParams(String),
+    /// Any other JSON-RPC error response
+    Rpc {
+        /// JSON-RPC error code
+        code: i32,
+        /// Error message sent by the node
+        message: String,
+    },
+    /// The response could not be decoded into the expected type
+    Decode(String),
+    /// The request timed out
+    Timeout,
+    /// Connection or transport failure
+    Transport(String),
+}
+
+impl TidygenRpcError {
+    /// Map a JSON-RPC error object onto the typed variants
+    fn from_error_object(err: ErrorObjectOwned) -> Self {
+        match err.code() {
+            RUNTIME_ERROR => Self::Runtime {
+                detail: err.data().map(|data| {
+                    // Data is a JSON string; fall back to the raw JSON otherwise
+                    serde_json::from_str::<String>(data.get())
+                        .unwrap_or_else(|_| data.get().to_owned())
+                }),
+            },
+            METHOD_NOT_FOUND_CODE => Self::MethodNotFound(err.message().to_owned()),
+            INVALID_PARAMS_CODE => Self::InvalidParams(err.message().to_owned()),
+            code => Self::Rpc {
+                code,
+                message: err.message().to_owned(),
+            },
+        }
+    }
+}
+
+impl From<JsonRpseeError> for TidygenRpcError {
+    fn from(err: JsonRpseeError) -> Self {
+        match err {
+            JsonRpseeError::Call(CallError::Custom(object)) => Self::from_error_object(object),
+            JsonRpseeError::Call(CallError::InvalidParams(e)) => Self::InvalidParams(e.to_string()),
+            JsonRpseeError::Call(call) => Self::from_error_object(call.into()),
+            JsonRpseeError::ParseError(e) => Self::Decode(e.to_string()),
+            JsonRpseeError::RequestTimeout => Self::Timeout,
+            other => Self::Transport(other.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for TidygenRpcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidAddress(address) => write!(f, "invalid SS58 address: {}", address),
+            Self::InvalidHash(hash) => write!(f, "invalid 32-byte hex hash: {}", hash),
+            Self::Runtime { detail: Some(detail) } => write!(f, "runtime error: {}", detail),
+            Self::Runtime { detail: None } => write!(f, "runtime error"),
+            Self::MethodNotFound(message) => write!(f, "method not found: {}", message),
+            Self::InvalidParams(message) => write!(f, "invalid params: {}", message),
+            Self::Rpc { code, message } => write!(f, "rpc error {}: {}", code, message),
+            Self::Decode(message) => write!(f, "decode error: {}", message),
+            Self::Timeout => write!(f, "request timed out"),
+            Self::Transport(message) => write!(f, "transport error: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for TidygenRpcError {}
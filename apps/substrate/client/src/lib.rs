@@ -0,0 +1,526 @@
+//! # TidyGen Client
+//!
+//! Typed Rust client for the RPC methods exposed by the TidyGen pallets.
+//!
+//! ## Overview
+//!
+//! The pallet RPC traits derive jsonrpsee clients via `#[rpc(client, server)]`. This crate
+//! re-exports those generated clients and wraps them in [`TidygenClient`], which:
+//! - Connects to a node over WebSocket with [`TidygenClient::connect`]
+//! - Accepts accounts as SS58 addresses and hashes as hex strings
+//! - Maps JSON-RPC error responses onto [`TidygenRpcError`]
+//!
+//! Runtime DTOs such as DID documents, invoices and snapshot pages depend on the runtime
+//! configuration, so the wrappers are generic over them: pass any type that deserializes
+//! from the node's JSON (a mirror struct or `serde_json::Value`). The pallets' `serde`
+//! feature derives that encoding on the runtime types themselves: fields are camelCase,
+//! byte fields are `0x` hex strings and accounts use their own serde form (SS58 for
+//! `AccountId32`).
+//!
+//! ## Example
+//!
+//! ```ignore
+//! let client = TidygenClient::connect("ws://127.0.0.1:9944").await?;
+//!
+//! let alice = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+//! let active = client.is_did_active(alice, None).await?;
+//! let invoice_id = client.get_invoice_by_hash("0x8f43...", None).await?;
+//! ```
+
+mod error;
+
+pub use error::{TidygenRpcError, RUNTIME_ERROR};
+pub use jsonrpsee::ws_client::{WsClient, WsClientBuilder};
+pub use pallet_compliance_log_rpc::ComplianceLogApiClient;
+pub use pallet_dao_rpc::DaoApiClient;
+pub use pallet_did_rpc::{CacheStats, DidApiClient};
+pub use pallet_ledger_rpc::LedgerApiClient;
+pub use pallet_tidygen_ledger_rpc::{AnchorWithProof, TidygenLedgerApiClient};
+pub use sp_core::{crypto::AccountId32, H256};
+pub use tidygen_primitives::{
+    features::FeatureFlags, governance::ProposalCategory, identity::Resolution,
+    pagination::PageCursor,
+};
+pub use tidygen_rpc::TidygenApiClient;
+
+use serde::de::DeserializeOwned;
+use sp_core::crypto::Ss58Codec;
+use std::str::FromStr;
+
+/// Result of a [`TidygenClient`] call
+pub type Result<T> = std::result::Result<T, TidygenRpcError>;
+
+/// Parse an SS58 address into an account ID
+pub fn parse_address(address: &str) -> Result<AccountId32> {
+    AccountId32::from_ss58check(address)
+        .map_err(|_| TidygenRpcError::InvalidAddress(address.to_owned()))
+}
+
+/// Parse a 32-byte hex hash, with or without `0x` prefix
+pub fn parse_hash(hash: &str) -> Result<H256> {
+    let digits = hash.strip_prefix("0x").unwrap_or(hash);
+    if digits.len() != 64 {
+        return Err(TidygenRpcError::InvalidHash(hash.to_owned()));
+    }
+    H256::from_str(digits).map_err(|_| TidygenRpcError::InvalidHash(hash.to_owned()))
+}
+
+/// Typed client for the TidyGen node RPC
+pub struct TidygenClient {
+    inner: WsClient,
+}
+
+impl TidygenClient {
+    /// Connect to a node over WebSocket with default settings
+    pub async fn connect(url: &str) -> Result<Self> {
+        Self::connect_with(WsClientBuilder::default(), url).await
+    }
+
+    /// Connect with a customized `WsClientBuilder` (timeouts, request limits, ...)
+    pub async fn connect_with(builder: WsClientBuilder, url: &str) -> Result<Self> {
+        let inner = builder.build(url).await?;
+        Ok(Self { inner })
+    }
+
+    /// The underlying jsonrpsee client, for direct use of the generated API clients
+    pub fn inner(&self) -> &WsClient {
+        &self.inner
+    }
+
+    // Runtime
+
+    /// Get the optional features the runtime enables, to decide what a frontend offers
+    pub async fn get_features(&self, at: Option<H256>) -> Result<FeatureFlags> {
+        Ok(TidygenApiClient::<H256>::get_features(&self.inner, at).await?)
+    }
+
+    // DID
+
+    /// Get the DID document of an account
+    pub async fn get_did<D>(&self, address: &str, at: Option<H256>) -> Result<Option<D>>
+    where
+        D: DeserializeOwned + Send + Sync + 'static,
+    {
+        let account = parse_address(address)?;
+        Ok(DidApiClient::<H256, AccountId32, D, ()>::get_did(&self.inner, account, at).await?)
+    }
+
+    /// Resolve a DID identifier to its account
+    pub async fn get_account_from_did(
+        &self,
+        did_identifier: &str,
+        at: Option<H256>,
+    ) -> Result<Option<AccountId32>> {
+        Ok(DidApiClient::<H256, AccountId32, (), ()>::get_account_from_did(
+            &self.inner,
+            did_identifier.to_owned(),
+            at,
+        )
+        .await?)
+    }
+
+    /// Check whether the DID of an account is active
+    pub async fn is_did_active(&self, address: &str, at: Option<H256>) -> Result<bool> {
+        let account = parse_address(address)?;
+        Ok(DidApiClient::<H256, AccountId32, (), ()>::is_did_active(&self.inner, account, at)
+            .await?)
+    }
+
+    /// Get the total number of registered DIDs
+    pub async fn get_total_dids(&self, at: Option<H256>) -> Result<u64> {
+        Ok(DidApiClient::<H256, AccountId32, (), ()>::get_total_dids(&self.inner, at).await?)
+    }
+
+    /// Get the trust score of an account's DID
+    pub async fn get_trust_score(&self, address: &str, at: Option<H256>) -> Result<u32> {
+        let account = parse_address(address)?;
+        Ok(DidApiClient::<H256, AccountId32, (), ()>::get_trust_score(&self.inner, account, at)
+            .await?)
+    }
+
+    /// Resolve a DID identifier to a local account or the network it lives on
+    pub async fn resolve_any(
+        &self,
+        did_identifier: &str,
+        at: Option<H256>,
+    ) -> Result<Resolution<AccountId32>> {
+        Ok(DidApiClient::<H256, AccountId32, (), ()>::resolve_any(
+            &self.inner,
+            did_identifier.to_owned(),
+            at,
+        )
+        .await?)
+    }
+
+    /// Get what changed on the DID of an account between `at_from` and `at_to` (best block if
+    /// `None`), or `None` if it has no DID at either block
+    pub async fn get_did_diff<F>(
+        &self,
+        address: &str,
+        at_from: H256,
+        at_to: Option<H256>,
+    ) -> Result<Option<F>>
+    where
+        F: DeserializeOwned + Send + Sync + 'static,
+    {
+        let account = parse_address(address)?;
+        Ok(DidApiClient::<H256, AccountId32, (), F>::get_diff(
+            &self.inner,
+            account,
+            at_from,
+            at_to,
+        )
+        .await?)
+    }
+
+    /// Get the hit and miss counters of the node's `did_getDid` response cache
+    pub async fn did_cache_stats(&self) -> Result<CacheStats> {
+        Ok(DidApiClient::<H256, AccountId32, (), ()>::cache_stats(&self.inner).await?)
+    }
+
+    // Ledger
+
+    /// Get a single invoice of a client
+    pub async fn get_invoice<I>(
+        &self,
+        client: &str,
+        invoice_id: u64,
+        at: Option<H256>,
+    ) -> Result<Option<I>>
+    where
+        I: DeserializeOwned + Send + Sync + 'static,
+    {
+        let client = parse_address(client)?;
+        Ok(LedgerApiClient::<H256, AccountId32, (), I, ()>::get_invoice(
+            &self.inner,
+            client,
+            invoice_id,
+            at,
+        )
+        .await?)
+    }
+
+    /// Get a page of a client's invoices and the cursor for the next page
+    pub async fn get_client_invoices_paged<I>(
+        &self,
+        client: &str,
+        cursor: Option<PageCursor>,
+        limit: u32,
+        at: Option<H256>,
+    ) -> Result<(Vec<I>, Option<PageCursor>)>
+    where
+        I: DeserializeOwned + Send + Sync + 'static,
+    {
+        let client = parse_address(client)?;
+        Ok(LedgerApiClient::<H256, AccountId32, (), I, ()>::get_client_invoices_paged(
+            &self.inner,
+            client,
+            cursor,
+            limit,
+            at,
+        )
+        .await?)
+    }
+
+    /// Get the number of invoices a client currently holds
+    pub async fn get_client_invoice_count(&self, client: &str, at: Option<H256>) -> Result<u32> {
+        let client = parse_address(client)?;
+        Ok(LedgerApiClient::<H256, AccountId32, (), (), ()>::get_client_invoice_count(
+            &self.inner,
+            client,
+            at,
+        )
+        .await?)
+    }
+
+    /// Get all invoices issued by a creator
+    pub async fn get_creator_invoices<I>(&self, creator: &str, at: Option<H256>) -> Result<Vec<I>>
+    where
+        I: DeserializeOwned + Send + Sync + 'static,
+    {
+        let creator = parse_address(creator)?;
+        Ok(
+            LedgerApiClient::<H256, AccountId32, (), I, ()>::get_creator_invoices(
+                &self.inner,
+                creator,
+                at,
+            )
+            .await?,
+        )
+    }
+
+    /// Get the invoice ID stored for a hex invoice hash
+    pub async fn get_invoice_by_hash(
+        &self,
+        invoice_hash: &str,
+        at: Option<H256>,
+    ) -> Result<Option<u64>> {
+        let invoice_hash = parse_hash(invoice_hash)?;
+        Ok(LedgerApiClient::<H256, AccountId32, (), (), ()>::get_invoice_by_hash(
+            &self.inner,
+            invoice_hash,
+            at,
+        )
+        .await?)
+    }
+
+    /// Get the invoice carrying an external reference (e.g. "INV-2025-001")
+    pub async fn get_invoice_by_external_ref<I>(
+        &self,
+        external_ref: &str,
+        at: Option<H256>,
+    ) -> Result<Option<I>>
+    where
+        I: DeserializeOwned + Send + Sync + 'static,
+    {
+        Ok(LedgerApiClient::<H256, AccountId32, (), I, ()>::get_invoice_by_external_ref(
+            &self.inner,
+            external_ref.to_owned(),
+            at,
+        )
+        .await?)
+    }
+
+    /// Get the hash kept for an archived invoice
+    pub async fn get_archived_invoice_hash(
+        &self,
+        client: &str,
+        invoice_id: u64,
+        at: Option<H256>,
+    ) -> Result<Option<H256>> {
+        let client = parse_address(client)?;
+        Ok(LedgerApiClient::<H256, AccountId32, (), (), ()>::get_archived_invoice_hash(
+            &self.inner,
+            client,
+            invoice_id,
+            at,
+        )
+        .await?)
+    }
+
+    /// Verify that a stored invoice still matches its hash
+    pub async fn verify_invoice_hash(
+        &self,
+        client: &str,
+        invoice_id: u64,
+        at: Option<H256>,
+    ) -> Result<bool> {
+        let client = parse_address(client)?;
+        Ok(LedgerApiClient::<H256, AccountId32, (), (), ()>::verify_invoice_hash(
+            &self.inner,
+            client,
+            invoice_id,
+            at,
+        )
+        .await?)
+    }
+
+    /// Verify the creator signature stored with an invoice against its hash
+    pub async fn verify_invoice_signature(
+        &self,
+        client: &str,
+        invoice_id: u64,
+        at: Option<H256>,
+    ) -> Result<bool> {
+        let client = parse_address(client)?;
+        Ok(LedgerApiClient::<H256, AccountId32, (), (), ()>::verify_invoice_signature(
+            &self.inner,
+            client,
+            invoice_id,
+            at,
+        )
+        .await?)
+    }
+
+    /// Get the total a client still owes across all of its invoices
+    pub async fn get_outstanding_balance<B>(&self, client: &str, at: Option<H256>) -> Result<B>
+    where
+        B: DeserializeOwned + Send + Sync + 'static,
+    {
+        let client = parse_address(client)?;
+        Ok(LedgerApiClient::<H256, AccountId32, B, (), ()>::get_outstanding_balance(
+            &self.inner,
+            client,
+            at,
+        )
+        .await?)
+    }
+
+    /// Get what a creator invoiced and what it collected, in that order
+    pub async fn get_creator_stats<B>(&self, creator: &str, at: Option<H256>) -> Result<(B, B)>
+    where
+        B: DeserializeOwned + Send + Sync + 'static,
+    {
+        let creator = parse_address(creator)?;
+        Ok(LedgerApiClient::<H256, AccountId32, B, (), ()>::get_creator_stats(
+            &self.inner,
+            creator,
+            at,
+        )
+        .await?)
+    }
+
+    /// Get what settling an invoice costs now, late fee accrued so far included
+    pub async fn get_amount_due<B>(
+        &self,
+        client: &str,
+        invoice_id: u64,
+        at: Option<H256>,
+    ) -> Result<Option<B>>
+    where
+        B: DeserializeOwned + Send + Sync + 'static,
+    {
+        let client = parse_address(client)?;
+        Ok(LedgerApiClient::<H256, AccountId32, B, (), ()>::get_amount_due(
+            &self.inner,
+            client,
+            invoice_id,
+            at,
+        )
+        .await?)
+    }
+
+    /// Check whether an account may decrypt the payload of the invoice with a hex hash
+    pub async fn has_access(
+        &self,
+        invoice_hash: &str,
+        who: &str,
+        at: Option<H256>,
+    ) -> Result<bool> {
+        let invoice_hash = parse_hash(invoice_hash)?;
+        let who = parse_address(who)?;
+        Ok(LedgerApiClient::<H256, AccountId32, (), (), ()>::has_access(
+            &self.inner,
+            invoice_hash,
+            who,
+            at,
+        )
+        .await?)
+    }
+
+    /// Get the documents attached to an invoice, in the order they were attached
+    pub async fn get_attachments<A>(
+        &self,
+        client: &str,
+        invoice_id: u64,
+        at: Option<H256>,
+    ) -> Result<Vec<A>>
+    where
+        A: DeserializeOwned + Send + Sync + 'static,
+    {
+        let client = parse_address(client)?;
+        Ok(LedgerApiClient::<H256, AccountId32, (), (), A>::get_attachments(
+            &self.inner,
+            client,
+            invoice_id,
+            at,
+        )
+        .await?)
+    }
+
+    // Anchors
+
+    /// Get the anchor of a hex transaction hash with a storage read proof at the block
+    pub async fn get_anchor_with_proof<N, A>(
+        &self,
+        tx_hash: &str,
+        at: Option<H256>,
+    ) -> Result<Option<AnchorWithProof<H256, N, A>>>
+    where
+        N: DeserializeOwned + Send + Sync + 'static,
+        A: DeserializeOwned + Send + Sync + 'static,
+    {
+        let tx_hash = parse_hash(tx_hash)?;
+        Ok(TidygenLedgerApiClient::<H256, N, A>::get_anchor_with_proof(&self.inner, tx_hash, at)
+            .await?)
+    }
+
+    // DAO
+
+    /// Export a page of the governance snapshot
+    pub async fn export_snapshot<P>(
+        &self,
+        limit: u32,
+        cursor: Option<PageCursor>,
+        at: Option<H256>,
+    ) -> Result<P>
+    where
+        P: DeserializeOwned + Send + Sync + 'static,
+    {
+        Ok(DaoApiClient::<H256, AccountId32, P, ()>::export_snapshot(
+            &self.inner,
+            limit,
+            cursor,
+            at,
+        )
+        .await?)
+    }
+
+    /// Digest over the full governance snapshot
+    pub async fn snapshot_digest(&self, at: Option<H256>) -> Result<H256> {
+        Ok(DaoApiClient::<H256, AccountId32, (), ()>::snapshot_digest(&self.inner, at).await?)
+    }
+
+    /// Get up to `limit` vote receipts of an account with their IDs, skipping the `offset`
+    /// oldest
+    pub async fn get_receipts<R>(
+        &self,
+        address: &str,
+        offset: u32,
+        limit: u32,
+        at: Option<H256>,
+    ) -> Result<Vec<(u64, R)>>
+    where
+        R: DeserializeOwned + Send + Sync + 'static,
+    {
+        let account = parse_address(address)?;
+        Ok(DaoApiClient::<H256, AccountId32, (), R>::get_receipts(
+            &self.inner,
+            account,
+            offset,
+            limit,
+            at,
+        )
+        .await?)
+    }
+
+    /// Get up to `limit` IDs of the proposals filed under a category, in creation order,
+    /// skipping the first `offset`
+    pub async fn get_proposals_by_category(
+        &self,
+        category: ProposalCategory,
+        offset: u32,
+        limit: u32,
+        at: Option<H256>,
+    ) -> Result<Vec<u64>> {
+        Ok(DaoApiClient::<H256, AccountId32, (), ()>::get_proposals_by_category(
+            &self.inner,
+            category,
+            offset,
+            limit,
+            at,
+        )
+        .await?)
+    }
+
+    // Compliance log
+
+    /// Get up to `limit` status transition records of a day, skipping the first `offset`
+    pub async fn get_compliance_day<R>(
+        &self,
+        day: u32,
+        offset: u32,
+        limit: u32,
+        at: Option<H256>,
+    ) -> Result<Vec<R>>
+    where
+        R: DeserializeOwned + Send + Sync + 'static,
+    {
+        Ok(ComplianceLogApiClient::<H256, R>::get_day(&self.inner, day, offset, limit, at).await?)
+    }
+
+    /// Running digest over the status transition records of a day
+    pub async fn compliance_day_digest(&self, day: u32, at: Option<H256>) -> Result<Option<H256>> {
+        Ok(ComplianceLogApiClient::<H256, ()>::day_digest(&self.inner, day, at).await?)
+    }
+}
@@ -0,0 +1,663 @@
+//! Round-trip tests against mocked pallet RPC servers
+
+use jsonrpsee::{
+    core::{async_trait, client::ClientT, RpcResult},
+    server::{ServerBuilder, ServerHandle},
+    types::error::{CallError, ErrorObject, INVALID_PARAMS_CODE},
+    RpcModule,
+};
+use pallet_compliance_log_rpc::ComplianceLogApiServer;
+use pallet_dao_rpc::DaoApiServer;
+use pallet_did_rpc::DidApiServer;
+use pallet_ledger_rpc::LedgerApiServer;
+use pallet_tidygen_ledger_rpc::TidygenLedgerApiServer;
+use serde::{Deserialize, Serialize};
+use sp_core::crypto::Ss58Codec;
+use sp_core::Bytes;
+use tidygen_client::{
+    AccountId32, AnchorWithProof, CacheStats, FeatureFlags, PageCursor, ProposalCategory,
+    Resolution, TidygenClient, TidygenRpcError, H256, RUNTIME_ERROR,
+};
+use tidygen_rpc::TidygenApiServer;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct TestDid {
+    controller: AccountId32,
+    nonce: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct TestDidDiff {
+    changed: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct TestInvoice {
+    id: u64,
+    client: AccountId32,
+    amount: u128,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct TestAttachment {
+    doc_hash: H256,
+    attached_by: AccountId32,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct TestAnchor {
+    anchored_by: AccountId32,
+    block_number: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct TestSnapshotPage {
+    entries: Vec<u64>,
+    next_cursor: Option<PageCursor>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct TestReceipt {
+    proposal_id: u64,
+    block: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct TestTransitionRecord {
+    old: u8,
+    new: u8,
+    block: u32,
+}
+
+fn alice() -> AccountId32 {
+    AccountId32::new([1u8; 32])
+}
+
+fn bob() -> AccountId32 {
+    AccountId32::new([2u8; 32])
+}
+
+fn invoice(id: u64) -> TestInvoice {
+    TestInvoice {
+        id,
+        client: alice(),
+        amount: 100 + u128::from(id),
+    }
+}
+
+fn runtime_error() -> jsonrpsee::core::Error {
+    CallError::Custom(ErrorObject::owned(RUNTIME_ERROR, "Runtime error", Some("trap"))).into()
+}
+
+/// Position of a cursor, rejected the way the pallet RPC servers do
+fn position(cursor: Option<PageCursor>) -> RpcResult<u64> {
+    cursor.map_or(Ok(0), |cursor| {
+        cursor.position().map_err(|_| {
+            CallError::Custom(ErrorObject::owned(
+                INVALID_PARAMS_CODE,
+                "Invalid cursor",
+                None::<()>,
+            ))
+            .into()
+        })
+    })
+}
+
+struct MockDid;
+
+#[async_trait]
+impl DidApiServer<H256, AccountId32, TestDid, TestDidDiff> for MockDid {
+    fn get_did(&self, account: AccountId32, _at: Option<H256>) -> RpcResult<Option<TestDid>> {
+        Ok((account == alice()).then(|| TestDid {
+            controller: account,
+            nonce: 3,
+        }))
+    }
+
+    fn get_account_from_did(
+        &self,
+        did_identifier: String,
+        _at: Option<H256>,
+    ) -> RpcResult<Option<AccountId32>> {
+        Ok((did_identifier == "did:tidygen:alice").then(alice))
+    }
+
+    fn is_did_active(&self, account: AccountId32, _at: Option<H256>) -> RpcResult<bool> {
+        Ok(account == alice())
+    }
+
+    fn get_total_dids(&self, at: Option<H256>) -> RpcResult<u64> {
+        // Echo whether a block hash arrived so the test can check `at` is forwarded
+        Ok(if at == Some(H256::repeat_byte(9)) { 7 } else { 1 })
+    }
+
+    fn get_trust_score(&self, account: AccountId32, _at: Option<H256>) -> RpcResult<u32> {
+        Ok(if account == alice() { 150 } else { 0 })
+    }
+
+    fn resolve_any(
+        &self,
+        did_identifier: String,
+        _at: Option<H256>,
+    ) -> RpcResult<Resolution<AccountId32>> {
+        Ok(match did_identifier.as_str() {
+            "did:tidygen:alice" => Resolution::Local(alice()),
+            "did:tidygen:finance-bob" => Resolution::External(*b"finance\0"),
+            _ => Resolution::Unknown,
+        })
+    }
+
+    fn get_diff(
+        &self,
+        account: AccountId32,
+        at_from: H256,
+        at_to: Option<H256>,
+    ) -> RpcResult<Option<TestDidDiff>> {
+        // The metadata bit, or nothing when diffing a block against itself
+        let changed = if Some(at_from) == at_to { 0 } else { 4 };
+        Ok((account == alice()).then_some(TestDidDiff { changed }))
+    }
+
+    fn cache_stats(&self) -> RpcResult<CacheStats> {
+        Ok(CacheStats { hits: 40, misses: 2, entries: 2, capacity: 128 })
+    }
+}
+
+struct MockLedger;
+
+#[async_trait]
+impl LedgerApiServer<H256, AccountId32, u128, TestInvoice, TestAttachment> for MockLedger {
+    fn get_invoice(
+        &self,
+        client: AccountId32,
+        invoice_id: u64,
+        _at: Option<H256>,
+    ) -> RpcResult<Option<TestInvoice>> {
+        if invoice_id == 99 {
+            return Err(runtime_error());
+        }
+        Ok((client == alice()).then(|| invoice(invoice_id)))
+    }
+
+    fn get_client_invoices_paged(
+        &self,
+        _client: AccountId32,
+        cursor: Option<PageCursor>,
+        limit: u32,
+        _at: Option<H256>,
+    ) -> RpcResult<(Vec<TestInvoice>, Option<PageCursor>)> {
+        let start = position(cursor)?;
+        let page = (start..start + u64::from(limit)).map(invoice).collect();
+        Ok((page, Some(PageCursor::new(&(start + u64::from(limit))))))
+    }
+
+    fn get_client_invoice_count(&self, client: AccountId32, _at: Option<H256>) -> RpcResult<u32> {
+        Ok(if client == alice() { 12 } else { 0 })
+    }
+
+    fn get_creator_invoices(
+        &self,
+        creator: AccountId32,
+        _at: Option<H256>,
+    ) -> RpcResult<Vec<TestInvoice>> {
+        Ok(if creator == bob() { vec![invoice(0), invoice(1)] } else { vec![] })
+    }
+
+    fn get_invoice_by_hash(&self, invoice_hash: H256, _at: Option<H256>) -> RpcResult<Option<u64>> {
+        Ok((invoice_hash == H256::repeat_byte(0xab)).then_some(5))
+    }
+
+    fn get_invoice_by_external_ref(
+        &self,
+        external_ref: String,
+        _at: Option<H256>,
+    ) -> RpcResult<Option<TestInvoice>> {
+        Ok((external_ref == "INV-2025-004").then(|| invoice(4)))
+    }
+
+    fn get_archived_invoice_hash(
+        &self,
+        _client: AccountId32,
+        invoice_id: u64,
+        _at: Option<H256>,
+    ) -> RpcResult<Option<H256>> {
+        Ok((invoice_id == 5).then(|| H256::repeat_byte(0xab)))
+    }
+
+    fn verify_invoice_hash(
+        &self,
+        _client: AccountId32,
+        invoice_id: u64,
+        _at: Option<H256>,
+    ) -> RpcResult<bool> {
+        Ok(invoice_id == 5)
+    }
+
+    fn verify_invoice_signature(
+        &self,
+        _client: AccountId32,
+        invoice_id: u64,
+        _at: Option<H256>,
+    ) -> RpcResult<bool> {
+        Ok(invoice_id == 4)
+    }
+
+    fn get_outstanding_balance(&self, client: AccountId32, _at: Option<H256>) -> RpcResult<u128> {
+        Ok(if client == alice() { 350 } else { 0 })
+    }
+
+    fn get_creator_stats(
+        &self,
+        creator: AccountId32,
+        _at: Option<H256>,
+    ) -> RpcResult<(u128, u128)> {
+        Ok(if creator == bob() { (500, 150) } else { (0, 0) })
+    }
+
+    fn get_amount_due(
+        &self,
+        client: AccountId32,
+        invoice_id: u64,
+        _at: Option<H256>,
+    ) -> RpcResult<Option<u128>> {
+        Ok((client == alice() && invoice_id == 5).then_some(125))
+    }
+
+    fn has_access(
+        &self,
+        invoice_hash: H256,
+        who: AccountId32,
+        _at: Option<H256>,
+    ) -> RpcResult<bool> {
+        Ok(invoice_hash == H256::repeat_byte(0xab) && who == bob())
+    }
+
+    fn get_attachments(
+        &self,
+        client: AccountId32,
+        invoice_id: u64,
+        _at: Option<H256>,
+    ) -> RpcResult<Vec<TestAttachment>> {
+        Ok(if client == alice() && invoice_id == 5 {
+            vec![TestAttachment { doc_hash: H256::repeat_byte(0xdd), attached_by: bob() }]
+        } else {
+            vec![]
+        })
+    }
+}
+
+fn anchor_bundle(at: H256) -> AnchorWithProof<H256, u32, TestAnchor> {
+    AnchorWithProof {
+        block_hash: at,
+        at_block: 12,
+        anchor: TestAnchor { anchored_by: alice(), block_number: 4 },
+        storage_key: Bytes(vec![0x11; 48]),
+        proof: vec![Bytes(vec![0x22; 8]), Bytes(vec![0x33; 4])],
+    }
+}
+
+struct MockTidygenLedger;
+
+#[async_trait]
+impl TidygenLedgerApiServer<H256, u32, TestAnchor> for MockTidygenLedger {
+    fn get_anchor_with_proof(
+        &self,
+        tx_hash: H256,
+        at: Option<H256>,
+    ) -> RpcResult<Option<AnchorWithProof<H256, u32, TestAnchor>>> {
+        let at = at.unwrap_or_else(|| H256::repeat_byte(1));
+        Ok((tx_hash == H256::repeat_byte(7)).then(|| anchor_bundle(at)))
+    }
+}
+
+struct MockTidygen;
+
+#[async_trait]
+impl TidygenApiServer<H256> for MockTidygen {
+    fn get_features(&self, at: Option<H256>) -> RpcResult<FeatureFlags> {
+        // An older block of a runtime that has since enabled late fees
+        Ok(match at {
+            Some(_) => FeatureFlags::INVOICE_WEBHOOK,
+            None => FeatureFlags::INVOICE_WEBHOOK | FeatureFlags::LATE_FEES,
+        })
+    }
+}
+
+struct MockDao;
+
+#[async_trait]
+impl DaoApiServer<H256, AccountId32, TestSnapshotPage, TestReceipt> for MockDao {
+    fn export_snapshot(
+        &self,
+        limit: u32,
+        cursor: Option<PageCursor>,
+        _at: Option<H256>,
+    ) -> RpcResult<TestSnapshotPage> {
+        let start = position(cursor)?;
+        Ok(TestSnapshotPage {
+            entries: (start..start + u64::from(limit)).collect(),
+            next_cursor: Some(PageCursor::new(&(start + u64::from(limit)))),
+        })
+    }
+
+    fn snapshot_digest(&self, _at: Option<H256>) -> RpcResult<H256> {
+        Ok(H256::repeat_byte(0xcd))
+    }
+
+    fn get_receipts(
+        &self,
+        account: AccountId32,
+        offset: u32,
+        limit: u32,
+        _at: Option<H256>,
+    ) -> RpcResult<Vec<(u64, TestReceipt)>> {
+        if account != alice() {
+            return Ok(Vec::new());
+        }
+        Ok((u64::from(offset)..u64::from(offset + limit))
+            .map(|id| (id, TestReceipt { proposal_id: id * 10, block: 5 }))
+            .collect())
+    }
+
+    fn get_proposals_by_category(
+        &self,
+        category: ProposalCategory,
+        offset: u32,
+        limit: u32,
+        _at: Option<H256>,
+    ) -> RpcResult<Vec<u64>> {
+        if category != ProposalCategory::Budget {
+            return Ok(Vec::new());
+        }
+        Ok((u64::from(offset)..u64::from(offset + limit)).map(|id| id * 3).collect())
+    }
+}
+
+struct MockComplianceLog;
+
+#[async_trait]
+impl ComplianceLogApiServer<H256, TestTransitionRecord> for MockComplianceLog {
+    fn get_day(
+        &self,
+        day: u32,
+        offset: u32,
+        limit: u32,
+        _at: Option<H256>,
+    ) -> RpcResult<Vec<TestTransitionRecord>> {
+        Ok((offset..offset + limit)
+            .map(|index| TestTransitionRecord { old: 0, new: 1, block: day * 100 + index })
+            .collect())
+    }
+
+    fn day_digest(&self, day: u32, _at: Option<H256>) -> RpcResult<Option<H256>> {
+        Ok((day == 3).then(|| H256::repeat_byte(0xef)))
+    }
+}
+
+/// Start a server with the given module and connect a client to it
+async fn connect(module: RpcModule<()>) -> (TidygenClient, ServerHandle) {
+    let server = ServerBuilder::default().build("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}", server.local_addr().unwrap());
+    let handle = server.start(module).unwrap();
+    let client = TidygenClient::connect(&url).await.unwrap();
+    (client, handle)
+}
+
+/// All pallet RPC modules merged, as a node would expose them
+fn full_module() -> RpcModule<()> {
+    let mut module = RpcModule::new(());
+    module.merge(MockTidygen.into_rpc()).unwrap();
+    module.merge(MockDid.into_rpc()).unwrap();
+    module.merge(MockLedger.into_rpc()).unwrap();
+    module.merge(MockTidygenLedger.into_rpc()).unwrap();
+    module.merge(MockDao.into_rpc()).unwrap();
+    module.merge(MockComplianceLog.into_rpc()).unwrap();
+    module
+}
+
+#[tokio::test]
+async fn features_round_trip() {
+    let (client, _handle) = connect(full_module()).await;
+
+    let features = client.get_features(None).await.unwrap();
+    assert!(features.contains(FeatureFlags::LATE_FEES));
+    assert!(!features.contains(FeatureFlags::INVOICE_ACCEPTANCE));
+    let older = client.get_features(Some(H256::repeat_byte(3))).await.unwrap();
+    assert_eq!(older, FeatureFlags::INVOICE_WEBHOOK);
+
+    // Frontends read the raw JSON, one snake_case boolean per flag
+    let raw: serde_json::Value = client
+        .inner()
+        .request("tidygen_getFeatures", jsonrpsee::rpc_params![])
+        .await
+        .unwrap();
+    assert_eq!(raw["late_fees"], true);
+    assert_eq!(raw["invoice_webhook"], true);
+    assert_eq!(raw["open_invoice_creation"], false);
+}
+
+#[tokio::test]
+async fn did_methods_round_trip() {
+    let (client, _handle) = connect(full_module()).await;
+    let alice_address = alice().to_ss58check();
+    let bob_address = bob().to_ss58check();
+
+    let did: Option<TestDid> = client.get_did(&alice_address, None).await.unwrap();
+    assert_eq!(did, Some(TestDid { controller: alice(), nonce: 3 }));
+    let missing: Option<TestDid> = client.get_did(&bob_address, None).await.unwrap();
+    assert_eq!(missing, None);
+
+    assert_eq!(
+        client.get_account_from_did("did:tidygen:alice", None).await.unwrap(),
+        Some(alice())
+    );
+    assert!(client.is_did_active(&alice_address, None).await.unwrap());
+    assert!(!client.is_did_active(&bob_address, None).await.unwrap());
+
+    assert_eq!(client.get_total_dids(None).await.unwrap(), 1);
+    assert_eq!(client.get_total_dids(Some(H256::repeat_byte(9))).await.unwrap(), 7);
+
+    assert_eq!(client.get_trust_score(&alice_address, None).await.unwrap(), 150);
+    assert_eq!(client.get_trust_score(&bob_address, None).await.unwrap(), 0);
+
+    assert_eq!(
+        client.resolve_any("did:tidygen:alice", None).await.unwrap(),
+        Resolution::Local(alice())
+    );
+    assert_eq!(
+        client.resolve_any("did:tidygen:finance-bob", None).await.unwrap(),
+        Resolution::External(*b"finance\0")
+    );
+    assert_eq!(client.resolve_any("did:tidygen:carol", None).await.unwrap(), Resolution::Unknown);
+
+    let from = H256::repeat_byte(1);
+    let diff: Option<TestDidDiff> = client.get_did_diff(&alice_address, from, None).await.unwrap();
+    assert_eq!(diff, Some(TestDidDiff { changed: 4 }));
+    let same: Option<TestDidDiff> =
+        client.get_did_diff(&alice_address, from, Some(from)).await.unwrap();
+    assert_eq!(same, Some(TestDidDiff { changed: 0 }));
+    let missing: Option<TestDidDiff> =
+        client.get_did_diff(&bob_address, from, None).await.unwrap();
+    assert_eq!(missing, None);
+
+    let stats = client.did_cache_stats().await.unwrap();
+    assert_eq!((stats.hits, stats.misses, stats.capacity), (40, 2, 128));
+}
+
+#[tokio::test]
+async fn ledger_methods_round_trip() {
+    let (client, _handle) = connect(full_module()).await;
+    let alice_address = alice().to_ss58check();
+    let hash_hex = format!("{:?}", H256::repeat_byte(0xab));
+
+    let found: Option<TestInvoice> = client.get_invoice(&alice_address, 3, None).await.unwrap();
+    assert_eq!(found, Some(invoice(3)));
+
+    let (page, next): (Vec<TestInvoice>, _) = client
+        .get_client_invoices_paged(&alice_address, Some(PageCursor::new(&2u64)), 3, None)
+        .await
+        .unwrap();
+    assert_eq!(page, vec![invoice(2), invoice(3), invoice(4)]);
+    assert_eq!(next, Some(PageCursor::new(&5u64)));
+
+    assert_eq!(client.get_client_invoice_count(&alice_address, None).await.unwrap(), 12);
+    assert_eq!(client.get_client_invoice_count(&bob().to_ss58check(), None).await.unwrap(), 0);
+
+    let created: Vec<TestInvoice> =
+        client.get_creator_invoices(&bob().to_ss58check(), None).await.unwrap();
+    assert_eq!(created, vec![invoice(0), invoice(1)]);
+
+    // Hashes are accepted with and without the 0x prefix
+    assert_eq!(client.get_invoice_by_hash(&hash_hex, None).await.unwrap(), Some(5));
+    assert_eq!(client.get_invoice_by_hash(&hash_hex[2..], None).await.unwrap(), Some(5));
+
+    let by_ref: Option<TestInvoice> =
+        client.get_invoice_by_external_ref("INV-2025-004", None).await.unwrap();
+    assert_eq!(by_ref, Some(invoice(4)));
+
+    assert_eq!(
+        client.get_archived_invoice_hash(&alice_address, 5, None).await.unwrap(),
+        Some(H256::repeat_byte(0xab))
+    );
+    assert!(client.verify_invoice_hash(&alice_address, 5, None).await.unwrap());
+    assert!(!client.verify_invoice_hash(&alice_address, 6, None).await.unwrap());
+    assert!(client.verify_invoice_signature(&alice_address, 4, None).await.unwrap());
+    assert!(!client.verify_invoice_signature(&alice_address, 5, None).await.unwrap());
+
+    let owed: u128 = client.get_outstanding_balance(&alice_address, None).await.unwrap();
+    assert_eq!(owed, 350);
+    let owed: u128 = client.get_outstanding_balance(&bob().to_ss58check(), None).await.unwrap();
+    assert_eq!(owed, 0);
+    let stats: (u128, u128) = client.get_creator_stats(&bob().to_ss58check(), None).await.unwrap();
+    assert_eq!(stats, (500, 150));
+    let due: Option<u128> = client.get_amount_due(&alice_address, 5, None).await.unwrap();
+    assert_eq!(due, Some(125));
+    let due: Option<u128> = client.get_amount_due(&alice_address, 6, None).await.unwrap();
+    assert_eq!(due, None);
+
+    assert!(client.has_access(&hash_hex, &bob().to_ss58check(), None).await.unwrap());
+    assert!(!client.has_access(&hash_hex, &alice_address, None).await.unwrap());
+
+    let attachments: Vec<TestAttachment> =
+        client.get_attachments(&alice_address, 5, None).await.unwrap();
+    assert_eq!(
+        attachments,
+        vec![TestAttachment { doc_hash: H256::repeat_byte(0xdd), attached_by: bob() }]
+    );
+    let none: Vec<TestAttachment> = client.get_attachments(&alice_address, 6, None).await.unwrap();
+    assert!(none.is_empty());
+}
+
+#[tokio::test]
+async fn anchor_proof_round_trip() {
+    let (client, _handle) = connect(full_module()).await;
+    let tx_hash = format!("{:?}", H256::repeat_byte(7));
+
+    let bundle: Option<AnchorWithProof<H256, u32, TestAnchor>> =
+        client.get_anchor_with_proof(&tx_hash, None).await.unwrap();
+    assert_eq!(bundle, Some(anchor_bundle(H256::repeat_byte(1))));
+
+    let at = H256::repeat_byte(9);
+    let bundle: Option<AnchorWithProof<H256, u32, TestAnchor>> =
+        client.get_anchor_with_proof(&tx_hash, Some(at)).await.unwrap();
+    assert_eq!(bundle.unwrap().block_hash, at);
+
+    let missing: Option<AnchorWithProof<H256, u32, TestAnchor>> = client
+        .get_anchor_with_proof(&format!("{:?}", H256::repeat_byte(8)), None)
+        .await
+        .unwrap();
+    assert_eq!(missing, None);
+}
+
+#[tokio::test]
+async fn dao_methods_round_trip() {
+    let (client, _handle) = connect(full_module()).await;
+
+    let page: TestSnapshotPage =
+        client.export_snapshot(2, Some(PageCursor::new(&4u64)), None).await.unwrap();
+    assert_eq!(
+        page,
+        TestSnapshotPage { entries: vec![4, 5], next_cursor: Some(PageCursor::new(&6u64)) }
+    );
+
+    assert_eq!(client.snapshot_digest(None).await.unwrap(), H256::repeat_byte(0xcd));
+
+    let receipts: Vec<(u64, TestReceipt)> =
+        client.get_receipts(&alice().to_ss58check(), 1, 2, None).await.unwrap();
+    assert_eq!(
+        receipts,
+        vec![
+            (1, TestReceipt { proposal_id: 10, block: 5 }),
+            (2, TestReceipt { proposal_id: 20, block: 5 }),
+        ]
+    );
+    let none: Vec<(u64, TestReceipt)> =
+        client.get_receipts(&bob().to_ss58check(), 0, 2, None).await.unwrap();
+    assert!(none.is_empty());
+
+    assert_eq!(
+        client.get_proposals_by_category(ProposalCategory::Budget, 2, 2, None).await.unwrap(),
+        vec![6, 9]
+    );
+    assert!(client
+        .get_proposals_by_category(ProposalCategory::Hiring, 0, 2, None)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn compliance_methods_round_trip() {
+    let (client, _handle) = connect(full_module()).await;
+
+    let records: Vec<TestTransitionRecord> =
+        client.get_compliance_day(3, 1, 2, None).await.unwrap();
+    assert_eq!(
+        records,
+        vec![
+            TestTransitionRecord { old: 0, new: 1, block: 301 },
+            TestTransitionRecord { old: 0, new: 1, block: 302 },
+        ]
+    );
+
+    assert_eq!(
+        client.compliance_day_digest(3, None).await.unwrap(),
+        Some(H256::repeat_byte(0xef))
+    );
+    assert_eq!(client.compliance_day_digest(4, None).await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn errors_are_typed() {
+    // Node without the DAO RPC installed
+    let mut module = RpcModule::new(());
+    module.merge(MockLedger.into_rpc()).unwrap();
+    let (client, _handle) = connect(module).await;
+    let alice_address = alice().to_ss58check();
+
+    // Invalid arguments are rejected before any request is sent
+    assert_eq!(
+        client.is_did_active("not-an-address", None).await,
+        Err(TidygenRpcError::InvalidAddress("not-an-address".into()))
+    );
+    assert_eq!(
+        client.get_invoice_by_hash("0x1234", None).await,
+        Err(TidygenRpcError::InvalidHash("0x1234".into()))
+    );
+
+    // Runtime API failures carry the node's detail
+    let failed = client.get_invoice::<TestInvoice>(&alice_address, 99, None).await;
+    assert_eq!(failed, Err(TidygenRpcError::Runtime { detail: Some("trap".into()) }));
+
+    assert!(matches!(
+        client.snapshot_digest(None).await,
+        Err(TidygenRpcError::MethodNotFound(_))
+    ));
+
+    // Cursors the server cannot read are invalid params, not runtime failures
+    let corrupted = PageCursor::try_from(vec![0u8, 1, 2]).unwrap();
+    let paged = client
+        .get_client_invoices_paged::<TestInvoice>(&alice_address, Some(corrupted), 2, None)
+        .await;
+    assert_eq!(paged, Err(TidygenRpcError::InvalidParams("Invalid cursor".into())));
+}
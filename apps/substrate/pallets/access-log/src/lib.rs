@@ -0,0 +1,196 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! # Access Log Pallet
+//!
+//! A pallet for recording who accessed which on-chain record, for regulated deployments.
+//!
+//! ## Overview
+//!
+//! Regulators may require on-chain evidence of every read of an invoice, DID document or
+//! ledger entry. The Access Log pallet provides functionality for:
+//! - Logging an access to a single resource with one unified extrinsic
+//! - Restricting logging to accounts holding the `AuditRead` capability
+//! - Keeping a bounded rolling log where the oldest records are overwritten
+//!
+//! It replaces the former `get_invoices` (pallet-ledger) and `resolve_did` (pallet-did)
+//! extrinsics, whose call indices stay reserved in those pallets.
+//!
+//! ## Interface
+//!
+//! ### Dispatchable Functions
+//!
+//! * `log_access` - Record an access to a resource
+//!
+//! ### Events
+//!
+//! * `ResourceAccessed` - Emitted when an access is recorded
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+#[frame_support::pallet]
+pub mod pallet {
+    use frame_support::{pallet_prelude::*, traits::Get};
+    use frame_system::pallet_prelude::*;
+    use sp_std::vec::Vec;
+
+    #[pallet::pallet]
+    pub struct Pallet<T>(_);
+
+    /// Capabilities an account can hold
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    pub enum Capability {
+        /// May read records for audit purposes
+        AuditRead,
+    }
+
+    /// Source of truth for account capabilities (typically the identity layer)
+    pub trait CapabilityCheck<AccountId> {
+        /// Whether `who` currently holds `capability`
+        fn has_capability(who: &AccountId, capability: Capability) -> bool;
+    }
+
+    /// No capabilities are granted to anyone
+    impl<AccountId> CapabilityCheck<AccountId> for () {
+        fn has_capability(_who: &AccountId, _capability: Capability) -> bool {
+            false
+        }
+    }
+
+    /// Reference to an auditable record
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    pub enum ResourceRef<AccountId> {
+        /// Invoice in pallet-ledger: (client, invoice_id)
+        Invoice(AccountId, u64),
+        /// DID document in pallet-did: (account)
+        Did(AccountId),
+        /// Ledger entry in pallet-tidygen-ledger: (entry_id)
+        LedgerEntry(u64),
+    }
+
+    /// Single access log record
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    #[scale_info(skip_type_params(T))]
+    pub struct AccessRecord<T: Config> {
+        /// Account that accessed the resource
+        pub accessor: T::AccountId,
+        /// Accessed resource
+        pub resource: ResourceRef<T::AccountId>,
+        /// Block number of the access
+        pub block_number: BlockNumberFor<T>,
+    }
+
+    #[pallet::config]
+    pub trait Config: frame_system::Config {
+        /// The overarching event type.
+        type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+
+        /// Capability lookup used to gate `log_access`
+        type Capabilities: CapabilityCheck<Self::AccountId>;
+
+        /// Number of records kept before the oldest ones are overwritten
+        #[pallet::constant]
+        type MaxLogEntries: Get<u32>;
+    }
+
+    /// Rolling access log: slot => record
+    #[pallet::storage]
+    #[pallet::getter(fn access_log)]
+    pub type AccessLog<T: Config> =
+        StorageMap<_, Twox64Concat, u32, AccessRecord<T>, OptionQuery>;
+
+    /// Total number of accesses ever logged (the next record goes to `count % MaxLogEntries`)
+    #[pallet::storage]
+    #[pallet::getter(fn access_count)]
+    pub type AccessCount<T> = StorageValue<_, u64, ValueQuery>;
+
+    #[pallet::event]
+    #[pallet::generate_deposit(pub(super) fn deposit_event)]
+    pub enum Event<T: Config> {
+        /// Resource accessed [accessor, resource, sequence]
+        ResourceAccessed {
+            accessor: T::AccountId,
+            resource: ResourceRef<T::AccountId>,
+            sequence: u64,
+        },
+    }
+
+    #[pallet::error]
+    pub enum Error<T> {
+        /// Caller does not hold the `AuditRead` capability
+        MissingCapability,
+    }
+
+    #[pallet::call]
+    impl<T: Config> Pallet<T> {
+        /// Record an access to a resource
+        ///
+        /// # Arguments
+        /// * `origin` - Transaction origin (must hold `AuditRead`)
+        /// * `resource` - The accessed resource
+        ///
+        /// # Returns
+        /// * `DispatchResult` - Success or error
+        ///
+        /// # Events
+        /// * `ResourceAccessed` - Emitted when the access is recorded
+        ///
+        /// # Errors
+        /// * `MissingCapability` - Origin does not hold `AuditRead`
+        #[pallet::call_index(0)]
+        #[pallet::weight(10_000)]
+        pub fn log_access(
+            origin: OriginFor<T>,
+            resource: ResourceRef<T::AccountId>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            ensure!(
+                T::Capabilities::has_capability(&who, Capability::AuditRead),
+                Error::<T>::MissingCapability
+            );
+
+            let sequence = AccessCount::<T>::get();
+            let record = AccessRecord {
+                accessor: who.clone(),
+                resource: resource.clone(),
+                block_number: frame_system::Pallet::<T>::block_number(),
+            };
+
+            // Overwrite the oldest slot once the log is full
+            AccessLog::<T>::insert(Self::slot(sequence), record);
+            AccessCount::<T>::put(sequence.saturating_add(1));
+
+            Self::deposit_event(Event::ResourceAccessed {
+                accessor: who,
+                resource,
+                sequence,
+            });
+
+            Ok(())
+        }
+    }
+
+    // Helper functions
+    impl<T: Config> Pallet<T> {
+        /// Slot of the log record with the given sequence number
+        fn slot(sequence: u64) -> u32 {
+            let max = u64::from(T::MaxLogEntries::get().max(1));
+            (sequence % max) as u32
+        }
+
+        /// Records still held in the log, oldest first
+        pub fn recent_accesses() -> Vec<AccessRecord<T>> {
+            let count = AccessCount::<T>::get();
+            let kept = count.min(u64::from(T::MaxLogEntries::get()));
+            (count - kept..count)
+                .filter_map(|sequence| AccessLog::<T>::get(Self::slot(sequence)))
+                .collect()
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use crate as pallet_access_log;
+use crate::{Capability, CapabilityCheck};
+use frame_support::{parameter_types, traits::ConstU32};
+use sp_core::H256;
+use sp_runtime::{
+    traits::{BlakeTwo256, IdentityLookup},
+    BuildStorage,
+};
+
+type Block = frame_system::mocking::MockBlock<Test>;
+
+// Configure a mock runtime to test the pallet
+frame_support::construct_runtime!(
+    pub enum Test {
+        System: frame_system,
+        AccessLog: pallet_access_log,
+    }
+);
+
+parameter_types! {
+    pub const BlockHashCount: u64 = 250;
+    pub const SS58Prefix: u8 = 42;
+}
+
+impl frame_system::Config for Test {
+    type BaseCallFilter = frame_support::traits::Everything;
+    type BlockWeights = ();
+    type BlockLength = ();
+    type DbWeight = ();
+    type RuntimeOrigin = RuntimeOrigin;
+    type RuntimeCall = RuntimeCall;
+    type Nonce = u64;
+    type Hash = H256;
+    type Hashing = BlakeTwo256;
+    type AccountId = u64;
+    type Lookup = IdentityLookup<Self::AccountId>;
+    type Block = Block;
+    type RuntimeEvent = RuntimeEvent;
+    type BlockHashCount = BlockHashCount;
+    type Version = ();
+    type PalletInfo = PalletInfo;
+    type AccountData = ();
+    type OnNewAccount = ();
+    type OnKilledAccount = ();
+    type SystemWeightInfo = ();
+    type SS58Prefix = SS58Prefix;
+    type OnSetCode = ();
+    type MaxConsumers = ConstU32<16>;
+}
+
+/// Account holding the `AuditRead` capability
+pub const AUDITOR: u64 = 1;
+
+/// Grants `AuditRead` to `AUDITOR` only
+pub struct AuditorOnly;
+
+impl CapabilityCheck<u64> for AuditorOnly {
+    fn has_capability(who: &u64, capability: Capability) -> bool {
+        *who == AUDITOR && capability == Capability::AuditRead
+    }
+}
+
+parameter_types! {
+    pub const MaxLogEntries: u32 = 3;
+}
+
+impl pallet_access_log::Config for Test {
+    type RuntimeEvent = RuntimeEvent;
+    type Capabilities = AuditorOnly;
+    type MaxLogEntries = MaxLogEntries;
+}
+
+// Build genesis storage
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    let mut ext: sp_io::TestExternalities = frame_system::GenesisConfig::<Test>::default()
+        .build_storage()
+        .unwrap()
+        .into();
+    // Events are not recorded at genesis, start at block 1
+    ext.execute_with(|| System::set_block_number(1));
+    ext
+}
@@ -0,0 +1,68 @@
+use crate::{mock::*, Error, Event, ResourceRef};
+use frame_support::{assert_noop, assert_ok};
+
+#[test]
+fn log_access_works() {
+    new_test_ext().execute_with(|| {
+        let resource = ResourceRef::Invoice(2, 0);
+
+        assert_ok!(AccessLog::log_access(RuntimeOrigin::signed(AUDITOR), resource.clone()));
+
+        // Verify record stored
+        assert_eq!(AccessLog::access_count(), 1);
+        let record = AccessLog::access_log(0).unwrap();
+        assert_eq!(record.accessor, AUDITOR);
+        assert_eq!(record.resource, resource);
+        assert_eq!(record.block_number, 1);
+
+        // Verify event
+        System::assert_has_event(
+            Event::ResourceAccessed {
+                accessor: AUDITOR,
+                resource,
+                sequence: 0,
+            }
+            .into(),
+        );
+    });
+}
+
+#[test]
+fn log_access_requires_capability() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            AccessLog::log_access(RuntimeOrigin::signed(2), ResourceRef::Did(3)),
+            Error::<Test>::MissingCapability
+        );
+        assert_eq!(AccessLog::access_count(), 0);
+    });
+}
+
+#[test]
+fn log_rotates_oldest_entries() {
+    new_test_ext().execute_with(|| {
+        let resources = [
+            ResourceRef::Invoice(2, 0),
+            ResourceRef::Did(3),
+            ResourceRef::LedgerEntry(7),
+            ResourceRef::LedgerEntry(8),
+            ResourceRef::Did(4),
+        ];
+
+        for resource in resources.iter() {
+            assert_ok!(AccessLog::log_access(RuntimeOrigin::signed(AUDITOR), resource.clone()));
+        }
+
+        // Only the last MaxLogEntries (3) records are kept, oldest first
+        assert_eq!(AccessLog::access_count(), 5);
+        let kept: Vec<_> = AccessLog::recent_accesses()
+            .into_iter()
+            .map(|r| r.resource)
+            .collect();
+        assert_eq!(kept, resources[2..].to_vec());
+
+        // Slot 0 now holds the fourth access
+        assert_eq!(AccessLog::access_log(0).unwrap().resource, ResourceRef::LedgerEntry(8));
+        assert!(AccessLog::access_log(3).is_none());
+    });
+}
@@ -0,0 +1,98 @@
+//! RPC interface for the Compliance Log pallet
+
+use codec::Codec;
+use jsonrpsee::{
+    core::{async_trait, RpcResult},
+    proc_macros::rpc,
+    types::error::{CallError, ErrorObject},
+};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::H256;
+use sp_runtime::traits::Block as BlockT;
+use std::sync::Arc;
+
+pub use pallet_compliance_log_runtime_api::ComplianceLogApi as ComplianceLogRuntimeApi;
+
+/// Error code for failed runtime API calls
+pub const RUNTIME_ERROR: i32 = 1;
+
+#[rpc(client, server)]
+pub trait ComplianceLogApi<BlockHash, TransitionRecord> {
+    /// Get up to `limit` records of a day, skipping the first `offset`
+    #[method(name = "compliance_getDay")]
+    fn get_day(
+        &self,
+        day: u32,
+        offset: u32,
+        limit: u32,
+        at: Option<BlockHash>,
+    ) -> RpcResult<Vec<TransitionRecord>>;
+
+    /// Running digest over the records of a day, to verify an archive of it
+    #[method(name = "compliance_dayDigest")]
+    fn day_digest(&self, day: u32, at: Option<BlockHash>) -> RpcResult<Option<H256>>;
+}
+
+/// A struct that implements the `ComplianceLogApi`.
+pub struct ComplianceLog<C, Block> {
+    client: Arc<C>,
+    _marker: std::marker::PhantomData<Block>,
+}
+
+impl<C, Block> ComplianceLog<C, Block> {
+    /// Create new `ComplianceLog` instance with the given reference to the client.
+    pub fn new(client: Arc<C>) -> Self {
+        Self {
+            client,
+            _marker: Default::default(),
+        }
+    }
+}
+
+#[async_trait]
+impl<C, Block, TransitionRecord>
+    ComplianceLogApiServer<<Block as BlockT>::Hash, TransitionRecord> for ComplianceLog<C, Block>
+where
+    Block: BlockT,
+    C: Send + Sync + 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block>,
+    C::Api: ComplianceLogRuntimeApi<Block, TransitionRecord>,
+    TransitionRecord: Codec,
+{
+    fn get_day(
+        &self,
+        day: u32,
+        offset: u32,
+        limit: u32,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> RpcResult<Vec<TransitionRecord>> {
+        let api = self.client.runtime_api();
+        let at = at.unwrap_or_else(|| self.client.info().best_hash);
+
+        api.get_day(at, day, offset, limit)
+            .map_err(runtime_error_into_rpc_err)
+    }
+
+    fn day_digest(
+        &self,
+        day: u32,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> RpcResult<Option<H256>> {
+        let api = self.client.runtime_api();
+        let at = at.unwrap_or_else(|| self.client.info().best_hash);
+
+        api.day_digest(at, day)
+            .map(|digest| digest.map(H256::from))
+            .map_err(runtime_error_into_rpc_err)
+    }
+}
+
+/// Converts a runtime trap into an RPC error.
+fn runtime_error_into_rpc_err(err: impl std::fmt::Debug) -> jsonrpsee::core::Error {
+    CallError::Custom(ErrorObject::owned(
+        RUNTIME_ERROR,
+        "Runtime error",
+        Some(format!("{:?}", err)),
+    ))
+    .into()
+}
@@ -0,0 +1,20 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Runtime API definition for the Compliance Log pallet
+
+use codec::Codec;
+use sp_std::vec::Vec;
+
+sp_api::decl_runtime_apis! {
+    /// The API to read the compliance log
+    pub trait ComplianceLogApi<TransitionRecord>
+    where
+        TransitionRecord: Codec,
+    {
+        /// Up to `limit` records of a day, skipping the first `offset`
+        fn get_day(day: u32, offset: u32, limit: u32) -> Vec<TransitionRecord>;
+
+        /// Running digest over the records of a day, `None` if nothing is recorded
+        fn day_digest(day: u32) -> Option<[u8; 32]>;
+    }
+}
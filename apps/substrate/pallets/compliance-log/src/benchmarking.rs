@@ -0,0 +1,38 @@
+//! Benchmarks for the compliance log calls whose weight depends on their input.
+//!
+//! The records `r` of the pruned day range up to `MaxRecordsPerDay`, each naming an invoice,
+//! the largest subject.
+
+use super::*;
+use frame_benchmarking::v2::*;
+use frame_support::traits::Get;
+use frame_system::RawOrigin;
+use tidygen_primitives::transitions::{OnStatusTransition, TransitionSubject};
+
+#[benchmarks]
+mod benchmarks {
+    use super::*;
+
+    #[benchmark]
+    fn prune_day(r: Linear<0, { T::MaxRecordsPerDay::get() }>) {
+        let client: T::AccountId = account("client", 0, 0);
+        frame_system::Pallet::<T>::set_block_number(0u32.into());
+        for i in 0..r {
+            Pallet::<T>::on_transition(TransitionSubject::Invoice(client.clone(), i.into()), 0, 1);
+        }
+        // An empty day is only prunable with a digest
+        DayDigests::<T>::mutate(0, |digest| *digest = Some(digest.unwrap_or_default()));
+        // The first day past the retention window
+        let today = T::BlocksPerDay::get().saturating_mul(T::RetentionDays::get().into());
+        frame_system::Pallet::<T>::set_block_number(today);
+        let caller: T::AccountId = whitelisted_caller();
+
+        #[extrinsic_call]
+        _(RawOrigin::Signed(caller), 0);
+
+        assert!(Days::<T>::get(0).is_empty());
+        assert_eq!(DayDigests::<T>::get(0), None);
+    }
+
+    impl_benchmark_test_suite!(Pallet, crate::mock::new_test_ext(), crate::mock::Test);
+}
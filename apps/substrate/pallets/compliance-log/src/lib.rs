@@ -0,0 +1,259 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! # Compliance Log Pallet
+//!
+//! A pallet keeping an append-only record of every status transition, by day, for regulators.
+//!
+//! ## Overview
+//!
+//! Regulators want a daily record of every status change of invoices, ledger entries,
+//! proposals and DIDs that can be retrieved long after the events are pruned. The Compliance
+//! Log pallet provides functionality for:
+//! - Receiving the transitions of other pallets through their `OnTransition` handler
+//! - Appending each as a compact `TransitionRecord` to the bucket of its day
+//! - Keeping a running digest per day, so an archive can be verified against the chain
+//! - Letting anyone prune days that fell out of the retention window
+//!
+//! ## Day Buckets
+//!
+//! The day of a block is `block / BlocksPerDay`. A day holds at most `MaxRecordsPerDay`
+//! records in the order the transitions happened; records are never changed or removed
+//! before their day is pruned. Transitions past the limit are counted in `DroppedRecords`
+//! instead, and the first one emits `DayFull`.
+//!
+//! The digest of a day starts at 32 zero bytes. Each record appended sets it to the
+//! Blake2-256 of the SCALE-encoded `(digest, record)`, so the digest of a past day is final
+//! and an archive holding that day's records in order can recompute it.
+//!
+//! ## Retention
+//!
+//! The last `RetentionDays` days, the current day included, are retained. Older days can be
+//! removed by any signed account with `prune_day`.
+//!
+//! ## Interface
+//!
+//! ### Dispatchable Functions
+//!
+//! * `prune_day` - Remove a day past the retention window
+//!
+//! ### Events
+//!
+//! * `DayFull` - Emitted when the first transition of a day is dropped
+//! * `DayPruned` - Emitted when a day is removed
+//!
+//! ### RPC Methods
+//!
+//! * `compliance_getDay` - Records of a day, paginated by offset
+//! * `compliance_dayDigest` - Running digest of a day
+
+pub use pallet::*;
+pub use weights::WeightInfo;
+
+pub mod weights;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+#[frame_support::pallet]
+pub mod pallet {
+    use crate::weights::WeightInfo;
+    use frame_support::{pallet_prelude::*, traits::Get};
+    use frame_system::pallet_prelude::*;
+    #[cfg(feature = "serde")]
+    use serde::{Deserialize, Serialize};
+    use sp_io::hashing::blake2_256;
+    use sp_runtime::traits::SaturatedConversion;
+    use sp_std::vec::Vec;
+    use tidygen_primitives::transitions::{OnStatusTransition, TransitionSubject};
+
+    #[pallet::pallet]
+    pub struct Pallet<T>(_);
+
+    /// Status transition of an entity, as recorded in its day
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    #[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+    pub struct TransitionRecord<AccountId, BlockNumber> {
+        /// Entity whose status changed, naming the pallet owning it
+        pub subject: TransitionSubject<AccountId>,
+        /// SCALE index of the previous status
+        pub old: u8,
+        /// SCALE index of the new status
+        pub new: u8,
+        /// Block the transition happened in
+        pub block: BlockNumber,
+    }
+
+    pub type RecordOf<T> =
+        TransitionRecord<<T as frame_system::Config>::AccountId, BlockNumberFor<T>>;
+
+    #[pallet::config]
+    pub trait Config: frame_system::Config {
+        /// The overarching event type.
+        type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+
+        /// Number of blocks in a day bucket
+        #[pallet::constant]
+        type BlocksPerDay: Get<BlockNumberFor<Self>>;
+
+        /// Maximum number of records kept for one day
+        #[pallet::constant]
+        type MaxRecordsPerDay: Get<u32>;
+
+        /// Number of days retained before they can be pruned, the current day included
+        #[pallet::constant]
+        type RetentionDays: Get<u32>;
+
+        /// Weights of the calls whose cost depends on their input
+        type WeightInfo: WeightInfo;
+    }
+
+    /// Records of each day, in the order the transitions happened: day => records
+    #[pallet::storage]
+    #[pallet::getter(fn day_records)]
+    pub type Days<T: Config> =
+        StorageMap<_, Twox64Concat, u32, BoundedVec<RecordOf<T>, T::MaxRecordsPerDay>, ValueQuery>;
+
+    /// Running digest over the records of each day: day => digest
+    #[pallet::storage]
+    #[pallet::getter(fn day_digest)]
+    pub type DayDigests<T: Config> = StorageMap<_, Twox64Concat, u32, [u8; 32], OptionQuery>;
+
+    /// Transitions not recorded because their day was full: day => count
+    #[pallet::storage]
+    #[pallet::getter(fn dropped_records)]
+    pub type DroppedRecords<T: Config> = StorageMap<_, Twox64Concat, u32, u32, ValueQuery>;
+
+    #[pallet::event]
+    #[pallet::generate_deposit(pub(super) fn deposit_event)]
+    pub enum Event<T: Config> {
+        /// A day reached `MaxRecordsPerDay`, further transitions are only counted [day]
+        DayFull { day: u32 },
+        /// A day past retention was removed [day, records, pruned_by]
+        DayPruned {
+            day: u32,
+            records: u32,
+            pruned_by: T::AccountId,
+        },
+    }
+
+    #[pallet::error]
+    pub enum Error<T> {
+        /// The day is still within `RetentionDays`
+        WithinRetention,
+        /// Nothing is recorded for the day
+        DayNotFound,
+    }
+
+    #[pallet::call]
+    impl<T: Config> Pallet<T> {
+        /// Remove a day past the retention window
+        ///
+        /// Charged for a day of `MaxRecordsPerDay` records and refunded down to the records
+        /// the day held.
+        ///
+        /// # Arguments
+        /// * `origin` - Transaction origin (any signed account)
+        /// * `day` - Day bucket to remove
+        ///
+        /// # Returns
+        /// * `DispatchResultWithPostInfo` - Success with the weight actually used, or error
+        ///
+        /// # Events
+        /// * `DayPruned` - Emitted when the day is removed
+        ///
+        /// # Errors
+        /// * `WithinRetention` - The day is one of the last `RetentionDays` days
+        /// * `DayNotFound` - Nothing is recorded for the day
+        #[pallet::call_index(0)]
+        #[pallet::weight(T::WeightInfo::prune_day(T::MaxRecordsPerDay::get()))]
+        pub fn prune_day(origin: OriginFor<T>, day: u32) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+
+            ensure!(
+                day.saturating_add(T::RetentionDays::get()) <= Self::current_day(),
+                Error::<T>::WithinRetention
+            );
+            ensure!(
+                DayDigests::<T>::contains_key(day) || DroppedRecords::<T>::contains_key(day),
+                Error::<T>::DayNotFound
+            );
+
+            // Only the length prefix is decoded, not the records
+            let records = Days::<T>::decode_len(day).unwrap_or(0) as u32;
+            Days::<T>::remove(day);
+            DayDigests::<T>::remove(day);
+            DroppedRecords::<T>::remove(day);
+
+            Self::deposit_event(Event::DayPruned {
+                day,
+                records,
+                pruned_by: who,
+            });
+
+            Ok(Some(T::WeightInfo::prune_day(records)).into())
+        }
+    }
+
+    // Helper functions
+    impl<T: Config> Pallet<T> {
+        /// Day bucket of `block`
+        pub fn day_of(block: BlockNumberFor<T>) -> u32 {
+            let blocks_per_day = T::BlocksPerDay::get().max(1u32.into());
+            (block / blocks_per_day).saturated_into()
+        }
+
+        /// Day bucket of the current block
+        pub fn current_day() -> u32 {
+            Self::day_of(frame_system::Pallet::<T>::block_number())
+        }
+
+        /// Digest of a day after appending `record` to one with digest `previous`
+        pub fn next_digest(previous: &[u8; 32], record: &RecordOf<T>) -> [u8; 32] {
+            blake2_256(&(previous, record).encode())
+        }
+
+        /// Up to `limit` records of `day`, skipping the first `offset`
+        pub fn get_day(day: u32, offset: u32, limit: u32) -> Vec<RecordOf<T>> {
+            Days::<T>::get(day)
+                .into_iter()
+                .skip(offset as usize)
+                .take(limit as usize)
+                .collect()
+        }
+
+        /// Append a transition to the current day, or count it if the day is full
+        fn record(subject: TransitionSubject<T::AccountId>, old: u8, new: u8) {
+            let block = frame_system::Pallet::<T>::block_number();
+            let day = Self::day_of(block);
+            let record = TransitionRecord { subject, old, new, block };
+
+            let digest = Self::next_digest(&DayDigests::<T>::get(day).unwrap_or_default(), &record);
+            if Days::<T>::try_append(day, record).is_ok() {
+                DayDigests::<T>::insert(day, digest);
+                return;
+            }
+
+            let dropped = DroppedRecords::<T>::mutate(day, |dropped| {
+                *dropped = dropped.saturating_add(1);
+                *dropped
+            });
+            if dropped == 1 {
+                Self::deposit_event(Event::DayFull { day });
+            }
+        }
+    }
+
+    /// Records the transitions reported by the other pallets
+    impl<T: Config> OnStatusTransition<T::AccountId> for Pallet<T> {
+        fn on_transition(subject: TransitionSubject<T::AccountId>, old: u8, new: u8) {
+            Self::record(subject, old, new);
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use crate::{mock::*, Error, Event, TransitionRecord};
+use frame_support::{assert_noop, assert_ok};
+use tidygen_primitives::transitions::{OnStatusTransition, TransitionSubject};
+
+/// Report a transition the way another pallet would
+fn transition(subject: TransitionSubject<u64>, old: u8, new: u8) {
+    <ComplianceLog as OnStatusTransition<u64>>::on_transition(subject, old, new);
+}
+
+#[test]
+fn transitions_land_in_the_day_of_their_block() {
+    new_test_ext().execute_with(|| {
+        transition(TransitionSubject::Invoice(2, 0), 0, 1);
+        System::set_block_number(9);
+        transition(TransitionSubject::Proposal(4), 0, 2);
+        System::set_block_number(BLOCKS_PER_DAY);
+        transition(TransitionSubject::Did(3), 0, 2);
+
+        assert_eq!(
+            ComplianceLog::get_day(0, 0, 10),
+            vec![
+                TransitionRecord {
+                    subject: TransitionSubject::Invoice(2, 0),
+                    old: 0,
+                    new: 1,
+                    block: 1,
+                },
+                TransitionRecord {
+                    subject: TransitionSubject::Proposal(4),
+                    old: 0,
+                    new: 2,
+                    block: 9,
+                },
+            ]
+        );
+        assert_eq!(ComplianceLog::get_day(1, 0, 10).len(), 1);
+        assert!(ComplianceLog::get_day(2, 0, 10).is_empty());
+
+        // Paginated by offset
+        let second = ComplianceLog::get_day(0, 1, 10);
+        assert_eq!(second.len(), 1);
+        assert_eq!(second[0].subject, TransitionSubject::Proposal(4));
+        assert_eq!(ComplianceLog::get_day(0, 0, 1).len(), 1);
+    });
+}
+
+#[test]
+fn day_digest_chains_the_records_in_order() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(ComplianceLog::day_digest(0), None);
+        transition(TransitionSubject::LedgerEntry(1), 0, 1);
+        transition(TransitionSubject::Did(3), 0, 2);
+
+        let digest = ComplianceLog::get_day(0, 0, 10)
+            .iter()
+            .fold([0u8; 32], |digest, record| ComplianceLog::next_digest(&digest, record));
+        assert_eq!(ComplianceLog::day_digest(0), Some(digest));
+
+        // Later days leave a past day's digest alone
+        System::set_block_number(BLOCKS_PER_DAY);
+        transition(TransitionSubject::LedgerEntry(1), 1, 2);
+        assert_eq!(ComplianceLog::day_digest(0), Some(digest));
+        assert_ne!(ComplianceLog::day_digest(1), Some(digest));
+    });
+}
+
+#[test]
+fn full_day_counts_further_transitions() {
+    new_test_ext().execute_with(|| {
+        for proposal_id in 0..3 {
+            transition(TransitionSubject::Proposal(proposal_id), 0, 1);
+        }
+        let digest = ComplianceLog::day_digest(0);
+
+        transition(TransitionSubject::Proposal(3), 0, 1);
+        System::assert_last_event(Event::DayFull { day: 0 }.into());
+        transition(TransitionSubject::Proposal(4), 0, 1);
+
+        assert_eq!(ComplianceLog::get_day(0, 0, 10).len(), 3);
+        assert_eq!(ComplianceLog::dropped_records(0), 2);
+        assert_eq!(ComplianceLog::day_digest(0), digest);
+        assert_eq!(
+            System::events()
+                .iter()
+                .filter(|record| record.event == RuntimeEvent::from(Event::DayFull { day: 0 }))
+                .count(),
+            1
+        );
+    });
+}
+
+#[test]
+fn prune_day_removes_days_past_retention() {
+    new_test_ext().execute_with(|| {
+        transition(TransitionSubject::Invoice(2, 0), 0, 1);
+
+        // Days 0 and 1 are retained on day 1
+        System::set_block_number(2 * BLOCKS_PER_DAY - 1);
+        assert_noop!(
+            ComplianceLog::prune_day(RuntimeOrigin::signed(5), 0),
+            Error::<Test>::WithinRetention
+        );
+
+        System::set_block_number(2 * BLOCKS_PER_DAY);
+        assert_ok!(ComplianceLog::prune_day(RuntimeOrigin::signed(5), 0));
+        System::assert_last_event(Event::DayPruned { day: 0, records: 1, pruned_by: 5 }.into());
+        assert!(ComplianceLog::get_day(0, 0, 10).is_empty());
+        assert_eq!(ComplianceLog::day_digest(0), None);
+
+        assert_noop!(
+            ComplianceLog::prune_day(RuntimeOrigin::signed(5), 0),
+            Error::<Test>::DayNotFound
+        );
+    });
+}
+
+#[test]
+fn prune_day_weight_follows_the_records() {
+    use crate::WeightInfo;
+    use frame_support::dispatch::GetDispatchInfo;
+
+    let estimate = <() as WeightInfo>::prune_day;
+    assert!(estimate(0).all_lt(estimate(3)));
+
+    new_test_ext().execute_with(|| {
+        transition(TransitionSubject::Invoice(2, 0), 0, 1);
+        System::set_block_number(2 * BLOCKS_PER_DAY);
+
+        // Charged for a full day, `MaxRecordsPerDay` is 3 in the mock
+        let info = crate::Call::<Test>::prune_day { day: 0 }.get_dispatch_info();
+        assert_eq!(info.weight, estimate(3));
+
+        let post_info = ComplianceLog::prune_day(RuntimeOrigin::signed(5), 0).unwrap();
+        assert_eq!(post_info.actual_weight, Some(estimate(1)));
+    });
+}
+
+#[test]
+fn records_serialize_with_tagged_subjects() {
+    let record = TransitionRecord {
+        subject: TransitionSubject::Invoice(2u64, 7),
+        old: 0,
+        new: 1,
+        block: 12u64,
+    };
+    assert_eq!(
+        serde_json::to_value(&record).unwrap(),
+        serde_json::json!({
+            "subject": { "kind": "invoice", "value": [2, 7] },
+            "old": 0,
+            "new": 1,
+            "block": 12,
+        })
+    );
+}
@@ -0,0 +1,47 @@
+//! Weights for the compliance log pallet.
+//!
+//! `prune_day` is a base cost plus a slope per record of the pruned day: the records are not
+//! decoded, but the whole day is part of the storage proof. This is the shape measured by
+//! the benchmark in `benchmarking.rs`. The figures below are estimates for reference
+//! hardware and are to be replaced by the benchmark output of the production runtime:
+//!
+//! ```text
+//! benchmark pallet --pallet pallet_compliance_log --extrinsic '*' --steps 3 --repeat 20
+//! ```
+
+use core::marker::PhantomData;
+use frame_support::{
+    traits::Get,
+    weights::{constants::RocksDbWeight, Weight},
+};
+
+/// Weight functions needed by the compliance log pallet
+pub trait WeightInfo {
+    /// `prune_day` of a day holding `r` records
+    fn prune_day(r: u32) -> Weight;
+}
+
+/// Weights for the compliance log pallet using the runtime's database weights
+pub struct SubstrateWeight<T>(PhantomData<T>);
+
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+    /// Reads: `DayDigests`, `DroppedRecords`, `Days` (length only).
+    /// Writes: `Days`, `DayDigests`, `DroppedRecords`.
+    fn prune_day(r: u32) -> Weight {
+        Weight::from_parts(18_000_000, 1_500)
+            // The day's records in the storage proof
+            .saturating_add(Weight::from_parts(2_000, 90).saturating_mul(r.into()))
+            .saturating_add(T::DbWeight::get().reads(3))
+            .saturating_add(T::DbWeight::get().writes(3))
+    }
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+    fn prune_day(r: u32) -> Weight {
+        Weight::from_parts(18_000_000, 1_500)
+            .saturating_add(Weight::from_parts(2_000, 90).saturating_mul(r.into()))
+            .saturating_add(RocksDbWeight::get().reads(3))
+            .saturating_add(RocksDbWeight::get().writes(3))
+    }
+}
@@ -0,0 +1,154 @@
+//! RPC interface for the DAO pallet
+
+use codec::Codec;
+use jsonrpsee::{
+    core::{async_trait, RpcResult},
+    proc_macros::rpc,
+    types::error::{CallError, ErrorObject, INVALID_PARAMS_CODE},
+};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::H256;
+use sp_runtime::traits::Block as BlockT;
+use std::sync::Arc;
+use tidygen_primitives::{
+    governance::ProposalCategory,
+    pagination::{InvalidCursor, PageCursor},
+};
+
+pub use pallet_dao_runtime_api::DaoApi as DaoRuntimeApi;
+
+/// Error code for failed runtime API calls
+pub const RUNTIME_ERROR: i32 = 1;
+
+#[rpc(client, server)]
+pub trait DaoApi<BlockHash, AccountId, SnapshotPage, VoteReceipt> {
+    /// Export a page of the governance snapshot (non-terminal proposals and tallies)
+    #[method(name = "dao_exportSnapshot")]
+    fn export_snapshot(
+        &self,
+        limit: u32,
+        cursor: Option<PageCursor>,
+        at: Option<BlockHash>,
+    ) -> RpcResult<SnapshotPage>;
+
+    /// Digest over the full governance snapshot
+    #[method(name = "dao_snapshotDigest")]
+    fn snapshot_digest(&self, at: Option<BlockHash>) -> RpcResult<H256>;
+
+    /// Get up to `limit` vote receipts of an account, skipping the `offset` oldest
+    #[method(name = "dao_getReceipts")]
+    fn get_receipts(
+        &self,
+        account: AccountId,
+        offset: u32,
+        limit: u32,
+        at: Option<BlockHash>,
+    ) -> RpcResult<Vec<(u64, VoteReceipt)>>;
+
+    /// Get up to `limit` IDs of the proposals filed under a category, in creation order,
+    /// skipping the first `offset`
+    #[method(name = "dao_getProposalsByCategory")]
+    fn get_proposals_by_category(
+        &self,
+        category: ProposalCategory,
+        offset: u32,
+        limit: u32,
+        at: Option<BlockHash>,
+    ) -> RpcResult<Vec<u64>>;
+}
+
+/// A struct that implements the `DaoApi`.
+pub struct Dao<C, Block> {
+    client: Arc<C>,
+    _marker: std::marker::PhantomData<Block>,
+}
+
+impl<C, Block> Dao<C, Block> {
+    /// Create new `Dao` instance with the given reference to the client.
+    pub fn new(client: Arc<C>) -> Self {
+        Self {
+            client,
+            _marker: Default::default(),
+        }
+    }
+}
+
+#[async_trait]
+impl<C, Block, AccountId, SnapshotPage, VoteReceipt>
+    DaoApiServer<<Block as BlockT>::Hash, AccountId, SnapshotPage, VoteReceipt> for Dao<C, Block>
+where
+    Block: BlockT,
+    C: Send + Sync + 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block>,
+    C::Api: DaoRuntimeApi<Block, AccountId, SnapshotPage, VoteReceipt>,
+    AccountId: Codec,
+    SnapshotPage: Codec,
+    VoteReceipt: Codec,
+{
+    fn export_snapshot(
+        &self,
+        limit: u32,
+        cursor: Option<PageCursor>,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> RpcResult<SnapshotPage> {
+        let api = self.client.runtime_api();
+        let at = at.unwrap_or_else(|| self.client.info().best_hash);
+
+        api.export_snapshot(at, limit, cursor)
+            .map_err(runtime_error_into_rpc_err)?
+            .map_err(invalid_cursor_into_rpc_err)
+    }
+
+    fn snapshot_digest(&self, at: Option<<Block as BlockT>::Hash>) -> RpcResult<H256> {
+        let api = self.client.runtime_api();
+        let at = at.unwrap_or_else(|| self.client.info().best_hash);
+
+        api.snapshot_digest(at)
+            .map(H256::from)
+            .map_err(runtime_error_into_rpc_err)
+    }
+
+    fn get_receipts(
+        &self,
+        account: AccountId,
+        offset: u32,
+        limit: u32,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> RpcResult<Vec<(u64, VoteReceipt)>> {
+        let api = self.client.runtime_api();
+        let at = at.unwrap_or_else(|| self.client.info().best_hash);
+
+        api.get_receipts(at, account, offset, limit)
+            .map_err(runtime_error_into_rpc_err)
+    }
+
+    fn get_proposals_by_category(
+        &self,
+        category: ProposalCategory,
+        offset: u32,
+        limit: u32,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> RpcResult<Vec<u64>> {
+        let api = self.client.runtime_api();
+        let at = at.unwrap_or_else(|| self.client.info().best_hash);
+
+        api.get_proposals_by_category(at, category, offset, limit)
+            .map_err(runtime_error_into_rpc_err)
+    }
+}
+
+/// Converts a runtime trap into an RPC error.
+fn runtime_error_into_rpc_err(err: impl std::fmt::Debug) -> jsonrpsee::core::Error {
+    CallError::Custom(ErrorObject::owned(
+        RUNTIME_ERROR,
+        "Runtime error",
+        Some(format!("{:?}", err)),
+    ))
+    .into()
+}
+
+/// Converts a rejected page cursor into an invalid params error.
+fn invalid_cursor_into_rpc_err(_: InvalidCursor) -> jsonrpsee::core::Error {
+    CallError::Custom(ErrorObject::owned(INVALID_PARAMS_CODE, "Invalid cursor", None::<()>))
+        .into()
+}
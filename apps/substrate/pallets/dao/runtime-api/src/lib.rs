@@ -0,0 +1,42 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Runtime API definition for the DAO pallet
+
+use codec::Codec;
+use sp_std::vec::Vec;
+use tidygen_primitives::{
+    governance::ProposalCategory,
+    pagination::{InvalidCursor, PageCursor},
+};
+
+sp_api::decl_runtime_apis! {
+    /// The API to interact with DAO pallet
+    pub trait DaoApi<AccountId, SnapshotPage, VoteReceipt>
+    where
+        AccountId: Codec,
+        SnapshotPage: Codec,
+        VoteReceipt: Codec,
+    {
+        /// Export up to `limit` non-terminal proposals starting at `cursor`, the first page
+        /// for `None`
+        fn export_snapshot(
+            limit: u32,
+            cursor: Option<PageCursor>,
+        ) -> Result<SnapshotPage, InvalidCursor>;
+
+        /// Digest over the full governance snapshot
+        fn snapshot_digest() -> [u8; 32];
+
+        /// Up to `limit` vote receipts of an account with their IDs, skipping the
+        /// `offset` oldest
+        fn get_receipts(account: AccountId, offset: u32, limit: u32) -> Vec<(u64, VoteReceipt)>;
+
+        /// Up to `limit` IDs of the proposals filed under `category`, in creation order,
+        /// skipping the first `offset`
+        fn get_proposals_by_category(
+            category: ProposalCategory,
+            offset: u32,
+            limit: u32,
+        ) -> Vec<u64>;
+    }
+}
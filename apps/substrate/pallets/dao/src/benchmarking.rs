@@ -0,0 +1,501 @@
+//! Benchmarks for the DAO calls.
+//!
+//! Title and description lengths `t` and `d` range from empty to `MaxTitleLength` and
+//! `MaxDescriptionLength`, the encoded call of `create_call_proposal` up to `MaxCallSize`;
+//! run with `--steps 3` to sample them at 0, half and full length. The voting calls are
+//! measured with up to `MaxDelegators` delegators carried by the vote, each of them a member,
+//! and with a full ring of receipts so minting one evicts the oldest.
+
+use super::*;
+use frame_benchmarking::v2::*;
+use frame_support::traits::{Currency, EnsureOrigin, Get};
+use frame_system::RawOrigin;
+use sp_io::hashing::blake2_256;
+use sp_runtime::traits::{Bounded, One, Saturating};
+use sp_std::{boxed::Box, vec, vec::Vec};
+use tidygen_primitives::governance::{ParameterChange, ProposalCategory};
+
+/// Pallet and call index of a `remark`, and the compact length of its bytes
+const REMARK_OVERHEAD: u32 = 7;
+
+/// Fund `who` well beyond the proposal deposit
+fn fund<T: Config>(who: &T::AccountId) {
+    T::Currency::make_free_balance_be(who, BalanceOf::<T>::max_value() / 2u32.into());
+}
+
+/// Let `who` vote even when only members may, and fill its receipts so the next one evicts
+fn setup_voter<T: Config>(who: &T::AccountId) {
+    Members::<T>::insert(who, ());
+    ReceiptRange::<T>::insert(who, (0, u64::from(T::MaxReceiptsPerAccount::get())));
+}
+
+/// Open a proposal with text as long as it gets, so reading it is the worst case, and return
+/// its proposer and ID
+fn setup_proposal<T: Config>() -> Result<(T::AccountId, u64), BenchmarkError> {
+    let proposer: T::AccountId = account("proposer", 0, 0);
+    fund::<T>(&proposer);
+    let proposal_id = ProposalCount::<T>::get();
+    Pallet::<T>::create_proposal(
+        RawOrigin::Signed(proposer.clone()).into(),
+        vec![b'y'; T::MaxTitleLength::get() as usize],
+        vec![b'y'; T::MaxDescriptionLength::get() as usize],
+        None,
+    )?;
+    Ok((proposer, proposal_id))
+}
+
+/// Give `delegate` `n` direct delegators, none of which voted yet
+fn add_delegators<T: Config>(delegate: &T::AccountId, n: u32) -> Result<(), BenchmarkError> {
+    for i in 0..n {
+        let delegator: T::AccountId = account("delegator", i, 0);
+        Members::<T>::insert(&delegator, ());
+        Pallet::<T>::delegate(RawOrigin::Signed(delegator).into(), delegate.clone())?;
+    }
+    Ok(())
+}
+
+/// Let another delegate vote on a proposal carrying `MaxDelegators` delegators, so the
+/// proposal's `DelegatedVotes` are as many as they get
+fn add_foreign_delegated_votes<T: Config>(proposal_id: u64) -> Result<(), BenchmarkError> {
+    let other: T::AccountId = account("other", 0, 0);
+    Members::<T>::insert(&other, ());
+    for i in 0..T::MaxDelegators::get() {
+        let delegator: T::AccountId = account("other_delegator", i, 0);
+        Members::<T>::insert(&delegator, ());
+        Pallet::<T>::delegate(RawOrigin::Signed(delegator).into(), other.clone())?;
+    }
+    Pallet::<T>::vote(RawOrigin::Signed(other).into(), proposal_id, VoteKind::Aye)?;
+    Ok(())
+}
+
+/// Chain `bottom` through `above` delegations to fresh accounts named `name`
+fn add_chain<T: Config>(
+    name: &'static str,
+    bottom: &T::AccountId,
+    above: u32,
+) -> Result<(), BenchmarkError> {
+    let mut current = bottom.clone();
+    for i in 0..above {
+        let next: T::AccountId = account(name, i, 0);
+        Pallet::<T>::delegate(RawOrigin::Signed(current).into(), next.clone())?;
+        current = next;
+    }
+    Ok(())
+}
+
+/// Vote a proposal through with as many ayes as its quorum asks for
+fn approve<T: Config>(proposal_id: u64) -> Result<(), BenchmarkError> {
+    let quorum = Proposals::<T>::get(proposal_id).ok_or("proposal is gone")?.quorum;
+    let voters = u32::try_from(quorum.max(1)).map_err(|_| "quorum too large")?;
+    for i in 0..voters {
+        let voter: T::AccountId = account("voter", i, 0);
+        Members::<T>::insert(&voter, ());
+        Pallet::<T>::vote(RawOrigin::Signed(voter).into(), proposal_id, VoteKind::Aye)?;
+    }
+    Ok(())
+}
+
+/// Move to the first block after the voting period of a proposal
+fn end_voting<T: Config>(proposal_id: u64) -> Result<(), BenchmarkError> {
+    let proposal = Proposals::<T>::get(proposal_id).ok_or("proposal is gone")?;
+    frame_system::Pallet::<T>::set_block_number(proposal.voting_end.saturating_add(One::one()));
+    Ok(())
+}
+
+/// Reject a proposal with a single nay once voting ended, slashing its deposit if configured
+fn setup_rejected<T: Config>() -> Result<u64, BenchmarkError> {
+    let (_, proposal_id) = setup_proposal::<T>()?;
+    let voter: T::AccountId = account("voter", 0, 0);
+    Members::<T>::insert(&voter, ());
+    Pallet::<T>::vote(RawOrigin::Signed(voter).into(), proposal_id, VoteKind::Nay)?;
+    end_voting::<T>(proposal_id)?;
+    Ok(proposal_id)
+}
+
+/// Status of a proposal, `None` if it does not exist
+fn status<T: Config>(proposal_id: u64) -> Option<ProposalStatus> {
+    Proposals::<T>::get(proposal_id).map(|proposal| proposal.status)
+}
+
+#[benchmarks(where <T as Config>::RuntimeCall: From<frame_system::Call<T>>)]
+mod benchmarks {
+    use super::*;
+
+    #[benchmark]
+    fn create_proposal(
+        t: Linear<0, { T::MaxTitleLength::get() }>,
+        d: Linear<0, { T::MaxDescriptionLength::get() }>,
+    ) {
+        let caller: T::AccountId = whitelisted_caller();
+        fund::<T>(&caller);
+        let proposal_id = ProposalCount::<T>::get();
+
+        #[extrinsic_call]
+        _(RawOrigin::Signed(caller), vec![b'x'; t as usize], vec![b'x'; d as usize], None);
+
+        assert_eq!(status::<T>(proposal_id), Some(ProposalStatus::Active));
+    }
+
+    #[benchmark]
+    fn create_parameter_proposal(
+        t: Linear<0, { T::MaxTitleLength::get() }>,
+        d: Linear<0, { T::MaxDescriptionLength::get() }>,
+    ) {
+        let caller: T::AccountId = whitelisted_caller();
+        fund::<T>(&caller);
+        let proposal_id = ProposalCount::<T>::get();
+
+        #[extrinsic_call]
+        _(
+            RawOrigin::Signed(caller),
+            vec![b'x'; t as usize],
+            vec![b'x'; d as usize],
+            None,
+            ParameterChange::DaoTextNormalization(true),
+        );
+
+        assert_eq!(status::<T>(proposal_id), Some(ProposalStatus::Active));
+    }
+
+    #[benchmark]
+    fn create_proposal_with_quorum(
+        t: Linear<0, { T::MaxTitleLength::get() }>,
+        d: Linear<0, { T::MaxDescriptionLength::get() }>,
+    ) {
+        let caller: T::AccountId = whitelisted_caller();
+        fund::<T>(&caller);
+        let proposal_id = ProposalCount::<T>::get();
+
+        #[extrinsic_call]
+        _(
+            RawOrigin::Signed(caller),
+            vec![b'x'; t as usize],
+            vec![b'x'; d as usize],
+            None,
+            T::MinQuorum::get(),
+        );
+
+        assert_eq!(status::<T>(proposal_id), Some(ProposalStatus::Active));
+    }
+
+    #[benchmark]
+    fn create_call_proposal(
+        t: Linear<0, { T::MaxTitleLength::get() }>,
+        d: Linear<0, { T::MaxDescriptionLength::get() }>,
+        c: Linear<0, { T::MaxCallSize::get().saturating_sub(REMARK_OVERHEAD) }>,
+    ) {
+        let caller: T::AccountId = whitelisted_caller();
+        fund::<T>(&caller);
+        let proposal_id = ProposalCount::<T>::get();
+        let call: <T as Config>::RuntimeCall =
+            frame_system::Call::<T>::remark { remark: vec![0; c as usize] }.into();
+
+        #[extrinsic_call]
+        _(
+            RawOrigin::Signed(caller),
+            vec![b'x'; t as usize],
+            vec![b'x'; d as usize],
+            None,
+            Box::new(call),
+        );
+
+        assert!(ProposalCalls::<T>::contains_key(proposal_id));
+    }
+
+    #[benchmark]
+    fn create_categorized_proposal(
+        t: Linear<0, { T::MaxTitleLength::get() }>,
+        d: Linear<0, { T::MaxDescriptionLength::get() }>,
+    ) {
+        let caller: T::AccountId = whitelisted_caller();
+        fund::<T>(&caller);
+        let proposal_id = ProposalCount::<T>::get();
+
+        #[extrinsic_call]
+        _(
+            RawOrigin::Signed(caller),
+            vec![b'x'; t as usize],
+            vec![b'x'; d as usize],
+            None,
+            ProposalCategory::Technical,
+        );
+
+        assert_eq!(
+            Proposals::<T>::get(proposal_id).map(|proposal| proposal.category),
+            Some(ProposalCategory::Technical)
+        );
+    }
+
+    #[benchmark]
+    fn amend_proposal(
+        t: Linear<0, { T::MaxTitleLength::get() }>,
+        d: Linear<0, { T::MaxDescriptionLength::get() }>,
+    ) -> Result<(), BenchmarkError> {
+        let (proposer, proposal_id) = setup_proposal::<T>()?;
+
+        #[extrinsic_call]
+        _(
+            RawOrigin::Signed(proposer),
+            proposal_id,
+            vec![b'x'; t as usize],
+            vec![b'x'; d as usize],
+        );
+
+        let proposal = Proposals::<T>::get(proposal_id).ok_or("proposal is gone")?;
+        assert_eq!(proposal.revision, 1);
+        Ok(())
+    }
+
+    #[benchmark]
+    fn vote(n: Linear<0, { T::MaxDelegators::get() }>) -> Result<(), BenchmarkError> {
+        let (_, proposal_id) = setup_proposal::<T>()?;
+        let caller: T::AccountId = whitelisted_caller();
+        setup_voter::<T>(&caller);
+        add_delegators::<T>(&caller, n)?;
+
+        #[extrinsic_call]
+        _(RawOrigin::Signed(caller.clone()), proposal_id, VoteKind::Aye);
+
+        let proposal = Proposals::<T>::get(proposal_id).ok_or("proposal is gone")?;
+        assert_eq!(proposal.votes_for, u64::from(n) + 1);
+        Ok(())
+    }
+
+    #[benchmark]
+    fn vote_with_salt(n: Linear<0, { T::MaxDelegators::get() }>) -> Result<(), BenchmarkError> {
+        let (_, proposal_id) = setup_proposal::<T>()?;
+        let caller: T::AccountId = whitelisted_caller();
+        setup_voter::<T>(&caller);
+        add_delegators::<T>(&caller, n)?;
+
+        #[extrinsic_call]
+        _(RawOrigin::Signed(caller.clone()), proposal_id, VoteKind::Aye, [7; 32]);
+
+        assert!(HasVoted::<T>::get(proposal_id, &caller));
+        Ok(())
+    }
+
+    #[benchmark]
+    fn vote_with_conviction(
+        n: Linear<0, { T::MaxDelegators::get() }>,
+    ) -> Result<(), BenchmarkError> {
+        let (_, proposal_id) = setup_proposal::<T>()?;
+        let caller: T::AccountId = whitelisted_caller();
+        fund::<T>(&caller);
+        setup_voter::<T>(&caller);
+        add_delegators::<T>(&caller, n)?;
+        let balance = T::VoteUnit::get();
+
+        #[extrinsic_call]
+        _(RawOrigin::Signed(caller.clone()), proposal_id, VoteKind::Aye, balance, MAX_CONVICTION);
+
+        assert!(VoteLocks::<T>::contains_key(&caller));
+        Ok(())
+    }
+
+    #[benchmark]
+    fn change_vote(n: Linear<0, { T::MaxDelegators::get() }>) -> Result<(), BenchmarkError> {
+        let (_, proposal_id) = setup_proposal::<T>()?;
+        let caller: T::AccountId = whitelisted_caller();
+        setup_voter::<T>(&caller);
+        add_delegators::<T>(&caller, n)?;
+        add_foreign_delegated_votes::<T>(proposal_id)?;
+        Pallet::<T>::vote(RawOrigin::Signed(caller.clone()).into(), proposal_id, VoteKind::Aye)?;
+
+        #[extrinsic_call]
+        _(RawOrigin::Signed(caller.clone()), proposal_id, VoteKind::Nay);
+
+        assert_eq!(Votes::<T>::get(proposal_id, &caller), Some(VoteKind::Nay));
+        Ok(())
+    }
+
+    #[benchmark]
+    fn retract_vote(n: Linear<0, { T::MaxDelegators::get() }>) -> Result<(), BenchmarkError> {
+        let (_, proposal_id) = setup_proposal::<T>()?;
+        let caller: T::AccountId = whitelisted_caller();
+        setup_voter::<T>(&caller);
+        add_delegators::<T>(&caller, n)?;
+        add_foreign_delegated_votes::<T>(proposal_id)?;
+        Pallet::<T>::vote(RawOrigin::Signed(caller.clone()).into(), proposal_id, VoteKind::Aye)?;
+
+        #[extrinsic_call]
+        _(RawOrigin::Signed(caller.clone()), proposal_id);
+
+        assert!(!HasVoted::<T>::get(proposal_id, &caller));
+        Ok(())
+    }
+
+    #[benchmark]
+    fn execute_proposal() -> Result<(), BenchmarkError> {
+        let (proposer, proposal_id) = setup_proposal::<T>()?;
+        approve::<T>(proposal_id)?;
+        end_voting::<T>(proposal_id)?;
+
+        #[extrinsic_call]
+        _(RawOrigin::Signed(proposer), proposal_id);
+
+        assert_eq!(status::<T>(proposal_id), Some(ProposalStatus::Executed));
+        Ok(())
+    }
+
+    #[benchmark]
+    fn close_proposal() -> Result<(), BenchmarkError> {
+        let proposal_id = setup_rejected::<T>()?;
+        let caller: T::AccountId = whitelisted_caller();
+
+        #[extrinsic_call]
+        _(RawOrigin::Signed(caller), proposal_id);
+
+        assert_eq!(status::<T>(proposal_id), Some(ProposalStatus::Rejected));
+        Ok(())
+    }
+
+    #[benchmark]
+    fn close_proposal_unsigned() -> Result<(), BenchmarkError> {
+        let proposal_id = setup_rejected::<T>()?;
+
+        #[extrinsic_call]
+        _(RawOrigin::None, proposal_id);
+
+        assert_eq!(status::<T>(proposal_id), Some(ProposalStatus::Rejected));
+        Ok(())
+    }
+
+    #[benchmark]
+    fn cancel_proposal() -> Result<(), BenchmarkError> {
+        let (proposer, proposal_id) = setup_proposal::<T>()?;
+
+        #[extrinsic_call]
+        _(RawOrigin::Signed(proposer), proposal_id);
+
+        assert_eq!(status::<T>(proposal_id), Some(ProposalStatus::Cancelled));
+        Ok(())
+    }
+
+    #[benchmark]
+    fn set_text_normalization() {
+        #[extrinsic_call]
+        _(RawOrigin::Root, true);
+
+        assert!(NormalizeText::<T>::get());
+    }
+
+    #[benchmark]
+    fn issue_poll_vouchers(
+        v: Linear<0, { T::MaxVouchersPerProposal::get() }>,
+    ) -> Result<(), BenchmarkError> {
+        let (proposer, proposal_id) = setup_proposal::<T>()?;
+        let vouchers = (0..v).map(|i| blake2_256(&i.to_le_bytes())).collect::<Vec<_>>();
+
+        #[extrinsic_call]
+        _(RawOrigin::Signed(proposer), proposal_id, vouchers);
+
+        assert_eq!(PollVoucherCount::<T>::get(proposal_id), v);
+        Ok(())
+    }
+
+    #[benchmark]
+    fn vote_with_voucher() -> Result<(), BenchmarkError> {
+        let (proposer, proposal_id) = setup_proposal::<T>()?;
+        let preimage = [7; 32];
+        let voucher = blake2_256(&preimage);
+        Pallet::<T>::issue_poll_vouchers(
+            RawOrigin::Signed(proposer).into(),
+            proposal_id,
+            vec![voucher],
+        )?;
+
+        #[extrinsic_call]
+        _(RawOrigin::None, proposal_id, preimage, VoteKind::Aye);
+
+        assert_eq!(PollVouchers::<T>::get(proposal_id, voucher), Some(true));
+        Ok(())
+    }
+
+    #[benchmark]
+    fn delegate(d: Linear<2, { T::MaxDelegationDepth::get() }>) -> Result<(), BenchmarkError> {
+        // A delegator below the caller makes the height of both chains change all the way
+        let caller: T::AccountId = whitelisted_caller();
+        let delegator: T::AccountId = account("delegator", 0, 0);
+        let previous: T::AccountId = account("delegate", 0, 0);
+        let to: T::AccountId = account("delegate", 1, 0);
+        add_chain::<T>("previous", &previous, d - 2)?;
+        add_chain::<T>("next", &to, d - 2)?;
+        Pallet::<T>::delegate(RawOrigin::Signed(caller.clone()).into(), previous)?;
+        Pallet::<T>::delegate(RawOrigin::Signed(delegator).into(), caller.clone())?;
+
+        #[extrinsic_call]
+        _(RawOrigin::Signed(caller.clone()), to.clone());
+
+        assert_eq!(Delegations::<T>::get(&caller), Some(to));
+        Ok(())
+    }
+
+    #[benchmark]
+    fn undelegate(d: Linear<2, { T::MaxDelegationDepth::get() }>) -> Result<(), BenchmarkError> {
+        let caller: T::AccountId = whitelisted_caller();
+        let delegator: T::AccountId = account("delegator", 0, 0);
+        let to: T::AccountId = account("delegate", 0, 0);
+        add_chain::<T>("previous", &to, d - 2)?;
+        Pallet::<T>::delegate(RawOrigin::Signed(caller.clone()).into(), to)?;
+        Pallet::<T>::delegate(RawOrigin::Signed(delegator).into(), caller.clone())?;
+
+        #[extrinsic_call]
+        _(RawOrigin::Signed(caller.clone()));
+
+        assert!(!Delegations::<T>::contains_key(&caller));
+        Ok(())
+    }
+
+    #[benchmark]
+    fn add_member() -> Result<(), BenchmarkError> {
+        let origin =
+            T::MembershipOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+        let member: T::AccountId = account("member", 0, 0);
+
+        #[extrinsic_call]
+        _(origin as T::RuntimeOrigin, member.clone());
+
+        assert!(Members::<T>::contains_key(&member));
+        Ok(())
+    }
+
+    #[benchmark]
+    fn remove_member() -> Result<(), BenchmarkError> {
+        let origin =
+            T::MembershipOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+        let member: T::AccountId = account("member", 0, 0);
+        Pallet::<T>::add_member(origin.clone(), member.clone())?;
+
+        #[extrinsic_call]
+        _(origin as T::RuntimeOrigin, member.clone());
+
+        assert!(!Members::<T>::contains_key(&member));
+        Ok(())
+    }
+
+    #[benchmark]
+    fn unlock() -> Result<(), BenchmarkError> {
+        let (_, proposal_id) = setup_proposal::<T>()?;
+        let caller: T::AccountId = whitelisted_caller();
+        fund::<T>(&caller);
+        setup_voter::<T>(&caller);
+        Pallet::<T>::vote_with_conviction(
+            RawOrigin::Signed(caller.clone()).into(),
+            proposal_id,
+            VoteKind::Aye,
+            T::VoteUnit::get(),
+            1,
+        )?;
+        let (_, until) = VoteLocks::<T>::get(&caller).ok_or("vote is not locked")?;
+        frame_system::Pallet::<T>::set_block_number(until);
+
+        #[extrinsic_call]
+        _(RawOrigin::Signed(caller.clone()));
+
+        assert!(!VoteLocks::<T>::contains_key(&caller));
+        Ok(())
+    }
+
+    impl_benchmark_test_suite!(Pallet, crate::mock::new_test_ext(), crate::mock::Test);
+}
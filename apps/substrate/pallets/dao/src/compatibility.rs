@@ -0,0 +1,98 @@
+//! Pinned call indices and storage prefixes of pallet-dao.
+//!
+//! Downstream tooling decodes extrinsics and storage by these values. See
+//! `tidygen_primitives::compat` for how an intended change to them is made.
+
+use crate::{mock::*, Call, Pallet};
+use codec::Encode;
+use frame_support::traits::{GetCallIndex, GetCallName, StorageInfoTrait};
+use tidygen_primitives::compat::{assert_call_indices, assert_storage_prefixes};
+
+/// Version of the tables below, bumped when an existing entry moves or goes away
+const COMPAT_VERSION: u32 = 1;
+
+/// Every dispatchable with its call index
+const CALL_INDICES: &[(&str, u8)] = &[
+    ("create_proposal", 0),
+    ("vote", 1),
+    ("execute_proposal", 2),
+    ("close_proposal", 3),
+    ("cancel_proposal", 4),
+    ("set_text_normalization", 5),
+    ("close_proposal_unsigned", 6),
+    ("vote_with_salt", 7),
+    ("issue_poll_vouchers", 8),
+    ("vote_with_voucher", 9),
+    ("create_parameter_proposal", 10),
+    ("create_proposal_with_quorum", 11),
+    ("delegate", 12),
+    ("undelegate", 13),
+    ("change_vote", 14),
+    ("retract_vote", 15),
+    ("create_call_proposal", 16),
+    ("create_categorized_proposal", 17),
+    ("add_member", 18),
+    ("remove_member", 19),
+    ("vote_with_conviction", 20),
+    ("unlock", 21),
+    ("amend_proposal", 22),
+];
+
+/// Every storage item with the `twox_128` of its name
+const STORAGE_PREFIXES: &[(&str, &str)] = &[
+    ("Proposals", "88c2f7188c6fdd1dffae2fa0d171f440"),
+    ("Votes", "b4adc6a1ce4f7cc2e696ed0fd06bd01c"),
+    ("ProposalsEndingAt", "9ba7752f8e707cde58a7650ecff815f9"),
+    ("ProposalCount", "6254e9d55588784fa2a62b726696e2b1"),
+    ("HasVoted", "65f8c231b14ca5bacfa8ea00efd871a4"),
+    ("Receipts", "5087a88ab53079a394ab62a465d46183"),
+    ("ReceiptRange", "fd4791642f0d0b0580eaf9e4c964950a"),
+    ("PollVouchers", "4dce00fe01fc0b3171a9391f99532c21"),
+    ("PollVoucherCount", "d8064e64be20b16cb821b57ad72e0030"),
+    ("NormalizeText", "d7ae493d7cd06cb7a5132c426cc728c0"),
+    ("Delegations", "f6eed5c65f50198f0f53f8f499f77091"),
+    ("Delegators", "e1c5df6d2773f08c7b6b1b6d0139c22a"),
+    ("DelegatedVotes", "5e9804f4163a433e0d3975ec69258eb1"),
+    ("ProposalCalls", "52629ece29f1209dcfe3986c378a9d93"),
+    ("AutoCloseCursor", "bf6584ec853f1efa7e018661d279599a"),
+    ("ProposalsByCategory", "8251c62e0746a9a94ed9bc0786f22c10"),
+    ("CategoryCount", "d84af68e90a97f13bcb2dd61d3b7e958"),
+    ("Members", "ba7fb8745735dc3be2a2c61a72c39e78"),
+    ("MemberCount", "6fda56a2c5fdcd6a2185f92a24a09894"),
+    ("VoteConvictions", "33152b05f11cad3655a58a8306913dda"),
+    ("VoteLocks", "70668767fd70ba7d560c0d3e5735ed20"),
+    ("VoteWeights", "cd1eead941db8cc3d726efdb89cc84d9"),
+    ("CarriedDelegators", "f2ea32fb3bd0cebd4a142dd8247c172f"),
+    ("DelegatorHeights", "8b1a41aa149bf9b7fa49eff31e7b2348"),
+];
+
+#[test]
+fn call_indices_are_stable() {
+    assert_call_indices(
+        "pallet-dao",
+        COMPAT_VERSION,
+        CALL_INDICES,
+        Call::<Test>::get_call_names(),
+        Call::<Test>::get_call_indices(),
+    );
+}
+
+#[test]
+fn encoded_calls_lead_with_the_pinned_index() {
+    assert_eq!(Call::<Test>::set_text_normalization { enabled: false }.encode(), vec![5, 0]);
+    assert_eq!(Call::<Test>::close_proposal_unsigned { proposal_id: 1 }.encode()[0], 6);
+
+    // In a runtime call the pallet index comes first, Dao is the third pallet of the mock
+    let call = RuntimeCall::Dao(Call::close_proposal { proposal_id: 7 });
+    assert_eq!(call.encode(), [&[2u8, 3][..], &7u64.encode()].concat());
+}
+
+#[test]
+fn storage_prefixes_are_stable() {
+    let actual: Vec<_> = Pallet::<Test>::storage_info()
+        .into_iter()
+        .map(|info| (info.storage_name, info.prefix))
+        .collect();
+
+    assert_storage_prefixes("pallet-dao", COMPAT_VERSION, STORAGE_PREFIXES, &actual);
+}
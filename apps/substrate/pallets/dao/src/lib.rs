@@ -8,7 +8,7 @@
 //!
 //! The DAO pallet provides functionality for:
 //! - Creating governance proposals
-//! - Voting on proposals (yes/no voting)
+//! - Voting on proposals (aye, nay or abstain)
 //! - Executing approved proposals
 //! - Managing proposal lifecycle
 //! - Token-weighted voting (optional)
@@ -18,11 +18,255 @@
 //! ### Dispatchable Functions
 //!
 //! * `create_proposal` - Create a new governance proposal
+//! * `create_parameter_proposal` - Propose a typed change of a runtime parameter
+//! * `create_call_proposal` - Propose a runtime call dispatched on execution
+//! * `create_categorized_proposal` - Create a proposal filed under a category
+//! * `add_member` - Add an account to the members
+//! * `remove_member` - Remove an account from the members
+//! * `vote_with_conviction` - Vote with a weight multiplier, locking the voter's balance
+//! * `unlock` - Remove the balance lock of conviction votes once it expired
+//! * `amend_proposal` - Replace the title and description of a proposal without votes
+//! * `create_proposal_with_quorum` - Create a proposal needing more votes than `MinQuorum`
 //! * `vote` - Cast a vote on a proposal
+//! * `vote_with_salt` - Cast a vote whose receipt hides the choice behind a salt
+//! * `change_vote` - Change a vote before the voting period ends
+//! * `retract_vote` - Withdraw a vote before the voting period ends
 //! * `execute_proposal` - Execute an approved proposal
 //! * `close_proposal` - Close a proposal after voting period
+//! * `close_proposal_unsigned` - Close an expired proposal (unsigned, from the offchain worker)
+//! * `set_text_normalization` - Toggle line ending normalization of proposal text (root only)
+//! * `issue_poll_vouchers` - Issue one-time vouchers for anonymous votes (proposer or admin)
+//! * `vote_with_voucher` - Cast an anonymous vote by spending a voucher (unsigned)
+//! * `delegate` - Let another account's votes carry the caller's weight
+//! * `undelegate` - Withdraw the caller's delegation
+//!
+//! ### Hooks
+//!
+//! * `on_initialize` - Closes the proposals whose voting period ended, within the
+//!   `AutoCloseWeight` budget. Work left over is resumed in the next block.
+//! * `offchain_worker` - Submits `close_proposal_unsigned` for ended proposals still active
+//!
+//! ## Deposits
+//!
+//! Creating a proposal reserves `ProposalDeposit` from the proposer. The reserved amount is
+//! recorded on the proposal and every release uses that recorded amount, so changing
+//! `ProposalDeposit` never releases more or less than was actually reserved.
+//!
+//! Executing or cancelling a proposal refunds the deposit, and so does closing it as
+//! rejected. With `SlashRejectedDeposits` enabled, a proposal rejected with an
+//! `approval_percentage` below `MinRefundApproval` is treated as spam instead: its deposit is
+//! slashed to `Config::Slashed` and `DepositSlashed` is emitted.
+//!
+//! ## Status Transitions
+//!
+//! Every `ProposalStatusChanged` is also reported to `Config::OnTransition`, e.g. a
+//! compliance log archiving them.
+//!
+//! ## Quorum
+//!
+//! A proposal only passes once at least its `quorum` of votes was cast, however they split:
+//! closing a proposal below quorum rejects it, `VotingEnded` reports whether quorum was
+//! reached, and `execute_proposal` refuses it. The quorum is fixed at creation, `MinQuorum`
+//! unless `create_proposal_with_quorum` asks for more. The version 5 upgrade gives stored
+//! proposals the `MinQuorum` configured when it runs, so an approved proposal below it can
+//! no longer be executed.
+//!
+//! ## Membership
+//!
+//! With `MembersOnly` set, only accounts in `Members` may `vote`, `vote_with_salt` and
+//! `change_vote`; others get `NotAMember`. `MembershipOrigin` manages the members with
+//! `add_member` and `remove_member`. A delegate's vote only carries delegators that are
+//! members, and votes a removed member already cast stay counted. Poll vouchers are handed out
+//! by their issuer, so `vote_with_voucher` is not gated.
+//!
+//! The member count feeds the quorum: a proposal created while only members vote needs at
+//! least `MemberQuorum` of `MemberCount` votes, rounded up, if that is more than the quorum
+//! it asked for. Like any quorum, it is fixed at creation.
+//!
+//! ## Abstentions
+//!
+//! A vote is a `VoteKind`: `Aye`, `Nay` or `Abstain`. Abstentions are tallied in
+//! `abstentions` and count towards `total_votes`, and thus quorum, but not towards the
+//! majority: a proposal can reach quorum through abstentions alone and still be rejected for
+//! lack of ayes. `approval_percentage` is the share of ayes among ayes and nays.
+//!
+//! `VoteKind` encodes `Nay` and `Aye` as the `bool` votes did before (0 and 1), so stored
+//! `Votes`, receipt choice hashes and `vote` calls encoded with a `bool` keep their meaning.
+//! The version 6 upgrade starts stored proposals with no abstentions.
+//!
+//! ## Delegation
+//!
+//! `delegate` hands the caller's voting power to another account. A vote then weighs one
+//! plus the direct delegators of the voter that have neither voted on the proposal nor been
+//! carried by another delegate's vote on it; delegations are not followed transitively. The
+//! weight is reported in `VoteCast` and recorded on the receipt. `DelegatedVotes` notes
+//! whose weight each vote carried, and `CarriedDelegators` lists them per vote; the version
+//! 10 upgrade builds that index. A delegator voting directly takes its weight back from that
+//! vote, so the delegator's own choice wins whichever vote comes first.
+//!
+//! Delegations that would close a cycle or make a chain longer than `MaxDelegationDepth`
+//! are rejected, and an account accepts at most `MaxDelegators` delegators. The check reads
+//! the longest chain below the caller from `DelegatorHeights`, which `delegate` and
+//! `undelegate` update along the chain above. Moving or withdrawing a delegation leaves
+//! votes already cast standing.
+//!
+//! ## Conviction Voting
+//!
+//! `vote_with_conviction` trades liquidity for say: the voter puts up a `balance` of at least
+//! one `VoteUnit` and at most its free balance, which is locked (`VOTE_LOCK_ID`) for as many
+//! voting periods of the proposal as the conviction, counted from the end of its voting
+//! period. The voter's own weight is the conviction alone: locking more balance adds no
+//! weight, so a large holder cannot reach a quorum on its own:
+//!
+//! | Conviction | Weight | Locked after voting ends |
+//! |------------|--------|--------------------------|
+//! | 1          | 1      | 1 voting period          |
+//! | 2          | 2      | 2 voting periods         |
+//! | 3          | 3      | 3 voting periods         |
+//! | 4          | 4      | 4 voting periods         |
+//! | 5          | 5      | 5 voting periods         |
+//! | 6          | 6      | 6 voting periods         |
+//!
+//! Plain votes weigh 1 and lock nothing. Delegators carried by the vote add one each, as
+//! usual. An account has a single lock in `VoteLocks`: further conviction votes raise its
+//! amount and end block, never lower them. Only `balance` is locked, so the rest of the free
+//! balance still pays fees and deposits, e.g. the fee of `unlock`. Once the end block is
+//! reached, `unlock` removes the lock. `VoteWeights` keeps the own weight of each conviction
+//! vote, so `change_vote` and `retract_vote` move its full weight; retracting a vote does not
+//! shorten the lock.
+//!
+//! ## Parameter Changes
+//!
+//! Instead of an opaque call, `create_parameter_proposal` attaches a typed
+//! `ParameterChange` to a proposal, such as "enable ledger text normalization", so voters
+//! and the snapshot RPC read exactly what would change. Executing the approved proposal hands
+//! the change to `Config::Parameters`, a `ParameterHandler` chaining the pallets that own
+//! governable parameters. An applied change emits `ParameterChanged`. A change no handler
+//! governs any more, e.g. after a runtime upgrade removed the parameter, emits
+//! `ParameterNoLongerExists` instead; the proposal is executed either way.
+//!
+//! ## Runtime Calls
+//!
+//! `create_call_proposal` attaches any runtime call of at most `MaxCallSize` encoded bytes
+//! and `MaxCallWeight`. The call is kept encoded in `ProposalCalls` and its Blake2-256 is
+//! stored on the proposal as `call_hash`. Executing the approved proposal dispatches it with
+//! `ProposalOrigin`; `PotOrigin` signs it as `Pallet::account_id`, the DAO's own account
+//! derived from `PalletId`, so proposals can spend what was sent to that account.
+//!
+//! The status change is stored before the call runs, so the call cannot execute its own
+//! proposal again. A failing call, or one that no longer decodes or now weighs more than
+//! `MaxCallWeight` after a runtime upgrade, only reverts its own changes: the proposal ends
+//! `ExecutionFailed` and `ProposalExecutionFailed` reports the error. `execute_proposal`
+//! charges `MaxCallWeight` up front and refunds what the call did not use. Cancelled and
+//! rejected proposals drop their call. The version 7 upgrade stores existing proposals
+//! without a call.
+//!
+//! ## Categories
+//!
+//! Every proposal is filed under a `ProposalCategory` (`General`, `Budget`, `Hiring` or
+//! `Technical`, see `tidygen_primitives::governance`), so UIs can filter proposals without
+//! parsing titles. `create_categorized_proposal` picks the category; every other way of
+//! creating a proposal files it under `General`. The category is fixed at creation and shown
+//! in snapshot entries.
+//!
+//! `ProposalsByCategory` lists the proposals of each category in creation order, with
+//! `CategoryCount` entries per category. Read a page of them with the runtime API
+//! `get_proposals_by_category` (RPC `dao_getProposalsByCategory`). The version 8 upgrade
+//! files stored proposals under `General`, in ID order.
+//!
+//! ## Auto-Close
+//!
+//! Active proposals are indexed by their voting end block in `ProposalsEndingAt`. In
+//! `on_initialize` the pallet closes the proposals indexed at the current block, exactly as
+//! `close_proposal` would, within the `AutoCloseWeight` budget. Blocks it did not get to are
+//! tracked by `AutoCloseCursor` and handled in later blocks, so UIs never see an ended
+//! proposal as active for long. A zero budget turns this off. The version 11 upgrade starts
+//! the cursor at the earliest indexed block, so proposals that ended before the upgrade are
+//! closed too.
+//!
+//! `close_proposal` remains as a fallback and does nothing for a proposal that was already
+//! closed. As a second fallback, when `OffchainAutoClose` is enabled, the offchain worker
+//! submits an unsigned `close_proposal_unsigned` transaction for every indexed proposal whose
+//! voting period has ended. `ValidateUnsigned` only accepts it while the proposal is still
+//! active and past its end, and tags it by proposal ID so the pool holds at most one per
+//! proposal.
+//!
+//! ## Governance Snapshots
+//!
+//! Before runtime upgrades, governance state is archived off-chain through the
+//! `pallet-dao-runtime-api` `export_snapshot` call (RPC `dao_exportSnapshot`). It returns
+//! all non-terminal proposals in ascending ID order, page by page; every page but the last
+//! carries the `PageCursor` to request the next one with. The last page carries
+//! `snapshot_digest()`: the Blake2-256 hash of the SCALE-encoded `Vec<ProposalSnapshot>`
+//! of the full export, so an archive can be checked against the chain state later.
+//!
+//! ## Vote Receipts
+//!
+//! Every vote mints a `VoteReceipt { proposal_id, weight, block, choice_hash }` into
+//! `Receipts` under the voter and a per-voter receipt ID that only increases. Receipts are
+//! stored apart from `Votes`, so they outlive pruned vote storage and let a voter prove
+//! participation later; read them with the runtime API `get_receipts` (RPC
+//! `dao_getReceipts`). An account keeps at most `MaxReceiptsPerAccount` receipts, evicting
+//! its oldest first.
+//!
+//! `choice_hash` is `Pallet::hash_choice`: the Blake2-256 of the SCALE-encoded choice
+//! followed by a salt. `vote` uses an empty salt. With `vote_with_salt` the salt is not kept
+//! in state, so the receipt alone does not disclose the choice until the voter reveals it.
+//! The salt remains visible in the extrinsic that cast the vote.
+//!
+//! ## Changing Votes
+//!
+//! Until the voting period ends, `change_vote` replaces a vote: its whole weight, including
+//! the delegators it carries, moves to the new choice and `VoteChanged` reports both. The
+//! change mints a receipt of its own, with an empty salt; earlier receipts stay as minted.
+//!
+//! `retract_vote` withdraws a vote altogether, with its weight, and reports it in
+//! `VoteRetracted`. The delegators it carried are free to be carried again, and the voter may
+//! vote again. Receipts of the withdrawn vote are kept.
+//!
+//! ## Anonymous Polls
+//!
+//! For sensitive polls the proposer or `AdminOrigin` issues one-time vouchers with
+//! `issue_poll_vouchers`: Blake2-256 hashes of random preimages generated off-chain and handed
+//! out to the eligible voters, e.g. by the HR backend. A voter casts `vote_with_voucher` as an
+//! unsigned transaction carrying the preimage. The vote is counted in the tally and the
+//! voucher is marked spent, but no account is recorded: no `Votes` or `HasVoted` entry and
+//! no receipt. Nothing on-chain links a voucher to its holder; that link only exists where
+//! the preimages are handed out.
+//!
+//! `ValidateUnsigned` only admits a voucher vote whose preimage hashes to an unspent voucher
+//! of a proposal still open for votes, tagged by the voucher hash, so the pool holds at most
+//! one transaction per voucher and random preimages never enter it. This counts
+//! participation without linkability, but is weaker than commit-reveal: tallies are visible
+//! while voting runs, and whoever handed out the preimages can attribute votes.
+//!
+//! ## Amendments
+//!
+//! Until the first vote, the proposer may fix the title and description with
+//! `amend_proposal`; the new text is checked like that of a new proposal. Each amendment
+//! bumps the proposal's `revision` and emits `ProposalAmended`, so voters and UIs can tell
+//! which text they saw. Once any vote is counted, anonymous and delegated ones included, the
+//! text is final and amending fails with `VotesAlreadyCast`. The version 9 upgrade starts
+//! stored proposals at revision 0.
+//!
+//! ## Text Handling
+//!
+//! Proposal titles and descriptions are `BoundedString`s of UTF-8 (see
+//! `tidygen_primitives::bounded_string`): text that is too long or not valid UTF-8 is
+//! refused with `TitleTooLong`, `DescriptionTooLong` or `InvalidUtf8`. When `NormalizeText`
+//! is enabled their line endings are also normalized to LF before storage (see
+//! `tidygen_primitives::text`). New chains enable it through the genesis config; existing
+//! chains keep it off until root opts in. The version 4 upgrade drops the invalid UTF-8
+//! sequences of proposals stored while the check depended on the switch.
 
 pub use pallet::*;
+pub use weights::WeightInfo;
+
+pub mod migrations;
+pub mod weights;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
 
 #[cfg(test)]
 mod mock;
@@ -30,24 +274,69 @@ mod mock;
 #[cfg(test)]
 mod tests;
 
+#[cfg(test)]
+mod compatibility;
+
 #[frame_support::pallet]
 pub mod pallet {
+    use crate::weights::WeightInfo;
     use frame_support::{
+        dispatch::{extract_actual_weight, GetDispatchInfo, PostDispatchInfo},
         pallet_prelude::*,
-        traits::{Currency, Get, ReservableCurrency},
+        traits::{
+            Currency, Get, LockIdentifier, LockableCurrency, OnUnbalanced, ReservableCurrency,
+            WithdrawReasons,
+        },
+        PalletId,
+    };
+    use frame_system::{
+        offchain::{SendTransactionTypes, SubmitTransaction},
+        pallet_prelude::*,
+    };
+    #[cfg(feature = "serde")]
+    use serde::{Deserialize, Serialize};
+    use sp_runtime::{
+        traits::{AccountIdConversion, Dispatchable, One, Saturating},
+        Perbill,
+    };
+    use sp_std::{boxed::Box, vec::Vec};
+    use tidygen_primitives::{
+        bounded_string::{BoundedString, BoundedStringError, Utf8},
+        features::FeatureFlags,
+        governance::{ParameterChange, ParameterHandler, ParameterNoLongerExists, ProposalCategory},
+        pagination::{InvalidCursor, PageCursor},
+        text,
+        transitions::{status_index, OnStatusTransition, StateMachine, TransitionSubject},
     };
-    use frame_system::pallet_prelude::*;
-    use sp_runtime::traits::Saturating;
-    use sp_std::vec::Vec;
 
-    type BalanceOf<T> =
+    pub type BalanceOf<T> =
         <<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+    pub type NegativeImbalanceOf<T> = <<T as Config>::Currency as Currency<
+        <T as frame_system::Config>::AccountId,
+    >>::NegativeImbalance;
+
+    /// The in-code storage version
+    const STORAGE_VERSION: StorageVersion = StorageVersion::new(11);
+
+    /// Blocks an unsigned auto-close transaction stays valid in the pool
+    const AUTO_CLOSE_LONGEVITY: u64 = 5;
+
+    /// Blocks an unsigned voucher vote stays valid in the pool
+    const VOUCHER_VOTE_LONGEVITY: u64 = 5;
+
+    /// Identifier of the balance lock of conviction votes
+    pub const VOTE_LOCK_ID: LockIdentifier = *b"tg/daovt";
+
+    /// Highest conviction of `vote_with_conviction`
+    pub const MAX_CONVICTION: u8 = 6;
 
     #[pallet::pallet]
+    #[pallet::storage_version(STORAGE_VERSION)]
     pub struct Pallet<T>(_);
 
     /// Proposal status
     #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
     pub enum ProposalStatus {
         /// Proposal is active and accepting votes
         Active,
@@ -61,6 +350,8 @@ pub mod pallet {
         Cancelled,
         /// Proposal voting period expired
         Expired,
+        /// Proposal was executed, but dispatching its call failed
+        ExecutionFailed,
     }
 
     impl Default for ProposalStatus {
@@ -69,6 +360,47 @@ pub mod pallet {
         }
     }
 
+    /// Allowed proposal status transitions. An approved proposal may be executed straight
+    /// from `Active` once voting has ended, without being closed first.
+    pub const PROPOSAL_STATUS: StateMachine<ProposalStatus> = StateMachine::new(&[
+        (ProposalStatus::Active, ProposalStatus::Approved),
+        (ProposalStatus::Active, ProposalStatus::Rejected),
+        (ProposalStatus::Active, ProposalStatus::Cancelled),
+        (ProposalStatus::Active, ProposalStatus::Expired),
+        (ProposalStatus::Active, ProposalStatus::Executed),
+        (ProposalStatus::Approved, ProposalStatus::Executed),
+        (ProposalStatus::Active, ProposalStatus::ExecutionFailed),
+        (ProposalStatus::Approved, ProposalStatus::ExecutionFailed),
+    ]);
+
+    /// Choice of a vote
+    ///
+    /// `Nay` and `Aye` encode as `false` and `true` did, see the module docs on abstentions.
+    #[derive(
+        Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen,
+    )]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub enum VoteKind {
+        /// In favor of the proposal
+        #[codec(index = 1)]
+        Aye,
+        /// Against the proposal
+        #[codec(index = 0)]
+        Nay,
+        /// Counted towards quorum only
+        #[codec(index = 2)]
+        Abstain,
+    }
+
+    /// Title of a proposal
+    pub type ProposalTitle<T> = BoundedString<<T as Config>::MaxTitleLength, Utf8>;
+
+    /// Description of a proposal
+    pub type ProposalDescription<T> = BoundedString<<T as Config>::MaxDescriptionLength, Utf8>;
+
+    /// SCALE-encoded runtime call of a proposal
+    pub type EncodedCall<T> = BoundedVec<u8, <T as Config>::MaxCallSize>;
+
     /// Proposal data structure
     #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
     #[scale_info(skip_type_params(T))]
@@ -78,9 +410,9 @@ pub mod pallet {
         /// Proposal creator
         pub proposer: T::AccountId,
         /// Proposal title
-        pub title: BoundedVec<u8, T::MaxTitleLength>,
+        pub title: ProposalTitle<T>,
         /// Proposal description
-        pub description: BoundedVec<u8, T::MaxDescriptionLength>,
+        pub description: ProposalDescription<T>,
         /// Block number when created
         pub created_at: BlockNumberFor<T>,
         /// Voting start block
@@ -93,12 +425,26 @@ pub mod pallet {
         pub votes_for: u64,
         /// Number of votes against
         pub votes_against: u64,
-        /// Total number of votes cast
+        /// Number of abstentions
+        pub abstentions: u64,
+        /// Total number of votes cast, abstentions included
         pub total_votes: u64,
         /// Whether proposal has been executed
         pub executed: bool,
         /// Execution block (if executed)
         pub executed_at: Option<BlockNumberFor<T>>,
+        /// Deposit reserved from the proposer at creation
+        pub deposit: BalanceOf<T>,
+        /// Parameter change applied on execution (`None` for a plain proposal)
+        pub parameter_change: Option<ParameterChange>,
+        /// Votes that must be cast for the result to count
+        pub quorum: u64,
+        /// Blake2-256 of the encoded call dispatched on execution (`None` without a call)
+        pub call_hash: Option<[u8; 32]>,
+        /// Category the proposal is filed under, fixed at creation
+        pub category: ProposalCategory,
+        /// Number of times the title and description were amended, 0 as created
+        pub revision: u32,
     }
 
     impl<T: Config> Proposal<T> {
@@ -112,28 +458,121 @@ pub mod pallet {
             current_block >= self.voting_end
         }
 
+        /// Check if enough votes were cast for the result to count
+        pub fn is_quorum_reached(&self) -> bool {
+            self.total_votes >= self.quorum
+        }
+
         /// Calculate if proposal is approved
-        /// Simple majority: votes_for > votes_against
+        /// Simple majority: votes_for > votes_against, once quorum is reached. Abstentions
+        /// only count towards quorum.
         pub fn is_approved(&self) -> bool {
-            self.votes_for > self.votes_against && self.total_votes > 0
+            self.is_quorum_reached() && self.votes_for > self.votes_against && self.total_votes > 0
         }
 
-        /// Get approval percentage
+        /// Get approval percentage: the share of ayes among ayes and nays
         pub fn approval_percentage(&self) -> u32 {
-            if self.total_votes == 0 {
+            let decisive = self.votes_for as u128 + self.votes_against as u128;
+            if decisive == 0 {
                 return 0;
             }
-            ((self.votes_for as u128 * 100) / self.total_votes as u128) as u32
+            ((self.votes_for as u128 * 100) / decisive) as u32
+        }
+
+        /// Add a vote of `weight` to the tally
+        pub fn count_vote(&mut self, vote: VoteKind, weight: u64) {
+            match vote {
+                VoteKind::Aye => self.votes_for = self.votes_for.saturating_add(weight),
+                VoteKind::Nay => self.votes_against = self.votes_against.saturating_add(weight),
+                VoteKind::Abstain => self.abstentions = self.abstentions.saturating_add(weight),
+            }
+            self.total_votes = self.total_votes.saturating_add(weight);
         }
+
+        /// Take `weight` of a counted vote back out of the tally
+        pub fn uncount_vote(&mut self, vote: VoteKind, weight: u64) {
+            match vote {
+                VoteKind::Aye => self.votes_for = self.votes_for.saturating_sub(weight),
+                VoteKind::Nay => self.votes_against = self.votes_against.saturating_sub(weight),
+                VoteKind::Abstain => self.abstentions = self.abstentions.saturating_sub(weight),
+            }
+            self.total_votes = self.total_votes.saturating_sub(weight);
+        }
+    }
+
+    /// Proof that an account voted, kept after the vote itself is pruned
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    #[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+    pub struct VoteReceipt<BlockNumber> {
+        /// Proposal voted on
+        pub proposal_id: u64,
+        /// Weight the vote was counted with
+        pub weight: u64,
+        /// Block the vote was cast in
+        pub block: BlockNumber,
+        /// Hash committing to the choice (see `Pallet::hash_choice`)
+        #[cfg_attr(feature = "serde", serde(with = "tidygen_primitives::hex"))]
+        pub choice_hash: [u8; 32],
+    }
+
+    /// Archived view of a non-terminal proposal
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    #[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+    pub struct ProposalSnapshot<BlockNumber> {
+        /// Proposal ID
+        pub id: u64,
+        /// Current status
+        pub status: ProposalStatus,
+        /// Number of votes in favor
+        pub votes_for: u64,
+        /// Number of votes against
+        pub votes_against: u64,
+        /// Number of abstentions
+        pub abstentions: u64,
+        /// Total number of votes cast, abstentions included
+        pub total_votes: u64,
+        /// Share of ayes among ayes and nays, in percent
+        pub approval_percentage: u32,
+        /// Whether quorum and the simple majority threshold are currently met
+        pub threshold_met: bool,
+        /// Voting start block
+        pub voting_start: BlockNumber,
+        /// Voting deadline
+        pub voting_end: BlockNumber,
+        /// Parameter change applied on execution, if any
+        pub parameter_change: Option<ParameterChange>,
+        /// Blake2-256 of the call dispatched on execution, if any
+        #[cfg_attr(feature = "serde", serde(with = "tidygen_primitives::hex::option"))]
+        pub call_hash: Option<[u8; 32]>,
+        /// Category the proposal is filed under
+        pub category: ProposalCategory,
+    }
+
+    /// One page of a governance snapshot export
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    #[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+    pub struct SnapshotPage<BlockNumber> {
+        /// Proposals on this page, in ascending ID order
+        pub entries: Vec<ProposalSnapshot<BlockNumber>>,
+        /// Cursor for the next page, `None` on the last page
+        pub next_cursor: Option<PageCursor>,
+        /// Digest over the full export, only set on the last page
+        #[cfg_attr(feature = "serde", serde(with = "tidygen_primitives::hex::option"))]
+        pub digest: Option<[u8; 32]>,
     }
 
     #[pallet::config]
-    pub trait Config: frame_system::Config {
+    pub trait Config: frame_system::Config + SendTransactionTypes<Call<Self>> {
         /// The overarching event type.
         type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
 
-        /// Currency type for bonds and deposits
-        type Currency: Currency<Self::AccountId> + ReservableCurrency<Self::AccountId>;
+        /// Currency type for bonds and deposits, and for the locks of conviction votes
+        type Currency: Currency<Self::AccountId>
+            + ReservableCurrency<Self::AccountId>
+            + LockableCurrency<Self::AccountId, Moment = BlockNumberFor<Self>>;
 
         /// Maximum length of proposal title
         #[pallet::constant]
@@ -154,6 +593,111 @@ pub mod pallet {
         /// Proposal deposit amount
         #[pallet::constant]
         type ProposalDeposit: Get<BalanceOf<Self>>;
+
+        /// Maximum number of proposals whose voting ends in the same block
+        #[pallet::constant]
+        type MaxProposalsPerBlock: Get<u32>;
+
+        /// Minimum number of votes a proposal needs for its result to count
+        #[pallet::constant]
+        type MinQuorum: Get<u64>;
+
+        /// Whether the offchain worker closes expired proposals
+        #[pallet::constant]
+        type OffchainAutoClose: Get<bool>;
+
+        /// Priority of unsigned auto-close transactions
+        #[pallet::constant]
+        type UnsignedPriority: Get<TransactionPriority>;
+
+        /// Maximum number of vote receipts kept per account
+        #[pallet::constant]
+        type MaxReceiptsPerAccount: Get<u32>;
+
+        /// Origin that may issue poll vouchers for any proposal
+        type AdminOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+        /// Maximum number of vouchers issued for one proposal
+        #[pallet::constant]
+        type MaxVouchersPerProposal: Get<u32>;
+
+        /// Applies the parameter changes of executed proposals
+        type Parameters: ParameterHandler;
+
+        /// Maximum number of delegations in a chain of delegating accounts
+        #[pallet::constant]
+        type MaxDelegationDepth: Get<u32>;
+
+        /// Maximum number of accounts delegating directly to one account
+        #[pallet::constant]
+        type MaxDelegators: Get<u32>;
+
+        /// Notified of every proposal status transition
+        type OnTransition: OnStatusTransition<Self::AccountId>;
+
+        /// Whether rejected proposals with little approval lose their deposit
+        #[pallet::constant]
+        type SlashRejectedDeposits: Get<bool>;
+
+        /// Approval percentage below which a rejected proposal's deposit is slashed
+        #[pallet::constant]
+        type MinRefundApproval: Get<u32>;
+
+        /// Receives slashed proposal deposits, e.g. the treasury
+        type Slashed: OnUnbalanced<NegativeImbalanceOf<Self>>;
+
+        /// Runtime call an executed proposal dispatches
+        type RuntimeCall: Parameter
+            + Dispatchable<RuntimeOrigin = Self::RuntimeOrigin, PostInfo = PostDispatchInfo>
+            + GetDispatchInfo;
+
+        /// Origin the calls of executed proposals are dispatched with, e.g. `PotOrigin`
+        type ProposalOrigin: Get<Self::RuntimeOrigin>;
+
+        /// Identifier the DAO's own account is derived from
+        #[pallet::constant]
+        type PalletId: Get<PalletId>;
+
+        /// Maximum size of the encoded call of a proposal
+        #[pallet::constant]
+        type MaxCallSize: Get<u32>;
+
+        /// Maximum weight of the call of a proposal, charged up front by `execute_proposal`
+        #[pallet::constant]
+        type MaxCallWeight: Get<Weight>;
+
+        /// Weight budget for closing ended proposals in `on_initialize`
+        #[pallet::constant]
+        type AutoCloseWeight: Get<Weight>;
+
+        /// Origin that may add and remove members
+        type MembershipOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+        /// Whether only members may vote
+        #[pallet::constant]
+        type MembersOnly: Get<bool>;
+
+        /// Share of the members whose votes a proposal needs, rounded up, when only members
+        /// may vote
+        #[pallet::constant]
+        type MemberQuorum: Get<Perbill>;
+
+        /// Least balance a conviction vote locks
+        #[pallet::constant]
+        type VoteUnit: Get<BalanceOf<Self>>;
+
+        /// Weights of the calls
+        type WeightInfo: WeightInfo;
+    }
+
+    /// Dispatches the calls of executed proposals as signed by the DAO's own account, see
+    /// `Pallet::account_id`
+    pub struct PotOrigin<T>(PhantomData<T>);
+
+    impl<T: Config> Get<OriginFor<T>> for PotOrigin<T> {
+        fn get() -> OriginFor<T> {
+            frame_system::RawOrigin::Signed(Pallet::<T>::account_id()).into()
+        }
     }
 
     /// Storage for proposals mapped by ProposalId
@@ -161,8 +705,8 @@ pub mod pallet {
     #[pallet::getter(fn proposals)]
     pub type Proposals<T: Config> = StorageMap<_, Blake2_128Concat, u64, Proposal<T>, OptionQuery>;
 
-    /// Storage for votes: double map (ProposalId, AccountId) => bool
-    /// bool = true means vote in favor, false means vote against
+    /// Storage for votes: double map (ProposalId, AccountId) => VoteKind
+    /// Entries stored as `bool` before abstentions decode as `Aye` and `Nay`
     #[pallet::storage]
     #[pallet::getter(fn votes)]
     pub type Votes<T: Config> = StorageDoubleMap<
@@ -171,10 +715,21 @@ pub mod pallet {
         u64, // ProposalId
         Blake2_128Concat,
         T::AccountId, // Voter
-        bool,         // in_favor
+        VoteKind,     // vote
         OptionQuery,
     >;
 
+    /// Active proposals by voting end block: block => proposal IDs
+    #[pallet::storage]
+    #[pallet::getter(fn proposals_ending_at)]
+    pub type ProposalsEndingAt<T: Config> = StorageMap<
+        _,
+        Twox64Concat,
+        BlockNumberFor<T>,
+        BoundedVec<u64, T::MaxProposalsPerBlock>,
+        ValueQuery,
+    >;
+
     /// Proposal counter for unique IDs
     #[pallet::storage]
     #[pallet::getter(fn proposal_count)]
@@ -193,20 +748,227 @@ pub mod pallet {
         ValueQuery,
     >;
 
+    /// Vote receipts: (voter, receipt ID) => receipt
+    #[pallet::storage]
+    #[pallet::getter(fn receipts)]
+    pub type Receipts<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        T::AccountId, // Voter
+        Twox64Concat,
+        u64, // Receipt ID
+        VoteReceipt<BlockNumberFor<T>>,
+        OptionQuery,
+    >;
+
+    /// Receipt IDs held by a voter: (oldest kept, next to mint)
+    #[pallet::storage]
+    #[pallet::getter(fn receipt_range)]
+    pub type ReceiptRange<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, (u64, u64), ValueQuery>;
+
+    /// Poll vouchers: (proposal ID, Blake2-256 of the preimage) => spent
+    #[pallet::storage]
+    #[pallet::getter(fn poll_vouchers)]
+    pub type PollVouchers<T: Config> = StorageDoubleMap<
+        _,
+        Twox64Concat,
+        u64, // ProposalId
+        Identity,
+        [u8; 32], // Voucher hash
+        bool,     // spent
+        OptionQuery,
+    >;
+
+    /// Number of vouchers issued per proposal
+    #[pallet::storage]
+    #[pallet::getter(fn poll_voucher_count)]
+    pub type PollVoucherCount<T: Config> = StorageMap<_, Twox64Concat, u64, u32, ValueQuery>;
+
+    /// Whether text fields are line-ending normalized; they are UTF-8 validated either way.
+    /// Defaults to off so chains created before this flag keep their behaviour.
+    #[pallet::storage]
+    #[pallet::getter(fn normalize_text)]
+    pub type NormalizeText<T> = StorageValue<_, bool, ValueQuery>;
+
+    /// Vote delegations: delegator => delegate
+    #[pallet::storage]
+    #[pallet::getter(fn delegations)]
+    pub type Delegations<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, T::AccountId, OptionQuery>;
+
+    /// Accounts delegating directly to an account: delegate => delegators
+    #[pallet::storage]
+    #[pallet::getter(fn delegators)]
+    pub type Delegators<T: Config> = StorageMap<
+        _,
+        Blake2_128Concat,
+        T::AccountId,
+        BoundedVec<T::AccountId, T::MaxDelegators>,
+        ValueQuery,
+    >;
+
+    /// Delegators whose weight a delegate's vote carries: (ProposalId, delegator) =>
+    /// (delegate, vote), so a later direct vote of the delegator can take it back
+    #[pallet::storage]
+    #[pallet::getter(fn delegated_votes)]
+    pub type DelegatedVotes<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        u64, // ProposalId
+        Blake2_128Concat,
+        T::AccountId,             // Delegator
+        (T::AccountId, VoteKind), // Delegate and its vote
+        OptionQuery,
+    >;
+
+    /// Encoded calls of proposals not yet executed: ProposalId => call hashing to the
+    /// proposal's `call_hash`
+    #[pallet::storage]
+    #[pallet::getter(fn proposal_calls)]
+    pub type ProposalCalls<T: Config> =
+        StorageMap<_, Twox64Concat, u64, EncodedCall<T>, OptionQuery>;
+
+    /// Next block whose ended proposals `on_initialize` closes
+    #[pallet::storage]
+    #[pallet::getter(fn auto_close_cursor)]
+    pub type AutoCloseCursor<T: Config> = StorageValue<_, BlockNumberFor<T>, OptionQuery>;
+
+    /// Proposals of each category in creation order: (category, position) => ProposalId
+    #[pallet::storage]
+    #[pallet::getter(fn proposals_by_category)]
+    pub type ProposalsByCategory<T: Config> = StorageDoubleMap<
+        _,
+        Twox64Concat,
+        ProposalCategory,
+        Twox64Concat,
+        u32, // Position within the category
+        u64, // ProposalId
+        OptionQuery,
+    >;
+
+    /// Number of proposals filed under each category
+    #[pallet::storage]
+    #[pallet::getter(fn category_count)]
+    pub type CategoryCount<T: Config> =
+        StorageMap<_, Twox64Concat, ProposalCategory, u32, ValueQuery>;
+
+    /// Accounts that may vote when `MembersOnly` is set
+    #[pallet::storage]
+    pub type Members<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, (), OptionQuery>;
+
+    /// Number of entries in `Members`
+    #[pallet::storage]
+    #[pallet::getter(fn member_count)]
+    pub type MemberCount<T> = StorageValue<_, u32, ValueQuery>;
+
+    /// Conviction of votes cast with `vote_with_conviction`: (ProposalId, voter) =>
+    /// conviction, the multiplier of the voter's own weight. Other votes have none.
+    #[pallet::storage]
+    #[pallet::getter(fn vote_conviction)]
+    pub type VoteConvictions<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        u64, // ProposalId
+        Blake2_128Concat,
+        T::AccountId, // Voter
+        u8,           // Conviction
+        OptionQuery,
+    >;
+
+    /// Balance locked by conviction votes: account => (amount, block the lock can be removed)
+    #[pallet::storage]
+    #[pallet::getter(fn vote_lock)]
+    pub type VoteLocks<T: Config> = StorageMap<
+        _,
+        Blake2_128Concat,
+        T::AccountId,
+        (BalanceOf<T>, BlockNumberFor<T>),
+        OptionQuery,
+    >;
+
+    /// Own weight of votes cast with `vote_with_conviction`: (ProposalId, voter) => the
+    /// weight counted for the vote, its conviction. Other votes weigh 1.
+    #[pallet::storage]
+    #[pallet::getter(fn vote_weight)]
+    pub type VoteWeights<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        u64, // ProposalId
+        Blake2_128Concat,
+        T::AccountId, // Voter
+        u64,          // Own weight
+        OptionQuery,
+    >;
+
+    /// Delegators a delegate's vote carries: (ProposalId, delegate) => delegators, the
+    /// reverse of `DelegatedVotes`
+    #[pallet::storage]
+    #[pallet::getter(fn carried_delegators)]
+    pub type CarriedDelegators<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        u64, // ProposalId
+        Blake2_128Concat,
+        T::AccountId, // Delegate
+        BoundedVec<T::AccountId, T::MaxDelegators>,
+        ValueQuery,
+    >;
+
+    /// Direct delegators of an account counted by their delegation height: delegate =>
+    /// count per height. Its length is the account's own height, the delegations in the
+    /// longest chain ending at it.
+    #[pallet::storage]
+    #[pallet::getter(fn delegator_heights)]
+    pub type DelegatorHeights<T: Config> = StorageMap<
+        _,
+        Blake2_128Concat,
+        T::AccountId,
+        BoundedVec<u32, T::MaxDelegationDepth>,
+        ValueQuery,
+    >;
+
+    #[pallet::genesis_config]
+    pub struct GenesisConfig<T: Config> {
+        /// Enable text normalization from genesis
+        pub normalize_text: bool,
+        #[serde(skip)]
+        pub _config: sp_std::marker::PhantomData<T>,
+    }
+
+    impl<T: Config> Default for GenesisConfig<T> {
+        fn default() -> Self {
+            // New chains normalize text from the start
+            Self {
+                normalize_text: true,
+                _config: Default::default(),
+            }
+        }
+    }
+
+    #[pallet::genesis_build]
+    impl<T: Config> BuildGenesisConfig for GenesisConfig<T> {
+        fn build(&self) {
+            NormalizeText::<T>::put(self.normalize_text);
+        }
+    }
+
     #[pallet::event]
     #[pallet::generate_deposit(pub(super) fn deposit_event)]
     pub enum Event<T: Config> {
-        /// Proposal created [proposal_id, proposer, title]
+        /// Proposal created [proposal_id, proposer, title, deposit]
         ProposalCreated {
             proposal_id: u64,
             proposer: T::AccountId,
             title: Vec<u8>,
+            deposit: BalanceOf<T>,
         },
-        /// Vote cast [proposal_id, voter, in_favor]
+        /// Vote cast [proposal_id, voter, vote, weight]
         VoteCast {
             proposal_id: u64,
             voter: T::AccountId,
-            in_favor: bool,
+            vote: VoteKind,
+            weight: u64,
         },
         /// Proposal executed [proposal_id, executor]
         ProposalExecuted {
@@ -224,26 +986,112 @@ pub mod pallet {
             proposal_id: u64,
             final_status: ProposalStatus,
         },
-        /// Voting period ended [proposal_id, approved]
+        /// Voting period ended [proposal_id, approved, quorum_reached]
         VotingEnded {
             proposal_id: u64,
             approved: bool,
+            quorum_reached: bool,
         },
-    }
-
-    #[pallet::error]
-    pub enum Error<T> {
-        /// Proposal not found
-        ProposalNotFound,
-        /// Proposal is not active
-        ProposalNotActive,
-        /// Already voted on this proposal
-        AlreadyVoted,
-        /// Voting period has not ended
-        VotingPeriodNotEnded,
-        /// Voting period has ended
-        VotingPeriodEnded,
-        /// Proposal not approved
+        /// Text normalization toggled [enabled]
+        TextNormalizationSet {
+            enabled: bool,
+        },
+        /// Poll vouchers issued [proposal_id, count]
+        PollVouchersIssued {
+            proposal_id: u64,
+            count: u32,
+        },
+        /// Anonymous vote cast with a voucher [proposal_id, vote]
+        VoucherVoteCast {
+            proposal_id: u64,
+            vote: VoteKind,
+        },
+        /// Parameter change of an executed proposal applied [proposal_id, change]
+        ParameterChanged {
+            proposal_id: u64,
+            change: ParameterChange,
+        },
+        /// Parameter change of an executed proposal not applied, as no handler governs the
+        /// parameter any more [proposal_id, change]
+        ParameterNoLongerExists {
+            proposal_id: u64,
+            change: ParameterChange,
+        },
+        /// Vote changed [proposal_id, voter, old, new]
+        VoteChanged {
+            proposal_id: u64,
+            voter: T::AccountId,
+            old: VoteKind,
+            new: VoteKind,
+        },
+        /// Deposit of a rejected proposal slashed [proposal_id, proposer, amount]
+        DepositSlashed {
+            proposal_id: u64,
+            proposer: T::AccountId,
+            amount: BalanceOf<T>,
+        },
+        /// Vote withdrawn [proposal_id, voter, vote, weight]
+        VoteRetracted {
+            proposal_id: u64,
+            voter: T::AccountId,
+            vote: VoteKind,
+            weight: u64,
+        },
+        /// Voting power delegated [delegator, delegate]
+        Delegated {
+            delegator: T::AccountId,
+            delegate: T::AccountId,
+        },
+        /// Delegation withdrawn [delegator, delegate]
+        Undelegated {
+            delegator: T::AccountId,
+            delegate: T::AccountId,
+        },
+        /// The call of an executed proposal failed; its changes were reverted, the
+        /// proposal's status is `ExecutionFailed` [proposal_id, error]
+        ProposalExecutionFailed {
+            proposal_id: u64,
+            error: DispatchError,
+        },
+        /// Account added to the members [who]
+        MemberAdded {
+            who: T::AccountId,
+        },
+        /// Account removed from the members [who]
+        MemberRemoved {
+            who: T::AccountId,
+        },
+        /// Balance locked for a conviction vote [who, amount, until]
+        VoteLocked {
+            who: T::AccountId,
+            amount: BalanceOf<T>,
+            until: BlockNumberFor<T>,
+        },
+        /// Balance lock of conviction votes removed [who, amount]
+        Unlocked {
+            who: T::AccountId,
+            amount: BalanceOf<T>,
+        },
+        /// Title and description of a proposal replaced [proposal_id, revision]
+        ProposalAmended {
+            proposal_id: u64,
+            revision: u32,
+        },
+    }
+
+    #[pallet::error]
+    pub enum Error<T> {
+        /// Proposal not found
+        ProposalNotFound,
+        /// Proposal is not active
+        ProposalNotActive,
+        /// Already voted on this proposal
+        AlreadyVoted,
+        /// Voting period has not ended
+        VotingPeriodNotEnded,
+        /// Voting period has ended
+        VotingPeriodEnded,
+        /// Proposal not approved
         ProposalNotApproved,
         /// Proposal already executed
         AlreadyExecuted,
@@ -255,47 +1103,1166 @@ pub mod pallet {
         InvalidVotingPeriod,
         /// Insufficient funds for proposal deposit
         InsufficientDeposit,
+        /// Text field is not valid UTF-8
+        InvalidUtf8,
+        /// Status change not allowed by the proposal lifecycle
+        InvalidStatusTransition,
+        /// Too many proposals end voting in the same block
+        TooManyProposalsEnding,
+        /// A voucher is issued twice for the proposal
+        DuplicateVoucher,
+        /// The proposal would exceed `MaxVouchersPerProposal` vouchers
+        TooManyVouchers,
+        /// The preimage does not hash to a voucher of the proposal
+        InvalidVoucher,
+        /// The voucher was already spent
+        VoucherSpent,
+        /// The requested quorum is below `MinQuorum`
+        QuorumBelowMinimum,
+        /// The delegate is the caller or delegates to it
+        DelegationCycle,
+        /// The delegation chain would exceed `MaxDelegationDepth`
+        DelegationTooDeep,
+        /// The delegate already has `MaxDelegators` delegators
+        TooManyDelegators,
+        /// The caller does not delegate
+        NotDelegating,
+        /// The caller has not voted on the proposal
+        NotVoted,
+        /// The new vote is the vote already cast
+        SameVote,
+        /// The encoded call exceeds `MaxCallSize`
+        CallTooLarge,
+        /// The call may weigh more than `MaxCallWeight`
+        CallTooHeavy,
+        /// The stored call no longer decodes, e.g. after a runtime upgrade
+        UndecodableCall,
+        /// Only members may vote, and the caller is not one
+        NotAMember,
+        /// The account is already a member
+        AlreadyMember,
+        /// The conviction is not between 1 and `MAX_CONVICTION`
+        InvalidConviction,
+        /// The caller has no balance locked by conviction votes
+        NotLocked,
+        /// The lock of the caller's conviction votes has not expired
+        LockNotExpired,
+        /// The proposal can no longer be amended, it already has votes
+        VotesAlreadyCast,
+        /// The balance of a conviction vote exceeds the caller's free balance
+        InsufficientBalance,
+        /// The balance of a conviction vote is less than one `VoteUnit`
+        BalanceBelowVoteUnit,
+    }
+
+    #[pallet::hooks]
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+        fn on_initialize(now: BlockNumberFor<T>) -> Weight {
+            Self::process_ended_proposals(now, T::AutoCloseWeight::get())
+        }
+
+        fn on_runtime_upgrade() -> Weight {
+            crate::migrations::v1::migrate::<T>()
+                .saturating_add(crate::migrations::v2::migrate::<T>())
+                .saturating_add(crate::migrations::v3::migrate::<T>())
+                .saturating_add(crate::migrations::v4::migrate::<T>())
+                .saturating_add(crate::migrations::v5::migrate::<T>())
+                .saturating_add(crate::migrations::v6::migrate::<T>())
+                .saturating_add(crate::migrations::v7::migrate::<T>())
+                .saturating_add(crate::migrations::v8::migrate::<T>())
+                .saturating_add(crate::migrations::v9::migrate::<T>())
+                .saturating_add(crate::migrations::v10::migrate::<T>())
+                .saturating_add(crate::migrations::v11::migrate::<T>())
+        }
+
+        fn offchain_worker(now: BlockNumberFor<T>) {
+            if !T::OffchainAutoClose::get() {
+                return;
+            }
+
+            for proposal_id in Self::expired_proposals(now) {
+                let call = Call::close_proposal_unsigned { proposal_id };
+                // Rejected submissions (e.g. already in the pool) are retried next block
+                let _ = SubmitTransaction::<T, Call<T>>::submit_unsigned_transaction(call.into());
+            }
+        }
+    }
+
+    #[pallet::validate_unsigned]
+    impl<T: Config> ValidateUnsigned for Pallet<T> {
+        type Call = Call<T>;
+
+        fn validate_unsigned(_source: TransactionSource, call: &Self::Call) -> TransactionValidity {
+            let proposal_id = match call {
+                Call::close_proposal_unsigned { proposal_id } => proposal_id,
+                Call::vote_with_voucher { proposal_id, voucher_preimage, .. } => {
+                    return Self::validate_voucher_vote(*proposal_id, voucher_preimage);
+                },
+                _ => return InvalidTransaction::Call.into(),
+            };
+            if !T::OffchainAutoClose::get() {
+                return InvalidTransaction::Call.into();
+            }
+
+            let proposal = Proposals::<T>::get(proposal_id).ok_or(InvalidTransaction::Stale)?;
+            if !proposal.is_active() {
+                return InvalidTransaction::Stale.into();
+            }
+            if !proposal.is_voting_ended(frame_system::Pallet::<T>::block_number()) {
+                return InvalidTransaction::Future.into();
+            }
+
+            ValidTransaction::with_tag_prefix("DaoAutoClose")
+                .priority(T::UnsignedPriority::get())
+                .and_provides(proposal_id)
+                .longevity(AUTO_CLOSE_LONGEVITY)
+                .propagate(true)
+                .build()
+        }
     }
 
-    #[pallet::call]
-    impl<T: Config> Pallet<T> {
-        /// Create a new governance proposal
+    #[pallet::call]
+    impl<T: Config> Pallet<T> {
+        /// Create a new governance proposal
+        ///
+        /// # Arguments
+        /// * `origin` - Transaction origin (proposer)
+        /// * `title` - Proposal title
+        /// * `description` - Proposal description
+        /// * `voting_period` - Voting period in blocks (optional, uses minimum if None)
+        ///
+        /// # Returns
+        /// * `DispatchResult` - Success or error
+        ///
+        /// # Events
+        /// * `ProposalCreated` - Emitted when proposal is created
+        ///
+        /// # Errors
+        /// * `TitleTooLong` - Title exceeds maximum length
+        /// * `DescriptionTooLong` - Description exceeds maximum length
+        /// * `InvalidVotingPeriod` - Voting period outside allowed range
+        /// * `InvalidUtf8` - Title or description is not valid UTF-8
+        /// * `TooManyProposalsEnding` - `MaxProposalsPerBlock` proposals already end in that block
+        #[pallet::call_index(0)]
+        #[pallet::weight(T::WeightInfo::create_proposal(
+            title.len() as u32,
+            description.len() as u32,
+        ))]
+        pub fn create_proposal(
+            origin: OriginFor<T>,
+            title: Vec<u8>,
+            description: Vec<u8>,
+            voting_period: Option<BlockNumberFor<T>>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            Self::do_create_proposal(
+                who,
+                title,
+                description,
+                voting_period,
+                None,
+                None,
+                T::MinQuorum::get(),
+                ProposalCategory::General,
+            )
+        }
+
+        /// Create a proposal that changes a runtime parameter once executed
+        ///
+        /// Same as `create_proposal`, with `change` stored on the proposal and applied by
+        /// `execute_proposal`.
+        ///
+        /// # Arguments
+        /// * `origin` - Transaction origin (proposer)
+        /// * `title` - Proposal title
+        /// * `description` - Proposal description
+        /// * `voting_period` - Voting period in blocks (optional, uses minimum if None)
+        /// * `change` - Parameter to change and its new value
+        ///
+        /// # Returns
+        /// * `DispatchResult` - Success or error
+        ///
+        /// # Events
+        /// * `ProposalCreated` - Emitted when proposal is created
+        ///
+        /// # Errors
+        /// * Any error of `create_proposal`
+        #[pallet::call_index(10)]
+        #[pallet::weight(T::WeightInfo::create_parameter_proposal(
+            title.len() as u32,
+            description.len() as u32,
+        ))]
+        pub fn create_parameter_proposal(
+            origin: OriginFor<T>,
+            title: Vec<u8>,
+            description: Vec<u8>,
+            voting_period: Option<BlockNumberFor<T>>,
+            change: ParameterChange,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            Self::do_create_proposal(
+                who,
+                title,
+                description,
+                voting_period,
+                Some(change),
+                None,
+                T::MinQuorum::get(),
+                ProposalCategory::General,
+            )
+        }
+
+        /// Create a proposal that needs at least `quorum` votes to pass
+        ///
+        /// Same as `create_proposal`, for decisions that need broader participation than
+        /// `MinQuorum`.
+        ///
+        /// # Arguments
+        /// * `origin` - Transaction origin (proposer)
+        /// * `title` - Proposal title
+        /// * `description` - Proposal description
+        /// * `voting_period` - Voting period in blocks (optional, uses minimum if None)
+        /// * `quorum` - Votes that must be cast for the result to count
+        ///
+        /// # Returns
+        /// * `DispatchResult` - Success or error
+        ///
+        /// # Events
+        /// * `ProposalCreated` - Emitted when proposal is created
+        ///
+        /// # Errors
+        /// * `QuorumBelowMinimum` - `quorum` is below `MinQuorum`
+        /// * Any error of `create_proposal`
+        #[pallet::call_index(11)]
+        #[pallet::weight(T::WeightInfo::create_proposal_with_quorum(
+            title.len() as u32,
+            description.len() as u32,
+        ))]
+        pub fn create_proposal_with_quorum(
+            origin: OriginFor<T>,
+            title: Vec<u8>,
+            description: Vec<u8>,
+            voting_period: Option<BlockNumberFor<T>>,
+            quorum: u64,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(quorum >= T::MinQuorum::get(), Error::<T>::QuorumBelowMinimum);
+
+            Self::do_create_proposal(
+                who,
+                title,
+                description,
+                voting_period,
+                None,
+                None,
+                quorum,
+                ProposalCategory::General,
+            )
+        }
+
+        /// Vote on a proposal
+        ///
+        /// # Arguments
+        /// * `origin` - Transaction origin (voter)
+        /// * `proposal_id` - ID of the proposal to vote on
+        /// * `vote` - `Aye`, `Nay` or `Abstain` (`true` and `false` encode as `Aye` and `Nay`)
+        ///
+        /// # Returns
+        /// * `DispatchResult` - Success or error
+        ///
+        /// # Events
+        /// * `VoteCast` - Emitted when vote is successfully cast
+        ///
+        /// # Errors
+        /// * `ProposalNotFound` - Proposal doesn't exist
+        /// * `ProposalNotActive` - Proposal is not active
+        /// * `AlreadyVoted` - Account has already voted
+        /// * `VotingPeriodEnded` - Voting period has ended
+        /// * `NotAMember` - Only members may vote, and the caller is not one
+        #[pallet::call_index(1)]
+        #[pallet::weight(T::WeightInfo::vote(T::MaxDelegators::get()))]
+        pub fn vote(
+            origin: OriginFor<T>,
+            proposal_id: u64,
+            vote: VoteKind,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            Self::do_vote(who, proposal_id, vote, &[], 1)
+        }
+
+        /// Vote on a proposal, hiding the choice in the vote receipt behind `salt`
+        ///
+        /// Same as `vote`, except that the receipt commits to the choice with
+        /// `hash_choice(vote, salt)`. Keep the salt to reveal the choice later.
+        ///
+        /// # Arguments
+        /// * `origin` - Transaction origin (voter)
+        /// * `proposal_id` - ID of the proposal to vote on
+        /// * `vote` - `Aye`, `Nay` or `Abstain`
+        /// * `salt` - Secret mixed into the receipt's choice hash
+        ///
+        /// # Returns
+        /// * `DispatchResult` - Success or error
+        ///
+        /// # Events
+        /// * `VoteCast` - Emitted when vote is successfully cast
+        ///
+        /// # Errors
+        /// * Any error of `vote`
+        #[pallet::call_index(7)]
+        #[pallet::weight(T::WeightInfo::vote_with_salt(T::MaxDelegators::get()))]
+        pub fn vote_with_salt(
+            origin: OriginFor<T>,
+            proposal_id: u64,
+            vote: VoteKind,
+            salt: [u8; 32],
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            Self::do_vote(who, proposal_id, vote, &salt, 1)
+        }
+
+        /// Execute an approved proposal
+        ///
+        /// # Arguments
+        /// * `origin` - Transaction origin (executor)
+        /// * `proposal_id` - ID of the proposal to execute
+        ///
+        /// # Returns
+        /// * `DispatchResultWithPostInfo` - Success with the weight used, or error
+        ///
+        /// # Events
+        /// * `ProposalExecuted` - Emitted when proposal is executed
+        /// * `ProposalExecutionFailed` - Emitted instead when the proposal's call failed
+        /// * `ProposalStatusChanged` - Emitted when status changes
+        /// * `ParameterChanged` - Emitted when the proposal's parameter change was applied
+        /// * `ParameterNoLongerExists` - Emitted instead when no handler governs the parameter
+        ///
+        /// # Errors
+        /// * `ProposalNotFound` - Proposal doesn't exist
+        /// * `VotingPeriodNotEnded` - Voting still in progress
+        /// * `ProposalNotApproved` - Proposal was not approved or is below quorum
+        /// * `AlreadyExecuted` - Proposal already executed
+        /// * `InvalidStatusTransition` - Proposal was cancelled or otherwise finalized
+        #[pallet::call_index(2)]
+        #[pallet::weight(T::WeightInfo::execute_proposal().saturating_add(T::MaxCallWeight::get()))]
+        pub fn execute_proposal(
+            origin: OriginFor<T>,
+            proposal_id: u64,
+        ) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+
+            // Get proposal
+            let mut proposal =
+                Proposals::<T>::get(proposal_id).ok_or(Error::<T>::ProposalNotFound)?;
+
+            // Check voting period ended
+            let current_block = frame_system::Pallet::<T>::block_number();
+            ensure!(
+                proposal.is_voting_ended(current_block),
+                Error::<T>::VotingPeriodNotEnded
+            );
+
+            // Check proposal is approved
+            ensure!(proposal.is_approved(), Error::<T>::ProposalNotApproved);
+
+            // Check not already executed
+            ensure!(!proposal.executed, Error::<T>::AlreadyExecuted);
+
+            // Update proposal status
+            let old_status = PROPOSAL_STATUS
+                .transition(&mut proposal.status, ProposalStatus::Executed)
+                .map_err(|_| Error::<T>::InvalidStatusTransition)?;
+            proposal.executed = true;
+            proposal.executed_at = Some(current_block);
+            let deposit = proposal.deposit;
+            let proposer = proposal.proposer.clone();
+            let parameter_change = proposal.parameter_change.clone();
+            if old_status == ProposalStatus::Active {
+                Self::unindex_proposal(proposal.voting_end, proposal_id);
+            }
+
+            // Store updated proposal, before its call runs so the call cannot execute it again
+            Proposals::<T>::insert(proposal_id, proposal);
+
+            // Unreserve deposit (return to proposer, whoever executes)
+            T::Currency::unreserve(&proposer, deposit);
+
+            // Dispatch the call; a failure only reverts the call's own changes
+            let (result, call_weight) = Self::dispatch_proposal_call(proposal_id);
+            let new_status = match result {
+                Ok(()) => {
+                    Self::deposit_event(Event::ProposalExecuted {
+                        proposal_id,
+                        executor: who,
+                    });
+                    ProposalStatus::Executed
+                },
+                Err(error) => {
+                    // `Executed` has no exits: the proposal goes from `old_status` straight to
+                    // `ExecutionFailed`, which the table allows
+                    Proposals::<T>::mutate(proposal_id, |proposal| {
+                        if let Some(proposal) = proposal {
+                            proposal.status = ProposalStatus::ExecutionFailed;
+                        }
+                    });
+                    Self::deposit_event(Event::ProposalExecutionFailed { proposal_id, error });
+                    ProposalStatus::ExecutionFailed
+                },
+            };
+
+            Self::note_status_change(proposal_id, old_status, new_status);
+
+            if let Some(change) = parameter_change {
+                let event = match T::Parameters::apply(&change) {
+                    Ok(()) => Event::ParameterChanged { proposal_id, change },
+                    Err(ParameterNoLongerExists) => {
+                        Event::ParameterNoLongerExists { proposal_id, change }
+                    },
+                };
+                Self::deposit_event(event);
+            }
+
+            Ok(Some(T::WeightInfo::execute_proposal().saturating_add(call_weight)).into())
+        }
+
+        /// Close a proposal after voting period
+        ///
+        /// This function finalizes the proposal status based on voting results: a proposal
+        /// below its quorum is rejected whatever the split.
+        /// Can be called by anyone after voting period ends. Proposals are normally closed by
+        /// `on_initialize` already; closing a finalized proposal again does nothing.
+        ///
+        /// # Arguments
+        /// * `origin` - Transaction origin
+        /// * `proposal_id` - ID of the proposal to close
+        ///
+        /// # Returns
+        /// * `DispatchResult` - Success or error
+        ///
+        /// # Events
+        /// * `VotingEnded` - Emitted when voting ends
+        /// * `ProposalClosed` - Emitted when proposal is closed
+        /// * `ProposalStatusChanged` - Emitted when status changes
+        #[pallet::call_index(3)]
+        #[pallet::weight(T::WeightInfo::close_proposal())]
+        pub fn close_proposal(origin: OriginFor<T>, proposal_id: u64) -> DispatchResult {
+            let _who = ensure_signed(origin)?;
+
+            // Already closed, e.g. by `on_initialize`, and possibly executed since
+            let proposal = Proposals::<T>::get(proposal_id).ok_or(Error::<T>::ProposalNotFound)?;
+            if matches!(
+                proposal.status,
+                ProposalStatus::Approved |
+                    ProposalStatus::Rejected |
+                    ProposalStatus::Executed |
+                    ProposalStatus::ExecutionFailed
+            ) {
+                return Ok(());
+            }
+
+            Self::do_close_proposal(proposal_id)
+        }
+
+        /// Close a proposal whose voting period has ended, without a signer
+        ///
+        /// Submitted by the offchain worker when `OffchainAutoClose` is enabled; see
+        /// `ValidateUnsigned` for the pool rules.
+        ///
+        /// # Arguments
+        /// * `origin` - Must be none (unsigned)
+        /// * `proposal_id` - ID of the proposal to close
+        ///
+        /// # Returns
+        /// * `DispatchResult` - Success or error
+        ///
+        /// # Events
+        /// * Same as `close_proposal`
+        ///
+        /// # Errors
+        /// * Same as `close_proposal`
+        #[pallet::call_index(6)]
+        #[pallet::weight(T::WeightInfo::close_proposal_unsigned())]
+        pub fn close_proposal_unsigned(origin: OriginFor<T>, proposal_id: u64) -> DispatchResult {
+            ensure_none(origin)?;
+
+            Self::do_close_proposal(proposal_id)
+        }
+
+        /// Cancel a proposal (only proposer can cancel before voting ends)
+        ///
+        /// # Arguments
+        /// * `origin` - Transaction origin (must be proposer)
+        /// * `proposal_id` - ID of the proposal to cancel
+        #[pallet::call_index(4)]
+        #[pallet::weight(T::WeightInfo::cancel_proposal())]
+        pub fn cancel_proposal(origin: OriginFor<T>, proposal_id: u64) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            // Get proposal
+            let mut proposal =
+                Proposals::<T>::get(proposal_id).ok_or(Error::<T>::ProposalNotFound)?;
+
+            // Only proposer can cancel
+            ensure!(proposal.proposer == who, Error::<T>::ProposalNotActive);
+
+            // Can only cancel active proposals
+            let old_status = PROPOSAL_STATUS
+                .transition(&mut proposal.status, ProposalStatus::Cancelled)
+                .map_err(|_| Error::<T>::ProposalNotActive)?;
+            let deposit = proposal.deposit;
+            Self::unindex_proposal(proposal.voting_end, proposal_id);
+
+            // Store updated proposal, dropping a call it will never dispatch
+            Proposals::<T>::insert(proposal_id, proposal);
+            ProposalCalls::<T>::remove(proposal_id);
+
+            // Unreserve deposit
+            T::Currency::unreserve(&who, deposit);
+
+            // Emit event
+            Self::note_status_change(proposal_id, old_status, ProposalStatus::Cancelled);
+
+            Ok(())
+        }
+
+        /// Enable or disable text normalization
+        ///
+        /// # Arguments
+        /// * `origin` - Must be root
+        /// * `enabled` - Whether the line endings of text fields are normalized
+        ///
+        /// # Events
+        /// * `TextNormalizationSet` - Emitted with the new setting
+        #[pallet::call_index(5)]
+        #[pallet::weight(T::WeightInfo::set_text_normalization())]
+        pub fn set_text_normalization(origin: OriginFor<T>, enabled: bool) -> DispatchResult {
+            ensure_root(origin)?;
+
+            NormalizeText::<T>::put(enabled);
+
+            Self::deposit_event(Event::TextNormalizationSet { enabled });
+
+            Ok(())
+        }
+
+        /// Issue one-time vouchers for anonymous votes on a proposal
+        ///
+        /// # Arguments
+        /// * `origin` - The proposer, or `AdminOrigin`
+        /// * `proposal_id` - ID of the proposal the vouchers vote on
+        /// * `vouchers` - Blake2-256 hashes of the preimages handed out to voters
+        ///
+        /// # Returns
+        /// * `DispatchResult` - Success or error
+        ///
+        /// # Events
+        /// * `PollVouchersIssued` - Emitted with the number of vouchers issued
+        ///
+        /// # Errors
+        /// * `ProposalNotFound` - Proposal doesn't exist
+        /// * `ProposalNotActive` - Proposal is not active
+        /// * `VotingPeriodEnded` - Voting period has ended
+        /// * `DuplicateVoucher` - A voucher is listed twice or was issued before
+        /// * `TooManyVouchers` - The proposal would exceed `MaxVouchersPerProposal` vouchers
+        #[pallet::call_index(8)]
+        #[pallet::weight(T::WeightInfo::issue_poll_vouchers(vouchers.len() as u32))]
+        pub fn issue_poll_vouchers(
+            origin: OriginFor<T>,
+            proposal_id: u64,
+            vouchers: Vec<[u8; 32]>,
+        ) -> DispatchResult {
+            let proposal = Self::ensure_open_for_votes(proposal_id)?;
+            match ensure_signed(origin.clone()) {
+                Ok(who) if who == proposal.proposer => {},
+                _ => {
+                    T::AdminOrigin::ensure_origin(origin)?;
+                },
+            }
+
+            let count = u32::try_from(vouchers.len()).map_err(|_| Error::<T>::TooManyVouchers)?;
+            let total = PollVoucherCount::<T>::get(proposal_id).saturating_add(count);
+            ensure!(total <= T::MaxVouchersPerProposal::get(), Error::<T>::TooManyVouchers);
+
+            for (i, voucher) in vouchers.iter().enumerate() {
+                ensure!(
+                    !vouchers[..i].contains(voucher)
+                        && !PollVouchers::<T>::contains_key(proposal_id, voucher),
+                    Error::<T>::DuplicateVoucher
+                );
+            }
+            for voucher in vouchers {
+                PollVouchers::<T>::insert(proposal_id, voucher, false);
+            }
+            PollVoucherCount::<T>::insert(proposal_id, total);
+
+            Self::deposit_event(Event::PollVouchersIssued { proposal_id, count });
+
+            Ok(())
+        }
+
+        /// Cast an anonymous vote by spending a voucher, without a signer
+        ///
+        /// See `ValidateUnsigned` for the pool rules.
+        ///
+        /// # Arguments
+        /// * `origin` - Must be none (unsigned)
+        /// * `proposal_id` - ID of the proposal to vote on
+        /// * `voucher_preimage` - Preimage of an unspent voucher of the proposal
+        /// * `vote` - `Aye`, `Nay` or `Abstain`
+        ///
+        /// # Returns
+        /// * `DispatchResult` - Success or error
+        ///
+        /// # Events
+        /// * `VoucherVoteCast` - Emitted with the choice, without a voter
+        ///
+        /// # Errors
+        /// * `ProposalNotFound` - Proposal doesn't exist
+        /// * `ProposalNotActive` - Proposal is not active
+        /// * `VotingPeriodEnded` - Voting period has ended
+        /// * `InvalidVoucher` - The preimage does not hash to a voucher of the proposal
+        /// * `VoucherSpent` - The voucher was already spent
+        #[pallet::call_index(9)]
+        #[pallet::weight(T::WeightInfo::vote_with_voucher())]
+        pub fn vote_with_voucher(
+            origin: OriginFor<T>,
+            proposal_id: u64,
+            voucher_preimage: [u8; 32],
+            vote: VoteKind,
+        ) -> DispatchResult {
+            ensure_none(origin)?;
+
+            let mut proposal = Self::ensure_open_for_votes(proposal_id)?;
+            let voucher = Self::ensure_voucher_unspent(proposal_id, &voucher_preimage)?;
+
+            PollVouchers::<T>::insert(proposal_id, voucher, true);
+            proposal.count_vote(vote, 1);
+            Proposals::<T>::insert(proposal_id, proposal);
+
+            Self::deposit_event(Event::VoucherVoteCast { proposal_id, vote });
+
+            Ok(())
+        }
+
+        /// Delegate the caller's voting power to another account
+        ///
+        /// When `to` votes on a proposal, its vote also carries the weight of every account
+        /// delegating to it directly that has not voted on that proposal itself. Replaces an
+        /// existing delegation of the caller.
+        ///
+        /// # Arguments
+        /// * `origin` - Transaction origin (delegator)
+        /// * `to` - Account to delegate to
+        ///
+        /// # Returns
+        /// * `DispatchResult` - Success or error
+        ///
+        /// # Events
+        /// * `Undelegated` - Emitted first when an existing delegation is replaced
+        /// * `Delegated` - Emitted when the delegation is recorded
+        ///
+        /// # Errors
+        /// * `DelegationCycle` - `to` is the caller or delegates to it, directly or not
+        /// * `DelegationTooDeep` - A chain through the delegation would exceed
+        ///   `MaxDelegationDepth`
+        /// * `TooManyDelegators` - `to` already has `MaxDelegators` delegators
+        #[pallet::call_index(12)]
+        #[pallet::weight(T::WeightInfo::delegate(T::MaxDelegationDepth::get()))]
+        pub fn delegate(origin: OriginFor<T>, to: T::AccountId) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            Self::ensure_delegation_allowed(&who, &to)?;
+            if let Some(previous) = Delegations::<T>::take(&who) {
+                Self::remove_delegator(&previous, &who);
+            }
+            Delegators::<T>::try_mutate(&to, |delegators| delegators.try_push(who.clone()))
+                .map_err(|_| Error::<T>::TooManyDelegators)?;
+            Delegations::<T>::insert(&who, &to);
+            Self::move_delegator_height(&to, None, Some(Self::delegation_height(&who)));
+
+            Self::deposit_event(Event::Delegated { delegator: who, delegate: to });
+
+            Ok(())
+        }
+
+        /// Withdraw the caller's delegation
+        ///
+        /// Votes the delegate already cast keep the caller's weight, unless the caller votes
+        /// on the proposal directly.
+        ///
+        /// # Arguments
+        /// * `origin` - Transaction origin (delegator)
+        ///
+        /// # Returns
+        /// * `DispatchResult` - Success or error
+        ///
+        /// # Events
+        /// * `Undelegated` - Emitted when the delegation is removed
+        ///
+        /// # Errors
+        /// * `NotDelegating` - The caller does not delegate
+        #[pallet::call_index(13)]
+        #[pallet::weight(T::WeightInfo::undelegate(T::MaxDelegationDepth::get()))]
+        pub fn undelegate(origin: OriginFor<T>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let delegate = Delegations::<T>::take(&who).ok_or(Error::<T>::NotDelegating)?;
+            Self::remove_delegator(&delegate, &who);
+
+            Ok(())
+        }
+
+        /// Change a vote while the proposal is open for votes
+        ///
+        /// The whole weight of the vote moves, including the delegators it carries. A new
+        /// receipt records the changed vote.
+        ///
+        /// # Arguments
+        /// * `origin` - Transaction origin (voter)
+        /// * `proposal_id` - ID of the proposal voted on
+        /// * `new` - The vote to count instead
+        ///
+        /// # Returns
+        /// * `DispatchResult` - Success or error
+        ///
+        /// # Events
+        /// * `VoteChanged` - Emitted when the vote is changed
+        ///
+        /// # Errors
+        /// * `ProposalNotFound` - Proposal doesn't exist
+        /// * `ProposalNotActive` - Proposal is not active
+        /// * `VotingPeriodEnded` - Voting period has ended
+        /// * `NotVoted` - The caller has not voted on the proposal
+        /// * `SameVote` - `new` is the vote already cast
+        /// * `NotAMember` - Only members may vote, and the caller is not one
+        #[pallet::call_index(14)]
+        #[pallet::weight(T::WeightInfo::change_vote(T::MaxDelegators::get()))]
+        pub fn change_vote(
+            origin: OriginFor<T>,
+            proposal_id: u64,
+            new: VoteKind,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(Self::may_vote(&who), Error::<T>::NotAMember);
+
+            let mut proposal = Self::ensure_open_for_votes(proposal_id)?;
+            let old = Votes::<T>::get(proposal_id, &who).ok_or(Error::<T>::NotVoted)?;
+            ensure!(old != new, Error::<T>::SameVote);
+
+            // Delegators the vote carries follow it, so a later direct vote uncounts `new`
+            let carried = CarriedDelegators::<T>::get(proposal_id, &who);
+            for delegator in &carried {
+                DelegatedVotes::<T>::insert(proposal_id, delegator, (&who, new));
+            }
+            let weight = Self::own_weight(proposal_id, &who).saturating_add(carried.len() as u64);
+
+            proposal.uncount_vote(old, weight);
+            proposal.count_vote(new, weight);
+            Proposals::<T>::insert(proposal_id, proposal);
+            Votes::<T>::insert(proposal_id, &who, new);
+
+            Self::mint_receipt(
+                &who,
+                VoteReceipt {
+                    proposal_id,
+                    weight,
+                    block: frame_system::Pallet::<T>::block_number(),
+                    choice_hash: Self::hash_choice(new, &[]),
+                },
+            );
+
+            Self::deposit_event(Event::VoteChanged { proposal_id, voter: who, old, new });
+
+            Ok(())
+        }
+
+        /// Withdraw a vote while the proposal is open for votes
+        ///
+        /// The whole weight of the vote leaves the tally, and the delegators it carried can
+        /// be carried by a later vote again. The caller may vote again afterwards. Receipts
+        /// already minted are kept.
+        ///
+        /// # Arguments
+        /// * `origin` - Transaction origin (voter)
+        /// * `proposal_id` - ID of the proposal voted on
+        ///
+        /// # Returns
+        /// * `DispatchResult` - Success or error
+        ///
+        /// # Events
+        /// * `VoteRetracted` - Emitted when the vote is withdrawn
+        ///
+        /// # Errors
+        /// * `ProposalNotFound` - Proposal doesn't exist
+        /// * `ProposalNotActive` - Proposal is not active
+        /// * `VotingPeriodEnded` - Voting period has ended
+        /// * `NotVoted` - The caller has not voted on the proposal
+        #[pallet::call_index(15)]
+        #[pallet::weight(T::WeightInfo::retract_vote(T::MaxDelegators::get()))]
+        pub fn retract_vote(origin: OriginFor<T>, proposal_id: u64) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let mut proposal = Self::ensure_open_for_votes(proposal_id)?;
+            let vote = Votes::<T>::take(proposal_id, &who).ok_or(Error::<T>::NotVoted)?;
+            HasVoted::<T>::remove(proposal_id, &who);
+
+            let carried = CarriedDelegators::<T>::take(proposal_id, &who);
+            for delegator in &carried {
+                DelegatedVotes::<T>::remove(proposal_id, delegator);
+            }
+            let weight = Self::own_weight(proposal_id, &who).saturating_add(carried.len() as u64);
+            VoteConvictions::<T>::remove(proposal_id, &who);
+            VoteWeights::<T>::remove(proposal_id, &who);
+
+            proposal.uncount_vote(vote, weight);
+            Proposals::<T>::insert(proposal_id, proposal);
+
+            Self::deposit_event(Event::VoteRetracted {
+                proposal_id,
+                voter: who,
+                vote,
+                weight,
+            });
+
+            Ok(())
+        }
+
+        /// Create a proposal that dispatches a runtime call once executed
+        ///
+        /// Same as `create_proposal`, with `call` kept encoded in `ProposalCalls` and its hash
+        /// stored on the proposal. `execute_proposal` dispatches it with `ProposalOrigin`.
+        ///
+        /// # Arguments
+        /// * `origin` - Transaction origin (proposer)
+        /// * `title` - Proposal title
+        /// * `description` - Proposal description
+        /// * `voting_period` - Voting period in blocks (optional, uses minimum if None)
+        /// * `call` - Call to dispatch on execution
+        ///
+        /// # Returns
+        /// * `DispatchResult` - Success or error
+        ///
+        /// # Events
+        /// * `ProposalCreated` - Emitted when proposal is created
+        ///
+        /// # Errors
+        /// * `CallTooLarge` - The encoded call exceeds `MaxCallSize`
+        /// * `CallTooHeavy` - The call may weigh more than `MaxCallWeight`
+        /// * Any error of `create_proposal`
+        #[pallet::call_index(16)]
+        #[pallet::weight(T::WeightInfo::create_call_proposal(
+            title.len() as u32,
+            description.len() as u32,
+            call.encoded_size() as u32,
+        ))]
+        pub fn create_call_proposal(
+            origin: OriginFor<T>,
+            title: Vec<u8>,
+            description: Vec<u8>,
+            voting_period: Option<BlockNumberFor<T>>,
+            call: Box<<T as Config>::RuntimeCall>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(
+                call.get_dispatch_info().weight.all_lte(T::MaxCallWeight::get()),
+                Error::<T>::CallTooHeavy
+            );
+            let call: EncodedCall<T> =
+                call.encode().try_into().map_err(|_| Error::<T>::CallTooLarge)?;
+
+            Self::do_create_proposal(
+                who,
+                title,
+                description,
+                voting_period,
+                None,
+                Some(call),
+                T::MinQuorum::get(),
+                ProposalCategory::General,
+            )
+        }
+
+        /// Create a proposal filed under a category
+        ///
+        /// Same as `create_proposal`, with the proposal listed under `category` in
+        /// `ProposalsByCategory`. Proposals created any other way are filed under `General`.
+        /// The category cannot be changed afterwards.
+        ///
+        /// # Arguments
+        /// * `origin` - Transaction origin (proposer)
+        /// * `title` - Proposal title
+        /// * `description` - Proposal description
+        /// * `voting_period` - Voting period in blocks (optional, uses minimum if None)
+        /// * `category` - Category to file the proposal under
+        ///
+        /// # Returns
+        /// * `DispatchResult` - Success or error
+        ///
+        /// # Events
+        /// * `ProposalCreated` - Emitted when proposal is created
+        ///
+        /// # Errors
+        /// * Any error of `create_proposal`
+        #[pallet::call_index(17)]
+        #[pallet::weight(T::WeightInfo::create_categorized_proposal(
+            title.len() as u32,
+            description.len() as u32,
+        ))]
+        pub fn create_categorized_proposal(
+            origin: OriginFor<T>,
+            title: Vec<u8>,
+            description: Vec<u8>,
+            voting_period: Option<BlockNumberFor<T>>,
+            category: ProposalCategory,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            Self::do_create_proposal(
+                who,
+                title,
+                description,
+                voting_period,
+                None,
+                None,
+                T::MinQuorum::get(),
+                category,
+            )
+        }
+
+        /// Add an account to the members
+        ///
+        /// # Arguments
+        /// * `origin` - Must be `MembershipOrigin`
+        /// * `who` - Account to add
+        ///
+        /// # Returns
+        /// * `DispatchResult` - Success or error
+        ///
+        /// # Events
+        /// * `MemberAdded` - Emitted when the account is added
+        ///
+        /// # Errors
+        /// * `AlreadyMember` - The account is already a member
+        #[pallet::call_index(18)]
+        #[pallet::weight(T::WeightInfo::add_member())]
+        pub fn add_member(origin: OriginFor<T>, who: T::AccountId) -> DispatchResult {
+            T::MembershipOrigin::ensure_origin(origin)?;
+            ensure!(!Members::<T>::contains_key(&who), Error::<T>::AlreadyMember);
+
+            Members::<T>::insert(&who, ());
+            MemberCount::<T>::mutate(|count| *count = count.saturating_add(1));
+
+            Self::deposit_event(Event::MemberAdded { who });
+
+            Ok(())
+        }
+
+        /// Remove an account from the members
+        ///
+        /// Votes the account already cast stay counted.
+        ///
+        /// # Arguments
+        /// * `origin` - Must be `MembershipOrigin`
+        /// * `who` - Account to remove
+        ///
+        /// # Returns
+        /// * `DispatchResult` - Success or error
+        ///
+        /// # Events
+        /// * `MemberRemoved` - Emitted when the account is removed
+        ///
+        /// # Errors
+        /// * `NotAMember` - The account is not a member
+        #[pallet::call_index(19)]
+        #[pallet::weight(T::WeightInfo::remove_member())]
+        pub fn remove_member(origin: OriginFor<T>, who: T::AccountId) -> DispatchResult {
+            T::MembershipOrigin::ensure_origin(origin)?;
+            ensure!(Members::<T>::take(&who).is_some(), Error::<T>::NotAMember);
+
+            MemberCount::<T>::mutate(|count| *count = count.saturating_sub(1));
+
+            Self::deposit_event(Event::MemberRemoved { who });
+
+            Ok(())
+        }
+
+        /// Vote on a proposal with conviction, locking the caller's balance
+        ///
+        /// Same as `vote`, with the voter's own weight being `conviction`; the amount of
+        /// `balance` does not change it. `balance` is locked until `conviction` voting periods
+        /// of the proposal after its voting ends; `unlock` removes the lock afterwards. A lock
+        /// of earlier conviction votes is only ever extended.
+        ///
+        /// # Arguments
+        /// * `origin` - Transaction origin (voter)
+        /// * `proposal_id` - ID of the proposal to vote on
+        /// * `vote` - `Aye`, `Nay` or `Abstain`
+        /// * `balance` - Part of the free balance to lock behind the vote, at least `VoteUnit`
+        /// * `conviction` - Multiplier of the voter's own weight, 1 to `MAX_CONVICTION`
+        ///
+        /// # Returns
+        /// * `DispatchResult` - Success or error
+        ///
+        /// # Events
+        /// * `VoteCast` - Emitted with the weight of the vote
+        /// * `VoteLocked` - Emitted with the lock covering the vote
+        ///
+        /// # Errors
+        /// * `InvalidConviction` - `conviction` is 0 or above `MAX_CONVICTION`
+        /// * `InsufficientBalance` - `balance` exceeds the caller's free balance
+        /// * `BalanceBelowVoteUnit` - `balance` is less than one `VoteUnit`
+        /// * Any error of `vote`
+        #[pallet::call_index(20)]
+        #[pallet::weight(T::WeightInfo::vote_with_conviction(T::MaxDelegators::get()))]
+        pub fn vote_with_conviction(
+            origin: OriginFor<T>,
+            proposal_id: u64,
+            vote: VoteKind,
+            balance: BalanceOf<T>,
+            conviction: u8,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!((1..=MAX_CONVICTION).contains(&conviction), Error::<T>::InvalidConviction);
+            ensure!(
+                balance <= T::Currency::free_balance(&who),
+                Error::<T>::InsufficientBalance
+            );
+            ensure!(balance >= T::VoteUnit::get(), Error::<T>::BalanceBelowVoteUnit);
+            let own_weight = u64::from(conviction);
+
+            Self::do_vote(who.clone(), proposal_id, vote, &[], own_weight)?;
+            VoteConvictions::<T>::insert(proposal_id, &who, conviction);
+            VoteWeights::<T>::insert(proposal_id, &who, own_weight);
+
+            let proposal = Proposals::<T>::get(proposal_id).ok_or(Error::<T>::ProposalNotFound)?;
+            let period = proposal.voting_end.saturating_sub(proposal.voting_start);
+            let lock_period = period.saturating_mul(conviction.into());
+            let until = proposal.voting_end.saturating_add(lock_period);
+            Self::extend_vote_lock(&who, balance, until);
+
+            Ok(())
+        }
+
+        /// Remove the balance lock of the caller's conviction votes once it expired
+        ///
+        /// # Arguments
+        /// * `origin` - Transaction origin (locked account)
+        ///
+        /// # Returns
+        /// * `DispatchResult` - Success or error
+        ///
+        /// # Events
+        /// * `Unlocked` - Emitted when the lock is removed
+        ///
+        /// # Errors
+        /// * `NotLocked` - The caller has no balance locked by conviction votes
+        /// * `LockNotExpired` - The lock lasts beyond the current block
+        #[pallet::call_index(21)]
+        #[pallet::weight(T::WeightInfo::unlock())]
+        pub fn unlock(origin: OriginFor<T>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let (amount, until) = VoteLocks::<T>::get(&who).ok_or(Error::<T>::NotLocked)?;
+            ensure!(
+                frame_system::Pallet::<T>::block_number() >= until,
+                Error::<T>::LockNotExpired
+            );
+
+            T::Currency::remove_lock(VOTE_LOCK_ID, &who);
+            VoteLocks::<T>::remove(&who);
+
+            Self::deposit_event(Event::Unlocked { who, amount });
+
+            Ok(())
+        }
+
+        /// Replace the title and description of a proposal nobody voted on yet
+        ///
+        /// Lets the proposer fix a typo right after submitting. Each amendment bumps the
+        /// proposal's `revision`; everything else is kept.
         ///
         /// # Arguments
-        /// * `origin` - Transaction origin (proposer)
-        /// * `title` - Proposal title
-        /// * `description` - Proposal description
-        /// * `voting_period` - Voting period in blocks (optional, uses minimum if None)
+        /// * `origin` - Transaction origin (must be proposer)
+        /// * `proposal_id` - ID of the proposal to amend
+        /// * `new_title` - Title replacing the current one
+        /// * `new_description` - Description replacing the current one
         ///
         /// # Returns
         /// * `DispatchResult` - Success or error
         ///
         /// # Events
-        /// * `ProposalCreated` - Emitted when proposal is created
+        /// * `ProposalAmended` - Emitted with the new revision
         ///
         /// # Errors
-        /// * `TitleTooLong` - Title exceeds maximum length
-        /// * `DescriptionTooLong` - Description exceeds maximum length
-        /// * `InvalidVotingPeriod` - Voting period outside allowed range
-        #[pallet::call_index(0)]
-        #[pallet::weight(10_000)]
-        pub fn create_proposal(
+        /// * `ProposalNotFound` - Proposal doesn't exist
+        /// * `ProposalNotActive` - Proposal is not active, or the caller is not its proposer
+        /// * `VotingPeriodEnded` - Voting period has ended
+        /// * `VotesAlreadyCast` - A vote was cast on the proposal
+        /// * `TitleTooLong` - Title exceeds max length
+        /// * `DescriptionTooLong` - Description exceeds max length
+        /// * `InvalidUtf8` - Title or description is not valid UTF-8
+        #[pallet::call_index(22)]
+        #[pallet::weight(T::WeightInfo::amend_proposal(
+            new_title.len() as u32,
+            new_description.len() as u32,
+        ))]
+        pub fn amend_proposal(
             origin: OriginFor<T>,
+            proposal_id: u64,
+            new_title: Vec<u8>,
+            new_description: Vec<u8>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let mut proposal = Self::ensure_open_for_votes(proposal_id)?;
+            ensure!(proposal.proposer == who, Error::<T>::ProposalNotActive);
+            ensure!(proposal.total_votes == 0, Error::<T>::VotesAlreadyCast);
+
+            proposal.title = Self::sanitize_text(new_title)?
+                .try_into()
+                .map_err(|e| Self::text_error(e, Error::<T>::TitleTooLong))?;
+            proposal.description = Self::sanitize_text(new_description)?
+                .try_into()
+                .map_err(|e| Self::text_error(e, Error::<T>::DescriptionTooLong))?;
+            proposal.revision = proposal.revision.saturating_add(1);
+            let revision = proposal.revision;
+            Proposals::<T>::insert(proposal_id, proposal);
+
+            Self::deposit_event(Event::ProposalAmended { proposal_id, revision });
+
+            Ok(())
+        }
+    }
+
+    // Helper functions
+    impl<T: Config> Pallet<T> {
+        /// Validate and store a new proposal, reserving its deposit
+        #[allow(clippy::too_many_arguments)]
+        fn do_create_proposal(
+            who: T::AccountId,
             title: Vec<u8>,
             description: Vec<u8>,
             voting_period: Option<BlockNumberFor<T>>,
+            parameter_change: Option<ParameterChange>,
+            call: Option<EncodedCall<T>>,
+            quorum: u64,
+            category: ProposalCategory,
         ) -> DispatchResult {
-            let who = ensure_signed(origin)?;
+            let title = Self::sanitize_text(title)?;
+            let description = Self::sanitize_text(description)?;
 
             // Validate inputs
-            let bounded_title: BoundedVec<u8, T::MaxTitleLength> = title
-                .clone()
+            let bounded_title: ProposalTitle<T> = title
                 .try_into()
-                .map_err(|_| Error::<T>::TitleTooLong)?;
+                .map_err(|e| Self::text_error(e, Error::<T>::TitleTooLong))?;
 
-            let bounded_description: BoundedVec<u8, T::MaxDescriptionLength> = description
+            let bounded_description: ProposalDescription<T> = description
                 .try_into()
-                .map_err(|_| Error::<T>::DescriptionTooLong)?;
+                .map_err(|e| Self::text_error(e, Error::<T>::DescriptionTooLong))?;
 
             // Determine voting period
             let period = voting_period.unwrap_or_else(|| T::MinVotingPeriod::get());
@@ -304,9 +2271,12 @@ pub mod pallet {
                 Error::<T>::InvalidVotingPeriod
             );
 
-            // Reserve deposit
-            T::Currency::reserve(&who, T::ProposalDeposit::get())
-                .map_err(|_| Error::<T>::InsufficientDeposit)?;
+            // When only members vote, a share of them must take part
+            let quorum = quorum.max(Self::member_quorum());
+
+            // Reserve deposit, remembering the amount for its release
+            let deposit = T::ProposalDeposit::get();
+            T::Currency::reserve(&who, deposit).map_err(|_| Error::<T>::InsufficientDeposit)?;
 
             // Get proposal ID
             let proposal_id = ProposalCount::<T>::get();
@@ -325,13 +2295,26 @@ pub mod pallet {
                 status: ProposalStatus::Active,
                 votes_for: 0,
                 votes_against: 0,
+                abstentions: 0,
                 total_votes: 0,
                 executed: false,
                 executed_at: None,
+                deposit,
+                parameter_change,
+                quorum,
+                call_hash: call.as_ref().map(|call| sp_io::hashing::blake2_256(call)),
+                category,
+                revision: 0,
             };
 
             // Store proposal
+            ProposalsEndingAt::<T>::try_append(voting_end, proposal_id)
+                .map_err(|_| Error::<T>::TooManyProposalsEnding)?;
             Proposals::<T>::insert(proposal_id, proposal);
+            if let Some(call) = call {
+                ProposalCalls::<T>::insert(proposal_id, call);
+            }
+            Self::index_by_category(category, proposal_id);
             ProposalCount::<T>::put(proposal_id.saturating_add(1));
 
             // Emit event
@@ -339,37 +2322,21 @@ pub mod pallet {
                 proposal_id,
                 proposer: who,
                 title: bounded_title.to_vec(),
+                deposit,
             });
 
             Ok(())
         }
 
-        /// Vote on a proposal
-        ///
-        /// # Arguments
-        /// * `origin` - Transaction origin (voter)
-        /// * `proposal_id` - ID of the proposal to vote on
-        /// * `in_favor` - true for yes, false for no
-        ///
-        /// # Returns
-        /// * `DispatchResult` - Success or error
-        ///
-        /// # Events
-        /// * `VoteCast` - Emitted when vote is successfully cast
-        ///
-        /// # Errors
-        /// * `ProposalNotFound` - Proposal doesn't exist
-        /// * `ProposalNotActive` - Proposal is not active
-        /// * `AlreadyVoted` - Account has already voted
-        /// * `VotingPeriodEnded` - Voting period has ended
-        #[pallet::call_index(1)]
-        #[pallet::weight(8_000)]
-        pub fn vote(
-            origin: OriginFor<T>,
+        /// Record a vote and mint its receipt, the voter's own vote weighing `own_weight`
+        fn do_vote(
+            who: T::AccountId,
             proposal_id: u64,
-            in_favor: bool,
+            vote: VoteKind,
+            salt: &[u8],
+            own_weight: u64,
         ) -> DispatchResult {
-            let who = ensure_signed(origin)?;
+            ensure!(Self::may_vote(&who), Error::<T>::NotAMember);
 
             // Get proposal
             let mut proposal =
@@ -392,118 +2359,253 @@ pub mod pallet {
             );
 
             // Record vote
-            Votes::<T>::insert(proposal_id, &who, in_favor);
+            Votes::<T>::insert(proposal_id, &who, vote);
             HasVoted::<T>::insert(proposal_id, &who, true);
 
-            // Update vote counts
-            if in_favor {
-                proposal.votes_for = proposal.votes_for.saturating_add(1);
-            } else {
-                proposal.votes_against = proposal.votes_against.saturating_add(1);
+            // A direct vote takes the voter's weight back from a delegate that voted
+            if let Some((delegate, delegated)) = DelegatedVotes::<T>::take(proposal_id, &who) {
+                CarriedDelegators::<T>::mutate_exists(proposal_id, &delegate, |carried| {
+                    if let Some(list) = carried {
+                        list.retain(|account| *account != who);
+                        if list.is_empty() {
+                            *carried = None;
+                        }
+                    }
+                });
+                proposal.uncount_vote(delegated, 1);
+            }
+
+            // Carry the weight of direct delegators not yet counted on this proposal
+            let mut delegators = Delegators::<T>::get(&who);
+            delegators.retain(|delegator| {
+                Self::may_vote(delegator) &&
+                    !HasVoted::<T>::get(proposal_id, delegator) &&
+                    !DelegatedVotes::<T>::contains_key(proposal_id, delegator)
+            });
+            for delegator in &delegators {
+                DelegatedVotes::<T>::insert(proposal_id, delegator, (&who, vote));
+            }
+            if !delegators.is_empty() {
+                CarriedDelegators::<T>::insert(proposal_id, &who, &delegators);
             }
-            proposal.total_votes = proposal.total_votes.saturating_add(1);
+            let weight = own_weight.saturating_add(delegators.len() as u64);
+
+            // Update vote counts
+            proposal.count_vote(vote, weight);
 
             // Store updated proposal
             Proposals::<T>::insert(proposal_id, proposal);
 
+            Self::mint_receipt(
+                &who,
+                VoteReceipt {
+                    proposal_id,
+                    weight,
+                    block: current_block,
+                    choice_hash: Self::hash_choice(vote, salt),
+                },
+            );
+
             // Emit event
             Self::deposit_event(Event::VoteCast {
                 proposal_id,
                 voter: who,
-                in_favor,
+                vote,
+                weight,
             });
 
             Ok(())
         }
 
-        /// Execute an approved proposal
-        ///
-        /// # Arguments
-        /// * `origin` - Transaction origin (executor)
-        /// * `proposal_id` - ID of the proposal to execute
-        ///
-        /// # Returns
-        /// * `DispatchResult` - Success or error
-        ///
-        /// # Events
-        /// * `ProposalExecuted` - Emitted when proposal is executed
-        /// * `ProposalStatusChanged` - Emitted when status changes
-        ///
-        /// # Errors
-        /// * `ProposalNotFound` - Proposal doesn't exist
-        /// * `VotingPeriodNotEnded` - Voting still in progress
-        /// * `ProposalNotApproved` - Proposal was not approved
-        /// * `AlreadyExecuted` - Proposal already executed
-        #[pallet::call_index(2)]
-        #[pallet::weight(15_000)]
-        pub fn execute_proposal(origin: OriginFor<T>, proposal_id: u64) -> DispatchResult {
-            let who = ensure_signed(origin)?;
+        /// Weight of `who`'s own vote on a proposal, without the delegators it carries: that
+        /// of its conviction, 1 for votes cast without one
+        fn own_weight(proposal_id: u64, who: &T::AccountId) -> u64 {
+            VoteWeights::<T>::get(proposal_id, who).unwrap_or(1)
+        }
 
-            // Get proposal
-            let mut proposal =
-                Proposals::<T>::get(proposal_id).ok_or(Error::<T>::ProposalNotFound)?;
+        /// Lock `amount` of the free balance of `who` until at least `until`, extending an
+        /// earlier lock. Like pallet-conviction-voting, the lock leaves out reserving.
+        fn extend_vote_lock(who: &T::AccountId, amount: BalanceOf<T>, until: BlockNumberFor<T>) {
+            let mut amount = amount;
+            let mut until = until;
+            if let Some((previous_amount, previous_until)) = VoteLocks::<T>::get(who) {
+                amount = amount.max(previous_amount);
+                until = until.max(previous_until);
+            }
 
-            // Check voting period ended
-            let current_block = frame_system::Pallet::<T>::block_number();
-            ensure!(
-                proposal.is_voting_ended(current_block),
-                Error::<T>::VotingPeriodNotEnded
-            );
+            let reasons = WithdrawReasons::except(WithdrawReasons::RESERVE);
+            T::Currency::set_lock(VOTE_LOCK_ID, who, amount, reasons);
+            VoteLocks::<T>::insert(who, (amount, until));
 
-            // Check proposal is approved
-            ensure!(proposal.is_approved(), Error::<T>::ProposalNotApproved);
+            Self::deposit_event(Event::VoteLocked { who: who.clone(), amount, until });
+        }
 
-            // Check not already executed
-            ensure!(!proposal.executed, Error::<T>::AlreadyExecuted);
+        /// Reject a delegation from `who` to `to` that would close a cycle or make a chain
+        /// longer than `MaxDelegationDepth`
+        fn ensure_delegation_allowed(who: &T::AccountId, to: &T::AccountId) -> DispatchResult {
+            let max_depth = T::MaxDelegationDepth::get();
+
+            // Delegations from `to` onwards; existing chains are acyclic and bounded
+            let mut above = 0u32;
+            let mut current = to.clone();
+            loop {
+                ensure!(current != *who, Error::<T>::DelegationCycle);
+                let Some(next) = Delegations::<T>::get(&current) else { break };
+                above = above.saturating_add(1);
+                ensure!(above < max_depth, Error::<T>::DelegationTooDeep);
+                current = next;
+            }
 
-            // Update proposal status
-            let old_status = proposal.status.clone();
-            proposal.status = ProposalStatus::Executed;
-            proposal.executed = true;
-            proposal.executed_at = Some(current_block);
+            let below = Self::delegation_height(who);
+            ensure!(
+                below.saturating_add(1).saturating_add(above) <= max_depth,
+                Error::<T>::DelegationTooDeep
+            );
+            Ok(())
+        }
 
-            // Store updated proposal
-            Proposals::<T>::insert(proposal_id, proposal);
+        /// Delegations in the longest chain ending at `who`
+        fn delegation_height(who: &T::AccountId) -> u32 {
+            DelegatorHeights::<T>::decode_len(who).unwrap_or(0) as u32
+        }
 
-            // Unreserve deposit (return to proposer)
-            T::Currency::unreserve(&who, T::ProposalDeposit::get());
+        /// Move a direct delegator of `account` from height `old` to `new` in
+        /// `DelegatorHeights`, `None` adding or removing it, and carry a resulting change of
+        /// the account's own height along the chain of delegations from it
+        fn move_delegator_height(account: &T::AccountId, old: Option<u32>, new: Option<u32>) {
+            let mut account = account.clone();
+            let (mut old, mut new) = (old, new);
+            loop {
+                let mut counts = DelegatorHeights::<T>::get(&account).into_inner();
+                let before = counts.len() as u32;
+                if let Some(count) = old.and_then(|height| counts.get_mut(height as usize)) {
+                    *count = count.saturating_sub(1);
+                }
+                if let Some(height) = new {
+                    if counts.len() <= height as usize {
+                        counts.resize(height as usize + 1, 0);
+                    }
+                    counts[height as usize] = counts[height as usize].saturating_add(1);
+                }
+                while counts.last() == Some(&0) {
+                    counts.pop();
+                }
+                let after = counts.len() as u32;
+
+                if counts.is_empty() {
+                    DelegatorHeights::<T>::remove(&account);
+                } else {
+                    // Chains are at most `MaxDelegationDepth` long, so are the counts
+                    DelegatorHeights::<T>::insert(&account, BoundedVec::truncate_from(counts));
+                }
+
+                if before == after {
+                    break;
+                }
+                let Some(next) = Delegations::<T>::get(&account) else { break };
+                (old, new) = (Some(before), Some(after));
+                account = next;
+            }
+        }
 
-            // Emit events
-            Self::deposit_event(Event::ProposalExecuted {
-                proposal_id,
-                executor: who,
+        /// Drop `delegator` from the delegators of `delegate`
+        fn remove_delegator(delegate: &T::AccountId, delegator: &T::AccountId) {
+            Self::move_delegator_height(delegate, Some(Self::delegation_height(delegator)), None);
+            Delegators::<T>::mutate_exists(delegate, |delegators| {
+                if let Some(list) = delegators {
+                    list.retain(|account| account != delegator);
+                    if list.is_empty() {
+                        *delegators = None;
+                    }
+                }
             });
+            Self::deposit_event(Event::Undelegated {
+                delegator: delegator.clone(),
+                delegate: delegate.clone(),
+            });
+        }
 
+        /// Emit `ProposalStatusChanged` and report the transition to `OnTransition`
+        fn note_status_change(
+            proposal_id: u64,
+            old_status: ProposalStatus,
+            new_status: ProposalStatus,
+        ) {
+            T::OnTransition::on_transition(
+                TransitionSubject::Proposal(proposal_id),
+                status_index(&old_status),
+                status_index(&new_status),
+            );
             Self::deposit_event(Event::ProposalStatusChanged {
                 proposal_id,
                 old_status,
-                new_status: ProposalStatus::Executed,
+                new_status,
             });
+        }
 
-            Ok(())
+        /// Active proposal whose voting period has not ended
+        fn ensure_open_for_votes(proposal_id: u64) -> Result<Proposal<T>, Error<T>> {
+            let proposal = Proposals::<T>::get(proposal_id).ok_or(Error::<T>::ProposalNotFound)?;
+            ensure!(proposal.is_active(), Error::<T>::ProposalNotActive);
+            ensure!(
+                !proposal.is_voting_ended(frame_system::Pallet::<T>::block_number()),
+                Error::<T>::VotingPeriodEnded
+            );
+            Ok(proposal)
         }
 
-        /// Close a proposal after voting period
-        ///
-        /// This function finalizes the proposal status based on voting results.
-        /// Can be called by anyone after voting period ends.
-        ///
-        /// # Arguments
-        /// * `origin` - Transaction origin
-        /// * `proposal_id` - ID of the proposal to close
-        ///
-        /// # Returns
-        /// * `DispatchResult` - Success or error
-        ///
-        /// # Events
-        /// * `VotingEnded` - Emitted when voting ends
-        /// * `ProposalClosed` - Emitted when proposal is closed
-        /// * `ProposalStatusChanged` - Emitted when status changes
-        #[pallet::call_index(3)]
-        #[pallet::weight(5_000)]
-        pub fn close_proposal(origin: OriginFor<T>, proposal_id: u64) -> DispatchResult {
-            let _who = ensure_signed(origin)?;
+        /// Hash of the unspent voucher of a proposal that `preimage` opens
+        fn ensure_voucher_unspent(
+            proposal_id: u64,
+            preimage: &[u8; 32],
+        ) -> Result<[u8; 32], Error<T>> {
+            let voucher = sp_io::hashing::blake2_256(preimage);
+            let spent =
+                PollVouchers::<T>::get(proposal_id, voucher).ok_or(Error::<T>::InvalidVoucher)?;
+            ensure!(!spent, Error::<T>::VoucherSpent);
+            Ok(voucher)
+        }
 
+        /// Pool validity of an unsigned `vote_with_voucher`: only for an unspent voucher of a
+        /// proposal open for votes, one transaction per voucher
+        fn validate_voucher_vote(proposal_id: u64, preimage: &[u8; 32]) -> TransactionValidity {
+            Self::ensure_open_for_votes(proposal_id).map_err(|_| InvalidTransaction::Stale)?;
+            let voucher = Self::ensure_voucher_unspent(proposal_id, preimage).map_err(|e| match e {
+                Error::<T>::VoucherSpent => InvalidTransaction::Stale,
+                _ => InvalidTransaction::BadProof,
+            })?;
+
+            ValidTransaction::with_tag_prefix("DaoVoucherVote")
+                .priority(T::UnsignedPriority::get())
+                .and_provides((proposal_id, voucher))
+                .longevity(VOUCHER_VOTE_LONGEVITY)
+                .propagate(true)
+                .build()
+        }
+
+        /// Hash committing to a vote choice: Blake2-256 of `vote.encode() ++ salt`
+        pub fn hash_choice(vote: VoteKind, salt: &[u8]) -> [u8; 32] {
+            let mut data = vote.encode();
+            data.extend_from_slice(salt);
+            sp_io::hashing::blake2_256(&data)
+        }
+
+        /// Store a receipt under the voter's next receipt ID, evicting the oldest receipt
+        /// once `MaxReceiptsPerAccount` are held
+        fn mint_receipt(voter: &T::AccountId, receipt: VoteReceipt<BlockNumberFor<T>>) {
+            let (mut oldest, next) = ReceiptRange::<T>::get(voter);
+            if next.saturating_sub(oldest) >= u64::from(T::MaxReceiptsPerAccount::get()) {
+                Receipts::<T>::remove(voter, oldest);
+                oldest = oldest.saturating_add(1);
+            }
+
+            Receipts::<T>::insert(voter, next, receipt);
+            ReceiptRange::<T>::insert(voter, (oldest, next.saturating_add(1)));
+        }
+
+        /// Finalize a proposal after its voting period as approved or rejected, rejecting it
+        /// below quorum
+        fn do_close_proposal(proposal_id: u64) -> DispatchResult {
             // Get proposal
             let mut proposal =
                 Proposals::<T>::get(proposal_id).ok_or(Error::<T>::ProposalNotFound)?;
@@ -515,11 +2617,8 @@ pub mod pallet {
                 Error::<T>::VotingPeriodNotEnded
             );
 
-            // Check not already executed or closed
-            ensure!(proposal.is_active(), Error::<T>::ProposalNotActive);
-
             // Determine final status
-            let old_status = proposal.status.clone();
+            let quorum_reached = proposal.is_quorum_reached();
             let is_approved = proposal.is_approved();
             let new_status = if is_approved {
                 ProposalStatus::Approved
@@ -527,15 +2626,24 @@ pub mod pallet {
                 ProposalStatus::Rejected
             };
 
-            proposal.status = new_status.clone();
+            // Only active proposals can be closed
+            let old_status = PROPOSAL_STATUS
+                .transition(&mut proposal.status, new_status.clone())
+                .map_err(|_| Error::<T>::ProposalNotActive)?;
+            if !is_approved {
+                Self::release_rejected_deposit(proposal_id, &proposal);
+                ProposalCalls::<T>::remove(proposal_id);
+            }
 
             // Store updated proposal
+            Self::unindex_proposal(proposal.voting_end, proposal_id);
             Proposals::<T>::insert(proposal_id, proposal);
 
             // Emit events
             Self::deposit_event(Event::VotingEnded {
                 proposal_id,
                 approved: is_approved,
+                quorum_reached,
             });
 
             Self::deposit_event(Event::ProposalClosed {
@@ -543,60 +2651,132 @@ pub mod pallet {
                 final_status: new_status.clone(),
             });
 
-            Self::deposit_event(Event::ProposalStatusChanged {
+            Self::note_status_change(proposal_id, old_status, new_status);
+
+            Ok(())
+        }
+
+        /// Dispatch the call of a proposal with `ProposalOrigin`, returning its result and the
+        /// weight it used. A proposal without a call succeeds without using any.
+        fn dispatch_proposal_call(proposal_id: u64) -> (DispatchResult, Weight) {
+            let Some(encoded) = ProposalCalls::<T>::take(proposal_id) else {
+                return (Ok(()), Weight::zero());
+            };
+            // The call was decoded from an extrinsic before, so its nesting is already bounded
+            let Ok(call) = <T as Config>::RuntimeCall::decode(&mut &encoded[..]) else {
+                return (Err(Error::<T>::UndecodableCall.into()), Weight::zero());
+            };
+
+            // A runtime upgrade may have made the call heavier than `execute_proposal` charges
+            let info = call.get_dispatch_info();
+            if !info.weight.all_lte(T::MaxCallWeight::get()) {
+                return (Err(Error::<T>::CallTooHeavy.into()), Weight::zero());
+            }
+            let result = call.dispatch(T::ProposalOrigin::get());
+            let weight = extract_actual_weight(&result, &info);
+            (result.map(|_| ()).map_err(|e| e.error), weight)
+        }
+
+        /// Refund the deposit of a rejected proposal, or slash it to `Config::Slashed` if
+        /// `SlashRejectedDeposits` is on and its approval is below `MinRefundApproval`
+        fn release_rejected_deposit(proposal_id: u64, proposal: &Proposal<T>) {
+            let proposer = &proposal.proposer;
+            let spam = T::SlashRejectedDeposits::get() &&
+                proposal.approval_percentage() < T::MinRefundApproval::get();
+            if !spam {
+                T::Currency::unreserve(proposer, proposal.deposit);
+                return;
+            }
+
+            let (imbalance, missing) = T::Currency::slash_reserved(proposer, proposal.deposit);
+            T::Slashed::on_unbalanced(imbalance);
+            Self::deposit_event(Event::DepositSlashed {
                 proposal_id,
-                old_status,
-                new_status,
+                proposer: proposer.clone(),
+                amount: proposal.deposit.saturating_sub(missing),
             });
+        }
 
-            Ok(())
+        /// Remove a proposal that is no longer active from `ProposalsEndingAt`
+        fn unindex_proposal(voting_end: BlockNumberFor<T>, proposal_id: u64) {
+            ProposalsEndingAt::<T>::mutate_exists(voting_end, |ids| {
+                if let Some(list) = ids {
+                    list.retain(|id| *id != proposal_id);
+                    if list.is_empty() {
+                        *ids = None;
+                    }
+                }
+            });
         }
 
-        /// Cancel a proposal (only proposer can cancel before voting ends)
-        ///
-        /// # Arguments
-        /// * `origin` - Transaction origin (must be proposer)
-        /// * `proposal_id` - ID of the proposal to cancel
-        #[pallet::call_index(4)]
-        #[pallet::weight(5_000)]
-        pub fn cancel_proposal(origin: OriginFor<T>, proposal_id: u64) -> DispatchResult {
-            let who = ensure_signed(origin)?;
+        /// Active proposals whose voting period has ended by block `now`
+        pub fn expired_proposals(now: BlockNumberFor<T>) -> Vec<u64> {
+            let mut expired: Vec<u64> = ProposalsEndingAt::<T>::iter()
+                .filter(|(voting_end, _)| *voting_end <= now)
+                .flat_map(|(_, ids)| ids.into_inner())
+                .collect();
+            expired.sort_unstable();
+            expired
+        }
 
-            // Get proposal
-            let mut proposal =
-                Proposals::<T>::get(proposal_id).ok_or(Error::<T>::ProposalNotFound)?;
+        /// Close the proposals indexed in `ProposalsEndingAt` up to `now`, block by block.
+        ///
+        /// Processing stops as soon as the next step would exceed `budget`; the position is
+        /// kept in `AutoCloseCursor` and the proposals left indexed are closed in later blocks.
+        pub(crate) fn process_ended_proposals(now: BlockNumberFor<T>, budget: Weight) -> Weight {
+            let db = T::DbWeight::get();
+            let per_block = db.reads(1);
+            // Closing a proposal here costs what `close_proposal` is charged for it
+            let per_proposal = T::WeightInfo::close_proposal();
+
+            // The cursor is read and written back
+            let mut used = db.reads_writes(1, 1);
+            if used.any_gt(budget) {
+                return Weight::zero();
+            }
+            let mut block = AutoCloseCursor::<T>::get().unwrap_or(now);
 
-            // Only proposer can cancel
-            ensure!(proposal.proposer == who, Error::<T>::ProposalNotActive);
+            'blocks: while block <= now {
+                if used.saturating_add(per_block).any_gt(budget) {
+                    break;
+                }
+                used = used.saturating_add(per_block);
 
-            // Can only cancel active proposals
-            ensure!(proposal.is_active(), Error::<T>::ProposalNotActive);
+                for proposal_id in ProposalsEndingAt::<T>::get(block) {
+                    if used.saturating_add(per_proposal).any_gt(budget) {
+                        break 'blocks;
+                    }
+                    used = used.saturating_add(per_proposal);
 
-            // Update status
-            let old_status = proposal.status.clone();
-            proposal.status = ProposalStatus::Cancelled;
+                    // Only active proposals are indexed, and closing unindexes them
+                    let _ = Self::do_close_proposal(proposal_id);
+                }
 
-            // Store updated proposal
-            Proposals::<T>::insert(proposal_id, proposal);
+                block = block.saturating_add(One::one());
+            }
 
-            // Unreserve deposit
-            T::Currency::unreserve(&who, T::ProposalDeposit::get());
+            AutoCloseCursor::<T>::put(block);
+            used
+        }
 
-            // Emit event
-            Self::deposit_event(Event::ProposalStatusChanged {
-                proposal_id,
-                old_status,
-                new_status: ProposalStatus::Cancelled,
-            });
+        /// Validate and normalize a text field if `NormalizeText` is enabled
+        pub(crate) fn sanitize_text(bytes: Vec<u8>) -> Result<Vec<u8>, Error<T>> {
+            if !NormalizeText::<T>::get() {
+                return Ok(bytes);
+            }
+            text::sanitize(bytes).ok_or(Error::<T>::InvalidUtf8)
+        }
 
-            Ok(())
+        /// The error for text refused as a `BoundedString`, `too_long` if over the bound
+        fn text_error(error: BoundedStringError, too_long: Error<T>) -> Error<T> {
+            match error {
+                BoundedStringError::TooLong => too_long,
+                BoundedStringError::InvalidCharset => Error::<T>::InvalidUtf8,
+            }
         }
-    }
 
-    // Helper functions
-    impl<T: Config> Pallet<T> {
         /// Get vote for an account on a proposal
-        pub fn get_vote(proposal_id: u64, voter: &T::AccountId) -> Option<bool> {
+        pub fn get_vote(proposal_id: u64, voter: &T::AccountId) -> Option<VoteKind> {
             Votes::<T>::get(proposal_id, voter)
         }
 
@@ -605,10 +2785,157 @@ pub mod pallet {
             HasVoted::<T>::get(proposal_id, voter)
         }
 
+        /// The DAO's contribution to the runtime's feature flags, from its `Config` switches
+        pub fn feature_bits() -> FeatureFlags {
+            FeatureFlags::empty()
+                .with(
+                    FeatureFlags::PROPOSAL_AUTO_CLOSE,
+                    T::OffchainAutoClose::get() || !T::AutoCloseWeight::get().is_zero(),
+                )
+                .with(FeatureFlags::SPAM_DEPOSIT_SLASHING, T::SlashRejectedDeposits::get())
+                .with(FeatureFlags::MEMBERS_ONLY_VOTING, T::MembersOnly::get())
+        }
+
+        /// Whether `who` may vote: anyone unless `MembersOnly` is set, then members only
+        pub fn may_vote(who: &T::AccountId) -> bool {
+            !T::MembersOnly::get() || Members::<T>::contains_key(who)
+        }
+
+        /// Whether `who` is a member
+        pub fn is_member(who: &T::AccountId) -> bool {
+            Members::<T>::contains_key(who)
+        }
+
+        /// Votes a new proposal needs from the current members: `MemberQuorum` of
+        /// `MemberCount`, rounded up, or none unless `MembersOnly` is set
+        pub fn member_quorum() -> u64 {
+            if !T::MembersOnly::get() {
+                return 0;
+            }
+            u64::from(T::MemberQuorum::get().mul_ceil(MemberCount::<T>::get()))
+        }
+
+        /// Append a proposal to the index of its category
+        pub(crate) fn index_by_category(category: ProposalCategory, proposal_id: u64) {
+            let position = CategoryCount::<T>::get(category);
+            ProposalsByCategory::<T>::insert(category, position, proposal_id);
+            CategoryCount::<T>::insert(category, position.saturating_add(1));
+        }
+
+        /// Up to `limit` IDs of the proposals filed under `category`, in creation order,
+        /// skipping the first `offset` (helper function for RPC)
+        pub fn get_proposals_by_category(
+            category: ProposalCategory,
+            offset: u32,
+            limit: u32,
+        ) -> Vec<u64> {
+            let end = offset.saturating_add(limit).min(CategoryCount::<T>::get(category));
+            (offset..end)
+                .filter_map(|position| ProposalsByCategory::<T>::get(category, position))
+                .collect()
+        }
+
+        /// The DAO's own account, derived from `PalletId`; funds sent to it can only be spent
+        /// by proposals dispatched with `PotOrigin`
+        pub fn account_id() -> T::AccountId {
+            T::PalletId::get().into_account_truncating()
+        }
+
         /// Get proposal with vote counts
         pub fn get_proposal_details(proposal_id: u64) -> Option<Proposal<T>> {
             Proposals::<T>::get(proposal_id)
         }
+
+        /// Up to `limit` receipts of `account` with their IDs, skipping the `offset` oldest
+        /// (helper function for RPC)
+        pub fn get_receipts(
+            account: &T::AccountId,
+            offset: u32,
+            limit: u32,
+        ) -> Vec<(u64, VoteReceipt<BlockNumberFor<T>>)> {
+            let (oldest, next) = ReceiptRange::<T>::get(account);
+            let start = oldest.saturating_add(u64::from(offset));
+            let end = start.saturating_add(u64::from(limit)).min(next);
+
+            (start..end)
+                .filter_map(|id| Receipts::<T>::get(account, id).map(|receipt| (id, receipt)))
+                .collect()
+        }
+
+        /// Snapshot of a proposal, or `None` if it is missing or in a terminal status
+        fn proposal_snapshot(proposal_id: u64) -> Option<ProposalSnapshot<BlockNumberFor<T>>> {
+            let proposal = Proposals::<T>::get(proposal_id)?;
+            if PROPOSAL_STATUS.is_terminal(&proposal.status) {
+                return None;
+            }
+
+            Some(ProposalSnapshot {
+                id: proposal.id,
+                approval_percentage: proposal.approval_percentage(),
+                threshold_met: proposal.is_approved(),
+                status: proposal.status,
+                votes_for: proposal.votes_for,
+                votes_against: proposal.votes_against,
+                abstentions: proposal.abstentions,
+                total_votes: proposal.total_votes,
+                voting_start: proposal.voting_start,
+                voting_end: proposal.voting_end,
+                parameter_change: proposal.parameter_change,
+                call_hash: proposal.call_hash,
+                category: proposal.category,
+            })
+        }
+
+        /// Export up to `limit` non-terminal proposals starting at `cursor`, the first page
+        /// for `None` (helper function for RPC)
+        pub fn export_snapshot(
+            limit: u32,
+            cursor: Option<&PageCursor>,
+        ) -> Result<SnapshotPage<BlockNumberFor<T>>, InvalidCursor> {
+            let count = ProposalCount::<T>::get();
+            let mut ids = PageCursor::position_or(cursor, 0u64)?..count;
+
+            let entries: Vec<_> = ids
+                .by_ref()
+                .filter_map(Self::proposal_snapshot)
+                .take(limit as usize)
+                .collect();
+            let next_cursor = ids
+                .find(|id| Self::proposal_snapshot(*id).is_some())
+                .map(|id| PageCursor::new(&id));
+            let digest = match next_cursor {
+                Some(_) => None,
+                None => Some(Self::snapshot_digest()),
+            };
+
+            Ok(SnapshotPage {
+                entries,
+                next_cursor,
+                digest,
+            })
+        }
+
+        /// Blake2-256 digest over the SCALE-encoded list of all non-terminal proposals
+        pub fn snapshot_digest() -> [u8; 32] {
+            let all: Vec<_> = (0..ProposalCount::<T>::get())
+                .filter_map(Self::proposal_snapshot)
+                .collect();
+            sp_io::hashing::blake2_256(&all.encode())
+        }
+    }
+
+    /// Applies the DAO's own parameters decided by governance
+    impl<T: Config> ParameterHandler for Pallet<T> {
+        fn apply(change: &ParameterChange) -> Result<(), ParameterNoLongerExists> {
+            match change {
+                ParameterChange::DaoTextNormalization(enabled) => {
+                    NormalizeText::<T>::put(enabled);
+                    Self::deposit_event(Event::TextNormalizationSet { enabled: *enabled });
+                    Ok(())
+                },
+                _ => Err(ParameterNoLongerExists),
+            }
+        }
     }
 }
 
@@ -0,0 +1,797 @@
+//! Storage migrations for the DAO pallet
+
+/// Version 1: record the reserved deposit on every proposal.
+///
+/// Proposals created before version 1 reserved the `ProposalDeposit` configured at that
+/// time, which the runtime no longer knows. They are assumed to have reserved the value
+/// configured when the migration runs, so upgrade before changing `ProposalDeposit`.
+pub mod v1 {
+    use crate::{migrations::v3, BalanceOf, Config, Pallet, ProposalStatus};
+    use codec::{Decode, Encode};
+    use frame_support::{
+        pallet_prelude::*,
+        traits::{GetStorageVersion, StorageVersion},
+    };
+    use frame_system::pallet_prelude::BlockNumberFor;
+
+    /// Proposal layout stored before version 1
+    #[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug)]
+    pub struct OldProposal<T: Config> {
+        pub id: u64,
+        pub proposer: T::AccountId,
+        pub title: BoundedVec<u8, T::MaxTitleLength>,
+        pub description: BoundedVec<u8, T::MaxDescriptionLength>,
+        pub created_at: BlockNumberFor<T>,
+        pub voting_start: BlockNumberFor<T>,
+        pub voting_end: BlockNumberFor<T>,
+        pub status: ProposalStatus,
+        pub votes_for: u64,
+        pub votes_against: u64,
+        pub total_votes: u64,
+        pub executed: bool,
+        pub executed_at: Option<BlockNumberFor<T>>,
+    }
+
+    impl<T: Config> OldProposal<T> {
+        /// Upgrade to the version 1 layout with the given deposit
+        pub fn upgrade(self, deposit: BalanceOf<T>) -> v3::OldProposal<T> {
+            v3::OldProposal {
+                id: self.id,
+                proposer: self.proposer,
+                title: self.title,
+                description: self.description,
+                created_at: self.created_at,
+                voting_start: self.voting_start,
+                voting_end: self.voting_end,
+                status: self.status,
+                votes_for: self.votes_for,
+                votes_against: self.votes_against,
+                total_votes: self.total_votes,
+                executed: self.executed,
+                executed_at: self.executed_at,
+                deposit,
+            }
+        }
+    }
+
+    /// Run the migration if the on-chain storage version is below 1
+    pub fn migrate<T: Config>() -> Weight {
+        let db = T::DbWeight::get();
+        if Pallet::<T>::on_chain_storage_version() >= 1 {
+            return db.reads(1);
+        }
+
+        let deposit = T::ProposalDeposit::get();
+        let mut translated = 0u64;
+
+        v3::Proposals::<T>::translate::<OldProposal<T>, _>(|_id, old| {
+            translated = translated.saturating_add(1);
+            Some(old.upgrade(deposit))
+        });
+
+        StorageVersion::new(1).put::<Pallet<T>>();
+
+        db.reads_writes(translated.saturating_add(1), translated.saturating_add(1))
+    }
+}
+
+/// Version 2: index active proposals by voting end block in `ProposalsEndingAt`.
+///
+/// Proposals that do not fit into a full block entry stay unindexed and must be closed
+/// manually with `close_proposal`.
+pub mod v2 {
+    use crate::{migrations::v3, Config, Pallet, ProposalStatus, ProposalsEndingAt};
+    use frame_support::{
+        pallet_prelude::*,
+        traits::{GetStorageVersion, StorageVersion},
+    };
+
+    /// Run the migration if the on-chain storage version is 1
+    pub fn migrate<T: Config>() -> Weight {
+        let db = T::DbWeight::get();
+        if Pallet::<T>::on_chain_storage_version() != 1 {
+            return db.reads(1);
+        }
+
+        let mut reads = 1u64;
+        let mut writes = 1u64;
+
+        // Still in the layout before version 3
+        for (proposal_id, proposal) in v3::Proposals::<T>::iter() {
+            reads = reads.saturating_add(1);
+            if proposal.status == ProposalStatus::Active {
+                let _ = ProposalsEndingAt::<T>::try_append(proposal.voting_end, proposal_id);
+                writes = writes.saturating_add(1);
+            }
+        }
+
+        StorageVersion::new(2).put::<Pallet<T>>();
+
+        db.reads_writes(reads, writes)
+    }
+}
+
+/// Version 3: add an empty `parameter_change` to every proposal.
+///
+/// Proposals created before version 3 are plain proposals, executing them changes nothing.
+pub mod v3 {
+    use crate::{migrations::v5, BalanceOf, Config, Pallet, ProposalStatus};
+    use codec::{Decode, Encode};
+    use tidygen_primitives::bounded_string::BoundedString;
+    use frame_support::{
+        pallet_prelude::*,
+        storage_alias,
+        traits::{GetStorageVersion, StorageVersion},
+    };
+    use frame_system::pallet_prelude::BlockNumberFor;
+
+    /// Proposal layout stored before version 3
+    #[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug)]
+    pub struct OldProposal<T: Config> {
+        pub id: u64,
+        pub proposer: T::AccountId,
+        pub title: BoundedVec<u8, T::MaxTitleLength>,
+        pub description: BoundedVec<u8, T::MaxDescriptionLength>,
+        pub created_at: BlockNumberFor<T>,
+        pub voting_start: BlockNumberFor<T>,
+        pub voting_end: BlockNumberFor<T>,
+        pub status: ProposalStatus,
+        pub votes_for: u64,
+        pub votes_against: u64,
+        pub total_votes: u64,
+        pub executed: bool,
+        pub executed_at: Option<BlockNumberFor<T>>,
+        pub deposit: BalanceOf<T>,
+    }
+
+    impl<T: Config> OldProposal<T> {
+        /// Upgrade to the version 3 layout as a plain proposal, dropping invalid UTF-8 from
+        /// its text as version 4 does
+        pub fn upgrade(self) -> v5::OldProposal<T> {
+            v5::OldProposal {
+                id: self.id,
+                proposer: self.proposer,
+                title: BoundedString::from_lossy(self.title.into_inner()),
+                description: BoundedString::from_lossy(self.description.into_inner()),
+                created_at: self.created_at,
+                voting_start: self.voting_start,
+                voting_end: self.voting_end,
+                status: self.status,
+                votes_for: self.votes_for,
+                votes_against: self.votes_against,
+                total_votes: self.total_votes,
+                executed: self.executed,
+                executed_at: self.executed_at,
+                deposit: self.deposit,
+                parameter_change: None,
+            }
+        }
+    }
+
+    /// Proposal storage before version 3
+    #[storage_alias]
+    pub type Proposals<T: Config> =
+        StorageMap<Pallet<T>, Blake2_128Concat, u64, OldProposal<T>, OptionQuery>;
+
+    /// Run the migration if the on-chain storage version is 2
+    pub fn migrate<T: Config>() -> Weight {
+        let db = T::DbWeight::get();
+        if Pallet::<T>::on_chain_storage_version() != 2 {
+            return db.reads(1);
+        }
+
+        let mut translated = 0u64;
+
+        v5::Proposals::<T>::translate::<OldProposal<T>, _>(|_id, old| {
+            translated = translated.saturating_add(1);
+            Some(old.upgrade())
+        });
+
+        StorageVersion::new(3).put::<Pallet<T>>();
+
+        db.reads_writes(translated.saturating_add(1), translated.saturating_add(1))
+    }
+}
+
+/// Version 4: drop the invalid UTF-8 sequences from proposal titles and descriptions.
+///
+/// Text stored while `NormalizeText` was off was not validated; it has been required to be
+/// UTF-8 since. Valid text is left untouched and the encoding is unchanged.
+pub mod v4 {
+    use crate::{migrations::v5::Proposals, Config, Pallet};
+    use frame_support::{
+        pallet_prelude::*,
+        traits::{GetStorageVersion, StorageVersion},
+    };
+    use tidygen_primitives::bounded_string::BoundedString;
+
+    /// Run the migration if the on-chain storage version is 3
+    pub fn migrate<T: Config>() -> Weight {
+        let db = T::DbWeight::get();
+        if Pallet::<T>::on_chain_storage_version() != 3 {
+            return db.reads(1);
+        }
+
+        let mut reads = 1u64;
+        let mut writes = 1u64;
+
+        // Still in the layout before version 5
+        for (proposal_id, mut proposal) in Proposals::<T>::iter() {
+            reads = reads.saturating_add(1);
+            if proposal.title.is_valid() && proposal.description.is_valid() {
+                continue;
+            }
+            proposal.title = BoundedString::from_lossy(proposal.title.to_vec());
+            proposal.description = BoundedString::from_lossy(proposal.description.to_vec());
+            Proposals::<T>::insert(proposal_id, proposal);
+            writes = writes.saturating_add(1);
+        }
+
+        StorageVersion::new(4).put::<Pallet<T>>();
+
+        db.reads_writes(reads, writes)
+    }
+}
+
+/// Version 5: give every proposal a quorum, the `MinQuorum` configured when it runs.
+///
+/// Approved proposals below that quorum can no longer be executed.
+pub mod v5 {
+    use crate::{
+        migrations::v6, BalanceOf, Config, Pallet, ProposalDescription, ProposalStatus,
+        ProposalTitle,
+    };
+    use codec::{Decode, Encode};
+    use frame_support::{
+        pallet_prelude::*,
+        storage_alias,
+        traits::{GetStorageVersion, StorageVersion},
+    };
+    use frame_system::pallet_prelude::BlockNumberFor;
+    use tidygen_primitives::governance::ParameterChange;
+
+    /// Proposal layout stored before version 5
+    #[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug)]
+    pub struct OldProposal<T: Config> {
+        pub id: u64,
+        pub proposer: T::AccountId,
+        pub title: ProposalTitle<T>,
+        pub description: ProposalDescription<T>,
+        pub created_at: BlockNumberFor<T>,
+        pub voting_start: BlockNumberFor<T>,
+        pub voting_end: BlockNumberFor<T>,
+        pub status: ProposalStatus,
+        pub votes_for: u64,
+        pub votes_against: u64,
+        pub total_votes: u64,
+        pub executed: bool,
+        pub executed_at: Option<BlockNumberFor<T>>,
+        pub deposit: BalanceOf<T>,
+        pub parameter_change: Option<ParameterChange>,
+    }
+
+    impl<T: Config> OldProposal<T> {
+        /// Upgrade to the version 5 layout with the given quorum
+        pub fn upgrade(self, quorum: u64) -> v6::OldProposal<T> {
+            v6::OldProposal {
+                id: self.id,
+                proposer: self.proposer,
+                title: self.title,
+                description: self.description,
+                created_at: self.created_at,
+                voting_start: self.voting_start,
+                voting_end: self.voting_end,
+                status: self.status,
+                votes_for: self.votes_for,
+                votes_against: self.votes_against,
+                total_votes: self.total_votes,
+                executed: self.executed,
+                executed_at: self.executed_at,
+                deposit: self.deposit,
+                parameter_change: self.parameter_change,
+                quorum,
+            }
+        }
+    }
+
+    /// Proposal storage before version 5
+    #[storage_alias]
+    pub type Proposals<T: Config> =
+        StorageMap<Pallet<T>, Blake2_128Concat, u64, OldProposal<T>, OptionQuery>;
+
+    /// Run the migration if the on-chain storage version is 4
+    pub fn migrate<T: Config>() -> Weight {
+        let db = T::DbWeight::get();
+        if Pallet::<T>::on_chain_storage_version() != 4 {
+            return db.reads(1);
+        }
+
+        let quorum = T::MinQuorum::get();
+        let mut translated = 0u64;
+
+        v6::Proposals::<T>::translate::<OldProposal<T>, _>(|_id, old| {
+            translated = translated.saturating_add(1);
+            Some(old.upgrade(quorum))
+        });
+
+        StorageVersion::new(5).put::<Pallet<T>>();
+
+        db.reads_writes(translated.saturating_add(1), translated.saturating_add(1))
+    }
+}
+
+/// Version 6: count abstentions on every proposal, starting from none.
+///
+/// `Votes` needs no translation: `VoteKind` decodes the stored `bool` votes as `Aye` and `Nay`.
+pub mod v6 {
+    use crate::{
+        migrations::v7, BalanceOf, Config, Pallet, ProposalDescription, ProposalStatus,
+        ProposalTitle,
+    };
+    use codec::{Decode, Encode};
+    use frame_support::{
+        pallet_prelude::*,
+        storage_alias,
+        traits::{GetStorageVersion, StorageVersion},
+    };
+    use frame_system::pallet_prelude::BlockNumberFor;
+    use tidygen_primitives::governance::ParameterChange;
+
+    /// Proposal layout stored before version 6
+    #[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug)]
+    pub struct OldProposal<T: Config> {
+        pub id: u64,
+        pub proposer: T::AccountId,
+        pub title: ProposalTitle<T>,
+        pub description: ProposalDescription<T>,
+        pub created_at: BlockNumberFor<T>,
+        pub voting_start: BlockNumberFor<T>,
+        pub voting_end: BlockNumberFor<T>,
+        pub status: ProposalStatus,
+        pub votes_for: u64,
+        pub votes_against: u64,
+        pub total_votes: u64,
+        pub executed: bool,
+        pub executed_at: Option<BlockNumberFor<T>>,
+        pub deposit: BalanceOf<T>,
+        pub parameter_change: Option<ParameterChange>,
+        pub quorum: u64,
+    }
+
+    impl<T: Config> OldProposal<T> {
+        /// Upgrade to the version 6 layout without abstentions
+        pub fn upgrade(self) -> v7::OldProposal<T> {
+            v7::OldProposal {
+                id: self.id,
+                proposer: self.proposer,
+                title: self.title,
+                description: self.description,
+                created_at: self.created_at,
+                voting_start: self.voting_start,
+                voting_end: self.voting_end,
+                status: self.status,
+                votes_for: self.votes_for,
+                votes_against: self.votes_against,
+                abstentions: 0,
+                total_votes: self.total_votes,
+                executed: self.executed,
+                executed_at: self.executed_at,
+                deposit: self.deposit,
+                parameter_change: self.parameter_change,
+                quorum: self.quorum,
+            }
+        }
+    }
+
+    /// Proposal storage before version 6
+    #[storage_alias]
+    pub type Proposals<T: Config> =
+        StorageMap<Pallet<T>, Blake2_128Concat, u64, OldProposal<T>, OptionQuery>;
+
+    /// Run the migration if the on-chain storage version is 5
+    pub fn migrate<T: Config>() -> Weight {
+        let db = T::DbWeight::get();
+        if Pallet::<T>::on_chain_storage_version() != 5 {
+            return db.reads(1);
+        }
+
+        let mut translated = 0u64;
+
+        v7::Proposals::<T>::translate::<OldProposal<T>, _>(|_id, old| {
+            translated = translated.saturating_add(1);
+            Some(old.upgrade())
+        });
+
+        StorageVersion::new(6).put::<Pallet<T>>();
+
+        db.reads_writes(translated.saturating_add(1), translated.saturating_add(1))
+    }
+}
+
+/// Version 7: proposals may carry a call, stored proposals carry none.
+pub mod v7 {
+    use crate::{
+        migrations::v8, BalanceOf, Config, Pallet, ProposalDescription, ProposalStatus,
+        ProposalTitle,
+    };
+    use codec::{Decode, Encode};
+    use frame_support::{
+        pallet_prelude::*,
+        storage_alias,
+        traits::{GetStorageVersion, StorageVersion},
+    };
+    use frame_system::pallet_prelude::BlockNumberFor;
+    use tidygen_primitives::governance::ParameterChange;
+
+    /// Proposal layout stored before version 7
+    #[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug)]
+    pub struct OldProposal<T: Config> {
+        pub id: u64,
+        pub proposer: T::AccountId,
+        pub title: ProposalTitle<T>,
+        pub description: ProposalDescription<T>,
+        pub created_at: BlockNumberFor<T>,
+        pub voting_start: BlockNumberFor<T>,
+        pub voting_end: BlockNumberFor<T>,
+        pub status: ProposalStatus,
+        pub votes_for: u64,
+        pub votes_against: u64,
+        pub abstentions: u64,
+        pub total_votes: u64,
+        pub executed: bool,
+        pub executed_at: Option<BlockNumberFor<T>>,
+        pub deposit: BalanceOf<T>,
+        pub parameter_change: Option<ParameterChange>,
+        pub quorum: u64,
+    }
+
+    impl<T: Config> OldProposal<T> {
+        /// Upgrade to the version 7 layout without a call
+        pub fn upgrade(self) -> v8::OldProposal<T> {
+            v8::OldProposal {
+                id: self.id,
+                proposer: self.proposer,
+                title: self.title,
+                description: self.description,
+                created_at: self.created_at,
+                voting_start: self.voting_start,
+                voting_end: self.voting_end,
+                status: self.status,
+                votes_for: self.votes_for,
+                votes_against: self.votes_against,
+                abstentions: self.abstentions,
+                total_votes: self.total_votes,
+                executed: self.executed,
+                executed_at: self.executed_at,
+                deposit: self.deposit,
+                parameter_change: self.parameter_change,
+                quorum: self.quorum,
+                call_hash: None,
+            }
+        }
+    }
+
+    /// Proposal storage before version 7
+    #[storage_alias]
+    pub type Proposals<T: Config> =
+        StorageMap<Pallet<T>, Blake2_128Concat, u64, OldProposal<T>, OptionQuery>;
+
+    /// Run the migration if the on-chain storage version is 6
+    pub fn migrate<T: Config>() -> Weight {
+        let db = T::DbWeight::get();
+        if Pallet::<T>::on_chain_storage_version() != 6 {
+            return db.reads(1);
+        }
+
+        let mut translated = 0u64;
+
+        v8::Proposals::<T>::translate::<OldProposal<T>, _>(|_id, old| {
+            translated = translated.saturating_add(1);
+            Some(old.upgrade())
+        });
+
+        StorageVersion::new(7).put::<Pallet<T>>();
+
+        db.reads_writes(translated.saturating_add(1), translated.saturating_add(1))
+    }
+}
+
+/// Version 8: proposals are filed under a category, stored proposals under `General`.
+///
+/// Stored proposals are indexed in `ProposalsByCategory` in ID order.
+pub mod v8 {
+    use crate::{
+        migrations::v9, BalanceOf, Config, Pallet, ProposalCount, ProposalDescription,
+        ProposalStatus, ProposalTitle,
+    };
+    use codec::{Decode, Encode};
+    use frame_support::{
+        pallet_prelude::*,
+        storage_alias,
+        traits::{GetStorageVersion, StorageVersion},
+    };
+    use frame_system::pallet_prelude::BlockNumberFor;
+    use tidygen_primitives::governance::{ParameterChange, ProposalCategory};
+
+    /// Proposal layout stored before version 8
+    #[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug)]
+    pub struct OldProposal<T: Config> {
+        pub id: u64,
+        pub proposer: T::AccountId,
+        pub title: ProposalTitle<T>,
+        pub description: ProposalDescription<T>,
+        pub created_at: BlockNumberFor<T>,
+        pub voting_start: BlockNumberFor<T>,
+        pub voting_end: BlockNumberFor<T>,
+        pub status: ProposalStatus,
+        pub votes_for: u64,
+        pub votes_against: u64,
+        pub abstentions: u64,
+        pub total_votes: u64,
+        pub executed: bool,
+        pub executed_at: Option<BlockNumberFor<T>>,
+        pub deposit: BalanceOf<T>,
+        pub parameter_change: Option<ParameterChange>,
+        pub quorum: u64,
+        pub call_hash: Option<[u8; 32]>,
+    }
+
+    impl<T: Config> OldProposal<T> {
+        /// Upgrade to the version 8 layout under `General`
+        pub fn upgrade(self) -> v9::OldProposal<T> {
+            v9::OldProposal {
+                id: self.id,
+                proposer: self.proposer,
+                title: self.title,
+                description: self.description,
+                created_at: self.created_at,
+                voting_start: self.voting_start,
+                voting_end: self.voting_end,
+                status: self.status,
+                votes_for: self.votes_for,
+                votes_against: self.votes_against,
+                abstentions: self.abstentions,
+                total_votes: self.total_votes,
+                executed: self.executed,
+                executed_at: self.executed_at,
+                deposit: self.deposit,
+                parameter_change: self.parameter_change,
+                quorum: self.quorum,
+                call_hash: self.call_hash,
+                category: ProposalCategory::General,
+            }
+        }
+    }
+
+    /// Proposal storage before version 8
+    #[storage_alias]
+    pub type Proposals<T: Config> =
+        StorageMap<Pallet<T>, Blake2_128Concat, u64, OldProposal<T>, OptionQuery>;
+
+    /// Run the migration if the on-chain storage version is 7
+    pub fn migrate<T: Config>() -> Weight {
+        let db = T::DbWeight::get();
+        if Pallet::<T>::on_chain_storage_version() != 7 {
+            return db.reads(1);
+        }
+
+        let mut translated = 0u64;
+
+        v9::Proposals::<T>::translate::<OldProposal<T>, _>(|_id, old| {
+            translated = translated.saturating_add(1);
+            Some(old.upgrade())
+        });
+
+        // The map iterates in hash order, so index by ID to keep creation order
+        let count = ProposalCount::<T>::get();
+        for proposal_id in 0..count {
+            if crate::Proposals::<T>::contains_key(proposal_id) {
+                Pallet::<T>::index_by_category(ProposalCategory::General, proposal_id);
+            }
+        }
+
+        StorageVersion::new(8).put::<Pallet<T>>();
+
+        db.reads_writes(
+            translated.saturating_add(count).saturating_add(2),
+            translated.saturating_mul(2).saturating_add(2),
+        )
+    }
+}
+
+/// Version 9: proposals count the amendments of their text, stored proposals none.
+pub mod v9 {
+    use crate::{
+        BalanceOf, Config, Pallet, Proposal, ProposalDescription, ProposalStatus, ProposalTitle,
+    };
+    use codec::{Decode, Encode};
+    use frame_support::{
+        pallet_prelude::*,
+        storage_alias,
+        traits::{GetStorageVersion, StorageVersion},
+    };
+    use frame_system::pallet_prelude::BlockNumberFor;
+    use tidygen_primitives::governance::{ParameterChange, ProposalCategory};
+
+    /// Proposal layout stored before version 9
+    #[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug)]
+    pub struct OldProposal<T: Config> {
+        pub id: u64,
+        pub proposer: T::AccountId,
+        pub title: ProposalTitle<T>,
+        pub description: ProposalDescription<T>,
+        pub created_at: BlockNumberFor<T>,
+        pub voting_start: BlockNumberFor<T>,
+        pub voting_end: BlockNumberFor<T>,
+        pub status: ProposalStatus,
+        pub votes_for: u64,
+        pub votes_against: u64,
+        pub abstentions: u64,
+        pub total_votes: u64,
+        pub executed: bool,
+        pub executed_at: Option<BlockNumberFor<T>>,
+        pub deposit: BalanceOf<T>,
+        pub parameter_change: Option<ParameterChange>,
+        pub quorum: u64,
+        pub call_hash: Option<[u8; 32]>,
+        pub category: ProposalCategory,
+    }
+
+    impl<T: Config> OldProposal<T> {
+        /// Upgrade to the current layout, not amended
+        pub fn upgrade(self) -> Proposal<T> {
+            Proposal {
+                id: self.id,
+                proposer: self.proposer,
+                title: self.title,
+                description: self.description,
+                created_at: self.created_at,
+                voting_start: self.voting_start,
+                voting_end: self.voting_end,
+                status: self.status,
+                votes_for: self.votes_for,
+                votes_against: self.votes_against,
+                abstentions: self.abstentions,
+                total_votes: self.total_votes,
+                executed: self.executed,
+                executed_at: self.executed_at,
+                deposit: self.deposit,
+                parameter_change: self.parameter_change,
+                quorum: self.quorum,
+                call_hash: self.call_hash,
+                category: self.category,
+                revision: 0,
+            }
+        }
+    }
+
+    /// Proposal storage before version 9
+    #[storage_alias]
+    pub type Proposals<T: Config> =
+        StorageMap<Pallet<T>, Blake2_128Concat, u64, OldProposal<T>, OptionQuery>;
+
+    /// Run the migration if the on-chain storage version is 8
+    pub fn migrate<T: Config>() -> Weight {
+        let db = T::DbWeight::get();
+        if Pallet::<T>::on_chain_storage_version() != 8 {
+            return db.reads(1);
+        }
+
+        let mut translated = 0u64;
+
+        crate::Proposals::<T>::translate::<OldProposal<T>, _>(|_id, old| {
+            translated = translated.saturating_add(1);
+            Some(old.upgrade())
+        });
+
+        StorageVersion::new(9).put::<Pallet<T>>();
+
+        db.reads_writes(translated.saturating_add(1), translated.saturating_add(1))
+    }
+}
+
+/// Version 10: `CarriedDelegators` indexes the delegators each vote carries, and
+/// `DelegatorHeights` counts the direct delegators of each account by delegation height.
+///
+/// The indexes are built from `DelegatedVotes` and from `Delegations` and `Delegators`.
+pub mod v10 {
+    use crate::{
+        CarriedDelegators, Config, DelegatedVotes, Delegations, DelegatorHeights, Delegators,
+        Pallet,
+    };
+    use frame_support::{
+        pallet_prelude::*,
+        traits::{GetStorageVersion, StorageVersion},
+    };
+    use sp_std::collections::btree_map::BTreeMap;
+
+    /// Delegations in the longest chain ending at `who`, remembering those already known
+    fn height<T: Config>(who: &T::AccountId, known: &mut BTreeMap<T::AccountId, u32>) -> u32 {
+        if let Some(height) = known.get(who) {
+            return *height;
+        }
+        let height = Delegators::<T>::get(who)
+            .iter()
+            .map(|delegator| height::<T>(delegator, known).saturating_add(1))
+            .max()
+            .unwrap_or(0);
+        known.insert(who.clone(), height);
+        height
+    }
+
+    /// Run the migration if the on-chain storage version is 9
+    pub fn migrate<T: Config>() -> Weight {
+        let db = T::DbWeight::get();
+        if Pallet::<T>::on_chain_storage_version() != 9 {
+            return db.reads(1);
+        }
+
+        let mut carried = 0u64;
+
+        for (proposal_id, delegator, (delegate, _)) in DelegatedVotes::<T>::iter() {
+            // A vote carries at most `MaxDelegators`, its delegate's direct delegators
+            let _ = CarriedDelegators::<T>::try_mutate(proposal_id, &delegate, |list| {
+                list.try_push(delegator)
+            });
+            carried = carried.saturating_add(1);
+        }
+
+        // Existing chains are acyclic and at most `MaxDelegationDepth` long
+        let mut delegations = 0u64;
+        let mut known = BTreeMap::new();
+        for (delegator, delegate) in Delegations::<T>::iter() {
+            let height = height::<T>(&delegator, &mut known) as usize;
+            DelegatorHeights::<T>::mutate(&delegate, |counts| {
+                let mut list = counts.to_vec();
+                if list.len() <= height {
+                    list.resize(height + 1, 0);
+                }
+                list[height] = list[height].saturating_add(1);
+                *counts = BoundedVec::truncate_from(list);
+            });
+            delegations = delegations.saturating_add(1);
+        }
+
+        StorageVersion::new(10).put::<Pallet<T>>();
+
+        let reads = carried.saturating_mul(2).saturating_add(delegations.saturating_mul(3));
+        let writes = carried.saturating_add(delegations);
+        db.reads_writes(reads.saturating_add(1), writes.saturating_add(1))
+    }
+}
+
+/// Version 11: `AutoCloseCursor` starts at the earliest block in `ProposalsEndingAt`.
+///
+/// Without a cursor `on_initialize` starts at the current block, and would never close the
+/// proposals indexed at earlier blocks.
+pub mod v11 {
+    use crate::{AutoCloseCursor, Config, Pallet, ProposalsEndingAt};
+    use frame_support::{
+        pallet_prelude::*,
+        traits::{GetStorageVersion, StorageVersion},
+    };
+
+    /// Run the migration if the on-chain storage version is 10
+    pub fn migrate<T: Config>() -> Weight {
+        let db = T::DbWeight::get();
+        if Pallet::<T>::on_chain_storage_version() != 10 {
+            return db.reads(1);
+        }
+
+        let mut blocks = 0u64;
+        let mut writes = 1u64;
+        if !AutoCloseCursor::<T>::exists() {
+            let earliest = ProposalsEndingAt::<T>::iter_keys()
+                .inspect(|_| blocks = blocks.saturating_add(1))
+                .min();
+            if let Some(block) = earliest {
+                AutoCloseCursor::<T>::put(block);
+                writes = writes.saturating_add(1);
+            }
+        }
+
+        StorageVersion::new(11).put::<Pallet<T>>();
+
+        db.reads_writes(blocks.saturating_add(2), writes)
+    }
+}
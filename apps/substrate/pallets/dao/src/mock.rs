@@ -1,20 +1,27 @@
 use crate as pallet_dao;
 use frame_support::{
     parameter_types,
-    traits::{ConstU32, ConstU64},
+    traits::{ConstBool, ConstU128, ConstU32, ConstU64},
+    weights::{constants::RocksDbWeight, Weight},
+    PalletId,
 };
 use sp_core::H256;
 use sp_runtime::{
+    testing::TestXt,
     traits::{BlakeTwo256, IdentityLookup},
-    BuildStorage,
+    BuildStorage, Perbill,
 };
 
 type Block = frame_system::mocking::MockBlock<Test>;
 
+/// Extrinsic type used for unsigned transactions submitted by the offchain worker
+pub type Extrinsic = TestXt<RuntimeCall, ()>;
+
 // Configure a mock runtime to test the pallet
 frame_support::construct_runtime!(
     pub enum Test {
         System: frame_system,
+        Balances: pallet_balances,
         Dao: pallet_dao,
     }
 );
@@ -28,7 +35,7 @@ impl frame_system::Config for Test {
     type BaseCallFilter = frame_support::traits::Everything;
     type BlockWeights = ();
     type BlockLength = ();
-    type DbWeight = ();
+    type DbWeight = RocksDbWeight;
     type RuntimeOrigin = RuntimeOrigin;
     type RuntimeCall = RuntimeCall;
     type Nonce = u64;
@@ -41,7 +48,7 @@ impl frame_system::Config for Test {
     type BlockHashCount = BlockHashCount;
     type Version = ();
     type PalletInfo = PalletInfo;
-    type AccountData = ();
+    type AccountData = pallet_balances::AccountData<u128>;
     type OnNewAccount = ();
     type OnKilledAccount = ();
     type SystemWeightInfo = ();
@@ -50,28 +57,111 @@ impl frame_system::Config for Test {
     type MaxConsumers = ConstU32<16>;
 }
 
+impl<C> frame_system::offchain::SendTransactionTypes<C> for Test
+where
+    RuntimeCall: From<C>,
+{
+    type OverarchingCall = RuntimeCall;
+    type Extrinsic = Extrinsic;
+}
+
+impl pallet_balances::Config for Test {
+    type MaxLocks = ConstU32<50>;
+    type MaxReserves = ();
+    type ReserveIdentifier = [u8; 8];
+    type Balance = u128;
+    type RuntimeEvent = RuntimeEvent;
+    type DustRemoval = ();
+    type ExistentialDeposit = ConstU128<1>;
+    type AccountStore = System;
+    type WeightInfo = ();
+    type FreezeIdentifier = ();
+    type MaxFreezes = ();
+    type RuntimeHoldReason = ();
+    type RuntimeFreezeReason = ();
+    type MaxHolds = ();
+}
+
 parameter_types! {
     pub const MaxTitleLength: u32 = 256;
     pub const MaxDescriptionLength: u32 = 2048;
     pub const MinVotingPeriod: u64 = 10;
     pub const MaxVotingPeriod: u64 = 1000;
-    pub const ProposalDeposit: u128 = 1000;
+    // Static so tests can change it mid-lifecycle
+    pub static ProposalDeposit: u128 = 1000;
+    // Static so tests can raise it
+    pub static MinQuorum: u64 = 1;
+    // Static so tests can turn slashing on
+    pub static SlashRejectedDeposits: bool = false;
+    pub const DaoPalletId: PalletId = PalletId(*b"py/tgdao");
+    // Static so tests can lower it
+    pub static MaxCallWeight: Weight = Weight::from_parts(1_000_000_000_000, 1024 * 1024);
+    // Static so tests can shrink the budget
+    pub static AutoCloseWeight: Weight = Weight::from_parts(1_000_000_000_000, 1024 * 1024);
+    // Static so tests can restrict voting to members
+    pub static MembersOnly: bool = false;
+    pub const MemberQuorum: Perbill = Perbill::from_percent(50);
+    pub const VoteUnit: u128 = 1_000;
 }
 
 impl pallet_dao::Config for Test {
     type RuntimeEvent = RuntimeEvent;
-    type Currency = ();
+    type Currency = Balances;
     type MaxTitleLength = MaxTitleLength;
     type MaxDescriptionLength = MaxDescriptionLength;
     type MinVotingPeriod = MinVotingPeriod;
     type MaxVotingPeriod = MaxVotingPeriod;
     type ProposalDeposit = ProposalDeposit;
+    type MaxProposalsPerBlock = ConstU32<16>;
+    type MinQuorum = MinQuorum;
+    type OffchainAutoClose = ConstBool<true>;
+    type UnsignedPriority = ConstU64<100>;
+    type MaxReceiptsPerAccount = ConstU32<3>;
+    type AdminOrigin = frame_system::EnsureRoot<u64>;
+    type MaxVouchersPerProposal = ConstU32<4>;
+    // Only the DAO's own parameters, so ledger changes have no handler
+    type Parameters = Dao;
+    type MaxDelegationDepth = ConstU32<3>;
+    type MaxDelegators = ConstU32<4>;
+    type OnTransition = ();
+    type SlashRejectedDeposits = SlashRejectedDeposits;
+    type MinRefundApproval = ConstU32<20>;
+    // Slashed deposits are burned
+    type Slashed = ();
+    type RuntimeCall = RuntimeCall;
+    type ProposalOrigin = pallet_dao::PotOrigin<Test>;
+    type PalletId = DaoPalletId;
+    type MaxCallSize = ConstU32<256>;
+    type MaxCallWeight = MaxCallWeight;
+    type AutoCloseWeight = AutoCloseWeight;
+    type MembershipOrigin = frame_system::EnsureRoot<u64>;
+    type MembersOnly = MembersOnly;
+    type MemberQuorum = MemberQuorum;
+    type VoteUnit = VoteUnit;
+    type WeightInfo = ();
 }
 
+/// Initial balance of every endowed test account
+pub const INITIAL_BALANCE: u128 = 10_000;
+
 // Build genesis storage
 pub fn new_test_ext() -> sp_io::TestExternalities {
-    frame_system::GenesisConfig::<Test>::default()
+    let mut t = frame_system::GenesisConfig::<Test>::default()
         .build_storage()
-        .unwrap()
-        .into()
+        .unwrap();
+
+    pallet_balances::GenesisConfig::<Test> {
+        balances: (1..=10).map(|who| (who, INITIAL_BALANCE)).collect(),
+    }
+    .assimilate_storage(&mut t)
+    .unwrap();
+
+    pallet_dao::GenesisConfig::<Test>::default()
+        .assimilate_storage(&mut t)
+        .unwrap();
+
+    let mut ext: sp_io::TestExternalities = t.into();
+    // Events are not recorded at genesis, start at block 1
+    ext.execute_with(|| System::set_block_number(1));
+    ext
 }
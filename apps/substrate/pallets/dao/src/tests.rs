@@ -1,5 +1,12 @@
-use crate::{mock::*, Error, Event, ProposalStatus};
+use crate::{
+    mock::*, Error, Event, ProposalStatus, VoteKind, VoteReceipt, Votes, PROPOSAL_STATUS,
+};
 use frame_support::{assert_noop, assert_ok};
+use tidygen_primitives::{
+    features::FeatureFlags,
+    governance::{ParameterChange, ProposalCategory},
+    pagination::{InvalidCursor, PageCursor},
+};
 
 #[test]
 fn create_proposal_works() {
@@ -36,6 +43,7 @@ fn create_proposal_works() {
                 proposal_id: 0,
                 proposer,
                 title,
+                deposit: 1000,
             }
             .into(),
         );
@@ -57,10 +65,10 @@ fn vote_in_favor_works() {
         ));
 
         // Vote in favor
-        assert_ok!(Dao::vote(RuntimeOrigin::signed(voter), 0, true));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(voter), 0, VoteKind::Aye));
 
         // Verify vote recorded
-        assert_eq!(Dao::get_vote(0, &voter), Some(true));
+        assert_eq!(Dao::get_vote(0, &voter), Some(VoteKind::Aye));
         assert!(Dao::has_account_voted(0, &voter));
 
         // Verify vote counts
@@ -74,7 +82,8 @@ fn vote_in_favor_works() {
             Event::VoteCast {
                 proposal_id: 0,
                 voter,
-                in_favor: true,
+                vote: VoteKind::Aye,
+                weight: 1,
             }
             .into(),
         );
@@ -96,10 +105,10 @@ fn vote_against_works() {
         ));
 
         // Vote against
-        assert_ok!(Dao::vote(RuntimeOrigin::signed(voter), 0, false));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(voter), 0, VoteKind::Nay));
 
         // Verify vote recorded
-        assert_eq!(Dao::get_vote(0, &voter), Some(false));
+        assert_eq!(Dao::get_vote(0, &voter), Some(VoteKind::Nay));
 
         // Verify vote counts
         let proposal = Dao::get_proposal_details(0).unwrap();
@@ -123,10 +132,10 @@ fn multiple_votes_work() {
         ));
 
         // Multiple voters
-        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 0, true));
-        assert_ok!(Dao::vote(RuntimeOrigin::signed(3), 0, true));
-        assert_ok!(Dao::vote(RuntimeOrigin::signed(4), 0, false));
-        assert_ok!(Dao::vote(RuntimeOrigin::signed(5), 0, true));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 0, VoteKind::Aye));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(3), 0, VoteKind::Aye));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(4), 0, VoteKind::Nay));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(5), 0, VoteKind::Aye));
 
         // Verify vote counts
         let proposal = Dao::get_proposal_details(0).unwrap();
@@ -152,11 +161,11 @@ fn cannot_vote_twice() {
         ));
 
         // First vote
-        assert_ok!(Dao::vote(RuntimeOrigin::signed(voter), 0, true));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(voter), 0, VoteKind::Aye));
 
         // Second vote should fail
         assert_noop!(
-            Dao::vote(RuntimeOrigin::signed(voter), 0, false),
+            Dao::vote(RuntimeOrigin::signed(voter), 0, VoteKind::Nay),
             Error::<Test>::AlreadyVoted
         );
     });
@@ -177,10 +186,10 @@ fn execute_approved_proposal_works() {
         ));
 
         // Cast votes (3 for, 1 against)
-        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 0, true));
-        assert_ok!(Dao::vote(RuntimeOrigin::signed(3), 0, true));
-        assert_ok!(Dao::vote(RuntimeOrigin::signed(4), 0, false));
-        assert_ok!(Dao::vote(RuntimeOrigin::signed(5), 0, true));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 0, VoteKind::Aye));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(3), 0, VoteKind::Aye));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(4), 0, VoteKind::Nay));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(5), 0, VoteKind::Aye));
 
         // Advance blocks past voting period
         System::set_block_number(11);
@@ -219,7 +228,7 @@ fn cannot_execute_before_voting_ends() {
         ));
 
         // Vote
-        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 0, true));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 0, VoteKind::Aye));
 
         // Try to execute before voting ends (current block = 1)
         assert_noop!(
@@ -243,9 +252,9 @@ fn cannot_execute_rejected_proposal() {
         ));
 
         // Vote against (2 against, 1 for)
-        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 0, false));
-        assert_ok!(Dao::vote(RuntimeOrigin::signed(3), 0, false));
-        assert_ok!(Dao::vote(RuntimeOrigin::signed(4), 0, true));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 0, VoteKind::Nay));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(3), 0, VoteKind::Nay));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(4), 0, VoteKind::Aye));
 
         // Advance blocks
         System::set_block_number(11);
@@ -272,9 +281,9 @@ fn close_proposal_works() {
         ));
 
         // Vote (2 for, 1 against = approved)
-        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 0, true));
-        assert_ok!(Dao::vote(RuntimeOrigin::signed(3), 0, true));
-        assert_ok!(Dao::vote(RuntimeOrigin::signed(4), 0, false));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 0, VoteKind::Aye));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(3), 0, VoteKind::Aye));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(4), 0, VoteKind::Nay));
 
         // Advance blocks
         System::set_block_number(11);
@@ -291,6 +300,7 @@ fn close_proposal_works() {
             Event::VotingEnded {
                 proposal_id: 0,
                 approved: true,
+                quorum_reached: true,
             }
             .into(),
         );
@@ -305,6 +315,160 @@ fn close_proposal_works() {
     });
 }
 
+#[test]
+fn proposal_exactly_at_quorum_passes() {
+    new_test_ext().execute_with(|| {
+        MinQuorum::set(3);
+        create_with_period(1, 10);
+        assert_eq!(Dao::get_proposal_details(0).unwrap().quorum, 3);
+
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 0, VoteKind::Aye));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(3), 0, VoteKind::Aye));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(4), 0, VoteKind::Nay));
+
+        System::set_block_number(11);
+        assert_ok!(Dao::close_proposal(RuntimeOrigin::signed(5), 0));
+        assert_eq!(Dao::get_proposal_details(0).unwrap().status, ProposalStatus::Approved);
+        System::assert_has_event(
+            Event::VotingEnded { proposal_id: 0, approved: true, quorum_reached: true }.into(),
+        );
+        assert_ok!(Dao::execute_proposal(RuntimeOrigin::signed(1), 0));
+    });
+}
+
+#[test]
+fn unanimous_proposal_below_quorum_is_rejected() {
+    new_test_ext().execute_with(|| {
+        MinQuorum::set(3);
+        create_with_period(1, 10);
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 0, VoteKind::Aye));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(3), 0, VoteKind::Aye));
+
+        System::set_block_number(11);
+        // Not executable straight from `Active` either
+        assert_noop!(
+            Dao::execute_proposal(RuntimeOrigin::signed(1), 0),
+            Error::<Test>::ProposalNotApproved
+        );
+
+        assert_ok!(Dao::close_proposal(RuntimeOrigin::signed(5), 0));
+        assert_eq!(Dao::get_proposal_details(0).unwrap().status, ProposalStatus::Rejected);
+        System::assert_has_event(
+            Event::VotingEnded { proposal_id: 0, approved: false, quorum_reached: false }.into(),
+        );
+        assert_noop!(
+            Dao::execute_proposal(RuntimeOrigin::signed(1), 0),
+            Error::<Test>::ProposalNotApproved
+        );
+    });
+}
+
+#[test]
+fn proposal_may_require_more_than_the_minimum_quorum() {
+    new_test_ext().execute_with(|| {
+        MinQuorum::set(2);
+        assert_noop!(
+            Dao::create_proposal_with_quorum(
+                RuntimeOrigin::signed(1),
+                b"Charter change".to_vec(),
+                b"Description".to_vec(),
+                Some(10),
+                1
+            ),
+            Error::<Test>::QuorumBelowMinimum
+        );
+        assert_ok!(Dao::create_proposal_with_quorum(
+            RuntimeOrigin::signed(1),
+            b"Charter change".to_vec(),
+            b"Description".to_vec(),
+            Some(10),
+            4
+        ));
+        assert_eq!(Dao::get_proposal_details(0).unwrap().quorum, 4);
+
+        // Enough for `MinQuorum`, not for this proposal
+        for voter in 2..=4 {
+            assert_ok!(Dao::vote(RuntimeOrigin::signed(voter), 0, VoteKind::Aye));
+        }
+        System::set_block_number(11);
+        assert_ok!(Dao::close_proposal(RuntimeOrigin::signed(5), 0));
+        System::assert_has_event(
+            Event::VotingEnded { proposal_id: 0, approved: false, quorum_reached: false }.into(),
+        );
+    });
+}
+
+#[test]
+fn abstentions_reach_quorum_without_carrying_the_proposal() {
+    new_test_ext().execute_with(|| {
+        MinQuorum::set(3);
+        create_with_period(1, 10);
+        for voter in 2..=4 {
+            assert_ok!(Dao::vote(RuntimeOrigin::signed(voter), 0, VoteKind::Abstain));
+        }
+        System::assert_last_event(
+            Event::VoteCast { proposal_id: 0, voter: 4, vote: VoteKind::Abstain, weight: 1 }
+                .into(),
+        );
+        assert_eq!(Dao::get_vote(0, &2), Some(VoteKind::Abstain));
+
+        let proposal = Dao::get_proposal_details(0).unwrap();
+        assert_eq!((proposal.votes_for, proposal.votes_against), (0, 0));
+        assert_eq!((proposal.abstentions, proposal.total_votes), (3, 3));
+        assert!(proposal.is_quorum_reached());
+        assert_eq!(proposal.approval_percentage(), 0);
+
+        System::set_block_number(11);
+        assert_ok!(Dao::close_proposal(RuntimeOrigin::signed(5), 0));
+        assert_eq!(Dao::get_proposal_details(0).unwrap().status, ProposalStatus::Rejected);
+        System::assert_has_event(
+            Event::VotingEnded { proposal_id: 0, approved: false, quorum_reached: true }.into(),
+        );
+    });
+}
+
+#[test]
+fn abstentions_help_quorum_but_not_the_majority() {
+    new_test_ext().execute_with(|| {
+        MinQuorum::set(5);
+        create_with_period(1, 10);
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 0, VoteKind::Aye));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(3), 0, VoteKind::Aye));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(4), 0, VoteKind::Nay));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(5), 0, VoteKind::Abstain));
+        assert!(!Dao::get_proposal_details(0).unwrap().is_quorum_reached());
+        assert_ok!(Dao::vote_with_salt(RuntimeOrigin::signed(6), 0, VoteKind::Abstain, [7; 32]));
+
+        let proposal = Dao::get_proposal_details(0).unwrap();
+        assert_eq!((proposal.abstentions, proposal.total_votes), (2, 5));
+        // Two ayes of three decisive votes
+        assert_eq!(proposal.approval_percentage(), 66);
+        assert!(proposal.is_approved());
+
+        System::set_block_number(11);
+        assert_ok!(Dao::execute_proposal(RuntimeOrigin::signed(1), 0));
+    });
+}
+
+#[test]
+fn votes_stored_as_bool_keep_their_meaning() {
+    use codec::{Decode, Encode};
+
+    new_test_ext().execute_with(|| {
+        assert_eq!(VoteKind::decode(&mut &true.encode()[..]), Ok(VoteKind::Aye));
+        assert_eq!(VoteKind::decode(&mut &false.encode()[..]), Ok(VoteKind::Nay));
+        // Receipts minted for `bool` votes commit to the same hash
+        let mut data = true.encode();
+        data.extend_from_slice(&[3; 32]);
+        assert_eq!(Dao::hash_choice(VoteKind::Aye, &[3; 32]), sp_io::hashing::blake2_256(&data));
+
+        // As `Votes` stored them before abstentions
+        create_with_period(1, 10);
+        frame_support::storage::unhashed::put(&Votes::<Test>::hashed_key_for(0, 2), &false);
+        assert_eq!(Dao::get_vote(0, &2), Some(VoteKind::Nay));
+    });
+}
+
 #[test]
 fn close_rejected_proposal_works() {
     new_test_ext().execute_with(|| {
@@ -319,9 +483,9 @@ fn close_rejected_proposal_works() {
         ));
 
         // Vote (1 for, 2 against = rejected)
-        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 0, true));
-        assert_ok!(Dao::vote(RuntimeOrigin::signed(3), 0, false));
-        assert_ok!(Dao::vote(RuntimeOrigin::signed(4), 0, false));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 0, VoteKind::Aye));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(3), 0, VoteKind::Nay));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(4), 0, VoteKind::Nay));
 
         // Advance blocks
         System::set_block_number(11);
@@ -351,11 +515,11 @@ fn full_proposal_lifecycle_approved() {
         assert_eq!(proposal.status, ProposalStatus::Active);
 
         // Phase 2: Voting
-        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 0, true));
-        assert_ok!(Dao::vote(RuntimeOrigin::signed(3), 0, true));
-        assert_ok!(Dao::vote(RuntimeOrigin::signed(4), 0, true));
-        assert_ok!(Dao::vote(RuntimeOrigin::signed(5), 0, false));
-        assert_ok!(Dao::vote(RuntimeOrigin::signed(6), 0, true));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 0, VoteKind::Aye));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(3), 0, VoteKind::Aye));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(4), 0, VoteKind::Aye));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(5), 0, VoteKind::Nay));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(6), 0, VoteKind::Aye));
 
         // Verify votes (4 for, 1 against)
         let proposal = Dao::get_proposal_details(0).unwrap();
@@ -392,10 +556,10 @@ fn full_proposal_lifecycle_rejected() {
         ));
 
         // Voting (1 for, 3 against = rejected)
-        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 0, true));
-        assert_ok!(Dao::vote(RuntimeOrigin::signed(3), 0, false));
-        assert_ok!(Dao::vote(RuntimeOrigin::signed(4), 0, false));
-        assert_ok!(Dao::vote(RuntimeOrigin::signed(5), 0, false));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 0, VoteKind::Aye));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(3), 0, VoteKind::Nay));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(4), 0, VoteKind::Nay));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(5), 0, VoteKind::Nay));
 
         // Close voting
         System::set_block_number(16);
@@ -447,7 +611,7 @@ fn cannot_vote_on_nonexistent_proposal() {
 
         // Try to vote on non-existent proposal
         assert_noop!(
-            Dao::vote(RuntimeOrigin::signed(voter), 999, true),
+            Dao::vote(RuntimeOrigin::signed(voter), 999, VoteKind::Aye),
             Error::<Test>::ProposalNotFound
         );
     });
@@ -468,10 +632,10 @@ fn approval_percentage_calculation_works() {
 
         // Cast votes (6 for, 4 against = 60% approval)
         for i in 2..8 {
-            assert_ok!(Dao::vote(RuntimeOrigin::signed(i), 0, true));
+            assert_ok!(Dao::vote(RuntimeOrigin::signed(i), 0, VoteKind::Aye));
         }
         for i in 8..12 {
-            assert_ok!(Dao::vote(RuntimeOrigin::signed(i), 0, false));
+            assert_ok!(Dao::vote(RuntimeOrigin::signed(i), 0, VoteKind::Nay));
         }
 
         let proposal = Dao::get_proposal_details(0).unwrap();
@@ -536,8 +700,8 @@ fn cannot_execute_twice() {
             Some(10)
         ));
 
-        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 0, true));
-        assert_ok!(Dao::vote(RuntimeOrigin::signed(3), 0, true));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 0, VoteKind::Aye));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(3), 0, VoteKind::Aye));
 
         System::set_block_number(11);
 
@@ -640,7 +804,7 @@ fn unanimous_approval_works() {
 
         // All vote in favor
         for i in 2..10 {
-            assert_ok!(Dao::vote(RuntimeOrigin::signed(i), 0, true));
+            assert_ok!(Dao::vote(RuntimeOrigin::signed(i), 0, VoteKind::Aye));
         }
 
         let proposal = Dao::get_proposal_details(0).unwrap();
@@ -665,10 +829,10 @@ fn tie_vote_rejects_proposal() {
         ));
 
         // Equal votes (2 for, 2 against)
-        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 0, true));
-        assert_ok!(Dao::vote(RuntimeOrigin::signed(3), 0, true));
-        assert_ok!(Dao::vote(RuntimeOrigin::signed(4), 0, false));
-        assert_ok!(Dao::vote(RuntimeOrigin::signed(5), 0, false));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 0, VoteKind::Aye));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(3), 0, VoteKind::Aye));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(4), 0, VoteKind::Nay));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(5), 0, VoteKind::Nay));
 
         let proposal = Dao::get_proposal_details(0).unwrap();
         assert!(!proposal.is_approved()); // Tie means not approved (needs majority)
@@ -696,22 +860,2419 @@ fn events_are_emitted_correctly() {
                 proposal_id: 0,
                 proposer,
                 title,
+                deposit: 1000,
             }
             .into(),
         );
 
         // Vote
-        assert_ok!(Dao::vote(RuntimeOrigin::signed(voter), 0, true));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(voter), 0, VoteKind::Aye));
 
         // Check VoteCast event
         System::assert_has_event(
             Event::VoteCast {
                 proposal_id: 0,
                 voter,
-                in_favor: true,
+                vote: VoteKind::Aye,
+                weight: 1,
+            }
+            .into(),
+        );
+    });
+}
+
+
+#[test]
+fn crlf_text_is_normalized() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Dao::create_proposal(
+            RuntimeOrigin::signed(1),
+            b"Budget\r\nQ4".to_vec(),
+            b"Line 1\r\nLine 2\r\n".to_vec(),
+            None
+        ));
+        assert_ok!(Dao::create_proposal(
+            RuntimeOrigin::signed(1),
+            b"Budget\nQ4".to_vec(),
+            b"Line 1\nLine 2\n".to_vec(),
+            None
+        ));
+
+        let crlf = Dao::get_proposal_details(0).unwrap();
+        let lf = Dao::get_proposal_details(1).unwrap();
+        assert_eq!(crlf.title, lf.title);
+        assert_eq!(crlf.description, lf.description);
+        assert_eq!(crlf.description.to_vec(), b"Line 1\nLine 2\n".to_vec());
+    });
+}
+
+#[test]
+fn invalid_utf8_title_fails() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Dao::create_proposal(
+                RuntimeOrigin::signed(1),
+                vec![0xc3, 0x28],
+                b"Description".to_vec(),
+                None
+            ),
+            Error::<Test>::InvalidUtf8
+        );
+    });
+}
+
+#[test]
+fn text_normalization_can_be_disabled() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Dao::set_text_normalization(RuntimeOrigin::root(), false));
+        assert!(!Dao::normalize_text());
+
+        assert_ok!(Dao::create_proposal(
+            RuntimeOrigin::signed(1),
+            b"Title\r\n".to_vec(),
+            b"Description".to_vec(),
+            None
+        ));
+        assert_eq!(Dao::get_proposal_details(0).unwrap().title.to_vec(), b"Title\r\n".to_vec());
+
+        // Text must be UTF-8 either way
+        assert_noop!(
+            Dao::create_proposal(
+                RuntimeOrigin::signed(1),
+                b"Title".to_vec(),
+                vec![0xc3, 0x28],
+                None
+            ),
+            Error::<Test>::InvalidUtf8
+        );
+    });
+}
+
+#[test]
+fn cancelled_proposal_cannot_be_closed_or_executed() {
+    new_test_ext().execute_with(|| {
+        let proposer = 1u64;
+
+        // Approving votes, then cancelled before voting ends
+        assert_ok!(Dao::create_proposal(
+            RuntimeOrigin::signed(proposer),
+            b"Test".to_vec(),
+            b"Test".to_vec(),
+            Some(10)
+        ));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 0, VoteKind::Aye));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(3), 0, VoteKind::Aye));
+        assert_ok!(Dao::cancel_proposal(RuntimeOrigin::signed(proposer), 0));
+
+        System::set_block_number(11);
+
+        assert_noop!(
+            Dao::close_proposal(RuntimeOrigin::signed(5), 0),
+            Error::<Test>::ProposalNotActive
+        );
+        assert_noop!(
+            Dao::execute_proposal(RuntimeOrigin::signed(proposer), 0),
+            Error::<Test>::InvalidStatusTransition
+        );
+    });
+}
+
+#[test]
+fn proposal_status_table_terminal_states_have_no_exits() {
+    let all = [
+        ProposalStatus::Active,
+        ProposalStatus::Approved,
+        ProposalStatus::Rejected,
+        ProposalStatus::Executed,
+        ProposalStatus::Cancelled,
+        ProposalStatus::Expired,
+        ProposalStatus::ExecutionFailed,
+    ];
+    let terminal = [
+        ProposalStatus::Rejected,
+        ProposalStatus::Executed,
+        ProposalStatus::Cancelled,
+        ProposalStatus::Expired,
+        ProposalStatus::ExecutionFailed,
+    ];
+
+    for state in &terminal {
+        assert!(PROPOSAL_STATUS.is_terminal(state));
+        for to in &all {
+            assert!(!PROPOSAL_STATUS.can_transition(state, to));
+        }
+    }
+    for state in &all {
+        assert!(!PROPOSAL_STATUS.can_transition(state, state));
+    }
+}
+
+#[test]
+fn export_snapshot_pages_and_digest_match() {
+    use codec::Encode;
+
+    new_test_ext().execute_with(|| {
+        // Proposals 0..5; 1 gets cancelled, 3 gets votes
+        for _ in 0..5 {
+            assert_ok!(Dao::create_proposal(
+                RuntimeOrigin::signed(1),
+                b"Test".to_vec(),
+                b"Test".to_vec(),
+                Some(10)
+            ));
+        }
+        assert_ok!(Dao::cancel_proposal(RuntimeOrigin::signed(1), 1));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 3, VoteKind::Aye));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(3), 3, VoteKind::Nay));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(4), 3, VoteKind::Aye));
+
+        // First page skips the cancelled proposal
+        let first = Dao::export_snapshot(2, None).unwrap();
+        assert_eq!(first.entries.iter().map(|e| e.id).collect::<Vec<_>>(), vec![0, 2]);
+        assert_eq!(first.next_cursor, Some(PageCursor::new(&3u64)));
+        assert_eq!(first.digest, None);
+
+        let second = Dao::export_snapshot(2, first.next_cursor.as_ref()).unwrap();
+        assert_eq!(second.entries.iter().map(|e| e.id).collect::<Vec<_>>(), vec![3, 4]);
+        assert_eq!(second.next_cursor, None);
+
+        // Tallies and deadlines are exported
+        let tallied = &second.entries[0];
+        assert_eq!((tallied.votes_for, tallied.votes_against, tallied.total_votes), (2, 1, 3));
+        assert_eq!(tallied.approval_percentage, 66);
+        assert!(tallied.threshold_met);
+        assert_eq!(tallied.voting_end, Dao::get_proposal_details(3).unwrap().voting_end);
+
+        // The archive digest matches the on-chain digest
+        let mut archive = first.entries;
+        archive.extend(second.entries);
+        let digest = sp_io::hashing::blake2_256(&archive.encode());
+        assert_eq!(second.digest, Some(digest));
+        assert_eq!(Dao::snapshot_digest(), digest);
+
+        // State changes change the digest
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(5), 4, VoteKind::Aye));
+        assert_ne!(Dao::snapshot_digest(), digest);
+    });
+}
+
+#[test]
+fn export_snapshot_rejects_corrupted_cursors() {
+    new_test_ext().execute_with(|| {
+        for _ in 0..3 {
+            create_with_period(1, 10);
+        }
+        let valid = Dao::export_snapshot(1, None).unwrap().next_cursor.unwrap();
+        let valid = valid.as_bytes();
+
+        // Wrong version, truncated, or with trailing bytes
+        let mut rejected = vec![[&[0u8][..], &valid[1..]].concat(), [valid, &[0u8][..]].concat()];
+        rejected.extend((0..valid.len()).map(|len| valid[..len].to_vec()));
+        for bytes in rejected {
+            let cursor = PageCursor::try_from(bytes).unwrap();
+            assert_eq!(Dao::export_snapshot(10, Some(&cursor)), Err(InvalidCursor));
+        }
+
+        // Arbitrary bytes either decode to some position or are rejected, never panic
+        let mut seed = 0x9e37_79b9_7f4a_7c15u64;
+        for _ in 0..500 {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            let len = (seed % 16) as usize;
+            let bytes: Vec<u8> = seed.to_le_bytes().iter().cycle().take(len).copied().collect();
+            let cursor = PageCursor::try_from(bytes).unwrap();
+            let _ = Dao::export_snapshot(10, Some(&cursor));
+        }
+    });
+}
+
+#[test]
+fn export_snapshot_of_empty_state() {
+    new_test_ext().execute_with(|| {
+        let page = Dao::export_snapshot(10, None).unwrap();
+        assert!(page.entries.is_empty());
+        assert_eq!(page.next_cursor, None);
+        assert_eq!(page.digest, Some(Dao::snapshot_digest()));
+    });
+}
+
+#[test]
+fn deposit_is_reserved_and_recorded() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Dao::create_proposal(
+            RuntimeOrigin::signed(1),
+            b"Proposal".to_vec(),
+            b"Description".to_vec(),
+            None
+        ));
+
+        assert_eq!(Dao::get_proposal_details(0).unwrap().deposit, 1000);
+        assert_eq!(Balances::reserved_balance(1), 1000);
+        assert_eq!(Balances::free_balance(1), INITIAL_BALANCE - 1000);
+    });
+}
+
+#[test]
+fn deposit_change_mid_lifecycle_releases_original_amount() {
+    new_test_ext().execute_with(|| {
+        // Proposal 0 reserves the original deposit
+        assert_ok!(Dao::create_proposal(
+            RuntimeOrigin::signed(1),
+            b"Old deposit".to_vec(),
+            b"Description".to_vec(),
+            Some(10)
+        ));
+
+        // Governance raises the deposit; proposal 1 reserves the new amount
+        ProposalDeposit::set(3000);
+        assert_ok!(Dao::create_proposal(
+            RuntimeOrigin::signed(1),
+            b"New deposit".to_vec(),
+            b"Description".to_vec(),
+            Some(10)
+        ));
+        System::assert_last_event(
+            Event::ProposalCreated {
+                proposal_id: 1,
+                proposer: 1,
+                title: b"New deposit".to_vec(),
+                deposit: 3000,
             }
             .into(),
         );
+        assert_eq!(Balances::reserved_balance(1), 4000);
+
+        // Cancelling proposal 1 releases exactly its 3000
+        assert_ok!(Dao::cancel_proposal(RuntimeOrigin::signed(1), 1));
+        assert_eq!(Balances::reserved_balance(1), 1000);
+
+        // Executing proposal 0 after the deposit drops releases its original 1000
+        ProposalDeposit::set(10);
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 0, VoteKind::Aye));
+        System::set_block_number(11);
+        assert_ok!(Dao::execute_proposal(RuntimeOrigin::signed(1), 0));
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(Balances::free_balance(1), INITIAL_BALANCE);
+    });
+}
+
+#[test]
+fn execution_by_another_account_refunds_the_proposer() {
+    new_test_ext().execute_with(|| {
+        let executor = 6u64;
+        create_with_period(1, 10);
+        // The executor's own deposit must stay reserved
+        create_with_period(executor, 100);
+        for voter in 2..=4 {
+            assert_ok!(Dao::vote(RuntimeOrigin::signed(voter), 0, VoteKind::Aye));
+        }
+
+        System::set_block_number(11);
+        assert_ok!(Dao::execute_proposal(RuntimeOrigin::signed(executor), 0));
+
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(Balances::free_balance(1), INITIAL_BALANCE);
+        assert_eq!(Balances::reserved_balance(executor), 1000);
+        assert_eq!(Balances::free_balance(executor), INITIAL_BALANCE - 1000);
+    });
+}
+
+#[test]
+fn closed_approved_proposal_keeps_the_deposit_until_executed() {
+    new_test_ext().execute_with(|| {
+        create_with_period(1, 10);
+        for voter in 2..=4 {
+            assert_ok!(Dao::vote(RuntimeOrigin::signed(voter), 0, VoteKind::Aye));
+        }
+
+        System::set_block_number(11);
+        assert_ok!(Dao::close_proposal(RuntimeOrigin::signed(5), 0));
+        assert_eq!(Balances::reserved_balance(1), 1000);
+
+        assert_ok!(Dao::execute_proposal(RuntimeOrigin::signed(5), 0));
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(Balances::reserved_balance(5), 0);
+        assert_eq!(Balances::free_balance(1), INITIAL_BALANCE);
+    });
+}
+
+#[test]
+fn migration_to_v1_records_deposit() {
+    use crate::migrations::v1::OldProposal;
+    use frame_support::{
+        storage::unhashed,
+        traits::{GetStorageVersion, Hooks, StorageVersion},
+    };
+
+    new_test_ext().execute_with(|| {
+        StorageVersion::new(0).put::<Dao>();
+
+        let old = OldProposal::<Test> {
+            id: 0,
+            proposer: 1,
+            title: b"Legacy".to_vec().try_into().unwrap(),
+            description: b"Description".to_vec().try_into().unwrap(),
+            created_at: 1,
+            voting_start: 1,
+            voting_end: 11,
+            status: ProposalStatus::Active,
+            votes_for: 2,
+            votes_against: 1,
+            total_votes: 3,
+            executed: false,
+            executed_at: None,
+        };
+        unhashed::put(&crate::Proposals::<Test>::hashed_key_for(0), &old);
+        crate::ProposalCount::<Test>::put(1);
+
+        Dao::on_runtime_upgrade();
+
+        assert_eq!(Dao::on_chain_storage_version(), 11);
+        let proposal = Dao::get_proposal_details(0).unwrap();
+        assert_eq!(proposal.deposit, 1000);
+        assert_eq!(proposal.parameter_change, None);
+        assert_eq!(Dao::proposals_ending_at(11).to_vec(), vec![0]);
+        assert_eq!(proposal.votes_for, 2);
+        assert_eq!(proposal.title.to_vec(), b"Legacy".to_vec());
+    });
+}
+
+#[test]
+fn migration_to_v3_keeps_proposals_plain() {
+    use crate::migrations::v3::{OldProposal, Proposals as OldProposals};
+    use frame_support::traits::{GetStorageVersion, Hooks, StorageVersion};
+
+    new_test_ext().execute_with(|| {
+        create_with_period(1, 10);
+        let current = Dao::get_proposal_details(0).unwrap();
+        StorageVersion::new(2).put::<Dao>();
+        OldProposals::<Test>::insert(
+            0,
+            OldProposal {
+                id: current.id,
+                proposer: current.proposer,
+                title: current.title.into_inner(),
+                description: current.description.into_inner(),
+                created_at: current.created_at,
+                voting_start: current.voting_start,
+                voting_end: current.voting_end,
+                status: current.status,
+                votes_for: current.votes_for,
+                votes_against: current.votes_against,
+                total_votes: current.total_votes,
+                executed: current.executed,
+                executed_at: current.executed_at,
+                deposit: current.deposit,
+            },
+        );
+
+        Dao::on_runtime_upgrade();
+
+        assert_eq!(Dao::on_chain_storage_version(), 11);
+        let proposal = Dao::get_proposal_details(0).unwrap();
+        assert_eq!(proposal.parameter_change, None);
+        assert_eq!(proposal.deposit, 1000);
+        assert_eq!(Dao::proposals_ending_at(11).to_vec(), vec![0]);
+    });
+}
+
+#[test]
+fn migration_to_v4_drops_invalid_utf8_from_text() {
+    use crate::ProposalTitle;
+    use codec::{Decode, Encode};
+    use frame_support::{
+        traits::{GetStorageVersion, Hooks, StorageVersion},
+        BoundedVec,
+    };
+
+    new_test_ext().execute_with(|| {
+        create_with_period(1, 10);
+        create_with_period(1, 10);
+
+        // As stored while `NormalizeText` was off: raw bytes decode whatever their charset
+        let raw: BoundedVec<u8, MaxTitleLength> = vec![b'Q', 0xff, b'4', 0xc3].try_into().unwrap();
+        let title = ProposalTitle::<Test>::decode(&mut &raw.encode()[..]).unwrap();
+        assert!(!title.is_valid());
+        let mut invalid = Dao::get_proposal_details(0).unwrap();
+        invalid.title = title;
+        store_before_v5(invalid);
+        store_before_v5(Dao::get_proposal_details(1).unwrap());
+        StorageVersion::new(3).put::<Dao>();
+
+        Dao::on_runtime_upgrade();
+
+        assert_eq!(Dao::on_chain_storage_version(), 11);
+        let repaired = Dao::get_proposal_details(0).unwrap();
+        assert_eq!(repaired.title.to_vec(), b"Q4".to_vec());
+        assert_eq!(repaired.description.to_vec(), b"Description".to_vec());
+        assert_eq!(Dao::get_proposal_details(1).unwrap().title.to_vec(), b"Proposal".to_vec());
     });
 }
 
+#[test]
+fn migration_to_v5_applies_the_minimum_quorum() {
+    use frame_support::traits::{GetStorageVersion, Hooks, StorageVersion};
+
+    new_test_ext().execute_with(|| {
+        create_with_period(1, 10);
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 0, VoteKind::Aye));
+        store_before_v5(Dao::get_proposal_details(0).unwrap());
+        StorageVersion::new(4).put::<Dao>();
+
+        MinQuorum::set(2);
+        Dao::on_runtime_upgrade();
+
+        assert_eq!(Dao::on_chain_storage_version(), 11);
+        let proposal = Dao::get_proposal_details(0).unwrap();
+        assert_eq!(proposal.quorum, 2);
+        assert_eq!(proposal.title.to_vec(), b"Proposal".to_vec());
+
+        // The single vote in favor no longer carries it
+        System::set_block_number(11);
+        assert_noop!(
+            Dao::execute_proposal(RuntimeOrigin::signed(1), 0),
+            Error::<Test>::ProposalNotApproved
+        );
+    });
+}
+
+#[test]
+fn migration_to_v6_starts_without_abstentions() {
+    use crate::migrations::v6::{OldProposal, Proposals as OldProposals};
+    use frame_support::traits::{GetStorageVersion, Hooks, StorageVersion};
+
+    new_test_ext().execute_with(|| {
+        create_with_period(1, 10);
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 0, VoteKind::Aye));
+        let current = Dao::get_proposal_details(0).unwrap();
+        OldProposals::<Test>::insert(
+            0,
+            OldProposal {
+                id: current.id,
+                proposer: current.proposer,
+                title: current.title,
+                description: current.description,
+                created_at: current.created_at,
+                voting_start: current.voting_start,
+                voting_end: current.voting_end,
+                status: current.status,
+                votes_for: current.votes_for,
+                votes_against: current.votes_against,
+                total_votes: current.total_votes,
+                executed: current.executed,
+                executed_at: current.executed_at,
+                deposit: current.deposit,
+                parameter_change: current.parameter_change,
+                quorum: current.quorum,
+            },
+        );
+        StorageVersion::new(5).put::<Dao>();
+
+        Dao::on_runtime_upgrade();
+
+        assert_eq!(Dao::on_chain_storage_version(), 11);
+        let proposal = Dao::get_proposal_details(0).unwrap();
+        assert_eq!((proposal.votes_for, proposal.abstentions, proposal.total_votes), (1, 0, 1));
+        assert_eq!(proposal.quorum, 1);
+        assert_eq!(Dao::get_vote(0, &2), Some(VoteKind::Aye));
+    });
+}
+
+#[test]
+fn migration_to_v7_stores_proposals_without_a_call() {
+    use crate::migrations::v7::{OldProposal, Proposals as OldProposals};
+    use frame_support::traits::{GetStorageVersion, Hooks, StorageVersion};
+
+    new_test_ext().execute_with(|| {
+        create_with_period(1, 10);
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 0, VoteKind::Abstain));
+        let current = Dao::get_proposal_details(0).unwrap();
+        OldProposals::<Test>::insert(
+            0,
+            OldProposal {
+                id: current.id,
+                proposer: current.proposer,
+                title: current.title,
+                description: current.description,
+                created_at: current.created_at,
+                voting_start: current.voting_start,
+                voting_end: current.voting_end,
+                status: current.status,
+                votes_for: current.votes_for,
+                votes_against: current.votes_against,
+                abstentions: current.abstentions,
+                total_votes: current.total_votes,
+                executed: current.executed,
+                executed_at: current.executed_at,
+                deposit: current.deposit,
+                parameter_change: current.parameter_change,
+                quorum: current.quorum,
+            },
+        );
+        StorageVersion::new(6).put::<Dao>();
+
+        Dao::on_runtime_upgrade();
+
+        assert_eq!(Dao::on_chain_storage_version(), 11);
+        let proposal = Dao::get_proposal_details(0).unwrap();
+        assert_eq!(proposal.call_hash, None);
+        assert_eq!((proposal.abstentions, proposal.total_votes), (1, 1));
+        assert!(Dao::proposal_calls(0).is_none());
+    });
+}
+
+#[test]
+fn migration_to_v8_files_stored_proposals_under_general() {
+    use crate::migrations::v8::{OldProposal, Proposals as OldProposals};
+    use crate::{CategoryCount, ProposalsByCategory};
+    use frame_support::traits::{GetStorageVersion, Hooks, StorageVersion};
+
+    new_test_ext().execute_with(|| {
+        for _ in 0..3 {
+            create_with_period(1, 10);
+        }
+        assert_ok!(Dao::cancel_proposal(RuntimeOrigin::signed(1), 1));
+        crate::Proposals::<Test>::remove(1);
+        // Stored before version 8, nothing was indexed
+        let _ = ProposalsByCategory::<Test>::clear(u32::MAX, None);
+        let _ = CategoryCount::<Test>::clear(u32::MAX, None);
+        for id in [0, 2] {
+            let current = Dao::get_proposal_details(id).unwrap();
+            OldProposals::<Test>::insert(
+                id,
+                OldProposal {
+                    id: current.id,
+                    proposer: current.proposer,
+                    title: current.title,
+                    description: current.description,
+                    created_at: current.created_at,
+                    voting_start: current.voting_start,
+                    voting_end: current.voting_end,
+                    status: current.status,
+                    votes_for: current.votes_for,
+                    votes_against: current.votes_against,
+                    abstentions: current.abstentions,
+                    total_votes: current.total_votes,
+                    executed: current.executed,
+                    executed_at: current.executed_at,
+                    deposit: current.deposit,
+                    parameter_change: current.parameter_change,
+                    quorum: current.quorum,
+                    call_hash: current.call_hash,
+                },
+            );
+        }
+        StorageVersion::new(7).put::<Dao>();
+
+        Dao::on_runtime_upgrade();
+
+        assert_eq!(Dao::on_chain_storage_version(), 11);
+        assert_eq!(Dao::get_proposal_details(2).unwrap().category, ProposalCategory::General);
+        assert_eq!(Dao::category_count(ProposalCategory::General), 2);
+        assert_eq!(Dao::get_proposals_by_category(ProposalCategory::General, 0, 10), vec![0, 2]);
+        assert_eq!(Dao::category_count(ProposalCategory::Budget), 0);
+
+        // Runs once
+        Dao::on_runtime_upgrade();
+        assert_eq!(Dao::category_count(ProposalCategory::General), 2);
+    });
+}
+
+#[test]
+fn migration_to_v9_starts_stored_proposals_at_revision_zero() {
+    use crate::migrations::v9::{OldProposal, Proposals as OldProposals};
+    use frame_support::traits::{GetStorageVersion, Hooks, StorageVersion};
+
+    new_test_ext().execute_with(|| {
+        assert_ok!(Dao::create_categorized_proposal(
+            RuntimeOrigin::signed(1),
+            b"Proposal".to_vec(),
+            b"Description".to_vec(),
+            Some(10),
+            ProposalCategory::Budget
+        ));
+        let current = Dao::get_proposal_details(0).unwrap();
+        OldProposals::<Test>::insert(
+            0,
+            OldProposal {
+                id: current.id,
+                proposer: current.proposer,
+                title: current.title.clone(),
+                description: current.description,
+                created_at: current.created_at,
+                voting_start: current.voting_start,
+                voting_end: current.voting_end,
+                status: current.status,
+                votes_for: current.votes_for,
+                votes_against: current.votes_against,
+                abstentions: current.abstentions,
+                total_votes: current.total_votes,
+                executed: current.executed,
+                executed_at: current.executed_at,
+                deposit: current.deposit,
+                parameter_change: current.parameter_change,
+                quorum: current.quorum,
+                call_hash: current.call_hash,
+                category: current.category,
+            },
+        );
+        StorageVersion::new(8).put::<Dao>();
+
+        Dao::on_runtime_upgrade();
+
+        assert_eq!(Dao::on_chain_storage_version(), 11);
+        let proposal = Dao::get_proposal_details(0).unwrap();
+        assert_eq!(proposal.revision, 0);
+        assert_eq!(proposal.category, ProposalCategory::Budget);
+        assert_eq!(proposal.title, current.title);
+        // The category index is left alone
+        assert_eq!(Dao::category_count(ProposalCategory::Budget), 1);
+    });
+}
+
+#[test]
+fn migration_to_v10_indexes_carried_delegators() {
+    use frame_support::traits::{GetStorageVersion, Hooks, StorageVersion};
+
+    new_test_ext().execute_with(|| {
+        create_with_period(1, 10);
+        assert_ok!(Dao::delegate(RuntimeOrigin::signed(3), 2));
+        assert_ok!(Dao::delegate(RuntimeOrigin::signed(4), 2));
+        assert_ok!(Dao::delegate(RuntimeOrigin::signed(6), 5));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 0, VoteKind::Aye));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(5), 0, VoteKind::Nay));
+        assert_ok!(Dao::delegate(RuntimeOrigin::signed(2), 7));
+        let _ = crate::CarriedDelegators::<Test>::clear(u32::MAX, None);
+        let _ = crate::DelegatorHeights::<Test>::clear(u32::MAX, None);
+        StorageVersion::new(9).put::<Dao>();
+
+        Dao::on_runtime_upgrade();
+
+        assert_eq!(Dao::on_chain_storage_version(), 11);
+        let mut carried = Dao::carried_delegators(0, 2).to_vec();
+        carried.sort();
+        assert_eq!(carried, vec![3, 4]);
+        assert_eq!(Dao::carried_delegators(0, 5).to_vec(), vec![6]);
+        assert_eq!(Dao::delegator_heights(2).to_vec(), vec![2]);
+        assert_eq!(Dao::delegator_heights(5).to_vec(), vec![1]);
+        assert_eq!(Dao::delegator_heights(7).to_vec(), vec![0, 1]);
+
+        // Changing the vote moves the indexed delegators along
+        assert_ok!(Dao::change_vote(RuntimeOrigin::signed(2), 0, VoteKind::Nay));
+        assert_eq!(tally(), (0, 5, 5));
+    });
+}
+
+/// Overwrite a proposal with its layout before version 5
+fn store_before_v5(proposal: crate::Proposal<Test>) {
+    use crate::migrations::v5::{OldProposal, Proposals as OldProposals};
+
+    OldProposals::<Test>::insert(
+        proposal.id,
+        OldProposal {
+            id: proposal.id,
+            proposer: proposal.proposer,
+            title: proposal.title,
+            description: proposal.description,
+            created_at: proposal.created_at,
+            voting_start: proposal.voting_start,
+            voting_end: proposal.voting_end,
+            status: proposal.status,
+            votes_for: proposal.votes_for,
+            votes_against: proposal.votes_against,
+            total_votes: proposal.total_votes,
+            executed: proposal.executed,
+            executed_at: proposal.executed_at,
+            deposit: proposal.deposit,
+            parameter_change: proposal.parameter_change,
+        },
+    );
+}
+
+fn create_with_period(proposer: u64, period: u64) {
+    assert_ok!(Dao::create_proposal(
+        RuntimeOrigin::signed(proposer),
+        b"Proposal".to_vec(),
+        b"Description".to_vec(),
+        Some(period)
+    ));
+}
+
+#[test]
+fn offchain_worker_submits_one_close_per_expired_proposal() {
+    use codec::Decode;
+    use frame_support::traits::Hooks;
+    use sp_core::offchain::{
+        testing::{TestOffchainExt, TestTransactionPoolExt},
+        OffchainDbExt, OffchainWorkerExt, TransactionPoolExt,
+    };
+
+    let mut ext = new_test_ext();
+    let (offchain, _state) = TestOffchainExt::new();
+    let (pool, pool_state) = TestTransactionPoolExt::new();
+    ext.register_extension(OffchainWorkerExt::new(offchain.clone()));
+    ext.register_extension(OffchainDbExt::new(offchain));
+    ext.register_extension(TransactionPoolExt::new(pool));
+
+    ext.execute_with(|| {
+        // Proposals 0 and 2 end at block 11, proposal 1 at 21, proposal 3 is cancelled
+        create_with_period(1, 10);
+        create_with_period(1, 20);
+        create_with_period(2, 10);
+        create_with_period(2, 10);
+        assert_ok!(Dao::cancel_proposal(RuntimeOrigin::signed(2), 3));
+
+        // Nothing has expired yet
+        Dao::offchain_worker(10);
+        assert!(pool_state.read().transactions.is_empty());
+
+        System::set_block_number(11);
+        Dao::offchain_worker(11);
+
+        let calls: Vec<_> = pool_state
+            .read()
+            .transactions
+            .iter()
+            .map(|tx| Extrinsic::decode(&mut &tx[..]).unwrap())
+            .map(|tx| {
+                assert!(tx.signature.is_none());
+                tx.call
+            })
+            .collect();
+        assert_eq!(
+            calls,
+            vec![
+                RuntimeCall::Dao(crate::Call::close_proposal_unsigned { proposal_id: 0 }),
+                RuntimeCall::Dao(crate::Call::close_proposal_unsigned { proposal_id: 2 }),
+            ]
+        );
+    });
+}
+
+#[test]
+fn unsigned_close_is_validated() {
+    use frame_support::{pallet_prelude::*, unsigned::ValidateUnsigned};
+
+    new_test_ext().execute_with(|| {
+        create_with_period(1, 10);
+        create_with_period(1, 10);
+        let validate = |proposal_id| {
+            <Dao as ValidateUnsigned>::validate_unsigned(
+                TransactionSource::External,
+                &crate::Call::close_proposal_unsigned { proposal_id },
+            )
+        };
+
+        // Not yet past its end, and unknown proposals
+        assert_eq!(validate(0), InvalidTransaction::Future.into());
+        assert_eq!(validate(9), InvalidTransaction::Stale.into());
+
+        System::set_block_number(11);
+        let valid = validate(0).unwrap();
+        assert_eq!(valid.priority, 100);
+        assert_eq!(valid.longevity, 5);
+        // Distinct tags per proposal, so the pool keeps one transaction for each
+        assert_ne!(valid.provides, validate(1).unwrap().provides);
+
+        assert_ok!(Dao::close_proposal_unsigned(RuntimeOrigin::none(), 0));
+        assert_eq!(Dao::get_proposal_details(0).unwrap().status, ProposalStatus::Rejected);
+
+        // Closed proposals are stale
+        assert_eq!(validate(0), InvalidTransaction::Stale.into());
+        assert_noop!(
+            Dao::close_proposal_unsigned(RuntimeOrigin::signed(1), 1),
+            sp_runtime::DispatchError::BadOrigin
+        );
+    });
+}
+
+#[test]
+fn ending_index_tracks_active_proposals() {
+    new_test_ext().execute_with(|| {
+        create_with_period(1, 10);
+        create_with_period(1, 10);
+        create_with_period(1, 10);
+        assert_eq!(Dao::proposals_ending_at(11).to_vec(), vec![0, 1, 2]);
+
+        assert_ok!(Dao::cancel_proposal(RuntimeOrigin::signed(1), 1));
+        assert_eq!(Dao::proposals_ending_at(11).to_vec(), vec![0, 2]);
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(3), 2, VoteKind::Aye));
+
+        System::set_block_number(11);
+        assert_eq!(Dao::expired_proposals(10), Vec::<u64>::new());
+        assert_eq!(Dao::expired_proposals(11), vec![0, 2]);
+
+        assert_ok!(Dao::close_proposal(RuntimeOrigin::signed(5), 0));
+        assert_eq!(Dao::proposals_ending_at(11).to_vec(), vec![2]);
+
+        // Executing straight from Active also unindexes, dropping the empty entry
+        assert_ok!(Dao::execute_proposal(RuntimeOrigin::signed(1), 2));
+        assert!(!crate::ProposalsEndingAt::<Test>::contains_key(11));
+        assert!(Dao::expired_proposals(11).is_empty());
+    });
+}
+
+#[test]
+fn vote_mints_receipt() {
+    new_test_ext().execute_with(|| {
+        create_with_period(1, 10);
+        create_with_period(1, 10);
+        System::set_block_number(3);
+
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 0, VoteKind::Aye));
+        assert_ok!(Dao::vote_with_salt(RuntimeOrigin::signed(2), 1, VoteKind::Nay, [9u8; 32]));
+
+        assert_eq!(
+            Dao::get_receipts(&2, 0, 10),
+            vec![
+                (
+                    0,
+                    VoteReceipt {
+                        proposal_id: 0,
+                        weight: 1,
+                        block: 3,
+                        choice_hash: Dao::hash_choice(VoteKind::Aye, &[]),
+                    }
+                ),
+                (
+                    1,
+                    VoteReceipt {
+                        proposal_id: 1,
+                        weight: 1,
+                        block: 3,
+                        choice_hash: Dao::hash_choice(VoteKind::Nay, &[9u8; 32]),
+                    }
+                ),
+            ]
+        );
+        // The salted hash does not reveal the choice without the salt
+        assert_ne!(
+            Dao::hash_choice(VoteKind::Nay, &[9u8; 32]),
+            Dao::hash_choice(VoteKind::Nay, &[])
+        );
+        assert_eq!(Dao::get_receipts(&2, 1, 10).len(), 1);
+        assert!(Dao::get_receipts(&3, 0, 10).is_empty());
+
+        // A rejected vote mints nothing
+        assert_noop!(
+            Dao::vote(RuntimeOrigin::signed(2), 0, VoteKind::Nay),
+            Error::<Test>::AlreadyVoted
+        );
+    });
+}
+
+#[test]
+fn receipts_survive_vote_pruning() {
+    new_test_ext().execute_with(|| {
+        create_with_period(1, 10);
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 0, VoteKind::Aye));
+
+        let _ = crate::Votes::<Test>::clear_prefix(0, u32::MAX, None);
+        let _ = crate::HasVoted::<Test>::clear_prefix(0, u32::MAX, None);
+        assert_eq!(Dao::get_vote(0, &2), None);
+
+        let receipts = Dao::get_receipts(&2, 0, 10);
+        assert_eq!(receipts.len(), 1);
+        assert_eq!(receipts[0].1.proposal_id, 0);
+        assert_eq!(receipts[0].1.choice_hash, Dao::hash_choice(VoteKind::Aye, &[]));
+    });
+}
+
+#[test]
+fn receipts_are_bounded_per_account() {
+    new_test_ext().execute_with(|| {
+        // MaxReceiptsPerAccount is 3 in the mock
+        for id in 0..5 {
+            create_with_period(1, 10);
+            assert_ok!(Dao::vote(RuntimeOrigin::signed(2), id, VoteKind::Aye));
+        }
+
+        // The two oldest were evicted, IDs keep increasing
+        let ids: Vec<u64> = Dao::get_receipts(&2, 0, 10).into_iter().map(|(id, _)| id).collect();
+        assert_eq!(ids, vec![2, 3, 4]);
+        assert_eq!(Dao::receipt_range(2), (2, 5));
+        assert!(Dao::receipts(2, 1).is_none());
+        assert_eq!(Dao::receipts(2, 4).unwrap().proposal_id, 4);
+    });
+}
+
+#[test]
+fn runtime_api_types_have_stable_json_field_names() {
+    new_test_ext().execute_with(|| {
+        create_with_period(1, 10);
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 0, VoteKind::Aye));
+
+        // Frontend types are generated from these names; renaming one breaks them
+        let (_, receipt) = Dao::get_receipts(&2, 0, 1).remove(0);
+        let json = serde_json::to_value(&receipt).unwrap();
+        let hash = json["choiceHash"].as_str().unwrap().to_owned();
+        assert_eq!(
+            json,
+            serde_json::json!({ "proposalId": 0, "weight": 1, "block": 1, "choiceHash": &hash })
+        );
+        assert!(hash.starts_with("0x") && hash.len() == 2 + 64);
+        assert_eq!(serde_json::from_value::<VoteReceipt<u64>>(json).unwrap(), receipt);
+
+        let page = Dao::export_snapshot(10, None).unwrap();
+        let json = serde_json::to_value(&page).unwrap();
+        assert_eq!(
+            json["entries"][0],
+            serde_json::json!({
+                "id": 0,
+                "status": "Active",
+                "votesFor": 1,
+                "votesAgainst": 0,
+                "abstentions": 0,
+                "totalVotes": 1,
+                "approvalPercentage": 100,
+                "thresholdMet": true,
+                "votingStart": 1,
+                "votingEnd": 11,
+                "parameterChange": null,
+                "callHash": null,
+                "category": "general",
+            })
+        );
+        assert_eq!(json["nextCursor"], serde_json::Value::Null);
+        assert!(json["digest"].as_str().unwrap().starts_with("0x"));
+        assert_eq!(serde_json::from_value::<crate::SnapshotPage<u64>>(json).unwrap(), page);
+    });
+}
+
+fn voucher(preimage: [u8; 32]) -> [u8; 32] {
+    sp_io::hashing::blake2_256(&preimage)
+}
+
+#[test]
+fn issue_poll_vouchers_is_restricted_and_bounded() {
+    new_test_ext().execute_with(|| {
+        create_with_period(1, 10);
+
+        assert_noop!(
+            Dao::issue_poll_vouchers(RuntimeOrigin::signed(2), 0, vec![voucher([1; 32])]),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_noop!(
+            Dao::issue_poll_vouchers(RuntimeOrigin::signed(1), 0, vec![[1; 32], [1; 32]]),
+            Error::<Test>::DuplicateVoucher
+        );
+
+        assert_ok!(Dao::issue_poll_vouchers(RuntimeOrigin::signed(1), 0, vec![[1; 32], [2; 32]]));
+        System::assert_last_event(Event::PollVouchersIssued { proposal_id: 0, count: 2 }.into());
+        // Root issues more on the proposer's behalf, never a voucher twice
+        assert_noop!(
+            Dao::issue_poll_vouchers(RuntimeOrigin::root(), 0, vec![[2; 32]]),
+            Error::<Test>::DuplicateVoucher
+        );
+        assert_ok!(Dao::issue_poll_vouchers(RuntimeOrigin::root(), 0, vec![[3; 32]]));
+        // MaxVouchersPerProposal is 4 in the mock
+        assert_noop!(
+            Dao::issue_poll_vouchers(RuntimeOrigin::root(), 0, vec![[4; 32], [5; 32]]),
+            Error::<Test>::TooManyVouchers
+        );
+        assert_eq!(Dao::poll_voucher_count(0), 3);
+
+        System::set_block_number(11);
+        assert_noop!(
+            Dao::issue_poll_vouchers(RuntimeOrigin::signed(1), 0, vec![[4; 32]]),
+            Error::<Test>::VotingPeriodEnded
+        );
+    });
+}
+
+#[test]
+fn voucher_votes_are_counted_once_without_a_voter() {
+    new_test_ext().execute_with(|| {
+        create_with_period(1, 10);
+        let vouchers = vec![voucher([1; 32]), voucher([2; 32]), voucher([3; 32])];
+        assert_ok!(Dao::issue_poll_vouchers(RuntimeOrigin::signed(1), 0, vouchers));
+
+        assert_noop!(
+            Dao::vote_with_voucher(RuntimeOrigin::signed(2), 0, [1; 32], VoteKind::Aye),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_noop!(
+            Dao::vote_with_voucher(RuntimeOrigin::none(), 0, [9; 32], VoteKind::Aye),
+            Error::<Test>::InvalidVoucher
+        );
+        // The voucher hash itself is no preimage
+        assert_noop!(
+            Dao::vote_with_voucher(RuntimeOrigin::none(), 0, voucher([1; 32]), VoteKind::Aye),
+            Error::<Test>::InvalidVoucher
+        );
+
+        assert_ok!(Dao::vote_with_voucher(RuntimeOrigin::none(), 0, [1; 32], VoteKind::Aye));
+        System::assert_last_event(
+            Event::VoucherVoteCast { proposal_id: 0, vote: VoteKind::Aye }.into(),
+        );
+        assert_noop!(
+            Dao::vote_with_voucher(RuntimeOrigin::none(), 0, [1; 32], VoteKind::Nay),
+            Error::<Test>::VoucherSpent
+        );
+        assert_ok!(Dao::vote_with_voucher(RuntimeOrigin::none(), 0, [2; 32], VoteKind::Aye));
+        assert_ok!(Dao::vote_with_voucher(RuntimeOrigin::none(), 0, [3; 32], VoteKind::Nay));
+        // Signed votes count alongside
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(4), 0, VoteKind::Nay));
+
+        let proposal = Dao::get_proposal_details(0).unwrap();
+        assert_eq!((proposal.votes_for, proposal.votes_against, proposal.total_votes), (2, 2, 4));
+        // Only the signed voter is on record
+        assert_eq!(Votes::<Test>::iter_prefix(0).count(), 1);
+        assert_eq!(Dao::poll_vouchers(0, voucher([1; 32])), Some(true));
+        assert_eq!(Dao::poll_vouchers(0, voucher([4; 32])), None);
+    });
+}
+
+#[test]
+fn unsigned_voucher_vote_is_validated() {
+    use frame_support::{pallet_prelude::*, unsigned::ValidateUnsigned};
+
+    new_test_ext().execute_with(|| {
+        create_with_period(1, 10);
+        create_with_period(1, 10);
+        assert_ok!(Dao::issue_poll_vouchers(
+            RuntimeOrigin::signed(1),
+            0,
+            vec![voucher([1; 32]), voucher([2; 32])]
+        ));
+        let validate = |proposal_id, voucher_preimage| {
+            <Dao as ValidateUnsigned>::validate_unsigned(
+                TransactionSource::External,
+                &crate::Call::vote_with_voucher {
+                    proposal_id,
+                    voucher_preimage,
+                    vote: VoteKind::Aye,
+                },
+            )
+        };
+
+        // Random preimages and vouchers of another proposal never enter the pool
+        assert_eq!(validate(0, [9; 32]), InvalidTransaction::BadProof.into());
+        assert_eq!(validate(1, [1; 32]), InvalidTransaction::BadProof.into());
+
+        let valid = validate(0, [1; 32]).unwrap();
+        assert_eq!(valid.priority, 100);
+        assert_eq!(valid.longevity, 5);
+        // One pool slot per voucher, whatever the choice
+        assert_ne!(valid.provides, validate(0, [2; 32]).unwrap().provides);
+        let flipped = <Dao as ValidateUnsigned>::validate_unsigned(
+            TransactionSource::External,
+            &crate::Call::vote_with_voucher {
+                proposal_id: 0,
+                voucher_preimage: [1; 32],
+                vote: VoteKind::Nay,
+            },
+        );
+        assert_eq!(valid.provides, flipped.unwrap().provides);
+
+        // Spent vouchers and closed polls are stale
+        assert_ok!(Dao::vote_with_voucher(RuntimeOrigin::none(), 0, [1; 32], VoteKind::Aye));
+        assert_eq!(validate(0, [1; 32]), InvalidTransaction::Stale.into());
+        System::set_block_number(11);
+        assert_eq!(validate(0, [2; 32]), InvalidTransaction::Stale.into());
+    });
+}
+
+fn propose_change(change: ParameterChange) {
+    assert_ok!(Dao::create_parameter_proposal(
+        RuntimeOrigin::signed(1),
+        b"Change a parameter".to_vec(),
+        b"Description".to_vec(),
+        Some(10),
+        change
+    ));
+}
+
+#[test]
+fn executed_parameter_proposal_applies_the_change() {
+    new_test_ext().execute_with(|| {
+        assert!(Dao::normalize_text());
+        let change = ParameterChange::DaoTextNormalization(false);
+        propose_change(change.clone());
+        assert_eq!(Dao::get_proposal_details(0).unwrap().parameter_change, Some(change.clone()));
+
+        // Nothing changes before execution
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 0, VoteKind::Aye));
+        System::set_block_number(11);
+        assert_ok!(Dao::close_proposal(RuntimeOrigin::signed(3), 0));
+        assert!(Dao::normalize_text());
+
+        assert_ok!(Dao::execute_proposal(RuntimeOrigin::signed(1), 0));
+        assert!(!Dao::normalize_text());
+        System::assert_has_event(Event::TextNormalizationSet { enabled: false }.into());
+        System::assert_last_event(Event::ParameterChanged { proposal_id: 0, change }.into());
+    });
+}
+
+#[test]
+fn rejected_parameter_proposal_changes_nothing() {
+    new_test_ext().execute_with(|| {
+        propose_change(ParameterChange::DaoTextNormalization(false));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 0, VoteKind::Nay));
+        System::set_block_number(11);
+
+        assert_noop!(
+            Dao::execute_proposal(RuntimeOrigin::signed(1), 0),
+            Error::<Test>::ProposalNotApproved
+        );
+        assert!(Dao::normalize_text());
+    });
+}
+
+#[test]
+fn change_without_a_handler_reports_the_parameter_gone() {
+    new_test_ext().execute_with(|| {
+        // The mock runtime has no ledger to apply this
+        let change = ParameterChange::LedgerTextNormalization(true);
+        propose_change(change.clone());
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 0, VoteKind::Aye));
+        System::set_block_number(11);
+
+        // The proposal is still executed and its deposit released
+        assert_ok!(Dao::execute_proposal(RuntimeOrigin::signed(1), 0));
+        assert_eq!(Dao::get_proposal_details(0).unwrap().status, ProposalStatus::Executed);
+        assert_eq!(Balances::reserved_balance(1), 0);
+        System::assert_last_event(Event::ParameterNoLongerExists { proposal_id: 0, change }.into());
+    });
+}
+
+#[test]
+fn snapshot_renders_the_parameter_change() {
+    new_test_ext().execute_with(|| {
+        propose_change(ParameterChange::DaoTextNormalization(false));
+
+        let page = Dao::export_snapshot(10, None).unwrap();
+        let json = serde_json::to_value(&page).unwrap();
+        assert_eq!(
+            json["entries"][0]["parameterChange"],
+            serde_json::json!({ "kind": "daoTextNormalization", "value": false })
+        );
+    });
+}
+
+/// Tally of the first proposal as (ayes, nays, total)
+fn tally() -> (u64, u64, u64) {
+    let proposal = Dao::get_proposal_details(0).unwrap();
+    (proposal.votes_for, proposal.votes_against, proposal.total_votes)
+}
+
+#[test]
+fn delegate_and_undelegate_record_the_delegation() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Dao::delegate(RuntimeOrigin::signed(2), 5));
+        assert_ok!(Dao::delegate(RuntimeOrigin::signed(3), 5));
+        System::assert_last_event(Event::Delegated { delegator: 3, delegate: 5 }.into());
+        assert_eq!(Dao::delegations(2), Some(5));
+        assert_eq!(Dao::delegators(5).into_inner(), vec![2, 3]);
+
+        // Delegating again moves the delegation
+        assert_ok!(Dao::delegate(RuntimeOrigin::signed(2), 6));
+        System::assert_has_event(Event::Undelegated { delegator: 2, delegate: 5 }.into());
+        assert_eq!(Dao::delegators(5).into_inner(), vec![3]);
+        assert_eq!(Dao::delegators(6).into_inner(), vec![2]);
+
+        assert_ok!(Dao::undelegate(RuntimeOrigin::signed(3)));
+        System::assert_last_event(Event::Undelegated { delegator: 3, delegate: 5 }.into());
+        assert_eq!(Dao::delegations(3), None);
+        assert!(Dao::delegators(5).is_empty());
+        assert_noop!(
+            Dao::undelegate(RuntimeOrigin::signed(3)),
+            Error::<Test>::NotDelegating
+        );
+    });
+}
+
+#[test]
+fn delegation_rejects_cycles_and_long_chains() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Dao::delegate(RuntimeOrigin::signed(2), 2),
+            Error::<Test>::DelegationCycle
+        );
+
+        // 2 -> 3 -> 4 is as long as the mock allows with a fourth link
+        assert_ok!(Dao::delegate(RuntimeOrigin::signed(2), 3));
+        assert_ok!(Dao::delegate(RuntimeOrigin::signed(3), 4));
+        assert_noop!(
+            Dao::delegate(RuntimeOrigin::signed(4), 2),
+            Error::<Test>::DelegationCycle
+        );
+        assert_ok!(Dao::delegate(RuntimeOrigin::signed(4), 5));
+
+        // Links at either end would make four delegations
+        assert_noop!(
+            Dao::delegate(RuntimeOrigin::signed(5), 6),
+            Error::<Test>::DelegationTooDeep
+        );
+        assert_noop!(
+            Dao::delegate(RuntimeOrigin::signed(1), 2),
+            Error::<Test>::DelegationTooDeep
+        );
+        // A branch off the middle of the chain stays within the limit
+        assert_ok!(Dao::delegate(RuntimeOrigin::signed(1), 3));
+    });
+}
+
+#[test]
+fn delegation_heights_follow_the_chains() {
+    new_test_ext().execute_with(|| {
+        // 2 -> 3 -> 4 -> 5 and 6 -> 4
+        assert_ok!(Dao::delegate(RuntimeOrigin::signed(2), 3));
+        assert_ok!(Dao::delegate(RuntimeOrigin::signed(3), 4));
+        assert_ok!(Dao::delegate(RuntimeOrigin::signed(4), 5));
+        assert_ok!(Dao::delegate(RuntimeOrigin::signed(6), 4));
+        assert_eq!(Dao::delegator_heights(3).to_vec(), vec![1]);
+        assert_eq!(Dao::delegator_heights(4).to_vec(), vec![1, 1]);
+        assert_eq!(Dao::delegator_heights(5).to_vec(), vec![0, 0, 1]);
+
+        // Shortening the longest chain lowers the heights above it
+        assert_ok!(Dao::undelegate(RuntimeOrigin::signed(2)));
+        assert!(Dao::delegator_heights(3).is_empty());
+        assert_eq!(Dao::delegator_heights(4).to_vec(), vec![2]);
+        assert_eq!(Dao::delegator_heights(5).to_vec(), vec![0, 1]);
+        // So 3 -> 4 -> 5 -> 7 fits again, and 1 -> 5 branches off it
+        assert_ok!(Dao::delegate(RuntimeOrigin::signed(5), 7));
+        assert_ok!(Dao::delegate(RuntimeOrigin::signed(1), 5));
+        assert_eq!(Dao::delegator_heights(7).to_vec(), vec![0, 0, 1]);
+
+        // Moving a delegation updates both chains
+        assert_ok!(Dao::delegate(RuntimeOrigin::signed(3), 1));
+        assert_eq!(Dao::delegator_heights(4).to_vec(), vec![1]);
+        assert_eq!(Dao::delegator_heights(1).to_vec(), vec![1]);
+        assert_eq!(Dao::delegator_heights(5).to_vec(), vec![0, 2]);
+        assert_eq!(Dao::delegator_heights(7).to_vec(), vec![0, 0, 1]);
+    });
+}
+
+#[test]
+fn delegation_is_bounded_by_max_delegators() {
+    new_test_ext().execute_with(|| {
+        for delegator in 2..=5 {
+            assert_ok!(Dao::delegate(RuntimeOrigin::signed(delegator), 1));
+        }
+        assert_noop!(
+            Dao::delegate(RuntimeOrigin::signed(6), 1),
+            Error::<Test>::TooManyDelegators
+        );
+    });
+}
+
+#[test]
+fn delegate_vote_carries_direct_delegators_only() {
+    new_test_ext().execute_with(|| {
+        create_with_period(1, 10);
+        // 4 delegates to 3 and 3 to 2: only 3 counts for 2
+        assert_ok!(Dao::delegate(RuntimeOrigin::signed(3), 2));
+        assert_ok!(Dao::delegate(RuntimeOrigin::signed(4), 3));
+        assert_ok!(Dao::delegate(RuntimeOrigin::signed(5), 2));
+        // 5 voted already, so its weight is its own
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(5), 0, VoteKind::Nay));
+
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 0, VoteKind::Aye));
+        System::assert_last_event(
+            Event::VoteCast { proposal_id: 0, voter: 2, vote: VoteKind::Aye, weight: 2 }.into(),
+        );
+        assert_eq!(tally(), (2, 1, 3));
+        assert_eq!(Dao::delegated_votes(0, 3), Some((2, VoteKind::Aye)));
+        assert_eq!(Dao::delegated_votes(0, 5), None);
+        assert_eq!(Dao::carried_delegators(0, 2).to_vec(), vec![3]);
+        assert_eq!(Dao::get_receipts(&2, 0, 1)[0].1.weight, 2);
+    });
+}
+
+#[test]
+fn direct_vote_overrides_the_delegation() {
+    new_test_ext().execute_with(|| {
+        create_with_period(1, 10);
+        assert_ok!(Dao::delegate(RuntimeOrigin::signed(3), 2));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 0, VoteKind::Aye));
+        assert_eq!(tally(), (2, 0, 2));
+
+        // The delegator takes its weight back, whichever way it votes
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(3), 0, VoteKind::Nay));
+        assert_eq!(tally(), (1, 1, 2));
+        assert_eq!(Dao::delegated_votes(0, 3), None);
+        assert!(Dao::carried_delegators(0, 2).is_empty());
+        assert_noop!(
+            Dao::vote(RuntimeOrigin::signed(3), 0, VoteKind::Aye),
+            Error::<Test>::AlreadyVoted
+        );
+    });
+}
+
+#[test]
+fn delegation_changes_mid_proposal_count_once() {
+    new_test_ext().execute_with(|| {
+        create_with_period(1, 10);
+        assert_ok!(Dao::delegate(RuntimeOrigin::signed(3), 2));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 0, VoteKind::Aye));
+
+        // 3 moves to 4 after 2 carried it: 4 votes with its own weight only
+        assert_ok!(Dao::delegate(RuntimeOrigin::signed(3), 4));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(4), 0, VoteKind::Nay));
+        assert_eq!(tally(), (2, 1, 3));
+
+        // Withdrawing leaves the cast vote standing, until 3 votes itself
+        assert_ok!(Dao::undelegate(RuntimeOrigin::signed(3)));
+        assert_eq!(tally(), (2, 1, 3));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(3), 0, VoteKind::Nay));
+        assert_eq!(tally(), (1, 2, 3));
+    });
+}
+
+#[test]
+fn change_vote_flips_the_tally_both_ways() {
+    new_test_ext().execute_with(|| {
+        create_with_period(1, 10);
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 0, VoteKind::Aye));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(3), 0, VoteKind::Aye));
+
+        assert_ok!(Dao::change_vote(RuntimeOrigin::signed(2), 0, VoteKind::Nay));
+        System::assert_last_event(
+            Event::VoteChanged { proposal_id: 0, voter: 2, old: VoteKind::Aye, new: VoteKind::Nay }
+                .into(),
+        );
+        assert_eq!(tally(), (1, 1, 2));
+        assert_eq!(Dao::get_vote(0, &2), Some(VoteKind::Nay));
+
+        assert_ok!(Dao::change_vote(RuntimeOrigin::signed(2), 0, VoteKind::Aye));
+        assert_eq!(tally(), (2, 0, 2));
+        assert_ok!(Dao::change_vote(RuntimeOrigin::signed(3), 0, VoteKind::Abstain));
+        assert_eq!(tally(), (1, 0, 2));
+
+        // Every change leaves a receipt of the new choice
+        let receipts = Dao::get_receipts(&2, 0, 10);
+        assert_eq!(receipts.len(), 3);
+        assert_eq!(receipts[2].1.choice_hash, Dao::hash_choice(VoteKind::Aye, &[]));
+    });
+}
+
+#[test]
+fn change_vote_needs_a_different_vote() {
+    new_test_ext().execute_with(|| {
+        create_with_period(1, 10);
+        assert_noop!(
+            Dao::change_vote(RuntimeOrigin::signed(2), 0, VoteKind::Nay),
+            Error::<Test>::NotVoted
+        );
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 0, VoteKind::Nay));
+        assert_noop!(
+            Dao::change_vote(RuntimeOrigin::signed(2), 0, VoteKind::Nay),
+            Error::<Test>::SameVote
+        );
+    });
+}
+
+#[test]
+fn change_vote_fails_after_the_voting_period() {
+    new_test_ext().execute_with(|| {
+        create_with_period(1, 10);
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 0, VoteKind::Aye));
+        System::set_block_number(11);
+        assert_noop!(
+            Dao::change_vote(RuntimeOrigin::signed(2), 0, VoteKind::Nay),
+            Error::<Test>::VotingPeriodEnded
+        );
+    });
+}
+
+#[test]
+fn change_vote_moves_the_delegated_weight() {
+    new_test_ext().execute_with(|| {
+        create_with_period(1, 10);
+        assert_ok!(Dao::delegate(RuntimeOrigin::signed(3), 2));
+        assert_ok!(Dao::delegate(RuntimeOrigin::signed(5), 4));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 0, VoteKind::Aye));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(4), 0, VoteKind::Abstain));
+
+        assert_ok!(Dao::change_vote(RuntimeOrigin::signed(2), 0, VoteKind::Nay));
+        assert_eq!(tally(), (0, 2, 4));
+        assert_eq!(Dao::delegated_votes(0, 3), Some((2, VoteKind::Nay)));
+        // Delegators carried by other votes stay where they are
+        assert_eq!(Dao::delegated_votes(0, 5), Some((4, VoteKind::Abstain)));
+
+        // The delegator overriding now takes back a nay
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(3), 0, VoteKind::Aye));
+        assert_eq!(tally(), (1, 1, 4));
+    });
+}
+
+#[test]
+fn retract_then_revote_counts_one_vote() {
+    new_test_ext().execute_with(|| {
+        create_with_period(1, 10);
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 0, VoteKind::Aye));
+
+        assert_ok!(Dao::retract_vote(RuntimeOrigin::signed(2), 0));
+        System::assert_last_event(
+            Event::VoteRetracted { proposal_id: 0, voter: 2, vote: VoteKind::Aye, weight: 1 }
+                .into(),
+        );
+        assert_eq!(tally(), (0, 0, 0));
+        assert_eq!(Dao::get_vote(0, &2), None);
+        assert!(!Dao::has_voted(0, 2));
+
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 0, VoteKind::Nay));
+        assert_eq!(tally(), (0, 1, 1));
+        assert_eq!(Dao::get_vote(0, &2), Some(VoteKind::Nay));
+    });
+}
+
+#[test]
+fn retract_vote_needs_an_open_vote() {
+    new_test_ext().execute_with(|| {
+        create_with_period(1, 10);
+        assert_noop!(
+            Dao::retract_vote(RuntimeOrigin::signed(2), 0),
+            Error::<Test>::NotVoted
+        );
+
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 0, VoteKind::Aye));
+        System::set_block_number(11);
+        assert_noop!(
+            Dao::retract_vote(RuntimeOrigin::signed(2), 0),
+            Error::<Test>::VotingPeriodEnded
+        );
+    });
+}
+
+#[test]
+fn retract_vote_releases_the_delegated_weight() {
+    new_test_ext().execute_with(|| {
+        create_with_period(1, 10);
+        assert_ok!(Dao::delegate(RuntimeOrigin::signed(3), 2));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 0, VoteKind::Aye));
+
+        assert_ok!(Dao::retract_vote(RuntimeOrigin::signed(2), 0));
+        assert_eq!(tally(), (0, 0, 0));
+        assert_eq!(Dao::delegated_votes(0, 3), None);
+        assert!(Dao::carried_delegators(0, 2).is_empty());
+
+        // Voting again carries the delegator again
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 0, VoteKind::Nay));
+        assert_eq!(tally(), (0, 2, 2));
+    });
+}
+
+/// Close the first proposal, created at block 1 with a 10 block voting period
+fn close_after_voting() {
+    System::set_block_number(11);
+    assert_ok!(Dao::close_proposal(RuntimeOrigin::signed(5), 0));
+}
+
+#[test]
+fn rejected_proposal_deposit_is_refunded_without_slashing() {
+    new_test_ext().execute_with(|| {
+        create_with_period(1, 10);
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 0, VoteKind::Nay));
+        close_after_voting();
+
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(Balances::free_balance(1), INITIAL_BALANCE);
+        assert!(!Dao::feature_bits().contains(FeatureFlags::SPAM_DEPOSIT_SLASHING));
+    });
+}
+
+#[test]
+fn spam_proposal_deposit_is_slashed() {
+    new_test_ext().execute_with(|| {
+        SlashRejectedDeposits::set(true);
+        let issuance = Balances::total_issuance();
+        create_with_period(1, 10);
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 0, VoteKind::Nay));
+        close_after_voting();
+
+        System::assert_has_event(
+            Event::DepositSlashed { proposal_id: 0, proposer: 1, amount: 1000 }.into(),
+        );
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(Balances::free_balance(1), INITIAL_BALANCE - 1000);
+        assert_eq!(Balances::total_issuance(), issuance - 1000);
+        assert!(Dao::feature_bits().contains(FeatureFlags::SPAM_DEPOSIT_SLASHING));
+    });
+}
+
+#[test]
+fn rejected_proposal_with_enough_approval_is_refunded() {
+    new_test_ext().execute_with(|| {
+        SlashRejectedDeposits::set(true);
+        create_with_period(1, 10);
+        // 1 aye against 3 nays is 25%, above `MinRefundApproval`
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 0, VoteKind::Aye));
+        for voter in 3..=5 {
+            assert_ok!(Dao::vote(RuntimeOrigin::signed(voter), 0, VoteKind::Nay));
+        }
+        close_after_voting();
+
+        assert_eq!(Dao::get_proposal_details(0).unwrap().status, ProposalStatus::Rejected);
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(Balances::free_balance(1), INITIAL_BALANCE);
+    });
+}
+
+#[test]
+fn cancelled_proposal_is_refunded_with_slashing_on() {
+    new_test_ext().execute_with(|| {
+        SlashRejectedDeposits::set(true);
+        create_with_period(1, 10);
+        assert_ok!(Dao::cancel_proposal(RuntimeOrigin::signed(1), 0));
+
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(Balances::free_balance(1), INITIAL_BALANCE);
+    });
+}
+
+fn propose_call(call: RuntimeCall) {
+    assert_ok!(Dao::create_call_proposal(
+        RuntimeOrigin::signed(1),
+        b"Dispatch a call".to_vec(),
+        b"Description".to_vec(),
+        Some(10),
+        Box::new(call)
+    ));
+}
+
+/// Vote the first proposal through and execute it once voting ended
+fn pass_and_execute() {
+    assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 0, VoteKind::Aye));
+    System::set_block_number(11);
+    assert_ok!(Dao::execute_proposal(RuntimeOrigin::signed(3), 0));
+}
+
+fn transfer_from_pot(value: u128) -> RuntimeCall {
+    RuntimeCall::Balances(pallet_balances::Call::transfer_allow_death { dest: 9, value })
+}
+
+#[test]
+fn executed_call_proposal_dispatches_as_the_dao() {
+    use codec::Encode;
+    use sp_runtime::traits::{BlakeTwo256, Hash};
+
+    new_test_ext().execute_with(|| {
+        let call = RuntimeCall::System(frame_system::Call::remark_with_event {
+            remark: b"approved".to_vec(),
+        });
+        propose_call(call.clone());
+
+        let encoded = call.encode();
+        let proposal = Dao::get_proposal_details(0).unwrap();
+        assert_eq!(proposal.call_hash, Some(sp_io::hashing::blake2_256(&encoded)));
+        assert_eq!(Dao::proposal_calls(0).unwrap().to_vec(), encoded);
+
+        pass_and_execute();
+
+        System::assert_has_event(
+            frame_system::Event::Remarked {
+                sender: Dao::account_id(),
+                hash: BlakeTwo256::hash(b"approved"),
+            }
+            .into(),
+        );
+        System::assert_has_event(Event::ProposalExecuted { proposal_id: 0, executor: 3 }.into());
+        assert_eq!(Dao::get_proposal_details(0).unwrap().status, ProposalStatus::Executed);
+        assert!(Dao::proposal_calls(0).is_none());
+    });
+}
+
+#[test]
+fn call_proposal_spends_from_the_dao_pot() {
+    new_test_ext().execute_with(|| {
+        let pot = Dao::account_id();
+        assert_ok!(Balances::transfer_allow_death(RuntimeOrigin::signed(7), pot, 5000));
+        propose_call(transfer_from_pot(2000));
+
+        pass_and_execute();
+
+        assert_eq!(Balances::free_balance(pot), 3000);
+        assert_eq!(Balances::free_balance(9), INITIAL_BALANCE + 2000);
+        assert_eq!(Balances::reserved_balance(1), 0);
+    });
+}
+
+#[test]
+fn failed_call_marks_the_proposal_execution_failed() {
+    new_test_ext().execute_with(|| {
+        // The pot is empty
+        propose_call(transfer_from_pot(2000));
+
+        pass_and_execute();
+
+        let failed = System::events().into_iter().any(|record| {
+            matches!(
+                record.event,
+                RuntimeEvent::Dao(Event::ProposalExecutionFailed { proposal_id: 0, .. })
+            )
+        });
+        assert!(failed);
+        System::assert_has_event(
+            Event::ProposalStatusChanged {
+                proposal_id: 0,
+                old_status: ProposalStatus::Active,
+                new_status: ProposalStatus::ExecutionFailed,
+            }
+            .into(),
+        );
+        let proposal = Dao::get_proposal_details(0).unwrap();
+        assert_eq!(proposal.status, ProposalStatus::ExecutionFailed);
+        assert_eq!(Balances::free_balance(9), INITIAL_BALANCE);
+        // The proposal passed, so its deposit is refunded all the same
+        assert_eq!(Balances::reserved_balance(1), 0);
+
+        assert_noop!(
+            Dao::execute_proposal(RuntimeOrigin::signed(3), 0),
+            Error::<Test>::AlreadyExecuted
+        );
+    });
+}
+
+#[test]
+fn undecodable_call_fails_the_execution() {
+    new_test_ext().execute_with(|| {
+        propose_call(transfer_from_pot(2000));
+        // As if a runtime upgrade removed the call
+        let garbage = frame_support::BoundedVec::truncate_from(vec![255]);
+        crate::ProposalCalls::<Test>::insert(0, garbage);
+
+        pass_and_execute();
+
+        System::assert_has_event(
+            Event::ProposalExecutionFailed {
+                proposal_id: 0,
+                error: Error::<Test>::UndecodableCall.into(),
+            }
+            .into(),
+        );
+        assert_eq!(Dao::get_proposal_details(0).unwrap().status, ProposalStatus::ExecutionFailed);
+    });
+}
+
+#[test]
+fn call_heavier_than_max_fails_the_execution() {
+    new_test_ext().execute_with(|| {
+        propose_call(transfer_from_pot(1));
+        // As if a runtime upgrade lowered the limit or raised the call's weight
+        MaxCallWeight::set(frame_support::weights::Weight::zero());
+
+        pass_and_execute();
+
+        System::assert_has_event(
+            Event::ProposalExecutionFailed {
+                proposal_id: 0,
+                error: Error::<Test>::CallTooHeavy.into(),
+            }
+            .into(),
+        );
+        assert_eq!(Dao::get_proposal_details(0).unwrap().status, ProposalStatus::ExecutionFailed);
+        assert_eq!(Balances::free_balance(9), INITIAL_BALANCE);
+    });
+}
+
+#[test]
+fn call_proposals_are_bounded() {
+    new_test_ext().execute_with(|| {
+        let remark = |len: usize| {
+            Box::new(RuntimeCall::System(frame_system::Call::remark { remark: vec![0; len] }))
+        };
+        let create = |call| {
+            Dao::create_call_proposal(
+                RuntimeOrigin::signed(1),
+                b"Title".to_vec(),
+                b"Description".to_vec(),
+                None,
+                call,
+            )
+        };
+
+        // MaxCallSize is 256 bytes in the mock
+        assert_noop!(create(remark(300)), Error::<Test>::CallTooLarge);
+        MaxCallWeight::set(frame_support::weights::Weight::zero());
+        assert_noop!(create(remark(10)), Error::<Test>::CallTooHeavy);
+    });
+}
+
+#[test]
+fn ended_call_proposals_drop_their_call() {
+    new_test_ext().execute_with(|| {
+        propose_call(transfer_from_pot(1));
+        propose_call(transfer_from_pot(2));
+
+        assert_ok!(Dao::cancel_proposal(RuntimeOrigin::signed(1), 0));
+        assert!(Dao::proposal_calls(0).is_none());
+
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 1, VoteKind::Nay));
+        System::set_block_number(11);
+        assert_ok!(Dao::close_proposal(RuntimeOrigin::signed(3), 1));
+        assert!(Dao::proposal_calls(1).is_none());
+    });
+}
+
+#[test]
+fn on_initialize_closes_proposals_when_voting_ends() {
+    use frame_support::traits::Hooks;
+
+    new_test_ext().execute_with(|| {
+        create_with_period(1, 10);
+        create_with_period(2, 10);
+        create_with_period(3, 20);
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(4), 0, VoteKind::Aye));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(4), 1, VoteKind::Nay));
+
+        System::set_block_number(11);
+        Dao::on_initialize(11);
+
+        assert_eq!(Dao::get_proposal_details(0).unwrap().status, ProposalStatus::Approved);
+        assert_eq!(Dao::get_proposal_details(1).unwrap().status, ProposalStatus::Rejected);
+        assert_eq!(Dao::get_proposal_details(2).unwrap().status, ProposalStatus::Active);
+        System::assert_has_event(
+            Event::ProposalClosed { proposal_id: 1, final_status: ProposalStatus::Rejected }
+                .into(),
+        );
+        assert!(Dao::proposals_ending_at(11).is_empty());
+        assert_eq!(Dao::proposals_ending_at(21).to_vec(), vec![2]);
+        assert_eq!(Dao::auto_close_cursor(), Some(12));
+        // The rejected proposal's deposit is released as by a manual close
+        assert_eq!(Balances::reserved_balance(2), 0);
+    });
+}
+
+#[test]
+fn auto_close_resumes_where_the_budget_ran_out() {
+    use crate::WeightInfo;
+    use frame_support::{traits::Hooks, weights::constants::RocksDbWeight};
+
+    new_test_ext().execute_with(|| {
+        create_with_period(1, 10);
+        create_with_period(2, 10);
+
+        // Cursor and index reads, cursor write, and a single proposal
+        let db = RocksDbWeight::get();
+        let close = <() as WeightInfo>::close_proposal();
+        AutoCloseWeight::set(db.reads_writes(2, 1).saturating_add(close));
+        System::set_block_number(11);
+        Dao::on_initialize(11);
+
+        assert_eq!(Dao::get_proposal_details(0).unwrap().status, ProposalStatus::Rejected);
+        assert_eq!(Dao::get_proposal_details(1).unwrap().status, ProposalStatus::Active);
+        assert_eq!(Dao::proposals_ending_at(11).to_vec(), vec![1]);
+        assert_eq!(Dao::auto_close_cursor(), Some(11));
+
+        System::set_block_number(12);
+        Dao::on_initialize(12);
+
+        assert_eq!(Dao::get_proposal_details(1).unwrap().status, ProposalStatus::Rejected);
+        assert!(Dao::proposals_ending_at(11).is_empty());
+        assert_eq!(Dao::auto_close_cursor(), Some(12));
+    });
+}
+
+#[test]
+fn zero_auto_close_budget_does_nothing() {
+    use frame_support::{traits::Hooks, weights::Weight};
+
+    new_test_ext().execute_with(|| {
+        create_with_period(1, 10);
+        AutoCloseWeight::set(Weight::zero());
+        System::set_block_number(11);
+
+        assert_eq!(Dao::on_initialize(11), Weight::zero());
+        assert_eq!(Dao::get_proposal_details(0).unwrap().status, ProposalStatus::Active);
+        assert_eq!(Dao::auto_close_cursor(), None);
+    });
+}
+
+#[test]
+fn migration_to_v11_starts_the_cursor_at_the_earliest_ended_proposal() {
+    use frame_support::traits::{GetStorageVersion, Hooks, StorageVersion};
+
+    new_test_ext().execute_with(|| {
+        create_with_period(1, 20);
+        create_with_period(2, 10);
+        // Proposals indexed before the cursor existed
+        crate::AutoCloseCursor::<Test>::kill();
+        StorageVersion::new(10).put::<Dao>();
+
+        Dao::on_runtime_upgrade();
+
+        assert_eq!(Dao::on_chain_storage_version(), 11);
+        assert_eq!(Dao::auto_close_cursor(), Some(11));
+
+        // Both are closed although their end blocks passed long ago
+        System::set_block_number(30);
+        Dao::on_initialize(30);
+        assert_eq!(Dao::get_proposal_details(0).unwrap().status, ProposalStatus::Rejected);
+        assert_eq!(Dao::get_proposal_details(1).unwrap().status, ProposalStatus::Rejected);
+        assert_eq!(Dao::auto_close_cursor(), Some(31));
+    });
+}
+
+#[test]
+fn manual_close_is_a_fallback_and_a_no_op_once_closed() {
+    use frame_support::traits::Hooks;
+
+    new_test_ext().execute_with(|| {
+        create_with_period(1, 10);
+        create_with_period(1, 10);
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 0, VoteKind::Aye));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 1, VoteKind::Aye));
+        System::set_block_number(11);
+
+        // Closed by hand before `on_initialize` ran, which then skips it
+        assert_ok!(Dao::close_proposal(RuntimeOrigin::signed(3), 0));
+        assert_eq!(Dao::proposals_ending_at(11).to_vec(), vec![1]);
+        Dao::on_initialize(11);
+        assert_eq!(Dao::get_proposal_details(1).unwrap().status, ProposalStatus::Approved);
+
+        // Closing again changes nothing and emits nothing
+        System::reset_events();
+        assert_ok!(Dao::close_proposal(RuntimeOrigin::signed(3), 0));
+        assert_ok!(Dao::close_proposal(RuntimeOrigin::signed(3), 1));
+        assert!(System::events().is_empty());
+
+        // Nor after execution
+        assert_ok!(Dao::execute_proposal(RuntimeOrigin::signed(3), 1));
+        System::reset_events();
+        assert_ok!(Dao::close_proposal(RuntimeOrigin::signed(3), 1));
+        assert!(System::events().is_empty());
+        assert_eq!(Dao::get_proposal_details(1).unwrap().status, ProposalStatus::Executed);
+    });
+}
+
+/// Create a proposal filed under `category`
+fn create_in(category: ProposalCategory) {
+    assert_ok!(Dao::create_categorized_proposal(
+        RuntimeOrigin::signed(1),
+        b"Proposal".to_vec(),
+        b"Description".to_vec(),
+        Some(10),
+        category
+    ));
+}
+
+#[test]
+fn proposals_are_indexed_by_category_in_creation_order() {
+    new_test_ext().execute_with(|| {
+        create_in(ProposalCategory::Budget);
+        create_in(ProposalCategory::Technical);
+        create_in(ProposalCategory::Budget);
+        create_with_period(1, 10);
+        create_in(ProposalCategory::Budget);
+
+        assert_eq!(Dao::get_proposal_details(1).unwrap().category, ProposalCategory::Technical);
+        assert_eq!(Dao::get_proposals_by_category(ProposalCategory::Budget, 0, 10), vec![0, 2, 4]);
+        assert_eq!(Dao::get_proposals_by_category(ProposalCategory::Technical, 0, 10), vec![1]);
+        assert_eq!(Dao::get_proposals_by_category(ProposalCategory::General, 0, 10), vec![3]);
+        assert!(Dao::get_proposals_by_category(ProposalCategory::Hiring, 0, 10).is_empty());
+        assert_eq!(Dao::category_count(ProposalCategory::Budget), 3);
+        assert_eq!(Dao::proposals_by_category(ProposalCategory::Budget, 1), Some(2));
+    });
+}
+
+#[test]
+fn category_pages_stop_at_the_end_of_the_index() {
+    new_test_ext().execute_with(|| {
+        for _ in 0..5 {
+            create_in(ProposalCategory::Hiring);
+        }
+        let page = |offset, limit| {
+            Dao::get_proposals_by_category(ProposalCategory::Hiring, offset, limit)
+        };
+
+        assert_eq!(page(0, 2), vec![0, 1]);
+        assert_eq!(page(2, 2), vec![2, 3]);
+        // Partial last page
+        assert_eq!(page(4, 2), vec![4]);
+        assert_eq!(page(3, 10), vec![3, 4]);
+        assert!(page(5, 2).is_empty());
+        assert!(page(100, 2).is_empty());
+        assert!(page(0, 0).is_empty());
+        // No overflow near the end of the range
+        assert!(page(u32::MAX, u32::MAX).is_empty());
+        assert_eq!(page(1, u32::MAX), vec![1, 2, 3, 4]);
+    });
+}
+
+#[test]
+fn category_is_kept_through_the_proposal_lifecycle() {
+    new_test_ext().execute_with(|| {
+        create_in(ProposalCategory::Technical);
+        create_in(ProposalCategory::Technical);
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 0, VoteKind::Aye));
+        assert_ok!(Dao::cancel_proposal(RuntimeOrigin::signed(1), 1));
+        System::set_block_number(11);
+        assert_ok!(Dao::execute_proposal(RuntimeOrigin::signed(3), 0));
+
+        assert_eq!(Dao::get_proposal_details(0).unwrap().category, ProposalCategory::Technical);
+        assert_eq!(Dao::get_proposal_details(1).unwrap().category, ProposalCategory::Technical);
+        assert_eq!(
+            Dao::get_proposals_by_category(ProposalCategory::Technical, 0, 10),
+            vec![0, 1]
+        );
+    });
+}
+
+#[test]
+fn other_create_calls_file_under_general() {
+    new_test_ext().execute_with(|| {
+        create_with_period(1, 10);
+        assert_ok!(Dao::create_parameter_proposal(
+            RuntimeOrigin::signed(1),
+            b"Proposal".to_vec(),
+            b"Description".to_vec(),
+            Some(10),
+            ParameterChange::DaoTextNormalization(false)
+        ));
+        propose_call(RuntimeCall::System(frame_system::Call::remark { remark: vec![] }));
+
+        assert_eq!(
+            Dao::get_proposals_by_category(ProposalCategory::General, 0, 10),
+            vec![0, 1, 2]
+        );
+        assert_eq!(Dao::category_count(ProposalCategory::Technical), 0);
+    });
+}
+
+/// Restrict voting to members and add `members`
+fn members_only(members: &[u64]) {
+    MembersOnly::set(true);
+    for who in members {
+        assert_ok!(Dao::add_member(RuntimeOrigin::root(), *who));
+    }
+}
+
+#[test]
+fn membership_is_managed_by_the_membership_origin() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Dao::add_member(RuntimeOrigin::signed(1), 2),
+            sp_runtime::DispatchError::BadOrigin
+        );
+
+        assert_ok!(Dao::add_member(RuntimeOrigin::root(), 2));
+        System::assert_last_event(Event::MemberAdded { who: 2 }.into());
+        assert!(Dao::is_member(&2));
+        assert_eq!(Dao::member_count(), 1);
+        assert_noop!(Dao::add_member(RuntimeOrigin::root(), 2), Error::<Test>::AlreadyMember);
+
+        assert_noop!(
+            Dao::remove_member(RuntimeOrigin::signed(2), 2),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_ok!(Dao::remove_member(RuntimeOrigin::root(), 2));
+        System::assert_last_event(Event::MemberRemoved { who: 2 }.into());
+        assert!(!Dao::is_member(&2));
+        assert_eq!(Dao::member_count(), 0);
+        assert_noop!(Dao::remove_member(RuntimeOrigin::root(), 2), Error::<Test>::NotAMember);
+    });
+}
+
+#[test]
+fn only_members_vote_when_members_only() {
+    new_test_ext().execute_with(|| {
+        // Open voting ignores membership
+        create_with_period(1, 10);
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(3), 0, VoteKind::Aye));
+        assert!(!Dao::feature_bits().contains(FeatureFlags::MEMBERS_ONLY_VOTING));
+
+        members_only(&[2]);
+        create_with_period(1, 10);
+        assert_noop!(
+            Dao::vote(RuntimeOrigin::signed(3), 1, VoteKind::Aye),
+            Error::<Test>::NotAMember
+        );
+        assert_noop!(
+            Dao::vote_with_salt(RuntimeOrigin::signed(3), 1, VoteKind::Aye, [7; 32]),
+            Error::<Test>::NotAMember
+        );
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 1, VoteKind::Aye));
+        assert_eq!(Dao::get_proposal_details(1).unwrap().votes_for, 1);
+        assert!(Dao::feature_bits().contains(FeatureFlags::MEMBERS_ONLY_VOTING));
+    });
+}
+
+#[test]
+fn removed_member_keeps_votes_already_cast() {
+    new_test_ext().execute_with(|| {
+        members_only(&[2, 3]);
+        create_with_period(1, 10);
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 0, VoteKind::Aye));
+
+        // Leaves mid-proposal: the vote stays, but can no longer be changed
+        assert_ok!(Dao::remove_member(RuntimeOrigin::root(), 2));
+        assert_noop!(
+            Dao::change_vote(RuntimeOrigin::signed(2), 0, VoteKind::Nay),
+            Error::<Test>::NotAMember
+        );
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(3), 0, VoteKind::Aye));
+
+        close_after_voting();
+        let proposal = Dao::get_proposal_details(0).unwrap();
+        assert_eq!((proposal.votes_for, proposal.total_votes), (2, 2));
+        assert_eq!(proposal.status, ProposalStatus::Approved);
+        assert_eq!(Dao::get_vote(0, &2), Some(VoteKind::Aye));
+
+        // Rejoins and votes again
+        create_with_period(1, 10);
+        assert_noop!(
+            Dao::vote(RuntimeOrigin::signed(2), 1, VoteKind::Aye),
+            Error::<Test>::NotAMember
+        );
+        assert_ok!(Dao::add_member(RuntimeOrigin::root(), 2));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 1, VoteKind::Aye));
+    });
+}
+
+#[test]
+fn member_count_feeds_the_quorum() {
+    new_test_ext().execute_with(|| {
+        // Half of three members, rounded up
+        members_only(&[2, 3, 4]);
+        assert_eq!(Dao::member_quorum(), 2);
+        create_with_period(1, 10);
+        assert_eq!(Dao::get_proposal_details(0).unwrap().quorum, 2);
+
+        // A larger requested quorum wins
+        assert_ok!(Dao::create_proposal_with_quorum(
+            RuntimeOrigin::signed(1),
+            b"Proposal".to_vec(),
+            b"Description".to_vec(),
+            Some(10),
+            3
+        ));
+        assert_eq!(Dao::get_proposal_details(1).unwrap().quorum, 3);
+
+        // Fixed at creation: a member leaving changes neither proposal
+        assert_ok!(Dao::remove_member(RuntimeOrigin::root(), 4));
+        assert_eq!(Dao::get_proposal_details(0).unwrap().quorum, 2);
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 0, VoteKind::Aye));
+        close_after_voting();
+        System::assert_has_event(
+            Event::VotingEnded { proposal_id: 0, approved: false, quorum_reached: false }.into(),
+        );
+
+        // Open voting falls back to `MinQuorum`
+        MembersOnly::set(false);
+        assert_eq!(Dao::member_quorum(), 0);
+        create_with_period(1, 10);
+        assert_eq!(Dao::get_proposal_details(2).unwrap().quorum, 1);
+    });
+}
+
+#[test]
+fn delegators_outside_the_members_add_no_weight() {
+    new_test_ext().execute_with(|| {
+        members_only(&[2, 3]);
+        assert_ok!(Dao::delegate(RuntimeOrigin::signed(3), 2));
+        assert_ok!(Dao::delegate(RuntimeOrigin::signed(4), 2));
+        create_with_period(1, 10);
+
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 0, VoteKind::Aye));
+        System::assert_last_event(
+            Event::VoteCast { proposal_id: 0, voter: 2, vote: VoteKind::Aye, weight: 2 }.into(),
+        );
+        assert_eq!(Dao::get_proposal_details(0).unwrap().votes_for, 2);
+    });
+}
+
+/// Vote on a proposal with conviction, locking `balance`
+fn vote_locking(
+    voter: u64,
+    proposal_id: u64,
+    balance: u128,
+    conviction: u8,
+) -> sp_runtime::DispatchResult {
+    Dao::vote_with_conviction(
+        RuntimeOrigin::signed(voter),
+        proposal_id,
+        VoteKind::Aye,
+        balance,
+        conviction,
+    )
+}
+
+#[test]
+fn conviction_multiplies_the_vote_weight() {
+    new_test_ext().execute_with(|| {
+        create_with_period(1, 10);
+
+        assert_ok!(vote_locking(2, 0, 2_500, 3));
+
+        System::assert_has_event(
+            Event::VoteCast { proposal_id: 0, voter: 2, vote: VoteKind::Aye, weight: 3 }.into(),
+        );
+        // Voting ends at block 11, then three voting periods of 10 blocks
+        System::assert_last_event(Event::VoteLocked { who: 2, amount: 2_500, until: 41 }.into());
+        let proposal = Dao::get_proposal_details(0).unwrap();
+        assert_eq!((proposal.votes_for, proposal.total_votes), (3, 3));
+        assert_eq!(Dao::vote_conviction(0, 2), Some(3));
+        assert_eq!(Dao::vote_weight(0, 2), Some(3));
+        assert_eq!(Dao::vote_lock(2), Some((2_500, 41)));
+        assert_eq!(Balances::locks(2)[0].id, crate::VOTE_LOCK_ID);
+        assert_eq!(Balances::usable_balance(2), INITIAL_BALANCE - 2_500);
+    });
+}
+
+#[test]
+fn conviction_must_be_between_one_and_six() {
+    new_test_ext().execute_with(|| {
+        create_with_period(1, 10);
+
+        for conviction in [0, crate::MAX_CONVICTION + 1] {
+            assert_noop!(
+                vote_locking(2, 0, VoteUnit::get(), conviction),
+                Error::<Test>::InvalidConviction
+            );
+        }
+        assert_ok!(vote_locking(2, 0, VoteUnit::get(), crate::MAX_CONVICTION));
+        assert_eq!(Dao::get_proposal_details(0).unwrap().votes_for, 6);
+    });
+}
+
+#[test]
+fn conviction_balance_must_be_free_and_at_least_a_unit() {
+    new_test_ext().execute_with(|| {
+        create_with_period(1, 10);
+
+        assert_noop!(
+            vote_locking(2, 0, INITIAL_BALANCE + 1, 1),
+            Error::<Test>::InsufficientBalance
+        );
+        assert_noop!(
+            vote_locking(2, 0, VoteUnit::get() - 1, crate::MAX_CONVICTION),
+            Error::<Test>::BalanceBelowVoteUnit
+        );
+        assert_ok!(vote_locking(2, 0, VoteUnit::get(), 1));
+        assert_eq!(Dao::get_proposal_details(0).unwrap().votes_for, 1);
+    });
+}
+
+#[test]
+fn locked_balance_does_not_add_vote_weight() {
+    new_test_ext().execute_with(|| {
+        MinQuorum::set(3);
+        create_with_period(1, 10);
+
+        // Locking everything at the lowest conviction still counts as a single vote
+        assert_ok!(vote_locking(2, 0, INITIAL_BALANCE, 1));
+        assert_ok!(vote_locking(3, 0, VoteUnit::get(), 1));
+        let proposal = Dao::get_proposal_details(0).unwrap();
+        assert_eq!((proposal.votes_for, proposal.total_votes), (2, 2));
+        assert_eq!(Dao::vote_weight(0, 2), Dao::vote_weight(0, 3));
+
+        // A large holder alone does not reach the quorum
+        close_after_voting();
+        System::assert_has_event(
+            Event::VotingEnded { proposal_id: 0, approved: false, quorum_reached: false }.into(),
+        );
+        assert_eq!(Dao::get_proposal_details(0).unwrap().status, ProposalStatus::Rejected);
+    });
+}
+
+#[test]
+fn unlock_only_after_the_lock_period() {
+    new_test_ext().execute_with(|| {
+        create_with_period(1, 10);
+        assert_ok!(vote_locking(2, 0, 1_000, 2));
+        assert_noop!(Dao::unlock(RuntimeOrigin::signed(3)), Error::<Test>::NotLocked);
+
+        // Locked until block 31, past the end of voting
+        close_after_voting();
+        assert_noop!(Dao::unlock(RuntimeOrigin::signed(2)), Error::<Test>::LockNotExpired);
+        System::set_block_number(30);
+        assert_noop!(Dao::unlock(RuntimeOrigin::signed(2)), Error::<Test>::LockNotExpired);
+        assert_eq!(Balances::usable_balance(2), INITIAL_BALANCE - 1_000);
+
+        System::set_block_number(31);
+        assert_ok!(Dao::unlock(RuntimeOrigin::signed(2)));
+        System::assert_last_event(Event::Unlocked { who: 2, amount: 1_000 }.into());
+        assert!(Balances::locks(2).is_empty());
+        assert_eq!(Dao::vote_lock(2), None);
+        assert_eq!(Balances::usable_balance(2), INITIAL_BALANCE);
+        assert_noop!(Dao::unlock(RuntimeOrigin::signed(2)), Error::<Test>::NotLocked);
+    });
+}
+
+#[test]
+fn locked_voter_still_pays_fees_and_deposits() {
+    use frame_support::traits::{Currency, ExistenceRequirement, WithdrawReasons};
+
+    new_test_ext().execute_with(|| {
+        create_with_period(1, 10);
+        assert_ok!(vote_locking(2, 0, 8_000, 1));
+        let fee = |amount| {
+            Balances::withdraw(
+                &2,
+                amount,
+                WithdrawReasons::TRANSACTION_PAYMENT,
+                ExistenceRequirement::KeepAlive,
+            )
+            .map(drop)
+        };
+
+        // Fees and deposits come out of the unlocked rest
+        assert!(fee(3_000).is_err());
+        assert_ok!(fee(100));
+        assert_ok!(Dao::create_proposal(
+            RuntimeOrigin::signed(2),
+            b"Title".to_vec(),
+            b"Description".to_vec(),
+            None
+        ));
+        assert_eq!(Balances::reserved_balance(2), ProposalDeposit::get());
+
+        // Once the lock expired the voter pays for `unlock` and gets everything back
+        System::set_block_number(21);
+        assert_ok!(fee(100));
+        assert_ok!(Dao::unlock(RuntimeOrigin::signed(2)));
+        assert_eq!(Balances::usable_balance(2), Balances::free_balance(2));
+    });
+}
+
+#[test]
+fn later_conviction_votes_only_extend_the_lock() {
+    new_test_ext().execute_with(|| {
+        create_with_period(1, 10);
+        create_with_period(1, 20);
+        create_with_period(1, 100);
+
+        assert_ok!(vote_locking(2, 0, 3_000, 6));
+        assert_eq!(Dao::vote_lock(2), Some((3_000, 71)));
+
+        // Would lock less until block 41, the earlier lock is larger and lasts longer
+        assert_ok!(vote_locking(2, 1, 1_000, 1));
+        assert_eq!(Dao::vote_lock(2), Some((3_000, 71)));
+
+        assert_ok!(vote_locking(2, 2, 5_000, 2));
+        assert_eq!(Dao::vote_lock(2), Some((5_000, 301)));
+        assert_eq!(Balances::locks(2).len(), 1);
+    });
+}
+
+#[test]
+fn changing_or_retracting_moves_the_whole_conviction_weight() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Dao::delegate(RuntimeOrigin::signed(3), 2));
+        create_with_period(1, 10);
+        assert_ok!(vote_locking(2, 0, 1_000, 4));
+        // Four for the voter, one for the delegator
+        assert_eq!(Dao::get_proposal_details(0).unwrap().votes_for, 5);
+
+        assert_ok!(Dao::change_vote(RuntimeOrigin::signed(2), 0, VoteKind::Nay));
+        let proposal = Dao::get_proposal_details(0).unwrap();
+        assert_eq!((proposal.votes_for, proposal.votes_against), (0, 5));
+
+        assert_ok!(Dao::retract_vote(RuntimeOrigin::signed(2), 0));
+        System::assert_last_event(
+            Event::VoteRetracted { proposal_id: 0, voter: 2, vote: VoteKind::Nay, weight: 5 }
+                .into(),
+        );
+        assert_eq!(Dao::get_proposal_details(0).unwrap().total_votes, 0);
+        assert_eq!(Dao::vote_conviction(0, 2), None);
+        assert_eq!(Dao::vote_weight(0, 2), None);
+        // The lock stays
+        assert_eq!(Dao::vote_lock(2), Some((1_000, 51)));
+
+        // A plain vote afterwards weighs one again, plus the delegator
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 0, VoteKind::Aye));
+        assert_eq!(Dao::get_proposal_details(0).unwrap().votes_for, 2);
+    });
+}
+
+/// Amend the first proposal as its proposer
+fn amend(title: &[u8], description: &[u8]) -> sp_runtime::DispatchResult {
+    Dao::amend_proposal(RuntimeOrigin::signed(1), 0, title.to_vec(), description.to_vec())
+}
+
+#[test]
+fn proposer_amends_a_proposal_without_votes() {
+    new_test_ext().execute_with(|| {
+        create_with_period(1, 10);
+
+        assert_ok!(amend(b"Fixed title", b"Fixed description"));
+        System::assert_last_event(Event::ProposalAmended { proposal_id: 0, revision: 1 }.into());
+        assert_ok!(amend(b"Fixed title, again", b"Fixed description"));
+        System::assert_last_event(Event::ProposalAmended { proposal_id: 0, revision: 2 }.into());
+
+        let proposal = Dao::get_proposal_details(0).unwrap();
+        assert_eq!(proposal.title.to_vec(), b"Fixed title, again".to_vec());
+        assert_eq!(proposal.description.to_vec(), b"Fixed description".to_vec());
+        assert_eq!(proposal.revision, 2);
+        // Nothing else changes
+        assert_eq!((proposal.voting_end, proposal.deposit), (11, 1000));
+        assert_eq!(proposal.status, ProposalStatus::Active);
+    });
+}
+
+#[test]
+fn amending_fails_from_the_first_vote_on() {
+    new_test_ext().execute_with(|| {
+        create_with_period(1, 10);
+        assert_ok!(amend(b"Before the vote", b"Description"));
+
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 0, VoteKind::Abstain));
+        assert_noop!(amend(b"After the vote", b"Description"), Error::<Test>::VotesAlreadyCast);
+        let proposal = Dao::get_proposal_details(0).unwrap();
+        assert_eq!(proposal.title.to_vec(), b"Before the vote".to_vec());
+        assert_eq!(proposal.revision, 1);
+    });
+}
+
+#[test]
+fn amending_fails_after_an_anonymous_vote() {
+    new_test_ext().execute_with(|| {
+        create_with_period(1, 10);
+        let preimage = [7u8; 32];
+        assert_ok!(Dao::issue_poll_vouchers(RuntimeOrigin::signed(1), 0, vec![voucher(preimage)]));
+        assert_ok!(Dao::vote_with_voucher(RuntimeOrigin::none(), 0, preimage, VoteKind::Aye));
+
+        assert_noop!(amend(b"Title", b"Description"), Error::<Test>::VotesAlreadyCast);
+    });
+}
+
+#[test]
+fn only_the_proposer_amends_an_open_proposal() {
+    new_test_ext().execute_with(|| {
+        create_with_period(1, 10);
+        assert_noop!(
+            Dao::amend_proposal(RuntimeOrigin::signed(2), 0, b"T".to_vec(), b"D".to_vec()),
+            Error::<Test>::ProposalNotActive
+        );
+        assert_noop!(
+            Dao::amend_proposal(RuntimeOrigin::signed(1), 9, b"T".to_vec(), b"D".to_vec()),
+            Error::<Test>::ProposalNotFound
+        );
+        assert_noop!(amend(&[b'x'; 257], b"Description"), Error::<Test>::TitleTooLong);
+
+        System::set_block_number(11);
+        assert_noop!(amend(b"Title", b"Description"), Error::<Test>::VotingPeriodEnded);
+
+        create_with_period(1, 10);
+        assert_ok!(Dao::cancel_proposal(RuntimeOrigin::signed(1), 1));
+        assert_noop!(
+            Dao::amend_proposal(RuntimeOrigin::signed(1), 1, b"T".to_vec(), b"D".to_vec()),
+            Error::<Test>::ProposalNotActive
+        );
+    });
+}
+
+#[test]
+fn weights_grow_with_text_and_delegators() {
+    use crate::WeightInfo;
+    use frame_support::dispatch::GetDispatchInfo;
+
+    // The estimates grow with title and description, up to 256 and 2048 bytes in the mock
+    let create = <() as WeightInfo>::create_proposal;
+    assert!(create(0, 0).all_lt(create(256, 0)));
+    assert!(create(256, 0).all_lt(create(256, 2048)));
+    let amend = <() as WeightInfo>::amend_proposal;
+    assert!(amend(0, 0).all_lt(amend(128, 1024)));
+    let vote = <() as WeightInfo>::vote;
+    assert!(vote(0).all_lt(vote(4)));
+
+    new_test_ext().execute_with(|| {
+        // `execute_proposal` reserves `MaxCallWeight` for the proposal's call on top of its own
+        let info = crate::Call::<Test>::execute_proposal { proposal_id: 0 }.get_dispatch_info();
+        let own = <() as WeightInfo>::execute_proposal();
+        assert_eq!(info.weight, own.saturating_add(MaxCallWeight::get()));
+    });
+}
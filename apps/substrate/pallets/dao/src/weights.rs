@@ -0,0 +1,495 @@
+//! Weights for the DAO pallet.
+//!
+//! Every call takes its weight from `WeightInfo`. The proposal creation calls and
+//! `amend_proposal` are a base cost plus a slope per byte of title and of description, and
+//! `create_call_proposal` one per byte of the encoded call; the voting calls grow with the
+//! delegators a vote carries, `delegate` and `undelegate` with the depth of the delegation
+//! chain and `issue_poll_vouchers` with the vouchers issued. These are the shapes measured
+//! by the benchmarks in `benchmarking.rs`. The figures below are
+//! estimates for reference hardware and are to be replaced by the benchmark output of the
+//! production runtime:
+//!
+//! ```text
+//! benchmark pallet --pallet pallet_dao --extrinsic '*' --steps 3 --repeat 20
+//! ```
+
+use core::marker::PhantomData;
+use frame_support::{
+    traits::Get,
+    weights::{constants::RocksDbWeight, Weight},
+};
+
+/// Weight functions needed by the DAO pallet
+pub trait WeightInfo {
+    /// `create_proposal` with a title of `t` and a description of `d` bytes
+    fn create_proposal(t: u32, d: u32) -> Weight;
+    /// `vote` by an account with `n` delegators
+    fn vote(n: u32) -> Weight;
+    /// `execute_proposal`, without the proposal's call
+    fn execute_proposal() -> Weight;
+    /// `close_proposal`
+    fn close_proposal() -> Weight;
+    /// `cancel_proposal`
+    fn cancel_proposal() -> Weight;
+    /// `set_text_normalization`
+    fn set_text_normalization() -> Weight;
+    /// `close_proposal_unsigned`
+    fn close_proposal_unsigned() -> Weight;
+    /// `vote_with_salt` by an account with `n` delegators
+    fn vote_with_salt(n: u32) -> Weight;
+    /// `issue_poll_vouchers` with `v` vouchers
+    fn issue_poll_vouchers(v: u32) -> Weight;
+    /// `vote_with_voucher`
+    fn vote_with_voucher() -> Weight;
+    /// `create_parameter_proposal` with `t` and `d` bytes of text
+    fn create_parameter_proposal(t: u32, d: u32) -> Weight;
+    /// `create_proposal_with_quorum` with `t` and `d` bytes of text
+    fn create_proposal_with_quorum(t: u32, d: u32) -> Weight;
+    /// `delegate` in chains of `d` delegations, replacing an existing delegation
+    fn delegate(d: u32) -> Weight;
+    /// `undelegate` from a chain of `d` delegations
+    fn undelegate(d: u32) -> Weight;
+    /// `change_vote` of a vote carrying `n` delegators
+    fn change_vote(n: u32) -> Weight;
+    /// `retract_vote` of a vote carrying `n` delegators
+    fn retract_vote(n: u32) -> Weight;
+    /// `create_call_proposal` with `t` and `d` bytes of text and a call of `c`
+    /// bytes
+    fn create_call_proposal(t: u32, d: u32, c: u32) -> Weight;
+    /// `create_categorized_proposal` with `t` and `d` bytes of text
+    fn create_categorized_proposal(t: u32, d: u32) -> Weight;
+    /// `add_member`
+    fn add_member() -> Weight;
+    /// `remove_member`
+    fn remove_member() -> Weight;
+    /// `vote_with_conviction` by an account with `n` delegators
+    fn vote_with_conviction(n: u32) -> Weight;
+    /// `unlock`
+    fn unlock() -> Weight;
+    /// `amend_proposal` with a new title of `t` and description of `d` bytes
+    fn amend_proposal(t: u32, d: u32) -> Weight;
+}
+
+/// Weights for the DAO pallet using the runtime's database weights
+pub struct SubstrateWeight<T>(PhantomData<T>);
+
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+    /// Reads: `NormalizeText`, `MemberCount`, `ProposalCount`, `ProposalsEndingAt`,
+    /// `CategoryCount`, deposit balance.
+    /// Writes: `Proposals`, `ProposalsEndingAt`, `ProposalsByCategory`, `CategoryCount`,
+    /// `ProposalCount`, deposit balance.
+    fn create_proposal(t: u32, d: u32) -> Weight {
+        Weight::from_parts(52_000_000, 4_300)
+            // Normalizing and storing the title
+            .saturating_add(Weight::from_parts(1_300, 1).saturating_mul(t.into()))
+            // Normalizing and storing the description
+            .saturating_add(Weight::from_parts(1_300, 1).saturating_mul(d.into()))
+            .saturating_add(T::DbWeight::get().reads(6))
+            .saturating_add(T::DbWeight::get().writes(6))
+    }
+
+    /// Reads: `Members`, `Proposals`, `HasVoted`, `DelegatedVotes`, `CarriedDelegators` of
+    /// the delegate overridden, `Delegators`, `ReceiptRange`; `Members`, `HasVoted` and
+    /// `DelegatedVotes` per delegator.
+    /// Writes: `Votes`, `HasVoted`, `DelegatedVotes`, `CarriedDelegators` of the delegate
+    /// overridden and of the caller, `Proposals`, `Receipts` (new and evicted),
+    /// `ReceiptRange`; `DelegatedVotes` per delegator.
+    fn vote(n: u32) -> Weight {
+        Weight::from_parts(46_000_000, 5_100)
+            // Checking and recording each delegator
+            .saturating_add(Weight::from_parts(9_500_000, 2_600).saturating_mul(n.into()))
+            .saturating_add(T::DbWeight::get().reads(7))
+            .saturating_add(T::DbWeight::get().writes(9))
+            .saturating_add(T::DbWeight::get().reads(3).saturating_mul(n.into()))
+            .saturating_add(T::DbWeight::get().writes(1).saturating_mul(n.into()))
+    }
+
+    /// Reads: `Proposals`, `ProposalsEndingAt`, `ProposalCalls`, deposit balance.
+    /// Writes: `Proposals`, `ProposalsEndingAt`, `ProposalCalls`, deposit balance.
+    /// The weight of the proposal's call comes on top.
+    fn execute_proposal() -> Weight {
+        Weight::from_parts(41_000_000, 4_300)
+            .saturating_add(T::DbWeight::get().reads(4))
+            .saturating_add(T::DbWeight::get().writes(4))
+    }
+
+    /// Reads: `Proposals`, `ProposalsEndingAt`, deposit balance.
+    /// Writes: `Proposals`, `ProposalsEndingAt`, `ProposalCalls`, deposit balance.
+    fn close_proposal() -> Weight {
+        Weight::from_parts(36_000_000, 4_300)
+            .saturating_add(T::DbWeight::get().reads(3))
+            .saturating_add(T::DbWeight::get().writes(4))
+    }
+
+    /// Reads: `Proposals`, `ProposalsEndingAt`, deposit balance.
+    /// Writes: `Proposals`, `ProposalsEndingAt`, `ProposalCalls`, deposit balance.
+    fn cancel_proposal() -> Weight {
+        Weight::from_parts(34_000_000, 4_300)
+            .saturating_add(T::DbWeight::get().reads(3))
+            .saturating_add(T::DbWeight::get().writes(4))
+    }
+
+    /// Writes: `NormalizeText`.
+    fn set_text_normalization() -> Weight {
+        Weight::from_parts(8_000_000, 0).saturating_add(T::DbWeight::get().writes(1))
+    }
+
+    /// Reads: `Proposals`, `ProposalsEndingAt`, deposit balance.
+    /// Writes: `Proposals`, `ProposalsEndingAt`, `ProposalCalls`, deposit balance.
+    fn close_proposal_unsigned() -> Weight {
+        Weight::from_parts(35_000_000, 4_300)
+            .saturating_add(T::DbWeight::get().reads(3))
+            .saturating_add(T::DbWeight::get().writes(4))
+    }
+
+    /// Same storage as `vote`.
+    fn vote_with_salt(n: u32) -> Weight {
+        Weight::from_parts(47_000_000, 5_100)
+            // Checking and recording each delegator
+            .saturating_add(Weight::from_parts(9_500_000, 2_600).saturating_mul(n.into()))
+            .saturating_add(T::DbWeight::get().reads(7))
+            .saturating_add(T::DbWeight::get().writes(9))
+            .saturating_add(T::DbWeight::get().reads(3).saturating_mul(n.into()))
+            .saturating_add(T::DbWeight::get().writes(1).saturating_mul(n.into()))
+    }
+
+    /// Reads: `Proposals`, `PollVoucherCount`; `PollVouchers` per voucher.
+    /// Writes: `PollVoucherCount`; `PollVouchers` per voucher.
+    fn issue_poll_vouchers(v: u32) -> Weight {
+        Weight::from_parts(24_000_000, 4_300)
+            // Checking each voucher against the list and storage
+            .saturating_add(Weight::from_parts(4_800_000, 2_500).saturating_mul(v.into()))
+            .saturating_add(T::DbWeight::get().reads(2))
+            .saturating_add(T::DbWeight::get().writes(1))
+            .saturating_add(T::DbWeight::get().reads(1).saturating_mul(v.into()))
+            .saturating_add(T::DbWeight::get().writes(1).saturating_mul(v.into()))
+    }
+
+    /// Reads: `Proposals`, `PollVouchers`.
+    /// Writes: `PollVouchers`, `Proposals`.
+    fn vote_with_voucher() -> Weight {
+        Weight::from_parts(31_000_000, 4_300)
+            .saturating_add(T::DbWeight::get().reads(2))
+            .saturating_add(T::DbWeight::get().writes(2))
+    }
+
+    /// Same storage as `create_proposal`.
+    fn create_parameter_proposal(t: u32, d: u32) -> Weight {
+        Weight::from_parts(53_000_000, 4_300)
+            // Normalizing and storing the title
+            .saturating_add(Weight::from_parts(1_300, 1).saturating_mul(t.into()))
+            // Normalizing and storing the description
+            .saturating_add(Weight::from_parts(1_300, 1).saturating_mul(d.into()))
+            .saturating_add(T::DbWeight::get().reads(6))
+            .saturating_add(T::DbWeight::get().writes(6))
+    }
+
+    /// Same storage as `create_proposal`.
+    fn create_proposal_with_quorum(t: u32, d: u32) -> Weight {
+        Weight::from_parts(52_000_000, 4_300)
+            // Normalizing and storing the title
+            .saturating_add(Weight::from_parts(1_300, 1).saturating_mul(t.into()))
+            // Normalizing and storing the description
+            .saturating_add(Weight::from_parts(1_300, 1).saturating_mul(d.into()))
+            .saturating_add(T::DbWeight::get().reads(6))
+            .saturating_add(T::DbWeight::get().writes(6))
+    }
+
+    /// Reads: `Delegations`, `DelegatorHeights` of the caller, `Delegators` of the previous and
+    /// the new delegate; `Delegations` and `DelegatorHeights` per delegation of both chains.
+    /// Writes: `Delegations`, `Delegators` of the previous and the new delegate;
+    /// `DelegatorHeights` per delegation of both chains.
+    fn delegate(d: u32) -> Weight {
+        Weight::from_parts(36_000_000, 3_600)
+            // Checking the chain and updating the heights along both chains
+            .saturating_add(Weight::from_parts(5_900_000, 2_500).saturating_mul(d.into()))
+            .saturating_add(T::DbWeight::get().reads(5))
+            .saturating_add(T::DbWeight::get().writes(3))
+            .saturating_add(T::DbWeight::get().reads(5).saturating_mul(d.into()))
+            .saturating_add(T::DbWeight::get().writes(2).saturating_mul(d.into()))
+    }
+
+    /// Reads: `Delegations`, `DelegatorHeights` of the caller, `Delegators`; `Delegations` and
+    /// `DelegatorHeights` per delegation of the chain.
+    /// Writes: `Delegations`, `Delegators`; `DelegatorHeights` per delegation of the chain.
+    fn undelegate(d: u32) -> Weight {
+        Weight::from_parts(24_000_000, 3_600)
+            // Updating the heights along the chain
+            .saturating_add(Weight::from_parts(2_900_000, 2_500).saturating_mul(d.into()))
+            .saturating_add(T::DbWeight::get().reads(3))
+            .saturating_add(T::DbWeight::get().writes(2))
+            .saturating_add(T::DbWeight::get().reads(2).saturating_mul(d.into()))
+            .saturating_add(T::DbWeight::get().writes(1).saturating_mul(d.into()))
+    }
+
+    /// Reads: `Members`, `Proposals`, `Votes`, `CarriedDelegators`, `VoteWeights`,
+    /// `ReceiptRange`.
+    /// Writes: `Proposals`, `Votes`, `Receipts` (new and evicted), `ReceiptRange`;
+    /// `DelegatedVotes` per delegator carried.
+    fn change_vote(n: u32) -> Weight {
+        Weight::from_parts(44_000_000, 5_100)
+            // Moving each carried delegator
+            .saturating_add(Weight::from_parts(4_100_000, 0).saturating_mul(n.into()))
+            .saturating_add(T::DbWeight::get().reads(6))
+            .saturating_add(T::DbWeight::get().writes(5))
+            .saturating_add(T::DbWeight::get().writes(1).saturating_mul(n.into()))
+    }
+
+    /// Reads: `Proposals`, `Votes`, `CarriedDelegators`, `VoteWeights`.
+    /// Writes: `Votes`, `HasVoted`, `CarriedDelegators`, `VoteConvictions`, `VoteWeights`,
+    /// `Proposals`; `DelegatedVotes` per delegator carried.
+    fn retract_vote(n: u32) -> Weight {
+        Weight::from_parts(37_000_000, 5_100)
+            // Releasing each carried delegator
+            .saturating_add(Weight::from_parts(3_700_000, 0).saturating_mul(n.into()))
+            .saturating_add(T::DbWeight::get().reads(4))
+            .saturating_add(T::DbWeight::get().writes(6))
+            .saturating_add(T::DbWeight::get().writes(1).saturating_mul(n.into()))
+    }
+
+    /// Same storage as `create_proposal`, and writes `ProposalCalls`.
+    fn create_call_proposal(t: u32, d: u32, c: u32) -> Weight {
+        Weight::from_parts(58_000_000, 4_300)
+            // Normalizing and storing the title
+            .saturating_add(Weight::from_parts(1_300, 1).saturating_mul(t.into()))
+            // Normalizing and storing the description
+            .saturating_add(Weight::from_parts(1_300, 1).saturating_mul(d.into()))
+            // Encoding, hashing and storing the call
+            .saturating_add(Weight::from_parts(1_100, 1).saturating_mul(c.into()))
+            .saturating_add(T::DbWeight::get().reads(6))
+            .saturating_add(T::DbWeight::get().writes(7))
+    }
+
+    /// Same storage as `create_proposal`.
+    fn create_categorized_proposal(t: u32, d: u32) -> Weight {
+        Weight::from_parts(52_000_000, 4_300)
+            // Normalizing and storing the title
+            .saturating_add(Weight::from_parts(1_300, 1).saturating_mul(t.into()))
+            // Normalizing and storing the description
+            .saturating_add(Weight::from_parts(1_300, 1).saturating_mul(d.into()))
+            .saturating_add(T::DbWeight::get().reads(6))
+            .saturating_add(T::DbWeight::get().writes(6))
+    }
+
+    /// Reads: `Members`, `MemberCount`.
+    /// Writes: `Members`, `MemberCount`.
+    fn add_member() -> Weight {
+        Weight::from_parts(18_000_000, 3_500)
+            .saturating_add(T::DbWeight::get().reads(2))
+            .saturating_add(T::DbWeight::get().writes(2))
+    }
+
+    /// Reads: `Members`, `MemberCount`.
+    /// Writes: `Members`, `MemberCount`.
+    fn remove_member() -> Weight {
+        Weight::from_parts(19_000_000, 3_500)
+            .saturating_add(T::DbWeight::get().reads(2))
+            .saturating_add(T::DbWeight::get().writes(2))
+    }
+
+    /// Same storage as `vote`, and the free balance, `Proposals` again, `VoteConvictions`,
+    /// `VoteWeights`, `VoteLocks`, balance locks and account.
+    fn vote_with_conviction(n: u32) -> Weight {
+        Weight::from_parts(68_000_000, 5_400)
+            // Checking and recording each delegator
+            .saturating_add(Weight::from_parts(9_500_000, 2_600).saturating_mul(n.into()))
+            .saturating_add(T::DbWeight::get().reads(12))
+            .saturating_add(T::DbWeight::get().writes(14))
+            .saturating_add(T::DbWeight::get().reads(3).saturating_mul(n.into()))
+            .saturating_add(T::DbWeight::get().writes(1).saturating_mul(n.into()))
+    }
+
+    /// Reads: `VoteLocks`, balance locks and account.
+    /// Writes: `VoteLocks`, balance locks and account.
+    fn unlock() -> Weight {
+        Weight::from_parts(32_000_000, 4_800)
+            .saturating_add(T::DbWeight::get().reads(3))
+            .saturating_add(T::DbWeight::get().writes(3))
+    }
+
+    /// Reads: `NormalizeText`, `Proposals`.
+    /// Writes: `Proposals`.
+    fn amend_proposal(t: u32, d: u32) -> Weight {
+        Weight::from_parts(31_000_000, 4_300)
+            // Normalizing and storing the title
+            .saturating_add(Weight::from_parts(1_300, 1).saturating_mul(t.into()))
+            // Normalizing and storing the description
+            .saturating_add(Weight::from_parts(1_300, 1).saturating_mul(d.into()))
+            .saturating_add(T::DbWeight::get().reads(2))
+            .saturating_add(T::DbWeight::get().writes(1))
+    }
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+    fn create_proposal(t: u32, d: u32) -> Weight {
+        Weight::from_parts(52_000_000, 4_300)
+            .saturating_add(Weight::from_parts(1_300, 1).saturating_mul(t.into()))
+            .saturating_add(Weight::from_parts(1_300, 1).saturating_mul(d.into()))
+            .saturating_add(RocksDbWeight::get().reads(6))
+            .saturating_add(RocksDbWeight::get().writes(6))
+    }
+
+    fn vote(n: u32) -> Weight {
+        Weight::from_parts(46_000_000, 5_100)
+            .saturating_add(Weight::from_parts(9_500_000, 2_600).saturating_mul(n.into()))
+            .saturating_add(RocksDbWeight::get().reads(7))
+            .saturating_add(RocksDbWeight::get().writes(9))
+            .saturating_add(RocksDbWeight::get().reads(3).saturating_mul(n.into()))
+            .saturating_add(RocksDbWeight::get().writes(1).saturating_mul(n.into()))
+    }
+
+    fn execute_proposal() -> Weight {
+        Weight::from_parts(41_000_000, 4_300)
+            .saturating_add(RocksDbWeight::get().reads(4))
+            .saturating_add(RocksDbWeight::get().writes(4))
+    }
+
+    fn close_proposal() -> Weight {
+        Weight::from_parts(36_000_000, 4_300)
+            .saturating_add(RocksDbWeight::get().reads(3))
+            .saturating_add(RocksDbWeight::get().writes(4))
+    }
+
+    fn cancel_proposal() -> Weight {
+        Weight::from_parts(34_000_000, 4_300)
+            .saturating_add(RocksDbWeight::get().reads(3))
+            .saturating_add(RocksDbWeight::get().writes(4))
+    }
+
+    fn set_text_normalization() -> Weight {
+        Weight::from_parts(8_000_000, 0).saturating_add(RocksDbWeight::get().writes(1))
+    }
+
+    fn close_proposal_unsigned() -> Weight {
+        Weight::from_parts(35_000_000, 4_300)
+            .saturating_add(RocksDbWeight::get().reads(3))
+            .saturating_add(RocksDbWeight::get().writes(4))
+    }
+
+    fn vote_with_salt(n: u32) -> Weight {
+        Weight::from_parts(47_000_000, 5_100)
+            .saturating_add(Weight::from_parts(9_500_000, 2_600).saturating_mul(n.into()))
+            .saturating_add(RocksDbWeight::get().reads(7))
+            .saturating_add(RocksDbWeight::get().writes(9))
+            .saturating_add(RocksDbWeight::get().reads(3).saturating_mul(n.into()))
+            .saturating_add(RocksDbWeight::get().writes(1).saturating_mul(n.into()))
+    }
+
+    fn issue_poll_vouchers(v: u32) -> Weight {
+        Weight::from_parts(24_000_000, 4_300)
+            .saturating_add(Weight::from_parts(4_800_000, 2_500).saturating_mul(v.into()))
+            .saturating_add(RocksDbWeight::get().reads(2))
+            .saturating_add(RocksDbWeight::get().writes(1))
+            .saturating_add(RocksDbWeight::get().reads(1).saturating_mul(v.into()))
+            .saturating_add(RocksDbWeight::get().writes(1).saturating_mul(v.into()))
+    }
+
+    fn vote_with_voucher() -> Weight {
+        Weight::from_parts(31_000_000, 4_300)
+            .saturating_add(RocksDbWeight::get().reads(2))
+            .saturating_add(RocksDbWeight::get().writes(2))
+    }
+
+    fn create_parameter_proposal(t: u32, d: u32) -> Weight {
+        Weight::from_parts(53_000_000, 4_300)
+            .saturating_add(Weight::from_parts(1_300, 1).saturating_mul(t.into()))
+            .saturating_add(Weight::from_parts(1_300, 1).saturating_mul(d.into()))
+            .saturating_add(RocksDbWeight::get().reads(6))
+            .saturating_add(RocksDbWeight::get().writes(6))
+    }
+
+    fn create_proposal_with_quorum(t: u32, d: u32) -> Weight {
+        Weight::from_parts(52_000_000, 4_300)
+            .saturating_add(Weight::from_parts(1_300, 1).saturating_mul(t.into()))
+            .saturating_add(Weight::from_parts(1_300, 1).saturating_mul(d.into()))
+            .saturating_add(RocksDbWeight::get().reads(6))
+            .saturating_add(RocksDbWeight::get().writes(6))
+    }
+
+    fn delegate(d: u32) -> Weight {
+        Weight::from_parts(36_000_000, 3_600)
+            .saturating_add(Weight::from_parts(5_900_000, 2_500).saturating_mul(d.into()))
+            .saturating_add(RocksDbWeight::get().reads(5))
+            .saturating_add(RocksDbWeight::get().writes(3))
+            .saturating_add(RocksDbWeight::get().reads(5).saturating_mul(d.into()))
+            .saturating_add(RocksDbWeight::get().writes(2).saturating_mul(d.into()))
+    }
+
+    fn undelegate(d: u32) -> Weight {
+        Weight::from_parts(24_000_000, 3_600)
+            .saturating_add(Weight::from_parts(2_900_000, 2_500).saturating_mul(d.into()))
+            .saturating_add(RocksDbWeight::get().reads(3))
+            .saturating_add(RocksDbWeight::get().writes(2))
+            .saturating_add(RocksDbWeight::get().reads(2).saturating_mul(d.into()))
+            .saturating_add(RocksDbWeight::get().writes(1).saturating_mul(d.into()))
+    }
+
+    fn change_vote(n: u32) -> Weight {
+        Weight::from_parts(44_000_000, 5_100)
+            .saturating_add(Weight::from_parts(4_100_000, 0).saturating_mul(n.into()))
+            .saturating_add(RocksDbWeight::get().reads(6))
+            .saturating_add(RocksDbWeight::get().writes(5))
+            .saturating_add(RocksDbWeight::get().writes(1).saturating_mul(n.into()))
+    }
+
+    fn retract_vote(n: u32) -> Weight {
+        Weight::from_parts(37_000_000, 5_100)
+            .saturating_add(Weight::from_parts(3_700_000, 0).saturating_mul(n.into()))
+            .saturating_add(RocksDbWeight::get().reads(4))
+            .saturating_add(RocksDbWeight::get().writes(6))
+            .saturating_add(RocksDbWeight::get().writes(1).saturating_mul(n.into()))
+    }
+
+    fn create_call_proposal(t: u32, d: u32, c: u32) -> Weight {
+        Weight::from_parts(58_000_000, 4_300)
+            .saturating_add(Weight::from_parts(1_300, 1).saturating_mul(t.into()))
+            .saturating_add(Weight::from_parts(1_300, 1).saturating_mul(d.into()))
+            .saturating_add(Weight::from_parts(1_100, 1).saturating_mul(c.into()))
+            .saturating_add(RocksDbWeight::get().reads(6))
+            .saturating_add(RocksDbWeight::get().writes(7))
+    }
+
+    fn create_categorized_proposal(t: u32, d: u32) -> Weight {
+        Weight::from_parts(52_000_000, 4_300)
+            .saturating_add(Weight::from_parts(1_300, 1).saturating_mul(t.into()))
+            .saturating_add(Weight::from_parts(1_300, 1).saturating_mul(d.into()))
+            .saturating_add(RocksDbWeight::get().reads(6))
+            .saturating_add(RocksDbWeight::get().writes(6))
+    }
+
+    fn add_member() -> Weight {
+        Weight::from_parts(18_000_000, 3_500)
+            .saturating_add(RocksDbWeight::get().reads(2))
+            .saturating_add(RocksDbWeight::get().writes(2))
+    }
+
+    fn remove_member() -> Weight {
+        Weight::from_parts(19_000_000, 3_500)
+            .saturating_add(RocksDbWeight::get().reads(2))
+            .saturating_add(RocksDbWeight::get().writes(2))
+    }
+
+    fn vote_with_conviction(n: u32) -> Weight {
+        Weight::from_parts(68_000_000, 5_400)
+            .saturating_add(Weight::from_parts(9_500_000, 2_600).saturating_mul(n.into()))
+            .saturating_add(RocksDbWeight::get().reads(12))
+            .saturating_add(RocksDbWeight::get().writes(14))
+            .saturating_add(RocksDbWeight::get().reads(3).saturating_mul(n.into()))
+            .saturating_add(RocksDbWeight::get().writes(1).saturating_mul(n.into()))
+    }
+
+    fn unlock() -> Weight {
+        Weight::from_parts(32_000_000, 4_800)
+            .saturating_add(RocksDbWeight::get().reads(3))
+            .saturating_add(RocksDbWeight::get().writes(3))
+    }
+
+    fn amend_proposal(t: u32, d: u32) -> Weight {
+        Weight::from_parts(31_000_000, 4_300)
+            .saturating_add(Weight::from_parts(1_300, 1).saturating_mul(t.into()))
+            .saturating_add(Weight::from_parts(1_300, 1).saturating_mul(d.into()))
+            .saturating_add(RocksDbWeight::get().reads(2))
+            .saturating_add(RocksDbWeight::get().writes(1))
+    }
+}
@@ -0,0 +1,140 @@
+//! LRU cache of encoded `did_getDid` responses.
+//!
+//! Entries are keyed by `(block hash, SCALE-encoded account)`. State at a block hash never
+//! changes, so entries never go stale and are only evicted by capacity; a request without a
+//! block hash is keyed on the best hash it is answered at. Responses are kept SCALE-encoded
+//! and decoded on every hit, so the cache does not depend on the `DidDocument` type.
+
+use schnellru::{ByLength, LruMap};
+use serde::{Deserialize, Serialize};
+use std::{
+    hash::Hash,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+};
+
+/// Hit and miss counters of the response cache, served by `did_cacheStats`
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheStats {
+    /// Requests answered from the cache
+    pub hits: u64,
+    /// Requests answered by the runtime API
+    pub misses: u64,
+    /// Responses currently cached
+    pub entries: u32,
+    /// Maximum number of cached responses, 0 when caching is disabled
+    pub capacity: u32,
+}
+
+/// Least recently used responses, up to a fixed number
+pub struct ResponseCache<BlockHash: Hash + Eq> {
+    entries: Mutex<LruMap<(BlockHash, Vec<u8>), Vec<u8>>>,
+    capacity: u32,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl<BlockHash: Hash + Eq> ResponseCache<BlockHash> {
+    /// Create a cache holding at most `capacity` responses
+    pub fn new(capacity: u32) -> Self {
+        Self {
+            entries: Mutex::new(LruMap::new(ByLength::new(capacity))),
+            capacity,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// The cached response for `at` and `account`, or the one `fetch` produces, which is
+    /// cached. The lock is not held while fetching, so concurrent misses may fetch twice.
+    pub fn get_or_fetch<E>(
+        &self,
+        at: BlockHash,
+        account: Vec<u8>,
+        fetch: impl FnOnce() -> Result<Vec<u8>, E>,
+    ) -> Result<Vec<u8>, E> {
+        let key = (at, account);
+        if let Some(response) = self.lock().get(&key) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(response.clone());
+        }
+
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let response = fetch()?;
+        self.lock().insert(key, response.clone());
+        Ok(response)
+    }
+
+    /// Current counters
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: self.lock().len() as u32,
+            capacity: self.capacity,
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, LruMap<(BlockHash, Vec<u8>), Vec<u8>>> {
+        // A panic while holding the lock leaves the map consistent, keep serving it
+        self.entries.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fetch(response: u8) -> impl FnOnce() -> Result<Vec<u8>, ()> {
+        move || Ok(vec![response])
+    }
+
+    #[test]
+    fn repeated_requests_hit_per_block_and_account() {
+        let cache = ResponseCache::new(4);
+
+        assert_eq!(cache.get_or_fetch(1u64, vec![7], fetch(1)), Ok(vec![1]));
+        // Cached, so the fetch is not run again
+        assert_eq!(cache.get_or_fetch(1, vec![7], || Err(())), Ok(vec![1]));
+        // Another block or account is a miss
+        assert_eq!(cache.get_or_fetch(2, vec![7], fetch(2)), Ok(vec![2]));
+        assert_eq!(cache.get_or_fetch(1, vec![8], fetch(3)), Ok(vec![3]));
+
+        assert_eq!(
+            cache.stats(),
+            CacheStats {
+                hits: 1,
+                misses: 3,
+                entries: 3,
+                capacity: 4,
+            }
+        );
+    }
+
+    #[test]
+    fn failed_fetches_are_not_cached() {
+        let cache = ResponseCache::new(4);
+
+        assert_eq!(cache.get_or_fetch(1u64, vec![7], || Err(())), Err(()));
+        assert_eq!(cache.get_or_fetch(1, vec![7], fetch(1)), Ok(vec![1]));
+        assert_eq!(cache.stats().misses, 2);
+        assert_eq!(cache.stats().entries, 1);
+    }
+
+    #[test]
+    fn least_recently_used_entry_is_evicted() {
+        let cache = ResponseCache::new(2);
+        cache.get_or_fetch(1u64, vec![1], fetch(1)).unwrap();
+        cache.get_or_fetch(1, vec![2], fetch(2)).unwrap();
+        // Touch account 1, so account 2 is the least recently used
+        cache.get_or_fetch(1, vec![1], || Err(())).unwrap();
+
+        cache.get_or_fetch(1, vec![3], fetch(3)).unwrap();
+        assert_eq!(cache.stats().entries, 2);
+        assert_eq!(cache.get_or_fetch(1, vec![1], || Err(())), Ok(vec![1]));
+        assert_eq!(cache.get_or_fetch(1, vec![2], || Err(())), Err(()));
+    }
+}
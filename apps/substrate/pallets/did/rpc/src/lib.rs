@@ -1,20 +1,27 @@
 //! RPC interface for the DID pallet
 
-use codec::Codec;
+mod cache;
+
+use codec::{Codec, Decode, Encode};
 use jsonrpsee::{
     core::{async_trait, RpcResult},
     proc_macros::rpc,
-    types::error::{CallError, ErrorCode, ErrorObject},
+    types::error::{CallError, ErrorObject},
 };
 use sp_api::ProvideRuntimeApi;
 use sp_blockchain::HeaderBackend;
 use sp_runtime::traits::Block as BlockT;
 use std::sync::Arc;
+use tidygen_primitives::identity::Resolution;
 
+pub use cache::{CacheStats, ResponseCache};
 pub use pallet_did_runtime_api::DidApi as DidRuntimeApi;
 
+/// Error code for failed runtime API calls
+pub const RUNTIME_ERROR: i32 = 1;
+
 #[rpc(client, server)]
-pub trait DidApi<BlockHash, AccountId, DidDocument> {
+pub trait DidApi<BlockHash, AccountId, DidDocument, DidDiff> {
     /// Get DID document for an account
     #[method(name = "did_getDid")]
     fn get_did(
@@ -38,43 +45,94 @@ pub trait DidApi<BlockHash, AccountId, DidDocument> {
     /// Get total DID count
     #[method(name = "did_getTotalDids")]
     fn get_total_dids(&self, at: Option<BlockHash>) -> RpcResult<u64>;
+
+    /// Get the trust score of an account's DID
+    #[method(name = "did_getTrustScore")]
+    fn get_trust_score(&self, account: AccountId, at: Option<BlockHash>) -> RpcResult<u32>;
+
+    /// Resolve a DID identifier to a local account or the network it lives on
+    #[method(name = "did_resolveAny")]
+    fn resolve_any(
+        &self,
+        did_identifier: String,
+        at: Option<BlockHash>,
+    ) -> RpcResult<Resolution<AccountId>>;
+
+    /// Field-by-field difference of an account's DID document between `at_from` and `at_to`
+    /// (best block if `None`); `None` if the account has no DID at either block
+    #[method(name = "did_getDiff")]
+    fn get_diff(
+        &self,
+        account: AccountId,
+        at_from: BlockHash,
+        at_to: Option<BlockHash>,
+    ) -> RpcResult<Option<DidDiff>>;
+
+    /// Get the hit and miss counters of the `did_getDid` response cache
+    #[method(name = "did_cacheStats")]
+    fn cache_stats(&self) -> RpcResult<CacheStats>;
 }
 
 /// A struct that implements the `DidApi`.
-pub struct Did<C, Block> {
+pub struct Did<C, Block: BlockT> {
     client: Arc<C>,
+    cache: Option<ResponseCache<Block::Hash>>,
     _marker: std::marker::PhantomData<Block>,
 }
 
-impl<C, Block> Did<C, Block> {
+impl<C, Block: BlockT> Did<C, Block> {
     /// Create new `Did` instance with the given reference to the client.
     pub fn new(client: Arc<C>) -> Self {
         Self {
             client,
+            cache: None,
+            _marker: Default::default(),
+        }
+    }
+
+    /// Create new `Did` instance caching up to `capacity` `did_getDid` responses, for nodes
+    /// serving many lookups of the same DIDs.
+    pub fn new_with_cache(client: Arc<C>, capacity: u32) -> Self {
+        Self {
+            client,
+            cache: Some(ResponseCache::new(capacity)),
             _marker: Default::default(),
         }
     }
 }
 
 #[async_trait]
-impl<C, Block, AccountId, DidDocument>
-    DidApiServer<<Block as BlockT>::Hash, AccountId, DidDocument> for Did<C, Block>
+impl<C, Block, AccountId, DidDocument, DidDiff>
+    DidApiServer<<Block as BlockT>::Hash, AccountId, DidDocument, DidDiff> for Did<C, Block>
 where
     Block: BlockT,
     C: Send + Sync + 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block>,
-    C::Api: DidRuntimeApi<Block, AccountId, DidDocument>,
-    AccountId: Codec,
+    C::Api: DidRuntimeApi<Block, AccountId, DidDocument, DidDiff>,
+    AccountId: Codec + Clone,
     DidDocument: Codec,
+    DidDiff: Codec,
 {
     fn get_did(
         &self,
         account: AccountId,
         at: Option<<Block as BlockT>::Hash>,
     ) -> RpcResult<Option<DidDocument>> {
-        let api = self.client.runtime_api();
+        // Resolved before keying the cache, so no entry of an older best block is served
         let at = at.unwrap_or_else(|| self.client.info().best_hash);
-
-        api.get_did(at, account).map_err(runtime_error_into_rpc_err)
+        let Some(cache) = &self.cache else {
+            return self
+                .client
+                .runtime_api()
+                .get_did(at, account)
+                .map_err(runtime_error_into_rpc_err);
+        };
+
+        let encoded = cache
+            .get_or_fetch(at, account.encode(), || {
+                self.client.runtime_api().get_did(at, account).map(|did| did.encode())
+            })
+            .map_err(runtime_error_into_rpc_err)?;
+        Option::<DidDocument>::decode(&mut &encoded[..]).map_err(runtime_error_into_rpc_err)
     }
 
     fn get_account_from_did(
@@ -107,11 +165,61 @@ where
 
         api.get_total_dids(at).map_err(runtime_error_into_rpc_err)
     }
+
+    fn get_trust_score(
+        &self,
+        account: AccountId,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> RpcResult<u32> {
+        let api = self.client.runtime_api();
+        let at = at.unwrap_or_else(|| self.client.info().best_hash);
+
+        api.get_trust_score(at, account).map_err(runtime_error_into_rpc_err)
+    }
+
+    fn resolve_any(
+        &self,
+        did_identifier: String,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> RpcResult<Resolution<AccountId>> {
+        let api = self.client.runtime_api();
+        let at = at.unwrap_or_else(|| self.client.info().best_hash);
+
+        api.resolve_any(at, did_identifier.as_bytes().to_vec())
+            .map_err(runtime_error_into_rpc_err)
+    }
+
+    fn get_diff(
+        &self,
+        account: AccountId,
+        at_from: <Block as BlockT>::Hash,
+        at_to: Option<<Block as BlockT>::Hash>,
+    ) -> RpcResult<Option<DidDiff>> {
+        let api = self.client.runtime_api();
+        let at_to = at_to.unwrap_or_else(|| self.client.info().best_hash);
+
+        // Both reads go through the runtime API at their block, so the runtime compares
+        // the earlier document against its own state without historical access
+        let from_doc =
+            api.get_did(at_from, account.clone()).map_err(runtime_error_into_rpc_err)?;
+        let Some(from_doc) = from_doc else {
+            return Ok(None);
+        };
+        api.did_diff(at_to, account, from_doc).map_err(runtime_error_into_rpc_err)
+    }
+
+    fn cache_stats(&self) -> RpcResult<CacheStats> {
+        Ok(self.cache.as_ref().map(ResponseCache::stats).unwrap_or_default())
+    }
 }
 
 /// Converts a runtime trap into an RPC error.
-fn runtime_error_into_rpc_err(err: impl std::fmt::Debug) -> ErrorObject<'static> {
-    CallError::Custom(ErrorCode::InternalError.into())
-        .into()
+fn runtime_error_into_rpc_err(err: impl std::fmt::Debug) -> jsonrpsee::core::Error {
+    CallError::Custom(ErrorObject::owned(
+        RUNTIME_ERROR,
+        "Runtime error",
+        Some(format!("{:?}", err)),
+    ))
+    .into()
 }
 
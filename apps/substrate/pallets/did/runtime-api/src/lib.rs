@@ -4,13 +4,15 @@
 
 use codec::Codec;
 use sp_std::vec::Vec;
+use tidygen_primitives::identity::Resolution;
 
 sp_api::decl_runtime_apis! {
     /// The API to interact with DID pallet
-    pub trait DidApi<AccountId, DidDocument>
+    pub trait DidApi<AccountId, DidDocument, DidDiff>
     where
         AccountId: Codec,
         DidDocument: Codec,
+        DidDiff: Codec,
     {
         /// Get DID document for an account
         fn get_did(account: AccountId) -> Option<DidDocument>;
@@ -23,6 +25,15 @@ sp_api::decl_runtime_apis! {
 
         /// Get total number of DIDs
         fn get_total_dids() -> u64;
+
+        /// Get the trust score of an account's DID
+        fn get_trust_score(account: AccountId) -> u32;
+
+        /// Resolve a DID identifier to a local account or the network it lives on
+        fn resolve_any(did_identifier: Vec<u8>) -> Resolution<AccountId>;
+
+        /// Compare `from_block_doc`, the account's DID document as fetched at an earlier
+        /// block, with the current one field by field; `None` if the account has no DID
+        fn did_diff(account: AccountId, from_block_doc: DidDocument) -> Option<DidDiff>;
     }
 }
-
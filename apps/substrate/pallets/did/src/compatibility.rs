@@ -0,0 +1,74 @@
+//! Pinned call indices and storage prefixes of pallet-did.
+//!
+//! Downstream tooling decodes extrinsics and storage by these values. See
+//! `tidygen_primitives::compat` for how an intended change to them is made.
+
+use crate::{mock::*, Call, Pallet};
+use codec::Encode;
+use frame_support::traits::{GetCallIndex, GetCallName, StorageInfoTrait};
+use tidygen_primitives::compat::{assert_call_indices, assert_storage_prefixes};
+
+/// Version of the tables below, bumped when an existing entry moves or goes away
+const COMPAT_VERSION: u32 = 1;
+
+/// Every dispatchable with its call index. Index 3 belonged to the removed `resolve_did` and
+/// must not be reused.
+const CALL_INDICES: &[(&str, u8)] = &[
+    ("register_did", 0),
+    ("update_did", 1),
+    ("revoke_did", 2),
+    ("set_mutations_frozen", 4),
+    ("self_suspend", 5),
+    ("self_unsuspend", 6),
+    ("unsuspend_did", 7),
+    ("grant_session", 8),
+    ("revoke_session", 9),
+    ("attest", 10),
+    ("revoke_attestation", 11),
+    ("register_external_did", 12),
+];
+
+/// Every storage item with the `twox_128` of its name
+const STORAGE_PREFIXES: &[(&str, &str)] = &[
+    ("DidDocuments", "f8b664d44f26182857468bb7c328dd52"),
+    ("DidToAccount", "c3bb115ec5dbd6da1f90864ca9a537ca"),
+    ("DidCount", "d36b37304087b376ddae53533a5ab2e5"),
+    ("MutationsFrozen", "4baf6dc2b116e7b3f56701eb1033942b"),
+    ("Suspensions", "5dd5f3679e83b14ce6d7b388fae08646"),
+    ("SessionKeys", "fce0f3dfae3d9ede958bd9e558ef2b95"),
+    ("Attestations", "ae394d879ddf7f99595bc0dd36e355b5"),
+    ("AttestedSubjects", "61067d6f8f50819283838838e9b987af"),
+    ("TrustScoreCache", "dc5fbf904cb787584455d2204a11b981"),
+    ("ExternalDids", "97a379c0323a659815970bc6375bcfce"),
+];
+
+#[test]
+fn call_indices_are_stable() {
+    assert_call_indices(
+        "pallet-did",
+        COMPAT_VERSION,
+        CALL_INDICES,
+        Call::<Test>::get_call_names(),
+        Call::<Test>::get_call_indices(),
+    );
+}
+
+#[test]
+fn encoded_calls_lead_with_the_pinned_index() {
+    assert_eq!(Call::<Test>::set_mutations_frozen { frozen: true }.encode(), vec![4, 1]);
+    assert_eq!(Call::<Test>::self_suspend {}.encode(), vec![5]);
+
+    // In a runtime call the pallet index comes first, Did is the second pallet of the mock
+    let call = RuntimeCall::Did(Call::revoke_did { account_id: 7 });
+    assert_eq!(call.encode(), [&[1u8, 2][..], &7u64.encode()].concat());
+}
+
+#[test]
+fn storage_prefixes_are_stable() {
+    let actual: Vec<_> = Pallet::<Test>::storage_info()
+        .into_iter()
+        .map(|info| (info.storage_name, info.prefix))
+        .collect();
+
+    assert_storage_prefixes("pallet-did", COMPAT_VERSION, STORAGE_PREFIXES, &actual);
+}
@@ -28,11 +28,94 @@
 //! * `register_did` - Register a new DID for an account
 //! * `update_did` - Update an existing DID document
 //! * `revoke_did` - Revoke a DID
-//! * `resolve_did` - Resolve a DID document (emits event)
+//! * `set_mutations_frozen` - Freeze or unfreeze all DID mutations (`FreezeOrigin` only)
+//! * `self_suspend` - Suspend the caller's own DID, even if another account controls it
+//! * `self_unsuspend` - Lift a self-suspension once `SelfSuspendCooldown` has passed
+//! * `unsuspend_did` - Lift a suspension immediately (controller only)
+//! * `grant_session` - Let a session key update the DID until a given block (controller only)
+//! * `revoke_session` - Withdraw a session grant (controller only)
+//! * `attest` - Vouch for another account's DID (accounts with an active DID)
+//! * `revoke_attestation` - Withdraw an attestation (attester only)
+//! * `register_external_did` - Record a DID registered on another network (`RegistrarOrigin`)
+//!
+//! ### Session Keys
+//!
+//! A controller can grant short-lived session (device) keys the right to call `update_did`
+//! without exposing the controller key. Each grant in `SessionKeys` has a scope:
+//! `MetadataOnly` keys may only replace the metadata, while `KeysAndMetadata` keys may also
+//! rotate the public key. A grant is valid up to, but excluding, its `expires_at` block.
+//! Expired grants are removed lazily when their key next calls `update_did`; that call then
+//! emits `SessionExpired` and leaves the document unchanged. Only the controller can grant
+//! or revoke sessions, so a session key cannot extend its own rights.
+//!
+//! ### Self-Suspension
+//!
+//! The subject of a DID can lock it on its own, e.g. when its device may be compromised,
+//! without waiting for the controller. The suspension records its initiator in
+//! `Suspensions`. The subject may only lift a suspension it initiated, and only after
+//! `SelfSuspendCooldown` blocks, so a stolen subject key cannot toggle the DID back and
+//! forth. The controller can lift any suspension at once. Revoking a suspended DID clears
+//! the suspension record.
+//!
+//! ### Trust Scores
+//!
+//! `get_trust_score` turns the attestations a DID received into a number partners can
+//! threshold on. An active DID starts at `BaseTrustScore`. Every attestation adds the
+//! attester's own score, capped at `MaxTrustScore`, times `AttestationWeight`. Contributions
+//! fade linearly with age: an attestation counts half at `AttestationHalfLife` blocks old
+//! and nothing from twice that age on. Revoked or suspended DIDs score zero and pass
+//! nothing on.
+//!
+//! Attester scores are evaluated at most `MAX_TRUST_DEPTH` levels deep; below that every
+//! active DID counts as `BaseTrustScore`, so attestation cycles cannot recurse forever.
+//! `attest` and `revoke_attestation` store the subject's new score in `TrustScoreCache`,
+//! where `get_trust_score` serves it for `TrustScoreCacheTtl` blocks; the query itself is
+//! read-only, as runtime API calls discard their writes, and computes anything not cached.
+//! Adding or revoking an attestation, and revoking, suspending or reactivating a DID, drops
+//! the cached score of that DID and of every DID it attests to, the only scores that change
+//! with it.
+//!
+//! ### External DIDs
+//!
+//! The ops, finance and archive chains of a group reference each other's DIDs. A registrar
+//! records an identifier issued by another network in `ExternalDids`, with the network ID
+//! and a block of that network to start the lookup from. `resolve_any` resolves an
+//! identifier to `Resolution::Local` with the account registered on this chain,
+//! `Resolution::External` with the network it lives on, or `Resolution::Unknown`.
+//! `get_account_from_did` keeps answering for local DIDs only.
+//!
+//! ### Maintenance Mode
+//!
+//! While `MutationsFrozen` is set, every state-changing call except `set_mutations_frozen`
+//! fails with `MutationsFrozen`. Reads (`get_did`, `get_account_from_did`, `is_did_active` and the
+//! RPCs built on them) keep working, so identities can still be resolved during incident
+//! response without a runtime-wide call filter.
+//!
+//! ### Status Transitions
+//!
+//! Every `DidStatusChanged` is also reported to `Config::OnTransition`, e.g. a compliance
+//! log archiving them.
 //!
 //! ### RPC Methods
 //!
 //! * `get_did` - Query DID document for an account
+//! * `get_trust_score` - Trust score of an account's DID
+//! * `resolve_any` - Resolve a DID identifier locally or to the network it lives on
+//! * `did_diff` - Field-by-field difference of a DID document against the current one
+//!
+//! ### Auditing Changes
+//!
+//! Auditors ask what changed on a DID between two blocks. `did_diff` takes the document as
+//! fetched at the earlier block and compares it against the current state, returning a
+//! `DidDiff`: a bitmask of the changed `DidField`s and the Blake2-256 hashes of each changed
+//! field before and after. The runtime never reads historical state. The RPC
+//! `did_getDiff(account, at_from, at_to)` fetches the document at `at_from` and calls
+//! `did_diff` at `at_to`.
+//!
+//! Audited reads of DID documents are recorded through `pallet-access-log`.
+//!
+//! The pallet implements `tidygen_primitives::identity::DidProvider`, so other pallets (e.g.
+//! `pallet-ledger` for invoice clients) can require an active DID without depending on it.
 
 pub use pallet::*;
 
@@ -42,22 +125,44 @@ mod mock;
 #[cfg(test)]
 mod tests;
 
+#[cfg(test)]
+mod compatibility;
+
 #[frame_support::pallet]
 pub mod pallet {
     use frame_support::{
         pallet_prelude::*,
-        traits::Get,
+        traits::{Get, PalletInfoAccess},
     };
     use frame_system::pallet_prelude::*;
+    #[cfg(feature = "serde")]
+    use serde::{Deserialize, Serialize};
     use sp_core::H256;
     use sp_io::hashing::blake2_256;
+    use sp_runtime::{
+        traits::{SaturatedConversion, Saturating},
+        ModuleError, Perbill,
+    };
     use sp_std::vec::Vec;
+    use tidygen_primitives::{
+        batch::SkipReason,
+        identity::{DidProvider, NetworkId, Resolution},
+        transitions::{status_index, OnStatusTransition, TransitionSubject},
+        writer::{ByteWriter, Overflow},
+    };
 
     #[pallet::pallet]
     pub struct Pallet<T>(_);
 
+    /// Prefix of every DID identifier issued by this pallet
+    pub const DID_PREFIX: &str = "did:substrate:tidygen:";
+
+    /// Levels of attesters whose own attestations count towards a trust score
+    pub const MAX_TRUST_DEPTH: u32 = 2;
+
     /// DID Document status
     #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
     pub enum DidStatus {
         /// DID is active and valid
         Active,
@@ -77,13 +182,27 @@ pub mod pallet {
     /// Follows W3C DID Core specification principles
     #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
     #[scale_info(skip_type_params(T))]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            rename_all = "camelCase",
+            bound(
+                serialize = "T::AccountId: Serialize, BlockNumberFor<T>: Serialize",
+                deserialize = "T::AccountId: Deserialize<'de>, \
+                               BlockNumberFor<T>: Deserialize<'de>"
+            )
+        )
+    )]
     pub struct DidDocument<T: Config> {
         /// Controller of this DID (typically the account owner)
         pub controller: T::AccountId,
         /// Public key for verification (can be used for authentication)
+        #[cfg_attr(feature = "serde", serde(with = "tidygen_primitives::hex"))]
         pub public_key: BoundedVec<u8, T::MaxPublicKeyLength>,
         /// Metadata (JSON string or additional properties)
         /// Can include: service endpoints, authentication methods, etc.
+        #[cfg_attr(feature = "serde", serde(with = "tidygen_primitives::hex"))]
         pub metadata: BoundedVec<u8, T::MaxMetadataLength>,
         /// Block number when DID was created
         pub created_at: BlockNumberFor<T>,
@@ -92,6 +211,7 @@ pub mod pallet {
         /// Status of the DID
         pub status: DidStatus,
         /// DID identifier (derived from account)
+        #[cfg_attr(feature = "serde", serde(with = "tidygen_primitives::hex"))]
         pub did_identifier: BoundedVec<u8, T::MaxDidLength>,
         /// Nonce for updates (prevents replay attacks)
         pub nonce: u64,
@@ -100,32 +220,188 @@ pub mod pallet {
     impl<T: Config> DidDocument<T> {
         /// Generate DID identifier from account
         /// Format: did:substrate:{network}:{account_hash}
+        ///
+        /// The account hash is the first 8 bytes of the Blake2-256 of the encoded account in
+        /// lowercase hex, so identifiers are always 38 bytes. Returns an empty identifier if
+        /// `MaxDidLength` is configured below that.
         pub fn generate_did_identifier(account: &T::AccountId) -> BoundedVec<u8, T::MaxDidLength> {
-            let account_bytes = account.encode();
-            let hash = blake2_256(&account_bytes);
-            let hex_hash = Self::to_hex(&hash[..8]); // Use first 8 bytes
-            
-            let did_str = format!("did:substrate:tidygen:{}", hex_hash);
-            did_str.as_bytes().to_vec()
-                .try_into()
-                .unwrap_or_default()
-        }
+            let hash = blake2_256(&account.encode());
 
-        /// Convert bytes to hex string
-        fn to_hex(bytes: &[u8]) -> String {
-            const HEX_CHARS: &[u8] = b"0123456789abcdef";
-            let mut hex = String::with_capacity(bytes.len() * 2);
-            for &byte in bytes {
-                hex.push(HEX_CHARS[(byte >> 4) as usize] as char);
-                hex.push(HEX_CHARS[(byte & 0xf) as usize] as char);
+            let mut did = ByteWriter::<T::MaxDidLength>::new();
+            match did.push_str(DID_PREFIX).and_then(|()| did.push_hex(&hash[..8])) {
+                Ok(()) => did.into_inner(),
+                Err(Overflow) => Default::default(),
             }
-            hex
         }
 
         /// Verify if DID is active
         pub fn is_active(&self) -> bool {
             self.status == DidStatus::Active
         }
+
+        /// Blake2-256 of the encoded `field`
+        pub fn field_hash(&self, field: DidField) -> [u8; 32] {
+            match field {
+                DidField::Controller => self.controller.using_encoded(blake2_256),
+                DidField::PublicKey => self.public_key.using_encoded(blake2_256),
+                DidField::Metadata => self.metadata.using_encoded(blake2_256),
+                DidField::CreatedAt => self.created_at.using_encoded(blake2_256),
+                DidField::UpdatedAt => self.updated_at.using_encoded(blake2_256),
+                DidField::Status => self.status.using_encoded(blake2_256),
+                DidField::DidIdentifier => self.did_identifier.using_encoded(blake2_256),
+                DidField::Nonce => self.nonce.using_encoded(blake2_256),
+            }
+        }
+
+        /// Compare with a later version of the document field by field
+        pub fn diff(&self, later: &Self) -> DidDiff {
+            let mut diff = DidDiff { changed: 0, changes: Vec::new() };
+            for field in DidField::ALL {
+                let (old_hash, new_hash) = (self.field_hash(field), later.field_hash(field));
+                if old_hash != new_hash {
+                    diff.changed |= field.bit();
+                    diff.changes.push(FieldChange { field, old_hash, new_hash });
+                }
+            }
+            diff
+        }
+    }
+
+    /// Field of a DID document, as compared by `did_diff`
+    #[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    #[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+    pub enum DidField {
+        /// `controller`
+        Controller,
+        /// `public_key`
+        PublicKey,
+        /// `metadata`
+        Metadata,
+        /// `created_at`
+        CreatedAt,
+        /// `updated_at`
+        UpdatedAt,
+        /// `status`
+        Status,
+        /// `did_identifier`
+        DidIdentifier,
+        /// `nonce`
+        Nonce,
+    }
+
+    impl DidField {
+        /// Every field, in document order
+        pub const ALL: [DidField; 8] = [
+            Self::Controller,
+            Self::PublicKey,
+            Self::Metadata,
+            Self::CreatedAt,
+            Self::UpdatedAt,
+            Self::Status,
+            Self::DidIdentifier,
+            Self::Nonce,
+        ];
+
+        /// Bit of the field in `DidDiff::changed`: 1 for `Controller`, 2 for `PublicKey`, ...
+        pub fn bit(self) -> u32 {
+            1 << self as u32
+        }
+    }
+
+    /// A changed field of a DID document with its hashes before and after
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    #[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+    pub struct FieldChange {
+        /// The changed field
+        pub field: DidField,
+        /// Blake2-256 of the encoded field in the earlier document
+        #[cfg_attr(feature = "serde", serde(with = "tidygen_primitives::hex"))]
+        pub old_hash: [u8; 32],
+        /// Blake2-256 of the encoded field in the later document
+        #[cfg_attr(feature = "serde", serde(with = "tidygen_primitives::hex"))]
+        pub new_hash: [u8; 32],
+    }
+
+    /// Field-by-field difference between two versions of a DID document
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    #[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+    pub struct DidDiff {
+        /// Bitmask of the changed fields, see `DidField::bit`
+        pub changed: u32,
+        /// The changed fields in document order
+        pub changes: Vec<FieldChange>,
+    }
+
+    impl DidDiff {
+        /// Whether no field changed
+        pub fn is_empty(&self) -> bool {
+            self.changed == 0
+        }
+
+        /// Whether `field` changed
+        pub fn contains(&self, field: DidField) -> bool {
+            self.changed & field.bit() != 0
+        }
+    }
+
+    /// Record of an active suspension
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    #[scale_info(skip_type_params(T))]
+    pub struct Suspension<T: Config> {
+        /// Account that suspended the DID
+        pub initiator: T::AccountId,
+        /// Block number when the DID was suspended
+        pub suspended_at: BlockNumberFor<T>,
+    }
+
+    /// Changes a session key may make through `update_did`
+    #[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub enum UpdateScope {
+        /// Replace the metadata only
+        MetadataOnly,
+        /// Rotate the public key and replace the metadata
+        KeysAndMetadata,
+    }
+
+    impl UpdateScope {
+        /// Whether this scope allows every change `required` allows
+        pub fn covers(&self, required: &UpdateScope) -> bool {
+            matches!((self, required), (Self::KeysAndMetadata, _) | (_, Self::MetadataOnly))
+        }
+    }
+
+    /// Right of a session key to update a DID
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    #[scale_info(skip_type_params(T))]
+    pub struct SessionGrant<T: Config> {
+        /// First block at which the grant is no longer valid
+        pub expires_at: BlockNumberFor<T>,
+        /// Changes the session key may make
+        pub scope: UpdateScope,
+    }
+
+    /// Location of a DID registered on another network of the chain group
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    #[scale_info(skip_type_params(T))]
+    pub struct ExternalRef<T: Config> {
+        /// Network the DID is registered on
+        pub network_id: NetworkId,
+        /// Block of that network around which the DID was registered, to start a lookup from
+        pub registered_hint_block: BlockNumberFor<T>,
+    }
+
+    /// Memoized trust score of a DID
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    #[scale_info(skip_type_params(T))]
+    pub struct ScoreCache<T: Config> {
+        /// Score as computed at `computed_at`
+        pub score: u32,
+        /// Block number the score was computed at
+        pub computed_at: BlockNumberFor<T>,
     }
 
     #[pallet::config]
@@ -144,6 +420,44 @@ pub mod pallet {
         /// Maximum length of DID identifier
         #[pallet::constant]
         type MaxDidLength: Get<u32>;
+
+        /// Origin allowed to freeze and unfreeze DID mutations
+        type FreezeOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+        /// Blocks a subject must wait before lifting its own suspension
+        #[pallet::constant]
+        type SelfSuspendCooldown: Get<BlockNumberFor<Self>>;
+
+        /// Maximum number of attestations a DID can receive, and an account can give
+        #[pallet::constant]
+        type MaxAttestations: Get<u32>;
+
+        /// Trust score of an active DID before attestations are counted
+        #[pallet::constant]
+        type BaseTrustScore: Get<u32>;
+
+        /// Cap on the attester score an attestation passes on
+        #[pallet::constant]
+        type MaxTrustScore: Get<u32>;
+
+        /// Share of the capped attester score an attestation contributes
+        #[pallet::constant]
+        type AttestationWeight: Get<Perbill>;
+
+        /// Age in blocks at which an attestation contributes half; it stops counting at twice
+        /// this age. Zero disables the decay.
+        #[pallet::constant]
+        type AttestationHalfLife: Get<BlockNumberFor<Self>>;
+
+        /// Blocks a trust score stored by an attestation change is served from `TrustScoreCache`
+        #[pallet::constant]
+        type TrustScoreCacheTtl: Get<BlockNumberFor<Self>>;
+
+        /// Origin allowed to record DIDs registered on other networks
+        type RegistrarOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+        /// Notified of every DID status transition
+        type OnTransition: OnStatusTransition<Self::AccountId>;
     }
 
     /// Storage for DID documents mapped by AccountId
@@ -175,6 +489,69 @@ pub mod pallet {
     #[pallet::getter(fn did_count)]
     pub type DidCount<T> = StorageValue<_, u64, ValueQuery>;
 
+    /// Whether DID mutations are frozen (maintenance mode)
+    #[pallet::storage]
+    #[pallet::getter(fn mutations_frozen)]
+    pub type MutationsFrozen<T> = StorageValue<_, bool, ValueQuery>;
+
+    /// Active suspensions by DID subject account
+    #[pallet::storage]
+    #[pallet::getter(fn suspensions)]
+    pub type Suspensions<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, Suspension<T>, OptionQuery>;
+
+    /// Session grants: (DID subject account, session key) => grant
+    #[pallet::storage]
+    #[pallet::getter(fn session_keys)]
+    pub type SessionKeys<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        T::AccountId,
+        Blake2_128Concat,
+        T::AccountId,
+        SessionGrant<T>,
+        OptionQuery,
+    >;
+
+    /// Attestations received: subject => [(attester, block number attested at)]
+    #[pallet::storage]
+    #[pallet::getter(fn attestations)]
+    pub type Attestations<T: Config> = StorageMap<
+        _,
+        Blake2_128Concat,
+        T::AccountId,
+        BoundedVec<(T::AccountId, BlockNumberFor<T>), T::MaxAttestations>,
+        ValueQuery,
+    >;
+
+    /// Attestations given: attester => [subject]
+    #[pallet::storage]
+    #[pallet::getter(fn attested_subjects)]
+    pub type AttestedSubjects<T: Config> = StorageMap<
+        _,
+        Blake2_128Concat,
+        T::AccountId,
+        BoundedVec<T::AccountId, T::MaxAttestations>,
+        ValueQuery,
+    >;
+
+    /// Memoized trust scores by DID subject account
+    #[pallet::storage]
+    #[pallet::getter(fn trust_score_cache)]
+    pub type TrustScoreCache<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, ScoreCache<T>, OptionQuery>;
+
+    /// DIDs registered on other networks: DID identifier => where it lives
+    #[pallet::storage]
+    #[pallet::getter(fn external_dids)]
+    pub type ExternalDids<T: Config> = StorageMap<
+        _,
+        Blake2_128Concat,
+        BoundedVec<u8, T::MaxDidLength>,
+        ExternalRef<T>,
+        OptionQuery,
+    >;
+
     #[pallet::event]
     #[pallet::generate_deposit(pub(super) fn deposit_event)]
     pub enum Event<T: Config> {
@@ -192,17 +569,60 @@ pub mod pallet {
         DidRevoked {
             account: T::AccountId,
         },
-        /// DID resolved [account_id, status]
-        DidResolved {
-            account: T::AccountId,
-            status: DidStatus,
-        },
         /// DID status changed [account_id, old_status, new_status]
         DidStatusChanged {
             account: T::AccountId,
             old_status: DidStatus,
             new_status: DidStatus,
         },
+        /// DID mutations frozen
+        DidMutationsFrozen,
+        /// DID mutations unfrozen
+        DidMutationsUnfrozen,
+        /// DID suspended [account_id, initiator]
+        DidSuspended {
+            account: T::AccountId,
+            initiator: T::AccountId,
+        },
+        /// DID suspension lifted [account_id, lifted_by]
+        DidUnsuspended {
+            account: T::AccountId,
+            lifted_by: T::AccountId,
+        },
+        /// Session key granted [account_id, session_key, scope, expires_at]
+        SessionGranted {
+            account: T::AccountId,
+            session_key: T::AccountId,
+            scope: UpdateScope,
+            expires_at: BlockNumberFor<T>,
+        },
+        /// Session key revoked by the controller [account_id, session_key]
+        SessionRevoked {
+            account: T::AccountId,
+            session_key: T::AccountId,
+        },
+        /// Expired session grant removed on use [account_id, session_key]
+        SessionExpired {
+            account: T::AccountId,
+            session_key: T::AccountId,
+        },
+        /// DID attested [subject, attester]
+        Attested {
+            subject: T::AccountId,
+            attester: T::AccountId,
+        },
+        /// Attestation withdrawn [subject, attester]
+        AttestationRevoked {
+            subject: T::AccountId,
+            attester: T::AccountId,
+        },
+        /// DID recorded as living on another network [did_identifier, network_id,
+        /// registered_hint_block]
+        ExternalDidRegistered {
+            did_identifier: Vec<u8>,
+            network_id: NetworkId,
+            registered_hint_block: BlockNumberFor<T>,
+        },
     }
 
     #[pallet::error]
@@ -225,6 +645,32 @@ pub mod pallet {
         InvalidDidIdentifier,
         /// DID identifier too long
         DidIdentifierTooLong,
+        /// DID mutations are frozen for maintenance
+        MutationsFrozen,
+        /// DID is not suspended
+        DidNotSuspended,
+        /// The suspension was not initiated by the caller
+        NotSuspensionInitiator,
+        /// `SelfSuspendCooldown` has not passed since the self-suspension
+        SelfSuspendCooldownActive,
+        /// A session grant must expire after the current block
+        InvalidSessionExpiry,
+        /// No session grant for this key
+        SessionNotFound,
+        /// The session grant does not cover the requested change
+        SessionScopeExceeded,
+        /// An account cannot attest its own DID
+        CannotAttestSelf,
+        /// Only accounts with an active DID can attest
+        AttesterDidNotActive,
+        /// The caller already attests this DID
+        AlreadyAttested,
+        /// The caller does not attest this DID
+        AttestationNotFound,
+        /// The DID already has, or the caller already gave, `MaxAttestations` attestations
+        TooManyAttestations,
+        /// The DID identifier is registered on this chain
+        DidIsLocal,
     }
 
     #[pallet::call]
@@ -247,6 +693,7 @@ pub mod pallet {
         /// * `DidAlreadyExists` - Account already has a DID
         /// * `PublicKeyTooLong` - Public key exceeds maximum length
         /// * `MetadataTooLong` - Metadata exceeds maximum length
+        /// * `MutationsFrozen` - DID mutations are frozen
         #[pallet::call_index(0)]
         #[pallet::weight(10_000)]
         pub fn register_did(
@@ -256,6 +703,7 @@ pub mod pallet {
             metadata: Vec<u8>,
         ) -> DispatchResult {
             let who = ensure_signed(origin)?;
+            Self::ensure_mutable()?;
 
             // Ensure DID doesn't already exist
             ensure!(
@@ -310,8 +758,11 @@ pub mod pallet {
 
         /// Update an existing DID document
         ///
+        /// Session keys granted with `grant_session` may call this within their scope. When
+        /// the caller's grant has expired, it is removed and nothing else changes.
+        ///
         /// # Arguments
-        /// * `origin` - Transaction origin (must be the controller)
+        /// * `origin` - Transaction origin (the controller or a session key)
         /// * `account_id` - Account whose DID to update
         /// * `public_key` - New public key (optional, pass None to keep existing)
         /// * `metadata` - New metadata (optional, pass None to keep existing)
@@ -321,11 +772,14 @@ pub mod pallet {
         ///
         /// # Events
         /// * `DidUpdated` - Emitted when DID is successfully updated
+        /// * `SessionExpired` - Emitted instead when the caller's session grant has expired
         ///
         /// # Errors
         /// * `DidNotFound` - DID does not exist
-        /// * `NotController` - Origin is not the DID controller
+        /// * `NotController` - Origin is neither the DID controller nor a session key
+        /// * `SessionScopeExceeded` - The session grant does not allow a key rotation
         /// * `DidRevoked` - DID is revoked and cannot be updated
+        /// * `MutationsFrozen` - DID mutations are frozen
         #[pallet::call_index(1)]
         #[pallet::weight(10_000)]
         pub fn update_did(
@@ -335,14 +789,27 @@ pub mod pallet {
             metadata: Option<Vec<u8>>,
         ) -> DispatchResult {
             let who = ensure_signed(origin)?;
+            Self::ensure_mutable()?;
+
+            let controller = DidDocuments::<T>::get(&account_id)
+                .map(|did| did.controller)
+                .ok_or(Error::<T>::DidNotFound)?;
+            if controller != who {
+                let required = if public_key.is_some() {
+                    UpdateScope::KeysAndMetadata
+                } else {
+                    UpdateScope::MetadataOnly
+                };
+                if !Self::authorize_session(&account_id, &who, &required)? {
+                    // The expired grant is gone; failing would revert its removal
+                    return Ok(());
+                }
+            }
 
             // Get existing DID document
             DidDocuments::<T>::try_mutate(&account_id, |did_opt| {
                 let did = did_opt.as_mut().ok_or(Error::<T>::DidNotFound)?;
 
-                // Verify controller
-                ensure!(did.controller == who, Error::<T>::NotController);
-
                 // Verify not revoked
                 ensure!(did.status != DidStatus::Revoked, Error::<T>::DidRevoked);
 
@@ -392,10 +859,12 @@ pub mod pallet {
         /// # Errors
         /// * `DidNotFound` - DID does not exist
         /// * `NotController` - Origin is not the DID controller
+        /// * `MutationsFrozen` - DID mutations are frozen
         #[pallet::call_index(2)]
         #[pallet::weight(5_000)]
         pub fn revoke_did(origin: OriginFor<T>, account_id: T::AccountId) -> DispatchResult {
             let who = ensure_signed(origin)?;
+            Self::ensure_mutable()?;
 
             // Get and update DID document
             DidDocuments::<T>::try_mutate(&account_id, |did_opt| {
@@ -407,50 +876,428 @@ pub mod pallet {
                 let old_status = did.status.clone();
                 did.status = DidStatus::Revoked;
                 did.updated_at = frame_system::Pallet::<T>::block_number();
+                Suspensions::<T>::remove(&account_id);
 
                 // Emit events
                 Self::deposit_event(Event::DidRevoked {
                     account: account_id.clone(),
                 });
 
-                Self::deposit_event(Event::DidStatusChanged {
-                    account: account_id.clone(),
-                    old_status,
-                    new_status: DidStatus::Revoked,
-                });
+                Self::note_status_change(account_id.clone(), old_status, DidStatus::Revoked);
 
                 Ok(())
             })
         }
 
-        /// Resolve a DID document (query operation that emits event)
+        // Call index 3 was `resolve_did`, superseded by `pallet-access-log`. Keep it reserved.
+
+        /// Freeze or unfreeze all DID mutations
         ///
-        /// In production, use RPC endpoint instead of this extrinsic
+        /// # Arguments
+        /// * `origin` - Must satisfy `FreezeOrigin`
+        /// * `frozen` - Whether mutations are frozen
+        ///
+        /// # Events
+        /// * `DidMutationsFrozen` / `DidMutationsUnfrozen` - Emitted when the flag changes
+        #[pallet::call_index(4)]
+        #[pallet::weight(5_000)]
+        pub fn set_mutations_frozen(origin: OriginFor<T>, frozen: bool) -> DispatchResult {
+            T::FreezeOrigin::ensure_origin(origin)?;
+
+            if MutationsFrozen::<T>::get() != frozen {
+                MutationsFrozen::<T>::put(frozen);
+
+                if frozen {
+                    Self::deposit_event(Event::DidMutationsFrozen);
+                } else {
+                    Self::deposit_event(Event::DidMutationsUnfrozen);
+                }
+            }
+
+            Ok(())
+        }
+
+        /// Suspend the caller's own DID
+        ///
+        /// Callable by the DID subject regardless of who controls the DID.
+        ///
+        /// # Arguments
+        /// * `origin` - Transaction origin (the DID subject)
+        ///
+        /// # Returns
+        /// * `DispatchResult` - Success or error
+        ///
+        /// # Events
+        /// * `DidSuspended` - Emitted with the subject as initiator
+        /// * `DidStatusChanged` - Emitted with status change details
+        ///
+        /// # Errors
+        /// * `DidNotFound` - The caller has no DID
+        /// * `DidRevoked` - DID is revoked
+        /// * `DidSuspended` - DID is already suspended
+        /// * `MutationsFrozen` - DID mutations are frozen
+        #[pallet::call_index(5)]
+        #[pallet::weight(5_000)]
+        pub fn self_suspend(origin: OriginFor<T>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            Self::ensure_mutable()?;
+
+            let current_block = frame_system::Pallet::<T>::block_number();
+
+            DidDocuments::<T>::try_mutate(&who, |did_opt| -> DispatchResult {
+                let did = did_opt.as_mut().ok_or(Error::<T>::DidNotFound)?;
+
+                ensure!(did.status != DidStatus::Revoked, Error::<T>::DidRevoked);
+                ensure!(did.status != DidStatus::Suspended, Error::<T>::DidSuspended);
+
+                did.status = DidStatus::Suspended;
+                did.updated_at = current_block;
+
+                Ok(())
+            })?;
+
+            Suspensions::<T>::insert(
+                &who,
+                Suspension {
+                    initiator: who.clone(),
+                    suspended_at: current_block,
+                },
+            );
+
+            Self::deposit_event(Event::DidSuspended {
+                account: who.clone(),
+                initiator: who.clone(),
+            });
+            Self::note_status_change(who, DidStatus::Active, DidStatus::Suspended);
+
+            Ok(())
+        }
+
+        /// Lift a suspension of the caller's own DID
+        ///
+        /// Only suspensions initiated by the caller can be lifted, and only once
+        /// `SelfSuspendCooldown` blocks have passed since the suspension.
         ///
         /// # Arguments
-        /// * `origin` - Transaction origin
-        /// * `account_id` - Account whose DID to resolve
+        /// * `origin` - Transaction origin (the DID subject)
         ///
         /// # Returns
         /// * `DispatchResult` - Success or error
         ///
         /// # Events
-        /// * `DidResolved` - Emitted with DID status
+        /// * `DidUnsuspended` - Emitted with the subject as the lifting account
+        /// * `DidStatusChanged` - Emitted with status change details
+        ///
+        /// # Errors
+        /// * `DidNotFound` - The caller has no DID
+        /// * `DidRevoked` - DID is revoked
+        /// * `DidNotSuspended` - DID is not suspended
+        /// * `NotSuspensionInitiator` - The suspension was not initiated by the caller
+        /// * `SelfSuspendCooldownActive` - The cooldown has not passed yet
+        /// * `MutationsFrozen` - DID mutations are frozen
+        #[pallet::call_index(6)]
+        #[pallet::weight(5_000)]
+        pub fn self_unsuspend(origin: OriginFor<T>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            Self::ensure_mutable()?;
+
+            let did = DidDocuments::<T>::get(&who).ok_or(Error::<T>::DidNotFound)?;
+            Self::ensure_suspended(&did.status)?;
+
+            let suspension = Suspensions::<T>::get(&who)
+                .filter(|suspension| suspension.initiator == who)
+                .ok_or(Error::<T>::NotSuspensionInitiator)?;
+            let unlocked_at = suspension
+                .suspended_at
+                .saturating_add(T::SelfSuspendCooldown::get());
+            ensure!(
+                frame_system::Pallet::<T>::block_number() >= unlocked_at,
+                Error::<T>::SelfSuspendCooldownActive
+            );
+
+            Self::do_unsuspend(who.clone(), who)
+        }
+
+        /// Lift the suspension of a DID immediately
+        ///
+        /// # Arguments
+        /// * `origin` - Transaction origin (must be the controller)
+        /// * `account_id` - Account whose DID to unsuspend
+        ///
+        /// # Returns
+        /// * `DispatchResult` - Success or error
+        ///
+        /// # Events
+        /// * `DidUnsuspended` - Emitted with the controller as the lifting account
+        /// * `DidStatusChanged` - Emitted with status change details
         ///
         /// # Errors
         /// * `DidNotFound` - DID does not exist
-        #[pallet::call_index(3)]
-        #[pallet::weight(3_000)]
-        pub fn resolve_did(origin: OriginFor<T>, account_id: T::AccountId) -> DispatchResult {
-            let _who = ensure_signed(origin)?;
+        /// * `NotController` - Origin is not the DID controller
+        /// * `DidRevoked` - DID is revoked
+        /// * `DidNotSuspended` - DID is not suspended
+        /// * `MutationsFrozen` - DID mutations are frozen
+        #[pallet::call_index(7)]
+        #[pallet::weight(5_000)]
+        pub fn unsuspend_did(origin: OriginFor<T>, account_id: T::AccountId) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            Self::ensure_mutable()?;
 
-            // Get DID document
             let did = DidDocuments::<T>::get(&account_id).ok_or(Error::<T>::DidNotFound)?;
+            ensure!(did.controller == who, Error::<T>::NotController);
+            Self::ensure_suspended(&did.status)?;
 
-            // Emit event
-            Self::deposit_event(Event::DidResolved {
+            Self::do_unsuspend(account_id, who)
+        }
+
+        /// Let a session key update a DID until `expires_at`
+        ///
+        /// Replaces any earlier grant of the same key.
+        ///
+        /// # Arguments
+        /// * `origin` - Transaction origin (must be the controller)
+        /// * `account_id` - Account whose DID the session key may update
+        /// * `session_key` - Account of the session key
+        /// * `scope` - Changes the session key may make
+        /// * `expires_at` - First block at which the grant is no longer valid
+        ///
+        /// # Returns
+        /// * `DispatchResult` - Success or error
+        ///
+        /// # Events
+        /// * `SessionGranted` - Emitted when the grant is stored
+        ///
+        /// # Errors
+        /// * `DidNotFound` - DID does not exist
+        /// * `NotController` - Origin is not the DID controller
+        /// * `DidRevoked` - DID is revoked
+        /// * `InvalidSessionExpiry` - `expires_at` is not after the current block
+        /// * `MutationsFrozen` - DID mutations are frozen
+        #[pallet::call_index(8)]
+        #[pallet::weight(5_000)]
+        pub fn grant_session(
+            origin: OriginFor<T>,
+            account_id: T::AccountId,
+            session_key: T::AccountId,
+            scope: UpdateScope,
+            expires_at: BlockNumberFor<T>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            Self::ensure_mutable()?;
+
+            let did = DidDocuments::<T>::get(&account_id).ok_or(Error::<T>::DidNotFound)?;
+            ensure!(did.controller == who, Error::<T>::NotController);
+            ensure!(did.status != DidStatus::Revoked, Error::<T>::DidRevoked);
+            ensure!(
+                expires_at > frame_system::Pallet::<T>::block_number(),
+                Error::<T>::InvalidSessionExpiry
+            );
+
+            SessionKeys::<T>::insert(&account_id, &session_key, SessionGrant { expires_at, scope });
+
+            Self::deposit_event(Event::SessionGranted {
                 account: account_id,
-                status: did.status,
+                session_key,
+                scope,
+                expires_at,
+            });
+
+            Ok(())
+        }
+
+        /// Withdraw the grant of a session key
+        ///
+        /// # Arguments
+        /// * `origin` - Transaction origin (must be the controller)
+        /// * `account_id` - Account whose DID the session key could update
+        /// * `session_key` - Account of the session key
+        ///
+        /// # Returns
+        /// * `DispatchResult` - Success or error
+        ///
+        /// # Events
+        /// * `SessionRevoked` - Emitted when the grant is removed
+        ///
+        /// # Errors
+        /// * `DidNotFound` - DID does not exist
+        /// * `NotController` - Origin is not the DID controller
+        /// * `SessionNotFound` - The key has no grant for this DID
+        /// * `MutationsFrozen` - DID mutations are frozen
+        #[pallet::call_index(9)]
+        #[pallet::weight(5_000)]
+        pub fn revoke_session(
+            origin: OriginFor<T>,
+            account_id: T::AccountId,
+            session_key: T::AccountId,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            Self::ensure_mutable()?;
+
+            let did = DidDocuments::<T>::get(&account_id).ok_or(Error::<T>::DidNotFound)?;
+            ensure!(did.controller == who, Error::<T>::NotController);
+            ensure!(
+                SessionKeys::<T>::take(&account_id, &session_key).is_some(),
+                Error::<T>::SessionNotFound
+            );
+
+            Self::deposit_event(Event::SessionRevoked {
+                account: account_id,
+                session_key,
+            });
+
+            Ok(())
+        }
+
+        /// Vouch for the DID of another account
+        ///
+        /// # Arguments
+        /// * `origin` - Transaction origin (the attester, must have an active DID)
+        /// * `subject` - Account whose DID is attested
+        ///
+        /// # Returns
+        /// * `DispatchResult` - Success or error
+        ///
+        /// # Events
+        /// * `Attested` - Emitted when the attestation is recorded
+        ///
+        /// # Errors
+        /// * `CannotAttestSelf` - Origin is the subject
+        /// * `AttesterDidNotActive` - Origin has no active DID
+        /// * `DidNotFound` - The subject has no DID
+        /// * `DidRevoked` - The subject's DID is revoked
+        /// * `AlreadyAttested` - Origin already attests the subject
+        /// * `TooManyAttestations` - Either side reached `MaxAttestations`
+        /// * `MutationsFrozen` - DID mutations are frozen
+        #[pallet::call_index(10)]
+        #[pallet::weight(10_000)]
+        pub fn attest(origin: OriginFor<T>, subject: T::AccountId) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            Self::ensure_mutable()?;
+
+            ensure!(who != subject, Error::<T>::CannotAttestSelf);
+            ensure!(Self::is_did_active(&who), Error::<T>::AttesterDidNotActive);
+            let did = DidDocuments::<T>::get(&subject).ok_or(Error::<T>::DidNotFound)?;
+            ensure!(did.status != DidStatus::Revoked, Error::<T>::DidRevoked);
+
+            let now = frame_system::Pallet::<T>::block_number();
+            Attestations::<T>::try_mutate(&subject, |attestations| -> DispatchResult {
+                ensure!(
+                    !attestations.iter().any(|(attester, _)| attester == &who),
+                    Error::<T>::AlreadyAttested
+                );
+                attestations
+                    .try_push((who.clone(), now))
+                    .map_err(|_| Error::<T>::TooManyAttestations.into())
+            })?;
+            AttestedSubjects::<T>::try_append(&who, &subject)
+                .map_err(|_| Error::<T>::TooManyAttestations)?;
+
+            Self::invalidate_trust_scores(&subject);
+            Self::cache_trust_score(&subject);
+
+            Self::deposit_event(Event::Attested {
+                subject,
+                attester: who,
+            });
+
+            Ok(())
+        }
+
+        /// Withdraw an attestation
+        ///
+        /// Works whatever the state of either DID, so attestations can always be withdrawn.
+        ///
+        /// # Arguments
+        /// * `origin` - Transaction origin (the attester)
+        /// * `subject` - Account whose DID was attested
+        ///
+        /// # Returns
+        /// * `DispatchResult` - Success or error
+        ///
+        /// # Events
+        /// * `AttestationRevoked` - Emitted when the attestation is removed
+        ///
+        /// # Errors
+        /// * `AttestationNotFound` - Origin does not attest the subject
+        /// * `MutationsFrozen` - DID mutations are frozen
+        #[pallet::call_index(11)]
+        #[pallet::weight(10_000)]
+        pub fn revoke_attestation(origin: OriginFor<T>, subject: T::AccountId) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            Self::ensure_mutable()?;
+
+            Attestations::<T>::try_mutate(&subject, |attestations| -> DispatchResult {
+                let len = attestations.len();
+                attestations.retain(|(attester, _)| attester != &who);
+                ensure!(attestations.len() < len, Error::<T>::AttestationNotFound);
+                Ok(())
+            })?;
+            AttestedSubjects::<T>::mutate(&who, |subjects| subjects.retain(|s| s != &subject));
+
+            Self::invalidate_trust_scores(&subject);
+            Self::cache_trust_score(&subject);
+
+            Self::deposit_event(Event::AttestationRevoked {
+                subject,
+                attester: who,
+            });
+
+            Ok(())
+        }
+
+        /// Record that a DID identifier is registered on another network of the chain group
+        ///
+        /// Invoices and other records on this chain can then reference the DID, and
+        /// `resolve_any` reports the network to look it up on. Registering an identifier
+        /// again replaces its network and hint block.
+        ///
+        /// # Arguments
+        /// * `origin` - Must satisfy `RegistrarOrigin`
+        /// * `did_identifier` - DID identifier as issued by the other network
+        /// * `network_id` - Network the DID is registered on
+        /// * `registered_hint_block` - Block of that network around which it was registered
+        ///
+        /// # Returns
+        /// * `DispatchResult` - Success or error
+        ///
+        /// # Events
+        /// * `ExternalDidRegistered` - Emitted with the recorded location
+        ///
+        /// # Errors
+        /// * `InvalidDidIdentifier` - The identifier is empty
+        /// * `DidIdentifierTooLong` - The identifier exceeds `MaxDidLength`
+        /// * `DidIsLocal` - The identifier is registered on this chain
+        /// * `MutationsFrozen` - DID mutations are frozen
+        #[pallet::call_index(12)]
+        #[pallet::weight(10_000)]
+        pub fn register_external_did(
+            origin: OriginFor<T>,
+            did_identifier: Vec<u8>,
+            network_id: NetworkId,
+            registered_hint_block: BlockNumberFor<T>,
+        ) -> DispatchResult {
+            T::RegistrarOrigin::ensure_origin(origin)?;
+            Self::ensure_mutable()?;
+
+            ensure!(!did_identifier.is_empty(), Error::<T>::InvalidDidIdentifier);
+            let bounded: BoundedVec<u8, T::MaxDidLength> = did_identifier
+                .clone()
+                .try_into()
+                .map_err(|_| Error::<T>::DidIdentifierTooLong)?;
+            ensure!(!DidToAccount::<T>::contains_key(&bounded), Error::<T>::DidIsLocal);
+
+            ExternalDids::<T>::insert(
+                bounded,
+                ExternalRef {
+                    network_id,
+                    registered_hint_block,
+                },
+            );
+
+            Self::deposit_event(Event::ExternalDidRegistered {
+                did_identifier,
+                network_id,
+                registered_hint_block,
             });
 
             Ok(())
@@ -459,11 +1306,165 @@ pub mod pallet {
 
     // Helper functions for RPC
     impl<T: Config> Pallet<T> {
+        /// Fail with `MutationsFrozen` while maintenance mode is active
+        fn ensure_mutable() -> DispatchResult {
+            ensure!(!MutationsFrozen::<T>::get(), Error::<T>::MutationsFrozen);
+            Ok(())
+        }
+
+        /// Fail unless `status` is `Suspended`
+        fn ensure_suspended(status: &DidStatus) -> DispatchResult {
+            match status {
+                DidStatus::Suspended => Ok(()),
+                DidStatus::Revoked => Err(Error::<T>::DidRevoked.into()),
+                DidStatus::Active => Err(Error::<T>::DidNotSuspended.into()),
+            }
+        }
+
+        /// Check that `session_key` may make the `required` changes to the DID of `account`
+        ///
+        /// Returns `false` after removing an expired grant, which the caller must not fail on.
+        fn authorize_session(
+            account: &T::AccountId,
+            session_key: &T::AccountId,
+            required: &UpdateScope,
+        ) -> Result<bool, DispatchError> {
+            let grant =
+                SessionKeys::<T>::get(account, session_key).ok_or(Error::<T>::NotController)?;
+
+            if frame_system::Pallet::<T>::block_number() >= grant.expires_at {
+                SessionKeys::<T>::remove(account, session_key);
+                Self::deposit_event(Event::SessionExpired {
+                    account: account.clone(),
+                    session_key: session_key.clone(),
+                });
+                return Ok(false);
+            }
+
+            ensure!(grant.scope.covers(required), Error::<T>::SessionScopeExceeded);
+            Ok(true)
+        }
+
+        /// Emit `DidStatusChanged` and report the transition to `OnTransition`
+        ///
+        /// Only active DIDs pass trust on, so the cached scores the DID affects are dropped.
+        fn note_status_change(account: T::AccountId, old_status: DidStatus, new_status: DidStatus) {
+            Self::invalidate_trust_scores(&account);
+            T::OnTransition::on_transition(
+                TransitionSubject::Did(account.clone()),
+                status_index(&old_status),
+                status_index(&new_status),
+            );
+            Self::deposit_event(Event::DidStatusChanged {
+                account,
+                old_status,
+                new_status,
+            });
+        }
+
+        /// Reactivate a suspended DID and drop its suspension record
+        fn do_unsuspend(account: T::AccountId, lifted_by: T::AccountId) -> DispatchResult {
+            DidDocuments::<T>::try_mutate(&account, |did_opt| -> DispatchResult {
+                let did = did_opt.as_mut().ok_or(Error::<T>::DidNotFound)?;
+                did.status = DidStatus::Active;
+                did.updated_at = frame_system::Pallet::<T>::block_number();
+                Ok(())
+            })?;
+            Suspensions::<T>::remove(&account);
+
+            Self::deposit_event(Event::DidUnsuspended {
+                account: account.clone(),
+                lifted_by,
+            });
+            Self::note_status_change(account, DidStatus::Suspended, DidStatus::Active);
+
+            Ok(())
+        }
+
+        /// Drop the cached scores that depend on the attestations `subject` received: its
+        /// own and those of the DIDs it attests to
+        fn invalidate_trust_scores(subject: &T::AccountId) {
+            TrustScoreCache::<T>::remove(subject);
+            for attested in AttestedSubjects::<T>::get(subject) {
+                TrustScoreCache::<T>::remove(&attested);
+            }
+        }
+
+        /// Compute the trust score of `account` and store it in `TrustScoreCache`
+        fn cache_trust_score(account: &T::AccountId) {
+            let now = frame_system::Pallet::<T>::block_number();
+            let score = Self::compute_trust_score(account, 0, now);
+            TrustScoreCache::<T>::insert(account, ScoreCache { score, computed_at: now });
+        }
+
+        /// Trust score of `account` with attesters evaluated from `depth` on
+        fn compute_trust_score(
+            account: &T::AccountId,
+            depth: u32,
+            now: BlockNumberFor<T>,
+        ) -> u32 {
+            if !Self::is_did_active(account) {
+                return 0;
+            }
+            let base = T::BaseTrustScore::get();
+            if depth >= MAX_TRUST_DEPTH {
+                return base;
+            }
+
+            Attestations::<T>::get(account).iter().fold(base, |score, (attester, attested_at)| {
+                let attester_score = Self::compute_trust_score(attester, depth + 1, now)
+                    .min(T::MaxTrustScore::get());
+                let contribution = T::AttestationWeight::get() * attester_score;
+                score.saturating_add(Self::decay(contribution, now.saturating_sub(*attested_at)))
+            })
+        }
+
+        /// `value` scaled down linearly with `age`: whole when new, half at
+        /// `AttestationHalfLife` and nothing from twice that age on
+        fn decay(value: u32, age: BlockNumberFor<T>) -> u32 {
+            let half_life: u32 = T::AttestationHalfLife::get().saturated_into();
+            if half_life == 0 {
+                return value;
+            }
+            let lifetime = half_life.saturating_mul(2);
+            let age: u32 = age.saturated_into();
+            Perbill::from_rational(lifetime.saturating_sub(age), lifetime) * value
+        }
+
+        /// Trust score of an account's DID (for RPC)
+        ///
+        /// Served from `TrustScoreCache` while the entry is younger than `TrustScoreCacheTtl`,
+        /// otherwise computed without being stored. Accounts without an active DID score zero.
+        pub fn get_trust_score(account: &T::AccountId) -> u32 {
+            if !Self::is_did_active(account) {
+                return 0;
+            }
+            let now = frame_system::Pallet::<T>::block_number();
+            if let Some(cache) = TrustScoreCache::<T>::get(account) {
+                if now < cache.computed_at.saturating_add(T::TrustScoreCacheTtl::get()) {
+                    return cache.score;
+                }
+            }
+
+            Self::compute_trust_score(account, 0, now)
+        }
+
         /// Get DID document for an account (for RPC)
         pub fn get_did(account: &T::AccountId) -> Option<DidDocument<T>> {
             DidDocuments::<T>::get(account)
         }
 
+        /// Difference between `from_block_doc`, the account's DID document as fetched at an
+        /// earlier block, and its current one (for RPC). `None` if the account has no DID.
+        ///
+        /// The caller supplies the earlier document, so the runtime needs no historical state.
+        pub fn did_diff(
+            account: &T::AccountId,
+            from_block_doc: &DidDocument<T>,
+        ) -> Option<DidDiff> {
+            DidDocuments::<T>::get(account).map(|current| from_block_doc.diff(&current))
+        }
+
         /// Get account from DID identifier (for RPC)
         pub fn get_account_from_did(did_identifier: &[u8]) -> Option<T::AccountId> {
             let bounded: BoundedVec<u8, T::MaxDidLength> = did_identifier
@@ -473,6 +1474,23 @@ pub mod pallet {
             DidToAccount::<T>::get(bounded)
         }
 
+        /// Resolve a DID identifier on this chain or to the network it lives on (for RPC)
+        ///
+        /// Local registrations take precedence over `ExternalDids`.
+        pub fn resolve_any(did_identifier: &[u8]) -> Resolution<T::AccountId> {
+            let Ok(bounded) = BoundedVec::<u8, T::MaxDidLength>::try_from(did_identifier.to_vec())
+            else {
+                return Resolution::Unknown;
+            };
+            if let Some(account) = DidToAccount::<T>::get(&bounded) {
+                return Resolution::Local(account);
+            }
+            match ExternalDids::<T>::get(&bounded) {
+                Some(external) => Resolution::External(external.network_id),
+                None => Resolution::Unknown,
+            }
+        }
+
         /// Verify if a DID is active
         pub fn is_did_active(account: &T::AccountId) -> bool {
             if let Some(did) = DidDocuments::<T>::get(account) {
@@ -486,6 +1504,48 @@ pub mod pallet {
         pub fn total_dids() -> u64 {
             DidCount::<T>::get()
         }
+
+        /// Classify the failure of a batch item for its skip event
+        pub fn skip_reason(error: DispatchError) -> SkipReason {
+            match error {
+                DispatchError::Module(ModuleError { index, error, .. })
+                    if usize::from(index) == <Self as PalletInfoAccess>::index() =>
+                {
+                    Error::<T>::decode(&mut &error[..])
+                        .map(Into::into)
+                        .unwrap_or(SkipReason::Other(error[0]))
+                }
+                DispatchError::BadOrigin => SkipReason::Unauthorized,
+                _ => SkipReason::Other(SkipReason::FOREIGN_ERROR),
+            }
+        }
+    }
+
+    impl<T: Config> DidProvider<T::AccountId> for Pallet<T> {
+        fn is_active(who: &T::AccountId) -> bool {
+            Self::is_did_active(who)
+        }
+    }
+
+    impl<T: Config> From<Error<T>> for SkipReason {
+        fn from(error: Error<T>) -> Self {
+            match error {
+                Error::<T>::PublicKeyTooLong
+                | Error::<T>::MetadataTooLong
+                | Error::<T>::DidIdentifierTooLong => Self::TooLong,
+                Error::<T>::DidAlreadyExists
+                | Error::<T>::AlreadyAttested
+                | Error::<T>::DidIsLocal => Self::Duplicate,
+                Error::<T>::DidNotFound | Error::<T>::AttestationNotFound => Self::NotFound,
+                Error::<T>::TooManyAttestations => Self::BoundExceeded,
+                Error::<T>::NotController
+                | Error::<T>::NotSuspensionInitiator
+                | Error::<T>::SessionScopeExceeded => Self::Unauthorized,
+                Error::<T>::SessionNotFound => Self::NotFound,
+                // Field-less pallet errors encode as their variant index
+                other => Self::Other(other.encode()[0]),
+            }
+        }
     }
 }
 
@@ -3,7 +3,7 @@ use frame_support::{parameter_types, traits::ConstU32};
 use sp_core::H256;
 use sp_runtime::{
     traits::{BlakeTwo256, IdentityLookup},
-    BuildStorage,
+    BuildStorage, Perbill,
 };
 
 type Block = frame_system::mocking::MockBlock<Test>;
@@ -51,6 +51,13 @@ parameter_types! {
     pub const MaxPublicKeyLength: u32 = 256;
     pub const MaxMetadataLength: u32 = 1024;
     pub const MaxDidLength: u32 = 256;
+    pub const SelfSuspendCooldown: u64 = 10;
+    pub const MaxAttestations: u32 = 4;
+    pub const BaseTrustScore: u32 = 100;
+    pub const MaxTrustScore: u32 = 1000;
+    pub const AttestationWeight: Perbill = Perbill::from_percent(50);
+    pub const AttestationHalfLife: u64 = 100;
+    pub const TrustScoreCacheTtl: u64 = 10;
 }
 
 impl pallet_did::Config for Test {
@@ -58,6 +65,16 @@ impl pallet_did::Config for Test {
     type MaxPublicKeyLength = MaxPublicKeyLength;
     type MaxMetadataLength = MaxMetadataLength;
     type MaxDidLength = MaxDidLength;
+    type FreezeOrigin = frame_system::EnsureRoot<u64>;
+    type SelfSuspendCooldown = SelfSuspendCooldown;
+    type MaxAttestations = MaxAttestations;
+    type BaseTrustScore = BaseTrustScore;
+    type MaxTrustScore = MaxTrustScore;
+    type AttestationWeight = AttestationWeight;
+    type AttestationHalfLife = AttestationHalfLife;
+    type TrustScoreCacheTtl = TrustScoreCacheTtl;
+    type RegistrarOrigin = frame_system::EnsureRoot<u64>;
+    type OnTransition = ();
 }
 
 // Build genesis storage
@@ -1,5 +1,6 @@
-use crate::{mock::*, Error, Event};
+use crate::{mock::*, DidField, DidStatus, Error, Event, ScoreCache, TrustScoreCache};
 use frame_support::{assert_noop, assert_ok};
+use tidygen_primitives::identity::Resolution;
 
 #[test]
 fn register_did_works() {
@@ -248,49 +249,6 @@ fn cannot_update_revoked_did() {
     });
 }
 
-#[test]
-fn resolve_did_works() {
-    new_test_ext().execute_with(|| {
-        let controller = 1u64;
-        let account = 2u64;
-        let public_key = b"0x1234".to_vec();
-        let metadata = b"data".to_vec();
-
-        // Register DID
-        assert_ok!(Did::register_did(
-            RuntimeOrigin::signed(controller),
-            account,
-            public_key,
-            metadata
-        ));
-
-        // Resolve DID
-        assert_ok!(Did::resolve_did(RuntimeOrigin::signed(controller), account));
-
-        // Verify event
-        System::assert_has_event(
-            Event::DidResolved {
-                account,
-                status: crate::DidStatus::Active,
-            }
-            .into(),
-        );
-    });
-}
-
-#[test]
-fn resolve_nonexistent_did_fails() {
-    new_test_ext().execute_with(|| {
-        let account = 1u64;
-
-        // Try to resolve non-existent DID
-        assert_noop!(
-            Did::resolve_did(RuntimeOrigin::signed(account), account),
-            Error::<Test>::DidNotFound
-        );
-    });
-}
-
 #[test]
 fn multiple_dids_work() {
     new_test_ext().execute_with(|| {
@@ -317,6 +275,16 @@ fn multiple_dids_work() {
     });
 }
 
+#[test]
+fn did_identifier_format_is_stable() {
+    let did = crate::DidDocument::<Test>::generate_did_identifier(&2);
+
+    // Prefix plus the first 8 bytes of blake2_256(account) in lowercase hex
+    assert_eq!(did.to_vec(), b"did:substrate:tidygen:e88b43fded6323ef".to_vec());
+    assert_eq!(did.len(), crate::DID_PREFIX.len() + 16);
+    assert_eq!(did.len(), 38);
+}
+
 #[test]
 fn did_identifier_is_unique() {
     new_test_ext().execute_with(|| {
@@ -468,3 +436,734 @@ fn nonce_increments_on_update() {
     });
 }
 
+
+#[test]
+fn frozen_mutations_are_rejected_and_reads_keep_working() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        assert_ok!(Did::register_did(
+            RuntimeOrigin::signed(1),
+            2,
+            b"key".to_vec(),
+            b"{}".to_vec()
+        ));
+
+        // Only the freeze origin may toggle the flag
+        assert_noop!(
+            Did::set_mutations_frozen(RuntimeOrigin::signed(1), true),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_ok!(Did::set_mutations_frozen(RuntimeOrigin::root(), true));
+        assert!(Did::mutations_frozen());
+        System::assert_last_event(Event::DidMutationsFrozen.into());
+
+        // Every mutating extrinsic is blocked
+        assert_noop!(
+            Did::register_did(RuntimeOrigin::signed(1), 3, b"key".to_vec(), b"{}".to_vec()),
+            Error::<Test>::MutationsFrozen
+        );
+        assert_noop!(
+            Did::update_did(RuntimeOrigin::signed(1), 2, None, Some(b"{\"v\":2}".to_vec())),
+            Error::<Test>::MutationsFrozen
+        );
+        assert_noop!(
+            Did::revoke_did(RuntimeOrigin::signed(1), 2),
+            Error::<Test>::MutationsFrozen
+        );
+
+        // Resolution still works
+        let did = Did::get_did(&2).unwrap();
+        assert_eq!(Did::get_account_from_did(&did.did_identifier), Some(2));
+        assert!(Did::is_did_active(&2));
+        assert_eq!(Did::total_dids(), 1);
+
+        // Unfreezing restores mutations
+        assert_ok!(Did::set_mutations_frozen(RuntimeOrigin::root(), false));
+        System::assert_last_event(Event::DidMutationsUnfrozen.into());
+        assert_ok!(Did::update_did(
+            RuntimeOrigin::signed(1),
+            2,
+            None,
+            Some(b"{\"v\":2}".to_vec())
+        ));
+        assert_ok!(Did::revoke_did(RuntimeOrigin::signed(1), 2));
+    });
+}
+
+/// Register a DID for `subject` controlled by `controller`
+fn register_controlled_did(controller: u64, subject: u64) {
+    assert_ok!(Did::register_did(
+        RuntimeOrigin::signed(controller),
+        subject,
+        b"0x1234".to_vec(),
+        b"{}".to_vec()
+    ));
+}
+
+#[test]
+fn self_suspend_requires_cooldown_before_self_unsuspend() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let (org, employee) = (1u64, 2u64);
+        register_controlled_did(org, employee);
+
+        // The subject suspends its own DID even though the org controls it
+        assert_ok!(Did::self_suspend(RuntimeOrigin::signed(employee)));
+        assert_eq!(Did::get_did(&employee).unwrap().status, DidStatus::Suspended);
+        assert!(!Did::is_did_active(&employee));
+        assert_eq!(Did::suspensions(employee).unwrap().initiator, employee);
+        System::assert_has_event(
+            Event::DidSuspended { account: employee, initiator: employee }.into(),
+        );
+        assert_noop!(
+            Did::self_suspend(RuntimeOrigin::signed(employee)),
+            Error::<Test>::DidSuspended
+        );
+
+        // Still locked one block before the cooldown ends
+        System::set_block_number(10);
+        assert_noop!(
+            Did::self_unsuspend(RuntimeOrigin::signed(employee)),
+            Error::<Test>::SelfSuspendCooldownActive
+        );
+
+        System::set_block_number(11);
+        assert_ok!(Did::self_unsuspend(RuntimeOrigin::signed(employee)));
+        assert!(Did::is_did_active(&employee));
+        assert!(Did::suspensions(employee).is_none());
+        System::assert_has_event(
+            Event::DidUnsuspended { account: employee, lifted_by: employee }.into(),
+        );
+        assert_noop!(
+            Did::self_unsuspend(RuntimeOrigin::signed(employee)),
+            Error::<Test>::DidNotSuspended
+        );
+        assert_noop!(
+            Did::self_suspend(RuntimeOrigin::signed(3)),
+            Error::<Test>::DidNotFound
+        );
+    });
+}
+
+#[test]
+fn controller_can_unsuspend_immediately() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let (org, employee) = (1u64, 2u64);
+        register_controlled_did(org, employee);
+        assert_ok!(Did::self_suspend(RuntimeOrigin::signed(employee)));
+
+        assert_noop!(
+            Did::unsuspend_did(RuntimeOrigin::signed(3), employee),
+            Error::<Test>::NotController
+        );
+        assert_ok!(Did::unsuspend_did(RuntimeOrigin::signed(org), employee));
+        assert!(Did::is_did_active(&employee));
+        System::assert_has_event(
+            Event::DidUnsuspended { account: employee, lifted_by: org }.into(),
+        );
+        System::assert_last_event(
+            Event::DidStatusChanged {
+                account: employee,
+                old_status: DidStatus::Suspended,
+                new_status: DidStatus::Active,
+            }
+            .into(),
+        );
+        assert_noop!(
+            Did::unsuspend_did(RuntimeOrigin::signed(org), employee),
+            Error::<Test>::DidNotSuspended
+        );
+    });
+}
+
+#[test]
+fn revocation_overrides_self_suspension() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let (org, employee) = (1u64, 2u64);
+        register_controlled_did(org, employee);
+        assert_ok!(Did::self_suspend(RuntimeOrigin::signed(employee)));
+
+        assert_ok!(Did::revoke_did(RuntimeOrigin::signed(org), employee));
+        assert_eq!(Did::get_did(&employee).unwrap().status, DidStatus::Revoked);
+        assert!(Did::suspensions(employee).is_none());
+
+        // Neither the subject nor the controller can bring a revoked DID back
+        System::set_block_number(20);
+        assert_noop!(
+            Did::self_unsuspend(RuntimeOrigin::signed(employee)),
+            Error::<Test>::DidRevoked
+        );
+        assert_noop!(
+            Did::unsuspend_did(RuntimeOrigin::signed(org), employee),
+            Error::<Test>::DidRevoked
+        );
+        assert_noop!(
+            Did::self_suspend(RuntimeOrigin::signed(employee)),
+            Error::<Test>::DidRevoked
+        );
+    });
+}
+
+#[test]
+fn session_scope_limits_what_a_session_key_can_update() {
+    use crate::UpdateScope;
+
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let (org, employee, device, laptop) = (1u64, 2u64, 5u64, 6u64);
+        register_controlled_did(org, employee);
+
+        // Only the controller hands out sessions, and only for the future
+        assert_noop!(
+            Did::grant_session(
+                RuntimeOrigin::signed(employee),
+                employee,
+                device,
+                UpdateScope::KeysAndMetadata,
+                10
+            ),
+            Error::<Test>::NotController
+        );
+        assert_noop!(
+            Did::grant_session(
+                RuntimeOrigin::signed(org),
+                employee,
+                device,
+                UpdateScope::MetadataOnly,
+                1
+            ),
+            Error::<Test>::InvalidSessionExpiry
+        );
+        assert_ok!(Did::grant_session(
+            RuntimeOrigin::signed(org),
+            employee,
+            device,
+            UpdateScope::MetadataOnly,
+            10
+        ));
+        System::assert_last_event(
+            Event::SessionGranted {
+                account: employee,
+                session_key: device,
+                scope: UpdateScope::MetadataOnly,
+                expires_at: 10,
+            }
+            .into(),
+        );
+
+        // A metadata-only key cannot rotate the public key
+        assert_ok!(Did::update_did(
+            RuntimeOrigin::signed(device),
+            employee,
+            None,
+            Some(b"{\"device\":1}".to_vec())
+        ));
+        assert_eq!(Did::get_did(&employee).unwrap().metadata.to_vec(), b"{\"device\":1}".to_vec());
+        assert_noop!(
+            Did::update_did(RuntimeOrigin::signed(device), employee, Some(b"0x99".to_vec()), None),
+            Error::<Test>::SessionScopeExceeded
+        );
+
+        // A wider grant allows both; keys without a grant stay locked out
+        assert_ok!(Did::grant_session(
+            RuntimeOrigin::signed(org),
+            employee,
+            laptop,
+            UpdateScope::KeysAndMetadata,
+            10
+        ));
+        assert_ok!(Did::update_did(
+            RuntimeOrigin::signed(laptop),
+            employee,
+            Some(b"0x99".to_vec()),
+            Some(b"{}".to_vec())
+        ));
+        let did_doc = Did::get_did(&employee).unwrap();
+        assert_eq!(did_doc.public_key.to_vec(), b"0x99".to_vec());
+        assert_eq!(did_doc.nonce, 2);
+        assert_noop!(
+            Did::update_did(RuntimeOrigin::signed(7), employee, None, Some(b"{}".to_vec())),
+            Error::<Test>::NotController
+        );
+
+        // Sessions only cover the DID they were granted for
+        register_controlled_did(org, 3);
+        assert_noop!(
+            Did::update_did(RuntimeOrigin::signed(laptop), 3, None, Some(b"{}".to_vec())),
+            Error::<Test>::NotController
+        );
+    });
+}
+
+#[test]
+fn expired_session_is_removed_on_use() {
+    use crate::UpdateScope;
+
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let (org, employee, device) = (1u64, 2u64, 5u64);
+        register_controlled_did(org, employee);
+        assert_ok!(Did::grant_session(
+            RuntimeOrigin::signed(org),
+            employee,
+            device,
+            UpdateScope::MetadataOnly,
+            10
+        ));
+
+        // Valid through the block before `expires_at`
+        System::set_block_number(9);
+        assert_ok!(Did::update_did(
+            RuntimeOrigin::signed(device),
+            employee,
+            None,
+            Some(b"{\"at\":9}".to_vec())
+        ));
+
+        System::set_block_number(10);
+        assert_ok!(Did::update_did(
+            RuntimeOrigin::signed(device),
+            employee,
+            None,
+            Some(b"{\"at\":10}".to_vec())
+        ));
+        System::assert_last_event(
+            Event::SessionExpired { account: employee, session_key: device }.into(),
+        );
+        assert!(Did::session_keys(employee, device).is_none());
+        let did_doc = Did::get_did(&employee).unwrap();
+        assert_eq!(did_doc.metadata.to_vec(), b"{\"at\":9}".to_vec());
+        assert_eq!(did_doc.nonce, 1);
+
+        // With the grant gone, the key is an outsider again
+        assert_noop!(
+            Did::update_did(RuntimeOrigin::signed(device), employee, None, Some(b"{}".to_vec())),
+            Error::<Test>::NotController
+        );
+    });
+}
+
+#[test]
+fn revoked_session_stops_working_mid_session() {
+    use crate::UpdateScope;
+
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let (org, employee, device) = (1u64, 2u64, 5u64);
+        register_controlled_did(org, employee);
+        assert_ok!(Did::grant_session(
+            RuntimeOrigin::signed(org),
+            employee,
+            device,
+            UpdateScope::KeysAndMetadata,
+            100
+        ));
+        assert_ok!(Did::update_did(
+            RuntimeOrigin::signed(device),
+            employee,
+            None,
+            Some(b"{}".to_vec())
+        ));
+
+        // The session key cannot revoke or extend grants itself
+        assert_noop!(
+            Did::revoke_session(RuntimeOrigin::signed(device), employee, device),
+            Error::<Test>::NotController
+        );
+        assert_noop!(
+            Did::grant_session(
+                RuntimeOrigin::signed(device),
+                employee,
+                device,
+                UpdateScope::KeysAndMetadata,
+                1_000
+            ),
+            Error::<Test>::NotController
+        );
+
+        System::set_block_number(5);
+        assert_ok!(Did::revoke_session(RuntimeOrigin::signed(org), employee, device));
+        System::assert_last_event(
+            Event::SessionRevoked { account: employee, session_key: device }.into(),
+        );
+        assert_noop!(
+            Did::update_did(RuntimeOrigin::signed(device), employee, None, Some(b"{}".to_vec())),
+            Error::<Test>::NotController
+        );
+        assert_noop!(
+            Did::revoke_session(RuntimeOrigin::signed(org), employee, device),
+            Error::<Test>::SessionNotFound
+        );
+
+        // The controller keeps full access
+        assert_ok!(Did::update_did(
+            RuntimeOrigin::signed(org),
+            employee,
+            Some(b"0x42".to_vec()),
+            None
+        ));
+    });
+}
+
+#[test]
+fn did_errors_map_to_skip_reasons() {
+    use tidygen_primitives::batch::SkipReason;
+
+    assert_eq!(SkipReason::from(Error::<Test>::PublicKeyTooLong), SkipReason::TooLong);
+    assert_eq!(SkipReason::from(Error::<Test>::DidAlreadyExists), SkipReason::Duplicate);
+    assert_eq!(SkipReason::from(Error::<Test>::DidNotFound), SkipReason::NotFound);
+    assert_eq!(SkipReason::from(Error::<Test>::NotController), SkipReason::Unauthorized);
+    // `DidRevoked` is the 6th error
+    assert_eq!(SkipReason::from(Error::<Test>::DidRevoked), SkipReason::Other(5));
+
+    new_test_ext().execute_with(|| {
+        register_controlled_did(1, 2);
+        let error = Did::revoke_did(RuntimeOrigin::signed(3), 2).unwrap_err();
+        assert_eq!(Did::skip_reason(error), SkipReason::Unauthorized);
+        let error = Did::revoke_did(RuntimeOrigin::signed(1), 4).unwrap_err();
+        assert_eq!(Did::skip_reason(error), SkipReason::NotFound);
+    });
+}
+
+#[test]
+fn did_document_json_field_names_are_stable() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Did::register_did(RuntimeOrigin::signed(1), 2, vec![0xab; 4], b"{}".to_vec()));
+        let did_doc = Did::get_did(&2).unwrap();
+        let json = serde_json::to_value(&did_doc).unwrap();
+
+        // Frontend types are generated from these names; renaming one breaks them
+        let mut keys: Vec<&str> = json.as_object().unwrap().keys().map(String::as_str).collect();
+        keys.sort();
+        assert_eq!(
+            keys,
+            [
+                "controller",
+                "createdAt",
+                "didIdentifier",
+                "metadata",
+                "nonce",
+                "publicKey",
+                "status",
+                "updatedAt",
+            ]
+        );
+        assert_eq!(json["publicKey"], "0xabababab");
+        assert_eq!(json["metadata"], "0x7b7d");
+        assert_eq!(json["status"], "Active");
+
+        assert_eq!(serde_json::from_value::<crate::DidDocument<Test>>(json).unwrap(), did_doc);
+    });
+}
+
+/// Changed fields of a diff, in document order
+fn changed_fields(diff: &crate::DidDiff) -> Vec<DidField> {
+    diff.changes.iter().map(|change| change.field).collect()
+}
+
+#[test]
+fn did_diff_reports_a_key_change() {
+    use codec::Encode;
+
+    new_test_ext().execute_with(|| {
+        register_controlled_did(1, 2);
+        let before = Did::get_did(&2).unwrap();
+
+        System::set_block_number(5);
+        assert_ok!(Did::update_did(RuntimeOrigin::signed(1), 2, Some(vec![0xcd; 4]), None));
+        let after = Did::get_did(&2).unwrap();
+
+        let diff = Did::did_diff(&2, &before).unwrap();
+        assert_eq!(
+            changed_fields(&diff),
+            [DidField::PublicKey, DidField::UpdatedAt, DidField::Nonce]
+        );
+        assert_eq!(diff.changed, 0b1001_0010);
+        assert!(diff.contains(DidField::PublicKey) && !diff.contains(DidField::Metadata));
+        let change = &diff.changes[0];
+        assert_eq!(change.old_hash, before.field_hash(DidField::PublicKey));
+        assert_eq!(change.new_hash, after.field_hash(DidField::PublicKey));
+        assert_eq!(change.new_hash, sp_io::hashing::blake2_256(&after.public_key.encode()));
+    });
+}
+
+#[test]
+fn did_diff_reports_a_metadata_change() {
+    new_test_ext().execute_with(|| {
+        register_controlled_did(1, 2);
+        let before = Did::get_did(&2).unwrap();
+
+        // Same block: only the metadata and the nonce move
+        assert_ok!(Did::update_did(RuntimeOrigin::signed(1), 2, None, Some(b"{\"v\":2}".to_vec())));
+
+        let diff = Did::did_diff(&2, &before).unwrap();
+        assert_eq!(changed_fields(&diff), [DidField::Metadata, DidField::Nonce]);
+        assert_eq!(diff.changed, DidField::Metadata.bit() | DidField::Nonce.bit());
+    });
+}
+
+#[test]
+fn did_diff_reports_a_status_change() {
+    use codec::Encode;
+
+    new_test_ext().execute_with(|| {
+        register_controlled_did(1, 2);
+        let before = Did::get_did(&2).unwrap();
+
+        System::set_block_number(3);
+        assert_ok!(Did::revoke_did(RuntimeOrigin::signed(1), 2));
+
+        let diff = Did::did_diff(&2, &before).unwrap();
+        assert_eq!(changed_fields(&diff), [DidField::UpdatedAt, DidField::Status]);
+        let status = &diff.changes[1];
+        assert_eq!(status.old_hash, sp_io::hashing::blake2_256(&DidStatus::Active.encode()));
+        assert_eq!(status.new_hash, sp_io::hashing::blake2_256(&DidStatus::Revoked.encode()));
+    });
+}
+
+#[test]
+fn did_diff_of_an_unchanged_did_is_empty() {
+    new_test_ext().execute_with(|| {
+        register_controlled_did(1, 2);
+        let before = Did::get_did(&2).unwrap();
+
+        // Changes elsewhere leave the document alone
+        System::set_block_number(4);
+        register_controlled_did(1, 3);
+
+        let diff = Did::did_diff(&2, &before).unwrap();
+        assert!(diff.is_empty());
+        assert!(diff.changes.is_empty());
+        // No DID now, nothing to compare against
+        assert_eq!(Did::did_diff(&4, &before), None);
+    });
+}
+
+#[test]
+fn did_diff_json_field_names_are_stable() {
+    new_test_ext().execute_with(|| {
+        register_controlled_did(1, 2);
+        let before = Did::get_did(&2).unwrap();
+        assert_ok!(Did::update_did(RuntimeOrigin::signed(1), 2, None, Some(b"[]".to_vec())));
+
+        let diff = Did::did_diff(&2, &before).unwrap();
+        let json = serde_json::to_value(&diff).unwrap();
+        assert_eq!(json["changed"], 0b1000_0100);
+        assert_eq!(json["changes"][0]["field"], "metadata");
+        assert_eq!(json["changes"][1]["field"], "nonce");
+        assert!(json["changes"][0]["oldHash"].as_str().unwrap().starts_with("0x"));
+        assert!(json["changes"][0]["newHash"].as_str().unwrap().starts_with("0x"));
+        assert_eq!(serde_json::from_value::<crate::DidDiff>(json).unwrap(), diff);
+    });
+}
+
+/// Register self-controlled DIDs for `accounts`
+fn register_dids(accounts: &[u64]) {
+    for account in accounts {
+        register_controlled_did(*account, *account);
+    }
+}
+
+#[test]
+fn trust_score_contributions_decay_linearly() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        register_dids(&[1, 2]);
+        assert_ok!(Did::attest(RuntimeOrigin::signed(2), 1));
+        System::assert_has_event(Event::Attested { subject: 1, attester: 2 }.into());
+
+        // Base score plus half of the attester's base score
+        assert_eq!(Did::get_trust_score(&1), 150);
+
+        // 20 of the 200 blocks the attestation counts for have passed
+        System::set_block_number(21);
+        assert_eq!(Did::get_trust_score(&1), 145);
+
+        // Half the contribution at the half-life, none from twice it on
+        System::set_block_number(101);
+        assert_eq!(Did::get_trust_score(&1), 125);
+        System::set_block_number(201);
+        assert_eq!(Did::get_trust_score(&1), 100);
+        System::set_block_number(500);
+        assert_eq!(Did::get_trust_score(&1), 100);
+
+        // Without an active DID there is no score
+        assert_ok!(Did::revoke_did(RuntimeOrigin::signed(1), 1));
+        assert_eq!(Did::get_trust_score(&1), 0);
+    });
+}
+
+#[test]
+fn attestation_changes_invalidate_cached_trust_scores() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        register_dids(&[1, 2, 3]);
+
+        // Queries compute without storing anything
+        assert_eq!(Did::get_trust_score(&1), 100);
+        assert_eq!(Did::trust_score_cache(1), None);
+
+        // Fresh entries are served as they are, stale ones recomputed
+        TrustScoreCache::<Test>::insert(1, ScoreCache { score: 7, computed_at: 1 });
+        assert_eq!(Did::get_trust_score(&1), 7);
+        System::set_block_number(11);
+        assert_eq!(Did::get_trust_score(&1), 100);
+
+        // Attesting the subject caches its new score
+        assert_ok!(Did::attest(RuntimeOrigin::signed(2), 1));
+        assert_eq!(Did::trust_score_cache(1), Some(ScoreCache { score: 150, computed_at: 11 }));
+        assert_eq!(Did::get_trust_score(&1), 150);
+
+        // Attesting one of its attesters drops it
+        assert_ok!(Did::attest(RuntimeOrigin::signed(3), 2));
+        assert_eq!(Did::trust_score_cache(1), None);
+        assert_eq!(Did::trust_score_cache(2), Some(ScoreCache { score: 150, computed_at: 11 }));
+        assert_eq!(Did::get_trust_score(&1), 175);
+
+        // And withdrawing that attestation again
+        assert_ok!(Did::revoke_attestation(RuntimeOrigin::signed(3), 2));
+        System::assert_has_event(Event::AttestationRevoked { subject: 2, attester: 3 }.into());
+        assert_eq!(Did::get_trust_score(&1), 150);
+        assert_noop!(
+            Did::revoke_attestation(RuntimeOrigin::signed(3), 2),
+            Error::<Test>::AttestationNotFound
+        );
+
+        assert_noop!(Did::attest(RuntimeOrigin::signed(1), 1), Error::<Test>::CannotAttestSelf);
+        assert_noop!(Did::attest(RuntimeOrigin::signed(9), 1), Error::<Test>::AttesterDidNotActive);
+        assert_noop!(Did::attest(RuntimeOrigin::signed(2), 1), Error::<Test>::AlreadyAttested);
+    });
+}
+
+#[test]
+fn status_changes_invalidate_cached_trust_scores() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        register_dids(&[1, 2, 3]);
+        assert_ok!(Did::attest(RuntimeOrigin::signed(2), 1));
+        assert_ok!(Did::attest(RuntimeOrigin::signed(3), 2));
+        assert_ok!(Did::attest(RuntimeOrigin::signed(3), 1));
+        assert!(Did::trust_score_cache(1).is_some());
+
+        // A suspended attester passes nothing on
+        assert_ok!(Did::self_suspend(RuntimeOrigin::signed(2)));
+        assert_eq!(Did::trust_score_cache(1), None);
+        assert_eq!(Did::get_trust_score(&1), 150);
+
+        // Reactivating it counts again
+        TrustScoreCache::<Test>::insert(1, ScoreCache { score: 150, computed_at: 1 });
+        assert_ok!(Did::unsuspend_did(RuntimeOrigin::signed(2), 2));
+        assert_eq!(Did::trust_score_cache(1), None);
+        assert_eq!(Did::get_trust_score(&1), 225);
+
+        // As does revoking it
+        TrustScoreCache::<Test>::insert(1, ScoreCache { score: 225, computed_at: 1 });
+        assert_ok!(Did::revoke_did(RuntimeOrigin::signed(2), 2));
+        assert_eq!(Did::trust_score_cache(1), None);
+        assert_eq!(Did::get_trust_score(&1), 150);
+    });
+}
+
+#[test]
+fn trust_score_recursion_stops_two_levels_down() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        register_dids(&[1, 2, 3, 4]);
+
+        // 3 attests 2, which attests 1: 1 gets half of 2's 150
+        assert_ok!(Did::attest(RuntimeOrigin::signed(2), 1));
+        assert_ok!(Did::attest(RuntimeOrigin::signed(3), 2));
+        assert_eq!(Did::get_trust_score(&1), 175);
+
+        // Attestations of 3 are three levels below 1 and do not count
+        assert_ok!(Did::attest(RuntimeOrigin::signed(4), 3));
+        TrustScoreCache::<Test>::remove(1);
+        assert_eq!(Did::get_trust_score(&1), 175);
+
+        // Closing the cycle 1 -> 3 -> 2 -> 1 still terminates
+        assert_ok!(Did::attest(RuntimeOrigin::signed(1), 3));
+        TrustScoreCache::<Test>::remove(1);
+        assert_eq!(Did::get_trust_score(&1), 175);
+        // 3 gets half of 4's 100 and half of 1's 150, where 1 no longer looks further
+        assert_eq!(Did::get_trust_score(&3), 225);
+    });
+}
+
+#[test]
+fn resolve_any_prefers_local_then_external_dids() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        register_dids(&[1]);
+        let local = Did::get_did(&1).unwrap().did_identifier.to_vec();
+        assert_eq!(Did::resolve_any(&local), Resolution::Local(1));
+        assert_eq!(Did::resolve_any(b"did:tidygen:finance-bob"), Resolution::Unknown);
+
+        assert_ok!(Did::register_external_did(
+            RuntimeOrigin::root(),
+            b"did:tidygen:finance-bob".to_vec(),
+            *b"finance\0",
+            40
+        ));
+        System::assert_last_event(
+            Event::ExternalDidRegistered {
+                did_identifier: b"did:tidygen:finance-bob".to_vec(),
+                network_id: *b"finance\0",
+                registered_hint_block: 40,
+            }
+            .into(),
+        );
+        assert_eq!(
+            Did::resolve_any(b"did:tidygen:finance-bob"),
+            Resolution::External(*b"finance\0")
+        );
+        // External DIDs have no account on this chain
+        assert_eq!(Did::get_account_from_did(b"did:tidygen:finance-bob"), None);
+
+        // Registering again moves the DID to another network
+        assert_ok!(Did::register_external_did(
+            RuntimeOrigin::root(),
+            b"did:tidygen:finance-bob".to_vec(),
+            *b"payroll\0",
+            55
+        ));
+        assert_eq!(
+            Did::resolve_any(b"did:tidygen:finance-bob"),
+            Resolution::External(*b"payroll\0")
+        );
+        assert_eq!(Did::resolve_any(&[b'x'; 300]), Resolution::Unknown);
+    });
+}
+
+#[test]
+fn register_external_did_checks_origin_and_identifier() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        register_dids(&[1]);
+        let local = Did::get_did(&1).unwrap().did_identifier.to_vec();
+
+        assert_noop!(
+            Did::register_external_did(RuntimeOrigin::signed(1), b"did:x".to_vec(), [0; 8], 1),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_noop!(
+            Did::register_external_did(RuntimeOrigin::root(), local, [0; 8], 1),
+            Error::<Test>::DidIsLocal
+        );
+        assert_noop!(
+            Did::register_external_did(RuntimeOrigin::root(), vec![], [0; 8], 1),
+            Error::<Test>::InvalidDidIdentifier
+        );
+        assert_noop!(
+            Did::register_external_did(RuntimeOrigin::root(), vec![b'x'; 257], [0; 8], 1),
+            Error::<Test>::DidIdentifierTooLong
+        );
+
+        assert_ok!(Did::set_mutations_frozen(RuntimeOrigin::root(), true));
+        assert_noop!(
+            Did::register_external_did(RuntimeOrigin::root(), b"did:x".to_vec(), [0; 8], 1),
+            Error::<Test>::MutationsFrozen
+        );
+    });
+}
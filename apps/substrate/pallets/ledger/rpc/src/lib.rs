@@ -0,0 +1,568 @@
+//! RPC interface for the Ledger pallet
+
+use codec::Codec;
+use futures::{FutureExt, StreamExt};
+use jsonrpsee::{
+    core::{async_trait, RpcResult},
+    proc_macros::rpc,
+    types::{
+        error::{CallError, ErrorObject, INVALID_PARAMS_CODE},
+        SubscriptionResult,
+    },
+    SubscriptionSink,
+};
+use sc_client_api::BlockchainEvents;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::{traits::SpawnNamed, H256};
+use sp_runtime::traits::Block as BlockT;
+use std::sync::Arc;
+use tidygen_primitives::{
+    pagination::{InvalidCursor, PageCursor},
+    subscription::{self, SubscriptionItem},
+};
+
+pub use pallet_ledger_runtime_api::{
+    CreatedInvoice, LedgerApi as LedgerRuntimeApi, LedgerEventsApi as LedgerEventsRuntimeApi,
+};
+
+/// Error code for failed runtime API calls
+pub const RUNTIME_ERROR: i32 = 1;
+
+/// Notifications buffered per subscriber before it is considered lagging
+pub const SUBSCRIPTION_BUFFER: usize = 256;
+
+/// An invoice created in a finalized block
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InvoiceNotification<BlockHash, AccountId, Balance> {
+    /// Finalized block the invoice was created in
+    pub block_hash: BlockHash,
+    /// Position of the `InvoiceCreated` event among the block's events
+    pub event_index: u32,
+    /// ID of the new invoice
+    pub invoice_id: u64,
+    /// Invoiced client
+    pub client: AccountId,
+    /// Invoice amount
+    pub amount: Balance,
+    /// Hash of the new invoice
+    pub invoice_hash: H256,
+    /// Invoice creator
+    pub created_by: AccountId,
+}
+
+#[rpc(client, server)]
+pub trait LedgerApi<BlockHash, AccountId, Balance, Invoice, Attachment> {
+    /// Get a single invoice of a client
+    #[method(name = "ledger_getInvoice")]
+    fn get_invoice(
+        &self,
+        client: AccountId,
+        invoice_id: u64,
+        at: Option<BlockHash>,
+    ) -> RpcResult<Option<Invoice>>;
+
+    /// Get a page of a client's invoices and the cursor for the next page
+    #[method(name = "ledger_getClientInvoicesPaged")]
+    fn get_client_invoices_paged(
+        &self,
+        client: AccountId,
+        cursor: Option<PageCursor>,
+        limit: u32,
+        at: Option<BlockHash>,
+    ) -> RpcResult<(Vec<Invoice>, Option<PageCursor>)>;
+
+    /// Get the number of invoices a client currently holds
+    #[method(name = "ledger_getClientInvoiceCount")]
+    fn get_client_invoice_count(
+        &self,
+        client: AccountId,
+        at: Option<BlockHash>,
+    ) -> RpcResult<u32>;
+
+    /// Get all invoices issued by a creator
+    #[method(name = "ledger_getCreatorInvoices")]
+    fn get_creator_invoices(
+        &self,
+        creator: AccountId,
+        at: Option<BlockHash>,
+    ) -> RpcResult<Vec<Invoice>>;
+
+    /// Get invoice ID from invoice hash
+    #[method(name = "ledger_getInvoiceByHash")]
+    fn get_invoice_by_hash(
+        &self,
+        invoice_hash: H256,
+        at: Option<BlockHash>,
+    ) -> RpcResult<Option<u64>>;
+
+    /// Get the invoice carrying an external reference (e.g. "INV-2025-001")
+    #[method(name = "ledger_getInvoiceByExternalRef")]
+    fn get_invoice_by_external_ref(
+        &self,
+        external_ref: String,
+        at: Option<BlockHash>,
+    ) -> RpcResult<Option<Invoice>>;
+
+    /// Get the hash of an archived invoice
+    #[method(name = "ledger_getArchivedInvoiceHash")]
+    fn get_archived_invoice_hash(
+        &self,
+        client: AccountId,
+        invoice_id: u64,
+        at: Option<BlockHash>,
+    ) -> RpcResult<Option<H256>>;
+
+    /// Verify invoice hash matches stored data
+    #[method(name = "ledger_verifyInvoiceHash")]
+    fn verify_invoice_hash(
+        &self,
+        client: AccountId,
+        invoice_id: u64,
+        at: Option<BlockHash>,
+    ) -> RpcResult<bool>;
+
+    /// Verify the creator signature stored with an invoice against its hash
+    #[method(name = "ledger_verifyInvoiceSignature")]
+    fn verify_invoice_signature(
+        &self,
+        client: AccountId,
+        invoice_id: u64,
+        at: Option<BlockHash>,
+    ) -> RpcResult<bool>;
+
+    /// Get the total a client still owes across all of its invoices
+    #[method(name = "ledger_getOutstandingBalance")]
+    fn get_outstanding_balance(
+        &self,
+        client: AccountId,
+        at: Option<BlockHash>,
+    ) -> RpcResult<Balance>;
+
+    /// Get what a creator invoiced and what it collected, in that order
+    #[method(name = "ledger_getCreatorStats")]
+    fn get_creator_stats(
+        &self,
+        creator: AccountId,
+        at: Option<BlockHash>,
+    ) -> RpcResult<(Balance, Balance)>;
+
+    /// Get what settling an invoice costs now, late fee accrued so far included
+    #[method(name = "ledger_getAmountDue")]
+    fn get_amount_due(
+        &self,
+        client: AccountId,
+        invoice_id: u64,
+        at: Option<BlockHash>,
+    ) -> RpcResult<Option<Balance>>;
+
+    /// Check whether an account may decrypt the payload of the invoice with a hash
+    #[method(name = "ledger_hasAccess")]
+    fn has_access(
+        &self,
+        invoice_hash: H256,
+        who: AccountId,
+        at: Option<BlockHash>,
+    ) -> RpcResult<bool>;
+
+    /// Get the documents attached to an invoice, in the order they were attached
+    #[method(name = "ledger_getAttachments")]
+    fn get_attachments(
+        &self,
+        client: AccountId,
+        invoice_id: u64,
+        at: Option<BlockHash>,
+    ) -> RpcResult<Vec<Attachment>>;
+}
+
+/// A struct that implements the `LedgerApi`.
+pub struct Ledger<C, Block> {
+    client: Arc<C>,
+    _marker: std::marker::PhantomData<Block>,
+}
+
+impl<C, Block> Ledger<C, Block> {
+    /// Create new `Ledger` instance with the given reference to the client.
+    pub fn new(client: Arc<C>) -> Self {
+        Self {
+            client,
+            _marker: Default::default(),
+        }
+    }
+}
+
+#[async_trait]
+impl<C, Block, AccountId, Balance, Invoice, Attachment>
+    LedgerApiServer<<Block as BlockT>::Hash, AccountId, Balance, Invoice, Attachment>
+    for Ledger<C, Block>
+where
+    Block: BlockT,
+    C: Send + Sync + 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block>,
+    C::Api: LedgerRuntimeApi<Block, AccountId, Balance, Invoice, Attachment>,
+    AccountId: Codec,
+    Balance: Codec,
+    Invoice: Codec,
+    Attachment: Codec,
+{
+    fn get_invoice(
+        &self,
+        client: AccountId,
+        invoice_id: u64,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> RpcResult<Option<Invoice>> {
+        let api = self.client.runtime_api();
+        let at = at.unwrap_or_else(|| self.client.info().best_hash);
+
+        api.get_invoice(at, client, invoice_id)
+            .map_err(runtime_error_into_rpc_err)
+    }
+
+    fn get_client_invoices_paged(
+        &self,
+        client: AccountId,
+        cursor: Option<PageCursor>,
+        limit: u32,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> RpcResult<(Vec<Invoice>, Option<PageCursor>)> {
+        let api = self.client.runtime_api();
+        let at = at.unwrap_or_else(|| self.client.info().best_hash);
+
+        api.get_client_invoices_paged(at, client, cursor, limit)
+            .map_err(runtime_error_into_rpc_err)?
+            .map_err(invalid_cursor_into_rpc_err)
+    }
+
+    fn get_client_invoice_count(
+        &self,
+        client: AccountId,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> RpcResult<u32> {
+        let api = self.client.runtime_api();
+        let at = at.unwrap_or_else(|| self.client.info().best_hash);
+
+        api.get_client_invoice_count(at, client)
+            .map_err(runtime_error_into_rpc_err)
+    }
+
+    fn get_creator_invoices(
+        &self,
+        creator: AccountId,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> RpcResult<Vec<Invoice>> {
+        let api = self.client.runtime_api();
+        let at = at.unwrap_or_else(|| self.client.info().best_hash);
+
+        api.get_creator_invoices(at, creator)
+            .map_err(runtime_error_into_rpc_err)
+    }
+
+    fn get_invoice_by_hash(
+        &self,
+        invoice_hash: H256,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> RpcResult<Option<u64>> {
+        let api = self.client.runtime_api();
+        let at = at.unwrap_or_else(|| self.client.info().best_hash);
+
+        api.get_invoice_by_hash(at, invoice_hash.into())
+            .map_err(runtime_error_into_rpc_err)
+    }
+
+    fn get_invoice_by_external_ref(
+        &self,
+        external_ref: String,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> RpcResult<Option<Invoice>> {
+        let api = self.client.runtime_api();
+        let at = at.unwrap_or_else(|| self.client.info().best_hash);
+
+        api.get_invoice_by_external_ref(at, external_ref.into_bytes())
+            .map_err(runtime_error_into_rpc_err)
+    }
+
+    fn get_archived_invoice_hash(
+        &self,
+        client: AccountId,
+        invoice_id: u64,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> RpcResult<Option<H256>> {
+        let api = self.client.runtime_api();
+        let at = at.unwrap_or_else(|| self.client.info().best_hash);
+
+        api.get_archived_invoice_hash(at, client, invoice_id)
+            .map(|hash| hash.map(H256::from))
+            .map_err(runtime_error_into_rpc_err)
+    }
+
+    fn verify_invoice_hash(
+        &self,
+        client: AccountId,
+        invoice_id: u64,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> RpcResult<bool> {
+        let api = self.client.runtime_api();
+        let at = at.unwrap_or_else(|| self.client.info().best_hash);
+
+        api.verify_invoice_hash(at, client, invoice_id)
+            .map_err(runtime_error_into_rpc_err)
+    }
+
+    fn verify_invoice_signature(
+        &self,
+        client: AccountId,
+        invoice_id: u64,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> RpcResult<bool> {
+        let api = self.client.runtime_api();
+        let at = at.unwrap_or_else(|| self.client.info().best_hash);
+
+        api.verify_invoice_signature(at, client, invoice_id)
+            .map_err(runtime_error_into_rpc_err)
+    }
+
+    fn get_outstanding_balance(
+        &self,
+        client: AccountId,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> RpcResult<Balance> {
+        let api = self.client.runtime_api();
+        let at = at.unwrap_or_else(|| self.client.info().best_hash);
+
+        api.get_outstanding_balance(at, client)
+            .map_err(runtime_error_into_rpc_err)
+    }
+
+    fn get_creator_stats(
+        &self,
+        creator: AccountId,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> RpcResult<(Balance, Balance)> {
+        let api = self.client.runtime_api();
+        let at = at.unwrap_or_else(|| self.client.info().best_hash);
+
+        api.get_creator_stats(at, creator)
+            .map_err(runtime_error_into_rpc_err)
+    }
+
+    fn get_amount_due(
+        &self,
+        client: AccountId,
+        invoice_id: u64,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> RpcResult<Option<Balance>> {
+        let api = self.client.runtime_api();
+        let at = at.unwrap_or_else(|| self.client.info().best_hash);
+
+        api.get_amount_due(at, client, invoice_id)
+            .map_err(runtime_error_into_rpc_err)
+    }
+
+    fn has_access(
+        &self,
+        invoice_hash: H256,
+        who: AccountId,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> RpcResult<bool> {
+        let api = self.client.runtime_api();
+        let at = at.unwrap_or_else(|| self.client.info().best_hash);
+
+        api.has_access(at, invoice_hash.into(), who)
+            .map_err(runtime_error_into_rpc_err)
+    }
+
+    fn get_attachments(
+        &self,
+        client: AccountId,
+        invoice_id: u64,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> RpcResult<Vec<Attachment>> {
+        let api = self.client.runtime_api();
+        let at = at.unwrap_or_else(|| self.client.info().best_hash);
+
+        api.get_attachments(at, client, invoice_id)
+            .map_err(runtime_error_into_rpc_err)
+    }
+}
+
+#[rpc(client, server)]
+pub trait LedgerEventsApi<BlockHash, AccountId, Balance> {
+    /// Subscribe to invoices created in finalized blocks, only those of `client` if given
+    #[subscription(
+        name = "ledger_subscribeInvoices" => "ledger_invoice",
+        unsubscribe = "ledger_unsubscribeInvoices",
+        item = SubscriptionItem<InvoiceNotification<BlockHash, AccountId, Balance>>
+    )]
+    fn subscribe_invoices(&self, client: Option<AccountId>);
+}
+
+/// A struct that implements the `LedgerEventsApi`.
+pub struct LedgerEvents<C, Block> {
+    client: Arc<C>,
+    executor: Arc<dyn SpawnNamed>,
+    _marker: std::marker::PhantomData<Block>,
+}
+
+impl<C, Block> LedgerEvents<C, Block> {
+    /// Create new `LedgerEvents` instance running subscriptions on `executor`.
+    pub fn new(client: Arc<C>, executor: Arc<dyn SpawnNamed>) -> Self {
+        Self {
+            client,
+            executor,
+            _marker: Default::default(),
+        }
+    }
+}
+
+impl<C, Block, AccountId, Balance>
+    LedgerEventsApiServer<<Block as BlockT>::Hash, AccountId, Balance> for LedgerEvents<C, Block>
+where
+    Block: BlockT,
+    C: Send + Sync + 'static + ProvideRuntimeApi<Block> + BlockchainEvents<Block>,
+    C::Api: LedgerEventsRuntimeApi<Block, AccountId, Balance>,
+    AccountId: Codec + PartialEq + Serialize + DeserializeOwned + Send + Sync + 'static,
+    Balance: Codec + Serialize + Send + Sync + 'static,
+{
+    fn subscribe_invoices(
+        &self,
+        sink: SubscriptionSink,
+        client: Option<AccountId>,
+    ) -> SubscriptionResult {
+        let (sender, receiver) = subscription::lag_channel(SUBSCRIPTION_BUFFER);
+        let finalized = self.client.finality_notification_stream().map(|n| n.hash);
+        let runtime = self.client.clone();
+        let fetch = move |hash| {
+            let created = runtime.runtime_api().created_invoices(hash)?;
+            Ok::<_, sp_api::ApiError>(invoice_notifications(hash, created, client.as_ref()))
+        };
+
+        self.executor.spawn(
+            "ledger-invoice-events",
+            Some("rpc"),
+            subscription::relay(finalized, fetch, sender).boxed(),
+        );
+        self.executor.spawn(
+            "ledger-invoice-subscription",
+            Some("rpc"),
+            async move {
+                sink.pipe_from_stream(receiver).await;
+            }
+            .boxed(),
+        );
+
+        Ok(())
+    }
+}
+
+/// Notifications for the invoices created in block `hash`, only those of `client` if given
+fn invoice_notifications<Hash: Copy, AccountId: PartialEq, Balance>(
+    hash: Hash,
+    created: Vec<CreatedInvoice<AccountId, Balance>>,
+    client: Option<&AccountId>,
+) -> Vec<InvoiceNotification<Hash, AccountId, Balance>> {
+    created
+        .into_iter()
+        .filter(|invoice| client.map_or(true, |client| client == &invoice.client))
+        .map(|invoice| InvoiceNotification {
+            block_hash: hash,
+            event_index: invoice.event_index,
+            invoice_id: invoice.invoice_id,
+            client: invoice.client,
+            amount: invoice.amount,
+            invoice_hash: invoice.invoice_hash.into(),
+            created_by: invoice.created_by,
+        })
+        .collect()
+}
+
+/// Converts a runtime trap into an RPC error.
+fn runtime_error_into_rpc_err(err: impl std::fmt::Debug) -> jsonrpsee::core::Error {
+    CallError::Custom(ErrorObject::owned(
+        RUNTIME_ERROR,
+        "Runtime error",
+        Some(format!("{:?}", err)),
+    ))
+    .into()
+}
+
+/// Converts a rejected page cursor into an invalid params error.
+fn invalid_cursor_into_rpc_err(_: InvalidCursor) -> jsonrpsee::core::Error {
+    CallError::Custom(ErrorObject::owned(INVALID_PARAMS_CODE, "Invalid cursor", None::<()>))
+        .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn created(event_index: u32, invoice_id: u64, client: u64) -> CreatedInvoice<u64, u128> {
+        CreatedInvoice {
+            event_index,
+            invoice_id,
+            client,
+            amount: 100,
+            invoice_hash: [invoice_id as u8; 32],
+            created_by: 1,
+        }
+    }
+
+    #[test]
+    fn notifications_keep_only_the_requested_client() {
+        let block = H256::repeat_byte(7);
+        let events = vec![created(1, 0, 2), created(4, 1, 3), created(6, 2, 2)];
+
+        let all = invoice_notifications(block, events.clone(), None);
+        assert_eq!(all.iter().map(|n| n.invoice_id).collect::<Vec<_>>(), vec![0, 1, 2]);
+
+        let for_client = invoice_notifications(block, events, Some(&2));
+        assert_eq!(for_client.len(), 2);
+        assert_eq!(
+            for_client[1],
+            InvoiceNotification {
+                block_hash: block,
+                event_index: 6,
+                invoice_id: 2,
+                client: 2,
+                amount: 100,
+                invoice_hash: H256::repeat_byte(2),
+                created_by: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn finalized_blocks_are_relayed_with_lag_notices() {
+        use futures::{executor::LocalPool, task::LocalSpawnExt};
+
+        // Stand-in for the client's finality notifications and runtime API
+        let (finalize, finalized) = futures::channel::mpsc::unbounded::<H256>();
+        let fetch = |hash: H256| {
+            let events = (0..hash[0]).map(|i| created(i.into(), i.into(), 2 + u64::from(i % 2)));
+            Ok::<_, ()>(invoice_notifications(hash, events.collect(), Some(&2)))
+        };
+        let (sender, mut receiver) = subscription::lag_channel(2);
+        let mut pool = LocalPool::new();
+        pool.spawner().spawn_local(subscription::relay(finalized, fetch, sender)).unwrap();
+
+        let mut received = |pool: &mut LocalPool, events: u8| -> Vec<_> {
+            finalize.unbounded_send(H256::repeat_byte(events)).unwrap();
+            pool.run_until_stalled();
+            std::iter::from_fn(|| receiver.try_next().ok().flatten())
+                .map(|item| match item {
+                    SubscriptionItem::Event(n) => Some(n.invoice_id),
+                    SubscriptionItem::Lagged { missed } => {
+                        assert_eq!(missed, 1);
+                        None
+                    }
+                })
+                .collect()
+        };
+
+        // Invoices 0 and 2 of client 2
+        assert_eq!(received(&mut pool, 4), vec![Some(0), Some(2)]);
+        // Invoices 0, 2 and 4 of client 2, with room for two
+        assert_eq!(received(&mut pool, 5), vec![Some(0), Some(2)]);
+        assert_eq!(received(&mut pool, 1), vec![None, Some(0)]);
+    }
+}
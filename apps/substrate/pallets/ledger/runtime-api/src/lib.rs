@@ -0,0 +1,77 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Runtime API definition for the Ledger pallet
+
+use codec::Codec;
+use sp_std::vec::Vec;
+use tidygen_primitives::pagination::{InvalidCursor, PageCursor};
+
+pub use pallet_ledger::CreatedInvoice;
+
+sp_api::decl_runtime_apis! {
+    /// The API to interact with Ledger pallet
+    pub trait LedgerApi<AccountId, Balance, Invoice, Attachment>
+    where
+        AccountId: Codec,
+        Balance: Codec,
+        Invoice: Codec,
+        Attachment: Codec,
+    {
+        /// Get a single invoice of a client
+        fn get_invoice(client: AccountId, invoice_id: u64) -> Option<Invoice>;
+
+        /// Get a page of a client's invoices starting at `cursor` (the first page for
+        /// `None`), together with the cursor for the next page
+        fn get_client_invoices_paged(
+            client: AccountId,
+            cursor: Option<PageCursor>,
+            limit: u32,
+        ) -> Result<(Vec<Invoice>, Option<PageCursor>), InvalidCursor>;
+
+        /// Get the number of invoices a client currently holds
+        fn get_client_invoice_count(client: AccountId) -> u32;
+
+        /// Get all invoices issued by a creator
+        fn get_creator_invoices(creator: AccountId) -> Vec<Invoice>;
+
+        /// Get invoice ID from invoice hash
+        fn get_invoice_by_hash(invoice_hash: [u8; 32]) -> Option<u64>;
+
+        /// Get the invoice carrying an external reference
+        fn get_invoice_by_external_ref(external_ref: Vec<u8>) -> Option<Invoice>;
+
+        /// Get the hash of an archived invoice
+        fn get_archived_invoice_hash(client: AccountId, invoice_id: u64) -> Option<[u8; 32]>;
+
+        /// Verify invoice hash matches stored data
+        fn verify_invoice_hash(client: AccountId, invoice_id: u64) -> bool;
+
+        /// Verify the creator signature stored with an invoice against its hash
+        fn verify_invoice_signature(client: AccountId, invoice_id: u64) -> bool;
+
+        /// Get the total a client still owes across all of its invoices
+        fn get_outstanding_balance(client: AccountId) -> Balance;
+
+        /// Get what a creator invoiced and what it collected, in that order
+        fn get_creator_stats(creator: AccountId) -> (Balance, Balance);
+
+        /// Get what settling an invoice costs now, late fee accrued so far included
+        fn get_amount_due(client: AccountId, invoice_id: u64) -> Option<Balance>;
+
+        /// Check whether an account may decrypt the payload of the invoice with a hash
+        fn has_access(invoice_hash: [u8; 32], who: AccountId) -> bool;
+
+        /// Get the documents attached to an invoice, in the order they were attached
+        fn get_attachments(client: AccountId, invoice_id: u64) -> Vec<Attachment>;
+    }
+
+    /// Ledger events of a block, read back for RPC subscriptions
+    pub trait LedgerEventsApi<AccountId, Balance>
+    where
+        AccountId: Codec,
+        Balance: Codec,
+    {
+        /// Get the `InvoiceCreated` events deposited in the block
+        fn created_invoices() -> Vec<CreatedInvoice<AccountId, Balance>>;
+    }
+}
@@ -0,0 +1,270 @@
+//! Benchmarks for the ledger calls whose weight depends on their input.
+//!
+//! The metadata length `m` ranges from empty to `MaxMetadataLength`; run with `--steps 3` to
+//! sample it at 0, half and full length (0, 512 and 1024 with the default limit). Line items
+//! `l` range up to `MaxLineItems`, each with a description of the maximum length, and the
+//! operator keys `k` up to `MaxOperatorKeys`. The signature checks verify signatures that
+//! match no key, which costs what a match does without needing a keystore to sign; the
+//! creator signature check assumes `CreatorKeys` gives the caller a key, such as
+//! `AccountIdAsPublicKey`. The
+//! invoices `n` of a client and the access grants `g` of an invoice range up to
+//! `MaxInvoicesPerClient` and `MaxGrantsPerInvoice`, and the archived invoices `a` up to
+//! `MaxArchivedPerCall`; `archive_invoices` places its grants on one archived invoice, as
+//! removing a grant costs the same whichever invoice it belongs to. Clients
+//! must count as identified, so the benchmarks assume a `DidProvider` that accepts a fresh
+//! account, such as `()`.
+
+use super::*;
+use frame_benchmarking::v2::*;
+use frame_support::{
+    traits::{Currency, Get},
+    BoundedVec,
+};
+use frame_system::RawOrigin;
+use sp_core::sr25519;
+use sp_runtime::traits::Bounded;
+use sp_std::{vec, vec::Vec};
+
+/// Fund and authorize a creator, and return it with a client account
+fn setup<T: Config>() -> (T::AccountId, T::AccountId) {
+    let creator: T::AccountId = whitelisted_caller();
+    let client: T::AccountId = account("client", 0, 0);
+    T::Currency::make_free_balance_be(&creator, BalanceOf::<T>::max_value() / 2u32.into());
+    AuthorizedCreators::<T>::insert(&creator, ());
+    (creator, client)
+}
+
+/// Create a plain invoice from `creator` to `client`
+fn create<T: Config>(creator: &T::AccountId, client: &T::AccountId) -> DispatchResult {
+    Pallet::<T>::create_invoice(
+        RawOrigin::Signed(creator.clone()).into(),
+        client.clone(),
+        1_000u32.into(),
+        0u32.into(),
+        0u32.into(),
+        vec![b'y'; 32],
+        None,
+        None,
+        vec![],
+        None,
+    )
+    .map(|_| ())
+    .map_err(|e| e.error)
+}
+
+#[benchmarks]
+mod benchmarks {
+    use super::*;
+
+    #[benchmark]
+    fn create_invoice(
+        m: Linear<0, { T::MaxMetadataLength::get() }>,
+        l: Linear<0, { T::MaxLineItems::get() }>,
+    ) -> Result<(), BenchmarkError> {
+        let (creator, client) = setup::<T>();
+        let metadata = vec![b'x'; m as usize];
+        let description = vec![b'd'; T::MaxLineItemDescriptionLength::get() as usize];
+        let line_items = (0..l)
+            .map(|_| LineItem::<T> {
+                description: description.clone().try_into().expect("at the maximum length"),
+                quantity: 1,
+                unit_price: 1_000u32.into(),
+            })
+            .collect::<Vec<_>>();
+        // Line items must add up to the amount
+        let amount = 1_000u32.saturating_mul(l.max(1));
+
+        #[extrinsic_call]
+        _(
+            RawOrigin::Signed(creator),
+            client.clone(),
+            amount.into(),
+            // Tax and discount are hashed only when set
+            100u32.into(),
+            100u32.into(),
+            metadata,
+            None,
+            None,
+            line_items,
+            None,
+        );
+
+        assert_eq!(ClientInvoiceCount::<T>::get(&client), 1);
+        Ok(())
+    }
+
+    #[benchmark]
+    fn verify_creator_signature() {
+        let (creator, _) = setup::<T>();
+        // Shaped as an sr25519 signature, so that check runs in full before the ed25519 one
+        let mut signature = [0u8; 64];
+        signature[63] = 0x80;
+
+        #[block]
+        {
+            assert!(!Pallet::<T>::verify_creator_signature(&creator, &[7; 32], &signature));
+        }
+    }
+
+    #[benchmark]
+    fn verify_operator_signature(k: Linear<1, { T::MaxOperatorKeys::get() }>) {
+        let keys = (0..k).map(|i| sr25519::Public::from_raw([i as u8; 32])).collect::<Vec<_>>();
+        OperatorKeys::<T>::put(BoundedVec::truncate_from(keys));
+        let mut signature = [0u8; 64];
+        signature[63] = 0x80;
+
+        #[block]
+        {
+            assert!(Pallet::<T>::verify_operator_signature(b"payload", &signature).is_none());
+        }
+    }
+
+    #[benchmark]
+    fn pay_invoice() -> Result<(), BenchmarkError> {
+        let (creator, client) = setup::<T>();
+        T::Currency::make_free_balance_be(&client, BalanceOf::<T>::max_value() / 2u32.into());
+        let invoice_id = InvoiceCount::<T>::get();
+        create::<T>(&creator, &client)?;
+        if T::RequireAcceptance::get() {
+            Pallet::<T>::accept_invoice(
+                RawOrigin::Signed(client.clone()).into(),
+                creator.clone(),
+                invoice_id,
+            )?;
+        }
+
+        #[extrinsic_call]
+        _(RawOrigin::Signed(client.clone()), creator, invoice_id);
+
+        let invoice = Invoices::<T>::get(&client, invoice_id).ok_or("invoice is gone")?;
+        assert_eq!(invoice.status, InvoiceStatus::Paid);
+        Ok(())
+    }
+
+    #[benchmark]
+    fn record_payment() -> Result<(), BenchmarkError> {
+        let (creator, client) = setup::<T>();
+        T::Currency::make_free_balance_be(&client, BalanceOf::<T>::max_value() / 2u32.into());
+        let invoice_id = InvoiceCount::<T>::get();
+        create::<T>(&creator, &client)?;
+        if T::RequireAcceptance::get() {
+            Pallet::<T>::accept_invoice(
+                RawOrigin::Signed(client.clone()).into(),
+                creator.clone(),
+                invoice_id,
+            )?;
+        }
+        // Paying the rest settles the invoice, the most expensive installment
+        Pallet::<T>::record_payment(
+            RawOrigin::Signed(client.clone()).into(),
+            client.clone(),
+            invoice_id,
+            400u32.into(),
+        )?;
+
+        #[extrinsic_call]
+        _(RawOrigin::Signed(client.clone()), client.clone(), invoice_id, 600u32.into());
+
+        let invoice = Invoices::<T>::get(&client, invoice_id).ok_or("invoice is gone")?;
+        assert_eq!(invoice.status, InvoiceStatus::Paid);
+        Ok(())
+    }
+
+    #[benchmark]
+    fn update_invoice_metadata(
+        m: Linear<0, { T::MaxMetadataLength::get() }>,
+    ) -> Result<(), BenchmarkError> {
+        let (creator, client) = setup::<T>();
+        let invoice_id = InvoiceCount::<T>::get();
+        // The replaced metadata is as long as it gets, so reading the invoice is the worst case
+        let max = T::MaxMetadataLength::get() as usize;
+        Pallet::<T>::create_invoice(
+            RawOrigin::Signed(creator.clone()).into(),
+            client.clone(),
+            1_000u32.into(),
+            0u32.into(),
+            0u32.into(),
+            vec![b'y'; max],
+            None,
+            None,
+            vec![],
+            None,
+        )?;
+        let metadata = vec![b'x'; m as usize];
+
+        #[extrinsic_call]
+        _(RawOrigin::Signed(creator), client.clone(), invoice_id, metadata);
+
+        let invoice = Invoices::<T>::get(&client, invoice_id).ok_or("invoice is gone")?;
+        assert_eq!(invoice.metadata.len(), m as usize);
+        Ok(())
+    }
+
+    #[benchmark]
+    fn purge_invoice(
+        n: Linear<1, { T::MaxInvoicesPerClient::get() }>,
+        g: Linear<0, { T::MaxGrantsPerInvoice::get() }>,
+    ) -> Result<(), BenchmarkError> {
+        let (creator, client) = setup::<T>();
+        let invoice_id = InvoiceCount::<T>::get();
+        for _ in 0..n {
+            create::<T>(&creator, &client)?;
+        }
+        let invoice_hash =
+            Invoices::<T>::get(&client, invoice_id).ok_or("invoice is gone")?.invoice_hash;
+        for i in 0..g {
+            Pallet::<T>::grant_access(
+                RawOrigin::Signed(creator.clone()).into(),
+                invoice_hash,
+                account("grantee", i, 0),
+                [0; 32],
+            )?;
+        }
+
+        #[extrinsic_call]
+        _(RawOrigin::Root, client.clone(), invoice_id, false);
+
+        assert_eq!(ClientInvoiceCount::<T>::get(&client), n - 1);
+        assert_eq!(AccessGrantCount::<T>::get(invoice_hash), 0);
+        Ok(())
+    }
+
+    #[benchmark]
+    fn archive_invoices(
+        n: Linear<{ T::MaxArchivedPerCall::get() }, { T::MaxInvoicesPerClient::get() }>,
+        a: Linear<1, { T::MaxArchivedPerCall::get() }>,
+        g: Linear<0, { T::MaxGrantsPerInvoice::get() }>,
+    ) -> Result<(), BenchmarkError> {
+        let (creator, client) = setup::<T>();
+        let first = InvoiceCount::<T>::get();
+        for _ in 0..n {
+            create::<T>(&creator, &client)?;
+        }
+        let invoice_hash =
+            Invoices::<T>::get(&client, first).ok_or("invoice is gone")?.invoice_hash;
+        for i in 0..g {
+            Pallet::<T>::grant_access(
+                RawOrigin::Signed(creator.clone()).into(),
+                invoice_hash,
+                account("grantee", i, 0),
+                [0; 32],
+            )?;
+        }
+        for invoice_id in first..first + a as u64 {
+            Pallet::<T>::cancel_invoice(
+                RawOrigin::Signed(creator.clone()).into(),
+                client.clone(),
+                invoice_id,
+            )?;
+        }
+
+        #[extrinsic_call]
+        _(RawOrigin::Root, client.clone(), first + n as u64);
+
+        assert_eq!(ClientInvoiceCount::<T>::get(&client), n - a);
+        assert_eq!(AccessGrantCount::<T>::get(invoice_hash), 0);
+        Ok(())
+    }
+
+    impl_benchmark_test_suite!(Pallet, crate::mock::new_test_ext(), crate::mock::Test);
+}
@@ -0,0 +1,124 @@
+//! Pinned call indices and storage prefixes of pallet-ledger.
+//!
+//! Downstream tooling decodes extrinsics and storage by these values. See
+//! `tidygen_primitives::compat` for how an intended change to them is made.
+
+use crate::{mock::*, Call, Pallet};
+use codec::Encode;
+use frame_support::traits::{GetCallIndex, GetCallName, StorageInfoTrait};
+use tidygen_primitives::compat::{assert_call_indices, assert_storage_prefixes};
+
+/// Version of the tables below, bumped when an existing entry moves or goes away
+const COMPAT_VERSION: u32 = 1;
+
+/// Every dispatchable with its call index. Index 1 belonged to the removed `get_invoices`
+/// and must not be reused.
+const CALL_INDICES: &[(&str, u8)] = &[
+    ("create_invoice", 0),
+    ("pay_invoice", 2),
+    ("cancel_invoice", 3),
+    ("set_text_normalization", 4),
+    ("create_signed_invoice", 5),
+    ("add_operator_key", 6),
+    ("remove_operator_key", 7),
+    ("record_payment", 8),
+    ("archive_invoices", 9),
+    ("update_invoice_metadata", 10),
+    ("create_credit_note", 11),
+    ("verify_invoice", 12),
+    ("create_invoice_with_ref", 13),
+    ("request_cancellation", 14),
+    ("approve_cancellation", 15),
+    ("reject_cancellation", 16),
+    ("finalize_cancellation", 17),
+    ("dispute_invoice", 18),
+    ("resolve_dispute", 19),
+    ("batch_create_invoices", 20),
+    ("authorize_creator", 21),
+    ("revoke_creator", 22),
+    ("grant_access", 23),
+    ("revoke_access", 24),
+    ("purge_invoice", 25),
+    ("challenge_invoice", 26),
+    ("concede_challenge", 27),
+    ("resolve_challenge", 28),
+    ("confirm_offchain_link", 29),
+    ("attach_document", 30),
+    ("accept_invoice", 31),
+    ("reject_invoice", 32),
+    ("claim_abandoned_invoice", 33),
+    ("assign_invoice", 34),
+];
+
+/// Every storage item with the `twox_128` of its name
+const STORAGE_PREFIXES: &[(&str, &str)] = &[
+    ("Invoices", "e26fec0801f08df78ff62bf3b56fc98b"),
+    ("ClientInvoiceCount", "622a996736c59c76e12b2e31a909e841"),
+    ("ClientInvoiceIds", "9a7fce3f60b52378ead4ebd5f5f3af76"),
+    ("InvoicesByCreator", "3d0356c82b90902a86d92382ec74abd1"),
+    ("ArchivedInvoiceHashes", "1d9215adc87243365f430e277e6bace4"),
+    ("InvoiceCount", "0ef8df4800bd3769bb4865d96d5c80d2"),
+    ("InvoiceByHash", "a03267d50a429825f7e8990f080394bf"),
+    ("InvoiceByExternalRef", "c53eefc7d2980721850c35520efbe193"),
+    ("CreditNotes", "e9ba075cf947eee3e206c8643244a2b7"),
+    ("CreditNoteCount", "8613fa8b2aeb86f0476d1ac07f1f8ea4"),
+    ("CreditNoteByHash", "39b9c0a0bf64dae430fed3ba1ff38619"),
+    ("OutstandingBalance", "662877f150788c0743ed9583b50fb9e3"),
+    ("InvoiceCredits", "bdd1566a871f0d1720195af71394cdb0"),
+    ("PendingCancellations", "1601fafe0258ae150884cb9e073a3537"),
+    ("CancellationBlockedUntil", "b5de9c7c5acb7fbdef21b4d1d233e2cf"),
+    ("InvoiceDisputes", "dbc520f0b06ce1c9f739cbb5386da527"),
+    ("InvoiceChallenges", "a86802490bb469b9ab068b3cbfee1308"),
+    ("ChallengeTimeouts", "3462d44433da3eeecb57d6e0e1c36383"),
+    ("DueInvoices", "1ba6b1f7cbb2370f137726d55d826147"),
+    ("OverdueCursor", "66d114090524cc6b11c6ad3c75adea4c"),
+    ("ExpiringInvoices", "5fdc6601125ba35b130822cc079b74c5"),
+    ("ExpiryCursor", "e0c4cc73639ee2de8206ceffe30ff527"),
+    ("NormalizeText", "d7ae493d7cd06cb7a5132c426cc728c0"),
+    ("OperatorKeys", "46da62bc3cbca7bd3a9c3b1472012513"),
+    ("InvoiceOperator", "5b1b1ba79cf560f28ef4640ebe157ee7"),
+    ("InvoiceSignatures", "90112d3de344fad2a31fa5a7a8c47b8f"),
+    ("UsedOperatorPayloads", "ce4d8a25bda78169c089710d03c2f464"),
+    ("AuthorizedCreators", "40f4a406899e19bebb25803841b18468"),
+    ("LastBatchOutcome", "16cfc87158735655605b9f9003b1f661"),
+    ("InvoiceDeposits", "9febc1860d064d1251b94910e75f63ab"),
+    ("AccessGrants", "850c16277b42da251353152558522c9b"),
+    ("OffchainLinks", "155eba8e1ab43da107cb10abec33583c"),
+    ("InvoiceAttachments", "2eb5ae43015eb845838d509623e24efe"),
+    ("NewInvoices", "166575c78f2a4f681ad33fb3eba4230b"),
+    ("PaymentsThisBlock", "0d39fe2388b1d46001a6fda10acd0852"),
+    ("InvoiceAssignments", "79ed6d24b231fb77f74d8f63f2e64cb9"),
+    ("CreatorStats", "de1fcde89ca324a3a8bf813c00b584dc"),
+    ("AccessGrantCount", "5f22c0a993c832a63f2839c7b4ce567b"),
+];
+
+#[test]
+fn call_indices_are_stable() {
+    assert_call_indices(
+        "pallet-ledger",
+        COMPAT_VERSION,
+        CALL_INDICES,
+        Call::<Test>::get_call_names(),
+        Call::<Test>::get_call_indices(),
+    );
+}
+
+#[test]
+fn encoded_calls_lead_with_the_pinned_index() {
+    assert_eq!(Call::<Test>::set_text_normalization { enabled: true }.encode(), vec![4, 1]);
+    assert_eq!(Call::<Test>::revoke_creator { creator: 9 }.encode()[0], 22);
+
+    // In a runtime call the pallet index comes first, Ledger is the third pallet of the mock
+    let call = RuntimeCall::Ledger(Call::approve_cancellation { invoice_id: 7 });
+    assert_eq!(call.encode(), [&[2u8, 15][..], &7u64.encode()].concat());
+}
+
+#[test]
+fn storage_prefixes_are_stable() {
+    let actual: Vec<_> = Pallet::<Test>::storage_info()
+        .into_iter()
+        .map(|info| (info.storage_name, info.prefix))
+        .collect();
+
+    assert_storage_prefixes("pallet-ledger", COMPAT_VERSION, STORAGE_PREFIXES, &actual);
+}
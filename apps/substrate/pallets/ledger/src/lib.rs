@@ -8,8 +8,9 @@
 //!
 //! The ERP Ledger pallet provides functionality for:
 //! - Creating invoices with SHA256 hashing for Django record linking
-//! - Storing invoices per client (AccountId)
-//! - Retrieving invoice history for clients
+//! - Storing invoices per client (AccountId), indexed by creator for vendor reporting
+//! - Retrieving invoice history for clients, paginated by invoice ID through the
+//!   `pallet-ledger-runtime-api` runtime API and the `pallet-ledger-rpc` RPC
 //! - Emitting events for invoice operations
 //!
 //! ## Interface
@@ -17,79 +18,943 @@
 //! ### Dispatchable Functions
 //!
 //! * `create_invoice` - Create a new invoice with automatic SHA256 hashing
-//! * `get_invoices` - Retrieve all invoices for a specific client
+//! * `pay_invoice` - Settle an invoice by transferring its amount to the beneficiary
+//! * `cancel_invoice` - Void an unpaid invoice (creator only)
+//! * `set_text_normalization` - Toggle UTF-8 validation and line ending normalization (root only)
+//! * `create_signed_invoice` - Create an invoice authorized by an operator key signature
+//! * `add_operator_key` - Register an operator signing key (admin only)
+//! * `remove_operator_key` - Retire an operator signing key (admin only)
+//! * `record_payment` - Pay part of an invoice; settles it once fully paid
+//! * `archive_invoices` - Prune settled, cancelled, expired or rejected invoices, keeping hashes
+//! * `update_invoice_metadata` - Fix the metadata of an unpaid invoice and re-hash it
+//! * `create_credit_note` - Credit part of an unpaid invoice without modifying it
+//! * `verify_invoice` - Record an attestation that an invoice matches an expected hash
+//! * `create_invoice_with_ref` - Create an invoice carrying a unique external reference
+//! * `request_cancellation` - Ask the client to agree to cancelling an invoice (creator only)
+//! * `approve_cancellation` - Agree to a requested cancellation (client only)
+//! * `reject_cancellation` - Object to a requested cancellation (client only)
+//! * `finalize_cancellation` - Cancel an invoice whose client let the request time out
+//! * `dispute_invoice` - Put an unpaid invoice under dispute (client only)
+//! * `resolve_dispute` - Reinstate or cancel a disputed invoice (`DisputeResolutionOrigin`)
+//! * `batch_create_invoices` - Create up to `MaxBatchSize` invoices at once, skipping the
+//!   entries that fail
+//! * `authorize_creator` - Allow an account to create invoices (admin only)
+//! * `revoke_creator` - Withdraw an account's permission to create invoices (admin only)
+//! * `grant_access` - Let an account decrypt an invoice's off-chain payload (creator or client)
+//! * `revoke_access` - Withdraw an access grant (creator or client)
+//! * `purge_invoice` - Delete an invoice and every index pointing at it (root only)
+//! * `challenge_invoice` - Challenge an unpaid invoice as fraudulent, posting `ChallengeBond`
+//! * `concede_challenge` - Accept a challenge, cancelling the invoice (creator only)
+//! * `resolve_challenge` - Uphold or dismiss a challenge (`ChallengeJudgeOrigin`)
+//! * `confirm_offchain_link` - Record that the ERP linked an invoice to its record (unsigned)
+//! * `attach_document` - Anchor the hash of a supporting document (creator or client)
+//! * `accept_invoice` - Acknowledge an invoice as correct (client only)
+//! * `reject_invoice` - Refuse an invoice with a reason, closing it (client only)
+//! * `claim_abandoned_invoice` - Cancel an abandoned invoice for its deposit (client only)
+//! * `assign_invoice` - Pass an invoice's right to payment on (beneficiary only)
 //!
 //! ### Events
 //!
 //! * `InvoiceCreated` - Emitted when a new invoice is created
-//! * `InvoiceRetrieved` - Emitted when invoices are retrieved
+//! * `InvoiceBatchCreated` - Emitted once per `batch_create_invoices` with the ID range
+//! * `InvoiceBatchItemSkipped` - Emitted for every `batch_create_invoices` entry that failed
+//! * `BatchCompleted` - Emitted last by `batch_create_invoices` with its `BatchOutcome`
+//! * `InvoicePaid` - Emitted when an invoice is settled
+//! * `PartialPaymentRecorded` - Emitted for every installment paid through `record_payment`
+//! * `InvoicesArchived` - Emitted when invoices are pruned into `ArchivedInvoiceHashes`
+//! * `InvoicePurged` - Emitted when root deletes an invoice outright
+//! * `InvoiceRehashed` - Emitted when metadata changes replace an invoice hash
+//! * `CreditNoteCreated` - Emitted when a credit note is issued against an invoice
+//! * `InvoiceVerified` - Emitted with the outcome of every `verify_invoice` call
+//! * `InvoiceHashStale` - Emitted when a stored invoice hash no longer matches its fields
+//! * `InvoiceCancelled` - Emitted when an invoice is cancelled
+//! * `CancellationRequested` / `CancellationApproved` / `CancellationRejected` /
+//!   `CancellationTimedOut` - Emitted for each step of a cancellation by consent
+//! * `InvoiceDisputed` / `DisputeResolved` - Emitted when a dispute is opened or resolved
+//! * `InvoiceChallenged` / `ChallengeClosed` - Emitted when a challenge is opened or closed
+//! * `ChallengeBondReleased` / `ChallengeBondSlashed` / `InvoiceDepositAwarded` - Emitted
+//!   when a closed challenge settles the bond and the creation deposit
+//! * `ChallengeTimeoutFailed` - Emitted when a challenge could not be closed at its timeout
+//! * `InvoiceOverdue` - Emitted when an unpaid invoice passes its due block
+//! * `InvoiceExpired` - Emitted when an unpaid invoice reaches its expiry block
+//! * `TextNormalizationSet` - Emitted when text normalization is toggled
+//! * `InvoiceAuthorized` - Emitted when an operator-signed invoice is accepted
+//! * `OperatorKeyAdded` / `OperatorKeyRemoved` - Emitted when the operator key set changes
+//! * `CreatorAuthorized` / `CreatorRevoked` - Emitted when the authorized creator set changes
+//! * `AccessGranted` / `AccessRevoked` - Emitted when an invoice access grant changes
+//! * `OffchainLinkConfirmed` - Emitted when the ERP acknowledged an invoice
+//! * `DocumentAttached` - Emitted when a document hash is attached to an invoice
+//! * `InvoiceAccepted` / `InvoiceRejected` - Emitted when the client answers an invoice
+//! * `LateFeeAccrued` - Emitted when a payment charges the late fee accrued on an invoice
+//! * `InvoiceAbandoned` - Emitted when the client claims an invoice its creator abandoned
+//! * `InvoiceAssigned` - Emitted when an invoice is assigned to a new beneficiary
+//! * `PaymentsDigest` - Emitted once at the end of a block with payments, summarizing them
+//!
+//! ### Operator Signatures
+//!
+//! The Django backend signs invoice payloads with an sr25519 operator key so that a
+//! compromised submitter account alone cannot forge invoices. The signed preimage is the
+//! SCALE encoding of `(OPERATOR_PAYLOAD_TAG, client, amount, metadata)` with the metadata as
+//! submitted (before normalization); see `Pallet::operator_payload`. Each payload is accepted
+//! once. The authorizing key is stored per invoice, so retiring a key does not affect invoices
+//! it already signed.
+//!
+//! Audited reads of invoices are recorded through `pallet-access-log`.
+//!
+//! ### Creator Signatures
+//!
+//! For non-repudiation, `create_invoice` optionally takes the creator's own sr25519 or
+//! ed25519 signature over the invoice hash. The hash covers the invoice ID and creation block,
+//! so the backend predicts both (the next `InvoiceCount` and the inclusion block) before
+//! signing. The signature is checked against the key `Config::CreatorKeys` gives for the
+//! caller, and the whole call fails with `InvalidSignature` if it does not verify. Accepted
+//! signatures are kept in `InvoiceSignatures`; `verify_invoice_signature`, also exposed by
+//! the runtime API, checks them against the invoice's current hash, so it turns `false` once
+//! `update_invoice_metadata` re-hashes the invoice.
+//!
+//! ### Authorized Creators
+//!
+//! Unless `OpenCreation` is enabled, only accounts in `AuthorizedCreators` may create
+//! invoices through `create_invoice`, `create_invoice_with_ref` and `batch_create_invoices`.
+//! This keeps arbitrary accounts from filling a client's invoice list up to
+//! `MaxInvoicesPerClient`. `AdminOrigin` manages the set. `create_signed_invoice` is
+//! authorized by its operator signature instead, and genesis invoices are not checked.
+//!
+//! ### Text Handling
+//!
+//! When `NormalizeText` is enabled, invoice metadata must be valid UTF-8 and its line
+//! endings are normalized to LF before hashing and storage (see `tidygen_primitives::text`),
+//! so CRLF and LF submissions of the same document produce identical hashes. New chains
+//! enable it through the genesis config; existing chains keep it off until root opts in,
+//! which avoids hash drift for already anchored invoices. The DAO can also toggle it with a
+//! `ParameterChange::LedgerTextNormalization` proposal, which the pallet applies as its
+//! `ParameterHandler`.
+//!
+//! ### Genesis
+//!
+//! The genesis config can seed open invoices when a chain is launched for an existing
+//! customer. They are created in order with IDs from 0 and block 0 as timestamp, so their
+//! hashes can be reproduced off-chain. Invalid entries (e.g. metadata longer than
+//! `MaxMetadataLength`, or a client without an active DID) abort the genesis build.
+//!
+//! ### Client Identity
+//!
+//! Every invoice creation path checks its client against `Config::DidProvider` and fails
+//! with `ClientHasNoActiveDid` unless the client has an active DID. Runtimes with
+//! `pallet-did` set it to that pallet, so revoked and suspended DIDs cannot be invoiced;
+//! `()` accepts every client. Existing invoices are not affected when a DID is revoked.
+//!
+//! ### Invoice Hash
+//!
+//! `Invoice::calculate_hash` hashes the invoice ID, client, amount, metadata, timestamp and
+//! creator, each prefixed with its byte length as a little-endian `u32` (see
+//! `length_prefixed`), followed by the description, quantity and unit price of every line
+//! item. The prefixes keep the preimage unambiguous, and `create_invoice`
+//! rejects a hash that is already mapped in `InvoiceByHash` instead of overwriting it.
+//!
+//! The preimage fields, in order, are:
+//!
+//! 1. `id` as a little-endian `u64`
+//! 2. `client`, SCALE encoded
+//! 3. `amount`, SCALE encoded (a little-endian `u128` for `u128` balances)
+//! 4. `metadata` as stored, i.e. after text normalization
+//! 5. `timestamp` (the creation block), SCALE encoded
+//! 6. `created_by`, SCALE encoded (the raw 32 bytes for an `AccountId32`)
+//! 7. for every line item: `description`, `quantity` as a little-endian `u32` and
+//!    `unit_price` SCALE encoded
+//! 8. if the invoice has a tax or a discount: `tax` and `discount`, SCALE encoded
+//!
+//! Covering the creator means two invoices that only differ by who created them hash
+//! differently, and nobody can claim a hash another account produced. Storage version 7
+//! added it and re-hashed every stored invoice; creator signatures made before then were
+//! over the old hash and no longer verify.
+//!
+//! The hash function is SHA256 or BLAKE2b-256, chosen for new invoices by
+//! `Config::InvoiceHashAlgorithm`. Each invoice records its own `hash_algorithm`, so
+//! verification and metadata re-hashing keep working after an upgrade that changes the
+//! setting. Invoices stored before storage version 6 are SHA256.
+//!
+//! ### Line Items
+//!
+//! `create_invoice` optionally takes up to `MaxLineItems` line items of (description,
+//! quantity, unit price). When given, their totals must add up exactly to the invoice amount
+//! (`LineItemsMismatch` otherwise), and they are part of the invoice hash, in order. Invoices
+//! without line items hash as before, so existing hashes stay valid.
+//!
+//! ### Tax and Discount
+//!
+//! `amount` is the invoice subtotal; VAT and discounts are separate `tax` and `discount`
+//! fields instead of being folded into it or the metadata. A discount may not exceed the
+//! amount (`DiscountExceedsAmount`). What the client owes is `Invoice::net_amount`, i.e.
+//! `amount - discount + tax`: `pay_invoice`, installments, credit notes and
+//! `OutstandingBalance` all work on the net amount. Both fields are part of the invoice hash
+//! when either is non-zero, so invoices without them keep their hash.
+//!
+//! ### External References
+//!
+//! Invoices can carry the Django `invoice_number` (e.g. `INV-2025-001`) as `external_ref`,
+//! set through `create_invoice_with_ref`. References are unique across all clients and
+//! resolve to the invoice with `get_invoice_by_external_ref`. Invoices created without one
+//! store an empty reference, which is not indexed. A reference is freed again when its
+//! invoice is archived or purged.
+//!
+//! ### Credit Notes
+//!
+//! Invoices are never reduced in place. Instead, the creator issues credit notes that
+//! reference the original invoice and carry their own SHA256 hash (resolvable through
+//! `CreditNoteByHash`). The sum credited against an invoice is kept in `InvoiceCredits`,
+//! and payments only cover what is still outstanding (see `Pallet::outstanding_amount`).
+//! An invoice credited down to zero is settled as `Paid` without any transfer.
+//!
+//! ### Batch Creation
+//!
+//! The Django sync job submits invoices in bulk through `batch_create_invoices`. Entries are
+//! created in order with consecutive IDs and the usual per-invoice events. Each entry runs
+//! in its own storage transaction: one that fails validation (metadata too long, client at
+//! `MaxInvoicesPerClient`, ...) is rolled back alone and reported in an
+//! `InvoiceBatchItemSkipped` event with its `SkipReason`, while the others are kept. The
+//! call ends with a `BatchCompleted` event carrying a `BatchOutcome` (created and skipped
+//! counts, index of the first skipped entry), also stored in `LastBatchOutcome` for the
+//! caller, so the sync job can check the result without going through every event. Its
+//! weight scales with the number of entries.
+//!
+//! ### Late Fees
+//!
+//! An invoice still unpaid after its `due_block` accrues `LateFeePerPeriod` for every full
+//! `LateFeePeriod` blocks past it, either a flat amount or a per-mille share of the net
+//! amount; a zero period disables late fees. The fee is charged lazily: `pay_invoice` and
+//! `record_payment` add what accrued so far to `accrued_late_fee` and `OutstandingBalance`,
+//! emitting `LateFeeAccrued`, before settling. `get_amount_due`, also exposed by the runtime
+//! API, gives the live figure between payments.
+//!
+//! ### Outstanding Balance
+//!
+//! `OutstandingBalance` keeps, per client, the sum of what is still owed on all of its
+//! invoices (see `Pallet::outstanding_amount`), so dashboards can read it without decoding
+//! every invoice. Creation adds the amount; payments, installments and credit notes subtract
+//! what they settle, and cancellation or expiry subtracts whatever was left. Read it with
+//! `get_outstanding_balance`, also exposed by the runtime API.
+//!
+//! ### Creator Revenue
+//!
+//! `CreatorStats` keeps, per creator, what it invoiced and what it collected. Creation and
+//! accrued late fees add to the invoiced total and every payment adds to the collected
+//! total, whoever the invoice is assigned to. An amount that stops being owed is taken out of
+//! the invoiced total instead of being tracked apart: a credit note takes its amount, and
+//! cancelling, expiring, rejecting or purging an invoice takes the unpaid rest, so what was
+//! collected before stays counted. Invoiced less collected thus always equals what the
+//! creator's clients still owe. Both totals saturate rather than fail. Read them with
+//! `get_creator_stats`, also exposed by the runtime API.
+//!
+//! ### Expiry
+//!
+//! `create_invoice` optionally takes an `expires_at` block, kept in the `ExpiringInvoices`
+//! index (at most `MaxExpiringInvoicesPerBlock` per block). Invoices still unpaid when their
+//! expiry block is swept become `Expired`, a terminal status handled like a cancellation:
+//! the unpaid rest is written off and the creation deposit released. Paid, cancelled and
+//! disputed invoices are left as they are. Unlike `due_block`, the expiry is not part of
+//! the invoice or its hash.
+//!
+//! ### Access Grants
+//!
+//! Confidential invoices keep their metadata encrypted off-chain; the chain only governs who
+//! may decrypt it. `AccessGrants` maps `(invoice_hash, grantee)` to a `GrantInfo` recording
+//! who granted access, when, and the hash of the key wrapped for the grantee. The creator or
+//! client of a stored invoice manages its grants with `grant_access` and `revoke_access`;
+//! granting again replaces the recorded key wrap, e.g. after a key rotation. The key
+//! management service watches `AccessGranted` and `AccessRevoked` to distribute or withdraw
+//! wrapped keys, and the backend checks `Pallet::has_access` (also exposed by the runtime
+//! API) before serving ciphertext. The creator and client themselves always have access.
+//! A hash holds at most `MaxGrantsPerInvoice` grants, counted in `AccessGrantCount`.
+//!
+//! Grants are keyed by hash and move with an invoice re-hashed by `update_invoice_metadata`,
+//! so grantees keep access; the key management service re-wraps keys for the new ciphertext
+//! on `InvoiceRehashed`.
+//!
+//! ### Document Attachments
+//!
+//! Supporting documents such as the invoice PDF or a delivery note stay off-chain; the
+//! creator or client anchors their hash with `attach_document`, labelled (e.g.
+//! `delivery-note`) so auditors know what to look for. An invoice holds at most
+//! `MaxAttachmentsPerInvoice` attachments, each document hash once, in
+//! `InvoiceAttachments` next to the invoice, so attaching does not change the invoice hash.
+//! Attachments cannot be removed; they go with the invoice when it is archived or purged.
+//! Read them with `get_attachments`, also exposed by the runtime API.
+//!
+//! ### Cancellation by Consent
+//!
+//! Besides the unilateral `cancel_invoice`, a creator can ask the client to agree with
+//! `request_cancellation`, committing to the reason by its hash. The client either approves,
+//! which cancels the invoice, or rejects, which keeps it active and blocks new requests for
+//! `CancellationCooldown` blocks. A client that does not answer within `CancellationTimeout`
+//! blocks is taken to agree: from the deadline kept in `PendingCancellations`, anyone can
+//! finalize the cancellation. Requests are dropped once the invoice is settled or cancelled.
+//!
+//! ### Client Acceptance
+//!
+//! The client acknowledges an invoice with `accept_invoice`, moving it to `Accepted`, or
+//! refuses it with `reject_invoice` and a reason bounded by `MaxMetadataLength`. `Rejected`
+//! is terminal and writes off the invoice like a cancellation. With `RequireAcceptance` on,
+//! `pay_invoice` and `record_payment` fail with `InvoiceNotAccepted` until the client has
+//! accepted. An accepted invoice is not flagged overdue, and one reinstated after a
+//! challenge is `Pending` again and must be accepted anew.
+//!
+//! ### Disputes
+//!
+//! A client disputes an invoice with `dispute_invoice`, giving a bounded reason. The dispute
+//! is kept in `InvoiceDisputes` next to the invoice status, which keeps its lifecycle.
+//! While it is open the invoice cannot be paid, credited, edited or cancelled, and a pending
+//! cancellation request is dropped. `DisputeResolutionOrigin` either reinstates the invoice
+//! or cancels it. `Config::DisputeHandler` is notified when a dispute opens and closes, so an
+//! escrow or arbitration pallet can hold and release the disputed funds.
+//!
+//! ### Status Transitions
+//!
+//! Every invoice status transition, whether dispatched or applied by a hook, is reported to
+//! `Config::OnTransition`, e.g. a compliance log archiving them.
+//!
+//! ### Challenges
+//!
+//! Fraudulent invoices are not only the client's concern, so anyone but the creator can
+//! challenge a pending or overdue invoice with `challenge_invoice`, committing to evidence by
+//! its hash and reserving `ChallengeBond`. The invoice moves to `InvoiceStatus::Challenged`,
+//! where it can be neither paid nor cancelled by its creator, and the challenge is kept in
+//! `InvoiceChallenges`. It closes in one of four ways:
+//!
+//! * The creator concedes: the invoice is cancelled and the bond returned.
+//! * `ChallengeJudgeOrigin` upholds it: the invoice is cancelled, the bond returned and the
+//!   creation deposit paid to the challenger.
+//! * `ChallengeJudgeOrigin` dismisses it: the bond is paid to the creator and the invoice is
+//!   reinstated as pending, or overdue once past its due block.
+//! * Nobody acts within `ChallengeTimeout` blocks: the challenge is upheld when
+//!   `on_initialize` reaches the block recorded in `ChallengeTimeouts`.
+//!
+//! ### Invoice Webhook
+//!
+//! Instead of polling events, the Django backend can be notified of new invoices. With
+//! `InvoiceWebhook` enabled, invoices are indexed per block in `NewInvoices` (at most
+//! `MaxWebhookInvoicesPerBlock`), and the offchain worker POSTs a JSON payload with the ID,
+//! client, amount, hash and block of each to an endpoint configured in offchain local
+//! storage. Failed deliveries are retried in later blocks a bounded number of times; see
+//! the `webhook` module.
+//!
+//! Payments are summarized instead of notified one by one: `on_finalize` of a block in which
+//! `pay_invoice` or `record_payment` moved funds emits a single `PaymentsDigest` with the
+//! number of payments, their total and `digest_hash`, a SHA256 chain over the ordered
+//! `(invoice_id, amount)` pairs. Starting from 32 zero bytes, each payment replaces the hash
+//! with `sha256(hash ++ invoice_id as little-endian u64 ++ SCALE encoded amount)`, so the
+//! webhook side can fetch the block's `InvoicePaid` and `PartialPaymentRecorded` events once
+//! and check them against the digest.
+//!
+//! ### Offchain Link Confirmation
+//!
+//! The ERP acknowledges an invoice by submitting the unsigned `confirm_offchain_link` with
+//! the Django record ID and the invoice hash it computed as proof. `ValidateUnsigned` only
+//! admits it to the pool while the proof is the hash `InvoiceByHash` maps to the invoice
+//! and the invoice has no confirmed link yet, tagged by invoice ID so the pool holds one
+//! confirmation per invoice. The link is kept in `OffchainLinks` and cannot be replaced.
+//!
+//! Invoice hashes are public, so the proof shows that the submitter computed the same
+//! hash, not who submitted it: the first confirmation wins, and the ERP checks the
+//! recorded `django_record_id` against its own.
+//!
+//! ### Creation Deposit
+//!
+//! To deter spam invoices against arbitrary clients, creating an invoice through an
+//! extrinsic reserves `InvoiceDeposit` from the creator. The reserved amount is recorded in
+//! `InvoiceDeposits`, so changing `InvoiceDeposit` never releases more or less than was
+//! reserved. The deposit is released once the invoice is paid or cancelled by its creator
+//! (directly or by consent), and slashed to `Config::Slashed` (e.g. the treasury) when a
+//! dispute is resolved by cancelling the invoice. Genesis invoices and invoices created
+//! before deposits were introduced carry none.
+//!
+//! ### Abandoned Invoices
+//!
+//! A creator who disappears would otherwise leave an invoice open and its deposit reserved
+//! forever. Every invoice records the block of its creator's `last_activity`: creation,
+//! `update_invoice_metadata`, `create_credit_note`, `request_cancellation`, or attaching a
+//! document. Once `AbandonThreshold` blocks pass without any while the invoice is still
+//! `Pending` or `Overdue`, the client may `claim_abandoned_invoice`: the deposit is
+//! repatriated to the client as compensation and the invoice is cancelled. A zero threshold
+//! disables claims.
+//!
+//! ### Assignment
+//!
+//! An invoice's right to payment can be sold, e.g. to a factoring company. Payments go to
+//! the invoice's `beneficiary`, initially its creator, who may `assign_invoice` to another
+//! account while the invoice is open and uncontested; the new beneficiary may assign it on.
+//! The creator keeps every other role on the invoice. Each assignment is kept in
+//! `InvoiceAssignments`, at most `MaxAssignmentsPerInvoice` per invoice, and read with
+//! `get_assignments`.
+//!
+//! ### Hooks
+//!
+//! * `on_initialize` - Flags unpaid invoices whose due block has been reached as overdue,
+//!   within the `OverdueCheckWeight` budget. Work left over is resumed in the next block.
+//!   Then upholds the challenges timing out at the block and clears `NewInvoices`.
+//! * `on_finalize` - Emits `PaymentsDigest` if the block had payments, and clears
+//!   `PaymentsThisBlock` either way
+//! * `on_idle` - Expires unpaid invoices whose expiry block has been reached, within the
+//!   weight left in the block. The position is kept in `ExpiryCursor`, so a sweep cut short
+//!   resumes in a later block.
+//! * `offchain_worker` - Notifies the webhook endpoint of the invoices created in the block
+//! * `on_runtime_upgrade` - Runs pending storage migrations (see `migrations`)
+//! * `pre_upgrade` / `post_upgrade` - With `try-runtime`, check that the migrations keep the
+//!   invoice counter, hash mappings and per-client indexes intact (see `migrations::checks`)
 
 pub use pallet::*;
+pub use tidygen_primitives::identity::DidProvider;
+pub use weights::WeightInfo;
+
+pub mod migrations;
+pub mod webhook;
+pub mod weights;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+#[cfg(test)]
+mod compatibility;
 
 #[frame_support::pallet]
 pub mod pallet {
+    use crate::weights::WeightInfo;
     use frame_support::{
         pallet_prelude::*,
-        traits::{Currency, Get},
+        storage::{with_transaction, TransactionOutcome},
+        traits::{
+            BalanceStatus, Currency, ExistenceRequirement, Get, OnUnbalanced, PalletInfoAccess,
+            ReservableCurrency,
+        },
     };
     use frame_system::pallet_prelude::*;
-    use sp_core::H256;
-    use sp_io::hashing::sha2_256;
-    use sp_runtime::traits::Hash;
+    #[cfg(feature = "serde")]
+    use serde::{Deserialize, Serialize};
+    use sp_core::{ed25519, sr25519, H256};
+    use sp_io::hashing::{blake2_256, sha2_256};
+    use sp_runtime::{
+        traits::{CheckedAdd, CheckedMul, Hash, One, Saturating, UniqueSaturatedInto, Zero},
+        ModuleError,
+    };
     use sp_std::vec::Vec;
+    use tidygen_primitives::{
+        batch::{BatchOutcome, SkipReason},
+        features::FeatureFlags,
+        governance::{ParameterChange, ParameterHandler, ParameterNoLongerExists},
+        identity::DidProvider,
+        pagination::{InvalidCursor, PageCursor},
+        text,
+        transitions::{status_index, OnStatusTransition, StateMachine, TransitionSubject},
+    };
 
-    type BalanceOf<T> =
+    pub type BalanceOf<T> =
         <<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+    pub type NegativeImbalanceOf<T> = <<T as Config>::Currency as Currency<
+        <T as frame_system::Config>::AccountId,
+    >>::NegativeImbalance;
+
+    /// The in-code storage version
+    const STORAGE_VERSION: StorageVersion = StorageVersion::new(13);
+
+    /// Blocks an unsigned link confirmation stays valid in the pool
+    const LINK_CONFIRMATION_LONGEVITY: u64 = 10;
+
+    /// Domain tag prefixed to operator-signed invoice payloads
+    pub const OPERATOR_PAYLOAD_TAG: &[u8] = b"tidygen/invoice/v1";
 
     #[pallet::pallet]
+    #[pallet::storage_version(STORAGE_VERSION)]
     pub struct Pallet<T>(_);
 
+    /// Invoice status
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub enum InvoiceStatus {
+        /// Invoice is awaiting payment
+        Pending,
+        /// Invoice has been settled by the client
+        Paid,
+        /// Invoice was cancelled by its creator
+        Cancelled,
+        /// Invoice passed its due block without being paid
+        Overdue,
+        /// Invoice reached its expiry block without being paid
+        Expired,
+        /// Invoice is challenged as fraudulent and cannot be paid until the challenge closes
+        Challenged,
+        /// Invoice was acknowledged by the client and awaits payment
+        Accepted,
+        /// Invoice was refused by the client
+        Rejected,
+    }
+
+    impl Default for InvoiceStatus {
+        fn default() -> Self {
+            Self::Pending
+        }
+    }
+
+    /// Hash function applied to invoice preimages
+    #[derive(
+        Clone, Copy, Default, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen,
+    )]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    #[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+    pub enum HashAlgorithm {
+        /// SHA-256, the only algorithm before storage version 6
+        #[default]
+        Sha256,
+        /// BLAKE2b with a 256-bit digest
+        Blake2b256,
+    }
+
+    impl HashAlgorithm {
+        /// Digest of `data` under this algorithm
+        pub fn hash(&self, data: &[u8]) -> [u8; 32] {
+            match self {
+                Self::Sha256 => sha2_256(data),
+                Self::Blake2b256 => blake2_256(data),
+            }
+        }
+    }
+
+    /// Fee charged for every `LateFeePeriod` an invoice stays unpaid past its due block
+    #[derive(
+        Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen,
+    )]
+    pub enum LateFee<Balance> {
+        /// The same amount every period
+        Flat(Balance),
+        /// Thousandths of the invoice's net amount every period
+        PerMille(u32),
+    }
+
+    /// Allowed invoice status transitions; `Paid`, `Cancelled`, `Expired` and `Rejected` are
+    /// terminal
+    pub const INVOICE_STATUS: StateMachine<InvoiceStatus> = StateMachine::new(&[
+        (InvoiceStatus::Pending, InvoiceStatus::Paid),
+        (InvoiceStatus::Pending, InvoiceStatus::Cancelled),
+        (InvoiceStatus::Pending, InvoiceStatus::Overdue),
+        (InvoiceStatus::Pending, InvoiceStatus::Expired),
+        (InvoiceStatus::Pending, InvoiceStatus::Challenged),
+        (InvoiceStatus::Pending, InvoiceStatus::Accepted),
+        (InvoiceStatus::Pending, InvoiceStatus::Rejected),
+        (InvoiceStatus::Overdue, InvoiceStatus::Paid),
+        (InvoiceStatus::Overdue, InvoiceStatus::Cancelled),
+        (InvoiceStatus::Overdue, InvoiceStatus::Expired),
+        (InvoiceStatus::Overdue, InvoiceStatus::Challenged),
+        (InvoiceStatus::Overdue, InvoiceStatus::Accepted),
+        (InvoiceStatus::Overdue, InvoiceStatus::Rejected),
+        // Acceptance commits the client to pay, so an accepted invoice is not flagged overdue
+        (InvoiceStatus::Accepted, InvoiceStatus::Paid),
+        (InvoiceStatus::Accepted, InvoiceStatus::Cancelled),
+        (InvoiceStatus::Accepted, InvoiceStatus::Expired),
+        (InvoiceStatus::Accepted, InvoiceStatus::Challenged),
+        // A dismissed challenge reinstates the invoice, which is flagged overdue again if due
+        (InvoiceStatus::Challenged, InvoiceStatus::Pending),
+        (InvoiceStatus::Challenged, InvoiceStatus::Cancelled),
+    ]);
+
+    /// Invoice line item, mirroring a row of the Django invoice
+    #[derive(
+        CloneNoBound,
+        PartialEqNoBound,
+        EqNoBound,
+        RuntimeDebugNoBound,
+        Encode,
+        Decode,
+        TypeInfo,
+        MaxEncodedLen,
+    )]
+    #[scale_info(skip_type_params(T))]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            rename_all = "camelCase",
+            bound(
+                serialize = "BalanceOf<T>: Serialize",
+                deserialize = "BalanceOf<T>: Deserialize<'de>"
+            )
+        )
+    )]
+    pub struct LineItem<T: Config> {
+        /// What was sold or worked on
+        #[cfg_attr(feature = "serde", serde(with = "tidygen_primitives::hex"))]
+        pub description: BoundedVec<u8, T::MaxLineItemDescriptionLength>,
+        /// Number of units
+        pub quantity: u32,
+        /// Price of a single unit
+        pub unit_price: BalanceOf<T>,
+    }
+
+    impl<T: Config> LineItem<T> {
+        /// `quantity * unit_price`, or `None` on overflow
+        pub fn total(&self) -> Option<BalanceOf<T>> {
+            self.unit_price.checked_mul(&BalanceOf::<T>::from(self.quantity))
+        }
+    }
+
     /// Invoice data structure
     /// This structure is designed to match Django ERP invoice model
     #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
     #[scale_info(skip_type_params(T))]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            rename_all = "camelCase",
+            bound(
+                serialize = "T::AccountId: Serialize, BalanceOf<T>: Serialize, \
+                             BlockNumberFor<T>: Serialize",
+                deserialize = "T::AccountId: Deserialize<'de>, BalanceOf<T>: Deserialize<'de>, \
+                               BlockNumberFor<T>: Deserialize<'de>"
+            )
+        )
+    )]
     pub struct Invoice<T: Config> {
         /// Unique invoice ID
         pub id: u64,
         /// Client account ID
         pub client: T::AccountId,
-        /// Invoice amount
+        /// Invoice amount (subtotal before tax and discount)
         pub amount: BalanceOf<T>,
         /// Invoice metadata (JSON string, invoice number, etc.)
+        #[cfg_attr(feature = "serde", serde(with = "tidygen_primitives::hex"))]
         pub metadata: BoundedVec<u8, T::MaxMetadataLength>,
         /// Block number when invoice was created (timestamp)
         pub timestamp: BlockNumberFor<T>,
-        /// SHA256 hash of invoice details (for Django linking)
+        /// Hash of invoice details (for Django linking), see `hash_algorithm`
+        #[cfg_attr(feature = "serde", serde(with = "tidygen_primitives::hex"))]
         pub invoice_hash: [u8; 32],
         /// Creator of the invoice
         pub created_by: T::AccountId,
+        /// Current status
+        pub status: InvoiceStatus,
+        /// Block by which the invoice should be paid (if any)
+        pub due_block: Option<BlockNumberFor<T>>,
+        /// Amount paid so far (installments via `record_payment`)
+        pub paid_amount: BalanceOf<T>,
+        /// External reference such as the Django invoice number (empty if none)
+        #[cfg_attr(feature = "serde", serde(with = "tidygen_primitives::hex"))]
+        pub external_ref: BoundedVec<u8, T::MaxExternalRefLength>,
+        /// Line items adding up to `amount` (empty if none)
+        pub line_items: BoundedVec<LineItem<T>, T::MaxLineItems>,
+        /// Algorithm `invoice_hash` was computed with
+        pub hash_algorithm: HashAlgorithm,
+        /// Tax added on top of `amount` (zero if none)
+        pub tax: BalanceOf<T>,
+        /// Discount taken off `amount` (zero if none)
+        pub discount: BalanceOf<T>,
+        /// Late fee accrued since the due block, as of the last payment
+        pub accrued_late_fee: BalanceOf<T>,
+        /// Block of the creator's last action on the invoice, from which `AbandonThreshold`
+        /// is counted
+        pub last_activity: BlockNumberFor<T>,
+        /// Account payments are made to, the creator unless the invoice was assigned
+        pub beneficiary: T::AccountId,
     }
 
     impl<T: Config> Invoice<T> {
-        /// Calculate SHA256 hash of invoice details
+        /// Calculate the hash of invoice details with the invoice's `hash_algorithm`
         /// This hash is used to link the on-chain invoice with Django database record
         pub fn calculate_hash(&self) -> [u8; 32] {
-            let mut data = Vec::new();
-            
-            // Encode invoice data for hashing
-            data.extend_from_slice(&self.id.to_le_bytes());
-            data.extend_from_slice(self.client.encode().as_slice());
-            data.extend_from_slice(self.amount.encode().as_slice());
-            data.extend_from_slice(self.metadata.encode().as_slice());
-            data.extend_from_slice(&self.timestamp.encode());
-            
-            // Calculate SHA256 hash
+            // Length-prefix every field so no two field splits share a preimage
+            let mut data = length_prefixed(&[
+                &self.id.to_le_bytes(),
+                &self.client.encode(),
+                &self.amount.encode(),
+                &self.metadata,
+                &self.timestamp.encode(),
+                &self.created_by.encode(),
+            ]);
+
+            // Appended only when present, so invoices without line items keep their hash
+            for item in &self.line_items {
+                data.extend(length_prefixed(&[
+                    &item.description,
+                    &item.quantity.to_le_bytes(),
+                    &item.unit_price.encode(),
+                ]));
+            }
+
+            // Likewise only when set, so invoices without tax or discount keep their hash
+            if !self.tax.is_zero() || !self.discount.is_zero() {
+                data.extend(length_prefixed(&[&self.tax.encode(), &self.discount.encode()]));
+            }
+
+            self.hash_algorithm.hash(&data)
+        }
+
+        /// Amount payable by the client: `amount - discount + tax`
+        pub fn net_amount(&self) -> BalanceOf<T> {
+            self.amount.saturating_sub(self.discount).saturating_add(self.tax)
+        }
+    }
+
+    /// Credit note issued against an invoice
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    #[scale_info(skip_type_params(T))]
+    pub struct CreditNote<T: Config> {
+        /// Unique credit note ID
+        pub id: u64,
+        /// ID of the credited invoice
+        pub invoice_id: u64,
+        /// Client of the credited invoice
+        pub client: T::AccountId,
+        /// Credited amount
+        pub amount: BalanceOf<T>,
+        /// Credit note metadata (credit note number, reason, etc.)
+        pub metadata: BoundedVec<u8, T::MaxMetadataLength>,
+        /// Block number when the credit note was issued
+        pub timestamp: BlockNumberFor<T>,
+        /// SHA256 hash of the credit note details
+        pub credit_note_hash: [u8; 32],
+        /// Issuer of the credit note (the invoice creator)
+        pub created_by: T::AccountId,
+    }
+
+    impl<T: Config> CreditNote<T> {
+        /// Calculate SHA256 hash of credit note details, length-prefixed like invoice hashes
+        pub fn calculate_hash(&self) -> [u8; 32] {
+            let data = length_prefixed(&[
+                &self.id.to_le_bytes(),
+                &self.invoice_id.to_le_bytes(),
+                &self.client.encode(),
+                &self.amount.encode(),
+                &self.metadata,
+                &self.timestamp.encode(),
+            ]);
+
             sha2_256(&data)
         }
     }
 
+    /// Cancellation requested by an invoice creator, awaiting the client's answer
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    #[scale_info(skip_type_params(T))]
+    pub struct CancellationRequest<T: Config> {
+        /// Hash of the cancellation reason kept off-chain
+        pub reason_hash: [u8; 32],
+        /// Block the cancellation was requested at
+        pub requested_at: BlockNumberFor<T>,
+        /// Block from which the cancellation can be finalized without the client
+        pub deadline: BlockNumberFor<T>,
+    }
+
+    /// Open dispute on an invoice
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    #[scale_info(skip_type_params(T))]
+    pub struct InvoiceDispute<T: Config> {
+        /// Reason given by the client
+        pub reason: BoundedVec<u8, T::MaxDisputeReasonLength>,
+        /// Block the dispute was opened at
+        pub opened_at: BlockNumberFor<T>,
+    }
+
+    /// Open fraud challenge against an invoice
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    #[scale_info(skip_type_params(T))]
+    pub struct InvoiceChallenge<T: Config> {
+        /// Account that opened the challenge and posted the bond
+        pub challenger: T::AccountId,
+        /// Hash of the evidence, kept off-chain
+        pub evidence_hash: [u8; 32],
+        /// Bond reserved from the challenger
+        pub bond: BalanceOf<T>,
+        /// Block the challenge was opened at
+        pub opened_at: BlockNumberFor<T>,
+        /// Block at which the challenge is upheld unless conceded or ruled on before
+        pub timeout_at: BlockNumberFor<T>,
+    }
+
+    /// Access to the encrypted payload of an invoice, granted by its creator or client
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    #[scale_info(skip_type_params(T))]
+    pub struct GrantInfo<T: Config> {
+        /// Invoice party that granted access
+        pub granted_by: T::AccountId,
+        /// Block the grant was made at
+        pub granted_at: BlockNumberFor<T>,
+        /// Hash of the payload key wrapped for the grantee
+        pub key_wrap_hash: [u8; 32],
+    }
+
+    /// Hash of a supporting document anchored to an invoice
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    #[scale_info(skip_type_params(T))]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            rename_all = "camelCase",
+            bound(
+                serialize = "T::AccountId: Serialize, BlockNumberFor<T>: Serialize",
+                deserialize = "T::AccountId: Deserialize<'de>, BlockNumberFor<T>: Deserialize<'de>"
+            )
+        )
+    )]
+    pub struct Attachment<T: Config> {
+        /// Hash of the document, kept off-chain
+        #[cfg_attr(feature = "serde", serde(with = "tidygen_primitives::hex"))]
+        pub doc_hash: [u8; 32],
+        /// What the document is, e.g. `invoice-pdf` or `delivery-note`
+        #[cfg_attr(feature = "serde", serde(with = "tidygen_primitives::hex"))]
+        pub label: BoundedVec<u8, T::MaxAttachmentLabelLength>,
+        /// Invoice party that attached it
+        pub attached_by: T::AccountId,
+        /// Block it was attached at
+        pub attached_at: BlockNumberFor<T>,
+    }
+
+    /// Transfer of an invoice's right to payment, kept for audit
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    #[scale_info(skip_type_params(T))]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            rename_all = "camelCase",
+            bound(
+                serialize = "T::AccountId: Serialize, BlockNumberFor<T>: Serialize",
+                deserialize = "T::AccountId: Deserialize<'de>, BlockNumberFor<T>: Deserialize<'de>"
+            )
+        )
+    )]
+    pub struct Assignment<T: Config> {
+        /// Beneficiary before the assignment
+        pub from: T::AccountId,
+        /// Beneficiary after the assignment
+        pub to: T::AccountId,
+        /// Block it was assigned at
+        pub assigned_at: BlockNumberFor<T>,
+    }
+
+    /// Acknowledgement of an invoice by the off-chain ERP
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    #[scale_info(skip_type_params(T))]
+    pub struct OffchainLink<T: Config> {
+        /// Primary key of the Django invoice record
+        pub django_record_id: u64,
+        /// Block the link was confirmed at
+        pub confirmed_at: BlockNumberFor<T>,
+    }
+
+    /// An `InvoiceCreated` event read back from the block that deposited it
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+    pub struct CreatedInvoice<AccountId, Balance> {
+        /// Position of the event among all events of the block
+        pub event_index: u32,
+        /// ID of the new invoice
+        pub invoice_id: u64,
+        /// Invoiced client
+        pub client: AccountId,
+        /// Invoice amount
+        pub amount: Balance,
+        /// Hash of the new invoice
+        pub invoice_hash: [u8; 32],
+        /// Invoice creator
+        pub created_by: AccountId,
+    }
+
+    /// Outcome of a dispute
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    pub enum DisputeResolution {
+        /// The invoice stands and can be paid again
+        Reinstated,
+        /// The invoice is cancelled
+        Cancelled,
+    }
+
+    /// How an invoice challenge was closed
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    pub enum ChallengeOutcome {
+        /// The creator conceded; the invoice is cancelled and the bond returned
+        Conceded,
+        /// `ChallengeJudgeOrigin` found the invoice fraudulent
+        Upheld,
+        /// `ChallengeJudgeOrigin` found the invoice legitimate; the bond goes to the creator
+        Dismissed,
+        /// Nobody ruled within `ChallengeTimeout`, which upholds the challenge
+        TimedOut,
+    }
+
+    /// Public keys that invoice creators sign `create_invoice` signatures with
+    pub trait CreatorPublicKey<AccountId> {
+        /// Raw sr25519 or ed25519 public key of `who`, `None` if it has none
+        fn public_key(who: &AccountId) -> Option<[u8; 32]>;
+    }
+
+    /// For accounts that are their own public key, like `AccountId32`
+    pub struct AccountIdAsPublicKey;
+
+    impl<AccountId: Encode> CreatorPublicKey<AccountId> for AccountIdAsPublicKey {
+        fn public_key(who: &AccountId) -> Option<[u8; 32]> {
+            who.using_encoded(|bytes| bytes.try_into().ok())
+        }
+    }
+
+    /// Notified when invoice disputes open and close, e.g. to hold and release escrowed funds
+    pub trait OnInvoiceDispute<AccountId, Balance> {
+        /// `client` disputed `invoice_id` of `creator`, with `outstanding` still to be paid
+        fn dispute_opened(
+            client: &AccountId,
+            creator: &AccountId,
+            invoice_id: u64,
+            outstanding: Balance,
+        );
+
+        /// The dispute on `invoice_id` was resolved
+        fn dispute_closed(
+            client: &AccountId,
+            creator: &AccountId,
+            invoice_id: u64,
+            resolution: &DisputeResolution,
+        );
+    }
+
+    /// Nobody is notified
+    impl<AccountId, Balance> OnInvoiceDispute<AccountId, Balance> for () {
+        fn dispute_opened(
+            _client: &AccountId,
+            _creator: &AccountId,
+            _invoice_id: u64,
+            _outstanding: Balance,
+        ) {
+        }
+
+        fn dispute_closed(
+            _client: &AccountId,
+            _creator: &AccountId,
+            _invoice_id: u64,
+            _resolution: &DisputeResolution,
+        ) {
+        }
+    }
+
+    /// Concatenate `fields`, each prefixed with its length as a little-endian `u32`
+    pub fn length_prefixed(fields: &[&[u8]]) -> Vec<u8> {
+        let mut data = Vec::new();
+        for field in fields {
+            data.extend_from_slice(&(field.len() as u32).to_le_bytes());
+            data.extend_from_slice(field);
+        }
+        data
+    }
+
     #[pallet::config]
     pub trait Config: frame_system::Config {
         /// The overarching event type.
         type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
 
-        /// Currency type for handling invoice amounts
-        type Currency: Currency<Self::AccountId>;
+        /// Currency type for handling invoice amounts and creation deposits
+        type Currency: Currency<Self::AccountId> + ReservableCurrency<Self::AccountId>;
 
         /// Maximum length of invoice metadata
         #[pallet::constant]
@@ -98,596 +963,4296 @@ pub mod pallet {
         /// Maximum number of invoices per client
         #[pallet::constant]
         type MaxInvoicesPerClient: Get<u32>;
+
+        /// Maximum number of invoices that can fall due in a single block
+        #[pallet::constant]
+        type MaxDueInvoicesPerBlock: Get<u32>;
+
+        /// Maximum number of invoices that can expire in a single block
+        #[pallet::constant]
+        type MaxExpiringInvoicesPerBlock: Get<u32>;
+
+        /// Weight budget for overdue detection in `on_initialize`
+        #[pallet::constant]
+        type OverdueCheckWeight: Get<Weight>;
+
+        /// Origin allowed to manage operator signing keys and authorized creators
+        type AdminOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+        /// Maximum number of active operator signing keys
+        #[pallet::constant]
+        type MaxOperatorKeys: Get<u32>;
+
+        /// Maximum length of an invoice external reference
+        #[pallet::constant]
+        type MaxExternalRefLength: Get<u32>;
+
+        /// Blocks a client has to answer a cancellation request before it can be finalized
+        #[pallet::constant]
+        type CancellationTimeout: Get<BlockNumberFor<Self>>;
+
+        /// Blocks during which no new cancellation can be requested after a rejection
+        #[pallet::constant]
+        type CancellationCooldown: Get<BlockNumberFor<Self>>;
+
+        /// Origin allowed to resolve invoice disputes
+        type DisputeResolutionOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+        /// Maximum length of a dispute reason
+        #[pallet::constant]
+        type MaxDisputeReasonLength: Get<u32>;
+
+        /// Notified when disputes are opened and closed
+        type DisputeHandler: OnInvoiceDispute<Self::AccountId, BalanceOf<Self>>;
+
+        /// Notified of every invoice status transition
+        type OnTransition: OnStatusTransition<Self::AccountId>;
+
+        /// Maximum number of invoices in one `batch_create_invoices` call
+        #[pallet::constant]
+        type MaxBatchSize: Get<u32>;
+
+        /// Whether any signed account may create invoices, not only `AuthorizedCreators`
+        #[pallet::constant]
+        type OpenCreation: Get<bool>;
+
+        /// Whether invoices must be accepted by their client before they can be paid
+        #[pallet::constant]
+        type RequireAcceptance: Get<bool>;
+
+        /// Maximum number of line items per invoice
+        #[pallet::constant]
+        type MaxLineItems: Get<u32>;
+
+        /// Maximum length of a line item description
+        #[pallet::constant]
+        type MaxLineItemDescriptionLength: Get<u32>;
+
+        /// Maximum length of a creator signature passed to `create_invoice`
+        #[pallet::constant]
+        type MaxSignatureLength: Get<u32>;
+
+        /// Public key of an invoice creator, checked against its signature
+        type CreatorKeys: CreatorPublicKey<Self::AccountId>;
+
+        /// Hash algorithm for new invoices; existing invoices keep the one they were hashed with
+        #[pallet::constant]
+        type InvoiceHashAlgorithm: Get<HashAlgorithm>;
+
+        /// DID status of invoice clients; `()` accepts clients without a DID
+        type DidProvider: DidProvider<Self::AccountId>;
+
+        /// Deposit reserved from the creator of every invoice until it is settled
+        #[pallet::constant]
+        type InvoiceDeposit: Get<BalanceOf<Self>>;
+
+        /// Receives deposits slashed when a dispute cancels an invoice, e.g. the treasury
+        type Slashed: OnUnbalanced<NegativeImbalanceOf<Self>>;
+
+        /// Bond reserved from the account challenging an invoice
+        #[pallet::constant]
+        type ChallengeBond: Get<BalanceOf<Self>>;
+
+        /// Blocks after which an open challenge is upheld without a ruling
+        #[pallet::constant]
+        type ChallengeTimeout: Get<BlockNumberFor<Self>>;
+
+        /// Maximum number of challenges timing out at the same block
+        #[pallet::constant]
+        type MaxChallengesPerBlock: Get<u32>;
+
+        /// Origin ruling on invoice challenges
+        type ChallengeJudgeOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+        /// Whether the offchain worker posts new invoices to the webhook endpoint
+        #[pallet::constant]
+        type InvoiceWebhook: Get<bool>;
+
+        /// Maximum number of invoices per block indexed for the webhook
+        #[pallet::constant]
+        type MaxWebhookInvoicesPerBlock: Get<u32>;
+
+        /// Priority of unsigned link confirmations
+        #[pallet::constant]
+        type UnsignedPriority: Get<TransactionPriority>;
+
+        /// Maximum number of documents attached to one invoice
+        #[pallet::constant]
+        type MaxAttachmentsPerInvoice: Get<u32>;
+
+        /// Maximum length of an attachment label
+        #[pallet::constant]
+        type MaxAttachmentLabelLength: Get<u32>;
+
+        /// Blocks after which an unpaid invoice past its due block is charged another late fee;
+        /// zero disables late fees
+        #[pallet::constant]
+        type LateFeePeriod: Get<BlockNumberFor<Self>>;
+
+        /// Late fee charged per `LateFeePeriod`
+        #[pallet::constant]
+        type LateFeePerPeriod: Get<LateFee<BalanceOf<Self>>>;
+
+        /// Blocks without activity of its creator after which the client may claim an
+        /// unanswered, unpaid invoice as abandoned; zero disables such claims
+        #[pallet::constant]
+        type AbandonThreshold: Get<BlockNumberFor<Self>>;
+
+        /// Maximum number of times one invoice can be assigned
+        #[pallet::constant]
+        type MaxAssignmentsPerInvoice: Get<u32>;
+
+        /// Maximum number of access grants on one invoice hash
+        #[pallet::constant]
+        type MaxGrantsPerInvoice: Get<u32>;
+
+        /// Maximum number of invoices one `archive_invoices` call archives, at most
+        /// `MaxInvoicesPerClient`
+        #[pallet::constant]
+        type MaxArchivedPerCall: Get<u32>;
+
+        /// Weights of the calls whose cost depends on their input
+        type WeightInfo: WeightInfo;
     }
 
-    /// Storage for invoices mapped by client AccountId
-    /// Each client has a vector of their invoices
+    /// Storage for invoices: double map (client AccountId, invoice ID) => Invoice
     #[pallet::storage]
     #[pallet::getter(fn invoices)]
-    pub type Invoices<T: Config> = StorageMap<
+    pub type Invoices<T: Config> = StorageDoubleMap<
         _,
         Blake2_128Concat,
-        T::AccountId,
-        BoundedVec<Invoice<T>, T::MaxInvoicesPerClient>,
-        ValueQuery,
+        T::AccountId, // Client
+        Blake2_128Concat,
+        u64, // Invoice ID
+        Invoice<T>,
+        OptionQuery,
     >;
 
-    /// Global invoice counter for unique IDs
+    /// Number of invoices stored per client (bounded by `MaxInvoicesPerClient`)
+    #[pallet::storage]
+    #[pallet::getter(fn client_invoice_count)]
+    pub type ClientInvoiceCount<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, u32, ValueQuery>;
+
+    /// Invoice IDs of a client in creation order: (client, position) => invoice ID.
+    /// IDs are ascending per client, which lets pagination seek by invoice ID.
+    #[pallet::storage]
+    pub type ClientInvoiceIds<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        T::AccountId, // Client
+        Twox64Concat,
+        u32, // Position
+        u64, // Invoice ID
+        OptionQuery,
+    >;
+
+    /// Invoices issued by a creator: (creator, invoice ID) => client
+    #[pallet::storage]
+    pub type InvoicesByCreator<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        T::AccountId, // Creator
+        Blake2_128Concat,
+        u64, // Invoice ID
+        T::AccountId, // Client
+        OptionQuery,
+    >;
+
+    /// Hashes of archived (pruned) invoices: (client, invoice ID) => invoice hash
+    #[pallet::storage]
+    pub type ArchivedInvoiceHashes<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        T::AccountId, // Client
+        Twox64Concat,
+        u64, // Invoice ID
+        [u8; 32],
+        OptionQuery,
+    >;
+
+    /// Global invoice counter for unique IDs
     #[pallet::storage]
     #[pallet::getter(fn invoice_count)]
     pub type InvoiceCount<T> = StorageValue<_, u64, ValueQuery>;
 
-    /// Storage for invoice hash to ID mapping (for quick lookups)
-    #[pallet::storage]
-    #[pallet::getter(fn invoice_by_hash)]
-    pub type InvoiceByHash<T: Config> = StorageMap<_, Blake2_128Concat, [u8; 32], u64, OptionQuery>;
+    /// Storage for invoice hash to ID mapping (for quick lookups)
+    #[pallet::storage]
+    #[pallet::getter(fn invoice_by_hash)]
+    pub type InvoiceByHash<T: Config> = StorageMap<_, Blake2_128Concat, [u8; 32], u64, OptionQuery>;
+
+    /// External reference to invoice mapping: external_ref => (client, invoice ID)
+    #[pallet::storage]
+    pub type InvoiceByExternalRef<T: Config> = StorageMap<
+        _,
+        Blake2_128Concat,
+        BoundedVec<u8, T::MaxExternalRefLength>,
+        (T::AccountId, u64),
+        OptionQuery,
+    >;
+
+    /// Credit notes by ID
+    #[pallet::storage]
+    #[pallet::getter(fn credit_notes)]
+    pub type CreditNotes<T: Config> =
+        StorageMap<_, Twox64Concat, u64, CreditNote<T>, OptionQuery>;
+
+    /// Global credit note counter for unique IDs
+    #[pallet::storage]
+    #[pallet::getter(fn credit_note_count)]
+    pub type CreditNoteCount<T> = StorageValue<_, u64, ValueQuery>;
+
+    /// Credit note hash to ID mapping
+    #[pallet::storage]
+    #[pallet::getter(fn credit_note_by_hash)]
+    pub type CreditNoteByHash<T: Config> =
+        StorageMap<_, Blake2_128Concat, [u8; 32], u64, OptionQuery>;
+
+    /// Amount a client still owes over all its invoices: client => amount
+    #[pallet::storage]
+    pub type OutstandingBalance<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, BalanceOf<T>, ValueQuery>;
+
+    /// Total amount credited against an invoice: (client, invoice ID) => amount
+    #[pallet::storage]
+    #[pallet::getter(fn invoice_credits)]
+    pub type InvoiceCredits<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        T::AccountId, // Client
+        Twox64Concat,
+        u64, // Invoice ID
+        BalanceOf<T>,
+        ValueQuery,
+    >;
+
+    /// Open cancellation requests: (client, invoice ID) => request
+    #[pallet::storage]
+    #[pallet::getter(fn pending_cancellations)]
+    pub type PendingCancellations<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        T::AccountId, // Client
+        Twox64Concat,
+        u64, // Invoice ID
+        CancellationRequest<T>,
+        OptionQuery,
+    >;
+
+    /// Block until which no cancellation can be requested after a rejection:
+    /// (client, invoice ID) => block number
+    #[pallet::storage]
+    #[pallet::getter(fn cancellation_blocked_until)]
+    pub type CancellationBlockedUntil<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        T::AccountId, // Client
+        Twox64Concat,
+        u64, // Invoice ID
+        BlockNumberFor<T>,
+        OptionQuery,
+    >;
+
+    /// Open invoice disputes: (client, invoice ID) => dispute
+    #[pallet::storage]
+    #[pallet::getter(fn invoice_disputes)]
+    pub type InvoiceDisputes<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        T::AccountId, // Client
+        Twox64Concat,
+        u64, // Invoice ID
+        InvoiceDispute<T>,
+        OptionQuery,
+    >;
+
+    /// Open invoice challenges: (client, invoice ID) => challenge
+    #[pallet::storage]
+    #[pallet::getter(fn invoice_challenge)]
+    pub type InvoiceChallenges<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        T::AccountId, // Client
+        Twox64Concat,
+        u64, // Invoice ID
+        InvoiceChallenge<T>,
+        OptionQuery,
+    >;
+
+    /// Challenges timing out at a given block: block number => [(client, invoice_id)]
+    #[pallet::storage]
+    #[pallet::getter(fn challenge_timeouts)]
+    pub type ChallengeTimeouts<T: Config> = StorageMap<
+        _,
+        Twox64Concat,
+        BlockNumberFor<T>,
+        BoundedVec<(T::AccountId, u64), T::MaxChallengesPerBlock>,
+        ValueQuery,
+    >;
+
+    /// Invoices falling due at a given block: block number => [(client, invoice_id)]
+    #[pallet::storage]
+    #[pallet::getter(fn due_invoices)]
+    pub type DueInvoices<T: Config> = StorageMap<
+        _,
+        Twox64Concat,
+        BlockNumberFor<T>,
+        BoundedVec<(T::AccountId, u64), T::MaxDueInvoicesPerBlock>,
+        ValueQuery,
+    >;
+
+    /// Position of the overdue scan: (next block to process, index within that block)
+    #[pallet::storage]
+    pub type OverdueCursor<T: Config> = StorageValue<_, (BlockNumberFor<T>, u32), OptionQuery>;
+
+    /// Invoices expiring at a given block: block number => [(client, invoice_id)]
+    #[pallet::storage]
+    #[pallet::getter(fn expiring_invoices)]
+    pub type ExpiringInvoices<T: Config> = StorageMap<
+        _,
+        Twox64Concat,
+        BlockNumberFor<T>,
+        BoundedVec<(T::AccountId, u64), T::MaxExpiringInvoicesPerBlock>,
+        ValueQuery,
+    >;
+
+    /// Position of the expiry sweep: (next block to process, index within that block)
+    #[pallet::storage]
+    pub type ExpiryCursor<T: Config> = StorageValue<_, (BlockNumberFor<T>, u32), OptionQuery>;
+
+    /// Whether text fields are UTF-8 validated and line-ending normalized.
+    /// Defaults to off so chains created before this flag keep their hashing behaviour.
+    #[pallet::storage]
+    #[pallet::getter(fn normalize_text)]
+    pub type NormalizeText<T> = StorageValue<_, bool, ValueQuery>;
+
+    /// Active operator signing keys
+    #[pallet::storage]
+    #[pallet::getter(fn operator_keys)]
+    pub type OperatorKeys<T: Config> =
+        StorageValue<_, BoundedVec<sr25519::Public, T::MaxOperatorKeys>, ValueQuery>;
+
+    /// Operator key that authorized an invoice: invoice ID => key
+    #[pallet::storage]
+    #[pallet::getter(fn invoice_operator)]
+    pub type InvoiceOperator<T> = StorageMap<_, Twox64Concat, u64, sr25519::Public, OptionQuery>;
+
+    /// Creator signature over the invoice hash: invoice ID => signature
+    #[pallet::storage]
+    #[pallet::getter(fn invoice_signature)]
+    pub type InvoiceSignatures<T: Config> =
+        StorageMap<_, Twox64Concat, u64, BoundedVec<u8, T::MaxSignatureLength>, OptionQuery>;
+
+    /// Blake2-256 hashes of operator payloads already used (replay protection)
+    #[pallet::storage]
+    pub type UsedOperatorPayloads<T> = StorageMap<_, Identity, [u8; 32], (), OptionQuery>;
+
+    /// Accounts allowed to create invoices when `OpenCreation` is disabled
+    #[pallet::storage]
+    #[pallet::getter(fn authorized_creators)]
+    pub type AuthorizedCreators<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, (), OptionQuery>;
+
+    /// Outcome of the latest `batch_create_invoices` call of each account
+    #[pallet::storage]
+    #[pallet::getter(fn last_batch_outcome)]
+    pub type LastBatchOutcome<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, BatchOutcome, OptionQuery>;
+
+    /// Creation deposit still reserved from the creator of an invoice, by invoice ID
+    #[pallet::storage]
+    #[pallet::getter(fn invoice_deposit)]
+    pub type InvoiceDeposits<T: Config> =
+        StorageMap<_, Twox64Concat, u64, BalanceOf<T>, OptionQuery>;
+
+    /// Access grants on encrypted invoice payloads: (invoice hash, grantee) => grant
+    #[pallet::storage]
+    #[pallet::getter(fn access_grant)]
+    pub type AccessGrants<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        [u8; 32],
+        Blake2_128Concat,
+        T::AccountId,
+        GrantInfo<T>,
+        OptionQuery,
+    >;
+
+    /// Django records the ERP linked invoices to, by invoice ID
+    #[pallet::storage]
+    #[pallet::getter(fn offchain_link)]
+    pub type OffchainLinks<T: Config> =
+        StorageMap<_, Twox64Concat, u64, OffchainLink<T>, OptionQuery>;
+
+    /// Supporting documents of an invoice: (client, invoice ID) => attachments in order
+    #[pallet::storage]
+    #[pallet::getter(fn invoice_attachments)]
+    pub type InvoiceAttachments<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        T::AccountId, // Client
+        Twox64Concat,
+        u64, // Invoice ID
+        BoundedVec<Attachment<T>, T::MaxAttachmentsPerInvoice>,
+        ValueQuery,
+    >;
+
+    /// Invoices created in the current block, for the webhook: [(client, invoice_id)].
+    /// Only filled while `InvoiceWebhook` is enabled; cleared at the start of every block.
+    #[pallet::storage]
+    pub type NewInvoices<T: Config> = StorageValue<
+        _,
+        BoundedVec<(T::AccountId, u64), T::MaxWebhookInvoicesPerBlock>,
+        ValueQuery,
+    >;
+
+    /// Payments made in the current block: (count, total amount, digest hash).
+    /// Taken by `on_finalize`, so it never outlives the block.
+    #[pallet::storage]
+    pub type PaymentsThisBlock<T: Config> =
+        StorageValue<_, (u32, BalanceOf<T>, [u8; 32]), ValueQuery>;
+
+    /// Assignment history of an invoice: (client, invoice ID) => assignments in order
+    #[pallet::storage]
+    #[pallet::getter(fn invoice_assignments)]
+    pub type InvoiceAssignments<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        T::AccountId, // Client
+        Twox64Concat,
+        u64, // Invoice ID
+        BoundedVec<Assignment<T>, T::MaxAssignmentsPerInvoice>,
+        ValueQuery,
+    >;
+
+    /// Revenue of each creator: creator => (invoiced, collected).
+    /// Invoiced less collected is what the creator's clients still owe on open invoices.
+    #[pallet::storage]
+    #[pallet::getter(fn creator_stats)]
+    pub type CreatorStats<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, (BalanceOf<T>, BalanceOf<T>), ValueQuery>;
+
+    /// Number of `AccessGrants` under each invoice hash, at most `MaxGrantsPerInvoice`
+    #[pallet::storage]
+    #[pallet::getter(fn access_grant_count)]
+    pub type AccessGrantCount<T: Config> =
+        StorageMap<_, Blake2_128Concat, [u8; 32], u32, ValueQuery>;
+
+    #[pallet::genesis_config]
+    pub struct GenesisConfig<T: Config> {
+        /// Enable text normalization from genesis
+        pub normalize_text: bool,
+        /// Invoices imported at chain launch: (client, amount, metadata, created_by)
+        pub invoices: Vec<(T::AccountId, BalanceOf<T>, Vec<u8>, T::AccountId)>,
+        #[serde(skip)]
+        pub _config: sp_std::marker::PhantomData<T>,
+    }
+
+    impl<T: Config> Default for GenesisConfig<T> {
+        fn default() -> Self {
+            // New chains normalize text from the start
+            Self {
+                normalize_text: true,
+                invoices: Vec::new(),
+                _config: Default::default(),
+            }
+        }
+    }
+
+    #[pallet::genesis_build]
+    impl<T: Config> BuildGenesisConfig for GenesisConfig<T> {
+        fn build(&self) {
+            NormalizeText::<T>::put(self.normalize_text);
+
+            // Seeded invoices get IDs from 0 and the genesis block as timestamp, and go
+            // through the regular creation path so every index is populated
+            for (client, amount, metadata, created_by) in &self.invoices {
+                if let Err(e) = Pallet::<T>::do_create_invoice(
+                    created_by.clone(),
+                    client.clone(),
+                    *amount,
+                    Zero::zero(),
+                    Zero::zero(),
+                    metadata.clone(),
+                    None,
+                    Vec::new(),
+                    Vec::new(),
+                ) {
+                    panic!("Invalid genesis invoice: {:?}", e);
+                }
+            }
+        }
+    }
+
+    #[pallet::event]
+    #[pallet::generate_deposit(pub(super) fn deposit_event)]
+    pub enum Event<T: Config> {
+        /// Invoice created [invoice_id, client, amount, net_amount, invoice_hash, line_items]
+        InvoiceCreated {
+            invoice_id: u64,
+            client: T::AccountId,
+            amount: BalanceOf<T>,
+            net_amount: BalanceOf<T>,
+            invoice_hash: [u8; 32],
+            created_by: T::AccountId,
+            line_items: u32,
+        },
+        /// Invoice hash stored [invoice_hash, invoice_id]
+        InvoiceHashStored {
+            invoice_hash: [u8; 32],
+            invoice_id: u64,
+        },
+        /// Invoice paid [invoice_id, payer, payee, amount]
+        ///
+        /// `amount` is the total paid towards the invoice, earlier installments included.
+        InvoicePaid {
+            invoice_id: u64,
+            payer: T::AccountId,
+            payee: T::AccountId,
+            amount: BalanceOf<T>,
+        },
+        /// Invoice cancelled [invoice_id, client]
+        InvoiceCancelled {
+            invoice_id: u64,
+            client: T::AccountId,
+        },
+        /// Invoice became overdue [invoice_id, client]
+        InvoiceOverdue {
+            invoice_id: u64,
+            client: T::AccountId,
+        },
+        /// Unpaid invoice reached its expiry block [invoice_id, client]
+        InvoiceExpired {
+            invoice_id: u64,
+            client: T::AccountId,
+        },
+        /// Text normalization toggled [enabled]
+        TextNormalizationSet {
+            enabled: bool,
+        },
+        /// Operator-signed invoice accepted [invoice_id, operator]
+        InvoiceAuthorized {
+            invoice_id: u64,
+            operator: sr25519::Public,
+        },
+        /// Operator key registered [key]
+        OperatorKeyAdded {
+            key: sr25519::Public,
+        },
+        /// Operator key retired [key]
+        OperatorKeyRemoved {
+            key: sr25519::Public,
+        },
+        /// Installment paid towards an invoice [invoice_id, amount, remaining]
+        PartialPaymentRecorded {
+            invoice_id: u64,
+            amount: BalanceOf<T>,
+            remaining: BalanceOf<T>,
+        },
+        /// Settled, cancelled, expired or rejected invoices archived [client, count]
+        InvoicesArchived {
+            client: T::AccountId,
+            count: u32,
+        },
+        /// Invoice metadata updated and hash recomputed [invoice_id, old_hash, new_hash]
+        InvoiceRehashed {
+            invoice_id: u64,
+            old_hash: [u8; 32],
+            new_hash: [u8; 32],
+        },
+        /// Credit note issued against an invoice [credit_note_id, invoice_id, amount]
+        CreditNoteCreated {
+            credit_note_id: u64,
+            invoice_id: u64,
+            amount: BalanceOf<T>,
+        },
+        /// Invoice hash checked by an auditor [invoice_id, verifier, matched]
+        InvoiceVerified {
+            invoice_id: u64,
+            verifier: T::AccountId,
+            matched: bool,
+        },
+        /// Stored invoice hash differs from the hash of the stored fields
+        /// [invoice_id, stored_hash, computed_hash]
+        InvoiceHashStale {
+            invoice_id: u64,
+            stored_hash: [u8; 32],
+            computed_hash: [u8; 32],
+        },
+        /// Creator asked the client to agree to a cancellation
+        /// [invoice_id, client, reason_hash, deadline]
+        CancellationRequested {
+            invoice_id: u64,
+            client: T::AccountId,
+            reason_hash: [u8; 32],
+            deadline: BlockNumberFor<T>,
+        },
+        /// Client agreed to a requested cancellation [invoice_id, client]
+        CancellationApproved {
+            invoice_id: u64,
+            client: T::AccountId,
+        },
+        /// Client objected to a requested cancellation [invoice_id, client, blocked_until]
+        CancellationRejected {
+            invoice_id: u64,
+            client: T::AccountId,
+            blocked_until: BlockNumberFor<T>,
+        },
+        /// Cancellation finalized after the client did not answer in time [invoice_id, client]
+        CancellationTimedOut {
+            invoice_id: u64,
+            client: T::AccountId,
+        },
+        /// Client disputed an invoice [invoice_id, client, creator]
+        InvoiceDisputed {
+            invoice_id: u64,
+            client: T::AccountId,
+            creator: T::AccountId,
+        },
+        /// Invoice dispute resolved [invoice_id, client, resolution]
+        DisputeResolved {
+            invoice_id: u64,
+            client: T::AccountId,
+            resolution: DisputeResolution,
+        },
+        /// Account allowed to create invoices [creator]
+        CreatorAuthorized {
+            creator: T::AccountId,
+        },
+        /// Account no longer allowed to create invoices [creator]
+        CreatorRevoked {
+            creator: T::AccountId,
+        },
+        /// Invoices created in one batch [count, first_id, last_id]
+        InvoiceBatchCreated {
+            count: u32,
+            first_id: u64,
+            last_id: u64,
+        },
+        /// Batch entry rolled back and skipped [index, reason]
+        InvoiceBatchItemSkipped {
+            index: u32,
+            reason: SkipReason,
+        },
+        /// Batch call finished [who, outcome]
+        BatchCompleted {
+            who: T::AccountId,
+            outcome: BatchOutcome,
+        },
+        /// Creation deposit reserved [invoice_id, creator, amount]
+        InvoiceDepositReserved {
+            invoice_id: u64,
+            creator: T::AccountId,
+            amount: BalanceOf<T>,
+        },
+        /// Creation deposit returned to the creator [invoice_id, creator, amount]
+        InvoiceDepositReleased {
+            invoice_id: u64,
+            creator: T::AccountId,
+            amount: BalanceOf<T>,
+        },
+        /// Creation deposit slashed after a lost dispute [invoice_id, creator, amount]
+        InvoiceDepositSlashed {
+            invoice_id: u64,
+            creator: T::AccountId,
+            amount: BalanceOf<T>,
+        },
+        /// Access to an invoice payload granted [invoice_hash, grantee, granted_by,
+        /// key_wrap_hash]
+        AccessGranted {
+            invoice_hash: [u8; 32],
+            grantee: T::AccountId,
+            granted_by: T::AccountId,
+            key_wrap_hash: [u8; 32],
+        },
+        /// Access to an invoice payload revoked [invoice_hash, grantee, revoked_by]
+        AccessRevoked {
+            invoice_hash: [u8; 32],
+            grantee: T::AccountId,
+            revoked_by: T::AccountId,
+        },
+        /// Invoice deleted with its hash mapping and indices [invoice_id, client, invoice_hash]
+        InvoicePurged {
+            invoice_id: u64,
+            client: T::AccountId,
+            invoice_hash: [u8; 32],
+        },
+        /// Invoice challenged as fraudulent [invoice_id, client, challenger, evidence_hash,
+        /// bond, timeout_at]
+        InvoiceChallenged {
+            invoice_id: u64,
+            client: T::AccountId,
+            challenger: T::AccountId,
+            evidence_hash: [u8; 32],
+            bond: BalanceOf<T>,
+            timeout_at: BlockNumberFor<T>,
+        },
+        /// Invoice challenge closed [invoice_id, client, challenger, outcome]
+        ChallengeClosed {
+            invoice_id: u64,
+            client: T::AccountId,
+            challenger: T::AccountId,
+            outcome: ChallengeOutcome,
+        },
+        /// Challenge bond returned to the challenger [invoice_id, challenger, amount]
+        ChallengeBondReleased {
+            invoice_id: u64,
+            challenger: T::AccountId,
+            amount: BalanceOf<T>,
+        },
+        /// Bond of a dismissed challenge paid to the invoice creator [invoice_id, challenger,
+        /// creator, amount]
+        ChallengeBondSlashed {
+            invoice_id: u64,
+            challenger: T::AccountId,
+            creator: T::AccountId,
+            amount: BalanceOf<T>,
+        },
+        /// Creation deposit of an invoice found fraudulent paid to the challenger
+        /// [invoice_id, creator, challenger, amount]
+        InvoiceDepositAwarded {
+            invoice_id: u64,
+            creator: T::AccountId,
+            challenger: T::AccountId,
+            amount: BalanceOf<T>,
+        },
+        /// The off-chain ERP linked an invoice to its record [invoice_id, django_record_id,
+        /// invoice_hash]
+        OffchainLinkConfirmed {
+            invoice_id: u64,
+            django_record_id: u64,
+            invoice_hash: [u8; 32],
+        },
+        /// Supporting document attached to an invoice [invoice_id, doc_hash]
+        DocumentAttached { invoice_id: u64, doc_hash: [u8; 32] },
+        /// Invoice accepted by its client [invoice_id, client]
+        InvoiceAccepted { invoice_id: u64, client: T::AccountId },
+        /// Invoice rejected by its client [invoice_id, client, reason]
+        InvoiceRejected {
+            invoice_id: u64,
+            client: T::AccountId,
+            reason: BoundedVec<u8, T::MaxMetadataLength>,
+        },
+        /// Late fee of an invoice brought up to date [invoice_id, client, accrued_late_fee]
+        LateFeeAccrued {
+            invoice_id: u64,
+            client: T::AccountId,
+            accrued_late_fee: BalanceOf<T>,
+        },
+        /// Invoice abandoned by its creator claimed by the client, who received the creation
+        /// deposit [invoice_id, client, creator, compensation]
+        InvoiceAbandoned {
+            invoice_id: u64,
+            client: T::AccountId,
+            creator: T::AccountId,
+            compensation: BalanceOf<T>,
+        },
+        /// Right to payment of an invoice assigned to a new beneficiary [invoice_id, from, to]
+        InvoiceAssigned { invoice_id: u64, from: T::AccountId, to: T::AccountId },
+        /// Payments made in the block [count, total_amount, digest_hash]
+        PaymentsDigest {
+            count: u32,
+            total_amount: BalanceOf<T>,
+            digest_hash: [u8; 32],
+        },
+        /// A challenge could not be closed at its timeout and was left open for
+        /// `ChallengeJudgeOrigin` [invoice_id, client, error]
+        ChallengeTimeoutFailed {
+            invoice_id: u64,
+            client: T::AccountId,
+            error: DispatchError,
+        },
+    }
+
+    #[pallet::error]
+    pub enum Error<T> {
+        /// Too many invoices for this client
+        TooManyInvoices,
+        /// Metadata too long
+        MetadataTooLong,
+        /// Invoice not found
+        InvoiceNotFound,
+        /// Invalid invoice data
+        InvalidInvoiceData,
+        /// Arithmetic overflow
+        ArithmeticOverflow,
+        /// Invoice has already been paid
+        InvoiceAlreadyPaid,
+        /// Invoice has been cancelled
+        InvoiceCancelled,
+        /// Invoice was not created by the given account
+        CreatorMismatch,
+        /// Only the invoice creator can perform this action
+        NotInvoiceCreator,
+        /// Due block must be in the future
+        InvalidDueBlock,
+        /// Too many invoices already fall due at this block
+        TooManyInvoicesDue,
+        /// Text field is not valid UTF-8
+        InvalidUtf8,
+        /// Signature does not match any active operator key
+        InvalidOperatorSignature,
+        /// Operator payload has already been used
+        OperatorPayloadReplayed,
+        /// Operator key is already registered
+        OperatorKeyExists,
+        /// Operator key is not registered
+        OperatorKeyNotFound,
+        /// Too many operator keys registered
+        TooManyOperatorKeys,
+        /// Status change not allowed by the invoice lifecycle
+        InvalidStatusTransition,
+        /// Payment would exceed the outstanding invoice amount
+        PaymentExceedsAmount,
+        /// Payment amount must be greater than zero
+        ZeroPayment,
+        /// No invoice matched the archival criteria
+        NothingToArchive,
+        /// Operator-signed invoices cannot be edited
+        InvoiceOperatorSigned,
+        /// Another invoice is already stored under the same hash
+        DuplicateInvoiceHash,
+        /// Credit amount must be greater than zero
+        ZeroCredit,
+        /// Credit would exceed the outstanding invoice amount
+        CreditExceedsOutstanding,
+        /// Another credit note is already stored under the same hash
+        DuplicateCreditNoteHash,
+        /// External reference too long
+        ExternalRefTooLong,
+        /// Another invoice already uses this external reference
+        DuplicateExternalRef,
+        /// A cancellation is already pending for this invoice
+        CancellationAlreadyRequested,
+        /// No cancellation is pending for this invoice
+        NoPendingCancellation,
+        /// The client rejected a cancellation recently
+        CancellationCooldownActive,
+        /// The client can still answer the cancellation request
+        CancellationTimeoutNotReached,
+        /// The invoice is under dispute
+        InvoiceDisputed,
+        /// The invoice is not under dispute
+        InvoiceNotDisputed,
+        /// Dispute reason too long
+        DisputeReasonTooLong,
+        /// The batch holds no invoices
+        EmptyBatch,
+        /// The batch holds more than `MaxBatchSize` invoices
+        BatchTooLarge,
+        /// The account is not an authorized invoice creator
+        NotAuthorizedCreator,
+        /// The account is already an authorized invoice creator
+        CreatorAlreadyAuthorized,
+        /// More than `MaxLineItems` line items
+        TooManyLineItems,
+        /// Line item totals do not add up to the invoice amount
+        LineItemsMismatch,
+        /// The discount is larger than the invoice amount
+        DiscountExceedsAmount,
+        /// The creator signature does not verify against the invoice hash
+        InvalidSignature,
+        /// Invoices can only be created for clients with an active DID
+        ClientHasNoActiveDid,
+        /// Insufficient free balance for the invoice creation deposit
+        InsufficientDeposit,
+        /// Expiry block must be in the future
+        InvalidExpiryBlock,
+        /// Too many invoices already expire at this block
+        TooManyInvoicesExpiring,
+        /// Invoice has expired
+        InvoiceExpired,
+        /// No invoice is stored under this hash
+        UnknownInvoiceHash,
+        /// Only the creator or client of the invoice can perform this action
+        NotInvoiceParty,
+        /// The account has no access grant on this invoice
+        GrantNotFound,
+        /// Paid invoices are only purged with `force`
+        PaidInvoiceRequiresForce,
+        /// The invoice is challenged as fraudulent
+        InvoiceChallenged,
+        /// The invoice has no open challenge
+        InvoiceNotChallenged,
+        /// Creators cannot challenge their own invoices
+        CannotChallengeOwnInvoice,
+        /// Insufficient free balance for the challenge bond
+        InsufficientChallengeBond,
+        /// Too many challenges already time out at this block
+        TooManyChallengesTimingOut,
+        /// The proof is not the hash of the invoice
+        LinkProofMismatch,
+        /// The invoice's link to the ERP is already confirmed
+        OffchainLinkAlreadyConfirmed,
+        /// Attachment label exceeds `MaxAttachmentLabelLength`
+        AttachmentLabelTooLong,
+        /// The document is already attached to the invoice
+        DuplicateAttachment,
+        /// The invoice already holds `MaxAttachmentsPerInvoice` attachments
+        TooManyAttachments,
+        /// The invoice has not been accepted by its client
+        InvoiceNotAccepted,
+        /// The invoice has already been accepted by its client
+        InvoiceAlreadyAccepted,
+        /// The invoice was rejected by its client
+        InvoiceRejected,
+        /// The invoice's creator was active within `AbandonThreshold`, or claims are disabled
+        InvoiceNotAbandoned,
+        /// The signer is not the invoice's beneficiary
+        NotInvoiceBeneficiary,
+        /// The account is already the invoice's beneficiary
+        AlreadyBeneficiary,
+        /// The invoice was already assigned `MaxAssignmentsPerInvoice` times
+        TooManyAssignments,
+        /// The invoice already has `MaxGrantsPerInvoice` access grants
+        TooManyGrants,
+    }
+
+    #[pallet::hooks]
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+        fn on_initialize(now: BlockNumberFor<T>) -> Weight {
+            Self::process_due_invoices(now, T::OverdueCheckWeight::get())
+                .saturating_add(Self::process_challenge_timeouts(now))
+                .saturating_add(crate::webhook::clear_index::<T>())
+                // Taking `PaymentsThisBlock` in `on_finalize`
+                .saturating_add(T::DbWeight::get().reads_writes(1, 1))
+        }
+
+        fn on_finalize(_now: BlockNumberFor<T>) {
+            let (count, total_amount, digest_hash) = PaymentsThisBlock::<T>::take();
+            if count > 0 {
+                Self::deposit_event(Event::PaymentsDigest { count, total_amount, digest_hash });
+            }
+        }
+
+        fn offchain_worker(now: BlockNumberFor<T>) {
+            crate::webhook::offchain_worker::<T>(now)
+        }
+
+        fn on_idle(now: BlockNumberFor<T>, remaining_weight: Weight) -> Weight {
+            Self::process_expiring_invoices(now, remaining_weight)
+        }
+
+        fn on_runtime_upgrade() -> Weight {
+            crate::migrations::v1::migrate::<T>()
+                .saturating_add(crate::migrations::v2::migrate::<T>())
+                .saturating_add(crate::migrations::v3::migrate::<T>())
+                .saturating_add(crate::migrations::v4::migrate::<T>())
+                .saturating_add(crate::migrations::v5::migrate::<T>())
+                .saturating_add(crate::migrations::v6::migrate::<T>())
+                .saturating_add(crate::migrations::v7::migrate::<T>())
+                .saturating_add(crate::migrations::v8::migrate::<T>())
+                .saturating_add(crate::migrations::v9::migrate::<T>())
+                .saturating_add(crate::migrations::v10::migrate::<T>())
+                .saturating_add(crate::migrations::v11::migrate::<T>())
+                .saturating_add(crate::migrations::v12::migrate::<T>())
+                .saturating_add(crate::migrations::v13::migrate::<T>())
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn pre_upgrade() -> Result<Vec<u8>, sp_runtime::TryRuntimeError> {
+            Ok(crate::migrations::checks::pre_upgrade::<T>())
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn post_upgrade(state: Vec<u8>) -> Result<(), sp_runtime::TryRuntimeError> {
+            crate::migrations::checks::post_upgrade::<T>(state).map_err(Into::into)
+        }
+    }
+
+    #[pallet::validate_unsigned]
+    impl<T: Config> ValidateUnsigned for Pallet<T> {
+        type Call = Call<T>;
+
+        fn validate_unsigned(_source: TransactionSource, call: &Self::Call) -> TransactionValidity {
+            let Call::confirm_offchain_link { invoice_id, proof_hash, .. } = call else {
+                return InvalidTransaction::Call.into();
+            };
+
+            match Self::ensure_link_confirmable(*invoice_id, proof_hash) {
+                Err(Error::<T>::LinkProofMismatch) => return InvalidTransaction::BadProof.into(),
+                Err(_) => return InvalidTransaction::Stale.into(),
+                Ok(()) => {},
+            }
+
+            ValidTransaction::with_tag_prefix("LedgerOffchainLink")
+                .priority(T::UnsignedPriority::get())
+                .and_provides(invoice_id)
+                .longevity(LINK_CONFIRMATION_LONGEVITY)
+                .propagate(true)
+                .build()
+        }
+    }
+
+    #[pallet::call]
+    impl<T: Config> Pallet<T> {
+        /// Create a new invoice
+        ///
+        /// # Arguments
+        /// * `origin` - Transaction origin (invoice creator)
+        /// * `client` - Client account ID
+        /// * `amount` - Invoice amount before tax and discount
+        /// * `tax` - Tax added to the amount, e.g. VAT (zero for none)
+        /// * `discount` - Discount taken off the amount (zero for none)
+        /// * `metadata` - Invoice metadata (e.g., invoice number, description, JSON data)
+        /// * `due_block` - Block by which the invoice should be paid (optional)
+        /// * `expires_at` - Block at which the invoice expires if still unpaid (optional)
+        /// * `line_items` - Line items whose totals add up to `amount` (empty for none)
+        /// * `signature` - sr25519 or ed25519 signature of the caller over the invoice hash
+        ///   (optional)
+        ///
+        /// # Returns
+        /// * `DispatchResultWithPostInfo` - Success with the weight actually used, or error
+        ///
+        /// # Events
+        /// * `InvoiceCreated` - Emitted when invoice is successfully created
+        /// * `InvoiceHashStored` - Emitted when invoice hash is stored
+        /// * `InvoiceDepositReserved` - Emitted when the creation deposit is reserved
+        ///
+        /// # Errors
+        /// * `NotAuthorizedCreator` - Caller is not in `AuthorizedCreators` (unless
+        ///   `OpenCreation` is enabled)
+        /// * `ClientHasNoActiveDid` - `DidProvider` reports no active DID for the client
+        /// * `InsufficientDeposit` - Caller cannot reserve `InvoiceDeposit`
+        /// * `InvalidUtf8` - Metadata is not valid UTF-8 (when `NormalizeText` is enabled)
+        /// * `InvalidDueBlock` - Due block is not in the future
+        /// * `TooManyInvoicesDue` - The due block already holds `MaxDueInvoicesPerBlock` invoices
+        /// * `InvalidExpiryBlock` - Expiry block is not in the future
+        /// * `TooManyInvoicesExpiring` - The expiry block already holds
+        ///   `MaxExpiringInvoicesPerBlock` invoices
+        /// * `DuplicateInvoiceHash` - Another invoice already has the same hash
+        /// * `TooManyLineItems` - More than `MaxLineItems` line items
+        /// * `LineItemsMismatch` - Line item totals do not add up to `amount`
+        /// * `DiscountExceedsAmount` - `discount` is larger than `amount`
+        /// * `InvalidSignature` - `signature` is not the caller's signature of the invoice hash
+        ///
+        /// # Example
+        /// ```ignore
+        /// create_invoice(
+        ///     origin,
+        ///     client_account,
+        ///     1000000,
+        ///     200000,
+        ///     0,
+        ///     b"INV-2025-001|Client XYZ|Net 30".to_vec(),
+        ///     Some(current_block + 30 * DAYS),
+        ///     Some(current_block + 365 * DAYS),
+        ///     vec![],
+        ///     None
+        /// )
+        /// ```
+        #[pallet::call_index(0)]
+        #[pallet::weight(Pallet::<T>::create_invoice_weight(
+            metadata.len() as u32,
+            line_items.len() as u32,
+            signature.is_some(),
+            false,
+        ))]
+        pub fn create_invoice(
+            origin: OriginFor<T>,
+            client: T::AccountId,
+            amount: BalanceOf<T>,
+            tax: BalanceOf<T>,
+            discount: BalanceOf<T>,
+            metadata: Vec<u8>,
+            due_block: Option<BlockNumberFor<T>>,
+            expires_at: Option<BlockNumberFor<T>>,
+            line_items: Vec<LineItem<T>>,
+            signature: Option<BoundedVec<u8, T::MaxSignatureLength>>,
+        ) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+
+            Self::do_create_invoice_call(
+                who,
+                client,
+                amount,
+                tax,
+                discount,
+                metadata,
+                due_block,
+                expires_at,
+                line_items,
+                signature,
+                Vec::new(),
+            )
+        }
+
+        // Call index 1 was `get_invoices`, superseded by `pallet-access-log`. Keep it reserved.
+
+        /// Pay an invoice
+        ///
+        /// Transfers the outstanding invoice amount (the net amount after tax and discount plus
+        /// the late fee accrued so far, less installments and credit notes) from the client to
+        /// the invoice's beneficiary and marks the invoice as paid.
+        ///
+        /// # Arguments
+        /// * `origin` - Transaction origin (the invoiced client)
+        /// * `creator` - Account that created the invoice
+        /// * `invoice_id` - ID of the invoice to pay
+        ///
+        /// # Returns
+        /// * `DispatchResult` - Success or error
+        ///
+        /// # Events
+        /// * `LateFeeAccrued` - Emitted first if the late fee grew since it was last charged
+        /// * `InvoicePaid` - Emitted with the total paid when the invoice is settled
+        ///
+        /// # Errors
+        /// * `InvoiceNotFound` - The client has no invoice with this ID
+        /// * `CreatorMismatch` - The invoice was not created by `creator`
+        /// * `InvoiceAlreadyPaid` - The invoice has already been settled
+        /// * `InvoiceCancelled` - The invoice has been cancelled
+        /// * `InvoiceDisputed` - The invoice is under dispute
+        /// * `InvoiceNotAccepted` - `RequireAcceptance` is on and the client has not accepted
+        #[pallet::call_index(2)]
+        #[pallet::weight(T::WeightInfo::pay_invoice())]
+        pub fn pay_invoice(
+            origin: OriginFor<T>,
+            creator: T::AccountId,
+            invoice_id: u64,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let (payee, amount) = Invoices::<T>::try_mutate(
+                &who,
+                invoice_id,
+                |maybe_invoice| -> Result<_, DispatchError> {
+                    let invoice = maybe_invoice.as_mut().ok_or(Error::<T>::InvoiceNotFound)?;
+
+                    ensure!(invoice.created_by == creator, Error::<T>::CreatorMismatch);
+                    Self::ensure_not_disputed(&who, invoice_id)?;
+                    Self::ensure_accepted(invoice)?;
+                    Self::accrue_late_fee(invoice)?;
+                    Self::set_invoice_status(invoice, InvoiceStatus::Paid)?;
+
+                    // Only what is still outstanding after installments and credits, late fee
+                    // included
+                    let remaining = Self::outstanding(invoice);
+
+                    // A failed transfer discards the status change along with the mutation
+                    T::Currency::transfer(
+                        &who,
+                        &invoice.beneficiary,
+                        remaining,
+                        ExistenceRequirement::KeepAlive,
+                    )?;
+                    invoice.paid_amount = invoice.paid_amount.saturating_add(remaining);
+                    Self::reduce_outstanding_balance(&who, remaining);
+                    Self::note_collected(&invoice.created_by, remaining);
+                    Self::note_payment(invoice_id, remaining);
+
+                    Ok((invoice.beneficiary.clone(), invoice.paid_amount))
+                },
+            )?;
+
+            Self::deposit_event(Event::InvoicePaid {
+                invoice_id,
+                payer: who,
+                payee,
+                amount,
+            });
+
+            Ok(())
+        }
+
+        /// Cancel an unpaid invoice
+        ///
+        /// The invoice stays in storage with a cancelled status, and its hash
+        /// mapping is kept so the cancelled invoice can still be proven to exist.
+        ///
+        /// # Arguments
+        /// * `origin` - Transaction origin (must be the invoice creator)
+        /// * `client` - Client account the invoice was issued to
+        /// * `invoice_id` - ID of the invoice to cancel
+        ///
+        /// # Returns
+        /// * `DispatchResult` - Success or error
+        ///
+        /// # Events
+        /// * `InvoiceCancelled` - Emitted when the invoice is cancelled
+        ///
+        /// # Errors
+        /// * `InvoiceNotFound` - The client has no invoice with this ID
+        /// * `NotInvoiceCreator` - Origin did not create the invoice
+        /// * `InvoiceAlreadyPaid` - Settled invoices cannot be cancelled
+        /// * `InvoiceCancelled` - The invoice is already cancelled
+        /// * `InvoiceDisputed` - The invoice is under dispute
+        /// * `InvoiceChallenged` - The invoice is challenged as fraudulent
+        #[pallet::call_index(3)]
+        #[pallet::weight(10_000)]
+        pub fn cancel_invoice(
+            origin: OriginFor<T>,
+            client: T::AccountId,
+            invoice_id: u64,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            Invoices::<T>::try_mutate(&client, invoice_id, |maybe_invoice| -> DispatchResult {
+                let invoice = maybe_invoice.as_mut().ok_or(Error::<T>::InvoiceNotFound)?;
+
+                ensure!(invoice.created_by == who, Error::<T>::NotInvoiceCreator);
+                Self::ensure_not_disputed(&client, invoice_id)?;
+                Self::ensure_not_challenged(invoice)?;
+                Self::set_invoice_status(invoice, InvoiceStatus::Cancelled)?;
+
+                Ok(())
+            })?;
+
+            Self::deposit_event(Event::InvoiceCancelled { invoice_id, client });
+
+            Ok(())
+        }
+
+        /// Enable or disable text normalization
+        ///
+        /// # Arguments
+        /// * `origin` - Must be root
+        /// * `enabled` - Whether text fields are validated and normalized
+        ///
+        /// # Events
+        /// * `TextNormalizationSet` - Emitted with the new setting
+        #[pallet::call_index(4)]
+        #[pallet::weight(5_000)]
+        pub fn set_text_normalization(origin: OriginFor<T>, enabled: bool) -> DispatchResult {
+            ensure_root(origin)?;
+
+            NormalizeText::<T>::put(enabled);
+
+            Self::deposit_event(Event::TextNormalizationSet { enabled });
+
+            Ok(())
+        }
+
+        /// Create an invoice authorized by an operator signature
+        ///
+        /// # Arguments
+        /// * `origin` - Transaction origin (submitter, recorded as invoice creator)
+        /// * `client` - Client account ID
+        /// * `amount` - Invoice amount
+        /// * `metadata` - Invoice metadata, exactly as signed
+        /// * `operator_sig` - sr25519 signature over `operator_payload(client, amount, metadata)`
+        ///
+        /// # Returns
+        /// * `DispatchResultWithPostInfo` - Success with the weight actually used, or error
+        ///
+        /// # Events
+        /// * `InvoiceCreated` - Emitted when invoice is successfully created
+        /// * `InvoiceHashStored` - Emitted when invoice hash is stored
+        /// * `InvoiceAuthorized` - Emitted with the authorizing operator key
+        ///
+        /// # Errors
+        /// * `InvalidOperatorSignature` - Signature is malformed or matches no active key
+        /// * `OperatorPayloadReplayed` - The same payload was already used
+        /// * `DuplicateInvoiceHash` - Another invoice already has the same hash
+        /// * `InsufficientDeposit` - Caller cannot reserve `InvoiceDeposit`
+        #[pallet::call_index(5)]
+        #[pallet::weight(Pallet::<T>::create_signed_invoice_weight(
+            metadata.len() as u32,
+            T::MaxOperatorKeys::get(),
+        ))]
+        pub fn create_signed_invoice(
+            origin: OriginFor<T>,
+            client: T::AccountId,
+            amount: BalanceOf<T>,
+            metadata: Vec<u8>,
+            operator_sig: Vec<u8>,
+        ) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+
+            let payload = Self::operator_payload(&client, &amount, &metadata);
+            let (operator, keys_tried) = Self::verify_operator_signature(&payload, &operator_sig)
+                .ok_or(Error::<T>::InvalidOperatorSignature)?;
+
+            let payload_hash = sp_io::hashing::blake2_256(&payload);
+            ensure!(
+                !UsedOperatorPayloads::<T>::contains_key(payload_hash),
+                Error::<T>::OperatorPayloadReplayed
+            );
+
+            let invoice_id = Self::do_create_invoice(
+                who.clone(),
+                client,
+                amount,
+                Zero::zero(),
+                Zero::zero(),
+                metadata,
+                None,
+                Vec::new(),
+                Vec::new(),
+            )?;
+            Self::reserve_deposit(&who, invoice_id)?;
+
+            UsedOperatorPayloads::<T>::insert(payload_hash, ());
+            InvoiceOperator::<T>::insert(invoice_id, operator.clone());
+
+            Self::deposit_event(Event::InvoiceAuthorized { invoice_id, operator });
+
+            let stored = Invoices::<T>::get(&client, invoice_id)
+                .map_or(0, |invoice| invoice.metadata.len() as u32);
+            Ok(Some(Self::create_signed_invoice_weight(stored, keys_tried)).into())
+        }
+
+        /// Register an operator signing key
+        ///
+        /// # Arguments
+        /// * `origin` - Must satisfy `AdminOrigin`
+        /// * `key` - sr25519 public key
+        ///
+        /// # Events
+        /// * `OperatorKeyAdded` - Emitted when the key is registered
+        ///
+        /// # Errors
+        /// * `OperatorKeyExists` - Key is already registered
+        /// * `TooManyOperatorKeys` - `MaxOperatorKeys` reached
+        #[pallet::call_index(6)]
+        #[pallet::weight(5_000)]
+        pub fn add_operator_key(origin: OriginFor<T>, key: sr25519::Public) -> DispatchResult {
+            T::AdminOrigin::ensure_origin(origin)?;
+
+            OperatorKeys::<T>::try_mutate(|keys| -> DispatchResult {
+                ensure!(!keys.contains(&key), Error::<T>::OperatorKeyExists);
+                keys.try_push(key.clone())
+                    .map_err(|_| Error::<T>::TooManyOperatorKeys)?;
+                Ok(())
+            })?;
+
+            Self::deposit_event(Event::OperatorKeyAdded { key });
+
+            Ok(())
+        }
+
+        /// Retire an operator signing key
+        ///
+        /// Invoices already authorized by the key remain valid.
+        ///
+        /// # Arguments
+        /// * `origin` - Must satisfy `AdminOrigin`
+        /// * `key` - sr25519 public key
+        ///
+        /// # Events
+        /// * `OperatorKeyRemoved` - Emitted when the key is retired
+        ///
+        /// # Errors
+        /// * `OperatorKeyNotFound` - Key is not registered
+        #[pallet::call_index(7)]
+        #[pallet::weight(5_000)]
+        pub fn remove_operator_key(origin: OriginFor<T>, key: sr25519::Public) -> DispatchResult {
+            T::AdminOrigin::ensure_origin(origin)?;
+
+            OperatorKeys::<T>::try_mutate(|keys| -> DispatchResult {
+                let index = keys
+                    .iter()
+                    .position(|k| k == &key)
+                    .ok_or(Error::<T>::OperatorKeyNotFound)?;
+                keys.remove(index);
+                Ok(())
+            })?;
+
+            Self::deposit_event(Event::OperatorKeyRemoved { key });
+
+            Ok(())
+        }
+
+        /// Record a (partial) payment towards an invoice
+        ///
+        /// Transfers `amount` from the payer to the invoice's beneficiary and adds it to the
+        /// invoice's `paid_amount`. The invoice becomes `Paid` once it is fully covered by
+        /// payments and credit notes.
+        ///
+        /// # Arguments
+        /// * `origin` - Transaction origin (payer)
+        /// * `client` - Client the invoice was issued to
+        /// * `invoice_id` - ID of the invoice
+        /// * `amount` - Installment amount
+        ///
+        /// # Returns
+        /// * `DispatchResult` - Success or error
+        ///
+        /// # Events
+        /// * `LateFeeAccrued` - Emitted first if the late fee grew since it was last charged
+        /// * `PartialPaymentRecorded` - Emitted with the amount still outstanding
+        /// * `InvoicePaid` - Emitted as well, with the total paid, when the installment settles
+        ///   the invoice
+        ///
+        /// # Errors
+        /// * `ZeroPayment` - `amount` is zero
+        /// * `InvoiceNotFound` - The client has no invoice with this ID
+        /// * `InvoiceAlreadyPaid` - The invoice has already been settled
+        /// * `InvoiceCancelled` - The invoice has been cancelled
+        /// * `InvoiceDisputed` - The invoice is under dispute
+        /// * `InvoiceNotAccepted` - `RequireAcceptance` is on and the client has not accepted
+        /// * `PaymentExceedsAmount` - The installment exceeds the outstanding amount
+        #[pallet::call_index(8)]
+        #[pallet::weight(T::WeightInfo::record_payment())]
+        pub fn record_payment(
+            origin: OriginFor<T>,
+            client: T::AccountId,
+            invoice_id: u64,
+            amount: BalanceOf<T>,
+        ) -> DispatchResult {
+            let payer = ensure_signed(origin)?;
+
+            ensure!(!amount.is_zero(), Error::<T>::ZeroPayment);
+
+            let (payee, remaining, total_paid) = Invoices::<T>::try_mutate(
+                &client,
+                invoice_id,
+                |maybe_invoice| -> Result<_, DispatchError> {
+                    let invoice = maybe_invoice.as_mut().ok_or(Error::<T>::InvoiceNotFound)?;
+                    Self::ensure_payable(invoice)?;
+                    Self::ensure_accepted(invoice)?;
+                    Self::accrue_late_fee(invoice)?;
+
+                    ensure!(
+                        amount <= Self::outstanding(invoice),
+                        Error::<T>::PaymentExceedsAmount
+                    );
+                    let paid_amount = invoice
+                        .paid_amount
+                        .checked_add(&amount)
+                        .ok_or(Error::<T>::ArithmeticOverflow)?;
+
+                    T::Currency::transfer(
+                        &payer,
+                        &invoice.beneficiary,
+                        amount,
+                        ExistenceRequirement::KeepAlive,
+                    )?;
+                    invoice.paid_amount = paid_amount;
+                    Self::reduce_outstanding_balance(&client, amount);
+                    Self::note_collected(&invoice.created_by, amount);
+                    Self::note_payment(invoice_id, amount);
+
+                    let remaining = Self::outstanding(invoice);
+                    if remaining.is_zero() {
+                        Self::set_invoice_status(invoice, InvoiceStatus::Paid)?;
+                    }
+
+                    Ok((invoice.beneficiary.clone(), remaining, invoice.paid_amount))
+                },
+            )?;
+
+            Self::deposit_event(Event::PartialPaymentRecorded {
+                invoice_id,
+                amount,
+                remaining,
+            });
+
+            if remaining.is_zero() {
+                Self::deposit_event(Event::InvoicePaid {
+                    invoice_id,
+                    payer,
+                    payee,
+                    amount: total_paid,
+                });
+            }
+
+            Ok(())
+        }
+
+        /// Archive settled, cancelled, expired or rejected invoices of a client
+        ///
+        /// Removes every `Paid`, `Cancelled`, `Expired` or `Rejected` invoice of `client` with
+        /// an ID below `up_to_id`, together with every record keyed by it, and keeps only its
+        /// hash in `ArchivedInvoiceHashes`, freeing room under `MaxInvoicesPerClient`. Pending
+        /// and overdue invoices are never archived, nor are invoices under dispute. A signed
+        /// origin only archives invoices it created; root archives any. At most
+        /// `MaxArchivedPerCall` invoices are archived per call, the lowest positions first;
+        /// the rest stay for a later call.
+        ///
+        /// # Arguments
+        /// * `origin` - Invoice creator or root
+        /// * `client` - Client whose invoices are archived
+        /// * `up_to_id` - Exclusive upper bound on archived invoice IDs
+        ///
+        /// # Returns
+        /// * `DispatchResultWithPostInfo` - Success with the weight actually used, or error
+        ///
+        /// # Events
+        /// * `InvoicesArchived` - Emitted with the number of archived invoices
+        ///
+        /// # Errors
+        /// * `NothingToArchive` - No invoice matched
+        #[pallet::call_index(9)]
+        #[pallet::weight(T::WeightInfo::archive_invoices(
+            T::MaxInvoicesPerClient::get(),
+            T::MaxArchivedPerCall::get(),
+            T::MaxArchivedPerCall::get().saturating_mul(T::MaxGrantsPerInvoice::get()),
+        ))]
+        pub fn archive_invoices(
+            origin: OriginFor<T>,
+            client: T::AccountId,
+            up_to_id: u64,
+        ) -> DispatchResultWithPostInfo {
+            let maybe_who = frame_system::ensure_signed_or_root(origin)?;
+            let may_archive = |invoice: &Invoice<T>| {
+                invoice.id < up_to_id &&
+                    INVOICE_STATUS.is_terminal(&invoice.status) &&
+                    !InvoiceDisputes::<T>::contains_key(&invoice.client, invoice.id) &&
+                    match &maybe_who {
+                        Some(who) => who == &invoice.created_by,
+                        None => true,
+                    }
+            };
+
+            let count = ClientInvoiceCount::<T>::get(&client);
+            let mut kept: Vec<u64> = Vec::new();
+            let mut archived = 0u32;
+            let mut grants = 0u32;
+
+            for position in 0..count {
+                let Some(invoice_id) = ClientInvoiceIds::<T>::take(&client, position) else {
+                    continue
+                };
+                match Invoices::<T>::get(&client, invoice_id) {
+                    Some(invoice)
+                        if archived < T::MaxArchivedPerCall::get() && may_archive(&invoice) =>
+                    {
+                        grants = grants
+                            .saturating_add(AccessGrantCount::<T>::get(invoice.invoice_hash));
+                        Self::remove_invoice_records(&invoice);
+                        ArchivedInvoiceHashes::<T>::insert(
+                            &client,
+                            invoice_id,
+                            invoice.invoice_hash,
+                        );
+                        archived = archived.saturating_add(1);
+                    }
+                    _ => kept.push(invoice_id),
+                }
+            }
+
+            ensure!(archived > 0, Error::<T>::NothingToArchive);
+
+            // Re-pack the per-client index so positions stay dense and ordered
+            for (position, invoice_id) in kept.iter().enumerate() {
+                ClientInvoiceIds::<T>::insert(&client, position as u32, invoice_id);
+            }
+            ClientInvoiceCount::<T>::insert(&client, kept.len() as u32);
+
+            Self::deposit_event(Event::InvoicesArchived {
+                client,
+                count: archived,
+            });
+
+            Ok(Some(T::WeightInfo::archive_invoices(count, archived, grants)).into())
+        }
+
+        /// Replace the metadata of an unpaid invoice
+        ///
+        /// The invoice hash is recomputed and `InvoiceByHash` moves from the old hash to the
+        /// new one, along with the access grants on the invoice. Invoices authorized by an
+        /// operator signature cannot be edited, since the signature covers the original
+        /// metadata.
+        ///
+        /// # Arguments
+        /// * `origin` - Transaction origin (must be the invoice creator)
+        /// * `client` - Client the invoice was issued to
+        /// * `invoice_id` - ID of the invoice
+        /// * `new_metadata` - Replacement metadata
+        ///
+        /// # Returns
+        /// * `DispatchResultWithPostInfo` - Success with the weight actually used, or error
+        ///
+        /// # Events
+        /// * `InvoiceRehashed` - Emitted with the old and new invoice hash
+        ///
+        /// # Errors
+        /// * `InvoiceNotFound` - The client has no invoice with this ID
+        /// * `NotInvoiceCreator` - Origin did not create the invoice
+        /// * `InvoiceAlreadyPaid` - The invoice has already been settled
+        /// * `InvoiceCancelled` - The invoice has been cancelled
+        /// * `InvoiceDisputed` - The invoice is under dispute
+        /// * `InvoiceOperatorSigned` - The invoice was created with `create_signed_invoice`
+        /// * `InvalidUtf8` - Metadata is not valid UTF-8 (when `NormalizeText` is enabled)
+        /// * `MetadataTooLong` - Metadata exceeds `MaxMetadataLength`
+        /// * `DuplicateInvoiceHash` - Another invoice already has the new hash
+        #[pallet::call_index(10)]
+        #[pallet::weight(Pallet::<T>::update_invoice_metadata_weight(
+            new_metadata.len() as u32,
+            T::MaxGrantsPerInvoice::get(),
+        ))]
+        pub fn update_invoice_metadata(
+            origin: OriginFor<T>,
+            client: T::AccountId,
+            invoice_id: u64,
+            new_metadata: Vec<u8>,
+        ) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+
+            let new_metadata = Self::sanitize_text(new_metadata)?;
+            let bounded_metadata: BoundedVec<u8, T::MaxMetadataLength> = new_metadata
+                .try_into()
+                .map_err(|_| Error::<T>::MetadataTooLong)?;
+            let stored_len = bounded_metadata.len() as u32;
+
+            ensure!(
+                !InvoiceOperator::<T>::contains_key(invoice_id),
+                Error::<T>::InvoiceOperatorSigned
+            );
+
+            let (old_hash, new_hash) = Invoices::<T>::try_mutate(
+                &client,
+                invoice_id,
+                |maybe_invoice| -> Result<_, DispatchError> {
+                    let invoice = maybe_invoice.as_mut().ok_or(Error::<T>::InvoiceNotFound)?;
+
+                    ensure!(invoice.created_by == who, Error::<T>::NotInvoiceCreator);
+                    Self::ensure_payable(invoice)?;
+                    invoice.last_activity = frame_system::Pallet::<T>::block_number();
+
+                    let old_hash = invoice.invoice_hash;
+                    invoice.metadata = bounded_metadata;
+                    invoice.invoice_hash = invoice.calculate_hash();
+                    ensure!(
+                        invoice.invoice_hash == old_hash
+                            || !InvoiceByHash::<T>::contains_key(invoice.invoice_hash),
+                        Error::<T>::DuplicateInvoiceHash
+                    );
+
+                    Ok((old_hash, invoice.invoice_hash))
+                },
+            )?;
+
+            // Move the hash lookup so the old hash does not dangle
+            InvoiceByHash::<T>::remove(old_hash);
+            InvoiceByHash::<T>::insert(new_hash, invoice_id);
+
+            // Grants are keyed by hash too; left behind, grantees would lose access and the
+            // grants would outlive the invoice
+            let mut grants = 0;
+            if old_hash != new_hash {
+                grants = AccessGrantCount::<T>::take(old_hash);
+                let moved: Vec<_> = AccessGrants::<T>::drain_prefix(old_hash).collect();
+                for (grantee, grant) in moved {
+                    AccessGrants::<T>::insert(new_hash, grantee, grant);
+                }
+                if grants > 0 {
+                    AccessGrantCount::<T>::insert(new_hash, grants);
+                }
+            }
+
+            Self::deposit_event(Event::InvoiceRehashed {
+                invoice_id,
+                old_hash,
+                new_hash,
+            });
+
+            Ok(Some(Self::update_invoice_metadata_weight(stored_len, grants)).into())
+        }
+
+        /// Issue a credit note against an unpaid invoice
+        ///
+        /// The original invoice is left untouched; the credited amount is added to
+        /// `InvoiceCredits` and deducted from what the client still has to pay. Crediting the
+        /// full outstanding amount settles the invoice.
+        ///
+        /// # Arguments
+        /// * `origin` - Transaction origin (must be the invoice creator)
+        /// * `client` - Client the invoice was issued to
+        /// * `original_invoice_id` - ID of the credited invoice
+        /// * `amount` - Credited amount
+        /// * `metadata` - Credit note metadata (e.g., credit note number, reason)
+        ///
+        /// # Returns
+        /// * `DispatchResult` - Success or error
+        ///
+        /// # Events
+        /// * `CreditNoteCreated` - Emitted when the credit note is stored
+        ///
+        /// # Errors
+        /// * `ZeroCredit` - `amount` is zero
+        /// * `InvalidUtf8` - Metadata is not valid UTF-8 (when `NormalizeText` is enabled)
+        /// * `MetadataTooLong` - Metadata exceeds `MaxMetadataLength`
+        /// * `InvoiceNotFound` - The client has no invoice with this ID
+        /// * `NotInvoiceCreator` - Origin did not create the invoice
+        /// * `InvoiceAlreadyPaid` - The invoice has already been settled
+        /// * `InvoiceCancelled` - The invoice has been cancelled
+        /// * `InvoiceDisputed` - The invoice is under dispute
+        /// * `CreditExceedsOutstanding` - The credit exceeds the outstanding amount
+        /// * `DuplicateCreditNoteHash` - Another credit note already has the same hash
+        #[pallet::call_index(11)]
+        #[pallet::weight(15_000)]
+        pub fn create_credit_note(
+            origin: OriginFor<T>,
+            client: T::AccountId,
+            original_invoice_id: u64,
+            amount: BalanceOf<T>,
+            metadata: Vec<u8>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            ensure!(!amount.is_zero(), Error::<T>::ZeroCredit);
+
+            let metadata = Self::sanitize_text(metadata)?;
+            let bounded_metadata: BoundedVec<u8, T::MaxMetadataLength> = metadata
+                .try_into()
+                .map_err(|_| Error::<T>::MetadataTooLong)?;
+
+            let credit_note_id = CreditNoteCount::<T>::get();
+            let next_id = credit_note_id
+                .checked_add(1)
+                .ok_or(Error::<T>::ArithmeticOverflow)?;
+
+            let credit_note = Invoices::<T>::try_mutate(
+                &client,
+                original_invoice_id,
+                |maybe_invoice| -> Result<_, DispatchError> {
+                    let invoice = maybe_invoice.as_mut().ok_or(Error::<T>::InvoiceNotFound)?;
+
+                    ensure!(invoice.created_by == who, Error::<T>::NotInvoiceCreator);
+                    Self::ensure_payable(invoice)?;
+                    invoice.last_activity = frame_system::Pallet::<T>::block_number();
+
+                    let outstanding = Self::outstanding(invoice);
+                    ensure!(amount <= outstanding, Error::<T>::CreditExceedsOutstanding);
+
+                    let mut credit_note = CreditNote {
+                        id: credit_note_id,
+                        invoice_id: original_invoice_id,
+                        client: client.clone(),
+                        amount,
+                        metadata: bounded_metadata,
+                        timestamp: frame_system::Pallet::<T>::block_number(),
+                        credit_note_hash: [0u8; 32],
+                        created_by: who.clone(),
+                    };
+                    credit_note.credit_note_hash = credit_note.calculate_hash();
+                    ensure!(
+                        !CreditNoteByHash::<T>::contains_key(credit_note.credit_note_hash),
+                        Error::<T>::DuplicateCreditNoteHash
+                    );
+
+                    InvoiceCredits::<T>::mutate(&client, original_invoice_id, |credited| {
+                        *credited = credited.saturating_add(amount)
+                    });
+                    Self::reduce_outstanding_balance(&client, amount);
+                    Self::note_written_off(&who, amount);
+
+                    // Nothing left to pay once fully credited
+                    if amount == outstanding {
+                        Self::set_invoice_status(invoice, InvoiceStatus::Paid)?;
+                    }
+
+                    Ok(credit_note)
+                },
+            )?;
+
+            CreditNoteByHash::<T>::insert(credit_note.credit_note_hash, credit_note_id);
+            CreditNotes::<T>::insert(credit_note_id, credit_note);
+            CreditNoteCount::<T>::put(next_id);
+
+            Self::deposit_event(Event::CreditNoteCreated {
+                credit_note_id,
+                invoice_id: original_invoice_id,
+                amount,
+            });
+
+            Ok(())
+        }
+
+        /// Attest whether an invoice matches a hash computed off-chain
+        ///
+        /// Compares `expected_hash` with the stored `invoice_hash` and records the outcome in
+        /// an event. A mismatch is a valid result, not an error, so negative attestations
+        /// are kept on-chain too. The stored hash is also recomputed from the stored fields,
+        /// and `InvoiceHashStale` is emitted if it no longer matches them.
+        ///
+        /// # Arguments
+        /// * `origin` - Transaction origin (the verifier)
+        /// * `client` - Client the invoice was issued to
+        /// * `invoice_id` - ID of the invoice
+        /// * `expected_hash` - Hash the verifier computed from the Django record
+        ///
+        /// # Returns
+        /// * `DispatchResult` - Success or error
+        ///
+        /// # Events
+        /// * `InvoiceVerified` - Emitted with whether `expected_hash` matched
+        /// * `InvoiceHashStale` - Emitted if the stored hash does not match the stored fields
+        ///
+        /// # Errors
+        /// * `InvoiceNotFound` - The client has no invoice with this ID
+        #[pallet::call_index(12)]
+        #[pallet::weight(10_000)]
+        pub fn verify_invoice(
+            origin: OriginFor<T>,
+            client: T::AccountId,
+            invoice_id: u64,
+            expected_hash: [u8; 32],
+        ) -> DispatchResult {
+            let verifier = ensure_signed(origin)?;
+
+            let invoice =
+                Invoices::<T>::get(&client, invoice_id).ok_or(Error::<T>::InvoiceNotFound)?;
+
+            let computed_hash = invoice.calculate_hash();
+            if computed_hash != invoice.invoice_hash {
+                Self::deposit_event(Event::InvoiceHashStale {
+                    invoice_id,
+                    stored_hash: invoice.invoice_hash,
+                    computed_hash,
+                });
+            }
+
+            Self::deposit_event(Event::InvoiceVerified {
+                invoice_id,
+                verifier,
+                matched: invoice.invoice_hash == expected_hash,
+            });
+
+            Ok(())
+        }
+
+        /// Create a new invoice with an external reference
+        ///
+        /// Same as `create_invoice`, taking the same arguments, additionally storing
+        /// `external_ref` (e.g. the Django invoice number) on the invoice and indexing it in
+        /// `InvoiceByExternalRef`.
+        ///
+        /// # Arguments
+        /// * `origin` - Transaction origin (invoice creator)
+        /// * `client` - Client account ID
+        /// * `amount` - Gross invoice amount, before tax and discount
+        /// * `tax` - Tax added to the amount
+        /// * `discount` - Discount subtracted from the amount
+        /// * `metadata` - Invoice metadata
+        /// * `due_block` - Block by which the invoice should be paid (optional)
+        /// * `expires_at` - Block at which the unpaid invoice expires (optional)
+        /// * `line_items` - Line items adding up to `amount` (optional)
+        /// * `signature` - The creator's signature of the invoice hash (optional)
+        /// * `external_ref` - Globally unique external reference (empty for none)
+        ///
+        /// # Returns
+        /// * `DispatchResultWithPostInfo` - Success with the weight actually used, or error
+        ///
+        /// # Events
+        /// * `InvoiceCreated` - Emitted when invoice is successfully created
+        /// * `InvoiceHashStored` - Emitted when invoice hash is stored
+        ///
+        /// # Errors
+        /// * `ExternalRefTooLong` - Reference exceeds `MaxExternalRefLength`
+        /// * `DuplicateExternalRef` - Another invoice already uses the reference
+        /// * Any error of `create_invoice`
+        #[pallet::call_index(13)]
+        #[pallet::weight(Pallet::<T>::create_invoice_weight(
+            metadata.len() as u32,
+            line_items.len() as u32,
+            signature.is_some(),
+            !external_ref.is_empty(),
+        ))]
+        pub fn create_invoice_with_ref(
+            origin: OriginFor<T>,
+            client: T::AccountId,
+            amount: BalanceOf<T>,
+            tax: BalanceOf<T>,
+            discount: BalanceOf<T>,
+            metadata: Vec<u8>,
+            due_block: Option<BlockNumberFor<T>>,
+            expires_at: Option<BlockNumberFor<T>>,
+            line_items: Vec<LineItem<T>>,
+            signature: Option<BoundedVec<u8, T::MaxSignatureLength>>,
+            external_ref: Vec<u8>,
+        ) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+
+            Self::do_create_invoice_call(
+                who,
+                client,
+                amount,
+                tax,
+                discount,
+                metadata,
+                due_block,
+                expires_at,
+                line_items,
+                signature,
+                external_ref,
+            )
+        }
+
+        /// Ask the client to agree to cancelling an invoice
+        ///
+        /// The client can approve or reject the request. Without an answer within
+        /// `CancellationTimeout` blocks, the cancellation can be finalized by anyone.
+        ///
+        /// # Arguments
+        /// * `origin` - Transaction origin (must be the invoice creator)
+        /// * `client` - Client account the invoice was issued to
+        /// * `invoice_id` - ID of the invoice to cancel
+        /// * `reason_hash` - Hash of the cancellation reason kept off-chain
+        ///
+        /// # Returns
+        /// * `DispatchResult` - Success or error
+        ///
+        /// # Events
+        /// * `CancellationRequested` - Emitted with the deadline for the client's answer
+        ///
+        /// # Errors
+        /// * `InvoiceNotFound` - The client has no invoice with this ID
+        /// * `NotInvoiceCreator` - Origin did not create the invoice
+        /// * `InvoiceAlreadyPaid` - Settled invoices cannot be cancelled
+        /// * `InvoiceCancelled` - The invoice is already cancelled
+        /// * `InvoiceDisputed` - The invoice is under dispute
+        /// * `InvoiceChallenged` - The invoice is challenged as fraudulent
+        /// * `CancellationAlreadyRequested` - A request is already pending
+        /// * `CancellationCooldownActive` - The client rejected a request less than
+        ///   `CancellationCooldown` blocks ago
+        #[pallet::call_index(14)]
+        #[pallet::weight(10_000)]
+        pub fn request_cancellation(
+            origin: OriginFor<T>,
+            client: T::AccountId,
+            invoice_id: u64,
+            reason_hash: [u8; 32],
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let invoice =
+                Invoices::<T>::get(&client, invoice_id).ok_or(Error::<T>::InvoiceNotFound)?;
+            ensure!(invoice.created_by == who, Error::<T>::NotInvoiceCreator);
+            ensure!(
+                INVOICE_STATUS.can_transition(&invoice.status, &InvoiceStatus::Cancelled),
+                Self::status_error(&invoice.status)
+            );
+            Self::ensure_not_disputed(&client, invoice_id)?;
+            Self::ensure_not_challenged(&invoice)?;
+            ensure!(
+                !PendingCancellations::<T>::contains_key(&client, invoice_id),
+                Error::<T>::CancellationAlreadyRequested
+            );
+
+            let now = frame_system::Pallet::<T>::block_number();
+            if let Some(blocked_until) = CancellationBlockedUntil::<T>::get(&client, invoice_id) {
+                ensure!(now >= blocked_until, Error::<T>::CancellationCooldownActive);
+                CancellationBlockedUntil::<T>::remove(&client, invoice_id);
+            }
+
+            Self::note_creator_activity(&client, invoice_id);
+            let deadline = now.saturating_add(T::CancellationTimeout::get());
+            PendingCancellations::<T>::insert(
+                &client,
+                invoice_id,
+                CancellationRequest {
+                    reason_hash,
+                    requested_at: now,
+                    deadline,
+                },
+            );
+
+            Self::deposit_event(Event::CancellationRequested {
+                invoice_id,
+                client,
+                reason_hash,
+                deadline,
+            });
+
+            Ok(())
+        }
+
+        /// Agree to a requested cancellation, cancelling the invoice
+        ///
+        /// # Arguments
+        /// * `origin` - Transaction origin (the invoiced client)
+        /// * `invoice_id` - ID of the invoice
+        ///
+        /// # Returns
+        /// * `DispatchResult` - Success or error
+        ///
+        /// # Events
+        /// * `CancellationApproved` - Emitted when the client agrees
+        /// * `InvoiceCancelled` - Emitted when the invoice is cancelled
+        ///
+        /// # Errors
+        /// * `NoPendingCancellation` - No cancellation was requested for the invoice
+        #[pallet::call_index(15)]
+        #[pallet::weight(10_000)]
+        pub fn approve_cancellation(origin: OriginFor<T>, invoice_id: u64) -> DispatchResult {
+            let client = ensure_signed(origin)?;
+
+            ensure!(
+                PendingCancellations::<T>::contains_key(&client, invoice_id),
+                Error::<T>::NoPendingCancellation
+            );
+
+            Self::deposit_event(Event::CancellationApproved {
+                invoice_id,
+                client: client.clone(),
+            });
+            Self::do_cancel_invoice(client, invoice_id)
+        }
+
+        /// Object to a requested cancellation
+        ///
+        /// The invoice stays active, and no new cancellation can be requested for it
+        /// during the next `CancellationCooldown` blocks.
+        ///
+        /// # Arguments
+        /// * `origin` - Transaction origin (the invoiced client)
+        /// * `invoice_id` - ID of the invoice
+        ///
+        /// # Returns
+        /// * `DispatchResult` - Success or error
+        ///
+        /// # Events
+        /// * `CancellationRejected` - Emitted with the end of the cooldown
+        ///
+        /// # Errors
+        /// * `NoPendingCancellation` - No cancellation was requested for the invoice
+        #[pallet::call_index(16)]
+        #[pallet::weight(10_000)]
+        pub fn reject_cancellation(origin: OriginFor<T>, invoice_id: u64) -> DispatchResult {
+            let client = ensure_signed(origin)?;
+
+            PendingCancellations::<T>::take(&client, invoice_id)
+                .ok_or(Error::<T>::NoPendingCancellation)?;
+
+            let blocked_until = frame_system::Pallet::<T>::block_number()
+                .saturating_add(T::CancellationCooldown::get());
+            CancellationBlockedUntil::<T>::insert(&client, invoice_id, blocked_until);
+
+            Self::deposit_event(Event::CancellationRejected {
+                invoice_id,
+                client,
+                blocked_until,
+            });
+
+            Ok(())
+        }
+
+        /// Cancel an invoice whose client did not answer the cancellation request in time
+        ///
+        /// Permissionless, so the creator or any keeper can finalize expired requests.
+        ///
+        /// # Arguments
+        /// * `origin` - Transaction origin (any signed account)
+        /// * `client` - Client account the invoice was issued to
+        /// * `invoice_id` - ID of the invoice
+        ///
+        /// # Returns
+        /// * `DispatchResult` - Success or error
+        ///
+        /// # Events
+        /// * `CancellationTimedOut` - Emitted when the request expired unanswered
+        /// * `InvoiceCancelled` - Emitted when the invoice is cancelled
+        ///
+        /// # Errors
+        /// * `NoPendingCancellation` - No cancellation was requested for the invoice
+        /// * `CancellationTimeoutNotReached` - The request deadline has not been reached
+        #[pallet::call_index(17)]
+        #[pallet::weight(10_000)]
+        pub fn finalize_cancellation(
+            origin: OriginFor<T>,
+            client: T::AccountId,
+            invoice_id: u64,
+        ) -> DispatchResult {
+            ensure_signed(origin)?;
+
+            let request = PendingCancellations::<T>::get(&client, invoice_id)
+                .ok_or(Error::<T>::NoPendingCancellation)?;
+            ensure!(
+                frame_system::Pallet::<T>::block_number() >= request.deadline,
+                Error::<T>::CancellationTimeoutNotReached
+            );
+
+            Self::deposit_event(Event::CancellationTimedOut {
+                invoice_id,
+                client: client.clone(),
+            });
+            Self::do_cancel_invoice(client, invoice_id)
+        }
+
+        /// Dispute an invoice
+        ///
+        /// Blocks payments, credit notes, edits and cancellation of the invoice until
+        /// `DisputeResolutionOrigin` resolves the dispute, and drops any pending cancellation
+        /// request.
+        ///
+        /// # Arguments
+        /// * `origin` - Transaction origin (the invoiced client)
+        /// * `creator` - Account that created the invoice
+        /// * `invoice_id` - ID of the invoice
+        /// * `reason` - Reason for the dispute
+        ///
+        /// # Returns
+        /// * `DispatchResult` - Success or error
+        ///
+        /// # Events
+        /// * `InvoiceDisputed` - Emitted when the dispute is opened
+        ///
+        /// # Errors
+        /// * `InvalidUtf8` - Reason is not valid UTF-8 (when `NormalizeText` is enabled)
+        /// * `DisputeReasonTooLong` - Reason exceeds `MaxDisputeReasonLength`
+        /// * `InvoiceNotFound` - The client has no invoice with this ID
+        /// * `CreatorMismatch` - The invoice was not created by `creator`
+        /// * `InvoiceAlreadyPaid` - The invoice has already been settled
+        /// * `InvoiceCancelled` - The invoice has been cancelled
+        /// * `InvoiceDisputed` - The invoice is already under dispute
+        #[pallet::call_index(18)]
+        #[pallet::weight(10_000)]
+        pub fn dispute_invoice(
+            origin: OriginFor<T>,
+            creator: T::AccountId,
+            invoice_id: u64,
+            reason: Vec<u8>,
+        ) -> DispatchResult {
+            let client = ensure_signed(origin)?;
+
+            let reason: BoundedVec<u8, T::MaxDisputeReasonLength> = Self::sanitize_text(reason)?
+                .try_into()
+                .map_err(|_| Error::<T>::DisputeReasonTooLong)?;
+
+            let invoice =
+                Invoices::<T>::get(&client, invoice_id).ok_or(Error::<T>::InvoiceNotFound)?;
+            ensure!(invoice.created_by == creator, Error::<T>::CreatorMismatch);
+            Self::ensure_payable(&invoice)?;
+
+            InvoiceDisputes::<T>::insert(
+                &client,
+                invoice_id,
+                InvoiceDispute {
+                    reason,
+                    opened_at: frame_system::Pallet::<T>::block_number(),
+                },
+            );
+            PendingCancellations::<T>::remove(&client, invoice_id);
+
+            T::DisputeHandler::dispute_opened(
+                &client,
+                &creator,
+                invoice_id,
+                Self::outstanding(&invoice),
+            );
+
+            Self::deposit_event(Event::InvoiceDisputed {
+                invoice_id,
+                client,
+                creator,
+            });
+
+            Ok(())
+        }
+
+        /// Resolve an invoice dispute
+        ///
+        /// # Arguments
+        /// * `origin` - Must satisfy `DisputeResolutionOrigin`
+        /// * `client` - Client the invoice was issued to
+        /// * `invoice_id` - ID of the invoice
+        /// * `resolution` - Whether the invoice is reinstated or cancelled
+        ///
+        /// # Returns
+        /// * `DispatchResult` - Success or error
+        ///
+        /// # Events
+        /// * `DisputeResolved` - Emitted with the resolution
+        /// * `InvoiceCancelled` - Emitted when the resolution cancels the invoice
+        /// * `InvoiceDepositSlashed` - Emitted when the resolution cancels an invoice that
+        ///   carries a creation deposit
+        ///
+        /// # Errors
+        /// * `InvoiceNotDisputed` - The invoice is not under dispute
+        #[pallet::call_index(19)]
+        #[pallet::weight(15_000)]
+        pub fn resolve_dispute(
+            origin: OriginFor<T>,
+            client: T::AccountId,
+            invoice_id: u64,
+            resolution: DisputeResolution,
+        ) -> DispatchResult {
+            T::DisputeResolutionOrigin::ensure_origin(origin)?;
+
+            InvoiceDisputes::<T>::take(&client, invoice_id)
+                .ok_or(Error::<T>::InvoiceNotDisputed)?;
+            let creator = Invoices::<T>::get(&client, invoice_id)
+                .ok_or(Error::<T>::InvoiceNotFound)?
+                .created_by;
+
+            Self::deposit_event(Event::DisputeResolved {
+                invoice_id,
+                client: client.clone(),
+                resolution: resolution.clone(),
+            });
+            if resolution == DisputeResolution::Cancelled {
+                // Slashed first, so cancelling finds no deposit left to release
+                Self::slash_deposit(&creator, invoice_id);
+                Self::do_cancel_invoice(client.clone(), invoice_id)?;
+            }
+
+            T::DisputeHandler::dispute_closed(&client, &creator, invoice_id, &resolution);
+
+            Ok(())
+        }
+
+        /// Create several invoices at once
+        ///
+        /// Every entry is validated and created like `create_invoice` without a due block,
+        /// in order and in its own storage transaction. A failing entry is rolled back and
+        /// skipped; the others are kept. The outcome is stored in `LastBatchOutcome`.
+        ///
+        /// Every entry is charged like `create_invoice` with its metadata. A skipped entry is
+        /// refunded down to an entry with empty metadata, as its writes are rolled back.
+        ///
+        /// # Arguments
+        /// * `origin` - Transaction origin (recorded as creator of every invoice)
+        /// * `invoices` - Entries of (client, amount, metadata)
+        ///
+        /// # Returns
+        /// * `DispatchResultWithPostInfo` - Success with the weight actually used, or error
+        ///
+        /// # Events
+        /// * `InvoiceCreated` / `InvoiceHashStored` - Emitted for every invoice
+        /// * `InvoiceBatchItemSkipped` - Emitted for every entry that failed
+        /// * `InvoiceBatchCreated` - Emitted with the range of IDs created, if any
+        /// * `BatchCompleted` - Emitted last with the `BatchOutcome`
+        ///
+        /// # Errors
+        /// * `NotAuthorizedCreator` - Caller may not create invoices
+        /// * `EmptyBatch` - No entries were given
+        /// * `BatchTooLarge` - More than `MaxBatchSize` entries were given
+        #[pallet::call_index(20)]
+        #[pallet::weight(Pallet::<T>::batch_create_invoices_weight(
+            invoices
+                .iter()
+                .take(T::MaxBatchSize::get() as usize)
+                .map(|(_, _, metadata)| metadata.len() as u32),
+        ))]
+        pub fn batch_create_invoices(
+            origin: OriginFor<T>,
+            invoices: Vec<(T::AccountId, BalanceOf<T>, Vec<u8>)>,
+        ) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            Self::ensure_authorized_creator(&who)?;
+
+            ensure!(!invoices.is_empty(), Error::<T>::EmptyBatch);
+            ensure!(
+                invoices.len() <= T::MaxBatchSize::get() as usize,
+                Error::<T>::BatchTooLarge
+            );
+
+            let first_id = InvoiceCount::<T>::get();
+            let mut outcome = BatchOutcome::default();
+            // Metadata length each entry is charged for
+            let mut charged = Vec::with_capacity(invoices.len());
+            for (index, (client, amount, metadata)) in invoices.into_iter().enumerate() {
+                let index = index as u32;
+                let metadata_len = metadata.len() as u32;
+                // Only this entry's changes, events included, are rolled back on error
+                let result = with_transaction(|| {
+                    let result = Self::do_create_invoice(
+                        who.clone(),
+                        client,
+                        amount,
+                        Zero::zero(),
+                        Zero::zero(),
+                        metadata,
+                        None,
+                        Vec::new(),
+                        Vec::new(),
+                    )
+                    .and_then(|invoice_id| Self::reserve_deposit(&who, invoice_id));
+                    match result {
+                        Ok(_) => TransactionOutcome::Commit(result),
+                        Err(_) => TransactionOutcome::Rollback(result),
+                    }
+                });
+                if let Err(error) = &result {
+                    Self::deposit_event(Event::InvoiceBatchItemSkipped {
+                        index,
+                        reason: Self::skip_reason(*error),
+                    });
+                }
+                charged.push(if result.is_ok() { metadata_len } else { 0 });
+                outcome.record(index, &result);
+            }
+
+            // Skipped entries did not take an ID, so the created ones are consecutive
+            if outcome.succeeded > 0 {
+                Self::deposit_event(Event::InvoiceBatchCreated {
+                    count: outcome.succeeded,
+                    first_id,
+                    last_id: InvoiceCount::<T>::get().saturating_sub(1),
+                });
+            }
+            LastBatchOutcome::<T>::insert(&who, outcome);
+            Self::deposit_event(Event::BatchCompleted { who, outcome });
+
+            Ok(Some(Self::batch_create_invoices_weight(charged)).into())
+        }
+
+        /// Allow an account to create invoices
+        ///
+        /// # Arguments
+        /// * `origin` - Must satisfy `AdminOrigin`
+        /// * `creator` - Account to authorize
+        ///
+        /// # Events
+        /// * `CreatorAuthorized` - Emitted when the account is added
+        ///
+        /// # Errors
+        /// * `CreatorAlreadyAuthorized` - Account is already authorized
+        #[pallet::call_index(21)]
+        #[pallet::weight(5_000)]
+        pub fn authorize_creator(origin: OriginFor<T>, creator: T::AccountId) -> DispatchResult {
+            T::AdminOrigin::ensure_origin(origin)?;
+
+            ensure!(
+                !AuthorizedCreators::<T>::contains_key(&creator),
+                Error::<T>::CreatorAlreadyAuthorized
+            );
+            AuthorizedCreators::<T>::insert(&creator, ());
+
+            Self::deposit_event(Event::CreatorAuthorized { creator });
+
+            Ok(())
+        }
+
+        /// Withdraw an account's permission to create invoices
+        ///
+        /// Invoices it already created are not affected.
+        ///
+        /// # Arguments
+        /// * `origin` - Must satisfy `AdminOrigin`
+        /// * `creator` - Account to revoke
+        ///
+        /// # Events
+        /// * `CreatorRevoked` - Emitted when the account is removed
+        ///
+        /// # Errors
+        /// * `NotAuthorizedCreator` - Account is not authorized
+        #[pallet::call_index(22)]
+        #[pallet::weight(5_000)]
+        pub fn revoke_creator(origin: OriginFor<T>, creator: T::AccountId) -> DispatchResult {
+            T::AdminOrigin::ensure_origin(origin)?;
+
+            AuthorizedCreators::<T>::take(&creator).ok_or(Error::<T>::NotAuthorizedCreator)?;
+
+            Self::deposit_event(Event::CreatorRevoked { creator });
+
+            Ok(())
+        }
+
+        /// Grant an account access to the encrypted payload of an invoice
+        ///
+        /// Replaces any grant the account already has on the invoice. An invoice holds at most
+        /// `MaxGrantsPerInvoice` grants.
+        ///
+        /// # Arguments
+        /// * `origin` - Transaction origin (creator or client of the invoice)
+        /// * `invoice_hash` - Hash of the invoice
+        /// * `grantee` - Account allowed to decrypt the payload
+        /// * `key_wrap_hash` - Hash of the payload key wrapped for the grantee
+        ///
+        /// # Events
+        /// * `AccessGranted` - Emitted with the grant for the key management service
+        ///
+        /// # Errors
+        /// * `UnknownInvoiceHash` - No invoice is stored under the hash
+        /// * `NotInvoiceParty` - Origin is neither the creator nor the client of the invoice
+        /// * `TooManyGrants` - The invoice already has `MaxGrantsPerInvoice` other grants
+        #[pallet::call_index(23)]
+        #[pallet::weight(10_000)]
+        pub fn grant_access(
+            origin: OriginFor<T>,
+            invoice_hash: [u8; 32],
+            grantee: T::AccountId,
+            key_wrap_hash: [u8; 32],
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            Self::ensure_invoice_party(&who, invoice_hash)?;
+
+            if !AccessGrants::<T>::contains_key(invoice_hash, &grantee) {
+                let count = AccessGrantCount::<T>::get(invoice_hash);
+                ensure!(count < T::MaxGrantsPerInvoice::get(), Error::<T>::TooManyGrants);
+                AccessGrantCount::<T>::insert(invoice_hash, count.saturating_add(1));
+            }
+            AccessGrants::<T>::insert(
+                invoice_hash,
+                &grantee,
+                GrantInfo {
+                    granted_by: who.clone(),
+                    granted_at: frame_system::Pallet::<T>::block_number(),
+                    key_wrap_hash,
+                },
+            );
+
+            Self::deposit_event(Event::AccessGranted {
+                invoice_hash,
+                grantee,
+                granted_by: who,
+                key_wrap_hash,
+            });
+
+            Ok(())
+        }
+
+        /// Withdraw an account's access to the encrypted payload of an invoice
+        ///
+        /// # Arguments
+        /// * `origin` - Transaction origin (creator or client of the invoice)
+        /// * `invoice_hash` - Hash of the invoice
+        /// * `grantee` - Account whose grant is withdrawn
+        ///
+        /// # Events
+        /// * `AccessRevoked` - Emitted so the key management service drops the wrapped key
+        ///
+        /// # Errors
+        /// * `UnknownInvoiceHash` - No invoice is stored under the hash
+        /// * `NotInvoiceParty` - Origin is neither the creator nor the client of the invoice
+        /// * `GrantNotFound` - The account has no grant on the invoice
+        #[pallet::call_index(24)]
+        #[pallet::weight(10_000)]
+        pub fn revoke_access(
+            origin: OriginFor<T>,
+            invoice_hash: [u8; 32],
+            grantee: T::AccountId,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            Self::ensure_invoice_party(&who, invoice_hash)?;
+
+            AccessGrants::<T>::take(invoice_hash, &grantee).ok_or(Error::<T>::GrantNotFound)?;
+            AccessGrantCount::<T>::mutate_exists(invoice_hash, |count| {
+                *count = count.map(|count| count.saturating_sub(1)).filter(|count| *count > 0);
+            });
+
+            Self::deposit_event(Event::AccessRevoked {
+                invoice_hash,
+                grantee,
+                revoked_by: who,
+            });
+
+            Ok(())
+        }
+
+        /// Delete an invoice for good, e.g. to correct an invoice created in error
+        ///
+        /// Unlike archival nothing of the invoice is kept: its `InvoiceByHash` and external
+        /// reference entries, per-client and per-creator indices, credits, cancellation state,
+        /// operator signature and access grants are all removed, its unpaid rest leaves
+        /// `OutstandingBalance` and its creator's invoiced total, and its creation deposit is
+        /// returned. A paid invoice is a settled record and is only purged with `force`. A
+        /// disputed invoice is never purged: `resolve_dispute` closes the dispute first, so
+        /// `DisputeHandler` and `OnTransition` learn of the outcome.
+        ///
+        /// # Arguments
+        /// * `origin` - Must be root
+        /// * `client` - Client the invoice was issued to
+        /// * `invoice_id` - ID of the invoice
+        /// * `force` - Also purge a paid invoice
+        ///
+        /// # Returns
+        /// * `DispatchResultWithPostInfo` - Success with the weight actually used, or error
+        ///
+        /// # Events
+        /// * `InvoicePurged` - Emitted with the hash that no longer resolves
+        ///
+        /// # Errors
+        /// * `InvoiceNotFound` - The client has no invoice with this ID
+        /// * `InvoiceDisputed` - The invoice is under dispute
+        /// * `PaidInvoiceRequiresForce` - The invoice is paid and `force` is unset
+        #[pallet::call_index(25)]
+        #[pallet::weight(T::WeightInfo::purge_invoice(
+            T::MaxInvoicesPerClient::get(),
+            T::MaxGrantsPerInvoice::get(),
+        ))]
+        pub fn purge_invoice(
+            origin: OriginFor<T>,
+            client: T::AccountId,
+            invoice_id: u64,
+            force: bool,
+        ) -> DispatchResultWithPostInfo {
+            ensure_root(origin)?;
+
+            let invoice =
+                Invoices::<T>::get(&client, invoice_id).ok_or(Error::<T>::InvoiceNotFound)?;
+            Self::ensure_not_disputed(&client, invoice_id)?;
+            ensure!(
+                force || invoice.status != InvoiceStatus::Paid,
+                Error::<T>::PaidInvoiceRequiresForce
+            );
+
+            let invoice_hash = invoice.invoice_hash;
+            let invoices = ClientInvoiceCount::<T>::get(&client);
+            let grants = AccessGrantCount::<T>::get(invoice_hash);
+            Self::do_purge_invoice(invoice);
+
+            Self::deposit_event(Event::InvoicePurged {
+                invoice_id,
+                client,
+                invoice_hash,
+            });
+
+            Ok(Some(T::WeightInfo::purge_invoice(invoices, grants)).into())
+        }
+
+        /// Challenge an invoice as fraudulent
+        ///
+        /// Anyone but the creator can challenge a pending or overdue invoice that is not under
+        /// dispute, committing to off-chain evidence by its hash and reserving `ChallengeBond`.
+        /// The invoice is `Challenged` and cannot be paid or cancelled by its creator until
+        /// the challenge closes through `concede_challenge`, `resolve_challenge` or, after
+        /// `ChallengeTimeout` blocks without either, in favour of the challenger.
+        ///
+        /// # Arguments
+        /// * `origin` - Transaction origin (challenger)
+        /// * `client` - Client the invoice was issued to
+        /// * `invoice_id` - ID of the invoice
+        /// * `evidence_hash` - Hash of the evidence backing the challenge
+        ///
+        /// # Returns
+        /// * `DispatchResult` - Success or error
+        ///
+        /// # Events
+        /// * `InvoiceChallenged` - Emitted with the bond and the block the challenge times out
+        ///
+        /// # Errors
+        /// * `InvoiceNotFound` - The client has no invoice with this ID
+        /// * `CannotChallengeOwnInvoice` - Origin created the invoice
+        /// * `InvoiceDisputed` - The invoice is under dispute
+        /// * `InvoiceChallenged` - The invoice is already challenged
+        /// * `InvoiceAlreadyPaid` / `InvoiceCancelled` / `InvoiceExpired` - The invoice is
+        ///   settled
+        /// * `InsufficientChallengeBond` - Origin cannot cover `ChallengeBond`
+        /// * `TooManyChallengesTimingOut` - `MaxChallengesPerBlock` challenges already time
+        ///   out at the same block
+        #[pallet::call_index(26)]
+        #[pallet::weight(20_000)]
+        pub fn challenge_invoice(
+            origin: OriginFor<T>,
+            client: T::AccountId,
+            invoice_id: u64,
+            evidence_hash: [u8; 32],
+        ) -> DispatchResult {
+            let challenger = ensure_signed(origin)?;
+
+            Invoices::<T>::try_mutate(&client, invoice_id, |maybe_invoice| -> DispatchResult {
+                let invoice = maybe_invoice.as_mut().ok_or(Error::<T>::InvoiceNotFound)?;
+
+                ensure!(invoice.created_by != challenger, Error::<T>::CannotChallengeOwnInvoice);
+                Self::ensure_not_disputed(&client, invoice_id)?;
+                Self::set_invoice_status(invoice, InvoiceStatus::Challenged)?;
+
+                Ok(())
+            })?;
+
+            let bond = T::ChallengeBond::get();
+            T::Currency::reserve(&challenger, bond)
+                .map_err(|_| Error::<T>::InsufficientChallengeBond)?;
+
+            let opened_at = frame_system::Pallet::<T>::block_number();
+            let timeout_at = opened_at.saturating_add(T::ChallengeTimeout::get());
+            ChallengeTimeouts::<T>::try_append(timeout_at, (client.clone(), invoice_id))
+                .map_err(|_| Error::<T>::TooManyChallengesTimingOut)?;
+
+            InvoiceChallenges::<T>::insert(
+                &client,
+                invoice_id,
+                InvoiceChallenge {
+                    challenger: challenger.clone(),
+                    evidence_hash,
+                    bond,
+                    opened_at,
+                    timeout_at,
+                },
+            );
+            PendingCancellations::<T>::remove(&client, invoice_id);
+
+            Self::deposit_event(Event::InvoiceChallenged {
+                invoice_id,
+                client,
+                challenger,
+                evidence_hash,
+                bond,
+                timeout_at,
+            });
+
+            Ok(())
+        }
+
+        /// Concede a challenge against one of the caller's invoices
+        ///
+        /// The invoice is cancelled, the challenger gets their bond back and the creator keeps
+        /// their creation deposit.
+        ///
+        /// # Arguments
+        /// * `origin` - Transaction origin (invoice creator)
+        /// * `client` - Client the invoice was issued to
+        /// * `invoice_id` - ID of the invoice
+        ///
+        /// # Returns
+        /// * `DispatchResult` - Success or error
+        ///
+        /// # Events
+        /// * `ChallengeClosed` - Emitted with `ChallengeOutcome::Conceded`
+        /// * `ChallengeBondReleased` - Emitted when the bond is returned
+        /// * `InvoiceCancelled` - Emitted when the invoice is cancelled
+        ///
+        /// # Errors
+        /// * `InvoiceNotFound` - The client has no invoice with this ID
+        /// * `NotInvoiceCreator` - Origin did not create the invoice
+        /// * `InvoiceNotChallenged` - The invoice has no open challenge
+        #[pallet::call_index(27)]
+        #[pallet::weight(20_000)]
+        pub fn concede_challenge(
+            origin: OriginFor<T>,
+            client: T::AccountId,
+            invoice_id: u64,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let invoice =
+                Invoices::<T>::get(&client, invoice_id).ok_or(Error::<T>::InvoiceNotFound)?;
+            ensure!(invoice.created_by == who, Error::<T>::NotInvoiceCreator);
+
+            Self::close_challenge(client, invoice_id, ChallengeOutcome::Conceded)
+        }
+
+        /// Rule on an invoice challenge
+        ///
+        /// An upheld challenge cancels the invoice, returns the bond and pays the creator's
+        /// creation deposit to the challenger. A dismissed challenge pays the bond to the
+        /// creator and reinstates the invoice as pending, or overdue once past its due block.
+        ///
+        /// # Arguments
+        /// * `origin` - Must satisfy `ChallengeJudgeOrigin`
+        /// * `client` - Client the invoice was issued to
+        /// * `invoice_id` - ID of the invoice
+        /// * `uphold` - Whether the invoice is found fraudulent
+        ///
+        /// # Returns
+        /// * `DispatchResult` - Success or error
+        ///
+        /// # Events
+        /// * `ChallengeClosed` - Emitted with `ChallengeOutcome::Upheld` or `Dismissed`
+        /// * `ChallengeBondReleased` / `InvoiceDepositAwarded` / `InvoiceCancelled` - Emitted
+        ///   when the challenge is upheld
+        /// * `ChallengeBondSlashed` - Emitted when the challenge is dismissed
+        /// * `InvoiceOverdue` - Emitted when a dismissed challenge leaves the invoice past due
+        ///
+        /// # Errors
+        /// * `InvoiceNotChallenged` - The invoice has no open challenge
+        #[pallet::call_index(28)]
+        #[pallet::weight(25_000)]
+        pub fn resolve_challenge(
+            origin: OriginFor<T>,
+            client: T::AccountId,
+            invoice_id: u64,
+            uphold: bool,
+        ) -> DispatchResult {
+            T::ChallengeJudgeOrigin::ensure_origin(origin)?;
+
+            let outcome =
+                if uphold { ChallengeOutcome::Upheld } else { ChallengeOutcome::Dismissed };
+            Self::close_challenge(client, invoice_id, outcome)
+        }
+
+        /// Record that the off-chain ERP linked an invoice to its record, without a signer
+        ///
+        /// Submitted by the ERP once it stored the invoice; see `ValidateUnsigned` for the
+        /// pool rules. Archived invoices keep their hash mapping and can still be linked.
+        ///
+        /// # Arguments
+        /// * `origin` - Must be none (unsigned)
+        /// * `invoice_id` - ID of the invoice
+        /// * `django_record_id` - Primary key of the Django invoice record
+        /// * `proof_hash` - Invoice hash as computed by the ERP
+        ///
+        /// # Returns
+        /// * `DispatchResult` - Success or error
+        ///
+        /// # Events
+        /// * `OffchainLinkConfirmed` - Emitted with the recorded link
+        ///
+        /// # Errors
+        /// * `LinkProofMismatch` - `proof_hash` is not the invoice's hash
+        /// * `OffchainLinkAlreadyConfirmed` - The invoice is already linked
+        #[pallet::call_index(29)]
+        #[pallet::weight(10_000)]
+        pub fn confirm_offchain_link(
+            origin: OriginFor<T>,
+            invoice_id: u64,
+            django_record_id: u64,
+            proof_hash: [u8; 32],
+        ) -> DispatchResult {
+            ensure_none(origin)?;
+            Self::ensure_link_confirmable(invoice_id, &proof_hash)?;
+
+            OffchainLinks::<T>::insert(
+                invoice_id,
+                OffchainLink {
+                    django_record_id,
+                    confirmed_at: frame_system::Pallet::<T>::block_number(),
+                },
+            );
+
+            Self::deposit_event(Event::OffchainLinkConfirmed {
+                invoice_id,
+                django_record_id,
+                invoice_hash: proof_hash,
+            });
+
+            Ok(())
+        }
+
+        /// Anchor the hash of a supporting document to an invoice
+        ///
+        /// # Arguments
+        /// * `origin` - Creator or client of the invoice
+        /// * `client` - Client the invoice was issued to
+        /// * `invoice_id` - ID of the invoice
+        /// * `doc_hash` - Hash of the document, e.g. SHA256 of the PDF
+        /// * `label` - What the document is, e.g. `delivery-note`
+        ///
+        /// # Returns
+        /// * `DispatchResult` - Success or error
+        ///
+        /// # Events
+        /// * `DocumentAttached` - Emitted with the invoice ID and document hash
+        ///
+        /// # Errors
+        /// * `InvoiceNotFound` - The client has no invoice with this ID
+        /// * `NotInvoiceParty` - Origin is neither the creator nor the client
+        /// * `AttachmentLabelTooLong` - `label` exceeds `MaxAttachmentLabelLength`
+        /// * `DuplicateAttachment` - The document is already attached
+        /// * `TooManyAttachments` - The invoice holds `MaxAttachmentsPerInvoice` attachments
+        #[pallet::call_index(30)]
+        #[pallet::weight(15_000)]
+        pub fn attach_document(
+            origin: OriginFor<T>,
+            client: T::AccountId,
+            invoice_id: u64,
+            doc_hash: [u8; 32],
+            label: Vec<u8>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let invoice =
+                Invoices::<T>::get(&client, invoice_id).ok_or(Error::<T>::InvoiceNotFound)?;
+            ensure!(
+                who == invoice.created_by || who == invoice.client,
+                Error::<T>::NotInvoiceParty
+            );
+            let label: BoundedVec<u8, T::MaxAttachmentLabelLength> =
+                label.try_into().map_err(|_| Error::<T>::AttachmentLabelTooLong)?;
+            let by_creator = who == invoice.created_by;
+
+            InvoiceAttachments::<T>::try_mutate(&client, invoice_id, |attachments| {
+                ensure!(
+                    !attachments.iter().any(|attachment| attachment.doc_hash == doc_hash),
+                    Error::<T>::DuplicateAttachment
+                );
+                attachments
+                    .try_push(Attachment {
+                        doc_hash,
+                        label,
+                        attached_by: who,
+                        attached_at: frame_system::Pallet::<T>::block_number(),
+                    })
+                    .map_err(|_| Error::<T>::TooManyAttachments)
+            })?;
+
+            if by_creator {
+                Self::note_creator_activity(&client, invoice_id);
+            }
+            Self::deposit_event(Event::DocumentAttached { invoice_id, doc_hash });
+
+            Ok(())
+        }
+
+        /// Accept an invoice as the client, acknowledging it as correct
+        ///
+        /// # Arguments
+        /// * `origin` - Transaction origin (the invoiced client)
+        /// * `creator` - Account that created the invoice
+        /// * `invoice_id` - ID of the invoice to accept
+        ///
+        /// # Returns
+        /// * `DispatchResult` - Success or error
+        ///
+        /// # Events
+        /// * `InvoiceAccepted` - Emitted with the invoice ID and client
+        ///
+        /// # Errors
+        /// * `InvoiceNotFound` - The client has no invoice with this ID
+        /// * `CreatorMismatch` - The invoice was not created by `creator`
+        /// * `InvoiceDisputed` - The invoice is under dispute
+        /// * `InvoiceAlreadyAccepted` - The invoice has already been accepted
+        /// * `InvoiceAlreadyPaid` / `InvoiceCancelled` / `InvoiceExpired` / `InvoiceRejected` -
+        ///   The invoice is closed
+        /// * `InvoiceChallenged` - The invoice is challenged
+        #[pallet::call_index(31)]
+        #[pallet::weight(10_000)]
+        pub fn accept_invoice(
+            origin: OriginFor<T>,
+            creator: T::AccountId,
+            invoice_id: u64,
+        ) -> DispatchResult {
+            let client = ensure_signed(origin)?;
+
+            Self::answer_invoice(&client, &creator, invoice_id, InvoiceStatus::Accepted)?;
+
+            Self::deposit_event(Event::InvoiceAccepted { invoice_id, client });
+
+            Ok(())
+        }
+
+        /// Reject an invoice as the client, closing it
+        ///
+        /// The unpaid rest is written off and the creation deposit released, as for a
+        /// cancellation.
+        ///
+        /// # Arguments
+        /// * `origin` - Transaction origin (the invoiced client)
+        /// * `creator` - Account that created the invoice
+        /// * `invoice_id` - ID of the invoice to reject
+        /// * `reason` - Why the invoice is refused (max `MaxMetadataLength` bytes)
+        ///
+        /// # Returns
+        /// * `DispatchResult` - Success or error
+        ///
+        /// # Events
+        /// * `InvoiceRejected` - Emitted with the invoice ID, client and reason
+        ///
+        /// # Errors
+        /// * `MetadataTooLong` - `reason` exceeds `MaxMetadataLength`
+        /// * `InvoiceNotFound` - The client has no invoice with this ID
+        /// * `CreatorMismatch` - The invoice was not created by `creator`
+        /// * `InvoiceDisputed` - The invoice is under dispute
+        /// * `InvoiceAlreadyAccepted` - The invoice has already been accepted
+        /// * `InvoiceAlreadyPaid` / `InvoiceCancelled` / `InvoiceExpired` / `InvoiceRejected` -
+        ///   The invoice is closed
+        /// * `InvoiceChallenged` - The invoice is challenged
+        #[pallet::call_index(32)]
+        #[pallet::weight(15_000)]
+        pub fn reject_invoice(
+            origin: OriginFor<T>,
+            creator: T::AccountId,
+            invoice_id: u64,
+            reason: Vec<u8>,
+        ) -> DispatchResult {
+            let client = ensure_signed(origin)?;
+
+            let reason: BoundedVec<u8, T::MaxMetadataLength> = Self::sanitize_text(reason)?
+                .try_into()
+                .map_err(|_| Error::<T>::MetadataTooLong)?;
+
+            Self::answer_invoice(&client, &creator, invoice_id, InvoiceStatus::Rejected)?;
+
+            Self::deposit_event(Event::InvoiceRejected { invoice_id, client, reason });
+
+            Ok(())
+        }
+
+        /// Cancel an invoice its creator abandoned, taking the creation deposit as
+        /// compensation
+        ///
+        /// An invoice still awaiting acceptance or payment is abandoned once
+        /// `AbandonThreshold` blocks passed since its creator last acted on it (see
+        /// `Invoice::last_activity`). The creation deposit is repatriated from the creator to
+        /// the client, then the invoice is cancelled, writing off its unpaid rest.
+        ///
+        /// # Arguments
+        /// * `origin` - Transaction origin (the invoiced client)
+        /// * `invoice_id` - ID of the invoice
+        ///
+        /// # Returns
+        /// * `DispatchResult` - Success or error
+        ///
+        /// # Events
+        /// * `InvoiceAbandoned` - Emitted with the creator and the compensation paid
+        /// * `InvoiceCancelled` - Emitted when the invoice is cancelled
+        ///
+        /// # Errors
+        /// * `InvoiceNotFound` - The client has no invoice with this ID
+        /// * `InvoiceAlreadyAccepted` / `InvoiceChallenged` - The invoice no longer awaits its
+        ///   creator
+        /// * `InvoiceAlreadyPaid` / `InvoiceCancelled` / `InvoiceExpired` / `InvoiceRejected` -
+        ///   The invoice is closed
+        /// * `InvoiceDisputed` - The invoice is under dispute
+        /// * `InvoiceNotAbandoned` - The creator was active within `AbandonThreshold` blocks,
+        ///   or the threshold is zero
+        #[pallet::call_index(33)]
+        #[pallet::weight(20_000)]
+        pub fn claim_abandoned_invoice(origin: OriginFor<T>, invoice_id: u64) -> DispatchResult {
+            let client = ensure_signed(origin)?;
+
+            let invoice =
+                Invoices::<T>::get(&client, invoice_id).ok_or(Error::<T>::InvoiceNotFound)?;
+            ensure!(
+                matches!(invoice.status, InvoiceStatus::Pending | InvoiceStatus::Overdue),
+                Self::status_error(&invoice.status)
+            );
+            Self::ensure_not_disputed(&client, invoice_id)?;
+
+            let threshold = T::AbandonThreshold::get();
+            let now = frame_system::Pallet::<T>::block_number();
+            ensure!(
+                !threshold.is_zero() && now >= invoice.last_activity.saturating_add(threshold),
+                Error::<T>::InvoiceNotAbandoned
+            );
+
+            // Paid out first, so cancelling finds no deposit left to release
+            let compensation = Self::compensate_client(&invoice.created_by, invoice_id, &client);
+            Self::deposit_event(Event::InvoiceAbandoned {
+                invoice_id,
+                client: client.clone(),
+                creator: invoice.created_by,
+                compensation,
+            });
+            Self::do_cancel_invoice(client, invoice_id)
+        }
+
+        /// Assign an invoice's right to payment to a new beneficiary, e.g. a factoring
+        /// company
+        ///
+        /// Later payments through `pay_invoice` and `record_payment` go to the new
+        /// beneficiary. Every assignment is kept in the invoice's history, at most
+        /// `MaxAssignmentsPerInvoice` of them.
+        ///
+        /// # Arguments
+        /// * `origin` - Transaction origin (the current beneficiary, initially the creator)
+        /// * `client` - Client the invoice was issued to
+        /// * `invoice_id` - ID of the invoice
+        /// * `new_beneficiary` - Account to receive payments from now on
+        ///
+        /// # Returns
+        /// * `DispatchResult` - Success or error
+        ///
+        /// # Events
+        /// * `InvoiceAssigned` - Emitted with the previous and the new beneficiary
+        ///
+        /// # Errors
+        /// * `InvoiceNotFound` - The client has no invoice with this ID
+        /// * `NotInvoiceBeneficiary` - The signer is not the current beneficiary
+        /// * `AlreadyBeneficiary` - `new_beneficiary` is the current beneficiary
+        /// * `InvoiceAlreadyPaid` / `InvoiceCancelled` / `InvoiceExpired` / `InvoiceRejected` -
+        ///   The invoice is closed
+        /// * `InvoiceDisputed` / `InvoiceChallenged` - The invoice is contested
+        /// * `TooManyAssignments` - The invoice was assigned `MaxAssignmentsPerInvoice` times
+        #[pallet::call_index(34)]
+        #[pallet::weight(10_000)]
+        pub fn assign_invoice(
+            origin: OriginFor<T>,
+            client: T::AccountId,
+            invoice_id: u64,
+            new_beneficiary: T::AccountId,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            Invoices::<T>::try_mutate(&client, invoice_id, |maybe_invoice| -> DispatchResult {
+                let invoice = maybe_invoice.as_mut().ok_or(Error::<T>::InvoiceNotFound)?;
+                ensure!(invoice.beneficiary == who, Error::<T>::NotInvoiceBeneficiary);
+                ensure!(invoice.beneficiary != new_beneficiary, Error::<T>::AlreadyBeneficiary);
+                Self::ensure_payable(invoice)?;
+
+                let assignment = Assignment {
+                    from: who.clone(),
+                    to: new_beneficiary.clone(),
+                    assigned_at: frame_system::Pallet::<T>::block_number(),
+                };
+                InvoiceAssignments::<T>::try_mutate(&client, invoice_id, |history| {
+                    history.try_push(assignment).map_err(|_| Error::<T>::TooManyAssignments)
+                })?;
+                invoice.beneficiary = new_beneficiary.clone();
+                Ok(())
+            })?;
+
+            Self::deposit_event(Event::InvoiceAssigned {
+                invoice_id,
+                from: who,
+                to: new_beneficiary,
+            });
+
+            Ok(())
+        }
+    }
+
+    // Helper functions (not dispatchable, for RPC or internal use)
+    impl<T: Config> Pallet<T> {
+        /// Create an invoice for `create_invoice` or `create_invoice_with_ref`, returning the
+        /// weight of what was stored
+        #[allow(clippy::too_many_arguments)]
+        fn do_create_invoice_call(
+            who: T::AccountId,
+            client: T::AccountId,
+            amount: BalanceOf<T>,
+            tax: BalanceOf<T>,
+            discount: BalanceOf<T>,
+            metadata: Vec<u8>,
+            due_block: Option<BlockNumberFor<T>>,
+            expires_at: Option<BlockNumberFor<T>>,
+            line_items: Vec<LineItem<T>>,
+            signature: Option<BoundedVec<u8, T::MaxSignatureLength>>,
+            external_ref: Vec<u8>,
+        ) -> DispatchResultWithPostInfo {
+            Self::ensure_authorized_creator(&who)?;
+
+            let invoice_id = Self::do_create_invoice(
+                who.clone(),
+                client.clone(),
+                amount,
+                tax,
+                discount,
+                metadata,
+                due_block,
+                external_ref,
+                line_items,
+            )?;
+            Self::reserve_deposit(&who, invoice_id)?;
+            if let Some(expires_at) = expires_at {
+                Self::schedule_expiry(&client, invoice_id, expires_at)?;
+            }
+
+            let invoice =
+                Invoices::<T>::get(&client, invoice_id).ok_or(Error::<T>::InvoiceNotFound)?;
+
+            // The hash is only known once the invoice is built; failing reverts the creation
+            let signed = signature.is_some();
+            if let Some(signature) = signature {
+                ensure!(
+                    Self::verify_creator_signature(&who, &invoice.invoice_hash, &signature),
+                    Error::<T>::InvalidSignature
+                );
+                InvoiceSignatures::<T>::insert(invoice_id, signature);
+            }
+
+            // Normalization may have shortened the metadata; charge for what was stored
+            Ok(Some(Self::create_invoice_weight(
+                invoice.metadata.len() as u32,
+                invoice.line_items.len() as u32,
+                signed,
+                !invoice.external_ref.is_empty(),
+            ))
+            .into())
+        }
+
+        /// Validate, hash and store a new invoice, returning its ID
+        fn do_create_invoice(
+            who: T::AccountId,
+            client: T::AccountId,
+            amount: BalanceOf<T>,
+            tax: BalanceOf<T>,
+            discount: BalanceOf<T>,
+            metadata: Vec<u8>,
+            due_block: Option<BlockNumberFor<T>>,
+            external_ref: Vec<u8>,
+            line_items: Vec<LineItem<T>>,
+        ) -> Result<u64, DispatchError> {
+            // Checked first: a full client fails on a single counter read, before any
+            // validation, hashing or index access
+            let client_count = ClientInvoiceCount::<T>::get(&client);
+            ensure!(
+                client_count < T::MaxInvoicesPerClient::get(),
+                Error::<T>::TooManyInvoices
+            );
+
+            ensure!(T::DidProvider::is_active(&client), Error::<T>::ClientHasNoActiveDid);
+
+            let metadata = Self::sanitize_text(metadata)?;
+
+            // Validate metadata length
+            let bounded_metadata: BoundedVec<u8, T::MaxMetadataLength> = metadata
+                .try_into()
+                .map_err(|_| Error::<T>::MetadataTooLong)?;
+
+            // External references are optional but unique when given
+            let external_ref: BoundedVec<u8, T::MaxExternalRefLength> = external_ref
+                .try_into()
+                .map_err(|_| Error::<T>::ExternalRefTooLong)?;
+            ensure!(
+                external_ref.is_empty() || !InvoiceByExternalRef::<T>::contains_key(&external_ref),
+                Error::<T>::DuplicateExternalRef
+            );
+
+            // Line items are optional but must account for the whole amount
+            let line_items: BoundedVec<LineItem<T>, T::MaxLineItems> = line_items
+                .try_into()
+                .map_err(|_| Error::<T>::TooManyLineItems)?;
+            if !line_items.is_empty() {
+                let total = line_items.iter().try_fold(BalanceOf::<T>::zero(), |sum, item| {
+                    item.total().and_then(|total| sum.checked_add(&total))
+                });
+                ensure!(total == Some(amount), Error::<T>::LineItemsMismatch);
+            }
+
+            ensure!(discount <= amount, Error::<T>::DiscountExceedsAmount);
+            let net_amount = amount
+                .saturating_sub(discount)
+                .checked_add(&tax)
+                .ok_or(Error::<T>::ArithmeticOverflow)?;
+
+            // Get next invoice ID
+            let invoice_id = InvoiceCount::<T>::get();
+            let current_block = frame_system::Pallet::<T>::block_number();
+
+            // Schedule overdue detection
+            if let Some(due) = due_block {
+                ensure!(due > current_block, Error::<T>::InvalidDueBlock);
+                DueInvoices::<T>::try_append(due, (client.clone(), invoice_id))
+                    .map_err(|_| Error::<T>::TooManyInvoicesDue)?;
+            }
+
+            // Create invoice struct
+            let mut invoice = Invoice {
+                id: invoice_id,
+                client: client.clone(),
+                amount,
+                metadata: bounded_metadata,
+                timestamp: current_block,
+                invoice_hash: [0u8; 32], // Placeholder, will be calculated
+                created_by: who.clone(),
+                status: InvoiceStatus::Pending,
+                due_block,
+                paid_amount: Zero::zero(),
+                external_ref: external_ref.clone(),
+                line_items,
+                hash_algorithm: T::InvoiceHashAlgorithm::get(),
+                tax,
+                discount,
+                accrued_late_fee: Zero::zero(),
+                last_activity: current_block,
+                beneficiary: who.clone(),
+            };
+
+            // Calculate hash of invoice details
+            let invoice_hash = invoice.calculate_hash();
+            let line_count = invoice.line_items.len() as u32;
+            invoice.invoice_hash = invoice_hash;
+
+            // Never overwrite the hash mapping of another invoice
+            ensure!(
+                !InvoiceByHash::<T>::contains_key(invoice_hash),
+                Error::<T>::DuplicateInvoiceHash
+            );
+
+            let outstanding_balance = OutstandingBalance::<T>::get(&client)
+                .checked_add(&net_amount)
+                .ok_or(Error::<T>::ArithmeticOverflow)?;
+
+            // Store invoice
+            Invoices::<T>::insert(&client, invoice_id, invoice);
+            OutstandingBalance::<T>::insert(&client, outstanding_balance);
+            Self::note_invoiced(&who, net_amount);
+            ClientInvoiceIds::<T>::insert(&client, client_count, invoice_id);
+            InvoicesByCreator::<T>::insert(&who, invoice_id, &client);
+            ClientInvoiceCount::<T>::insert(&client, client_count.saturating_add(1));
+
+            // Store hash mapping for quick lookup
+            InvoiceByHash::<T>::insert(invoice_hash, invoice_id);
+            if !external_ref.is_empty() {
+                InvoiceByExternalRef::<T>::insert(external_ref, (client.clone(), invoice_id));
+            }
+
+            // Increment invoice counter
+            let next_id = invoice_id
+                .checked_add(1)
+                .ok_or(Error::<T>::ArithmeticOverflow)?;
+            InvoiceCount::<T>::put(next_id);
+            crate::webhook::index_invoice::<T>(&client, invoice_id);
+
+            // Emit events
+            Self::deposit_event(Event::InvoiceCreated {
+                invoice_id,
+                client: client.clone(),
+                amount,
+                net_amount,
+                invoice_hash,
+                created_by: who,
+                line_items: line_count,
+            });
+
+            Self::deposit_event(Event::InvoiceHashStored {
+                invoice_hash,
+                invoice_id,
+            });
+
+            Ok(invoice_id)
+        }
+
+        /// Remove an invoice and everything keyed by it.
+        ///
+        /// Entries left in `DueInvoices` and `ExpiringInvoices` are skipped by the sweeps once
+        /// the invoice is gone.
+        fn do_purge_invoice(invoice: Invoice<T>) {
+            let client = &invoice.client;
+            let invoice_id = invoice.id;
+
+            let outstanding = Self::outstanding(&invoice);
+            Self::reduce_outstanding_balance(client, outstanding);
+            Self::note_written_off(&invoice.created_by, outstanding);
+            Self::release_deposit(&invoice.created_by, invoice_id);
+            if let Some(challenge) = InvoiceChallenges::<T>::take(client, invoice_id) {
+                Self::release_challenge_bond(invoice_id, &challenge);
+            }
+
+            Self::remove_invoice_records(&invoice);
+
+            // Only drop the hash mapping if it still points at this invoice
+            if InvoiceByHash::<T>::get(invoice.invoice_hash) == Some(invoice_id) {
+                InvoiceByHash::<T>::remove(invoice.invoice_hash);
+            }
+
+            // Re-pack the per-client index so positions stay dense and ordered
+            let count = ClientInvoiceCount::<T>::get(client);
+            let kept: Vec<u64> = (0..count)
+                .filter_map(|position| ClientInvoiceIds::<T>::take(client, position))
+                .filter(|id| *id != invoice_id)
+                .collect();
+            for (position, id) in kept.iter().enumerate() {
+                ClientInvoiceIds::<T>::insert(client, position as u32, id);
+            }
+            ClientInvoiceCount::<T>::insert(client, kept.len() as u32);
+        }
+
+        /// Weight of `create_invoice` with `metadata` bytes and `line_items` line items, plus
+        /// checking the creator's signature if `signed` and indexing an external reference if
+        /// `external_ref`
+        pub(crate) fn create_invoice_weight(
+            metadata: u32,
+            line_items: u32,
+            signed: bool,
+            external_ref: bool,
+        ) -> Weight {
+            let mut weight = T::WeightInfo::create_invoice(metadata, line_items);
+            if signed {
+                // Storing it in `InvoiceSignatures`
+                weight = weight
+                    .saturating_add(T::WeightInfo::verify_creator_signature())
+                    .saturating_add(T::DbWeight::get().writes(1));
+            }
+            if external_ref {
+                // Checking and inserting `InvoiceByExternalRef`
+                weight = weight.saturating_add(T::DbWeight::get().reads_writes(1, 1));
+            }
+            weight
+        }
+
+        /// Weight of `batch_create_invoices` with an entry of `metadata` bytes for each item
+        pub(crate) fn batch_create_invoices_weight(
+            metadata: impl IntoIterator<Item = u32>,
+        ) -> Weight {
+            metadata.into_iter().fold(
+                // `InvoiceCount` read, `LastBatchOutcome` written
+                T::DbWeight::get().reads_writes(1, 1),
+                |weight, metadata| {
+                    weight.saturating_add(Self::create_invoice_weight(metadata, 0, false, false))
+                },
+            )
+        }
+
+        /// Weight of `update_invoice_metadata` with `metadata` bytes, moving `grants` access
+        /// grants to the new hash
+        pub(crate) fn update_invoice_metadata_weight(metadata: u32, grants: u32) -> Weight {
+            let grants = grants as u64;
+            T::WeightInfo::update_invoice_metadata(metadata)
+                // `AccessGrantCount` and every grant read, removed and written under the new hash
+                .saturating_add(T::DbWeight::get().reads_writes(grants + 1, 2 * grants + 2))
+        }
+
+        /// Weight of `create_signed_invoice` with `metadata` bytes, trying `keys` operator keys
+        pub(crate) fn create_signed_invoice_weight(metadata: u32, keys: u32) -> Weight {
+            T::WeightInfo::create_invoice(metadata, 0)
+                .saturating_add(T::WeightInfo::verify_operator_signature(keys))
+                // `UsedOperatorPayloads` read and written, `InvoiceOperator` written
+                .saturating_add(T::DbWeight::get().reads_writes(1, 2))
+        }
+
+        /// Remove an invoice and every record keyed by it, shared by `purge_invoice` and
+        /// `archive_invoices`. `InvoiceByHash` and the client's invoice index are left to the
+        /// caller.
+        fn remove_invoice_records(invoice: &Invoice<T>) {
+            let client = &invoice.client;
+            let invoice_id = invoice.id;
+
+            Invoices::<T>::remove(client, invoice_id);
+            InvoicesByCreator::<T>::remove(&invoice.created_by, invoice_id);
+            InvoiceCredits::<T>::remove(client, invoice_id);
+            PendingCancellations::<T>::remove(client, invoice_id);
+            CancellationBlockedUntil::<T>::remove(client, invoice_id);
+            InvoiceDisputes::<T>::remove(client, invoice_id);
+            InvoiceOperator::<T>::remove(invoice_id);
+            InvoiceSignatures::<T>::remove(invoice_id);
+            OffchainLinks::<T>::remove(invoice_id);
+            InvoiceAttachments::<T>::remove(client, invoice_id);
+            InvoiceAssignments::<T>::remove(client, invoice_id);
+            let grants = AccessGrantCount::<T>::take(invoice.invoice_hash);
+            let _ = AccessGrants::<T>::clear_prefix(invoice.invoice_hash, grants, None);
+            if !invoice.external_ref.is_empty() {
+                InvoiceByExternalRef::<T>::remove(&invoice.external_ref);
+            }
+        }
+
+        /// Ensure `proof_hash` is the current hash of the invoice and it is not linked yet
+        fn ensure_link_confirmable(invoice_id: u64, proof_hash: &[u8; 32]) -> Result<(), Error<T>> {
+            ensure!(
+                InvoiceByHash::<T>::get(proof_hash) == Some(invoice_id),
+                Error::<T>::LinkProofMismatch
+            );
+            ensure!(
+                !OffchainLinks::<T>::contains_key(invoice_id),
+                Error::<T>::OffchainLinkAlreadyConfirmed
+            );
+            Ok(())
+        }
+
+        /// Cancel an invoice agreed to be cancelled by its client
+        fn do_cancel_invoice(client: T::AccountId, invoice_id: u64) -> DispatchResult {
+            Invoices::<T>::try_mutate(&client, invoice_id, |maybe_invoice| -> DispatchResult {
+                let invoice = maybe_invoice.as_mut().ok_or(Error::<T>::InvoiceNotFound)?;
+                Self::set_invoice_status(invoice, InvoiceStatus::Cancelled)?;
+                Ok(())
+            })?;
+
+            Self::deposit_event(Event::InvoiceCancelled { invoice_id, client });
+
+            Ok(())
+        }
+
+        /// Close the open challenge on an invoice with `outcome`, settling the bond and the
+        /// creation deposit.
+        ///
+        /// A conceded challenge cancels the invoice and returns the bond. An upheld or timed
+        /// out one also pays the creation deposit to the challenger. A dismissed one pays the
+        /// bond to the creator and reinstates the invoice.
+        fn close_challenge(
+            client: T::AccountId,
+            invoice_id: u64,
+            outcome: ChallengeOutcome,
+        ) -> DispatchResult {
+            let challenge = InvoiceChallenges::<T>::take(&client, invoice_id)
+                .ok_or(Error::<T>::InvoiceNotChallenged)?;
+            let creator = Invoices::<T>::get(&client, invoice_id)
+                .ok_or(Error::<T>::InvoiceNotFound)?
+                .created_by;
+
+            Self::deposit_event(Event::ChallengeClosed {
+                invoice_id,
+                client: client.clone(),
+                challenger: challenge.challenger.clone(),
+                outcome: outcome.clone(),
+            });
+
+            match outcome {
+                ChallengeOutcome::Conceded => {
+                    Self::release_challenge_bond(invoice_id, &challenge);
+                    Self::do_cancel_invoice(client, invoice_id)
+                },
+                ChallengeOutcome::Upheld | ChallengeOutcome::TimedOut => {
+                    Self::release_challenge_bond(invoice_id, &challenge);
+                    // Awarded first, so cancelling finds no deposit left to release
+                    Self::award_deposit(&creator, invoice_id, &challenge.challenger);
+                    Self::do_cancel_invoice(client, invoice_id)
+                },
+                ChallengeOutcome::Dismissed => {
+                    Self::slash_challenge_bond(invoice_id, &challenge, &creator);
+                    Self::reinstate_challenged(&client, invoice_id)
+                },
+            }
+        }
+
+        /// Move a challenged invoice back to `Pending`, or `Overdue` once past its due block
+        fn reinstate_challenged(client: &T::AccountId, invoice_id: u64) -> DispatchResult {
+            let now = frame_system::Pallet::<T>::block_number();
+            let overdue = Invoices::<T>::try_mutate(client, invoice_id, |maybe_invoice| {
+                let invoice = maybe_invoice.as_mut().ok_or(Error::<T>::InvoiceNotFound)?;
+                Self::set_invoice_status(invoice, InvoiceStatus::Pending)?;
+                // The due sweep skipped the invoice while it was challenged
+                if invoice.due_block.is_some_and(|due| due <= now) {
+                    Self::set_invoice_status(invoice, InvoiceStatus::Overdue)?;
+                    return Ok::<_, Error<T>>(true);
+                }
+                Ok(false)
+            })?;
+
+            if overdue {
+                Self::deposit_event(Event::InvoiceOverdue {
+                    invoice_id,
+                    client: client.clone(),
+                });
+            }
+
+            Ok(())
+        }
+
+        /// Canonical preimage signed by operators for `create_signed_invoice`
+        pub fn operator_payload(
+            client: &T::AccountId,
+            amount: &BalanceOf<T>,
+            metadata: &[u8],
+        ) -> Vec<u8> {
+            (OPERATOR_PAYLOAD_TAG, client, amount, metadata).encode()
+        }
+
+        /// Active operator key that produced `signature` over `payload`, if any, with the
+        /// number of keys tried to find it
+        pub(crate) fn verify_operator_signature(
+            payload: &[u8],
+            signature: &[u8],
+        ) -> Option<(sr25519::Public, u32)> {
+            let signature = sr25519::Signature::try_from(signature).ok()?;
+            OperatorKeys::<T>::get()
+                .into_iter()
+                .zip(1..)
+                .find(|(key, _)| sp_io::crypto::sr25519_verify(&signature, payload, key))
+        }
+
+        /// Whether `signature` is an sr25519 or ed25519 signature of `hash` by `who`'s key
+        pub(crate) fn verify_creator_signature(
+            who: &T::AccountId,
+            hash: &[u8; 32],
+            signature: &[u8],
+        ) -> bool {
+            let Some(key) = T::CreatorKeys::public_key(who) else {
+                return false;
+            };
+            let sr25519_valid = sr25519::Signature::try_from(signature).map_or(false, |signature| {
+                sp_io::crypto::sr25519_verify(&signature, hash, &sr25519::Public::from_raw(key))
+            });
+            sr25519_valid
+                || ed25519::Signature::try_from(signature).map_or(false, |signature| {
+                    sp_io::crypto::ed25519_verify(&signature, hash, &ed25519::Public::from_raw(key))
+                })
+        }
+
+        /// Move an invoice to `to` following `INVOICE_STATUS`, returning the previous status.
+        ///
+        /// Cancellation requests on an invoice end with it reaching a terminal status, and
+        /// cancelling, expiring or rejecting removes its unpaid rest from `OutstandingBalance`
+        /// and from its creator's invoiced total. Reaching any terminal status releases the
+        /// creation deposit.
+        pub(crate) fn set_invoice_status(
+            invoice: &mut Invoice<T>,
+            to: InvoiceStatus,
+        ) -> Result<InvoiceStatus, Error<T>> {
+            let from = invoice.status.clone();
+            // Whatever is left unpaid is no longer owed once cancelled, expired or rejected
+            let written_off = Self::outstanding(invoice);
+            INVOICE_STATUS
+                .transition(&mut invoice.status, to)
+                .map_err(|_| Self::status_error(&from))?;
+            Self::note_transition(invoice, &from);
+
+            if matches!(
+                invoice.status,
+                InvoiceStatus::Cancelled | InvoiceStatus::Expired | InvoiceStatus::Rejected
+            ) {
+                Self::reduce_outstanding_balance(&invoice.client, written_off);
+                Self::note_written_off(&invoice.created_by, written_off);
+            }
+
+            if INVOICE_STATUS.is_terminal(&invoice.status) {
+                PendingCancellations::<T>::remove(&invoice.client, invoice.id);
+                CancellationBlockedUntil::<T>::remove(&invoice.client, invoice.id);
+                Self::release_deposit(&invoice.created_by, invoice.id);
+            }
+
+            Ok(from)
+        }
+
+        /// Report the transition of `invoice` from `from` to its current status
+        fn note_transition(invoice: &Invoice<T>, from: &InvoiceStatus) {
+            T::OnTransition::on_transition(
+                TransitionSubject::Invoice(invoice.client.clone(), invoice.id),
+                status_index(from),
+                status_index(&invoice.status),
+            );
+        }
+
+        /// Reserve `InvoiceDeposit` from the creator of a new invoice
+        fn reserve_deposit(creator: &T::AccountId, invoice_id: u64) -> DispatchResult {
+            let amount = T::InvoiceDeposit::get();
+            if amount.is_zero() {
+                return Ok(());
+            }
+
+            T::Currency::reserve(creator, amount).map_err(|_| Error::<T>::InsufficientDeposit)?;
+            InvoiceDeposits::<T>::insert(invoice_id, amount);
+
+            Self::deposit_event(Event::InvoiceDepositReserved {
+                invoice_id,
+                creator: creator.clone(),
+                amount,
+            });
+
+            Ok(())
+        }
+
+        /// Return the creation deposit of an invoice, if it still has one
+        fn release_deposit(creator: &T::AccountId, invoice_id: u64) {
+            if let Some(amount) = InvoiceDeposits::<T>::take(invoice_id) {
+                T::Currency::unreserve(creator, amount);
+                Self::deposit_event(Event::InvoiceDepositReleased {
+                    invoice_id,
+                    creator: creator.clone(),
+                    amount,
+                });
+            }
+        }
+
+        /// Slash the creation deposit of an invoice to `Config::Slashed`, if it has one
+        fn slash_deposit(creator: &T::AccountId, invoice_id: u64) {
+            if let Some(deposit) = InvoiceDeposits::<T>::take(invoice_id) {
+                let (imbalance, missing) = T::Currency::slash_reserved(creator, deposit);
+                let amount = deposit.saturating_sub(missing);
+                T::Slashed::on_unbalanced(imbalance);
+                Self::deposit_event(Event::InvoiceDepositSlashed {
+                    invoice_id,
+                    creator: creator.clone(),
+                    amount,
+                });
+            }
+        }
+
+        /// Pay the creation deposit of an invoice found fraudulent to its challenger, if it has
+        /// one
+        fn award_deposit(creator: &T::AccountId, invoice_id: u64, challenger: &T::AccountId) {
+            if let Some(deposit) = InvoiceDeposits::<T>::take(invoice_id) {
+                let missing = T::Currency::repatriate_reserved(
+                    creator,
+                    challenger,
+                    deposit,
+                    BalanceStatus::Free,
+                )
+                .unwrap_or(deposit);
+                Self::deposit_event(Event::InvoiceDepositAwarded {
+                    invoice_id,
+                    creator: creator.clone(),
+                    challenger: challenger.clone(),
+                    amount: deposit.saturating_sub(missing),
+                });
+            }
+        }
+
+        /// Pay the creation deposit of an abandoned invoice to its client, returning the amount
+        /// paid
+        fn compensate_client(
+            creator: &T::AccountId,
+            invoice_id: u64,
+            client: &T::AccountId,
+        ) -> BalanceOf<T> {
+            let Some(deposit) = InvoiceDeposits::<T>::take(invoice_id) else {
+                return Zero::zero();
+            };
+            let missing =
+                T::Currency::repatriate_reserved(creator, client, deposit, BalanceStatus::Free)
+                    .unwrap_or(deposit);
+            deposit.saturating_sub(missing)
+        }
+
+        /// Restart the abandonment clock of an invoice its creator acted on
+        fn note_creator_activity(client: &T::AccountId, invoice_id: u64) {
+            Invoices::<T>::mutate(client, invoice_id, |maybe_invoice| {
+                if let Some(invoice) = maybe_invoice {
+                    invoice.last_activity = frame_system::Pallet::<T>::block_number();
+                }
+            });
+        }
+
+        /// Return the bond of a conceded or upheld challenge
+        fn release_challenge_bond(invoice_id: u64, challenge: &InvoiceChallenge<T>) {
+            T::Currency::unreserve(&challenge.challenger, challenge.bond);
+            Self::deposit_event(Event::ChallengeBondReleased {
+                invoice_id,
+                challenger: challenge.challenger.clone(),
+                amount: challenge.bond,
+            });
+        }
+
+        /// Pay the bond of a dismissed challenge to the invoice creator
+        fn slash_challenge_bond(
+            invoice_id: u64,
+            challenge: &InvoiceChallenge<T>,
+            creator: &T::AccountId,
+        ) {
+            let missing = T::Currency::repatriate_reserved(
+                &challenge.challenger,
+                creator,
+                challenge.bond,
+                BalanceStatus::Free,
+            )
+            .unwrap_or(challenge.bond);
+            Self::deposit_event(Event::ChallengeBondSlashed {
+                invoice_id,
+                challenger: challenge.challenger.clone(),
+                creator: creator.clone(),
+                amount: challenge.bond.saturating_sub(missing),
+            });
+        }
+
+        /// Net amount plus accrued late fee still to be paid on an invoice after installments
+        /// and credit notes
+        pub(crate) fn outstanding(invoice: &Invoice<T>) -> BalanceOf<T> {
+            if matches!(
+                invoice.status,
+                InvoiceStatus::Cancelled | InvoiceStatus::Expired | InvoiceStatus::Rejected
+            ) {
+                return Zero::zero();
+            }
+            invoice
+                .net_amount()
+                .saturating_add(invoice.accrued_late_fee)
+                .saturating_sub(invoice.paid_amount)
+                .saturating_sub(InvoiceCredits::<T>::get(&invoice.client, invoice.id))
+        }
+
+        /// Late fee an invoice has accrued by `now`: one `LateFeePerPeriod` for every full
+        /// `LateFeePeriod` since its due block. Only open invoices accrue fees, and a charged
+        /// fee is never reduced.
+        pub(crate) fn late_fee_at(
+            invoice: &Invoice<T>,
+            now: BlockNumberFor<T>,
+        ) -> Result<BalanceOf<T>, Error<T>> {
+            let period = T::LateFeePeriod::get();
+            let open = matches!(
+                invoice.status,
+                InvoiceStatus::Pending | InvoiceStatus::Overdue | InvoiceStatus::Accepted
+            );
+            let due = match invoice.due_block {
+                Some(due) if open && !period.is_zero() && now > due => due,
+                _ => return Ok(invoice.accrued_late_fee),
+            };
+
+            let periods: u32 = (now.saturating_sub(due) / period).unique_saturated_into();
+            let per_period = match T::LateFeePerPeriod::get() {
+                LateFee::Flat(fee) => fee,
+                LateFee::PerMille(per_mille) => invoice
+                    .net_amount()
+                    .checked_mul(&per_mille.into())
+                    .ok_or(Error::<T>::ArithmeticOverflow)? /
+                    1000u32.into(),
+            };
+            let fee = per_period
+                .checked_mul(&periods.into())
+                .ok_or(Error::<T>::ArithmeticOverflow)?;
+
+            Ok(fee.max(invoice.accrued_late_fee))
+        }
+
+        /// Charge the late fee an invoice has accrued by now, adding the increase to the
+        /// client's `OutstandingBalance` and to the creator's invoiced total
+        fn accrue_late_fee(invoice: &mut Invoice<T>) -> Result<(), Error<T>> {
+            let fee = Self::late_fee_at(invoice, frame_system::Pallet::<T>::block_number())?;
+            let increase = fee.saturating_sub(invoice.accrued_late_fee);
+            if increase.is_zero() {
+                return Ok(());
+            }
+
+            let balance = OutstandingBalance::<T>::get(&invoice.client)
+                .checked_add(&increase)
+                .ok_or(Error::<T>::ArithmeticOverflow)?;
+            OutstandingBalance::<T>::insert(&invoice.client, balance);
+            Self::note_invoiced(&invoice.created_by, increase);
+            invoice.accrued_late_fee = fee;
+
+            Self::deposit_event(Event::LateFeeAccrued {
+                invoice_id: invoice.id,
+                client: invoice.client.clone(),
+                accrued_late_fee: fee,
+            });
+
+            Ok(())
+        }
+
+        /// Add a payment to `PaymentsThisBlock`, chaining it into the digest
+        fn note_payment(invoice_id: u64, amount: BalanceOf<T>) {
+            PaymentsThisBlock::<T>::mutate(|(count, total, digest)| {
+                *count = count.saturating_add(1);
+                *total = total.saturating_add(amount);
+                let preimage =
+                    [&digest[..], &invoice_id.to_le_bytes(), &amount.encode()[..]].concat();
+                *digest = sha2_256(&preimage);
+            });
+        }
+
+        /// Subtract a settled or written-off amount from a client's `OutstandingBalance`
+        fn reduce_outstanding_balance(client: &T::AccountId, amount: BalanceOf<T>) {
+            OutstandingBalance::<T>::mutate(client, |balance| {
+                *balance = balance.saturating_sub(amount)
+            });
+        }
+
+        /// Add a newly owed amount to a creator's invoiced total in `CreatorStats`
+        fn note_invoiced(creator: &T::AccountId, amount: BalanceOf<T>) {
+            CreatorStats::<T>::mutate(creator, |(invoiced, _)| {
+                *invoiced = invoiced.saturating_add(amount)
+            });
+        }
+
+        /// Add a payment to a creator's collected total in `CreatorStats`
+        fn note_collected(creator: &T::AccountId, amount: BalanceOf<T>) {
+            CreatorStats::<T>::mutate(creator, |(_, collected)| {
+                *collected = collected.saturating_add(amount)
+            });
+        }
+
+        /// Take an amount no longer owed, credited or written off, out of a creator's invoiced
+        /// total in `CreatorStats`
+        fn note_written_off(creator: &T::AccountId, amount: BalanceOf<T>) {
+            if amount.is_zero() {
+                return;
+            }
+            CreatorStats::<T>::mutate(creator, |(invoiced, _)| {
+                *invoiced = invoiced.saturating_sub(amount)
+            });
+        }
+
+        /// Ensure an account may create invoices
+        fn ensure_authorized_creator(who: &T::AccountId) -> Result<(), Error<T>> {
+            ensure!(
+                T::OpenCreation::get() || AuthorizedCreators::<T>::contains_key(who),
+                Error::<T>::NotAuthorizedCreator
+            );
+            Ok(())
+        }
+
+        /// Ensure an invoice is not under dispute
+        fn ensure_not_disputed(client: &T::AccountId, invoice_id: u64) -> Result<(), Error<T>> {
+            ensure!(
+                !InvoiceDisputes::<T>::contains_key(client, invoice_id),
+                Error::<T>::InvoiceDisputed
+            );
+            Ok(())
+        }
 
-    #[pallet::event]
-    #[pallet::generate_deposit(pub(super) fn deposit_event)]
-    pub enum Event<T: Config> {
-        /// Invoice created [invoice_id, client, amount, invoice_hash]
-        InvoiceCreated {
-            invoice_id: u64,
-            client: T::AccountId,
-            amount: BalanceOf<T>,
-            invoice_hash: [u8; 32],
-            created_by: T::AccountId,
-        },
-        /// Invoices retrieved [client, count]
-        InvoiceRetrieved {
-            client: T::AccountId,
-            count: u32,
-        },
-        /// Invoice hash stored [invoice_hash, invoice_id]
-        InvoiceHashStored {
-            invoice_hash: [u8; 32],
+        /// Ensure an invoice is not challenged, which would let its creator cancel it without
+        /// facing the ruling
+        fn ensure_not_challenged(invoice: &Invoice<T>) -> Result<(), Error<T>> {
+            ensure!(invoice.status != InvoiceStatus::Challenged, Error::<T>::InvoiceChallenged);
+            Ok(())
+        }
+
+        /// Ensure an invoice can still receive payments
+        fn ensure_payable(invoice: &Invoice<T>) -> Result<(), Error<T>> {
+            Self::ensure_not_disputed(&invoice.client, invoice.id)?;
+            if INVOICE_STATUS.can_transition(&invoice.status, &InvoiceStatus::Paid) {
+                Ok(())
+            } else {
+                Err(Self::status_error(&invoice.status))
+            }
+        }
+
+        /// Move an invoice of `client` created by `creator` to the client's answer, `Accepted`
+        /// or `Rejected`
+        fn answer_invoice(
+            client: &T::AccountId,
+            creator: &T::AccountId,
             invoice_id: u64,
-        },
-    }
+            answer: InvoiceStatus,
+        ) -> DispatchResult {
+            Invoices::<T>::try_mutate(client, invoice_id, |maybe_invoice| {
+                let invoice = maybe_invoice.as_mut().ok_or(Error::<T>::InvoiceNotFound)?;
+                ensure!(&invoice.created_by == creator, Error::<T>::CreatorMismatch);
+                Self::ensure_not_disputed(client, invoice_id)?;
+                Self::set_invoice_status(invoice, answer)?;
+                Ok(())
+            })
+        }
 
-    #[pallet::error]
-    pub enum Error<T> {
-        /// Too many invoices for this client
-        TooManyInvoices,
-        /// Metadata too long
-        MetadataTooLong,
-        /// Invoice not found
-        InvoiceNotFound,
-        /// Invalid invoice data
-        InvalidInvoiceData,
-        /// Arithmetic overflow
-        ArithmeticOverflow,
-    }
+        /// Ensure the client has answered an invoice awaiting acceptance, if that is required
+        fn ensure_accepted(invoice: &Invoice<T>) -> Result<(), Error<T>> {
+            let awaiting =
+                matches!(invoice.status, InvoiceStatus::Pending | InvoiceStatus::Overdue);
+            ensure!(!(T::RequireAcceptance::get() && awaiting), Error::<T>::InvoiceNotAccepted);
+            Ok(())
+        }
 
-    #[pallet::call]
-    impl<T: Config> Pallet<T> {
-        /// Create a new invoice
-        ///
-        /// # Arguments
-        /// * `origin` - Transaction origin (invoice creator)
-        /// * `client` - Client account ID
-        /// * `amount` - Invoice amount
-        /// * `metadata` - Invoice metadata (e.g., invoice number, description, JSON data)
-        ///
-        /// # Returns
-        /// * `DispatchResult` - Success or error
+        /// Error reported when an invoice in `status` cannot change state
+        fn status_error(status: &InvoiceStatus) -> Error<T> {
+            match status {
+                InvoiceStatus::Paid => Error::<T>::InvoiceAlreadyPaid,
+                InvoiceStatus::Cancelled => Error::<T>::InvoiceCancelled,
+                InvoiceStatus::Expired => Error::<T>::InvoiceExpired,
+                InvoiceStatus::Challenged => Error::<T>::InvoiceChallenged,
+                InvoiceStatus::Accepted => Error::<T>::InvoiceAlreadyAccepted,
+                InvoiceStatus::Rejected => Error::<T>::InvoiceRejected,
+                _ => Error::<T>::InvalidStatusTransition,
+            }
+        }
+
+        /// Validate and normalize a text field if `NormalizeText` is enabled
+        pub(crate) fn sanitize_text(bytes: Vec<u8>) -> Result<Vec<u8>, Error<T>> {
+            if !NormalizeText::<T>::get() {
+                return Ok(bytes);
+            }
+            text::sanitize(bytes).ok_or(Error::<T>::InvalidUtf8)
+        }
+
+        /// Walk the `DueInvoices` index up to `now`, flagging unpaid invoices as overdue.
         ///
-        /// # Events
-        /// * `InvoiceCreated` - Emitted when invoice is successfully created
-        /// * `InvoiceHashStored` - Emitted when invoice hash is stored
+        /// Processing stops as soon as the next step would exceed `budget`; the position
+        /// is kept in `OverdueCursor` so the remaining entries are handled in later blocks.
+        pub(crate) fn process_due_invoices(now: BlockNumberFor<T>, budget: Weight) -> Weight {
+            let db = T::DbWeight::get();
+            let per_block = db.reads_writes(1, 1);
+            let per_invoice = db.reads_writes(1, 1);
+
+            let mut used = db.reads(1);
+            let (mut block, mut index) = OverdueCursor::<T>::get().unwrap_or((now, 0));
+
+            'blocks: while block <= now {
+                if used.saturating_add(per_block).any_gt(budget) {
+                    break;
+                }
+                used = used.saturating_add(per_block);
+
+                let due = DueInvoices::<T>::get(block);
+                while let Some((client, invoice_id)) = due.get(index as usize) {
+                    if used.saturating_add(per_invoice).any_gt(budget) {
+                        break 'blocks;
+                    }
+                    used = used.saturating_add(per_invoice);
+
+                    Self::mark_overdue(client, *invoice_id);
+                    index = index.saturating_add(1);
+                }
+
+                DueInvoices::<T>::remove(block);
+                block = block.saturating_add(One::one());
+                index = 0;
+            }
+
+            OverdueCursor::<T>::put((block, index));
+            used.saturating_add(db.writes(1))
+        }
+
+        /// Flip a pending invoice to `Overdue`; invoices in any other state are left untouched.
+        fn mark_overdue(client: &T::AccountId, invoice_id: u64) {
+            let flipped = Invoices::<T>::mutate(client, invoice_id, |maybe_invoice| {
+                maybe_invoice.as_mut().is_some_and(|invoice| {
+                    INVOICE_STATUS
+                        .transition(&mut invoice.status, InvoiceStatus::Overdue)
+                        .map(|from| Self::note_transition(invoice, &from))
+                        .is_ok()
+                })
+            });
+
+            if flipped {
+                Self::deposit_event(Event::InvoiceOverdue {
+                    invoice_id,
+                    client: client.clone(),
+                });
+            }
+        }
+
+        /// Uphold every challenge timing out at `now` that is still open.
         ///
-        /// # Example
-        /// ```ignore
-        /// create_invoice(
-        ///     origin,
-        ///     client_account,
-        ///     1000000,
-        ///     b"INV-2025-001|Client XYZ|Net 30".to_vec()
-        /// )
-        /// ```
-        #[pallet::call_index(0)]
-        #[pallet::weight(10_000)]
-        pub fn create_invoice(
-            origin: OriginFor<T>,
-            client: T::AccountId,
-            amount: BalanceOf<T>,
-            metadata: Vec<u8>,
-        ) -> DispatchResult {
-            let who = ensure_signed(origin)?;
+        /// `ChallengeTimeouts` holds at most `MaxChallengesPerBlock` entries per block, so the
+        /// whole block is handled at once.
+        pub(crate) fn process_challenge_timeouts(now: BlockNumberFor<T>) -> Weight {
+            let db = T::DbWeight::get();
+            // Challenge, invoice and deposit reads; challenge, invoice, balance, reserve,
+            // cancellation and deposit writes
+            let per_challenge = db.reads_writes(3, 8);
 
-            // Validate metadata length
-            let bounded_metadata: BoundedVec<u8, T::MaxMetadataLength> = metadata
-                .try_into()
-                .map_err(|_| Error::<T>::MetadataTooLong)?;
+            let timeouts = ChallengeTimeouts::<T>::take(now);
+            let mut used = db.reads_writes(1, 1);
+            for (client, invoice_id) in timeouts {
+                used = used.saturating_add(per_challenge);
+                // Conceded or ruled-on challenges are gone; a later challenge on the same
+                // invoice times out at its own block
+                let open = InvoiceChallenges::<T>::get(&client, invoice_id)
+                    .is_some_and(|challenge| challenge.timeout_at == now);
+                if !open {
+                    continue;
+                }
 
-            // Get next invoice ID
-            let invoice_id = InvoiceCount::<T>::get();
-            let current_block = frame_system::Pallet::<T>::block_number();
+                // Hooks run outside a storage transaction, and `close_challenge` changes
+                // storage before the steps that can fail
+                let result = with_transaction(|| {
+                    let result = Self::close_challenge(
+                        client.clone(),
+                        invoice_id,
+                        ChallengeOutcome::TimedOut,
+                    );
+                    match result {
+                        Ok(_) => TransactionOutcome::Commit(result),
+                        Err(_) => TransactionOutcome::Rollback(result),
+                    }
+                });
+                if let Err(error) = result {
+                    Self::deposit_event(Event::ChallengeTimeoutFailed {
+                        invoice_id,
+                        client,
+                        error,
+                    });
+                }
+            }
+            used
+        }
 
-            // Create invoice struct
-            let mut invoice = Invoice {
-                id: invoice_id,
-                client: client.clone(),
-                amount,
-                metadata: bounded_metadata,
-                timestamp: current_block,
-                invoice_hash: [0u8; 32], // Placeholder, will be calculated
-                created_by: who.clone(),
-            };
+        /// Add an invoice to the `ExpiringInvoices` index at `expires_at`
+        fn schedule_expiry(
+            client: &T::AccountId,
+            invoice_id: u64,
+            expires_at: BlockNumberFor<T>,
+        ) -> DispatchResult {
+            ensure!(
+                expires_at > frame_system::Pallet::<T>::block_number(),
+                Error::<T>::InvalidExpiryBlock
+            );
+            ExpiringInvoices::<T>::try_append(expires_at, (client.clone(), invoice_id))
+                .map_err(|_| Error::<T>::TooManyInvoicesExpiring)?;
+            Ok(())
+        }
 
-            // Calculate SHA256 hash of invoice details
-            let invoice_hash = invoice.calculate_hash();
-            invoice.invoice_hash = invoice_hash;
+        /// Walk the `ExpiringInvoices` index up to `now`, expiring unpaid invoices.
+        ///
+        /// Never uses more than `budget`: nothing is done when it does not even cover the
+        /// cursor, and processing stops before the next step would exceed it. The position is
+        /// kept in `ExpiryCursor` so the remaining entries are handled in later blocks.
+        pub(crate) fn process_expiring_invoices(
+            now: BlockNumberFor<T>,
+            budget: Weight,
+        ) -> Weight {
+            let db = T::DbWeight::get();
+            let per_block = db.reads_writes(1, 1);
+            // Invoice, dispute and deposit reads; invoice, balance, cancellation and deposit
+            // writes
+            let per_invoice = db.reads_writes(3, 5);
 
-            // Get or create invoice list for client
-            let mut client_invoices = Invoices::<T>::get(&client);
+            let mut used = db.reads_writes(1, 1);
+            if used.any_gt(budget) {
+                return Weight::zero();
+            }
+            let (mut block, mut index) = ExpiryCursor::<T>::get().unwrap_or((now, 0));
 
-            // Check if we can add more invoices
-            client_invoices
-                .try_push(invoice.clone())
-                .map_err(|_| Error::<T>::TooManyInvoices)?;
+            'blocks: while block <= now {
+                if used.saturating_add(per_block).any_gt(budget) {
+                    break;
+                }
+                used = used.saturating_add(per_block);
 
-            // Store updated invoice list
-            Invoices::<T>::insert(&client, client_invoices);
+                let expiring = ExpiringInvoices::<T>::get(block);
+                while let Some((client, invoice_id)) = expiring.get(index as usize) {
+                    if used.saturating_add(per_invoice).any_gt(budget) {
+                        break 'blocks;
+                    }
+                    used = used.saturating_add(per_invoice);
 
-            // Store hash mapping for quick lookup
-            InvoiceByHash::<T>::insert(invoice_hash, invoice_id);
+                    Self::expire_invoice(client, *invoice_id);
+                    index = index.saturating_add(1);
+                }
 
-            // Increment invoice counter
-            let next_id = invoice_id
-                .checked_add(1)
-                .ok_or(Error::<T>::ArithmeticOverflow)?;
-            InvoiceCount::<T>::put(next_id);
+                ExpiringInvoices::<T>::remove(block);
+                block = block.saturating_add(One::one());
+                index = 0;
+            }
 
-            // Emit events
-            Self::deposit_event(Event::InvoiceCreated {
-                invoice_id,
-                client: client.clone(),
-                amount,
-                invoice_hash,
-                created_by: who,
-            });
+            ExpiryCursor::<T>::put((block, index));
+            used
+        }
 
-            Self::deposit_event(Event::InvoiceHashStored {
-                invoice_hash,
-                invoice_id,
+        /// Expire an unpaid, undisputed invoice; invoices in any other state are left untouched.
+        fn expire_invoice(client: &T::AccountId, invoice_id: u64) {
+            if InvoiceDisputes::<T>::contains_key(client, invoice_id) {
+                return;
+            }
+            let expired = Invoices::<T>::mutate(client, invoice_id, |maybe_invoice| {
+                maybe_invoice.as_mut().is_some_and(|invoice| {
+                    Self::set_invoice_status(invoice, InvoiceStatus::Expired).is_ok()
+                })
             });
 
+            if expired {
+                Self::deposit_event(Event::InvoiceExpired {
+                    invoice_id,
+                    client: client.clone(),
+                });
+            }
+        }
+
+        /// The stored invoice with `invoice_hash` that `who` created or was invoiced with
+        fn invoice_of_party(who: &T::AccountId, invoice_hash: [u8; 32]) -> Option<Invoice<T>> {
+            let invoice_id = InvoiceByHash::<T>::get(invoice_hash)?;
+            // Invoices the caller created record their client; otherwise it is the client
+            let client =
+                InvoicesByCreator::<T>::get(who, invoice_id).unwrap_or_else(|| who.clone());
+            Invoices::<T>::get(&client, invoice_id)
+                .filter(|invoice| invoice.invoice_hash == invoice_hash)
+        }
+
+        /// Ensure `who` is the creator or client of the invoice stored under `invoice_hash`
+        fn ensure_invoice_party(who: &T::AccountId, invoice_hash: [u8; 32]) -> DispatchResult {
+            ensure!(
+                InvoiceByHash::<T>::contains_key(invoice_hash),
+                Error::<T>::UnknownInvoiceHash
+            );
+            ensure!(
+                Self::invoice_of_party(who, invoice_hash).is_some(),
+                Error::<T>::NotInvoiceParty
+            );
             Ok(())
         }
 
-        /// Get all invoices for a specific client
-        ///
-        /// This is a read-only operation that emits an event for tracking purposes.
-        /// In a real application, you would query this via RPC instead of as an extrinsic.
-        ///
-        /// # Arguments
-        /// * `origin` - Transaction origin
-        /// * `client` - Client account ID to query invoices for
-        ///
-        /// # Returns
-        /// * `DispatchResult` - Success or error
+        /// Whether `who` may decrypt the payload of the invoice with `invoice_hash`
+        /// (helper function for RPC)
         ///
-        /// # Events
-        /// * `InvoiceRetrieved` - Emitted with the count of invoices retrieved
-        #[pallet::call_index(1)]
-        #[pallet::weight(5_000)]
-        pub fn get_invoices(origin: OriginFor<T>, client: T::AccountId) -> DispatchResult {
-            let _who = ensure_signed(origin)?;
+        /// True for accounts holding a grant and for the invoice's creator and client.
+        pub fn has_access(invoice_hash: [u8; 32], who: &T::AccountId) -> bool {
+            AccessGrants::<T>::contains_key(invoice_hash, who) ||
+                Self::invoice_of_party(who, invoice_hash).is_some()
+        }
+
+        /// Total a client still owes over all its invoices (helper function for RPC)
+        pub fn get_outstanding_balance(client: &T::AccountId) -> BalanceOf<T> {
+            OutstandingBalance::<T>::get(client)
+        }
 
-            // Get invoices for client
-            let client_invoices = Invoices::<T>::get(&client);
-            let count = client_invoices.len() as u32;
+        /// Invoiced and collected totals of a creator (helper function for RPC)
+        pub fn get_creator_stats(creator: &T::AccountId) -> (BalanceOf<T>, BalanceOf<T>) {
+            CreatorStats::<T>::get(creator)
+        }
 
-            // Emit event
-            Self::deposit_event(Event::InvoiceRetrieved { client, count });
+        /// The ledger's contribution to the runtime's feature flags, from its `Config` switches
+        pub fn feature_bits() -> FeatureFlags {
+            FeatureFlags::empty()
+                .with(FeatureFlags::OPEN_INVOICE_CREATION, T::OpenCreation::get())
+                .with(FeatureFlags::INVOICE_ACCEPTANCE, T::RequireAcceptance::get())
+                .with(FeatureFlags::INVOICE_WEBHOOK, T::InvoiceWebhook::get())
+                .with(FeatureFlags::INVOICE_DEPOSITS, !T::InvoiceDeposit::get().is_zero())
+                .with(FeatureFlags::LATE_FEES, !T::LateFeePeriod::get().is_zero())
+        }
 
-            Ok(())
+        /// `InvoiceCreated` events deposited so far in the current block (helper function for
+        /// RPC subscriptions, which call it at each finalized block)
+        pub fn created_invoices() -> Vec<CreatedInvoice<T::AccountId, BalanceOf<T>>>
+        where
+            <T as Config>::RuntimeEvent: TryInto<Event<T>>,
+        {
+            frame_system::Pallet::<T>::read_events_no_consensus()
+                .enumerate()
+                .filter_map(|(index, record)| {
+                    match <T as Config>::RuntimeEvent::from(record.event).try_into().ok()? {
+                        Event::InvoiceCreated {
+                            invoice_id,
+                            client,
+                            amount,
+                            invoice_hash,
+                            created_by,
+                            ..
+                        } => Some(CreatedInvoice {
+                            event_index: index as u32,
+                            invoice_id,
+                            client,
+                            amount,
+                            invoice_hash,
+                            created_by,
+                        }),
+                        _ => None,
+                    }
+                })
+                .collect()
         }
-    }
 
-    // Helper functions (not dispatchable, for RPC or internal use)
-    impl<T: Config> Pallet<T> {
         /// Get invoice by hash (helper function for RPC)
         pub fn get_invoice_by_hash(hash: [u8; 32]) -> Option<u64> {
             InvoiceByHash::<T>::get(hash)
         }
 
-        /// Get all invoices for a client (helper function for RPC)
-        pub fn get_client_invoices(client: &T::AccountId) -> Vec<Invoice<T>> {
-            Invoices::<T>::get(client).into_inner()
+        /// Get the invoice carrying an external reference (helper function for RPC)
+        pub fn get_invoice_by_external_ref(external_ref: &[u8]) -> Option<Invoice<T>> {
+            let bounded: BoundedVec<u8, T::MaxExternalRefLength> =
+                external_ref.to_vec().try_into().ok()?;
+            let (client, invoice_id) = InvoiceByExternalRef::<T>::get(bounded)?;
+            Invoices::<T>::get(client, invoice_id)
         }
 
-        /// Verify invoice hash matches stored data (for Django verification)
-        pub fn verify_invoice_hash(client: &T::AccountId, invoice_id: u64) -> bool {
-            let invoices = Invoices::<T>::get(client);
-            if let Some(invoice) = invoices.iter().find(|i| i.id == invoice_id) {
-                let calculated_hash = invoice.calculate_hash();
-                calculated_hash == invoice.invoice_hash
-            } else {
-                false
-            }
+        /// Get a single invoice of a client (helper function for RPC)
+        pub fn get_invoice(client: &T::AccountId, invoice_id: u64) -> Option<Invoice<T>> {
+            Invoices::<T>::get(client, invoice_id)
         }
-    }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use frame_support::{assert_noop, assert_ok, parameter_types, traits::ConstU32};
-    use sp_core::H256;
-    use sp_runtime::{
-        traits::{BlakeTwo256, IdentityLookup},
-        BuildStorage,
-    };
+        /// Documents attached to an invoice, in the order they were attached (helper function
+        /// for RPC)
+        pub fn get_attachments(client: &T::AccountId, invoice_id: u64) -> Vec<Attachment<T>> {
+            InvoiceAttachments::<T>::get(client, invoice_id).into_inner()
+        }
 
-    type Block = frame_system::mocking::MockBlock<Test>;
-
-    // Configure a mock runtime to test the pallet
-    frame_support::construct_runtime!(
-        pub enum Test {
-            System: frame_system,
-            Ledger: pallet,
-        }
-    );
-
-    parameter_types! {
-        pub const BlockHashCount: u64 = 250;
-        pub const SS58Prefix: u8 = 42;
-    }
-
-    impl frame_system::Config for Test {
-        type BaseCallFilter = frame_support::traits::Everything;
-        type BlockWeights = ();
-        type BlockLength = ();
-        type DbWeight = ();
-        type RuntimeOrigin = RuntimeOrigin;
-        type RuntimeCall = RuntimeCall;
-        type Nonce = u64;
-        type Hash = H256;
-        type Hashing = BlakeTwo256;
-        type AccountId = u64;
-        type Lookup = IdentityLookup<Self::AccountId>;
-        type Block = Block;
-        type RuntimeEvent = RuntimeEvent;
-        type BlockHashCount = BlockHashCount;
-        type Version = ();
-        type PalletInfo = PalletInfo;
-        type AccountData = ();
-        type OnNewAccount = ();
-        type OnKilledAccount = ();
-        type SystemWeightInfo = ();
-        type SS58Prefix = SS58Prefix;
-        type OnSetCode = ();
-        type MaxConsumers = frame_support::traits::ConstU32<16>;
-    }
-
-    parameter_types! {
-        pub const MaxMetadataLength: u32 = 1024;
-        pub const MaxInvoicesPerClient: u32 = 1000;
-    }
-
-    impl pallet::Config for Test {
-        type RuntimeEvent = RuntimeEvent;
-        type Currency = ();
-        type MaxMetadataLength = MaxMetadataLength;
-        type MaxInvoicesPerClient = MaxInvoicesPerClient;
-    }
-
-    // Build genesis storage
-    fn new_test_ext() -> sp_io::TestExternalities {
-        frame_system::GenesisConfig::<Test>::default()
-            .build_storage()
-            .unwrap()
-            .into()
-    }
-
-    #[test]
-    fn create_invoice_works() {
-        new_test_ext().execute_with(|| {
-            // Setup
-            let creator = 1u64;
-            let client = 2u64;
-            let amount = 1000u128;
-            let metadata = b"INV-2025-001|Test Client|Net 30".to_vec();
-
-            // Create invoice
-            assert_ok!(Ledger::create_invoice(
-                RuntimeOrigin::signed(creator),
-                client,
-                amount,
-                metadata.clone()
-            ));
-
-            // Verify invoice count incremented
-            assert_eq!(Ledger::invoice_count(), 1);
-
-            // Verify invoice is stored for client
-            let client_invoices = Ledger::get_client_invoices(&client);
-            assert_eq!(client_invoices.len(), 1);
-
-            // Verify invoice data
-            let invoice = &client_invoices[0];
-            assert_eq!(invoice.id, 0);
-            assert_eq!(invoice.client, client);
-            assert_eq!(invoice.amount, amount);
-            assert_eq!(invoice.metadata.to_vec(), metadata);
-            assert_eq!(invoice.created_by, creator);
-
-            // Verify hash was calculated
-            assert_ne!(invoice.invoice_hash, [0u8; 32]);
-
-            // Verify hash mapping
-            let stored_id = Ledger::get_invoice_by_hash(invoice.invoice_hash);
-            assert_eq!(stored_id, Some(0));
-        });
-    }
-
-    #[test]
-    fn create_multiple_invoices_works() {
-        new_test_ext().execute_with(|| {
-            let creator = 1u64;
-            let client = 2u64;
-
-            // Create first invoice
-            assert_ok!(Ledger::create_invoice(
-                RuntimeOrigin::signed(creator),
-                client,
-                1000u128,
-                b"Invoice 1".to_vec()
-            ));
+        /// Assignments of an invoice, oldest first (helper function for RPC)
+        pub fn get_assignments(client: &T::AccountId, invoice_id: u64) -> Vec<Assignment<T>> {
+            InvoiceAssignments::<T>::get(client, invoice_id).into_inner()
+        }
 
-            // Create second invoice
-            assert_ok!(Ledger::create_invoice(
-                RuntimeOrigin::signed(creator),
-                client,
-                2000u128,
-                b"Invoice 2".to_vec()
-            ));
+        /// Get all invoices for a client, ordered by invoice ID (helper function for RPC)
+        pub fn get_client_invoices(client: &T::AccountId) -> Vec<Invoice<T>> {
+            let mut invoices: Vec<Invoice<T>> = Invoices::<T>::iter_prefix_values(client).collect();
+            invoices.sort_by_key(|i| i.id);
+            invoices
+        }
 
-            // Create third invoice
-            assert_ok!(Ledger::create_invoice(
-                RuntimeOrigin::signed(creator),
-                client,
-                3000u128,
-                b"Invoice 3".to_vec()
-            ));
-
-            // Verify count
-            assert_eq!(Ledger::invoice_count(), 3);
-
-            // Verify all invoices are stored
-            let client_invoices = Ledger::get_client_invoices(&client);
-            assert_eq!(client_invoices.len(), 3);
-
-            // Verify invoice IDs are sequential
-            assert_eq!(client_invoices[0].id, 0);
-            assert_eq!(client_invoices[1].id, 1);
-            assert_eq!(client_invoices[2].id, 2);
-
-            // Verify amounts
-            assert_eq!(client_invoices[0].amount, 1000u128);
-            assert_eq!(client_invoices[1].amount, 2000u128);
-            assert_eq!(client_invoices[2].amount, 3000u128);
-        });
-    }
-
-    #[test]
-    fn multiple_clients_work() {
-        new_test_ext().execute_with(|| {
-            let creator = 1u64;
-            let client1 = 2u64;
-            let client2 = 3u64;
-
-            // Create invoices for client 1
-            assert_ok!(Ledger::create_invoice(
-                RuntimeOrigin::signed(creator),
-                client1,
-                1000u128,
-                b"Client 1 - Invoice 1".to_vec()
-            ));
-
-            assert_ok!(Ledger::create_invoice(
-                RuntimeOrigin::signed(creator),
-                client1,
-                1500u128,
-                b"Client 1 - Invoice 2".to_vec()
-            ));
-
-            // Create invoices for client 2
-            assert_ok!(Ledger::create_invoice(
-                RuntimeOrigin::signed(creator),
-                client2,
-                2000u128,
-                b"Client 2 - Invoice 1".to_vec()
-            ));
-
-            // Verify client 1 invoices
-            let client1_invoices = Ledger::get_client_invoices(&client1);
-            assert_eq!(client1_invoices.len(), 2);
-
-            // Verify client 2 invoices
-            let client2_invoices = Ledger::get_client_invoices(&client2);
-            assert_eq!(client2_invoices.len(), 1);
-
-            // Verify total count
-            assert_eq!(Ledger::invoice_count(), 3);
-        });
-    }
-
-    #[test]
-    fn get_invoices_works() {
-        new_test_ext().execute_with(|| {
-            let creator = 1u64;
-            let client = 2u64;
-
-            // Create invoices
-            assert_ok!(Ledger::create_invoice(
-                RuntimeOrigin::signed(creator),
-                client,
-                1000u128,
-                b"Invoice 1".to_vec()
-            ));
+        /// Number of invoices a client currently holds (helper function for RPC)
+        pub fn get_client_invoice_count(client: &T::AccountId) -> u32 {
+            ClientInvoiceCount::<T>::get(client)
+        }
 
-            assert_ok!(Ledger::create_invoice(
-                RuntimeOrigin::signed(creator),
-                client,
-                2000u128,
-                b"Invoice 2".to_vec()
-            ));
-
-            // Get invoices (this emits an event)
-            assert_ok!(Ledger::get_invoices(RuntimeOrigin::signed(creator), client));
-
-            // Verify event was emitted (checking system events)
-            System::assert_has_event(
-                Event::InvoiceRetrieved {
-                    client,
-                    count: 2,
+        /// Get all invoices issued by a creator, ordered by invoice ID (helper function for RPC)
+        pub fn get_creator_invoices(creator: &T::AccountId) -> Vec<Invoice<T>> {
+            let mut invoices: Vec<Invoice<T>> = InvoicesByCreator::<T>::iter_prefix(creator)
+                .filter_map(|(invoice_id, client)| Invoices::<T>::get(client, invoice_id))
+                .collect();
+            invoices.sort_by_key(|i| i.id);
+            invoices
+        }
+
+        /// Get a page of a client's invoices, ordered by invoice ID (helper function for RPC)
+        ///
+        /// Returns up to `limit` invoices starting at `cursor` (the first page for `None`), and
+        /// the cursor of the next page (`None` once the end is reached). Cursors hold an
+        /// invoice ID, so pages stay stable when new invoices are created.
+        pub fn get_client_invoices_paged(
+            client: &T::AccountId,
+            cursor: Option<&PageCursor>,
+            limit: u32,
+        ) -> Result<(Vec<Invoice<T>>, Option<PageCursor>), InvalidCursor> {
+            let start: u64 = PageCursor::position_or(cursor, 0)?;
+            if limit == 0 {
+                return Ok((Vec::new(), None));
+            }
+
+            // Binary search for the first position holding an ID >= start
+            let count = ClientInvoiceCount::<T>::get(client);
+            let (mut low, mut high) = (0u32, count);
+            while low < high {
+                let mid = low + (high - low) / 2;
+                match ClientInvoiceIds::<T>::get(client, mid) {
+                    Some(id) if id < start => low = mid + 1,
+                    _ => high = mid,
                 }
-                .into(),
-            );
-        });
-    }
+            }
 
-    #[test]
-    fn invoice_hash_is_unique() {
-        new_test_ext().execute_with(|| {
-            let creator = 1u64;
-            let client = 2u64;
+            let end = low.saturating_add(limit).min(count);
+            let invoices = (low..end)
+                .filter_map(|position| ClientInvoiceIds::<T>::get(client, position))
+                .filter_map(|id| Invoices::<T>::get(client, id))
+                .collect();
+            let next = ClientInvoiceIds::<T>::get(client, end).map(|id| PageCursor::new(&id));
 
-            // Create first invoice
-            assert_ok!(Ledger::create_invoice(
-                RuntimeOrigin::signed(creator),
-                client,
-                1000u128,
-                b"Invoice 1".to_vec()
-            ));
+            Ok((invoices, next))
+        }
 
-            // Create second invoice with different data
-            assert_ok!(Ledger::create_invoice(
-                RuntimeOrigin::signed(creator),
-                client,
-                1000u128,  // Same amount
-                b"Invoice 1".to_vec()  // Same metadata
-            ));
+        /// Net amount a client still owes on an invoice after payments and credit notes
+        /// (helper function for RPC). Cancelled invoices owe nothing.
+        pub fn outstanding_amount(client: &T::AccountId, invoice_id: u64) -> Option<BalanceOf<T>> {
+            Invoices::<T>::get(client, invoice_id).map(|invoice| Self::outstanding(&invoice))
+        }
 
-            // Get invoices
-            let invoices = Ledger::get_client_invoices(&client);
+        /// Amount a client has to pay to settle an invoice now, including the late fee accrued
+        /// up to the current block even if not yet charged (helper function for RPC). `None`
+        /// if there is no such invoice or its fee overflows.
+        pub fn get_amount_due(client: &T::AccountId, invoice_id: u64) -> Option<BalanceOf<T>> {
+            let mut invoice = Invoices::<T>::get(client, invoice_id)?;
+            invoice.accrued_late_fee =
+                Self::late_fee_at(&invoice, frame_system::Pallet::<T>::block_number()).ok()?;
+            Some(Self::outstanding(&invoice))
+        }
 
-            // Hashes should be different because IDs and timestamps are different
-            assert_ne!(invoices[0].invoice_hash, invoices[1].invoice_hash);
-        });
-    }
+        /// Hash of an archived invoice (for Django verification of pruned invoices)
+        pub fn get_archived_invoice_hash(
+            client: &T::AccountId,
+            invoice_id: u64,
+        ) -> Option<[u8; 32]> {
+            ArchivedInvoiceHashes::<T>::get(client, invoice_id)
+        }
 
-    #[test]
-    fn verify_invoice_hash_works() {
-        new_test_ext().execute_with(|| {
-            let creator = 1u64;
-            let client = 2u64;
+        /// Verify invoice hash matches stored data (for Django verification)
+        pub fn verify_invoice_hash(client: &T::AccountId, invoice_id: u64) -> bool {
+            if let Some(invoice) = Invoices::<T>::get(client, invoice_id) {
+                let calculated_hash = invoice.calculate_hash();
+                calculated_hash == invoice.invoice_hash
+            } else {
+                false
+            }
+        }
 
-            // Create invoice
-            assert_ok!(Ledger::create_invoice(
-                RuntimeOrigin::signed(creator),
-                client,
-                1000u128,
-                b"Test Invoice".to_vec()
-            ));
-
-            // Verify hash
-            assert!(Ledger::verify_invoice_hash(&client, 0));
-
-            // Verify non-existent invoice returns false
-            assert!(!Ledger::verify_invoice_hash(&client, 999));
-        });
-    }
-
-    #[test]
-    fn metadata_too_long_fails() {
-        new_test_ext().execute_with(|| {
-            let creator = 1u64;
-            let client = 2u64;
-            
-            // Create metadata that exceeds MaxMetadataLength (1024)
-            let long_metadata = vec![0u8; 1025];
-
-            // Should fail with MetadataTooLong error
-            assert_noop!(
-                Ledger::create_invoice(
-                    RuntimeOrigin::signed(creator),
-                    client,
-                    1000u128,
-                    long_metadata
+        /// Verify the creator signature stored for an invoice against its current hash
+        ///
+        /// `false` when the invoice was created without a signature, or was re-hashed since.
+        pub fn verify_invoice_signature(client: &T::AccountId, invoice_id: u64) -> bool {
+            let invoice = Invoices::<T>::get(client, invoice_id);
+            match (invoice, InvoiceSignatures::<T>::get(invoice_id)) {
+                (Some(invoice), Some(signature)) => Self::verify_creator_signature(
+                    &invoice.created_by,
+                    &invoice.invoice_hash,
+                    &signature,
                 ),
-                Error::<Test>::MetadataTooLong
-            );
-        });
-    }
-
-    #[test]
-    fn invoice_hash_lookup_works() {
-        new_test_ext().execute_with(|| {
-            let creator = 1u64;
-            let client = 2u64;
+                _ => false,
+            }
+        }
 
-            // Create invoice
-            assert_ok!(Ledger::create_invoice(
-                RuntimeOrigin::signed(creator),
-                client,
-                1000u128,
-                b"Test Invoice".to_vec()
-            ));
-
-            // Get the invoice to obtain its hash
-            let invoices = Ledger::get_client_invoices(&client);
-            let invoice_hash = invoices[0].invoice_hash;
-
-            // Lookup invoice by hash
-            let found_id = Ledger::get_invoice_by_hash(invoice_hash);
-            assert_eq!(found_id, Some(0));
-        });
-    }
-
-    #[test]
-    fn events_are_emitted() {
-        new_test_ext().execute_with(|| {
-            let creator = 1u64;
-            let client = 2u64;
-            let amount = 1000u128;
-
-            // Create invoice
-            assert_ok!(Ledger::create_invoice(
-                RuntimeOrigin::signed(creator),
-                client,
-                amount,
-                b"Test Invoice".to_vec()
-            ));
-
-            // Get the invoice hash
-            let invoices = Ledger::get_client_invoices(&client);
-            let invoice_hash = invoices[0].invoice_hash;
-
-            // Check InvoiceCreated event
-            System::assert_has_event(
-                Event::InvoiceCreated {
-                    invoice_id: 0,
-                    client,
-                    amount,
-                    invoice_hash,
-                    created_by: creator,
+        /// Classify the failure of a batch item for its skip event
+        pub fn skip_reason(error: DispatchError) -> SkipReason {
+            match error {
+                DispatchError::Module(ModuleError { index, error, .. })
+                    if usize::from(index) == <Self as PalletInfoAccess>::index() =>
+                {
+                    Error::<T>::decode(&mut &error[..])
+                        .map(Into::into)
+                        .unwrap_or(SkipReason::Other(error[0]))
                 }
-                .into(),
-            );
+                DispatchError::BadOrigin => SkipReason::Unauthorized,
+                _ => SkipReason::Other(SkipReason::FOREIGN_ERROR),
+            }
+        }
+    }
 
-            // Check InvoiceHashStored event
-            System::assert_has_event(
-                Event::InvoiceHashStored {
-                    invoice_hash,
-                    invoice_id: 0,
-                }
-                .into(),
-            );
-        });
+    impl<T: Config> From<Error<T>> for SkipReason {
+        fn from(error: Error<T>) -> Self {
+            match error {
+                Error::<T>::MetadataTooLong
+                | Error::<T>::DisputeReasonTooLong
+                | Error::<T>::TooManyLineItems
+                | Error::<T>::AttachmentLabelTooLong => Self::TooLong,
+                Error::<T>::DuplicateInvoiceHash
+                | Error::<T>::DuplicateCreditNoteHash
+                | Error::<T>::OperatorPayloadReplayed
+                | Error::<T>::OperatorKeyExists
+                | Error::<T>::CancellationAlreadyRequested
+                | Error::<T>::CreatorAlreadyAuthorized
+                | Error::<T>::OffchainLinkAlreadyConfirmed
+                | Error::<T>::DuplicateAttachment => Self::Duplicate,
+                Error::<T>::InvoiceNotFound
+                | Error::<T>::OperatorKeyNotFound
+                | Error::<T>::NoPendingCancellation
+                | Error::<T>::InvoiceNotDisputed
+                | Error::<T>::InvoiceNotChallenged
+                | Error::<T>::UnknownInvoiceHash
+                | Error::<T>::GrantNotFound => Self::NotFound,
+                Error::<T>::NotInvoiceCreator
+                | Error::<T>::CreatorMismatch
+                | Error::<T>::InvalidOperatorSignature
+                | Error::<T>::InvalidSignature
+                | Error::<T>::NotAuthorizedCreator
+                | Error::<T>::NotInvoiceParty
+                | Error::<T>::CannotChallengeOwnInvoice
+                | Error::<T>::LinkProofMismatch => Self::Unauthorized,
+                Error::<T>::TooManyInvoices
+                | Error::<T>::TooManyInvoicesDue
+                | Error::<T>::TooManyInvoicesExpiring
+                | Error::<T>::TooManyChallengesTimingOut
+                | Error::<T>::TooManyOperatorKeys
+                | Error::<T>::BatchTooLarge
+                | Error::<T>::PaymentExceedsAmount
+                | Error::<T>::DiscountExceedsAmount
+                | Error::<T>::CreditExceedsOutstanding
+                | Error::<T>::TooManyAttachments
+                | Error::<T>::TooManyAssignments => Self::BoundExceeded,
+                // Field-less pallet errors encode as their variant index
+                other => Self::Other(other.encode()[0]),
+            }
+        }
     }
-}
 
+    /// Applies the ledger's parameters decided by governance
+    impl<T: Config> ParameterHandler for Pallet<T> {
+        fn apply(change: &ParameterChange) -> Result<(), ParameterNoLongerExists> {
+            match change {
+                ParameterChange::LedgerTextNormalization(enabled) => {
+                    NormalizeText::<T>::put(enabled);
+                    Self::deposit_event(Event::TextNormalizationSet { enabled: *enabled });
+                    Ok(())
+                },
+                _ => Err(ParameterNoLongerExists),
+            }
+        }
+    }
+}
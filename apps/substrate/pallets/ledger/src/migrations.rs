@@ -0,0 +1,1016 @@
+//! Storage migrations for the Ledger pallet
+
+/// Version 1: move invoices from a per-client `BoundedVec` into a double map keyed by
+/// (client, invoice ID), track per-client counts and ordering in `ClientInvoiceCount`
+/// and `ClientInvoiceIds`, and index invoices by creator in `InvoicesByCreator`.
+pub mod v1 {
+    use crate::{
+        migrations::v3, BalanceOf, ClientInvoiceCount, ClientInvoiceIds, Config, InvoiceStatus,
+        InvoicesByCreator, Pallet,
+    };
+    use codec::{Decode, Encode};
+    use frame_support::{
+        pallet_prelude::*,
+        storage_alias,
+        traits::{GetStorageVersion, StorageVersion},
+    };
+    use frame_system::pallet_prelude::BlockNumberFor;
+    use sp_runtime::traits::Zero;
+    use sp_std::vec::Vec;
+
+    /// Invoice layout stored before version 1
+    #[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug)]
+    pub struct OldInvoice<T: Config> {
+        pub id: u64,
+        pub client: T::AccountId,
+        pub amount: BalanceOf<T>,
+        pub metadata: BoundedVec<u8, T::MaxMetadataLength>,
+        pub timestamp: BlockNumberFor<T>,
+        pub invoice_hash: [u8; 32],
+        pub created_by: T::AccountId,
+    }
+
+    /// Invoice storage before version 1: client => list of invoices
+    #[storage_alias]
+    pub type Invoices<T: Config> = StorageMap<
+        Pallet<T>,
+        Blake2_128Concat,
+        <T as frame_system::Config>::AccountId,
+        BoundedVec<OldInvoice<T>, <T as Config>::MaxInvoicesPerClient>,
+        ValueQuery,
+    >;
+
+    /// Run the migration if the on-chain storage version is below 1
+    pub fn migrate<T: Config>() -> Weight {
+        let db = T::DbWeight::get();
+        if Pallet::<T>::on_chain_storage_version() >= 1 {
+            return db.reads(1);
+        }
+
+        // Drain first: the old and new maps share the same storage prefix
+        let old: Vec<_> = Invoices::<T>::drain().collect();
+        let mut reads = 1u64;
+        let mut writes = 1u64;
+
+        for (client, invoices) in old {
+            reads = reads.saturating_add(1);
+            let count = invoices.len() as u32;
+
+            // Invoices were appended in ID order; keep that order explicit for the index
+            let mut invoices = invoices.into_inner();
+            invoices.sort_by_key(|i| i.id);
+
+            for (position, old_invoice) in invoices.into_iter().enumerate() {
+                let invoice = v3::OldInvoice::<T> {
+                    id: old_invoice.id,
+                    client: old_invoice.client,
+                    amount: old_invoice.amount,
+                    metadata: old_invoice.metadata,
+                    timestamp: old_invoice.timestamp,
+                    invoice_hash: old_invoice.invoice_hash,
+                    created_by: old_invoice.created_by,
+                    status: InvoiceStatus::Pending,
+                    due_block: None,
+                    paid_amount: Zero::zero(),
+                };
+                ClientInvoiceIds::<T>::insert(&client, position as u32, invoice.id);
+                InvoicesByCreator::<T>::insert(&invoice.created_by, invoice.id, &client);
+                v3::Invoices::<T>::insert(&client, invoice.id, invoice);
+                writes = writes.saturating_add(3);
+            }
+
+            ClientInvoiceCount::<T>::insert(&client, count);
+            writes = writes.saturating_add(2);
+        }
+
+        StorageVersion::new(1).put::<Pallet<T>>();
+
+        db.reads_writes(reads, writes)
+    }
+}
+
+/// Version 2: recompute invoice hashes with the length-prefixed preimage and move their
+/// `InvoiceByHash` entries to the new hashes.
+///
+/// Archived invoice hashes are kept as recorded.
+pub mod v2 {
+    use crate::{
+        migrations::v3::{Invoices, OldInvoice},
+        Config, HashAlgorithm, InvoiceByHash, Pallet,
+    };
+    use frame_support::{
+        pallet_prelude::*,
+        traits::{GetStorageVersion, StorageVersion},
+    };
+    use sp_runtime::traits::Zero;
+
+    /// Run the migration if the on-chain storage version is 1
+    pub fn migrate<T: Config>() -> Weight {
+        let db = T::DbWeight::get();
+        if Pallet::<T>::on_chain_storage_version() != 1 {
+            return db.reads(1);
+        }
+
+        let mut reads = 1u64;
+        let mut writes = 1u64;
+
+        Invoices::<T>::translate::<OldInvoice<T>, _>(|_client, invoice_id, mut invoice| {
+            reads = reads.saturating_add(2);
+            let old_hash = invoice.invoice_hash;
+            // Neither the external reference nor absent line items change the hash
+            let current = invoice
+                .clone()
+                .upgrade(Default::default())
+                .upgrade(Default::default())
+                .upgrade(HashAlgorithm::Sha256)
+                .upgrade(Zero::zero(), Zero::zero())
+                .upgrade(Zero::zero())
+                .upgrade()
+                .upgrade();
+            invoice.invoice_hash = current.calculate_hash();
+            writes = writes.saturating_add(1);
+
+            // Only move the lookup if it still points at this invoice
+            if InvoiceByHash::<T>::get(old_hash) == Some(invoice_id) {
+                InvoiceByHash::<T>::remove(old_hash);
+                writes = writes.saturating_add(1);
+            }
+            InvoiceByHash::<T>::insert(invoice.invoice_hash, invoice_id);
+            writes = writes.saturating_add(1);
+
+            Some(invoice)
+        });
+
+        StorageVersion::new(2).put::<Pallet<T>>();
+
+        db.reads_writes(reads, writes)
+    }
+}
+
+/// Version 3: add an empty `external_ref` to every invoice.
+pub mod v3 {
+    use crate::{migrations::v4, BalanceOf, Config, InvoiceStatus, Pallet};
+    use codec::{Decode, Encode};
+    use frame_support::{
+        pallet_prelude::*,
+        storage_alias,
+        traits::{GetStorageVersion, StorageVersion},
+    };
+    use frame_system::pallet_prelude::BlockNumberFor;
+
+    /// Invoice layout stored before version 3
+    #[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug)]
+    pub struct OldInvoice<T: Config> {
+        pub id: u64,
+        pub client: T::AccountId,
+        pub amount: BalanceOf<T>,
+        pub metadata: BoundedVec<u8, T::MaxMetadataLength>,
+        pub timestamp: BlockNumberFor<T>,
+        pub invoice_hash: [u8; 32],
+        pub created_by: T::AccountId,
+        pub status: InvoiceStatus,
+        pub due_block: Option<BlockNumberFor<T>>,
+        pub paid_amount: BalanceOf<T>,
+    }
+
+    impl<T: Config> OldInvoice<T> {
+        /// Upgrade to the version 3 layout with the given external reference
+        pub fn upgrade(
+            self,
+            external_ref: BoundedVec<u8, T::MaxExternalRefLength>,
+        ) -> v4::OldInvoice<T> {
+            v4::OldInvoice {
+                id: self.id,
+                client: self.client,
+                amount: self.amount,
+                metadata: self.metadata,
+                timestamp: self.timestamp,
+                invoice_hash: self.invoice_hash,
+                created_by: self.created_by,
+                status: self.status,
+                due_block: self.due_block,
+                paid_amount: self.paid_amount,
+                external_ref,
+            }
+        }
+    }
+
+    /// Invoice storage before version 3
+    #[storage_alias]
+    pub type Invoices<T: Config> = StorageDoubleMap<
+        Pallet<T>,
+        Blake2_128Concat,
+        <T as frame_system::Config>::AccountId,
+        Blake2_128Concat,
+        u64,
+        OldInvoice<T>,
+        OptionQuery,
+    >;
+
+    /// Run the migration if the on-chain storage version is 2
+    pub fn migrate<T: Config>() -> Weight {
+        let db = T::DbWeight::get();
+        if Pallet::<T>::on_chain_storage_version() != 2 {
+            return db.reads(1);
+        }
+
+        let mut translated = 0u64;
+
+        v4::Invoices::<T>::translate::<OldInvoice<T>, _>(|_client, _invoice_id, old| {
+            translated = translated.saturating_add(1);
+            Some(old.upgrade(Default::default()))
+        });
+
+        StorageVersion::new(3).put::<Pallet<T>>();
+
+        db.reads_writes(translated.saturating_add(1), translated.saturating_add(1))
+    }
+}
+
+/// Version 4: add empty `line_items` to every invoice.
+///
+/// Invoice hashes only cover line items that are present, so they stay valid.
+pub mod v4 {
+    use crate::{migrations::v6, BalanceOf, Config, InvoiceStatus, Pallet};
+    use codec::{Decode, Encode};
+    use frame_support::{
+        pallet_prelude::*,
+        storage_alias,
+        traits::{GetStorageVersion, StorageVersion},
+    };
+    use frame_system::pallet_prelude::BlockNumberFor;
+
+    /// Invoice layout stored before version 4
+    #[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug)]
+    pub struct OldInvoice<T: Config> {
+        pub id: u64,
+        pub client: T::AccountId,
+        pub amount: BalanceOf<T>,
+        pub metadata: BoundedVec<u8, T::MaxMetadataLength>,
+        pub timestamp: BlockNumberFor<T>,
+        pub invoice_hash: [u8; 32],
+        pub created_by: T::AccountId,
+        pub status: InvoiceStatus,
+        pub due_block: Option<BlockNumberFor<T>>,
+        pub paid_amount: BalanceOf<T>,
+        pub external_ref: BoundedVec<u8, T::MaxExternalRefLength>,
+    }
+
+    impl<T: Config> OldInvoice<T> {
+        /// Upgrade to the version 4 layout with the given line items
+        pub fn upgrade(
+            self,
+            line_items: BoundedVec<crate::LineItem<T>, T::MaxLineItems>,
+        ) -> v6::OldInvoice<T> {
+            v6::OldInvoice {
+                id: self.id,
+                client: self.client,
+                amount: self.amount,
+                metadata: self.metadata,
+                timestamp: self.timestamp,
+                invoice_hash: self.invoice_hash,
+                created_by: self.created_by,
+                status: self.status,
+                due_block: self.due_block,
+                paid_amount: self.paid_amount,
+                external_ref: self.external_ref,
+                line_items,
+            }
+        }
+    }
+
+    /// Invoice storage before version 4
+    #[storage_alias]
+    pub type Invoices<T: Config> = StorageDoubleMap<
+        Pallet<T>,
+        Blake2_128Concat,
+        <T as frame_system::Config>::AccountId,
+        Blake2_128Concat,
+        u64,
+        OldInvoice<T>,
+        OptionQuery,
+    >;
+
+    /// Run the migration if the on-chain storage version is 3
+    pub fn migrate<T: Config>() -> Weight {
+        let db = T::DbWeight::get();
+        if Pallet::<T>::on_chain_storage_version() != 3 {
+            return db.reads(1);
+        }
+
+        let mut translated = 0u64;
+
+        v6::Invoices::<T>::translate::<OldInvoice<T>, _>(|_client, _invoice_id, old| {
+            translated = translated.saturating_add(1);
+            Some(old.upgrade(Default::default()))
+        });
+
+        StorageVersion::new(4).put::<Pallet<T>>();
+
+        db.reads_writes(translated.saturating_add(1), translated.saturating_add(1))
+    }
+}
+
+/// Version 5: compute `OutstandingBalance` for every client from its stored invoices.
+pub mod v5 {
+    use crate::{migrations::v6::Invoices, Config, HashAlgorithm, OutstandingBalance, Pallet};
+    use frame_support::{
+        pallet_prelude::*,
+        traits::{GetStorageVersion, StorageVersion},
+    };
+    use sp_runtime::traits::{Saturating, Zero};
+
+    /// Run the migration if the on-chain storage version is 4
+    pub fn migrate<T: Config>() -> Weight {
+        let db = T::DbWeight::get();
+        if Pallet::<T>::on_chain_storage_version() != 4 {
+            return db.reads(1);
+        }
+
+        let mut reads = 1u64;
+        let mut writes = 1u64;
+
+        for (client, _invoice_id, invoice) in Invoices::<T>::iter() {
+            // The invoice and its credits
+            reads = reads.saturating_add(2);
+            let current = invoice
+                .upgrade(HashAlgorithm::Sha256)
+                .upgrade(Zero::zero(), Zero::zero())
+                .upgrade(Zero::zero())
+                .upgrade()
+                .upgrade();
+            let owed = Pallet::<T>::outstanding(&current);
+            if !owed.is_zero() {
+                OutstandingBalance::<T>::mutate(&client, |balance| {
+                    *balance = balance.saturating_add(owed)
+                });
+                writes = writes.saturating_add(1);
+            }
+        }
+
+        StorageVersion::new(5).put::<Pallet<T>>();
+
+        db.reads_writes(reads, writes)
+    }
+}
+
+/// Version 6: record the algorithm of every invoice hash, SHA256 for all stored invoices.
+///
+/// Hashes are not recomputed; new invoices use `Config::InvoiceHashAlgorithm`.
+pub mod v6 {
+    use crate::{
+        migrations::v8, BalanceOf, Config, HashAlgorithm, InvoiceStatus, LineItem, Pallet,
+    };
+    use codec::{Decode, Encode};
+    use frame_support::{
+        pallet_prelude::*,
+        storage_alias,
+        traits::{GetStorageVersion, StorageVersion},
+    };
+    use frame_system::pallet_prelude::BlockNumberFor;
+
+    /// Invoice layout stored before version 6
+    #[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug)]
+    pub struct OldInvoice<T: Config> {
+        pub id: u64,
+        pub client: T::AccountId,
+        pub amount: BalanceOf<T>,
+        pub metadata: BoundedVec<u8, T::MaxMetadataLength>,
+        pub timestamp: BlockNumberFor<T>,
+        pub invoice_hash: [u8; 32],
+        pub created_by: T::AccountId,
+        pub status: InvoiceStatus,
+        pub due_block: Option<BlockNumberFor<T>>,
+        pub paid_amount: BalanceOf<T>,
+        pub external_ref: BoundedVec<u8, T::MaxExternalRefLength>,
+        pub line_items: BoundedVec<LineItem<T>, T::MaxLineItems>,
+    }
+
+    impl<T: Config> OldInvoice<T> {
+        /// Upgrade to the version 6 layout, recording the algorithm of the stored hash
+        pub fn upgrade(self, hash_algorithm: HashAlgorithm) -> v8::OldInvoice<T> {
+            v8::OldInvoice {
+                id: self.id,
+                client: self.client,
+                amount: self.amount,
+                metadata: self.metadata,
+                timestamp: self.timestamp,
+                invoice_hash: self.invoice_hash,
+                created_by: self.created_by,
+                status: self.status,
+                due_block: self.due_block,
+                paid_amount: self.paid_amount,
+                external_ref: self.external_ref,
+                line_items: self.line_items,
+                hash_algorithm,
+            }
+        }
+    }
+
+    /// Invoice storage before version 6
+    #[storage_alias]
+    pub type Invoices<T: Config> = StorageDoubleMap<
+        Pallet<T>,
+        Blake2_128Concat,
+        <T as frame_system::Config>::AccountId,
+        Blake2_128Concat,
+        u64,
+        OldInvoice<T>,
+        OptionQuery,
+    >;
+
+    /// Run the migration if the on-chain storage version is 5
+    pub fn migrate<T: Config>() -> Weight {
+        let db = T::DbWeight::get();
+        if Pallet::<T>::on_chain_storage_version() != 5 {
+            return db.reads(1);
+        }
+
+        let mut translated = 0u64;
+
+        v8::Invoices::<T>::translate::<OldInvoice<T>, _>(|_client, _invoice_id, old| {
+            translated = translated.saturating_add(1);
+            // Every hash stored so far was SHA256
+            Some(old.upgrade(HashAlgorithm::Sha256))
+        });
+
+        StorageVersion::new(6).put::<Pallet<T>>();
+
+        db.reads_writes(translated.saturating_add(1), translated.saturating_add(1))
+    }
+}
+
+/// Version 7: recompute invoice hashes now that the preimage covers `created_by`, and move
+/// their `InvoiceByHash` entries to the new hashes.
+///
+/// Every invoice keeps its `hash_algorithm`. Archived invoice hashes and creator signatures
+/// are kept as recorded.
+pub mod v7 {
+    use crate::{
+        migrations::v8::{Invoices, OldInvoice},
+        Config, InvoiceByHash, Pallet,
+    };
+    use frame_support::{
+        pallet_prelude::*,
+        traits::{GetStorageVersion, StorageVersion},
+    };
+    use sp_runtime::traits::Zero;
+
+    /// Run the migration if the on-chain storage version is 6
+    pub fn migrate<T: Config>() -> Weight {
+        let db = T::DbWeight::get();
+        if Pallet::<T>::on_chain_storage_version() != 6 {
+            return db.reads(1);
+        }
+
+        let mut reads = 1u64;
+        let mut writes = 1u64;
+
+        Invoices::<T>::translate::<OldInvoice<T>, _>(|_client, invoice_id, mut invoice| {
+            reads = reads.saturating_add(2);
+            let old_hash = invoice.invoice_hash;
+            // Without tax or discount the hash is the same as after version 8
+            invoice.invoice_hash = invoice
+                .clone()
+                .upgrade(Zero::zero(), Zero::zero())
+                .upgrade(Zero::zero())
+                .upgrade()
+                .upgrade()
+                .calculate_hash();
+            writes = writes.saturating_add(1);
+
+            // Only move the lookup if it still points at this invoice
+            if InvoiceByHash::<T>::get(old_hash) == Some(invoice_id) {
+                InvoiceByHash::<T>::remove(old_hash);
+                writes = writes.saturating_add(1);
+            }
+            InvoiceByHash::<T>::insert(invoice.invoice_hash, invoice_id);
+            writes = writes.saturating_add(1);
+
+            Some(invoice)
+        });
+
+        StorageVersion::new(7).put::<Pallet<T>>();
+
+        db.reads_writes(reads, writes)
+    }
+}
+
+/// Version 8: add a zero `tax` and `discount` to every invoice.
+///
+/// Hashes are unchanged, neither field is part of the preimage while both are zero.
+pub mod v8 {
+    use crate::{
+        migrations::v9, BalanceOf, Config, HashAlgorithm, InvoiceStatus, LineItem, Pallet,
+    };
+    use codec::{Decode, Encode};
+    use frame_support::{
+        pallet_prelude::*,
+        storage_alias,
+        traits::{GetStorageVersion, StorageVersion},
+    };
+    use frame_system::pallet_prelude::BlockNumberFor;
+    use sp_runtime::traits::Zero;
+
+    /// Invoice layout stored before version 8
+    #[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug)]
+    pub struct OldInvoice<T: Config> {
+        pub id: u64,
+        pub client: T::AccountId,
+        pub amount: BalanceOf<T>,
+        pub metadata: BoundedVec<u8, T::MaxMetadataLength>,
+        pub timestamp: BlockNumberFor<T>,
+        pub invoice_hash: [u8; 32],
+        pub created_by: T::AccountId,
+        pub status: InvoiceStatus,
+        pub due_block: Option<BlockNumberFor<T>>,
+        pub paid_amount: BalanceOf<T>,
+        pub external_ref: BoundedVec<u8, T::MaxExternalRefLength>,
+        pub line_items: BoundedVec<LineItem<T>, T::MaxLineItems>,
+        pub hash_algorithm: HashAlgorithm,
+    }
+
+    impl<T: Config> OldInvoice<T> {
+        /// Upgrade to the version 8 layout with the given tax and discount
+        pub fn upgrade(self, tax: BalanceOf<T>, discount: BalanceOf<T>) -> v9::OldInvoice<T> {
+            v9::OldInvoice {
+                id: self.id,
+                client: self.client,
+                amount: self.amount,
+                metadata: self.metadata,
+                timestamp: self.timestamp,
+                invoice_hash: self.invoice_hash,
+                created_by: self.created_by,
+                status: self.status,
+                due_block: self.due_block,
+                paid_amount: self.paid_amount,
+                external_ref: self.external_ref,
+                line_items: self.line_items,
+                hash_algorithm: self.hash_algorithm,
+                tax,
+                discount,
+            }
+        }
+    }
+
+    /// Invoice storage before version 8
+    #[storage_alias]
+    pub type Invoices<T: Config> = StorageDoubleMap<
+        Pallet<T>,
+        Blake2_128Concat,
+        <T as frame_system::Config>::AccountId,
+        Blake2_128Concat,
+        u64,
+        OldInvoice<T>,
+        OptionQuery,
+    >;
+
+    /// Run the migration if the on-chain storage version is 7
+    pub fn migrate<T: Config>() -> Weight {
+        let db = T::DbWeight::get();
+        if Pallet::<T>::on_chain_storage_version() != 7 {
+            return db.reads(1);
+        }
+
+        let mut translated = 0u64;
+
+        v9::Invoices::<T>::translate::<OldInvoice<T>, _>(|_client, _invoice_id, old| {
+            translated = translated.saturating_add(1);
+            Some(old.upgrade(Zero::zero(), Zero::zero()))
+        });
+
+        StorageVersion::new(8).put::<Pallet<T>>();
+
+        db.reads_writes(translated.saturating_add(1), translated.saturating_add(1))
+    }
+}
+
+/// Version 9: add a zero `accrued_late_fee` to every invoice.
+///
+/// Fees of invoices already past their due block are charged from the due block when they
+/// are next paid.
+pub mod v9 {
+    use crate::{
+        migrations::v10, BalanceOf, Config, HashAlgorithm, InvoiceStatus, LineItem, Pallet,
+    };
+    use codec::{Decode, Encode};
+    use frame_support::{
+        pallet_prelude::*,
+        storage_alias,
+        traits::{GetStorageVersion, StorageVersion},
+    };
+    use frame_system::pallet_prelude::BlockNumberFor;
+    use sp_runtime::traits::Zero;
+
+    /// Invoice layout stored before version 9
+    #[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug)]
+    pub struct OldInvoice<T: Config> {
+        pub id: u64,
+        pub client: T::AccountId,
+        pub amount: BalanceOf<T>,
+        pub metadata: BoundedVec<u8, T::MaxMetadataLength>,
+        pub timestamp: BlockNumberFor<T>,
+        pub invoice_hash: [u8; 32],
+        pub created_by: T::AccountId,
+        pub status: InvoiceStatus,
+        pub due_block: Option<BlockNumberFor<T>>,
+        pub paid_amount: BalanceOf<T>,
+        pub external_ref: BoundedVec<u8, T::MaxExternalRefLength>,
+        pub line_items: BoundedVec<LineItem<T>, T::MaxLineItems>,
+        pub hash_algorithm: HashAlgorithm,
+        pub tax: BalanceOf<T>,
+        pub discount: BalanceOf<T>,
+    }
+
+    impl<T: Config> OldInvoice<T> {
+        /// Upgrade to the version 9 layout with the given accrued late fee
+        pub fn upgrade(self, accrued_late_fee: BalanceOf<T>) -> v10::OldInvoice<T> {
+            v10::OldInvoice {
+                id: self.id,
+                client: self.client,
+                amount: self.amount,
+                metadata: self.metadata,
+                timestamp: self.timestamp,
+                invoice_hash: self.invoice_hash,
+                created_by: self.created_by,
+                status: self.status,
+                due_block: self.due_block,
+                paid_amount: self.paid_amount,
+                external_ref: self.external_ref,
+                line_items: self.line_items,
+                hash_algorithm: self.hash_algorithm,
+                tax: self.tax,
+                discount: self.discount,
+                accrued_late_fee,
+            }
+        }
+    }
+
+    /// Invoice storage before version 9
+    #[storage_alias]
+    pub type Invoices<T: Config> = StorageDoubleMap<
+        Pallet<T>,
+        Blake2_128Concat,
+        <T as frame_system::Config>::AccountId,
+        Blake2_128Concat,
+        u64,
+        OldInvoice<T>,
+        OptionQuery,
+    >;
+
+    /// Run the migration if the on-chain storage version is 8
+    pub fn migrate<T: Config>() -> Weight {
+        let db = T::DbWeight::get();
+        if Pallet::<T>::on_chain_storage_version() != 8 {
+            return db.reads(1);
+        }
+
+        let mut translated = 0u64;
+
+        v10::Invoices::<T>::translate::<OldInvoice<T>, _>(|_client, _invoice_id, old| {
+            translated = translated.saturating_add(1);
+            Some(old.upgrade(Zero::zero()))
+        });
+
+        StorageVersion::new(9).put::<Pallet<T>>();
+
+        db.reads_writes(translated.saturating_add(1), translated.saturating_add(1))
+    }
+}
+
+/// Version 10: record the creator's `last_activity` on every invoice, its creation block.
+///
+/// Claims on invoices abandoned since before the upgrade are possible `AbandonThreshold`
+/// blocks after their creation.
+pub mod v10 {
+    use crate::{migrations::v11, BalanceOf, Config, HashAlgorithm, InvoiceStatus, LineItem, Pallet};
+    use codec::{Decode, Encode};
+    use frame_support::{
+        pallet_prelude::*,
+        storage_alias,
+        traits::{GetStorageVersion, StorageVersion},
+    };
+    use frame_system::pallet_prelude::BlockNumberFor;
+
+    /// Invoice layout stored before version 10
+    #[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug)]
+    pub struct OldInvoice<T: Config> {
+        pub id: u64,
+        pub client: T::AccountId,
+        pub amount: BalanceOf<T>,
+        pub metadata: BoundedVec<u8, T::MaxMetadataLength>,
+        pub timestamp: BlockNumberFor<T>,
+        pub invoice_hash: [u8; 32],
+        pub created_by: T::AccountId,
+        pub status: InvoiceStatus,
+        pub due_block: Option<BlockNumberFor<T>>,
+        pub paid_amount: BalanceOf<T>,
+        pub external_ref: BoundedVec<u8, T::MaxExternalRefLength>,
+        pub line_items: BoundedVec<LineItem<T>, T::MaxLineItems>,
+        pub hash_algorithm: HashAlgorithm,
+        pub tax: BalanceOf<T>,
+        pub discount: BalanceOf<T>,
+        pub accrued_late_fee: BalanceOf<T>,
+    }
+
+    impl<T: Config> OldInvoice<T> {
+        /// Upgrade to the version 10 layout, taking the creation block as last activity
+        pub fn upgrade(self) -> v11::OldInvoice<T> {
+            v11::OldInvoice {
+                id: self.id,
+                client: self.client,
+                amount: self.amount,
+                metadata: self.metadata,
+                timestamp: self.timestamp,
+                invoice_hash: self.invoice_hash,
+                created_by: self.created_by,
+                status: self.status,
+                due_block: self.due_block,
+                paid_amount: self.paid_amount,
+                external_ref: self.external_ref,
+                line_items: self.line_items,
+                hash_algorithm: self.hash_algorithm,
+                tax: self.tax,
+                discount: self.discount,
+                accrued_late_fee: self.accrued_late_fee,
+                last_activity: self.timestamp,
+            }
+        }
+    }
+
+    /// Invoice storage before version 10
+    #[storage_alias]
+    pub type Invoices<T: Config> = StorageDoubleMap<
+        Pallet<T>,
+        Blake2_128Concat,
+        <T as frame_system::Config>::AccountId,
+        Blake2_128Concat,
+        u64,
+        OldInvoice<T>,
+        OptionQuery,
+    >;
+
+    /// Run the migration if the on-chain storage version is 9
+    pub fn migrate<T: Config>() -> Weight {
+        let db = T::DbWeight::get();
+        if Pallet::<T>::on_chain_storage_version() != 9 {
+            return db.reads(1);
+        }
+
+        let mut translated = 0u64;
+
+        v11::Invoices::<T>::translate::<OldInvoice<T>, _>(|_client, _invoice_id, old| {
+            translated = translated.saturating_add(1);
+            Some(old.upgrade())
+        });
+
+        StorageVersion::new(10).put::<Pallet<T>>();
+
+        db.reads_writes(translated.saturating_add(1), translated.saturating_add(1))
+    }
+}
+
+/// Version 11: make every invoice's creator its `beneficiary`.
+///
+/// No invoice was assigned before the upgrade, so each keeps paying its creator.
+pub mod v11 {
+    use crate::{BalanceOf, Config, HashAlgorithm, Invoice, InvoiceStatus, LineItem, Pallet};
+    use codec::{Decode, Encode};
+    use frame_support::{
+        pallet_prelude::*,
+        storage_alias,
+        traits::{GetStorageVersion, StorageVersion},
+    };
+    use frame_system::pallet_prelude::BlockNumberFor;
+
+    /// Invoice layout stored before version 11
+    #[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug)]
+    pub struct OldInvoice<T: Config> {
+        pub id: u64,
+        pub client: T::AccountId,
+        pub amount: BalanceOf<T>,
+        pub metadata: BoundedVec<u8, T::MaxMetadataLength>,
+        pub timestamp: BlockNumberFor<T>,
+        pub invoice_hash: [u8; 32],
+        pub created_by: T::AccountId,
+        pub status: InvoiceStatus,
+        pub due_block: Option<BlockNumberFor<T>>,
+        pub paid_amount: BalanceOf<T>,
+        pub external_ref: BoundedVec<u8, T::MaxExternalRefLength>,
+        pub line_items: BoundedVec<LineItem<T>, T::MaxLineItems>,
+        pub hash_algorithm: HashAlgorithm,
+        pub tax: BalanceOf<T>,
+        pub discount: BalanceOf<T>,
+        pub accrued_late_fee: BalanceOf<T>,
+        pub last_activity: BlockNumberFor<T>,
+    }
+
+    impl<T: Config> OldInvoice<T> {
+        /// Upgrade to the current layout, with the creator as beneficiary
+        pub fn upgrade(self) -> Invoice<T> {
+            Invoice {
+                id: self.id,
+                client: self.client,
+                amount: self.amount,
+                metadata: self.metadata,
+                timestamp: self.timestamp,
+                invoice_hash: self.invoice_hash,
+                beneficiary: self.created_by.clone(),
+                created_by: self.created_by,
+                status: self.status,
+                due_block: self.due_block,
+                paid_amount: self.paid_amount,
+                external_ref: self.external_ref,
+                line_items: self.line_items,
+                hash_algorithm: self.hash_algorithm,
+                tax: self.tax,
+                discount: self.discount,
+                accrued_late_fee: self.accrued_late_fee,
+                last_activity: self.last_activity,
+            }
+        }
+    }
+
+    /// Invoice storage before version 11
+    #[storage_alias]
+    pub type Invoices<T: Config> = StorageDoubleMap<
+        Pallet<T>,
+        Blake2_128Concat,
+        <T as frame_system::Config>::AccountId,
+        Blake2_128Concat,
+        u64,
+        OldInvoice<T>,
+        OptionQuery,
+    >;
+
+    /// Run the migration if the on-chain storage version is 10
+    pub fn migrate<T: Config>() -> Weight {
+        let db = T::DbWeight::get();
+        if Pallet::<T>::on_chain_storage_version() != 10 {
+            return db.reads(1);
+        }
+
+        let mut translated = 0u64;
+
+        crate::Invoices::<T>::translate::<OldInvoice<T>, _>(|_client, _invoice_id, old| {
+            translated = translated.saturating_add(1);
+            Some(old.upgrade())
+        });
+
+        StorageVersion::new(11).put::<Pallet<T>>();
+
+        db.reads_writes(translated.saturating_add(1), translated.saturating_add(1))
+    }
+}
+
+/// Version 12: compute `CreatorStats` for every creator from its stored invoices.
+///
+/// Payments of invoices archived or purged before the upgrade are not counted.
+pub mod v12 {
+    use crate::{Config, CreatorStats, Invoices, Pallet};
+    use frame_support::{
+        pallet_prelude::*,
+        traits::{GetStorageVersion, StorageVersion},
+    };
+    use sp_runtime::traits::Saturating;
+
+    /// Run the migration if the on-chain storage version is 11
+    pub fn migrate<T: Config>() -> Weight {
+        let db = T::DbWeight::get();
+        if Pallet::<T>::on_chain_storage_version() != 11 {
+            return db.reads(1);
+        }
+
+        let mut reads = 1u64;
+        let mut writes = 1u64;
+
+        for (_client, _invoice_id, invoice) in Invoices::<T>::iter() {
+            // The invoice, its credits and the creator's totals
+            reads = reads.saturating_add(3);
+            writes = writes.saturating_add(1);
+            let owed = Pallet::<T>::outstanding(&invoice);
+            CreatorStats::<T>::mutate(&invoice.created_by, |(invoiced, collected)| {
+                *invoiced = invoiced.saturating_add(invoice.paid_amount).saturating_add(owed);
+                *collected = collected.saturating_add(invoice.paid_amount);
+            });
+        }
+
+        StorageVersion::new(12).put::<Pallet<T>>();
+
+        db.reads_writes(reads, writes)
+    }
+}
+
+/// Version 13: count the `AccessGrants` under every invoice hash in `AccessGrantCount`.
+///
+/// Hashes granted more than `MaxGrantsPerInvoice` times before the upgrade keep their grants
+/// and accept new ones only once revocations bring them under the limit.
+pub mod v13 {
+    use crate::{AccessGrantCount, AccessGrants, Config, Pallet};
+    use frame_support::{
+        pallet_prelude::*,
+        traits::{GetStorageVersion, StorageVersion},
+    };
+
+    /// Run the migration if the on-chain storage version is 12
+    pub fn migrate<T: Config>() -> Weight {
+        let db = T::DbWeight::get();
+        if Pallet::<T>::on_chain_storage_version() != 12 {
+            return db.reads(1);
+        }
+
+        let mut grants = 0u64;
+
+        for (invoice_hash, _grantee, _grant) in AccessGrants::<T>::iter() {
+            grants = grants.saturating_add(1);
+            AccessGrantCount::<T>::mutate(invoice_hash, |count| *count = count.saturating_add(1));
+        }
+
+        StorageVersion::new(13).put::<Pallet<T>>();
+
+        db.reads_writes(grants.saturating_mul(2).saturating_add(1), grants.saturating_add(1))
+    }
+}
+
+/// Invariants checked around `on_runtime_upgrade` by try-runtime.
+///
+/// Whatever layout the invoices had before the upgrade, afterwards the storage version is
+/// current, every invoice decodes under its own client and ID, the invoice counter and the
+/// number of hash mappings are unchanged, and the per-client indexes agree with the stored
+/// invoices.
+#[cfg(any(feature = "try-runtime", test))]
+pub mod checks {
+    use crate::{
+        BalanceOf, ClientInvoiceCount, Config, InvoiceByHash, InvoiceCount, Invoices,
+        OutstandingBalance, Pallet,
+    };
+    use codec::{Decode, Encode};
+    use frame_support::{ensure, traits::GetStorageVersion};
+    use sp_runtime::traits::{Saturating, Zero};
+    use sp_std::{collections::btree_map::BTreeMap, vec::Vec};
+
+    /// State recorded by `pre_upgrade` for `post_upgrade`
+    #[derive(Encode, Decode)]
+    struct PreUpgrade {
+        invoice_count: u64,
+        hash_mappings: u64,
+    }
+
+    /// Record what the upgrade must preserve
+    pub fn pre_upgrade<T: Config>() -> Vec<u8> {
+        PreUpgrade {
+            invoice_count: InvoiceCount::<T>::get(),
+            hash_mappings: InvoiceByHash::<T>::iter_keys().count() as u64,
+        }
+        .encode()
+    }
+
+    /// Check the migrated storage against the recorded state
+    pub fn post_upgrade<T: Config>(state: Vec<u8>) -> Result<(), &'static str> {
+        let pre = PreUpgrade::decode(&mut &state[..]).map_err(|_| "invalid pre-upgrade state")?;
+
+        ensure!(
+            Pallet::<T>::on_chain_storage_version() == Pallet::<T>::current_storage_version(),
+            "storage version not updated"
+        );
+        ensure!(InvoiceCount::<T>::get() == pre.invoice_count, "invoice counter changed");
+        // Re-hashing moves mappings; it never drops them
+        ensure!(
+            InvoiceByHash::<T>::iter_keys().count() as u64 >= pre.hash_mappings,
+            "invoice hash mappings lost"
+        );
+
+        let mut invoices = 0usize;
+        let mut per_client = BTreeMap::<T::AccountId, (u32, BalanceOf<T>)>::new();
+        for (client, invoice_id, invoice) in Invoices::<T>::iter() {
+            invoices = invoices.saturating_add(1);
+            ensure!(
+                invoice.client == client && invoice.id == invoice_id,
+                "invoice stored under another key"
+            );
+            ensure!(
+                InvoiceByHash::<T>::get(invoice.invoice_hash) == Some(invoice_id),
+                "invoice hash not mapped to its invoice"
+            );
+            let (count, owed) = per_client.entry(client).or_insert((0, Zero::zero()));
+            *count = count.saturating_add(1);
+            *owed = (*owed).saturating_add(Pallet::<T>::outstanding(&invoice));
+        }
+        // `iter` skips values that fail to decode
+        ensure!(Invoices::<T>::iter_keys().count() == invoices, "invoices fail to decode");
+
+        for (client, count) in ClientInvoiceCount::<T>::iter() {
+            let stored = per_client.get(&client).map_or(0, |(count, _)| *count);
+            ensure!(stored == count, "client invoice count out of sync");
+        }
+        for (client, (count, owed)) in per_client {
+            ensure!(ClientInvoiceCount::<T>::get(&client) == count, "client invoice count missing");
+            ensure!(
+                OutstandingBalance::<T>::get(&client) == owed,
+                "outstanding balance out of sync"
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,220 @@
+use crate as pallet_ledger;
+use frame_support::{
+    parameter_types,
+    traits::{ConstU128, ConstU32, ConstU64, Currency, OnUnbalanced},
+    weights::{constants::RocksDbWeight, Weight},
+};
+use sp_core::{ed25519, sr25519, Pair, H256};
+use sp_runtime::{
+    traits::{BlakeTwo256, IdentityLookup},
+    BuildStorage,
+};
+
+type Block = frame_system::mocking::MockBlock<Test>;
+
+// Configure a mock runtime to test the pallet
+frame_support::construct_runtime!(
+    pub enum Test {
+        System: frame_system,
+        Balances: pallet_balances,
+        Ledger: pallet_ledger,
+    }
+);
+
+parameter_types! {
+    pub const BlockHashCount: u64 = 250;
+    pub const SS58Prefix: u8 = 42;
+}
+
+impl frame_system::Config for Test {
+    type BaseCallFilter = frame_support::traits::Everything;
+    type BlockWeights = ();
+    type BlockLength = ();
+    type DbWeight = RocksDbWeight;
+    type RuntimeOrigin = RuntimeOrigin;
+    type RuntimeCall = RuntimeCall;
+    type Nonce = u64;
+    type Hash = H256;
+    type Hashing = BlakeTwo256;
+    type AccountId = u64;
+    type Lookup = IdentityLookup<Self::AccountId>;
+    type Block = Block;
+    type RuntimeEvent = RuntimeEvent;
+    type BlockHashCount = BlockHashCount;
+    type Version = ();
+    type PalletInfo = PalletInfo;
+    type AccountData = pallet_balances::AccountData<u128>;
+    type OnNewAccount = ();
+    type OnKilledAccount = ();
+    type SystemWeightInfo = ();
+    type SS58Prefix = SS58Prefix;
+    type OnSetCode = ();
+    type MaxConsumers = ConstU32<16>;
+}
+
+impl pallet_balances::Config for Test {
+    type MaxLocks = ConstU32<50>;
+    type MaxReserves = ();
+    type ReserveIdentifier = [u8; 8];
+    type Balance = u128;
+    type RuntimeEvent = RuntimeEvent;
+    type DustRemoval = ();
+    type ExistentialDeposit = ConstU128<1>;
+    type AccountStore = System;
+    type WeightInfo = ();
+    type FreezeIdentifier = ();
+    type MaxFreezes = ();
+    type RuntimeHoldReason = ();
+    type RuntimeFreezeReason = ();
+    type MaxHolds = ();
+}
+
+parameter_types! {
+    pub const MaxMetadataLength: u32 = 1024;
+    pub static MaxInvoicesPerClient: u32 = 1000;
+    pub static MaxArchivedPerCall: u32 = 100;
+    pub const MaxDueInvoicesPerBlock: u32 = 16;
+    pub const MaxExpiringInvoicesPerBlock: u32 = 2;
+    pub static OverdueCheckWeight: Weight = Weight::from_parts(1_000_000_000, 0);
+    // Static so tests can switch to restricted creation
+    pub static OpenCreation: bool = true;
+    // Static so tests can flip the algorithm as a runtime upgrade would
+    pub static InvoiceHashAlgorithm: pallet_ledger::HashAlgorithm = Default::default();
+    // Static so tests can require a creation deposit
+    pub static InvoiceDeposit: u128 = 0;
+    // Static so tests can enable the webhook
+    pub static InvoiceWebhook: bool = false;
+    // Static so tests can require client acceptance
+    pub static RequireAcceptance: bool = false;
+    // Static so tests can enable late fees
+    pub static LateFeePeriod: u64 = 0;
+    pub static LateFeePerPeriod: pallet_ledger::LateFee<u128> = pallet_ledger::LateFee::Flat(0);
+    // Static so tests can enable abandoned invoice claims
+    pub static AbandonThreshold: u64 = 0;
+}
+
+impl pallet_ledger::Config for Test {
+    type RuntimeEvent = RuntimeEvent;
+    type Currency = Balances;
+    type MaxMetadataLength = MaxMetadataLength;
+    type MaxInvoicesPerClient = MaxInvoicesPerClient;
+    type MaxDueInvoicesPerBlock = MaxDueInvoicesPerBlock;
+    type MaxExpiringInvoicesPerBlock = MaxExpiringInvoicesPerBlock;
+    type OverdueCheckWeight = OverdueCheckWeight;
+    type AdminOrigin = frame_system::EnsureRoot<u64>;
+    type MaxOperatorKeys = ConstU32<4>;
+    type MaxExternalRefLength = ConstU32<32>;
+    type CancellationTimeout = ConstU64<10>;
+    type CancellationCooldown = ConstU64<20>;
+    type DisputeResolutionOrigin = frame_system::EnsureRoot<u64>;
+    type MaxDisputeReasonLength = ConstU32<64>;
+    type DisputeHandler = RecordDisputes;
+    type OnTransition = ();
+    type MaxBatchSize = ConstU32<4>;
+    type OpenCreation = OpenCreation;
+    type RequireAcceptance = RequireAcceptance;
+    type MaxLineItems = ConstU32<4>;
+    type MaxLineItemDescriptionLength = ConstU32<32>;
+    type MaxSignatureLength = ConstU32<64>;
+    type CreatorKeys = DevCreatorKeys;
+    type InvoiceHashAlgorithm = InvoiceHashAlgorithm;
+    type DidProvider = ();
+    type InvoiceDeposit = InvoiceDeposit;
+    type Slashed = ToTreasury;
+    type ChallengeBond = ConstU128<30>;
+    type ChallengeTimeout = ConstU64<20>;
+    type MaxChallengesPerBlock = ConstU32<2>;
+    type ChallengeJudgeOrigin = frame_system::EnsureRoot<u64>;
+    type InvoiceWebhook = InvoiceWebhook;
+    type MaxWebhookInvoicesPerBlock = ConstU32<4>;
+    type UnsignedPriority = ConstU64<100>;
+    type MaxAttachmentsPerInvoice = ConstU32<2>;
+    type MaxAttachmentLabelLength = ConstU32<32>;
+    type LateFeePeriod = LateFeePeriod;
+    type LateFeePerPeriod = LateFeePerPeriod;
+    type AbandonThreshold = AbandonThreshold;
+    type MaxAssignmentsPerInvoice = ConstU32<2>;
+    type MaxGrantsPerInvoice = ConstU32<3>;
+    type MaxArchivedPerCall = MaxArchivedPerCall;
+    type WeightInfo = ();
+}
+
+/// Account receiving slashed deposits
+pub const TREASURY: u64 = 99;
+
+/// Credits slashed deposits to `TREASURY`, as a treasury pallet would
+pub struct ToTreasury;
+
+impl OnUnbalanced<pallet_balances::NegativeImbalance<Test>> for ToTreasury {
+    fn on_nonzero_unbalanced(amount: pallet_balances::NegativeImbalance<Test>) {
+        Balances::resolve_creating(&TREASURY, amount);
+    }
+}
+
+/// Account 1 signs as sr25519 `//Alice` and account 3 as ed25519 `//Bob`; others have no key
+pub struct DevCreatorKeys;
+
+impl pallet_ledger::CreatorPublicKey<u64> for DevCreatorKeys {
+    fn public_key(who: &u64) -> Option<[u8; 32]> {
+        match who {
+            1 => Some(sr25519::Pair::from_string("//Alice", None).unwrap().public().0),
+            3 => Some(ed25519::Pair::from_string("//Bob", None).unwrap().public().0),
+            _ => None,
+        }
+    }
+}
+
+parameter_types! {
+    /// Dispute notifications received by `RecordDisputes`: (invoice_id, opened)
+    pub static DisputeNotifications: Vec<(u64, bool)> = Vec::new();
+}
+
+/// Dispute handler remembering every notification
+pub struct RecordDisputes;
+
+impl pallet_ledger::OnInvoiceDispute<u64, u128> for RecordDisputes {
+    fn dispute_opened(_client: &u64, _creator: &u64, invoice_id: u64, _outstanding: u128) {
+        let mut notifications = DisputeNotifications::get();
+        notifications.push((invoice_id, true));
+        DisputeNotifications::set(notifications);
+    }
+
+    fn dispute_closed(
+        _client: &u64,
+        _creator: &u64,
+        invoice_id: u64,
+        _resolution: &pallet_ledger::DisputeResolution,
+    ) {
+        let mut notifications = DisputeNotifications::get();
+        notifications.push((invoice_id, false));
+        DisputeNotifications::set(notifications);
+    }
+}
+
+/// Initial balance of every endowed test account
+pub const INITIAL_BALANCE: u128 = 10_000;
+
+// Build genesis storage
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    new_test_ext_with(Default::default())
+}
+
+/// Build genesis storage with a custom ledger genesis config
+pub fn new_test_ext_with(ledger: pallet_ledger::GenesisConfig<Test>) -> sp_io::TestExternalities {
+    let mut t = frame_system::GenesisConfig::<Test>::default()
+        .build_storage()
+        .unwrap();
+
+    pallet_balances::GenesisConfig::<Test> {
+        balances: (1..=5).map(|who| (who, INITIAL_BALANCE)).collect(),
+    }
+    .assimilate_storage(&mut t)
+    .unwrap();
+
+    ledger.assimilate_storage(&mut t).unwrap();
+
+    let mut ext: sp_io::TestExternalities = t.into();
+    // Events are not recorded at genesis, start at block 1
+    ext.execute_with(|| System::set_block_number(1));
+    ext
+}
@@ -0,0 +1,5832 @@
+use crate::{mock::*, Error, Event, InvoiceStatus, LateFee, LineItem, INVOICE_STATUS};
+use frame_support::{
+    assert_noop, assert_ok,
+    dispatch::DispatchResultWithPostInfo,
+    traits::Hooks,
+    weights::{constants::RocksDbWeight, Weight},
+};
+use sp_runtime::TokenError;
+use tidygen_primitives::{
+    batch::{BatchOutcome, SkipReason},
+    features::FeatureFlags,
+    pagination::{InvalidCursor, PageCursor},
+};
+
+#[test]
+fn create_invoice_works() {
+    new_test_ext().execute_with(|| {
+        // Setup
+        let creator = 1u64;
+        let client = 2u64;
+        let amount = 1000u128;
+        let metadata = b"INV-2025-001|Test Client|Net 30".to_vec();
+
+        // Create invoice
+        assert_ok!(Ledger::create_invoice(
+            RuntimeOrigin::signed(creator),
+            client,
+            amount,
+            0,
+            0,
+            metadata.clone(),
+            None,
+            None,
+            vec![],
+            None
+        ));
+
+        // Verify invoice count incremented
+        assert_eq!(Ledger::invoice_count(), 1);
+
+        // Verify invoice is stored for client
+        let client_invoices = Ledger::get_client_invoices(&client);
+        assert_eq!(client_invoices.len(), 1);
+
+        // Verify invoice data
+        let invoice = &client_invoices[0];
+        assert_eq!(invoice.id, 0);
+        assert_eq!(invoice.client, client);
+        assert_eq!(invoice.amount, amount);
+        assert_eq!(invoice.metadata.to_vec(), metadata);
+        assert_eq!(invoice.created_by, creator);
+
+        // Verify hash was calculated
+        assert_ne!(invoice.invoice_hash, [0u8; 32]);
+
+        // Verify hash mapping
+        let stored_id = Ledger::get_invoice_by_hash(invoice.invoice_hash);
+        assert_eq!(stored_id, Some(0));
+    });
+}
+
+#[test]
+fn create_multiple_invoices_works() {
+    new_test_ext().execute_with(|| {
+        let creator = 1u64;
+        let client = 2u64;
+
+        // Create first invoice
+        assert_ok!(Ledger::create_invoice(
+            RuntimeOrigin::signed(creator),
+            client,
+            1000u128,
+            0,
+            0,
+            b"Invoice 1".to_vec(),
+            None,
+            None,
+            vec![],
+            None
+        ));
+
+        // Create second invoice
+        assert_ok!(Ledger::create_invoice(
+            RuntimeOrigin::signed(creator),
+            client,
+            2000u128,
+            0,
+            0,
+            b"Invoice 2".to_vec(),
+            None,
+            None,
+            vec![],
+            None
+        ));
+
+        // Create third invoice
+        assert_ok!(Ledger::create_invoice(
+            RuntimeOrigin::signed(creator),
+            client,
+            3000u128,
+            0,
+            0,
+            b"Invoice 3".to_vec(),
+            None,
+            None,
+            vec![],
+            None
+        ));
+
+        // Verify count
+        assert_eq!(Ledger::invoice_count(), 3);
+
+        // Verify all invoices are stored
+        let client_invoices = Ledger::get_client_invoices(&client);
+        assert_eq!(client_invoices.len(), 3);
+
+        // Verify invoice IDs are sequential
+        assert_eq!(client_invoices[0].id, 0);
+        assert_eq!(client_invoices[1].id, 1);
+        assert_eq!(client_invoices[2].id, 2);
+
+        // Verify amounts
+        assert_eq!(client_invoices[0].amount, 1000u128);
+        assert_eq!(client_invoices[1].amount, 2000u128);
+        assert_eq!(client_invoices[2].amount, 3000u128);
+    });
+}
+
+#[test]
+fn multiple_clients_work() {
+    new_test_ext().execute_with(|| {
+        let creator = 1u64;
+        let client1 = 2u64;
+        let client2 = 3u64;
+
+        // Create invoices for client 1
+        assert_ok!(Ledger::create_invoice(
+            RuntimeOrigin::signed(creator),
+            client1,
+            1000u128,
+            0,
+            0,
+            b"Client 1 - Invoice 1".to_vec(),
+            None,
+            None,
+            vec![],
+            None
+        ));
+
+        assert_ok!(Ledger::create_invoice(
+            RuntimeOrigin::signed(creator),
+            client1,
+            1500u128,
+            0,
+            0,
+            b"Client 1 - Invoice 2".to_vec(),
+            None,
+            None,
+            vec![],
+            None
+        ));
+
+        // Create invoices for client 2
+        assert_ok!(Ledger::create_invoice(
+            RuntimeOrigin::signed(creator),
+            client2,
+            2000u128,
+            0,
+            0,
+            b"Client 2 - Invoice 1".to_vec(),
+            None,
+            None,
+            vec![],
+            None
+        ));
+
+        // Verify client 1 invoices
+        let client1_invoices = Ledger::get_client_invoices(&client1);
+        assert_eq!(client1_invoices.len(), 2);
+
+        // Verify client 2 invoices
+        let client2_invoices = Ledger::get_client_invoices(&client2);
+        assert_eq!(client2_invoices.len(), 1);
+
+        // Verify total count
+        assert_eq!(Ledger::invoice_count(), 3);
+    });
+}
+
+#[test]
+fn invoice_hash_is_unique() {
+    new_test_ext().execute_with(|| {
+        let creator = 1u64;
+        let client = 2u64;
+
+        // Create first invoice
+        assert_ok!(Ledger::create_invoice(
+            RuntimeOrigin::signed(creator),
+            client,
+            1000u128,
+            0,
+            0,
+            b"Invoice 1".to_vec(),
+            None,
+            None,
+            vec![],
+            None
+        ));
+
+        // Create second invoice with different data
+        assert_ok!(Ledger::create_invoice(
+            RuntimeOrigin::signed(creator),
+            client,
+            1000u128, // Same amount
+            0,
+            0,
+            b"Invoice 1".to_vec(), // Same metadata
+            None,
+            None,
+            vec![],
+            None
+        ));
+
+        // Get invoices
+        let invoices = Ledger::get_client_invoices(&client);
+
+        // Hashes should be different because IDs and timestamps are different
+        assert_ne!(invoices[0].invoice_hash, invoices[1].invoice_hash);
+    });
+}
+
+#[test]
+fn verify_invoice_hash_works() {
+    new_test_ext().execute_with(|| {
+        let creator = 1u64;
+        let client = 2u64;
+
+        // Create invoice
+        assert_ok!(Ledger::create_invoice(
+            RuntimeOrigin::signed(creator),
+            client,
+            1000u128,
+            0,
+            0,
+            b"Test Invoice".to_vec(),
+            None,
+            None,
+            vec![],
+            None
+        ));
+
+        // Verify hash
+        assert!(Ledger::verify_invoice_hash(&client, 0));
+
+        // Verify non-existent invoice returns false
+        assert!(!Ledger::verify_invoice_hash(&client, 999));
+    });
+}
+
+#[test]
+fn metadata_too_long_fails() {
+    new_test_ext().execute_with(|| {
+        let creator = 1u64;
+        let client = 2u64;
+        
+        // Create metadata that exceeds MaxMetadataLength (1024)
+        let long_metadata = vec![0u8; 1025];
+
+        // Should fail with MetadataTooLong error
+        assert_noop!(
+            Ledger::create_invoice(
+                RuntimeOrigin::signed(creator),
+                client,
+                1000u128,
+                0,
+                0,
+                long_metadata,
+                None,
+                None,
+                vec![],
+                None
+            ),
+            Error::<Test>::MetadataTooLong
+        );
+    });
+}
+
+#[test]
+fn create_invoice_weight_follows_stored_metadata_length() {
+    use crate::WeightInfo;
+    use frame_support::dispatch::GetDispatchInfo;
+
+    // The estimate grows with the metadata, up to `MaxMetadataLength` (1024)
+    let estimate = <() as WeightInfo>::create_invoice;
+    assert!(estimate(0, 0).all_lt(estimate(512, 0)));
+    assert!(estimate(512, 0).all_lt(estimate(1024, 0)));
+
+    new_test_ext().execute_with(|| {
+        assert_ok!(Ledger::set_text_normalization(RuntimeOrigin::root(), true));
+        let metadata = b"INV-1\r\nNet 30\r\n".to_vec();
+        let info = crate::Call::<Test>::create_invoice {
+            client: 2,
+            amount: 100,
+            tax: 0,
+            discount: 0,
+            metadata: metadata.clone(),
+            due_block: None,
+            expires_at: None,
+            line_items: vec![],
+            signature: None,
+        }
+        .get_dispatch_info();
+        assert_eq!(info.weight, estimate(15, 0));
+
+        let post_info = Ledger::create_invoice(
+            RuntimeOrigin::signed(1),
+            2,
+            100u128,
+            0,
+            0,
+            metadata,
+            None,
+            None,
+            vec![],
+            None,
+        )
+        .unwrap();
+
+        // Only the 13 bytes left after normalizing the line endings are charged
+        assert_eq!(post_info.actual_weight, Some(estimate(13, 0)));
+        assert!(post_info.calc_actual_weight(&info).all_lt(info.weight));
+    });
+}
+
+#[test]
+fn creation_weights_cover_line_items_and_signatures() {
+    use crate::WeightInfo;
+    use frame_support::{dispatch::GetDispatchInfo, traits::Get};
+    use sp_core::Pair;
+
+    // The estimate grows with the line items, up to `MaxLineItems` (4)
+    let estimate = <() as WeightInfo>::create_invoice;
+    assert!(estimate(0, 0).all_lt(estimate(0, 4)));
+    assert!(Ledger::create_invoice_weight(0, 0, false, false)
+        .all_lt(Ledger::create_invoice_weight(0, 0, true, false)));
+    assert!(Ledger::create_invoice_weight(0, 0, false, false)
+        .all_lt(Ledger::create_invoice_weight(0, 0, false, true)));
+
+    new_test_ext().execute_with(|| {
+        let create = |signature| crate::Call::<Test>::create_invoice {
+            client: 2,
+            amount: 100,
+            tax: 0,
+            discount: 0,
+            metadata: b"INV-1".to_vec(),
+            due_block: None,
+            expires_at: None,
+            line_items: vec![line_item(b"Work", 1, 100)],
+            signature,
+        };
+        assert_eq!(
+            create(None).get_dispatch_info().weight,
+            Ledger::create_invoice_weight(5, 1, false, false)
+        );
+        let signature = frame_support::BoundedVec::truncate_from(vec![0; 64]);
+        assert_eq!(
+            create(Some(signature)).get_dispatch_info().weight,
+            Ledger::create_invoice_weight(5, 1, true, false)
+        );
+
+        // Operator signatures are charged for every key, refunded down to the keys tried
+        let first = operator_pair(7);
+        let second = operator_pair(8);
+        assert_ok!(Ledger::add_operator_key(RuntimeOrigin::root(), first.public()));
+        assert_ok!(Ledger::add_operator_key(RuntimeOrigin::root(), second.public()));
+        let sig = operator_sign(&second, 2, 1000, b"INV-1");
+        let info = crate::Call::<Test>::create_signed_invoice {
+            client: 2,
+            amount: 1000,
+            metadata: b"INV-1".to_vec(),
+            operator_sig: sig.clone(),
+        }
+        .get_dispatch_info();
+        let max_keys = <Test as crate::Config>::MaxOperatorKeys::get();
+        assert_eq!(info.weight, Ledger::create_signed_invoice_weight(5, max_keys));
+
+        let post_info = Ledger::create_signed_invoice(
+            RuntimeOrigin::signed(1),
+            2,
+            1000u128,
+            b"INV-1".to_vec(),
+            sig,
+        )
+        .unwrap();
+        assert_eq!(post_info.actual_weight, Some(Ledger::create_signed_invoice_weight(5, 2)));
+    });
+}
+
+#[test]
+fn purge_invoice_weight_follows_invoices_and_grants() {
+    use crate::WeightInfo;
+    use frame_support::{dispatch::GetDispatchInfo, traits::Get};
+
+    let estimate = <() as WeightInfo>::purge_invoice;
+    assert!(estimate(1, 0).all_lt(estimate(2, 0)));
+    assert!(estimate(1, 0).ref_time() < estimate(1, 1).ref_time());
+
+    new_test_ext().execute_with(|| {
+        create_invoices(2, 3);
+        let hash = Ledger::get_invoice(&2, 1).unwrap().invoice_hash;
+        assert_ok!(Ledger::grant_access(RuntimeOrigin::signed(1), hash, 4, [7; 32]));
+
+        // Charged upfront for a full client and invoice
+        let info = crate::Call::<Test>::purge_invoice { client: 2, invoice_id: 1, force: false }
+            .get_dispatch_info();
+        let max_invoices = <Test as crate::Config>::MaxInvoicesPerClient::get();
+        let max_grants = <Test as crate::Config>::MaxGrantsPerInvoice::get();
+        assert_eq!(info.weight, estimate(max_invoices, max_grants));
+
+        // Refunded down to the three invoices and one grant there were
+        let post_info = Ledger::purge_invoice(RuntimeOrigin::root(), 2, 1, false).unwrap();
+        assert_eq!(post_info.actual_weight, Some(estimate(3, 1)));
+    });
+}
+
+#[test]
+fn invoice_hash_lookup_works() {
+    new_test_ext().execute_with(|| {
+        let creator = 1u64;
+        let client = 2u64;
+
+        // Create invoice
+        assert_ok!(Ledger::create_invoice(
+            RuntimeOrigin::signed(creator),
+            client,
+            1000u128,
+            0,
+            0,
+            b"Test Invoice".to_vec(),
+            None,
+            None,
+            vec![],
+            None
+        ));
+
+        // Get the invoice to obtain its hash
+        let invoices = Ledger::get_client_invoices(&client);
+        let invoice_hash = invoices[0].invoice_hash;
+
+        // Lookup invoice by hash
+        let found_id = Ledger::get_invoice_by_hash(invoice_hash);
+        assert_eq!(found_id, Some(0));
+    });
+}
+
+#[test]
+fn events_are_emitted() {
+    new_test_ext().execute_with(|| {
+        let creator = 1u64;
+        let client = 2u64;
+        let amount = 1000u128;
+
+        // Create invoice
+        assert_ok!(Ledger::create_invoice(
+            RuntimeOrigin::signed(creator),
+            client,
+            amount,
+            0,
+            0,
+            b"Test Invoice".to_vec(),
+            None,
+            None,
+            vec![],
+            None
+        ));
+
+        // Get the invoice hash
+        let invoices = Ledger::get_client_invoices(&client);
+        let invoice_hash = invoices[0].invoice_hash;
+
+        // Check InvoiceCreated event
+        System::assert_has_event(
+            Event::InvoiceCreated {
+                invoice_id: 0,
+                client,
+                amount,
+                net_amount: amount,
+                invoice_hash,
+                created_by: creator,
+                line_items: 0,
+            }
+            .into(),
+        );
+
+        // Check InvoiceHashStored event
+        System::assert_has_event(
+            Event::InvoiceHashStored {
+                invoice_hash,
+                invoice_id: 0,
+            }
+            .into(),
+        );
+    });
+}
+
+#[test]
+fn pay_invoice_works() {
+    new_test_ext().execute_with(|| {
+        let creator = 1u64;
+        let client = 2u64;
+        let amount = 1000u128;
+
+        // Create invoice
+        assert_ok!(Ledger::create_invoice(
+            RuntimeOrigin::signed(creator),
+            client,
+            amount,
+            0,
+            0,
+            b"Invoice to pay".to_vec(),
+            None,
+            None,
+            vec![],
+            None
+        ));
+
+        // Client pays the invoice
+        assert_ok!(Ledger::pay_invoice(RuntimeOrigin::signed(client), creator, 0));
+
+        // Verify funds moved
+        assert_eq!(Balances::free_balance(client), INITIAL_BALANCE - amount);
+        assert_eq!(Balances::free_balance(creator), INITIAL_BALANCE + amount);
+
+        // Verify invoice marked as paid
+        let invoices = Ledger::get_client_invoices(&client);
+        assert_eq!(invoices[0].status, InvoiceStatus::Paid);
+
+        // Verify event
+        System::assert_has_event(
+            Event::InvoicePaid {
+                invoice_id: 0,
+                payer: client,
+                payee: creator,
+                amount,
+            }
+            .into(),
+        );
+    });
+}
+
+#[test]
+fn cannot_pay_invoice_twice() {
+    new_test_ext().execute_with(|| {
+        let creator = 1u64;
+        let client = 2u64;
+
+        assert_ok!(Ledger::create_invoice(
+            RuntimeOrigin::signed(creator),
+            client,
+            1000u128,
+            0,
+            0,
+            b"Invoice".to_vec(),
+            None,
+            None,
+            vec![],
+            None
+        ));
+        assert_ok!(Ledger::pay_invoice(RuntimeOrigin::signed(client), creator, 0));
+
+        // Second payment should fail
+        assert_noop!(
+            Ledger::pay_invoice(RuntimeOrigin::signed(client), creator, 0),
+            Error::<Test>::InvoiceAlreadyPaid
+        );
+    });
+}
+
+#[test]
+fn cannot_pay_cancelled_invoice() {
+    new_test_ext().execute_with(|| {
+        let creator = 1u64;
+        let client = 2u64;
+
+        assert_ok!(Ledger::create_invoice(
+            RuntimeOrigin::signed(creator),
+            client,
+            1000u128,
+            0,
+            0,
+            b"Invoice".to_vec(),
+            None,
+            None,
+            vec![],
+            None
+        ));
+
+        assert_ok!(Ledger::cancel_invoice(RuntimeOrigin::signed(creator), client, 0));
+
+        assert_noop!(
+            Ledger::pay_invoice(RuntimeOrigin::signed(client), creator, 0),
+            Error::<Test>::InvoiceCancelled
+        );
+    });
+}
+
+#[test]
+fn pay_invoice_with_insufficient_balance_fails() {
+    new_test_ext().execute_with(|| {
+        let creator = 1u64;
+        let client = 2u64;
+
+        // Invoice for more than the client owns
+        assert_ok!(Ledger::create_invoice(
+            RuntimeOrigin::signed(creator),
+            client,
+            INITIAL_BALANCE + 1,
+            0,
+            0,
+            b"Too expensive".to_vec(),
+            None,
+            None,
+            vec![],
+            None
+        ));
+
+        // Fails without touching balances or invoice status
+        assert_noop!(
+            Ledger::pay_invoice(RuntimeOrigin::signed(client), creator, 0),
+            TokenError::FundsUnavailable
+        );
+
+        let invoices = Ledger::get_client_invoices(&client);
+        assert_eq!(invoices[0].status, InvoiceStatus::Pending);
+    });
+}
+
+#[test]
+fn pay_invoice_checks_creator_and_existence() {
+    new_test_ext().execute_with(|| {
+        let creator = 1u64;
+        let client = 2u64;
+
+        assert_ok!(Ledger::create_invoice(
+            RuntimeOrigin::signed(creator),
+            client,
+            1000u128,
+            0,
+            0,
+            b"Invoice".to_vec(),
+            None,
+            None,
+            vec![],
+            None
+        ));
+
+        // Wrong creator
+        assert_noop!(
+            Ledger::pay_invoice(RuntimeOrigin::signed(client), 3, 0),
+            Error::<Test>::CreatorMismatch
+        );
+
+        // Only the invoiced client can find the invoice
+        assert_noop!(
+            Ledger::pay_invoice(RuntimeOrigin::signed(3), creator, 0),
+            Error::<Test>::InvoiceNotFound
+        );
+
+        // Unknown invoice ID
+        assert_noop!(
+            Ledger::pay_invoice(RuntimeOrigin::signed(client), creator, 999),
+            Error::<Test>::InvoiceNotFound
+        );
+    });
+}
+
+#[test]
+fn cancel_invoice_works() {
+    new_test_ext().execute_with(|| {
+        let creator = 1u64;
+        let client = 2u64;
+
+        assert_ok!(Ledger::create_invoice(
+            RuntimeOrigin::signed(creator),
+            client,
+            1000u128,
+            0,
+            0,
+            b"Mistaken invoice".to_vec(),
+            None,
+            None,
+            vec![],
+            None
+        ));
+        let invoice_hash = Ledger::get_client_invoices(&client)[0].invoice_hash;
+
+        // Creator cancels the invoice
+        assert_ok!(Ledger::cancel_invoice(RuntimeOrigin::signed(creator), client, 0));
+
+        // Invoice is kept with a cancelled status
+        let invoices = Ledger::get_client_invoices(&client);
+        assert_eq!(invoices.len(), 1);
+        assert_eq!(invoices[0].status, InvoiceStatus::Cancelled);
+
+        // Hash lookup still proves the invoice existed
+        assert_eq!(Ledger::get_invoice_by_hash(invoice_hash), Some(0));
+
+        System::assert_has_event(Event::InvoiceCancelled { invoice_id: 0, client }.into());
+    });
+}
+
+#[test]
+fn cannot_cancel_invoice_twice() {
+    new_test_ext().execute_with(|| {
+        let creator = 1u64;
+        let client = 2u64;
+
+        assert_ok!(Ledger::create_invoice(
+            RuntimeOrigin::signed(creator),
+            client,
+            1000u128,
+            0,
+            0,
+            b"Invoice".to_vec(),
+            None,
+            None,
+            vec![],
+            None
+        ));
+        assert_ok!(Ledger::cancel_invoice(RuntimeOrigin::signed(creator), client, 0));
+
+        assert_noop!(
+            Ledger::cancel_invoice(RuntimeOrigin::signed(creator), client, 0),
+            Error::<Test>::InvoiceCancelled
+        );
+    });
+}
+
+#[test]
+fn only_creator_can_cancel_invoice() {
+    new_test_ext().execute_with(|| {
+        let creator = 1u64;
+        let client = 2u64;
+
+        assert_ok!(Ledger::create_invoice(
+            RuntimeOrigin::signed(creator),
+            client,
+            1000u128,
+            0,
+            0,
+            b"Invoice".to_vec(),
+            None,
+            None,
+            vec![],
+            None
+        ));
+
+        // Neither the client nor a third party can cancel
+        assert_noop!(
+            Ledger::cancel_invoice(RuntimeOrigin::signed(client), client, 0),
+            Error::<Test>::NotInvoiceCreator
+        );
+        assert_noop!(
+            Ledger::cancel_invoice(RuntimeOrigin::signed(3), client, 0),
+            Error::<Test>::NotInvoiceCreator
+        );
+    });
+}
+
+#[test]
+fn cannot_cancel_paid_invoice() {
+    new_test_ext().execute_with(|| {
+        let creator = 1u64;
+        let client = 2u64;
+
+        assert_ok!(Ledger::create_invoice(
+            RuntimeOrigin::signed(creator),
+            client,
+            1000u128,
+            0,
+            0,
+            b"Invoice".to_vec(),
+            None,
+            None,
+            vec![],
+            None
+        ));
+        assert_ok!(Ledger::pay_invoice(RuntimeOrigin::signed(client), creator, 0));
+
+        assert_noop!(
+            Ledger::cancel_invoice(RuntimeOrigin::signed(creator), client, 0),
+            Error::<Test>::InvoiceAlreadyPaid
+        );
+    });
+}
+
+/// Number of `InvoiceOverdue` events deposited so far
+fn overdue_events() -> usize {
+    System::events()
+        .iter()
+        .filter(|r| matches!(r.event, RuntimeEvent::Ledger(Event::InvoiceOverdue { .. })))
+        .count()
+}
+
+#[test]
+fn invoice_becomes_overdue_exactly_once() {
+    new_test_ext().execute_with(|| {
+        let creator = 1u64;
+        let client = 2u64;
+
+        // Invoice due at block 10
+        assert_ok!(Ledger::create_invoice(
+            RuntimeOrigin::signed(creator),
+            client,
+            1000u128,
+            0,
+            0,
+            b"Net 30".to_vec(),
+            Some(10),
+            None,
+            vec![],
+            None
+        ));
+        assert_eq!(Ledger::get_client_invoices(&client)[0].due_block, Some(10));
+
+        // Not overdue before the due block
+        System::set_block_number(9);
+        Ledger::on_initialize(9);
+        assert_eq!(Ledger::get_client_invoices(&client)[0].status, InvoiceStatus::Pending);
+
+        // Flips at the due block
+        System::set_block_number(10);
+        Ledger::on_initialize(10);
+        assert_eq!(Ledger::get_client_invoices(&client)[0].status, InvoiceStatus::Overdue);
+        System::assert_has_event(Event::InvoiceOverdue { invoice_id: 0, client }.into());
+        assert!(Ledger::due_invoices(10).is_empty());
+
+        // Later blocks do not emit again
+        System::set_block_number(11);
+        Ledger::on_initialize(11);
+        assert_eq!(overdue_events(), 1);
+
+        // Overdue invoices can still be paid
+        assert_ok!(Ledger::pay_invoice(RuntimeOrigin::signed(client), creator, 0));
+    });
+}
+
+#[test]
+fn paid_invoice_does_not_become_overdue() {
+    new_test_ext().execute_with(|| {
+        let creator = 1u64;
+        let client = 2u64;
+
+        assert_ok!(Ledger::create_invoice(
+            RuntimeOrigin::signed(creator),
+            client,
+            1000u128,
+            0,
+            0,
+            b"Net 30".to_vec(),
+            Some(5),
+            None,
+            vec![],
+            None
+        ));
+        assert_ok!(Ledger::pay_invoice(RuntimeOrigin::signed(client), creator, 0));
+
+        System::set_block_number(5);
+        Ledger::on_initialize(5);
+
+        assert_eq!(Ledger::get_client_invoices(&client)[0].status, InvoiceStatus::Paid);
+        assert_eq!(overdue_events(), 0);
+    });
+}
+
+#[test]
+fn overdue_check_respects_weight_budget() {
+    new_test_ext().execute_with(|| {
+        let creator = 1u64;
+        let client = 2u64;
+
+        for i in 0..2 {
+            assert_ok!(Ledger::create_invoice(
+                RuntimeOrigin::signed(creator),
+                client,
+                1000u128,
+                0,
+                0,
+                format!("Invoice {}", i).as_bytes().to_vec(),
+                Some(5),
+                None,
+                vec![],
+                None
+            ));
+        }
+
+        // Budget covers the cursor read, one block and a single invoice
+        let db = RocksDbWeight::get();
+        OverdueCheckWeight::set(db.reads(1).saturating_add(db.reads_writes(2, 2)));
+
+        System::set_block_number(5);
+        Ledger::on_initialize(5);
+        assert_eq!(overdue_events(), 1);
+        assert_eq!(Ledger::due_invoices(5).len(), 2);
+
+        // The remaining invoice is picked up in the next block
+        System::set_block_number(6);
+        Ledger::on_initialize(6);
+        assert_eq!(overdue_events(), 2);
+        assert!(Ledger::due_invoices(5).is_empty());
+
+        let invoices = Ledger::get_client_invoices(&client);
+        assert!(invoices.iter().all(|i| i.status == InvoiceStatus::Overdue));
+
+        // With no budget at all nothing is processed
+        OverdueCheckWeight::set(Weight::zero());
+        assert_eq!(Ledger::on_initialize(7), RocksDbWeight::get().reads_writes(1, 1));
+    });
+}
+
+#[test]
+fn due_block_must_be_in_future() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Ledger::create_invoice(
+                RuntimeOrigin::signed(1),
+                2,
+                1000u128,
+                0,
+                0,
+                b"Invoice".to_vec(),
+                Some(1),
+                None,
+                vec![],
+                None
+            ),
+            Error::<Test>::InvalidDueBlock
+        );
+    });
+}
+
+#[test]
+fn due_index_is_bounded_per_block() {
+    new_test_ext().execute_with(|| {
+        // Fill block 20 up to MaxDueInvoicesPerBlock (16)
+        for _ in 0..16 {
+            assert_ok!(Ledger::create_invoice(
+                RuntimeOrigin::signed(1),
+                2,
+                1000u128,
+                0,
+                0,
+                b"Invoice".to_vec(),
+                Some(20),
+                None,
+                vec![],
+                None
+            ));
+        }
+
+        assert_noop!(
+            Ledger::create_invoice(
+                RuntimeOrigin::signed(1),
+                2,
+                1000u128,
+                0,
+                0,
+                b"Invoice".to_vec(),
+                Some(20),
+                None,
+                vec![],
+                None
+            ),
+            Error::<Test>::TooManyInvoicesDue
+        );
+    });
+}
+
+/// Number of `InvoiceExpired` events deposited so far
+fn expired_events() -> usize {
+    System::events()
+        .iter()
+        .filter(|r| matches!(r.event, RuntimeEvent::Ledger(Event::InvoiceExpired { .. })))
+        .count()
+}
+
+/// Create an invoice from account 1 to account 2 that expires at `expires_at`
+fn create_expiring_invoice(metadata: &[u8], expires_at: u64) {
+    assert_ok!(Ledger::create_invoice(
+        RuntimeOrigin::signed(1),
+        2,
+        1000u128,
+        0,
+        0,
+        metadata.to_vec(),
+        None,
+        Some(expires_at),
+        vec![],
+        None
+    ));
+}
+
+#[test]
+fn invoice_expires_exactly_at_expiry_block() {
+    new_test_ext().execute_with(|| {
+        InvoiceDeposit::set(50);
+        create_expiring_invoice(b"INV-1", 10);
+        assert_eq!(Ledger::expiring_invoices(10).into_inner(), vec![(2, 0)]);
+        assert_eq!(Ledger::get_outstanding_balance(&2), 1000);
+
+        // Still pending one block before expiry
+        System::set_block_number(9);
+        Ledger::on_idle(9, Weight::MAX);
+        assert_eq!(Ledger::get_client_invoices(&2)[0].status, InvoiceStatus::Pending);
+
+        // Expires at the expiry block itself
+        System::set_block_number(10);
+        Ledger::on_idle(10, Weight::MAX);
+        assert_eq!(Ledger::get_client_invoices(&2)[0].status, InvoiceStatus::Expired);
+        System::assert_has_event(Event::InvoiceExpired { invoice_id: 0, client: 2 }.into());
+        assert!(Ledger::expiring_invoices(10).is_empty());
+
+        // The unpaid amount is written off and the deposit released
+        assert_eq!(Ledger::get_outstanding_balance(&2), 0);
+        assert_eq!(Balances::reserved_balance(1), 0);
+
+        // Expired invoices are terminal
+        assert_noop!(
+            Ledger::pay_invoice(RuntimeOrigin::signed(2), 1, 0),
+            Error::<Test>::InvoiceExpired
+        );
+
+        System::set_block_number(11);
+        Ledger::on_idle(11, Weight::MAX);
+        assert_eq!(expired_events(), 1);
+    });
+}
+
+#[test]
+fn invoice_paid_before_expiry_does_not_expire() {
+    new_test_ext().execute_with(|| {
+        create_expiring_invoice(b"INV-1", 10);
+
+        System::set_block_number(9);
+        Ledger::on_idle(9, Weight::MAX);
+        assert_ok!(Ledger::pay_invoice(RuntimeOrigin::signed(2), 1, 0));
+
+        System::set_block_number(10);
+        Ledger::on_idle(10, Weight::MAX);
+        assert_eq!(Ledger::get_client_invoices(&2)[0].status, InvoiceStatus::Paid);
+        assert_eq!(expired_events(), 0);
+        assert!(Ledger::expiring_invoices(10).is_empty());
+    });
+}
+
+#[test]
+fn expiry_sweep_respects_remaining_weight() {
+    new_test_ext().execute_with(|| {
+        create_expiring_invoice(b"INV-1", 5);
+        create_expiring_invoice(b"INV-2", 5);
+
+        // Covers the cursor, one block and a single invoice
+        let db = RocksDbWeight::get();
+        let budget = db.reads_writes(2, 2).saturating_add(db.reads_writes(3, 5));
+
+        System::set_block_number(5);
+        assert_eq!(Ledger::on_idle(5, budget), budget);
+        assert_eq!(expired_events(), 1);
+        assert_eq!(crate::ExpiryCursor::<Test>::get(), Some((5, 1)));
+
+        // The sweep resumes where it stopped
+        System::set_block_number(6);
+        Ledger::on_idle(6, budget);
+        assert_eq!(expired_events(), 2);
+        assert!(Ledger::expiring_invoices(5).is_empty());
+
+        // Nothing is done when the cursor itself does not fit
+        assert_eq!(Ledger::on_idle(7, db.reads(1)), Weight::zero());
+        assert_eq!(crate::ExpiryCursor::<Test>::get(), Some((6, 0)));
+    });
+}
+
+#[test]
+fn expiry_block_must_be_in_future_and_bounded() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Ledger::create_invoice(
+                RuntimeOrigin::signed(1),
+                2,
+                1000u128,
+                0,
+                0,
+                b"INV-1".to_vec(),
+                None,
+                Some(1),
+                vec![],
+                None
+            ),
+            Error::<Test>::InvalidExpiryBlock
+        );
+
+        // Fill block 20 up to MaxExpiringInvoicesPerBlock (2)
+        create_expiring_invoice(b"INV-1", 20);
+        create_expiring_invoice(b"INV-2", 20);
+        assert_noop!(
+            Ledger::create_invoice(
+                RuntimeOrigin::signed(1),
+                2,
+                1000u128,
+                0,
+                0,
+                b"INV-3".to_vec(),
+                None,
+                Some(20),
+                vec![],
+                None
+            ),
+            Error::<Test>::TooManyInvoicesExpiring
+        );
+    });
+}
+
+/// Create invoice 0 in a fresh chain and return it
+fn invoice_from_fresh_chain(metadata: Vec<u8>) -> crate::Invoice<Test> {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Ledger::create_invoice(
+            RuntimeOrigin::signed(1),
+            2,
+            1000u128,
+            0,
+            0,
+            metadata,
+            None,
+            None,
+            vec![],
+            None
+        ));
+        Ledger::get_client_invoices(&2)[0].clone()
+    })
+}
+
+#[test]
+fn crlf_metadata_hashes_like_lf() {
+    let crlf = invoice_from_fresh_chain(b"INV-2025-001\r\nClient XYZ\r\nNet 30".to_vec());
+    let lf = invoice_from_fresh_chain(b"INV-2025-001\nClient XYZ\nNet 30".to_vec());
+
+    // Same stored bytes and same hash
+    assert_eq!(crlf.metadata.to_vec(), b"INV-2025-001\nClient XYZ\nNet 30".to_vec());
+    assert_eq!(crlf.metadata, lf.metadata);
+    assert_eq!(crlf.invoice_hash, lf.invoice_hash);
+}
+
+#[test]
+fn invalid_utf8_metadata_fails() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Ledger::create_invoice(
+                RuntimeOrigin::signed(1),
+                2,
+                1000u128,
+                0,
+                0,
+                vec![b'I', b'N', b'V', 0xff],
+                None,
+                None,
+                vec![],
+                None
+            ),
+            Error::<Test>::InvalidUtf8
+        );
+    });
+}
+
+#[test]
+fn text_normalization_can_be_disabled() {
+    new_test_ext().execute_with(|| {
+        // Only root can toggle the flag
+        assert_noop!(
+            Ledger::set_text_normalization(RuntimeOrigin::signed(1), false),
+            sp_runtime::DispatchError::BadOrigin
+        );
+
+        assert_ok!(Ledger::set_text_normalization(RuntimeOrigin::root(), false));
+        assert!(!Ledger::normalize_text());
+        System::assert_has_event(Event::TextNormalizationSet { enabled: false }.into());
+
+        // Raw bytes are stored untouched
+        let raw = vec![b'a', b'\r', b'\n', 0xff];
+        assert_ok!(Ledger::create_invoice(
+            RuntimeOrigin::signed(1),
+            2,
+            1000u128,
+            0,
+            0,
+            raw.clone(),
+            None,
+            None,
+            vec![],
+            None
+        ));
+        assert_eq!(Ledger::get_client_invoices(&2)[0].metadata.to_vec(), raw);
+    });
+}
+
+#[test]
+fn migration_to_v1_moves_invoices_into_double_map() {
+    use crate::migrations::v1::{Invoices as OldInvoices, OldInvoice};
+    use frame_support::traits::{GetStorageVersion, StorageVersion};
+
+    new_test_ext().execute_with(|| {
+        StorageVersion::new(0).put::<Ledger>();
+
+        // Two invoices for client 2 in the pre-v1 layout
+        let old: Vec<OldInvoice<Test>> = (0..2u64)
+            .map(|id| OldInvoice {
+                id,
+                client: 2,
+                amount: 100 * (id as u128 + 1),
+                metadata: b"INV".to_vec().try_into().unwrap(),
+                timestamp: 1,
+                invoice_hash: [id as u8 + 1; 32],
+                created_by: 1,
+            })
+            .collect();
+        OldInvoices::<Test>::insert(2, frame_support::BoundedVec::truncate_from(old));
+
+        crate::migrations::v1::migrate::<Test>();
+
+        assert_eq!(Ledger::on_chain_storage_version(), 1);
+        assert_eq!(Ledger::client_invoice_count(2), 2);
+
+        // Bring the invoice layout up to date without re-hashing
+        StorageVersion::new(2).put::<Ledger>();
+        crate::migrations::v3::migrate::<Test>();
+        crate::migrations::v4::migrate::<Test>();
+        crate::migrations::v5::migrate::<Test>();
+        crate::migrations::v6::migrate::<Test>();
+        StorageVersion::new(7).put::<Ledger>();
+        crate::migrations::v8::migrate::<Test>();
+        crate::migrations::v9::migrate::<Test>();
+        crate::migrations::v10::migrate::<Test>();
+        crate::migrations::v11::migrate::<Test>();
+        crate::migrations::v12::migrate::<Test>();
+
+        let invoice = Ledger::get_invoice(&2, 1).unwrap();
+        assert_eq!(invoice.amount, 200);
+        assert_eq!(invoice.invoice_hash, [2u8; 32]);
+        assert_eq!(invoice.status, InvoiceStatus::Pending);
+        assert_eq!(invoice.due_block, None);
+        assert_eq!(invoice.paid_amount, 0);
+        assert_eq!(Ledger::get_client_invoices(&2).len(), 2);
+        let (page, next) = Ledger::get_client_invoices_paged(&2, Some(&cursor(1)), 10).unwrap();
+        assert_eq!(page.len(), 1);
+        assert_eq!(next, None);
+
+        // Running it again is a no-op
+        crate::migrations::v1::migrate::<Test>();
+        assert_eq!(Ledger::client_invoice_count(2), 2);
+    });
+}
+
+#[test]
+fn invoices_are_stored_per_client_and_id() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Ledger::create_invoice(
+            RuntimeOrigin::signed(1),
+            2,
+            100u128,
+            0,
+            0,
+            b"a".to_vec(),
+            None,
+            None,
+            vec![],
+            None
+        ));
+        assert_ok!(Ledger::create_invoice(
+            RuntimeOrigin::signed(1),
+            3,
+            200u128,
+            0,
+            0,
+            b"b".to_vec(),
+            None,
+            None,
+            vec![],
+            None
+        ));
+
+        assert_eq!(Ledger::get_invoice(&2, 0).unwrap().amount, 100);
+        assert_eq!(Ledger::get_invoice(&3, 1).unwrap().amount, 200);
+        assert!(Ledger::get_invoice(&2, 1).is_none());
+        assert_eq!(Ledger::client_invoice_count(2), 1);
+        assert_eq!(Ledger::client_invoice_count(3), 1);
+    });
+}
+
+/// Sign the canonical operator payload for an invoice
+fn operator_sign(
+    pair: &sp_core::sr25519::Pair,
+    client: u64,
+    amount: u128,
+    metadata: &[u8],
+) -> Vec<u8> {
+    use sp_core::Pair;
+    let payload = Ledger::operator_payload(&client, &amount, metadata);
+    pair.sign(&payload).0.to_vec()
+}
+
+fn operator_pair(seed: u8) -> sp_core::sr25519::Pair {
+    use sp_core::Pair;
+    sp_core::sr25519::Pair::from_seed(&[seed; 32])
+}
+
+#[test]
+fn create_signed_invoice_works() {
+    use sp_core::Pair;
+
+    new_test_ext().execute_with(|| {
+        let operator = operator_pair(7);
+        assert_ok!(Ledger::add_operator_key(RuntimeOrigin::root(), operator.public()));
+
+        let sig = operator_sign(&operator, 2, 1000, b"INV-1");
+        assert_ok!(Ledger::create_signed_invoice(
+            RuntimeOrigin::signed(1),
+            2,
+            1000u128,
+            b"INV-1".to_vec(),
+            sig
+        ));
+
+        assert_eq!(Ledger::invoice_operator(0), Some(operator.public()));
+        assert_eq!(Ledger::get_invoice(&2, 0).unwrap().created_by, 1);
+        System::assert_has_event(
+            Event::InvoiceAuthorized { invoice_id: 0, operator: operator.public() }.into(),
+        );
+    });
+}
+
+#[test]
+fn create_signed_invoice_rejects_invalid_signature() {
+    use sp_core::Pair;
+
+    new_test_ext().execute_with(|| {
+        let operator = operator_pair(7);
+        assert_ok!(Ledger::add_operator_key(RuntimeOrigin::root(), operator.public()));
+
+        // Signed by an unregistered key
+        let sig = operator_sign(&operator_pair(8), 2, 1000, b"INV-1");
+        assert_noop!(
+            Ledger::create_signed_invoice(
+                RuntimeOrigin::signed(1),
+                2,
+                1000u128,
+                b"INV-1".to_vec(),
+                sig
+            ),
+            Error::<Test>::InvalidOperatorSignature
+        );
+
+        // Signature over a different amount
+        let sig = operator_sign(&operator, 2, 999, b"INV-1");
+        assert_noop!(
+            Ledger::create_signed_invoice(
+                RuntimeOrigin::signed(1),
+                2,
+                1000u128,
+                b"INV-1".to_vec(),
+                sig
+            ),
+            Error::<Test>::InvalidOperatorSignature
+        );
+
+        // Malformed signature bytes
+        assert_noop!(
+            Ledger::create_signed_invoice(
+                RuntimeOrigin::signed(1),
+                2,
+                1000u128,
+                b"INV-1".to_vec(),
+                vec![0u8; 10]
+            ),
+            Error::<Test>::InvalidOperatorSignature
+        );
+    });
+}
+
+#[test]
+fn create_signed_invoice_rejects_replay() {
+    use sp_core::Pair;
+
+    new_test_ext().execute_with(|| {
+        let operator = operator_pair(7);
+        assert_ok!(Ledger::add_operator_key(RuntimeOrigin::root(), operator.public()));
+
+        let sig = operator_sign(&operator, 2, 1000, b"INV-1");
+        assert_ok!(Ledger::create_signed_invoice(
+            RuntimeOrigin::signed(1),
+            2,
+            1000u128,
+            b"INV-1".to_vec(),
+            sig.clone()
+        ));
+        assert_noop!(
+            Ledger::create_signed_invoice(
+                RuntimeOrigin::signed(3),
+                2,
+                1000u128,
+                b"INV-1".to_vec(),
+                sig
+            ),
+            Error::<Test>::OperatorPayloadReplayed
+        );
+    });
+}
+
+#[test]
+fn operator_key_rotation_keeps_past_invoices() {
+    use sp_core::Pair;
+
+    new_test_ext().execute_with(|| {
+        let old_key = operator_pair(7);
+        let new_key = operator_pair(8);
+        assert_ok!(Ledger::add_operator_key(RuntimeOrigin::root(), old_key.public()));
+
+        let sig = operator_sign(&old_key, 2, 1000, b"INV-1");
+        assert_ok!(Ledger::create_signed_invoice(
+            RuntimeOrigin::signed(1),
+            2,
+            1000u128,
+            b"INV-1".to_vec(),
+            sig
+        ));
+
+        // Rotate
+        assert_ok!(Ledger::add_operator_key(RuntimeOrigin::root(), new_key.public()));
+        assert_ok!(Ledger::remove_operator_key(RuntimeOrigin::root(), old_key.public()));
+        assert_eq!(Ledger::operator_keys().into_inner(), vec![new_key.public()]);
+
+        // Past invoice keeps its authorization record and hash
+        assert_eq!(Ledger::invoice_operator(0), Some(old_key.public()));
+        assert!(Ledger::verify_invoice_hash(&2, 0));
+
+        // The retired key can no longer sign, the new one can
+        let sig = operator_sign(&old_key, 2, 500, b"INV-2");
+        assert_noop!(
+            Ledger::create_signed_invoice(
+                RuntimeOrigin::signed(1),
+                2,
+                500u128,
+                b"INV-2".to_vec(),
+                sig
+            ),
+            Error::<Test>::InvalidOperatorSignature
+        );
+        let sig = operator_sign(&new_key, 2, 500, b"INV-2");
+        assert_ok!(Ledger::create_signed_invoice(
+            RuntimeOrigin::signed(1),
+            2,
+            500u128,
+            b"INV-2".to_vec(),
+            sig
+        ));
+        assert_eq!(Ledger::invoice_operator(1), Some(new_key.public()));
+    });
+}
+
+#[test]
+fn operator_keys_require_admin_origin() {
+    use sp_core::Pair;
+
+    new_test_ext().execute_with(|| {
+        let key = operator_pair(7).public();
+        assert_noop!(
+            Ledger::add_operator_key(RuntimeOrigin::signed(1), key),
+            sp_runtime::DispatchError::BadOrigin
+        );
+
+        assert_ok!(Ledger::add_operator_key(RuntimeOrigin::root(), key));
+        assert_noop!(
+            Ledger::add_operator_key(RuntimeOrigin::root(), key),
+            Error::<Test>::OperatorKeyExists
+        );
+        assert_noop!(
+            Ledger::remove_operator_key(RuntimeOrigin::root(), operator_pair(8).public()),
+            Error::<Test>::OperatorKeyNotFound
+        );
+    });
+}
+
+/// Create invoice `INV-1` for client 2 with a creator signature
+fn create_invoice_signed_by(creator: u64, signature: &[u8]) -> DispatchResultWithPostInfo {
+    Ledger::create_invoice(
+        RuntimeOrigin::signed(creator),
+        2,
+        1000u128,
+        0,
+        0,
+        b"INV-1".to_vec(),
+        None,
+        None,
+        vec![],
+        Some(signature.to_vec().try_into().unwrap()),
+    )
+}
+
+#[test]
+fn create_invoice_stores_sr25519_creator_signature() {
+    use sp_core::{sr25519, Pair};
+
+    // The creator signs the hash the invoice will get, same ID and block as on a fresh chain
+    let hash = invoice_from_fresh_chain(b"INV-1".to_vec()).invoice_hash;
+    let alice = sr25519::Pair::from_string("//Alice", None).unwrap();
+    let signature = alice.sign(&hash);
+
+    new_test_ext().execute_with(|| {
+        assert_ok!(create_invoice_signed_by(1, &signature.0));
+
+        assert_eq!(Ledger::get_invoice(&2, 0).unwrap().invoice_hash, hash);
+        assert_eq!(Ledger::invoice_signature(0).unwrap().to_vec(), signature.0.to_vec());
+        assert!(Ledger::verify_invoice_signature(&2, 0));
+        assert!(sr25519::Pair::verify(&signature, hash, &alice.public()));
+    });
+}
+
+#[test]
+fn create_invoice_accepts_ed25519_creator_signature() {
+    use sp_core::{ed25519, Pair};
+
+    let hash = invoice_from_fresh_chain(b"INV-1".to_vec()).invoice_hash;
+    let bob = ed25519::Pair::from_string("//Bob", None).unwrap();
+
+    new_test_ext().execute_with(|| {
+        assert_ok!(create_invoice_signed_by(3, &bob.sign(&hash).0));
+        assert!(Ledger::verify_invoice_signature(&2, 0));
+    });
+}
+
+#[test]
+fn create_invoice_rejects_invalid_creator_signature() {
+    use sp_core::{sr25519, Pair};
+
+    let hash = invoice_from_fresh_chain(b"INV-1".to_vec()).invoice_hash;
+    let alice = sr25519::Pair::from_string("//Alice", None).unwrap();
+    let bob = sr25519::Pair::from_string("//Bob", None).unwrap();
+
+    new_test_ext().execute_with(|| {
+        let rejected: [(u64, Vec<u8>); 5] = [
+            // Someone else's key
+            (1, bob.sign(&hash).0.to_vec()),
+            // Alice's key but another invoice's hash
+            (1, alice.sign(&[0u8; 32]).0.to_vec()),
+            // Not a signature at all
+            (1, vec![7u8; 10]),
+            (1, vec![]),
+            // A valid signature from a caller without a known key
+            (2, alice.sign(&hash).0.to_vec()),
+        ];
+        for (creator, signature) in rejected {
+            assert_noop!(
+                create_invoice_signed_by(creator, &signature),
+                Error::<Test>::InvalidSignature
+            );
+        }
+
+        // Nothing was stored
+        assert_eq!(Ledger::invoice_count(), 0);
+        assert_eq!(Ledger::invoice_signature(0), None);
+    });
+}
+
+#[test]
+fn verify_invoice_signature_needs_a_signature_over_the_current_hash() {
+    use sp_core::{sr25519, Pair};
+
+    let hash = invoice_from_fresh_chain(b"INV-1".to_vec()).invoice_hash;
+    let alice = sr25519::Pair::from_string("//Alice", None).unwrap();
+
+    new_test_ext().execute_with(|| {
+        assert_ok!(create_invoice_signed_by(1, &alice.sign(&hash).0));
+        assert_ok!(Ledger::create_invoice(
+            RuntimeOrigin::signed(1),
+            2,
+            500u128,
+            0,
+            0,
+            b"INV-2".to_vec(),
+            None,
+            None,
+            vec![],
+            None
+        ));
+
+        assert!(Ledger::verify_invoice_signature(&2, 0));
+        // Unsigned, unknown, or looked up under another client
+        assert!(!Ledger::verify_invoice_signature(&2, 1));
+        assert!(!Ledger::verify_invoice_signature(&2, 7));
+        assert!(!Ledger::verify_invoice_signature(&3, 0));
+
+        // The signature is kept but no longer covers the re-hashed invoice
+        assert_ok!(Ledger::update_invoice_metadata(
+            RuntimeOrigin::signed(1),
+            2,
+            0,
+            b"INV-1b".to_vec()
+        ));
+        assert!(Ledger::invoice_signature(0).is_some());
+        assert!(!Ledger::verify_invoice_signature(&2, 0));
+    });
+}
+
+/// Create `n` invoices for `client` (invoice IDs follow the global counter)
+fn create_invoices(client: u64, n: u32) {
+    for i in 0..n {
+        assert_ok!(Ledger::create_invoice(
+            RuntimeOrigin::signed(1),
+            client,
+            100u128 + i as u128,
+            0,
+            0,
+            format!("INV-{}", i).into_bytes(),
+            None,
+            None,
+            vec![],
+            None
+        ));
+    }
+}
+
+/// Cursor resuming at invoice `id`
+fn cursor(id: u64) -> PageCursor {
+    PageCursor::new(&id)
+}
+
+#[test]
+fn get_client_invoices_paged_works() {
+    new_test_ext().execute_with(|| {
+        // Interleave clients so client 2 holds IDs 0, 2, 4, 6, 8
+        for _ in 0..5 {
+            create_invoices(2, 1);
+            create_invoices(3, 1);
+        }
+
+        let (page, next) = Ledger::get_client_invoices_paged(&2, None, 2).unwrap();
+        assert_eq!(page.iter().map(|i| i.id).collect::<Vec<_>>(), vec![0, 2]);
+        assert_eq!(next, Some(cursor(4)));
+
+        let (page, next) = Ledger::get_client_invoices_paged(&2, Some(&cursor(4)), 2).unwrap();
+        assert_eq!(page.iter().map(|i| i.id).collect::<Vec<_>>(), vec![4, 6]);
+        assert_eq!(next, Some(cursor(8)));
+
+        let (page, next) = Ledger::get_client_invoices_paged(&2, Some(&cursor(8)), 2).unwrap();
+        assert_eq!(page.iter().map(|i| i.id).collect::<Vec<_>>(), vec![8]);
+        assert_eq!(next, None);
+
+        // A cursor between IDs starts at the next invoice of the client
+        let (page, _) = Ledger::get_client_invoices_paged(&2, Some(&cursor(3)), 1).unwrap();
+        assert_eq!(page[0].id, 4);
+    });
+}
+
+#[test]
+fn get_client_invoices_paged_is_stable_across_new_invoices() {
+    new_test_ext().execute_with(|| {
+        create_invoices(2, 3);
+
+        let (first, next) = Ledger::get_client_invoices_paged(&2, None, 2).unwrap();
+        assert_eq!(first.len(), 2);
+        assert_eq!(next, Some(cursor(2)));
+
+        // New invoices do not shift the remaining pages
+        create_invoices(2, 2);
+        let (page, next) = Ledger::get_client_invoices_paged(&2, Some(&cursor(2)), 2).unwrap();
+        assert_eq!(page.iter().map(|i| i.id).collect::<Vec<_>>(), vec![2, 3]);
+        assert_eq!(next, Some(cursor(4)));
+    });
+}
+
+#[test]
+fn get_client_invoices_paged_edge_cases() {
+    new_test_ext().execute_with(|| {
+        // Client with no invoices
+        assert_eq!(Ledger::get_client_invoices_paged(&2, None, 10), Ok((vec![], None)));
+
+        create_invoices(2, 3);
+
+        // Limit of zero
+        assert_eq!(Ledger::get_client_invoices_paged(&2, None, 0), Ok((vec![], None)));
+
+        // Cursor past the end
+        assert_eq!(
+            Ledger::get_client_invoices_paged(&2, Some(&cursor(100)), 10),
+            Ok((vec![], None))
+        );
+    });
+}
+
+#[test]
+fn get_client_invoices_paged_rejects_corrupted_cursors() {
+    new_test_ext().execute_with(|| {
+        create_invoices(2, 3);
+        let (_, next) = Ledger::get_client_invoices_paged(&2, None, 1).unwrap();
+        let valid = next.unwrap().as_bytes().to_vec();
+
+        // Wrong version, truncated, or with trailing bytes
+        let mut rejected =
+            vec![[&[0u8][..], &valid[1..]].concat(), [&valid[..], &[0u8][..]].concat()];
+        rejected.extend((0..valid.len()).map(|len| valid[..len].to_vec()));
+        for bytes in rejected {
+            let cursor = PageCursor::try_from(bytes).unwrap();
+            assert_eq!(
+                Ledger::get_client_invoices_paged(&2, Some(&cursor), 10),
+                Err(InvalidCursor)
+            );
+            // Even with nothing to return, the cursor is checked
+            assert_eq!(
+                Ledger::get_client_invoices_paged(&2, Some(&cursor), 0),
+                Err(InvalidCursor)
+            );
+        }
+
+        // Arbitrary bytes either decode to some position or are rejected, never panic
+        let mut seed = 0x9e37_79b9_7f4a_7c15u64;
+        for _ in 0..500 {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            let len = (seed % 16) as usize;
+            let bytes: Vec<u8> = seed.to_le_bytes().iter().cycle().take(len).copied().collect();
+            let cursor = PageCursor::try_from(bytes).unwrap();
+            let _ = Ledger::get_client_invoices_paged(&2, Some(&cursor), 10);
+        }
+    });
+}
+
+#[test]
+fn invoice_status_table_has_terminal_paid_and_cancelled() {
+    let all = [
+        InvoiceStatus::Pending,
+        InvoiceStatus::Paid,
+        InvoiceStatus::Cancelled,
+        InvoiceStatus::Overdue,
+        InvoiceStatus::Accepted,
+        InvoiceStatus::Rejected,
+    ];
+
+    for terminal in [InvoiceStatus::Paid, InvoiceStatus::Cancelled, InvoiceStatus::Rejected] {
+        assert!(INVOICE_STATUS.is_terminal(&terminal));
+        for to in &all {
+            assert!(!INVOICE_STATUS.can_transition(&terminal, to));
+        }
+    }
+
+    // No self-transitions, and nothing leads back to Pending
+    for state in &all {
+        assert!(!INVOICE_STATUS.can_transition(state, state));
+        assert!(!INVOICE_STATUS.can_transition(state, &InvoiceStatus::Pending));
+    }
+}
+
+#[test]
+fn overdue_invoice_can_still_be_paid_or_cancelled() {
+    new_test_ext().execute_with(|| {
+        // Two invoices for client 2, both due at block 5
+        for _ in 0..2 {
+            assert_ok!(Ledger::create_invoice(
+                RuntimeOrigin::signed(1),
+                2,
+                100u128,
+                0,
+                0,
+                b"Net 30".to_vec(),
+                Some(5),
+                None,
+                vec![],
+                None
+            ));
+        }
+        System::set_block_number(5);
+        Ledger::on_initialize(5);
+        assert_eq!(Ledger::get_invoice(&2, 0).unwrap().status, InvoiceStatus::Overdue);
+
+        assert_ok!(Ledger::pay_invoice(RuntimeOrigin::signed(2), 1, 0));
+        assert_eq!(Ledger::get_invoice(&2, 0).unwrap().status, InvoiceStatus::Paid);
+
+        assert_ok!(Ledger::cancel_invoice(RuntimeOrigin::signed(1), 2, 1));
+        assert_eq!(Ledger::get_invoice(&2, 1).unwrap().status, InvoiceStatus::Cancelled);
+    });
+}
+
+#[test]
+fn record_payment_accumulates_installments() {
+    new_test_ext().execute_with(|| {
+        let creator = 1u64;
+        let client = 2u64;
+
+        assert_ok!(Ledger::create_invoice(
+            RuntimeOrigin::signed(creator),
+            client,
+            1000u128,
+            0,
+            0,
+            b"INV-1".to_vec(),
+            None,
+            None,
+            vec![],
+            None
+        ));
+
+        assert_ok!(Ledger::record_payment(RuntimeOrigin::signed(client), client, 0, 300));
+        System::assert_has_event(
+            Event::PartialPaymentRecorded { invoice_id: 0, amount: 300, remaining: 700 }.into(),
+        );
+
+        let invoice = Ledger::get_invoice(&client, 0).unwrap();
+        assert_eq!(invoice.paid_amount, 300);
+        assert_eq!(invoice.status, InvoiceStatus::Pending);
+        assert_eq!(Balances::free_balance(creator), INITIAL_BALANCE + 300);
+        assert_eq!(Balances::free_balance(client), INITIAL_BALANCE - 300);
+
+        // Any account may pay an installment
+        assert_ok!(Ledger::record_payment(RuntimeOrigin::signed(3), client, 0, 200));
+        assert_eq!(Ledger::get_invoice(&client, 0).unwrap().paid_amount, 500);
+        assert_eq!(Balances::free_balance(3), INITIAL_BALANCE - 200);
+    });
+}
+
+#[test]
+fn record_payment_of_exact_remaining_settles_invoice() {
+    new_test_ext().execute_with(|| {
+        let creator = 1u64;
+        let client = 2u64;
+
+        assert_ok!(Ledger::create_invoice(
+            RuntimeOrigin::signed(creator),
+            client,
+            1000u128,
+            0,
+            0,
+            b"INV-1".to_vec(),
+            None,
+            None,
+            vec![],
+            None
+        ));
+        assert_ok!(Ledger::record_payment(RuntimeOrigin::signed(client), client, 0, 400));
+
+        // Exactly the remaining balance
+        assert_ok!(Ledger::record_payment(RuntimeOrigin::signed(client), client, 0, 600));
+
+        let invoice = Ledger::get_invoice(&client, 0).unwrap();
+        assert_eq!(invoice.paid_amount, 1000);
+        assert_eq!(invoice.status, InvoiceStatus::Paid);
+        System::assert_has_event(
+            Event::PartialPaymentRecorded { invoice_id: 0, amount: 600, remaining: 0 }.into(),
+        );
+        System::assert_has_event(
+            Event::InvoicePaid { invoice_id: 0, payer: client, payee: creator, amount: 1000 }
+                .into(),
+        );
+        assert_eq!(Balances::free_balance(creator), INITIAL_BALANCE + 1000);
+
+        // Nothing more can be paid
+        assert_noop!(
+            Ledger::record_payment(RuntimeOrigin::signed(client), client, 0, 1),
+            Error::<Test>::InvoiceAlreadyPaid
+        );
+    });
+}
+
+#[test]
+fn record_payment_rejects_overpayment_and_zero() {
+    new_test_ext().execute_with(|| {
+        let client = 2u64;
+
+        assert_ok!(Ledger::create_invoice(
+            RuntimeOrigin::signed(1),
+            client,
+            1000u128,
+            0,
+            0,
+            b"INV-1".to_vec(),
+            None,
+            None,
+            vec![],
+            None
+        ));
+        assert_ok!(Ledger::record_payment(RuntimeOrigin::signed(client), client, 0, 900));
+
+        assert_noop!(
+            Ledger::record_payment(RuntimeOrigin::signed(client), client, 0, 101),
+            Error::<Test>::PaymentExceedsAmount
+        );
+        assert_noop!(
+            Ledger::record_payment(RuntimeOrigin::signed(client), client, 0, 0),
+            Error::<Test>::ZeroPayment
+        );
+        assert_noop!(
+            Ledger::record_payment(RuntimeOrigin::signed(client), client, 1, 10),
+            Error::<Test>::InvoiceNotFound
+        );
+    });
+}
+
+#[test]
+fn pay_invoice_transfers_only_outstanding_amount() {
+    new_test_ext().execute_with(|| {
+        let creator = 1u64;
+        let client = 2u64;
+
+        assert_ok!(Ledger::create_invoice(
+            RuntimeOrigin::signed(creator),
+            client,
+            1000u128,
+            0,
+            0,
+            b"INV-1".to_vec(),
+            None,
+            None,
+            vec![],
+            None
+        ));
+        assert_ok!(Ledger::record_payment(RuntimeOrigin::signed(client), client, 0, 250));
+        assert_ok!(Ledger::pay_invoice(RuntimeOrigin::signed(client), creator, 0));
+
+        assert_eq!(Ledger::get_invoice(&client, 0).unwrap().paid_amount, 1000);
+        assert_eq!(Balances::free_balance(client), INITIAL_BALANCE - 1000);
+        System::assert_has_event(
+            Event::InvoicePaid { invoice_id: 0, payer: client, payee: creator, amount: 1000 }
+                .into(),
+        );
+    });
+}
+
+#[test]
+fn creator_index_agrees_with_client_view() {
+    new_test_ext().execute_with(|| {
+        // Creators 1 and 3 both bill clients 2 and 4
+        for (creator, client) in [(1u64, 2u64), (3, 2), (1, 4), (3, 4), (1, 2)] {
+            assert_ok!(Ledger::create_invoice(
+                RuntimeOrigin::signed(creator),
+                client,
+                100u128,
+                0,
+                0,
+                b"INV".to_vec(),
+                None,
+                None,
+                vec![],
+                None
+            ));
+        }
+
+        let ids = |invoices: Vec<crate::Invoice<Test>>| -> Vec<u64> {
+            invoices.iter().map(|i| i.id).collect()
+        };
+        assert_eq!(ids(Ledger::get_creator_invoices(&1)), vec![0, 2, 4]);
+        assert_eq!(ids(Ledger::get_creator_invoices(&3)), vec![1, 3]);
+        assert!(Ledger::get_creator_invoices(&5).is_empty());
+
+        // Every invoice appears exactly once in each view, with the same content
+        let mut by_creator = Ledger::get_creator_invoices(&1);
+        by_creator.extend(Ledger::get_creator_invoices(&3));
+        let mut by_client = Ledger::get_client_invoices(&2);
+        by_client.extend(Ledger::get_client_invoices(&4));
+        by_creator.sort_by_key(|i| i.id);
+        by_client.sort_by_key(|i| i.id);
+        assert_eq!(by_creator, by_client);
+
+        // Status changes are visible through the creator view
+        assert_ok!(Ledger::cancel_invoice(RuntimeOrigin::signed(3), 2, 1));
+        assert_eq!(Ledger::get_creator_invoices(&3)[0].status, InvoiceStatus::Cancelled);
+    });
+}
+
+#[test]
+fn client_invoice_count_tracks_creation_and_archival() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(Ledger::get_client_invoice_count(&2), 0);
+
+        create_invoices(2, 3);
+        create_invoices(3, 1);
+        assert_eq!(Ledger::get_client_invoice_count(&2), 3);
+        assert_eq!(Ledger::get_client_invoice_count(&3), 1);
+
+        // Archived invoices are no longer counted
+        assert_ok!(Ledger::pay_invoice(RuntimeOrigin::signed(2), 1, 0));
+        assert_ok!(Ledger::archive_invoices(RuntimeOrigin::signed(1), 2, 3));
+        assert_eq!(Ledger::get_client_invoice_count(&2), 2);
+    });
+}
+
+#[test]
+fn full_client_fails_before_invoice_validation() {
+    new_test_ext().execute_with(|| {
+        MaxInvoicesPerClient::set(2);
+        create_invoices(2, 2);
+
+        // The cap is reported even for an invoice that would fail validation anyway
+        assert_noop!(
+            Ledger::create_invoice(
+                RuntimeOrigin::signed(1),
+                2,
+                100u128,
+                0,
+                0,
+                vec![0u8; 2048],
+                Some(1),
+                None,
+                vec![],
+                None
+            ),
+            Error::<Test>::TooManyInvoices
+        );
+        assert_eq!(Ledger::client_invoice_count(2), 2);
+    });
+}
+
+#[test]
+fn archive_invoices_removes_every_record_of_the_invoice() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Ledger::create_invoice_with_ref(
+            RuntimeOrigin::signed(1),
+            2,
+            1000u128,
+            0,
+            0,
+            b"Client XYZ".to_vec(),
+            None,
+            None,
+            Vec::new(),
+            None,
+            b"INV-2025-001".to_vec()
+        ));
+        let hash = Ledger::get_invoice(&2, 0).unwrap().invoice_hash;
+        assert_ok!(Ledger::attach_document(
+            RuntimeOrigin::signed(1),
+            2,
+            0,
+            [9; 32],
+            b"delivery-note".to_vec()
+        ));
+        assert_ok!(Ledger::grant_access(RuntimeOrigin::signed(1), hash, 4, [7; 32]));
+        assert_ok!(Ledger::cancel_invoice(RuntimeOrigin::signed(1), 2, 0));
+
+        assert_ok!(Ledger::archive_invoices(RuntimeOrigin::signed(1), 2, 1));
+
+        // Only the hash is kept
+        assert_eq!(Ledger::get_archived_invoice_hash(&2, 0), Some(hash));
+        assert!(Ledger::get_attachments(&2, 0).is_empty());
+        assert_eq!(Ledger::access_grant(hash, 4), None);
+        assert_eq!(Ledger::access_grant_count(hash), 0);
+        assert_eq!(Ledger::get_invoice_by_external_ref(b"INV-2025-001"), None);
+
+        // The reference is free for a new invoice
+        assert_ok!(Ledger::create_invoice_with_ref(
+            RuntimeOrigin::signed(1),
+            2,
+            1000u128,
+            0,
+            0,
+            b"Client XYZ".to_vec(),
+            None,
+            None,
+            Vec::new(),
+            None,
+            b"INV-2025-001".to_vec()
+        ));
+        assert_eq!(Ledger::get_invoice_by_external_ref(b"INV-2025-001").unwrap().id, 1);
+    });
+}
+
+#[test]
+fn archive_invoices_is_bounded_per_call_and_refunds() {
+    use crate::WeightInfo;
+    use frame_support::{dispatch::GetDispatchInfo, traits::Get};
+
+    let estimate = <() as WeightInfo>::archive_invoices;
+    assert!(estimate(1, 0, 0).all_lt(estimate(2, 0, 0)));
+    assert!(estimate(1, 0, 0).ref_time() < estimate(1, 1, 0).ref_time());
+    assert!(estimate(1, 1, 0).ref_time() < estimate(1, 1, 1).ref_time());
+
+    new_test_ext().execute_with(|| {
+        MaxArchivedPerCall::set(2);
+        create_invoices(2, 3);
+        let hash = Ledger::get_invoice(&2, 0).unwrap().invoice_hash;
+        assert_ok!(Ledger::grant_access(RuntimeOrigin::signed(1), hash, 4, [7; 32]));
+        for invoice_id in 0..3 {
+            assert_ok!(Ledger::cancel_invoice(RuntimeOrigin::signed(1), 2, invoice_id));
+        }
+
+        // Charged upfront for a full client and a full call with full grants
+        let info =
+            crate::Call::<Test>::archive_invoices { client: 2, up_to_id: 3 }.get_dispatch_info();
+        let max_invoices = <Test as crate::Config>::MaxInvoicesPerClient::get();
+        let max_grants = <Test as crate::Config>::MaxGrantsPerInvoice::get();
+        assert_eq!(info.weight, estimate(max_invoices, 2, 2 * max_grants));
+
+        // Two archived this call, refunded down to three invoices and one grant
+        let post_info = Ledger::archive_invoices(RuntimeOrigin::root(), 2, 3).unwrap();
+        assert_eq!(post_info.actual_weight, Some(estimate(3, 2, 1)));
+        System::assert_has_event(Event::InvoicesArchived { client: 2, count: 2 }.into());
+        let remaining: Vec<u64> = Ledger::get_client_invoices(&2).iter().map(|i| i.id).collect();
+        assert_eq!(remaining, vec![2]);
+
+        // The next call picks up the rest
+        assert_ok!(Ledger::archive_invoices(RuntimeOrigin::root(), 2, 3));
+        System::assert_has_event(Event::InvoicesArchived { client: 2, count: 1 }.into());
+        assert_eq!(Ledger::client_invoice_count(2), 0);
+    });
+}
+
+#[test]
+fn archive_invoices_frees_room_under_cap() {
+    new_test_ext().execute_with(|| {
+        MaxInvoicesPerClient::set(5);
+        let creator = 1u64;
+        let client = 2u64;
+
+        // Fill the client up to the cap
+        create_invoices(client, 5);
+        assert_noop!(
+            Ledger::create_invoice(
+                RuntimeOrigin::signed(creator),
+                client,
+                100u128,
+                0,
+                0,
+                b"INV-X".to_vec(),
+                None,
+                None,
+                vec![],
+                None
+            ),
+            Error::<Test>::TooManyInvoices
+        );
+
+        // Settle 0 and 1, cancel 3; 2 and 4 stay pending
+        assert_ok!(Ledger::pay_invoice(RuntimeOrigin::signed(client), creator, 0));
+        assert_ok!(Ledger::pay_invoice(RuntimeOrigin::signed(client), creator, 1));
+        assert_ok!(Ledger::cancel_invoice(RuntimeOrigin::signed(creator), client, 3));
+        let hash_of_3 = Ledger::get_invoice(&client, 3).unwrap().invoice_hash;
+
+        assert_ok!(Ledger::archive_invoices(RuntimeOrigin::signed(creator), client, 5));
+        System::assert_has_event(Event::InvoicesArchived { client, count: 3 }.into());
+
+        // Only unpaid invoices remain; archived ones keep their hash
+        let remaining: Vec<u64> =
+            Ledger::get_client_invoices(&client).iter().map(|i| i.id).collect();
+        assert_eq!(remaining, vec![2, 4]);
+        assert_eq!(Ledger::client_invoice_count(client), 2);
+        assert_eq!(Ledger::get_archived_invoice_hash(&client, 3), Some(hash_of_3));
+        assert_eq!(Ledger::get_archived_invoice_hash(&client, 2), None);
+
+        // Indexes stay consistent
+        let by_creator: Vec<u64> =
+            Ledger::get_creator_invoices(&creator).iter().map(|i| i.id).collect();
+        assert_eq!(by_creator, vec![2, 4]);
+        let (page, next) = Ledger::get_client_invoices_paged(&client, None, 1).unwrap();
+        assert_eq!(page[0].id, 2);
+        assert_eq!(next, Some(cursor(4)));
+
+        // Billing the client works again
+        create_invoices(client, 3);
+        assert_eq!(Ledger::client_invoice_count(client), 5);
+        let (page, _) = Ledger::get_client_invoices_paged(&client, Some(&cursor(5)), 10).unwrap();
+        assert_eq!(page.iter().map(|i| i.id).collect::<Vec<_>>(), vec![5, 6, 7]);
+    });
+}
+
+#[test]
+fn archive_invoices_respects_bound_and_origin() {
+    new_test_ext().execute_with(|| {
+        let client = 2u64;
+
+        // Invoice 0 from creator 1, invoice 1 from creator 3, both cancelled
+        assert_ok!(Ledger::create_invoice(
+            RuntimeOrigin::signed(1),
+            client,
+            100u128,
+            0,
+            0,
+            b"A".to_vec(),
+            None,
+            None,
+            vec![],
+            None
+        ));
+        assert_ok!(Ledger::create_invoice(
+            RuntimeOrigin::signed(3),
+            client,
+            100u128,
+            0,
+            0,
+            b"B".to_vec(),
+            None,
+            None,
+            vec![],
+            None
+        ));
+        assert_ok!(Ledger::cancel_invoice(RuntimeOrigin::signed(1), client, 0));
+        assert_ok!(Ledger::cancel_invoice(RuntimeOrigin::signed(3), client, 1));
+
+        // up_to_id is exclusive
+        assert_noop!(
+            Ledger::archive_invoices(RuntimeOrigin::signed(1), client, 0),
+            Error::<Test>::NothingToArchive
+        );
+
+        // A creator only archives its own invoices
+        assert_ok!(Ledger::archive_invoices(RuntimeOrigin::signed(1), client, 2));
+        System::assert_has_event(Event::InvoicesArchived { client, count: 1 }.into());
+        assert!(Ledger::get_invoice(&client, 0).is_none());
+        assert!(Ledger::get_invoice(&client, 1).is_some());
+        assert_noop!(
+            Ledger::archive_invoices(RuntimeOrigin::signed(1), client, 2),
+            Error::<Test>::NothingToArchive
+        );
+
+        // Root archives anything eligible
+        assert_ok!(Ledger::archive_invoices(RuntimeOrigin::root(), client, 2));
+        assert!(Ledger::get_client_invoices(&client).is_empty());
+    });
+}
+
+#[test]
+fn archive_invoices_never_prunes_unpaid() {
+    new_test_ext().execute_with(|| {
+        create_invoices(2, 2);
+        assert_ok!(Ledger::record_payment(RuntimeOrigin::signed(2), 2, 0, 50));
+
+        assert_noop!(
+            Ledger::archive_invoices(RuntimeOrigin::root(), 2, 10),
+            Error::<Test>::NothingToArchive
+        );
+        assert_eq!(Ledger::client_invoice_count(2), 2);
+    });
+}
+
+#[test]
+fn update_invoice_metadata_rehashes() {
+    new_test_ext().execute_with(|| {
+        let creator = 1u64;
+        let client = 2u64;
+
+        assert_ok!(Ledger::create_invoice(
+            RuntimeOrigin::signed(creator),
+            client,
+            1000u128,
+            0,
+            0,
+            b"INV-001|Acme Crop".to_vec(),
+            None,
+            None,
+            vec![],
+            None
+        ));
+        let old_hash = Ledger::get_invoice(&client, 0).unwrap().invoice_hash;
+
+        assert_ok!(Ledger::update_invoice_metadata(
+            RuntimeOrigin::signed(creator),
+            client,
+            0,
+            b"INV-001|Acme Corp".to_vec()
+        ));
+
+        let invoice = Ledger::get_invoice(&client, 0).unwrap();
+        let new_hash = invoice.invoice_hash;
+        assert_ne!(new_hash, old_hash);
+        assert_eq!(new_hash, invoice.calculate_hash());
+        assert_eq!(invoice.metadata.to_vec(), b"INV-001|Acme Corp".to_vec());
+        assert!(Ledger::verify_invoice_hash(&client, 0));
+        System::assert_has_event(
+            Event::InvoiceRehashed { invoice_id: 0, old_hash, new_hash }.into(),
+        );
+
+        // New hash resolves, old hash no longer does
+        assert_eq!(Ledger::get_invoice_by_hash(new_hash), Some(0));
+        assert_eq!(Ledger::get_invoice_by_hash(old_hash), None);
+    });
+}
+
+#[test]
+fn update_invoice_metadata_restrictions() {
+    use sp_core::Pair;
+
+    new_test_ext().execute_with(|| {
+        let creator = 1u64;
+        let client = 2u64;
+        create_invoices(client, 2);
+
+        assert_noop!(
+            Ledger::update_invoice_metadata(RuntimeOrigin::signed(3), client, 0, b"x".to_vec()),
+            Error::<Test>::NotInvoiceCreator
+        );
+        assert_noop!(
+            Ledger::update_invoice_metadata(
+                RuntimeOrigin::signed(creator),
+                client,
+                9,
+                b"x".to_vec()
+            ),
+            Error::<Test>::InvoiceNotFound
+        );
+
+        // Settled and cancelled invoices are immutable
+        assert_ok!(Ledger::pay_invoice(RuntimeOrigin::signed(client), creator, 0));
+        assert_noop!(
+            Ledger::update_invoice_metadata(
+                RuntimeOrigin::signed(creator),
+                client,
+                0,
+                b"x".to_vec()
+            ),
+            Error::<Test>::InvoiceAlreadyPaid
+        );
+        assert_ok!(Ledger::cancel_invoice(RuntimeOrigin::signed(creator), client, 1));
+        assert_noop!(
+            Ledger::update_invoice_metadata(
+                RuntimeOrigin::signed(creator),
+                client,
+                1,
+                b"x".to_vec()
+            ),
+            Error::<Test>::InvoiceCancelled
+        );
+
+        // Operator-signed invoices keep the signed metadata
+        let operator = operator_pair(7);
+        assert_ok!(Ledger::add_operator_key(RuntimeOrigin::root(), operator.public()));
+        let sig = operator_sign(&operator, client, 500, b"INV-S");
+        assert_ok!(Ledger::create_signed_invoice(
+            RuntimeOrigin::signed(creator),
+            client,
+            500u128,
+            b"INV-S".to_vec(),
+            sig
+        ));
+        assert_noop!(
+            Ledger::update_invoice_metadata(
+                RuntimeOrigin::signed(creator),
+                client,
+                2,
+                b"x".to_vec()
+            ),
+            Error::<Test>::InvoiceOperatorSigned
+        );
+    });
+}
+
+#[test]
+fn invoice_hash_preimage_is_length_prefixed() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Ledger::create_invoice(
+            RuntimeOrigin::signed(1),
+            2,
+            1000u128,
+            0,
+            0,
+            b"INV".to_vec(),
+            None,
+            None,
+            vec![],
+            None
+        ));
+        let invoice = Ledger::get_invoice(&2, 0).unwrap();
+
+        // id, client, amount, metadata, timestamp, creator; each with a u32 LE length prefix
+        let mut expected = Vec::new();
+        expected.extend_from_slice(&8u32.to_le_bytes());
+        expected.extend_from_slice(&0u64.to_le_bytes());
+        expected.extend_from_slice(&8u32.to_le_bytes());
+        expected.extend_from_slice(&2u64.to_le_bytes());
+        expected.extend_from_slice(&16u32.to_le_bytes());
+        expected.extend_from_slice(&1000u128.to_le_bytes());
+        expected.extend_from_slice(&3u32.to_le_bytes());
+        expected.extend_from_slice(b"INV");
+        expected.extend_from_slice(&8u32.to_le_bytes());
+        expected.extend_from_slice(&1u64.to_le_bytes());
+        expected.extend_from_slice(&8u32.to_le_bytes());
+        expected.extend_from_slice(&1u64.to_le_bytes());
+
+        assert_eq!(invoice.invoice_hash, sp_io::hashing::sha2_256(&expected));
+    });
+}
+
+#[test]
+fn hash_algorithms_match_documented_digests() {
+    use crate::HashAlgorithm;
+    use sp_core::hexdisplay::HexDisplay;
+
+    new_test_ext().execute_with(|| {
+        // Invoice 0 from 1 to client 2 over 1000 with metadata "INV" at block 1, as in the README
+        assert_ok!(Ledger::create_invoice(
+            RuntimeOrigin::signed(1),
+            2,
+            1000u128,
+            0,
+            0,
+            b"INV".to_vec(),
+            None,
+            None,
+            vec![],
+            None
+        ));
+        let mut invoice = Ledger::get_invoice(&2, 0).unwrap();
+        assert_eq!(invoice.hash_algorithm, HashAlgorithm::Sha256);
+
+        let digest = |invoice: &crate::Invoice<Test>| {
+            HexDisplay::from(&invoice.calculate_hash()).to_string()
+        };
+        assert_eq!(
+            digest(&invoice),
+            "e50ff48fbf9c1ee6cd0d3f403510f9b5397adf7b5af8a129516fca52344f2299"
+        );
+        invoice.hash_algorithm = HashAlgorithm::Blake2b256;
+        assert_eq!(
+            digest(&invoice),
+            "7b90e3e0ca026cf10721c535b500f5fe4a4f21455430fffd09974cbf501e46f9"
+        );
+    });
+}
+
+#[test]
+fn switching_hash_algorithm_keeps_existing_invoices_verifiable() {
+    use crate::HashAlgorithm;
+
+    new_test_ext().execute_with(|| {
+        create_invoices(2, 1);
+
+        // A runtime upgrade switches new invoices to BLAKE2b
+        InvoiceHashAlgorithm::set(HashAlgorithm::Blake2b256);
+        create_invoices(2, 1);
+
+        let old = Ledger::get_invoice(&2, 0).unwrap();
+        let new = Ledger::get_invoice(&2, 1).unwrap();
+        assert_eq!(old.hash_algorithm, HashAlgorithm::Sha256);
+        assert_eq!(new.hash_algorithm, HashAlgorithm::Blake2b256);
+        assert!(Ledger::verify_invoice_hash(&2, 0));
+        assert!(Ledger::verify_invoice_hash(&2, 1));
+        assert_eq!(Ledger::get_invoice_by_hash(new.invoice_hash), Some(1));
+
+        // Re-hashing on a metadata update stays with the invoice's own algorithm
+        assert_ok!(Ledger::update_invoice_metadata(
+            RuntimeOrigin::signed(1),
+            2,
+            0,
+            b"INV-0 amended".to_vec()
+        ));
+        let old = Ledger::get_invoice(&2, 0).unwrap();
+        assert_eq!(old.hash_algorithm, HashAlgorithm::Sha256);
+        assert!(Ledger::verify_invoice_hash(&2, 0));
+    });
+}
+
+#[test]
+fn length_prefixed_separates_adjacent_fields() {
+    // Shifting bytes between neighbouring fields keeps the plain concatenation...
+    let splits: [[&[u8]; 2]; 3] = [[b"AB", b"C"], [b"A", b"BC"], [b"", b"ABC"]];
+    for [left, right] in splits {
+        assert_eq!([left, right].concat(), b"ABC".to_vec());
+    }
+
+    // ...but never the length-prefixed encoding
+    for (i, a) in splits.iter().enumerate() {
+        for b in &splits[i + 1..] {
+            assert_ne!(crate::length_prefixed(a), crate::length_prefixed(b));
+        }
+    }
+
+    // Metadata that embeds the next field's bytes still hashes differently
+    let a = invoice_from_fresh_chain(b"INV-1".to_vec());
+    let mut shifted = b"INV-1".to_vec();
+    shifted.extend_from_slice(&8u32.to_le_bytes());
+    shifted.extend_from_slice(&1u64.to_le_bytes());
+    let b = invoice_from_fresh_chain(shifted);
+    assert_ne!(a.invoice_hash, b.invoice_hash);
+}
+
+#[test]
+fn create_invoice_rejects_duplicate_hash() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Ledger::create_invoice(
+            RuntimeOrigin::signed(1),
+            2,
+            1000u128,
+            0,
+            0,
+            b"INV-001".to_vec(),
+            None,
+            None,
+            vec![],
+            None
+        ));
+
+        // Replaying the same ID, client, amount, metadata and block yields the same hash
+        crate::InvoiceCount::<Test>::put(0);
+        assert_noop!(
+            Ledger::create_invoice(
+                RuntimeOrigin::signed(1),
+                2,
+                1000u128,
+                0,
+                0,
+                b"INV-001".to_vec(),
+                None,
+                None,
+                vec![],
+                None
+            ),
+            Error::<Test>::DuplicateInvoiceHash
+        );
+
+        // The original mapping is untouched
+        let invoice = Ledger::get_invoice(&2, 0).unwrap();
+        assert_eq!(Ledger::get_invoice_by_hash(invoice.invoice_hash), Some(0));
+    });
+}
+
+#[test]
+fn migration_to_v2_rehashes_invoices() {
+    use frame_support::traits::{GetStorageVersion, StorageVersion};
+
+    new_test_ext().execute_with(|| {
+        create_invoices(2, 2);
+
+        // Simulate invoices hashed with the v1 preimage
+        for id in 0..2u64 {
+            let legacy = [id as u8 + 7; 32];
+            crate::Invoices::<Test>::mutate(2, id, |invoice| {
+                let invoice = invoice.as_mut().unwrap();
+                crate::InvoiceByHash::<Test>::remove(invoice.invoice_hash);
+                invoice.invoice_hash = legacy;
+            });
+            crate::InvoiceByHash::<Test>::insert(legacy, id);
+            assert!(!Ledger::verify_invoice_hash(&2, id));
+        }
+        StorageVersion::new(1).put::<Ledger>();
+
+        Ledger::on_runtime_upgrade();
+
+        assert_eq!(Ledger::on_chain_storage_version(), 13);
+        for id in 0..2u64 {
+            let invoice = Ledger::get_invoice(&2, id).unwrap();
+            assert!(Ledger::verify_invoice_hash(&2, id));
+            assert_eq!(Ledger::get_invoice_by_hash(invoice.invoice_hash), Some(id));
+            assert_eq!(Ledger::get_invoice_by_hash([id as u8 + 7; 32]), None);
+        }
+    });
+}
+
+#[test]
+fn genesis_seeds_invoices() {
+    let genesis = crate::GenesisConfig::<Test> {
+        invoices: vec![
+            (2, 1000, b"INV-2025-001".to_vec(), 1),
+            (3, 500, b"INV-2025-002\r\nNet 30".to_vec(), 1),
+            (2, 250, b"INV-2025-003".to_vec(), 4),
+        ],
+        ..Default::default()
+    };
+
+    new_test_ext_with(genesis).execute_with(|| {
+        assert_eq!(Ledger::invoice_count(), 3);
+        assert_eq!(Ledger::client_invoice_count(2), 2);
+        assert_eq!(Ledger::client_invoice_count(3), 1);
+
+        let first = Ledger::get_invoice(&2, 0).unwrap();
+        assert_eq!(first.amount, 1000);
+        assert_eq!(first.created_by, 1);
+        assert_eq!(first.timestamp, 0);
+        assert_eq!(first.status, InvoiceStatus::Pending);
+
+        // Genesis metadata is normalized like any other
+        let second = Ledger::get_invoice(&3, 1).unwrap();
+        assert_eq!(second.metadata.to_vec(), b"INV-2025-002\nNet 30".to_vec());
+
+        for (client, id) in [(2, 0), (3, 1), (2, 2)] {
+            let invoice = Ledger::get_invoice(&client, id).unwrap();
+            assert!(Ledger::verify_invoice_hash(&client, id));
+            assert_eq!(Ledger::get_invoice_by_hash(invoice.invoice_hash), Some(id));
+        }
+        assert_eq!(Ledger::get_creator_invoices(&4).len(), 1);
+
+        // New invoices continue after the seeded IDs
+        assert_ok!(Ledger::create_invoice(
+            RuntimeOrigin::signed(1),
+            2,
+            100u128,
+            0,
+            0,
+            b"INV-2025-004".to_vec(),
+            None,
+            None,
+            vec![],
+            None
+        ));
+        assert!(Ledger::get_invoice(&2, 3).is_some());
+    });
+}
+
+#[test]
+#[should_panic(expected = "MetadataTooLong")]
+fn genesis_rejects_oversized_metadata() {
+    let genesis = crate::GenesisConfig::<Test> {
+        invoices: vec![(2, 1000, vec![b'x'; 10_000], 1)],
+        ..Default::default()
+    };
+    new_test_ext_with(genesis);
+}
+
+#[test]
+fn multiple_partial_credit_notes_reduce_outstanding() {
+    new_test_ext().execute_with(|| {
+        let creator = 1u64;
+        let client = 2u64;
+
+        assert_ok!(Ledger::create_invoice(
+            RuntimeOrigin::signed(creator),
+            client,
+            1000u128,
+            0,
+            0,
+            b"INV-1".to_vec(),
+            None,
+            None,
+            vec![],
+            None
+        ));
+        let invoice_hash = Ledger::get_invoice(&client, 0).unwrap().invoice_hash;
+
+        assert_ok!(Ledger::create_credit_note(
+            RuntimeOrigin::signed(creator),
+            client,
+            0,
+            200,
+            b"CN-1|damaged goods".to_vec()
+        ));
+        System::assert_last_event(
+            Event::CreditNoteCreated { credit_note_id: 0, invoice_id: 0, amount: 200 }.into(),
+        );
+        assert_ok!(Ledger::create_credit_note(
+            RuntimeOrigin::signed(creator),
+            client,
+            0,
+            300,
+            b"CN-2|volume discount".to_vec()
+        ));
+        assert_eq!(Ledger::outstanding_amount(&client, 0), Some(500));
+        assert_eq!(Ledger::invoice_credits(client, 0), 300 + 200);
+        assert_eq!(Ledger::credit_note_count(), 2);
+
+        // Credit notes are stored with their own hashes; the invoice itself is unchanged
+        let note = Ledger::credit_notes(1).unwrap();
+        assert_eq!((note.invoice_id, note.client, note.amount), (0, client, 300));
+        assert_eq!(note.credit_note_hash, note.calculate_hash());
+        assert_eq!(Ledger::credit_note_by_hash(note.credit_note_hash), Some(1));
+        assert_ne!(Ledger::credit_notes(0).unwrap().credit_note_hash, note.credit_note_hash);
+        let invoice = Ledger::get_invoice(&client, 0).unwrap();
+        assert_eq!((invoice.amount, invoice.invoice_hash), (1000, invoice_hash));
+        assert_eq!(invoice.status, InvoiceStatus::Pending);
+
+        // Payments only cover what is left after credits
+        assert_ok!(Ledger::record_payment(RuntimeOrigin::signed(client), client, 0, 100));
+        System::assert_has_event(
+            Event::PartialPaymentRecorded { invoice_id: 0, amount: 100, remaining: 400 }.into(),
+        );
+        assert_ok!(Ledger::pay_invoice(RuntimeOrigin::signed(client), creator, 0));
+        System::assert_has_event(
+            Event::InvoicePaid { invoice_id: 0, payer: client, payee: creator, amount: 500 }
+                .into(),
+        );
+        assert_eq!(Balances::free_balance(client), INITIAL_BALANCE - 500);
+        assert_eq!(Ledger::outstanding_amount(&client, 0), Some(0));
+    });
+}
+
+#[test]
+fn credit_note_cannot_exceed_outstanding() {
+    new_test_ext().execute_with(|| {
+        let creator = 1u64;
+        let client = 2u64;
+
+        assert_ok!(Ledger::create_invoice(
+            RuntimeOrigin::signed(creator),
+            client,
+            1000u128,
+            0,
+            0,
+            b"INV-1".to_vec(),
+            None,
+            None,
+            vec![],
+            None
+        ));
+        assert_ok!(Ledger::record_payment(RuntimeOrigin::signed(client), client, 0, 600));
+        assert_ok!(Ledger::create_credit_note(
+            RuntimeOrigin::signed(creator),
+            client,
+            0,
+            300,
+            b"CN-1".to_vec()
+        ));
+
+        // Only 100 is left after the installment and the first credit
+        assert_noop!(
+            Ledger::create_credit_note(
+                RuntimeOrigin::signed(creator),
+                client,
+                0,
+                101,
+                b"CN-2".to_vec()
+            ),
+            Error::<Test>::CreditExceedsOutstanding
+        );
+        assert_noop!(
+            Ledger::record_payment(RuntimeOrigin::signed(client), client, 0, 101),
+            Error::<Test>::PaymentExceedsAmount
+        );
+        assert_noop!(
+            Ledger::create_credit_note(RuntimeOrigin::signed(creator), client, 0, 0, vec![]),
+            Error::<Test>::ZeroCredit
+        );
+        assert_noop!(
+            Ledger::create_credit_note(RuntimeOrigin::signed(3), client, 0, 50, vec![]),
+            Error::<Test>::NotInvoiceCreator
+        );
+        assert_noop!(
+            Ledger::create_credit_note(RuntimeOrigin::signed(creator), client, 1, 50, vec![]),
+            Error::<Test>::InvoiceNotFound
+        );
+
+        // Crediting the rest settles the invoice without a transfer
+        assert_ok!(Ledger::create_credit_note(
+            RuntimeOrigin::signed(creator),
+            client,
+            0,
+            100,
+            b"CN-2".to_vec()
+        ));
+        assert_eq!(Ledger::get_invoice(&client, 0).unwrap().status, InvoiceStatus::Paid);
+        assert_eq!(Balances::free_balance(client), INITIAL_BALANCE - 600);
+        assert_noop!(
+            Ledger::create_credit_note(RuntimeOrigin::signed(creator), client, 0, 1, vec![]),
+            Error::<Test>::InvoiceAlreadyPaid
+        );
+    });
+}
+
+#[test]
+fn ledger_errors_map_to_skip_reasons() {
+    assert_eq!(SkipReason::from(Error::<Test>::MetadataTooLong), SkipReason::TooLong);
+    assert_eq!(SkipReason::from(Error::<Test>::DuplicateInvoiceHash), SkipReason::Duplicate);
+    assert_eq!(SkipReason::from(Error::<Test>::InvoiceNotFound), SkipReason::NotFound);
+    assert_eq!(SkipReason::from(Error::<Test>::NotInvoiceCreator), SkipReason::Unauthorized);
+    assert_eq!(SkipReason::from(Error::<Test>::TooManyInvoices), SkipReason::BoundExceeded);
+    // Unclassified errors keep their variant index (`InvalidUtf8` is the 12th)
+    assert_eq!(SkipReason::from(Error::<Test>::InvalidUtf8), SkipReason::Other(11));
+
+    new_test_ext().execute_with(|| {
+        // Errors raised by a call resolve through the module error
+        let error = Ledger::create_invoice(
+            RuntimeOrigin::signed(1),
+            2,
+            100u128,
+            0,
+            0,
+            vec![0u8; 2048],
+            None,
+            None,
+            vec![],
+            None,
+        )
+        .unwrap_err()
+        .error;
+        assert_eq!(Ledger::skip_reason(error), SkipReason::TooLong);
+
+        assert_eq!(
+            Ledger::skip_reason(sp_runtime::DispatchError::BadOrigin),
+            SkipReason::Unauthorized
+        );
+        assert_eq!(
+            Ledger::skip_reason(TokenError::FundsUnavailable.into()),
+            SkipReason::Other(SkipReason::FOREIGN_ERROR)
+        );
+    });
+}
+
+#[test]
+fn verify_invoice_records_match_and_mismatch() {
+    new_test_ext().execute_with(|| {
+        let client = 2u64;
+        let auditor = 3u64;
+
+        assert_ok!(Ledger::create_invoice(
+            RuntimeOrigin::signed(1),
+            client,
+            1000u128,
+            0,
+            0,
+            b"INV-1".to_vec(),
+            None,
+            None,
+            vec![],
+            None
+        ));
+        let invoice_hash = Ledger::get_invoice(&client, 0).unwrap().invoice_hash;
+
+        assert_ok!(Ledger::verify_invoice(RuntimeOrigin::signed(auditor), client, 0, invoice_hash));
+        System::assert_last_event(
+            Event::InvoiceVerified { invoice_id: 0, verifier: auditor, matched: true }.into(),
+        );
+
+        // A mismatch is recorded, not rejected
+        assert_ok!(Ledger::verify_invoice(RuntimeOrigin::signed(auditor), client, 0, [0u8; 32]));
+        System::assert_last_event(
+            Event::InvoiceVerified { invoice_id: 0, verifier: auditor, matched: false }.into(),
+        );
+        assert!(!System::events().iter().any(|record| matches!(
+            record.event,
+            RuntimeEvent::Ledger(Event::InvoiceHashStale { .. })
+        )));
+    });
+}
+
+#[test]
+fn verify_invoice_flags_stale_stored_hash() {
+    new_test_ext().execute_with(|| {
+        let client = 2u64;
+
+        assert_ok!(Ledger::create_invoice(
+            RuntimeOrigin::signed(1),
+            client,
+            1000u128,
+            0,
+            0,
+            b"INV-1".to_vec(),
+            None,
+            None,
+            vec![],
+            None
+        ));
+        let stored_hash = Ledger::get_invoice(&client, 0).unwrap().invoice_hash;
+
+        // Tamper with a hashed field behind the pallet's back
+        crate::Invoices::<Test>::mutate(client, 0, |invoice| {
+            invoice.as_mut().unwrap().amount = 1;
+        });
+        let computed_hash = Ledger::get_invoice(&client, 0).unwrap().calculate_hash();
+
+        assert_ok!(Ledger::verify_invoice(RuntimeOrigin::signed(3), client, 0, stored_hash));
+        System::assert_has_event(
+            Event::InvoiceHashStale { invoice_id: 0, stored_hash, computed_hash }.into(),
+        );
+        System::assert_last_event(
+            Event::InvoiceVerified { invoice_id: 0, verifier: 3, matched: true }.into(),
+        );
+    });
+}
+
+#[test]
+fn verify_invoice_requires_existing_invoice() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Ledger::verify_invoice(RuntimeOrigin::signed(3), 2, 0, [0u8; 32]),
+            Error::<Test>::InvoiceNotFound
+        );
+    });
+}
+
+#[test]
+fn external_ref_is_unique_and_resolves_invoice() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Ledger::create_invoice_with_ref(
+            RuntimeOrigin::signed(1),
+            2,
+            1000u128,
+            0,
+            0,
+            b"Client XYZ|Net 30".to_vec(),
+            None,
+            None,
+            Vec::new(),
+            None,
+            b"INV-2025-001".to_vec()
+        ));
+
+        let invoice = Ledger::get_invoice_by_external_ref(b"INV-2025-001").unwrap();
+        assert_eq!((invoice.id, invoice.client), (0, 2));
+        assert_eq!(invoice.external_ref.to_vec(), b"INV-2025-001".to_vec());
+        assert_eq!(Ledger::get_invoice_by_external_ref(b"INV-2025-002"), None);
+
+        // References are unique across clients
+        assert_noop!(
+            Ledger::create_invoice_with_ref(
+                RuntimeOrigin::signed(1),
+                3,
+                500u128,
+                0,
+                0,
+                b"Other".to_vec(),
+                None,
+                None,
+                Vec::new(),
+                None,
+                b"INV-2025-001".to_vec()
+            ),
+            Error::<Test>::DuplicateExternalRef
+        );
+        assert_noop!(
+            Ledger::create_invoice_with_ref(
+                RuntimeOrigin::signed(1),
+                3,
+                500u128,
+                0,
+                0,
+                b"Other".to_vec(),
+                None,
+                None,
+                Vec::new(),
+                None,
+                vec![b'X'; 33]
+            ),
+            Error::<Test>::ExternalRefTooLong
+        );
+
+        // Invoices without a reference are unaffected and not indexed
+        assert_ok!(Ledger::create_invoice(
+            RuntimeOrigin::signed(1),
+            3,
+            500u128,
+            0,
+            0,
+            b"No ref".to_vec(),
+            None,
+            None,
+            vec![],
+            None
+        ));
+        assert_ok!(Ledger::create_invoice_with_ref(
+            RuntimeOrigin::signed(1),
+            3,
+            600u128,
+            0,
+            0,
+            b"Empty ref".to_vec(),
+            None,
+            None,
+            Vec::new(),
+            None,
+            Vec::new()
+        ));
+        assert!(Ledger::get_invoice(&3, 1).unwrap().external_ref.is_empty());
+        assert_eq!(Ledger::get_invoice_by_external_ref(b""), None);
+    });
+}
+
+#[test]
+fn create_invoice_with_ref_takes_every_create_invoice_parameter() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Ledger::create_invoice_with_ref(
+            RuntimeOrigin::signed(1),
+            2,
+            1000u128,
+            100,
+            50,
+            b"Client XYZ".to_vec(),
+            None,
+            Some(20),
+            vec![line_item(b"Design", 2, 300), line_item(b"Build", 1, 400)],
+            None,
+            b"INV-2025-001".to_vec()
+        ));
+
+        let invoice = Ledger::get_invoice_by_external_ref(b"INV-2025-001").unwrap();
+        assert_eq!((invoice.tax, invoice.discount), (100, 50));
+        assert_eq!(invoice.line_items.len(), 2);
+        assert_eq!(Ledger::expiring_invoices(20).to_vec(), vec![(2, 0)]);
+
+        // Line items are checked as for `create_invoice`
+        assert_noop!(
+            Ledger::create_invoice_with_ref(
+                RuntimeOrigin::signed(1),
+                2,
+                1000u128,
+                0,
+                0,
+                b"Client XYZ".to_vec(),
+                None,
+                None,
+                vec![line_item(b"Design", 2, 300)],
+                None,
+                b"INV-2025-002".to_vec()
+            ),
+            Error::<Test>::LineItemsMismatch
+        );
+    });
+}
+
+#[test]
+fn migration_to_v3_adds_empty_external_ref() {
+    use crate::migrations::v3::{Invoices as OldInvoices, OldInvoice};
+    use frame_support::traits::{GetStorageVersion, StorageVersion};
+
+    new_test_ext().execute_with(|| {
+        StorageVersion::new(2).put::<Ledger>();
+        OldInvoices::<Test>::insert(
+            2,
+            0,
+            OldInvoice {
+                id: 0,
+                client: 2,
+                amount: 100,
+                metadata: b"INV".to_vec().try_into().unwrap(),
+                timestamp: 1,
+                invoice_hash: [9u8; 32],
+                created_by: 1,
+                status: InvoiceStatus::Overdue,
+                due_block: Some(5),
+                paid_amount: 40,
+            },
+        );
+
+        Ledger::on_runtime_upgrade();
+
+        assert_eq!(Ledger::on_chain_storage_version(), 13);
+        let invoice = Ledger::get_invoice(&2, 0).unwrap();
+        assert!(invoice.external_ref.is_empty());
+        // Re-hashed by the version 7 migration
+        assert_eq!(invoice.invoice_hash, invoice.calculate_hash());
+        assert_eq!(invoice.status, InvoiceStatus::Overdue);
+        assert_eq!(invoice.paid_amount, 40);
+    });
+}
+
+/// Create invoice 0 from creator 1 to client 2 and request its cancellation at block 1
+fn request_cancellation_of_first_invoice() {
+    assert_ok!(Ledger::create_invoice(
+        RuntimeOrigin::signed(1),
+        2,
+        1000u128,
+        0,
+        0,
+        b"Cancel me".to_vec(),
+        None,
+        None,
+        vec![],
+        None
+    ));
+    assert_ok!(Ledger::request_cancellation(RuntimeOrigin::signed(1), 2, 0, [7u8; 32]));
+}
+
+#[test]
+fn cancellation_by_consent() {
+    new_test_ext().execute_with(|| {
+        request_cancellation_of_first_invoice();
+        System::assert_last_event(
+            Event::CancellationRequested {
+                invoice_id: 0,
+                client: 2,
+                reason_hash: [7u8; 32],
+                deadline: 11,
+            }
+            .into(),
+        );
+
+        // Only the creator requests, once
+        assert_noop!(
+            Ledger::request_cancellation(RuntimeOrigin::signed(3), 2, 0, [7u8; 32]),
+            Error::<Test>::NotInvoiceCreator
+        );
+        assert_noop!(
+            Ledger::request_cancellation(RuntimeOrigin::signed(1), 2, 0, [8u8; 32]),
+            Error::<Test>::CancellationAlreadyRequested
+        );
+
+        // Only the client of the invoice can answer
+        assert_noop!(
+            Ledger::approve_cancellation(RuntimeOrigin::signed(1), 0),
+            Error::<Test>::NoPendingCancellation
+        );
+
+        assert_ok!(Ledger::approve_cancellation(RuntimeOrigin::signed(2), 0));
+        assert_eq!(Ledger::get_invoice(&2, 0).unwrap().status, InvoiceStatus::Cancelled);
+        assert!(Ledger::pending_cancellations(2, 0).is_none());
+        System::assert_has_event(Event::CancellationApproved { invoice_id: 0, client: 2 }.into());
+        System::assert_last_event(Event::InvoiceCancelled { invoice_id: 0, client: 2 }.into());
+
+        assert_noop!(
+            Ledger::request_cancellation(RuntimeOrigin::signed(1), 2, 0, [7u8; 32]),
+            Error::<Test>::InvoiceCancelled
+        );
+    });
+}
+
+#[test]
+fn cancellation_finalizes_after_timeout() {
+    new_test_ext().execute_with(|| {
+        request_cancellation_of_first_invoice();
+
+        // The client can still answer until the deadline
+        System::set_block_number(10);
+        assert_noop!(
+            Ledger::finalize_cancellation(RuntimeOrigin::signed(3), 2, 0),
+            Error::<Test>::CancellationTimeoutNotReached
+        );
+
+        // Anyone can finalize once it is reached
+        System::set_block_number(11);
+        assert_ok!(Ledger::finalize_cancellation(RuntimeOrigin::signed(3), 2, 0));
+        assert_eq!(Ledger::get_invoice(&2, 0).unwrap().status, InvoiceStatus::Cancelled);
+        System::assert_has_event(Event::CancellationTimedOut { invoice_id: 0, client: 2 }.into());
+        System::assert_last_event(Event::InvoiceCancelled { invoice_id: 0, client: 2 }.into());
+
+        assert_noop!(
+            Ledger::finalize_cancellation(RuntimeOrigin::signed(3), 2, 0),
+            Error::<Test>::NoPendingCancellation
+        );
+    });
+}
+
+#[test]
+fn rejected_cancellation_keeps_invoice_and_blocks_requests() {
+    new_test_ext().execute_with(|| {
+        request_cancellation_of_first_invoice();
+
+        assert_ok!(Ledger::reject_cancellation(RuntimeOrigin::signed(2), 0));
+        System::assert_last_event(
+            Event::CancellationRejected { invoice_id: 0, client: 2, blocked_until: 21 }.into(),
+        );
+        assert_eq!(Ledger::get_invoice(&2, 0).unwrap().status, InvoiceStatus::Pending);
+
+        // The rejected request can no longer be finalized after its deadline
+        System::set_block_number(11);
+        assert_noop!(
+            Ledger::finalize_cancellation(RuntimeOrigin::signed(1), 2, 0),
+            Error::<Test>::NoPendingCancellation
+        );
+
+        // New requests wait for the cooldown
+        System::set_block_number(20);
+        assert_noop!(
+            Ledger::request_cancellation(RuntimeOrigin::signed(1), 2, 0, [8u8; 32]),
+            Error::<Test>::CancellationCooldownActive
+        );
+        System::set_block_number(21);
+        assert_ok!(Ledger::request_cancellation(RuntimeOrigin::signed(1), 2, 0, [8u8; 32]));
+        assert!(Ledger::cancellation_blocked_until(2, 0).is_none());
+    });
+}
+
+#[test]
+fn settled_invoice_drops_pending_cancellation() {
+    new_test_ext().execute_with(|| {
+        request_cancellation_of_first_invoice();
+
+        assert_ok!(Ledger::pay_invoice(RuntimeOrigin::signed(2), 1, 0));
+        assert!(Ledger::pending_cancellations(2, 0).is_none());
+
+        System::set_block_number(11);
+        assert_noop!(
+            Ledger::finalize_cancellation(RuntimeOrigin::signed(1), 2, 0),
+            Error::<Test>::NoPendingCancellation
+        );
+        assert_eq!(Ledger::get_invoice(&2, 0).unwrap().status, InvoiceStatus::Paid);
+    });
+}
+
+#[test]
+fn disputed_invoice_blocks_payment_and_cancellation() {
+    new_test_ext().execute_with(|| {
+        request_cancellation_of_first_invoice();
+
+        // Only the client of an invoice from `creator` can dispute it
+        assert_noop!(
+            Ledger::dispute_invoice(RuntimeOrigin::signed(2), 3, 0, b"Wrong".to_vec()),
+            Error::<Test>::CreatorMismatch
+        );
+        assert_noop!(
+            Ledger::dispute_invoice(RuntimeOrigin::signed(2), 1, 0, vec![b'x'; 65]),
+            Error::<Test>::DisputeReasonTooLong
+        );
+
+        assert_ok!(Ledger::dispute_invoice(
+            RuntimeOrigin::signed(2),
+            1,
+            0,
+            b"Goods not delivered".to_vec()
+        ));
+        System::assert_last_event(
+            Event::InvoiceDisputed { invoice_id: 0, client: 2, creator: 1 }.into(),
+        );
+        assert_eq!(
+            Ledger::invoice_disputes(2, 0).unwrap().reason.to_vec(),
+            b"Goods not delivered".to_vec()
+        );
+        assert_eq!(DisputeNotifications::get(), vec![(0, true)]);
+
+        // The pending cancellation request is dropped
+        assert!(Ledger::pending_cancellations(2, 0).is_none());
+
+        assert_noop!(
+            Ledger::pay_invoice(RuntimeOrigin::signed(2), 1, 0),
+            Error::<Test>::InvoiceDisputed
+        );
+        assert_noop!(
+            Ledger::record_payment(RuntimeOrigin::signed(2), 2, 0, 100),
+            Error::<Test>::InvoiceDisputed
+        );
+        assert_noop!(
+            Ledger::cancel_invoice(RuntimeOrigin::signed(1), 2, 0),
+            Error::<Test>::InvoiceDisputed
+        );
+        assert_noop!(
+            Ledger::request_cancellation(RuntimeOrigin::signed(1), 2, 0, [7u8; 32]),
+            Error::<Test>::InvoiceDisputed
+        );
+        assert_noop!(
+            Ledger::dispute_invoice(RuntimeOrigin::signed(2), 1, 0, b"Again".to_vec()),
+            Error::<Test>::InvoiceDisputed
+        );
+    });
+}
+
+#[test]
+fn resolved_dispute_reinstates_or_cancels() {
+    use crate::DisputeResolution;
+
+    new_test_ext().execute_with(|| {
+        create_invoices(2, 2);
+        for invoice_id in 0..2 {
+            assert_ok!(Ledger::dispute_invoice(
+                RuntimeOrigin::signed(2),
+                1,
+                invoice_id,
+                b"Overcharged".to_vec()
+            ));
+        }
+
+        assert_noop!(
+            Ledger::resolve_dispute(RuntimeOrigin::signed(1), 2, 0, DisputeResolution::Reinstated),
+            sp_runtime::DispatchError::BadOrigin
+        );
+
+        // Reinstated invoices can be paid again
+        assert_ok!(Ledger::resolve_dispute(
+            RuntimeOrigin::root(),
+            2,
+            0,
+            DisputeResolution::Reinstated
+        ));
+        System::assert_last_event(
+            Event::DisputeResolved {
+                invoice_id: 0,
+                client: 2,
+                resolution: DisputeResolution::Reinstated,
+            }
+            .into(),
+        );
+        assert_ok!(Ledger::pay_invoice(RuntimeOrigin::signed(2), 1, 0));
+
+        // Upheld disputes cancel the invoice
+        assert_ok!(Ledger::resolve_dispute(
+            RuntimeOrigin::root(),
+            2,
+            1,
+            DisputeResolution::Cancelled
+        ));
+        assert_eq!(Ledger::get_invoice(&2, 1).unwrap().status, InvoiceStatus::Cancelled);
+        System::assert_last_event(Event::InvoiceCancelled { invoice_id: 1, client: 2 }.into());
+
+        assert_noop!(
+            Ledger::resolve_dispute(RuntimeOrigin::root(), 2, 1, DisputeResolution::Reinstated),
+            Error::<Test>::InvoiceNotDisputed
+        );
+        assert_eq!(
+            DisputeNotifications::get(),
+            vec![(0, true), (1, true), (0, false), (1, false)]
+        );
+    });
+}
+
+#[test]
+fn creation_deposit_is_released_when_invoices_are_settled() {
+    new_test_ext().execute_with(|| {
+        InvoiceDeposit::set(50);
+        create_invoices(2, 3);
+        System::assert_has_event(
+            Event::InvoiceDepositReserved { invoice_id: 2, creator: 1, amount: 50 }.into(),
+        );
+        assert_eq!(Balances::reserved_balance(1), 150);
+        assert_eq!(Balances::free_balance(1), INITIAL_BALANCE - 150);
+        assert_eq!(Ledger::invoice_deposit(0), Some(50));
+
+        assert_ok!(Ledger::pay_invoice(RuntimeOrigin::signed(2), 1, 0));
+        System::assert_has_event(
+            Event::InvoiceDepositReleased { invoice_id: 0, creator: 1, amount: 50 }.into(),
+        );
+        assert_eq!(Balances::reserved_balance(1), 100);
+
+        assert_ok!(Ledger::cancel_invoice(RuntimeOrigin::signed(1), 2, 1));
+        assert_eq!(Balances::reserved_balance(1), 50);
+        assert_eq!(Ledger::invoice_deposit(1), None);
+
+        // The recorded deposit is released, not the current `InvoiceDeposit`
+        InvoiceDeposit::set(80);
+        assert_ok!(Ledger::record_payment(RuntimeOrigin::signed(2), 2, 2, 2));
+        assert_eq!(Balances::reserved_balance(1), 50);
+        assert_ok!(Ledger::record_payment(RuntimeOrigin::signed(2), 2, 2, 100));
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(Balances::free_balance(1), INITIAL_BALANCE + 100 + 102);
+    });
+}
+
+#[test]
+fn creation_deposit_must_be_affordable() {
+    new_test_ext().execute_with(|| {
+        InvoiceDeposit::set(INITIAL_BALANCE + 1);
+        assert_noop!(
+            Ledger::create_invoice(
+                RuntimeOrigin::signed(1),
+                2,
+                100,
+                0,
+                0,
+                b"INV-1".to_vec(),
+                None,
+                None,
+                vec![],
+                None
+            ),
+            Error::<Test>::InsufficientDeposit
+        );
+
+        // Without a deposit requirement nothing is reserved or recorded
+        InvoiceDeposit::set(0);
+        create_invoices(2, 1);
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(Ledger::invoice_deposit(0), None);
+    });
+}
+
+#[test]
+fn lost_dispute_slashes_creation_deposit_to_treasury() {
+    use crate::DisputeResolution;
+
+    new_test_ext().execute_with(|| {
+        InvoiceDeposit::set(50);
+        create_invoices(2, 2);
+        for invoice_id in 0..2 {
+            assert_ok!(Ledger::dispute_invoice(
+                RuntimeOrigin::signed(2),
+                1,
+                invoice_id,
+                b"Never ordered".to_vec()
+            ));
+        }
+
+        // A reinstated invoice keeps its deposit until it is settled
+        assert_ok!(Ledger::resolve_dispute(
+            RuntimeOrigin::root(),
+            2,
+            0,
+            DisputeResolution::Reinstated
+        ));
+        assert_eq!(Balances::reserved_balance(1), 100);
+
+        assert_ok!(Ledger::resolve_dispute(
+            RuntimeOrigin::root(),
+            2,
+            1,
+            DisputeResolution::Cancelled
+        ));
+        System::assert_has_event(
+            Event::InvoiceDepositSlashed { invoice_id: 1, creator: 1, amount: 50 }.into(),
+        );
+        assert_eq!(Balances::reserved_balance(1), 50);
+        assert_eq!(Balances::free_balance(1), INITIAL_BALANCE - 100);
+        assert_eq!(Balances::free_balance(TREASURY), 50);
+        assert_eq!(Ledger::invoice_deposit(1), None);
+
+        // Cancelling did not release it a second time
+        assert!(!System::events().iter().any(|record| matches!(
+            record.event,
+            RuntimeEvent::Ledger(Event::InvoiceDepositReleased { invoice_id: 1, .. })
+        )));
+    });
+}
+
+#[test]
+fn batch_create_invoices_works() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Ledger::create_invoice(
+            RuntimeOrigin::signed(1),
+            2,
+            100,
+            0,
+            0,
+            b"INV-0".to_vec(),
+            None,
+            None,
+            vec![],
+            None
+        ));
+
+        assert_ok!(Ledger::batch_create_invoices(
+            RuntimeOrigin::signed(1),
+            vec![
+                (2, 200, b"INV-1".to_vec()),
+                (3, 300, b"INV-2".to_vec()),
+                (2, 400, b"INV-3".to_vec()),
+            ]
+        ));
+
+        // Consecutive IDs in batch order, each with the usual events
+        assert_eq!(Ledger::invoice_count(), 4);
+        assert_eq!(Ledger::get_invoice(&2, 1).unwrap().amount, 200);
+        assert_eq!(Ledger::get_invoice(&3, 2).unwrap().amount, 300);
+        assert_eq!(Ledger::get_invoice(&2, 3).unwrap().created_by, 1);
+        assert_eq!(Ledger::client_invoice_count(2), 3);
+        let hash = Ledger::get_invoice(&3, 2).unwrap().invoice_hash;
+        System::assert_has_event(
+            Event::InvoiceCreated {
+                invoice_id: 2,
+                client: 3,
+                amount: 300,
+                net_amount: 300,
+                invoice_hash: hash,
+                created_by: 1,
+                line_items: 0,
+            }
+            .into(),
+        );
+        System::assert_has_event(
+            Event::InvoiceBatchCreated { count: 3, first_id: 1, last_id: 3 }.into(),
+        );
+        let outcome = BatchOutcome { succeeded: 3, skipped: 0, first_error_index: None };
+        System::assert_last_event(Event::BatchCompleted { who: 1, outcome }.into());
+        assert_eq!(Ledger::last_batch_outcome(1), Some(outcome));
+    });
+}
+
+/// `(index, reason)` of the `InvoiceBatchItemSkipped` events and IDs of the invoices created
+fn batch_item_events() -> (Vec<(u32, SkipReason)>, Vec<u64>) {
+    let mut skipped = Vec::new();
+    let mut created = Vec::new();
+    for record in System::events() {
+        match record.event {
+            RuntimeEvent::Ledger(Event::InvoiceBatchItemSkipped { index, reason }) => {
+                skipped.push((index, reason))
+            }
+            RuntimeEvent::Ledger(Event::InvoiceCreated { invoice_id, .. }) => {
+                created.push(invoice_id)
+            }
+            _ => {}
+        }
+    }
+    (skipped, created)
+}
+
+#[test]
+fn batch_skips_only_the_failing_entries() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        MaxInvoicesPerClient::set(2);
+        let too_long = vec![b'x'; 1025];
+
+        assert_ok!(Ledger::batch_create_invoices(
+            RuntimeOrigin::signed(1),
+            vec![
+                (2, 200, b"INV-1".to_vec()),
+                (3, 300, too_long),
+                (2, 400, b"INV-3".to_vec()),
+                // Client 2 is at its cap of 2 by now
+                (2, 500, b"INV-4".to_vec()),
+            ]
+        ));
+
+        // Kept entries got consecutive IDs; skipped ones left nothing behind
+        assert_eq!(Ledger::invoice_count(), 2);
+        assert_eq!(Ledger::get_invoice(&2, 0).unwrap().amount, 200);
+        assert_eq!(Ledger::get_invoice(&2, 1).unwrap().amount, 400);
+        assert_eq!(Ledger::client_invoice_count(3), 0);
+        assert_eq!(Ledger::get_outstanding_balance(&2), 600);
+
+        let outcome = Ledger::last_batch_outcome(1).unwrap();
+        assert_eq!(outcome, BatchOutcome { succeeded: 2, skipped: 2, first_error_index: Some(1) });
+
+        // The summary agrees with the per-item events
+        let (skipped, created) = batch_item_events();
+        assert_eq!(skipped, vec![(1, SkipReason::TooLong), (3, SkipReason::BoundExceeded)]);
+        assert_eq!(created, vec![0, 1]);
+        assert_eq!(outcome.succeeded as usize, created.len());
+        assert_eq!(outcome.skipped as usize, skipped.len());
+        assert_eq!(outcome.first_error_index, skipped.first().map(|(index, _)| *index));
+        System::assert_has_event(
+            Event::InvoiceBatchCreated { count: 2, first_id: 0, last_id: 1 }.into(),
+        );
+        System::assert_last_event(Event::BatchCompleted { who: 1, outcome }.into());
+    });
+}
+
+#[test]
+fn batch_with_only_failing_entries_still_reports_its_outcome() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        assert_ok!(Ledger::batch_create_invoices(
+            RuntimeOrigin::signed(1),
+            vec![(2, 100, vec![b'x'; 1025]), (3, 100, vec![b'y'; 1025])]
+        ));
+
+        assert_eq!(Ledger::invoice_count(), 0);
+        let outcome = BatchOutcome { succeeded: 0, skipped: 2, first_error_index: Some(0) };
+        assert_eq!(Ledger::last_batch_outcome(1), Some(outcome));
+        assert_eq!(batch_item_events().1, Vec::<u64>::new());
+        assert!(!System::events().iter().any(|record| matches!(
+            record.event,
+            RuntimeEvent::Ledger(Event::InvoiceBatchCreated { .. })
+        )));
+        System::assert_last_event(Event::BatchCompleted { who: 1, outcome }.into());
+    });
+}
+
+#[test]
+fn batch_rejects_empty_and_oversized_input() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Ledger::batch_create_invoices(RuntimeOrigin::signed(1), vec![]),
+            Error::<Test>::EmptyBatch
+        );
+        // MaxBatchSize is 4 in the mock
+        let five = (0..5u8).map(|i| (2, 100, vec![b'A' + i])).collect();
+        assert_noop!(
+            Ledger::batch_create_invoices(RuntimeOrigin::signed(1), five),
+            Error::<Test>::BatchTooLarge
+        );
+    });
+}
+
+#[test]
+fn batch_weight_follows_the_entries_and_refunds_skipped_ones() {
+    use frame_support::dispatch::GetDispatchInfo;
+
+    let entries = |lengths: &[usize]| -> Vec<(u64, u128, Vec<u8>)> {
+        lengths.iter().map(|len| (2, 100, vec![b'x'; *len])).collect()
+    };
+    let call = |lengths: &[usize]| crate::Call::<Test>::batch_create_invoices {
+        invoices: entries(lengths),
+    };
+    let weight = |lengths: &[usize]| call(lengths).get_dispatch_info().weight;
+
+    // Every entry is charged like `create_invoice` with its metadata
+    assert_eq!(weight(&[5, 1025]), Ledger::batch_create_invoices_weight([5, 1025]));
+    assert!(weight(&[5]).all_lt(weight(&[5, 5])));
+    assert!(weight(&[5, 5]).all_lt(weight(&[5, 500])));
+    // Entries beyond `MaxBatchSize` (4) fail the call and are not charged
+    assert_eq!(weight(&[1; 5]), weight(&[1; 4]));
+
+    new_test_ext().execute_with(|| {
+        let info = call(&[5, 1025]).get_dispatch_info();
+        let post_info =
+            Ledger::batch_create_invoices(RuntimeOrigin::signed(1), entries(&[5, 1025])).unwrap();
+
+        // The entry with too long metadata was skipped and refunded
+        assert_eq!(Ledger::last_batch_outcome(1).unwrap().skipped, 1);
+        assert_eq!(post_info.actual_weight, Some(Ledger::batch_create_invoices_weight([5, 0])));
+        assert!(post_info.calc_actual_weight(&info).all_lt(info.weight));
+    });
+}
+
+#[test]
+fn restricted_creation_requires_authorization() {
+    new_test_ext().execute_with(|| {
+        OpenCreation::set(false);
+
+        assert_noop!(
+            Ledger::create_invoice(
+                RuntimeOrigin::signed(1),
+                2,
+                100,
+                0,
+                0,
+                b"INV-1".to_vec(),
+                None,
+                None,
+                vec![],
+                None
+            ),
+            Error::<Test>::NotAuthorizedCreator
+        );
+        assert_noop!(
+            Ledger::create_invoice_with_ref(
+                RuntimeOrigin::signed(1),
+                2,
+                100,
+                0,
+                0,
+                b"INV-1".to_vec(),
+                None,
+                None,
+                Vec::new(),
+                None,
+                b"INV-1".to_vec()
+            ),
+            Error::<Test>::NotAuthorizedCreator
+        );
+        assert_noop!(
+            Ledger::batch_create_invoices(RuntimeOrigin::signed(1), vec![(2, 100, b"A".to_vec())]),
+            Error::<Test>::NotAuthorizedCreator
+        );
+
+        // Only the admin origin manages the set
+        assert_noop!(
+            Ledger::authorize_creator(RuntimeOrigin::signed(1), 1),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_ok!(Ledger::authorize_creator(RuntimeOrigin::root(), 1));
+        System::assert_last_event(Event::CreatorAuthorized { creator: 1 }.into());
+        assert!(Ledger::authorized_creators(1).is_some());
+        assert_noop!(
+            Ledger::authorize_creator(RuntimeOrigin::root(), 1),
+            Error::<Test>::CreatorAlreadyAuthorized
+        );
+
+        assert_ok!(Ledger::create_invoice(
+            RuntimeOrigin::signed(1),
+            2,
+            100,
+            0,
+            0,
+            b"INV-1".to_vec(),
+            None,
+            None,
+            vec![],
+            None
+        ));
+        assert_noop!(
+            Ledger::create_invoice(
+                RuntimeOrigin::signed(3),
+                2,
+                100,
+                0,
+                0,
+                b"INV-2".to_vec(),
+                None,
+                None,
+                vec![],
+                None
+            ),
+            Error::<Test>::NotAuthorizedCreator
+        );
+    });
+}
+
+#[test]
+fn revoked_creator_cannot_create_invoices() {
+    new_test_ext().execute_with(|| {
+        OpenCreation::set(false);
+        assert_ok!(Ledger::authorize_creator(RuntimeOrigin::root(), 1));
+        assert_ok!(Ledger::create_invoice(
+            RuntimeOrigin::signed(1),
+            2,
+            100,
+            0,
+            0,
+            b"INV-1".to_vec(),
+            None,
+            None,
+            vec![],
+            None
+        ));
+
+        assert_ok!(Ledger::revoke_creator(RuntimeOrigin::root(), 1));
+        System::assert_last_event(Event::CreatorRevoked { creator: 1 }.into());
+        assert_noop!(
+            Ledger::revoke_creator(RuntimeOrigin::root(), 1),
+            Error::<Test>::NotAuthorizedCreator
+        );
+
+        assert_noop!(
+            Ledger::create_invoice(
+                RuntimeOrigin::signed(1),
+                2,
+                200,
+                0,
+                0,
+                b"INV-2".to_vec(),
+                None,
+                None,
+                vec![],
+                None
+            ),
+            Error::<Test>::NotAuthorizedCreator
+        );
+        // Invoices created before the revocation are unaffected
+        assert_ok!(Ledger::pay_invoice(RuntimeOrigin::signed(2), 1, 0));
+    });
+}
+
+#[test]
+fn open_creation_allows_any_account() {
+    new_test_ext().execute_with(|| {
+        assert!(OpenCreation::get());
+        assert!(Ledger::authorized_creators(3).is_none());
+
+        assert_ok!(Ledger::create_invoice(
+            RuntimeOrigin::signed(3),
+            2,
+            100,
+            0,
+            0,
+            b"INV-1".to_vec(),
+            None,
+            None,
+            vec![],
+            None
+        ));
+        assert_eq!(Ledger::get_invoice(&2, 0).unwrap().created_by, 3);
+    });
+}
+
+fn line_item(description: &[u8], quantity: u32, unit_price: u128) -> LineItem<Test> {
+    LineItem { description: description.to_vec().try_into().unwrap(), quantity, unit_price }
+}
+
+#[test]
+fn invoice_with_line_items_works() {
+    new_test_ext().execute_with(|| {
+        let items = vec![line_item(b"Cleaning hours", 10, 50), line_item(b"Supplies", 1, 200)];
+        assert_ok!(Ledger::create_invoice(
+            RuntimeOrigin::signed(1),
+            2,
+            700,
+            0,
+            0,
+            b"INV-1".to_vec(),
+            None,
+            None,
+            items.clone(),
+            None
+        ));
+
+        let invoice = Ledger::get_invoice(&2, 0).unwrap();
+        assert_eq!(invoice.line_items.to_vec(), items);
+        assert_eq!(invoice.invoice_hash, invoice.calculate_hash());
+        System::assert_has_event(
+            Event::InvoiceCreated {
+                invoice_id: 0,
+                client: 2,
+                amount: 700,
+                net_amount: 700,
+                invoice_hash: invoice.invoice_hash,
+                created_by: 1,
+                line_items: 2,
+            }
+            .into(),
+        );
+
+        // Line items are part of the hash, in order
+        let mut reordered = invoice.clone();
+        reordered.line_items = vec![items[1].clone(), items[0].clone()].try_into().unwrap();
+        assert_ne!(reordered.calculate_hash(), invoice.invoice_hash);
+        let mut changed = invoice.clone();
+        changed.line_items =
+            vec![items[0].clone(), line_item(b"Supplies", 2, 100)].try_into().unwrap();
+        assert_ne!(changed.calculate_hash(), invoice.invoice_hash);
+    });
+}
+
+#[test]
+fn invoice_without_line_items_keeps_hash() {
+    use codec::Encode;
+
+    new_test_ext().execute_with(|| {
+        create_invoices(2, 1);
+
+        let invoice = Ledger::get_invoice(&2, 0).unwrap();
+        assert!(invoice.line_items.is_empty());
+        let preimage = crate::length_prefixed(&[
+            &invoice.id.to_le_bytes(),
+            &invoice.client.encode(),
+            &invoice.amount.encode(),
+            &invoice.metadata,
+            &invoice.timestamp.encode(),
+            &invoice.created_by.encode(),
+        ]);
+        assert_eq!(invoice.invoice_hash, sp_io::hashing::sha2_256(&preimage));
+    });
+}
+
+#[test]
+fn line_items_must_add_up_to_amount() {
+    new_test_ext().execute_with(|| {
+        let create = |amount, items| {
+            Ledger::create_invoice(
+                RuntimeOrigin::signed(1),
+                2,
+                amount,
+                0,
+                0,
+                b"INV-1".to_vec(),
+                None,
+                None,
+                items,
+                None,
+            )
+        };
+
+        assert_noop!(
+            create(600, vec![line_item(b"Hours", 10, 50), line_item(b"Supplies", 1, 200)]),
+            Error::<Test>::LineItemsMismatch
+        );
+        // Overflowing totals never match
+        assert_noop!(
+            create(u128::MAX, vec![line_item(b"Hours", 2, u128::MAX)]),
+            Error::<Test>::LineItemsMismatch
+        );
+        // MaxLineItems is 4 in the mock
+        assert_noop!(
+            create(500, vec![line_item(b"Hours", 1, 100); 5]),
+            Error::<Test>::TooManyLineItems
+        );
+
+        assert_ok!(create(400, vec![line_item(b"Hours", 1, 100); 4]));
+    });
+}
+
+/// Create invoice 0 from creator 1 to client 2 with the given tax and discount
+fn create_taxed_invoice(amount: u128, tax: u128, discount: u128) -> DispatchResultWithPostInfo {
+    Ledger::create_invoice(
+        RuntimeOrigin::signed(1),
+        2,
+        amount,
+        tax,
+        discount,
+        b"INV-1".to_vec(),
+        None,
+        None,
+        vec![],
+        None,
+    )
+}
+
+#[test]
+fn zero_tax_and_discount_leave_amount_and_hash_unchanged() {
+    use codec::Encode;
+
+    new_test_ext().execute_with(|| {
+        assert_ok!(create_taxed_invoice(1000, 0, 0));
+
+        let invoice = Ledger::get_invoice(&2, 0).unwrap();
+        assert_eq!(invoice.net_amount(), 1000);
+        assert_eq!(Ledger::get_outstanding_balance(&2), 1000);
+        // Neither field is part of the preimage
+        let preimage = crate::length_prefixed(&[
+            &invoice.id.to_le_bytes(),
+            &invoice.client.encode(),
+            &invoice.amount.encode(),
+            &invoice.metadata,
+            &invoice.timestamp.encode(),
+            &invoice.created_by.encode(),
+        ]);
+        assert_eq!(invoice.invoice_hash, sp_io::hashing::sha2_256(&preimage));
+    });
+}
+
+#[test]
+fn discount_may_equal_but_not_exceed_amount() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(create_taxed_invoice(1000, 0, 1001), Error::<Test>::DiscountExceedsAmount);
+
+        assert_ok!(create_taxed_invoice(1000, 0, 1000));
+        let invoice = Ledger::get_invoice(&2, 0).unwrap();
+        assert_eq!(invoice.net_amount(), 0);
+        assert_eq!(Ledger::get_outstanding_balance(&2), 0);
+        System::assert_has_event(
+            Event::InvoiceCreated {
+                invoice_id: 0,
+                client: 2,
+                amount: 1000,
+                net_amount: 0,
+                invoice_hash: invoice.invoice_hash,
+                created_by: 1,
+                line_items: 0,
+            }
+            .into(),
+        );
+    });
+}
+
+#[test]
+fn invoice_hash_changes_with_tax() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(create_taxed_invoice(1000, 200, 0));
+        let invoice = Ledger::get_invoice(&2, 0).unwrap();
+        assert_eq!(invoice.invoice_hash, invoice.calculate_hash());
+
+        let mut other = invoice.clone();
+        other.tax = 150;
+        assert_ne!(other.calculate_hash(), invoice.invoice_hash);
+        other.tax = 0;
+        assert_ne!(other.calculate_hash(), invoice.invoice_hash);
+        // The same figure as a discount is a different invoice
+        other.discount = 200;
+        assert_ne!(other.calculate_hash(), invoice.invoice_hash);
+    });
+}
+
+#[test]
+fn pay_invoice_transfers_net_amount() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(create_taxed_invoice(1000, 200, 100));
+        assert_eq!(Ledger::get_invoice(&2, 0).unwrap().net_amount(), 1100);
+        assert_eq!(Ledger::get_outstanding_balance(&2), 1100);
+
+        assert_ok!(Ledger::pay_invoice(RuntimeOrigin::signed(2), 1, 0));
+
+        assert_eq!(Balances::free_balance(2), INITIAL_BALANCE - 1100);
+        assert_eq!(Ledger::get_invoice(&2, 0).unwrap().paid_amount, 1100);
+        assert_eq!(Ledger::get_outstanding_balance(&2), 0);
+        System::assert_has_event(
+            Event::InvoicePaid { invoice_id: 0, payer: 2, payee: 1, amount: 1100 }.into(),
+        );
+    });
+}
+
+#[test]
+fn migration_to_v4_adds_empty_line_items() {
+    use crate::migrations::v4::{Invoices as OldInvoices, OldInvoice};
+    use frame_support::traits::{GetStorageVersion, StorageVersion};
+
+    new_test_ext().execute_with(|| {
+        StorageVersion::new(3).put::<Ledger>();
+        OldInvoices::<Test>::insert(
+            2,
+            0,
+            OldInvoice {
+                id: 0,
+                client: 2,
+                amount: 100,
+                metadata: b"INV".to_vec().try_into().unwrap(),
+                timestamp: 1,
+                invoice_hash: [9u8; 32],
+                created_by: 1,
+                status: InvoiceStatus::Pending,
+                due_block: None,
+                paid_amount: 0,
+                external_ref: b"INV-2025-001".to_vec().try_into().unwrap(),
+            },
+        );
+
+        Ledger::on_runtime_upgrade();
+
+        assert_eq!(Ledger::on_chain_storage_version(), 13);
+        let invoice = Ledger::get_invoice(&2, 0).unwrap();
+        assert!(invoice.line_items.is_empty());
+        assert_eq!(invoice.external_ref.to_vec(), b"INV-2025-001".to_vec());
+        // Re-hashed by the version 7 migration
+        assert_eq!(invoice.invoice_hash, invoice.calculate_hash());
+    });
+}
+
+/// A client's outstanding total recomputed from scratch from its stored invoices
+fn recomputed_outstanding(client: u64) -> u128 {
+    crate::Invoices::<Test>::iter_prefix_values(client)
+        .map(|invoice| Ledger::outstanding_amount(&client, invoice.id).unwrap())
+        .sum()
+}
+
+#[test]
+fn outstanding_balance_follows_invoice_lifecycle() {
+    new_test_ext().execute_with(|| {
+        create_invoices(2, 3); // 100, 101, 102
+        assert_eq!(Ledger::get_outstanding_balance(&2), 303);
+
+        assert_ok!(Ledger::record_payment(RuntimeOrigin::signed(2), 2, 0, 40));
+        assert_ok!(Ledger::create_credit_note(
+            RuntimeOrigin::signed(1),
+            2,
+            0,
+            10,
+            b"CN-1".to_vec()
+        ));
+        assert_eq!(Ledger::get_outstanding_balance(&2), 253);
+
+        // Paying settles the rest, cancelling writes it off
+        assert_ok!(Ledger::pay_invoice(RuntimeOrigin::signed(2), 1, 0));
+        assert_ok!(Ledger::cancel_invoice(RuntimeOrigin::signed(1), 2, 1));
+        assert_eq!(Ledger::get_outstanding_balance(&2), 102);
+        assert_eq!(Ledger::get_outstanding_balance(&3), 0);
+
+        // Archiving settled invoices changes nothing
+        assert_ok!(Ledger::archive_invoices(RuntimeOrigin::root(), 2, 3));
+        assert_eq!(Ledger::get_outstanding_balance(&2), 102);
+    });
+}
+
+#[test]
+fn outstanding_balance_matches_invoices_under_random_operations() {
+    new_test_ext().execute_with(|| {
+        // Deterministic xorshift, so a failing sequence reproduces
+        let mut seed = 0x2545_f491_4f6c_dd1du64;
+        let mut next = |bound: u64| {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            seed % bound
+        };
+        let mut succeeded = [0u32; 6];
+
+        for step in 0..400u64 {
+            let client = 2 + next(2);
+            let id = next(Ledger::invoice_count().max(1));
+            let kind = next(6);
+            let result = match kind {
+                0 => Ledger::create_invoice(
+                    RuntimeOrigin::signed(1),
+                    client,
+                    1 + u128::from(next(200)),
+                    0,
+                    0,
+                    step.to_le_bytes().to_vec(),
+                    None,
+                    None,
+                    vec![],
+                    None,
+                )
+                .map(|_| ())
+                .map_err(|error| error.error),
+                1 => Ledger::record_payment(
+                    RuntimeOrigin::signed(client),
+                    client,
+                    id,
+                    1 + u128::from(next(100)),
+                ),
+                2 => Ledger::pay_invoice(RuntimeOrigin::signed(client), 1, id),
+                3 => Ledger::cancel_invoice(RuntimeOrigin::signed(1), client, id),
+                4 => Ledger::create_credit_note(
+                    RuntimeOrigin::signed(1),
+                    client,
+                    id,
+                    1 + u128::from(next(50)),
+                    step.to_le_bytes().to_vec(),
+                ),
+                _ => Ledger::archive_invoices(RuntimeOrigin::root(), client, id),
+            };
+            if result.is_ok() {
+                succeeded[kind as usize] += 1;
+            }
+
+            for client in [2, 3] {
+                assert_eq!(
+                    Ledger::get_outstanding_balance(&client),
+                    recomputed_outstanding(client),
+                    "client {} diverged at step {}",
+                    client,
+                    step
+                );
+            }
+            let (invoiced, collected) = Ledger::get_creator_stats(&1);
+            assert_eq!(
+                invoiced - collected,
+                recomputed_outstanding(2) + recomputed_outstanding(3),
+                "creator stats diverged at step {}",
+                step
+            );
+        }
+
+        // Every kind of transition was exercised
+        assert!(succeeded.iter().all(|count| *count > 0), "{:?}", succeeded);
+    });
+}
+
+#[test]
+fn migration_to_v5_computes_outstanding_balances() {
+    use frame_support::traits::{GetStorageVersion, StorageVersion};
+
+    new_test_ext().execute_with(|| {
+        create_invoices(2, 2);
+        create_invoices(3, 1);
+        assert_ok!(Ledger::record_payment(RuntimeOrigin::signed(2), 2, 1, 30));
+        assert_ok!(Ledger::cancel_invoice(RuntimeOrigin::signed(1), 3, 2));
+
+        // Forget the aggregate, as on a chain upgraded from version 4
+        let _ = crate::OutstandingBalance::<Test>::clear(u32::MAX, None);
+        StorageVersion::new(4).put::<Ledger>();
+
+        Ledger::on_runtime_upgrade();
+
+        assert_eq!(Ledger::on_chain_storage_version(), 13);
+        assert_eq!(Ledger::get_outstanding_balance(&2), 100 + 101 - 30);
+        assert_eq!(Ledger::get_outstanding_balance(&3), 0);
+    });
+}
+
+#[test]
+fn creator_stats_follow_invoice_lifecycle() {
+    new_test_ext().execute_with(|| {
+        create_invoices(2, 3); // 100, 101, 102
+        assert_eq!(Ledger::get_creator_stats(&1), (303, 0));
+
+        assert_ok!(Ledger::record_payment(RuntimeOrigin::signed(2), 2, 0, 40));
+        assert_ok!(Ledger::pay_invoice(RuntimeOrigin::signed(2), 1, 1));
+        assert_eq!(Ledger::get_creator_stats(&1), (303, 141));
+
+        // A credit note takes its amount out of the invoiced total
+        assert_ok!(Ledger::create_credit_note(
+            RuntimeOrigin::signed(1),
+            2,
+            0,
+            10,
+            b"CN-1".to_vec()
+        ));
+        assert_eq!(Ledger::get_creator_stats(&1), (293, 141));
+
+        // Cancelling a partly paid invoice writes off only its unpaid rest
+        assert_ok!(Ledger::cancel_invoice(RuntimeOrigin::signed(1), 2, 0));
+        assert_eq!(Ledger::get_creator_stats(&1), (243, 141));
+        assert_eq!(243 - 141, Ledger::get_outstanding_balance(&2));
+
+        // Purging an open invoice writes it off too
+        assert_ok!(Ledger::purge_invoice(RuntimeOrigin::root(), 2, 2, false));
+        assert_eq!(Ledger::get_creator_stats(&1), (141, 141));
+
+        // Only creators have stats
+        assert_eq!(Ledger::get_creator_stats(&2), (0, 0));
+    });
+}
+
+#[test]
+fn creator_stats_saturate() {
+    new_test_ext().execute_with(|| {
+        crate::CreatorStats::<Test>::insert(1, (u128::MAX - 50, u128::MAX - 10));
+
+        create_invoices(2, 1);
+        assert_eq!(Ledger::get_creator_stats(&1), (u128::MAX, u128::MAX - 10));
+        assert_ok!(Ledger::pay_invoice(RuntimeOrigin::signed(2), 1, 0));
+        assert_eq!(Ledger::get_creator_stats(&1), (u128::MAX, u128::MAX));
+
+        // Writing off more than is left invoiced stops at zero
+        create_invoices(3, 1);
+        crate::CreatorStats::<Test>::insert(1, (20, 0));
+        assert_ok!(Ledger::cancel_invoice(RuntimeOrigin::signed(1), 3, 1));
+        assert_eq!(Ledger::get_creator_stats(&1), (0, 0));
+    });
+}
+
+#[test]
+fn migration_to_v12_computes_creator_stats() {
+    use frame_support::traits::{GetStorageVersion, StorageVersion};
+
+    new_test_ext().execute_with(|| {
+        create_invoices(2, 3);
+        assert_ok!(Ledger::record_payment(RuntimeOrigin::signed(2), 2, 0, 30));
+        assert_ok!(Ledger::pay_invoice(RuntimeOrigin::signed(2), 1, 1));
+        assert_ok!(Ledger::cancel_invoice(RuntimeOrigin::signed(1), 2, 0));
+        let stats = Ledger::get_creator_stats(&1);
+        assert_eq!(stats, (30 + 101 + 102, 30 + 101));
+
+        // Forget the totals, as on a chain upgraded from version 11
+        let _ = crate::CreatorStats::<Test>::clear(u32::MAX, None);
+        StorageVersion::new(11).put::<Ledger>();
+
+        Ledger::on_runtime_upgrade();
+
+        assert_eq!(Ledger::on_chain_storage_version(), 13);
+        assert_eq!(Ledger::get_creator_stats(&1), stats);
+    });
+}
+
+#[test]
+fn migration_to_v13_counts_access_grants() {
+    use frame_support::traits::{GetStorageVersion, StorageVersion};
+
+    new_test_ext().execute_with(|| {
+        create_invoices(2, 2);
+        let first = Ledger::get_invoice(&2, 0).unwrap().invoice_hash;
+        let second = Ledger::get_invoice(&2, 1).unwrap().invoice_hash;
+        for grantee in 3..=5 {
+            assert_ok!(Ledger::grant_access(RuntimeOrigin::signed(1), first, grantee, [7; 32]));
+        }
+        assert_ok!(Ledger::grant_access(RuntimeOrigin::signed(2), second, 3, [7; 32]));
+
+        // Forget the counts, as on a chain upgraded from version 12
+        let _ = crate::AccessGrantCount::<Test>::clear(u32::MAX, None);
+        StorageVersion::new(12).put::<Ledger>();
+
+        Ledger::on_runtime_upgrade();
+
+        assert_eq!(Ledger::on_chain_storage_version(), 13);
+        assert_eq!(Ledger::access_grant_count(first), 3);
+        assert_eq!(Ledger::access_grant_count(second), 1);
+    });
+}
+
+#[test]
+fn migration_to_v6_records_sha256_for_stored_hashes() {
+    use crate::{
+        migrations::v6::{Invoices as OldInvoices, OldInvoice},
+        HashAlgorithm,
+    };
+    use frame_support::traits::{GetStorageVersion, StorageVersion};
+
+    new_test_ext().execute_with(|| {
+        create_invoices(2, 1);
+        let current = Ledger::get_invoice(&2, 0).unwrap();
+        StorageVersion::new(5).put::<Ledger>();
+        OldInvoices::<Test>::insert(
+            2,
+            0,
+            OldInvoice {
+                id: current.id,
+                client: current.client,
+                amount: current.amount,
+                metadata: current.metadata,
+                timestamp: current.timestamp,
+                invoice_hash: current.invoice_hash,
+                created_by: current.created_by,
+                status: current.status,
+                due_block: current.due_block,
+                paid_amount: current.paid_amount,
+                external_ref: current.external_ref,
+                line_items: current.line_items,
+            },
+        );
+
+        // The setting in force after the upgrade does not apply to stored hashes
+        InvoiceHashAlgorithm::set(HashAlgorithm::Blake2b256);
+        Ledger::on_runtime_upgrade();
+
+        assert_eq!(Ledger::on_chain_storage_version(), 13);
+        let invoice = Ledger::get_invoice(&2, 0).unwrap();
+        assert_eq!(invoice.hash_algorithm, HashAlgorithm::Sha256);
+        assert!(Ledger::verify_invoice_hash(&2, 0));
+    });
+}
+
+#[test]
+fn invoice_hash_covers_creator() {
+    new_test_ext().execute_with(|| {
+        create_invoices(2, 1);
+        let mut invoice = Ledger::get_invoice(&2, 0).unwrap();
+
+        // Same fields, another creator
+        invoice.created_by = 3;
+        assert_ne!(invoice.calculate_hash(), Ledger::get_invoice(&2, 0).unwrap().invoice_hash);
+    });
+}
+
+#[test]
+fn migration_to_v7_rehashes_with_creator() {
+    use crate::migrations::checks;
+    use frame_support::traits::{GetStorageVersion, StorageVersion};
+
+    new_test_ext().execute_with(|| {
+        create_invoices(2, 2);
+        create_invoices(3, 1);
+
+        // Invoices hashed before the creator was part of the preimage
+        let stale = |id: u64| [id as u8 + 1; 32];
+        for (client, id) in [(2, 0), (2, 1), (3, 2)] {
+            let hash = Ledger::get_invoice(&client, id).unwrap().invoice_hash;
+            crate::InvoiceByHash::<Test>::remove(hash);
+            crate::InvoiceByHash::<Test>::insert(stale(id), id);
+            crate::Invoices::<Test>::mutate(client, id, |invoice| {
+                invoice.as_mut().unwrap().invoice_hash = stale(id)
+            });
+        }
+        StorageVersion::new(6).put::<Ledger>();
+
+        let state = checks::pre_upgrade::<Test>();
+        Ledger::on_runtime_upgrade();
+        assert_eq!(checks::post_upgrade::<Test>(state), Ok(()));
+
+        assert_eq!(Ledger::on_chain_storage_version(), 13);
+        for (client, id) in [(2, 0), (2, 1), (3, 2)] {
+            assert!(Ledger::verify_invoice_hash(&client, id));
+            assert_eq!(Ledger::get_invoice_by_hash(stale(id)), None);
+            let hash = Ledger::get_invoice(&client, id).unwrap().invoice_hash;
+            assert_eq!(Ledger::get_invoice_by_hash(hash), Some(id));
+        }
+    });
+}
+
+#[test]
+fn migration_to_v8_adds_zero_tax_and_discount() {
+    use crate::migrations::{
+        checks,
+        v8::{Invoices as OldInvoices, OldInvoice},
+    };
+    use frame_support::traits::{GetStorageVersion, StorageVersion};
+
+    new_test_ext().execute_with(|| {
+        create_invoices(2, 1);
+        let current = Ledger::get_invoice(&2, 0).unwrap();
+        StorageVersion::new(7).put::<Ledger>();
+        OldInvoices::<Test>::insert(
+            2,
+            0,
+            OldInvoice {
+                id: current.id,
+                client: current.client,
+                amount: current.amount,
+                metadata: current.metadata,
+                timestamp: current.timestamp,
+                invoice_hash: current.invoice_hash,
+                created_by: current.created_by,
+                status: current.status,
+                due_block: current.due_block,
+                paid_amount: current.paid_amount,
+                external_ref: current.external_ref,
+                line_items: current.line_items,
+                hash_algorithm: current.hash_algorithm,
+            },
+        );
+
+        let state = checks::pre_upgrade::<Test>();
+        Ledger::on_runtime_upgrade();
+        assert_eq!(checks::post_upgrade::<Test>(state), Ok(()));
+
+        assert_eq!(Ledger::on_chain_storage_version(), 13);
+        let invoice = Ledger::get_invoice(&2, 0).unwrap();
+        assert_eq!((invoice.tax, invoice.discount), (0, 0));
+        assert_eq!(invoice.net_amount(), invoice.amount);
+        assert!(Ledger::verify_invoice_hash(&2, 0));
+    });
+}
+
+#[test]
+fn migration_to_v10_takes_the_creation_block_as_last_activity() {
+    use crate::migrations::{
+        checks,
+        v10::{Invoices as OldInvoices, OldInvoice},
+    };
+    use frame_support::traits::{GetStorageVersion, StorageVersion};
+
+    new_test_ext().execute_with(|| {
+        System::set_block_number(3);
+        create_invoices(2, 1);
+        let current = Ledger::get_invoice(&2, 0).unwrap();
+        StorageVersion::new(9).put::<Ledger>();
+        OldInvoices::<Test>::insert(
+            2,
+            0,
+            OldInvoice {
+                id: current.id,
+                client: current.client,
+                amount: current.amount,
+                metadata: current.metadata,
+                timestamp: current.timestamp,
+                invoice_hash: current.invoice_hash,
+                created_by: current.created_by,
+                status: current.status,
+                due_block: current.due_block,
+                paid_amount: current.paid_amount,
+                external_ref: current.external_ref,
+                line_items: current.line_items,
+                hash_algorithm: current.hash_algorithm,
+                tax: current.tax,
+                discount: current.discount,
+                accrued_late_fee: current.accrued_late_fee,
+            },
+        );
+
+        System::set_block_number(40);
+        let state = checks::pre_upgrade::<Test>();
+        Ledger::on_runtime_upgrade();
+        assert_eq!(checks::post_upgrade::<Test>(state), Ok(()));
+
+        assert_eq!(Ledger::on_chain_storage_version(), 13);
+        assert_eq!(Ledger::get_invoice(&2, 0).unwrap().last_activity, 3);
+        assert!(Ledger::verify_invoice_hash(&2, 0));
+    });
+}
+
+#[test]
+fn migration_to_v11_makes_the_creator_beneficiary() {
+    use crate::migrations::{
+        checks,
+        v11::{Invoices as OldInvoices, OldInvoice},
+    };
+    use frame_support::traits::{GetStorageVersion, StorageVersion};
+
+    new_test_ext().execute_with(|| {
+        create_invoices(2, 1);
+        let current = Ledger::get_invoice(&2, 0).unwrap();
+        StorageVersion::new(10).put::<Ledger>();
+        OldInvoices::<Test>::insert(
+            2,
+            0,
+            OldInvoice {
+                id: current.id,
+                client: current.client,
+                amount: current.amount,
+                metadata: current.metadata,
+                timestamp: current.timestamp,
+                invoice_hash: current.invoice_hash,
+                created_by: current.created_by,
+                status: current.status,
+                due_block: current.due_block,
+                paid_amount: current.paid_amount,
+                external_ref: current.external_ref,
+                line_items: current.line_items,
+                hash_algorithm: current.hash_algorithm,
+                tax: current.tax,
+                discount: current.discount,
+                accrued_late_fee: current.accrued_late_fee,
+                last_activity: current.last_activity,
+            },
+        );
+
+        let state = checks::pre_upgrade::<Test>();
+        Ledger::on_runtime_upgrade();
+        assert_eq!(checks::post_upgrade::<Test>(state), Ok(()));
+
+        assert_eq!(Ledger::on_chain_storage_version(), 13);
+        assert_eq!(Ledger::get_invoice(&2, 0).unwrap().beneficiary, 1);
+        assert!(Ledger::verify_invoice_hash(&2, 0));
+
+        // Payments still reach the creator
+        assert_ok!(Ledger::pay_invoice(RuntimeOrigin::signed(2), 1, 0));
+        assert_eq!(Balances::free_balance(1), INITIAL_BALANCE + 100);
+    });
+}
+
+#[test]
+fn upgrade_from_unversioned_storage_passes_checks() {
+    use crate::migrations::{checks, v1};
+    use codec::Encode;
+    use frame_support::{
+        storage::unhashed,
+        traits::{GetStorageVersion, StorageVersion},
+    };
+
+    new_test_ext().execute_with(|| {
+        StorageVersion::new(0).put::<Ledger>();
+
+        // Pre-v1 invoices of client 2, encoded by hand as
+        // (id, client, amount, metadata, timestamp, invoice_hash, created_by)
+        let old: Vec<(u64, u64, u128, Vec<u8>, u64, [u8; 32], u64)> = (0..3u64)
+            .map(|id| (id, 2, 100 * (id as u128 + 1), b"INV".to_vec(), 1, [id as u8 + 1; 32], 1))
+            .collect();
+        unhashed::put_raw(&v1::Invoices::<Test>::hashed_key_for(2), &old.encode());
+        for (id, .., hash, _) in &old {
+            crate::InvoiceByHash::<Test>::insert(hash, id);
+        }
+        crate::InvoiceCount::<Test>::put(3);
+
+        let state = checks::pre_upgrade::<Test>();
+        Ledger::on_runtime_upgrade();
+        assert_eq!(checks::post_upgrade::<Test>(state), Ok(()));
+
+        assert_eq!(Ledger::on_chain_storage_version(), Ledger::current_storage_version());
+        assert_eq!(Ledger::client_invoice_count(2), 3);
+        assert_eq!(Ledger::get_outstanding_balance(&2), 100 + 200 + 300);
+        for invoice in Ledger::get_client_invoices(&2) {
+            assert_eq!(invoice.invoice_hash, invoice.calculate_hash());
+            assert_eq!(Ledger::get_invoice_by_hash(invoice.invoice_hash), Some(invoice.id));
+        }
+    });
+}
+
+#[test]
+fn post_upgrade_checks_catch_broken_storage() {
+    use crate::migrations::checks;
+    use frame_support::{
+        storage::unhashed,
+        traits::{GetStorageVersion, StorageVersion},
+    };
+
+    new_test_ext().execute_with(|| {
+        Ledger::current_storage_version().put::<Ledger>();
+        create_invoices(2, 2);
+        let state = checks::pre_upgrade::<Test>();
+        assert_eq!(checks::post_upgrade::<Test>(state.clone()), Ok(()));
+
+        // A migration that did not run
+        StorageVersion::new(4).put::<Ledger>();
+        assert_eq!(
+            checks::post_upgrade::<Test>(state.clone()),
+            Err("storage version not updated")
+        );
+        Ledger::current_storage_version().put::<Ledger>();
+
+        // A lost hash mapping
+        let hash = Ledger::get_invoice(&2, 0).unwrap().invoice_hash;
+        crate::InvoiceByHash::<Test>::remove(hash);
+        assert_eq!(
+            checks::post_upgrade::<Test>(state.clone()),
+            Err("invoice hash mappings lost")
+        );
+        crate::InvoiceByHash::<Test>::insert(hash, 0);
+
+        // An invoice left in a layout that no longer decodes
+        unhashed::put_raw(&crate::Invoices::<Test>::hashed_key_for(2, 1), &[1, 2, 3]);
+        assert_eq!(checks::post_upgrade::<Test>(state), Err("invoices fail to decode"));
+    });
+}
+
+/// Sorted top-level field names of a JSON object
+fn json_keys(value: &serde_json::Value) -> Vec<String> {
+    let mut keys: Vec<String> = value.as_object().unwrap().keys().cloned().collect();
+    keys.sort();
+    keys
+}
+
+#[test]
+fn invoice_json_field_names_are_stable() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Ledger::create_invoice(
+            RuntimeOrigin::signed(1),
+            2,
+            100,
+            0,
+            0,
+            b"INV".to_vec(),
+            None,
+            None,
+            vec![line_item(b"Cleaning", 2, 50)],
+            None
+        ));
+        let invoice = Ledger::get_invoice(&2, 0).unwrap();
+        let json = serde_json::to_value(&invoice).unwrap();
+
+        // Frontend types are generated from these names; renaming one breaks them
+        assert_eq!(
+            json_keys(&json),
+            [
+                "accruedLateFee",
+                "amount",
+                "beneficiary",
+                "client",
+                "createdBy",
+                "discount",
+                "dueBlock",
+                "externalRef",
+                "hashAlgorithm",
+                "id",
+                "invoiceHash",
+                "lastActivity",
+                "lineItems",
+                "metadata",
+                "paidAmount",
+                "status",
+                "tax",
+                "timestamp",
+            ]
+        );
+        assert_eq!(json["metadata"], "0x494e56");
+        assert_eq!(json["externalRef"], "0x");
+        assert_eq!(json["status"], "Pending");
+        assert_eq!(json["hashAlgorithm"], "sha256");
+        assert_eq!(json["invoiceHash"].as_str().unwrap().len(), 2 + 64);
+        assert_eq!(
+            json["lineItems"][0],
+            serde_json::json!({
+                "description": "0x436c65616e696e67",
+                "quantity": 2,
+                "unitPrice": 50,
+            })
+        );
+
+        assert_eq!(serde_json::from_value::<crate::Invoice<Test>>(json).unwrap(), invoice);
+    });
+}
+
+#[test]
+fn created_invoices_reads_back_the_block_events() {
+    new_test_ext().execute_with(|| {
+        create_invoices(2, 1);
+        assert_ok!(Ledger::record_payment(RuntimeOrigin::signed(2), 2, 0, 50));
+        create_invoices(3, 1);
+
+        let created = Ledger::created_invoices();
+        let summary: Vec<_> =
+            created.iter().map(|c| (c.invoice_id, c.client, c.amount, c.created_by)).collect();
+        assert_eq!(summary, vec![(0, 2, 100, 1), (1, 3, 100, 1)]);
+
+        // Indexes point at the events themselves, among those of other pallets
+        let events = System::events();
+        for c in &created {
+            assert!(matches!(
+                events[c.event_index as usize].event,
+                RuntimeEvent::Ledger(Event::InvoiceCreated { invoice_id, invoice_hash, .. })
+                    if invoice_id == c.invoice_id && invoice_hash == c.invoice_hash
+            ));
+        }
+
+        // Events only live for the block that deposited them
+        System::reset_events();
+        assert!(Ledger::created_invoices().is_empty());
+    });
+}
+
+#[test]
+fn invoice_parties_grant_access_to_payload() {
+    new_test_ext().execute_with(|| {
+        create_invoices(2, 1);
+        let hash = Ledger::get_invoice(&2, 0).unwrap().invoice_hash;
+
+        // The creator and client have access without a grant
+        assert!(Ledger::has_access(hash, &1));
+        assert!(Ledger::has_access(hash, &2));
+        assert!(!Ledger::has_access(hash, &4));
+
+        // The client grants an auditor access
+        System::set_block_number(3);
+        assert_ok!(Ledger::grant_access(RuntimeOrigin::signed(2), hash, 4, [7; 32]));
+        System::assert_has_event(
+            Event::AccessGranted {
+                invoice_hash: hash,
+                grantee: 4,
+                granted_by: 2,
+                key_wrap_hash: [7; 32],
+            }
+            .into(),
+        );
+        let grant = Ledger::access_grant(hash, 4).unwrap();
+        assert_eq!((grant.granted_by, grant.granted_at, grant.key_wrap_hash), (2, 3, [7; 32]));
+        assert!(Ledger::has_access(hash, &4));
+
+        // Granting again rotates the wrapped key
+        assert_ok!(Ledger::grant_access(RuntimeOrigin::signed(1), hash, 4, [8; 32]));
+        assert_eq!(Ledger::access_grant(hash, 4).unwrap().key_wrap_hash, [8; 32]);
+
+        // Others cannot manage grants
+        assert_noop!(
+            Ledger::grant_access(RuntimeOrigin::signed(4), hash, 5, [9; 32]),
+            Error::<Test>::NotInvoiceParty
+        );
+        assert_noop!(
+            Ledger::revoke_access(RuntimeOrigin::signed(3), hash, 4),
+            Error::<Test>::NotInvoiceParty
+        );
+    });
+}
+
+#[test]
+fn revoked_grant_loses_access() {
+    new_test_ext().execute_with(|| {
+        create_invoices(2, 1);
+        let hash = Ledger::get_invoice(&2, 0).unwrap().invoice_hash;
+        assert_ok!(Ledger::grant_access(RuntimeOrigin::signed(1), hash, 4, [7; 32]));
+
+        // Either party may revoke, whoever granted
+        assert_ok!(Ledger::revoke_access(RuntimeOrigin::signed(2), hash, 4));
+        System::assert_has_event(
+            Event::AccessRevoked { invoice_hash: hash, grantee: 4, revoked_by: 2 }.into(),
+        );
+        assert_eq!(Ledger::access_grant(hash, 4), None);
+        assert!(!Ledger::has_access(hash, &4));
+
+        assert_noop!(
+            Ledger::revoke_access(RuntimeOrigin::signed(1), hash, 4),
+            Error::<Test>::GrantNotFound
+        );
+    });
+}
+
+#[test]
+fn grants_follow_the_invoice_hash_on_metadata_update() {
+    new_test_ext().execute_with(|| {
+        create_invoices(2, 1);
+        let old_hash = Ledger::get_invoice(&2, 0).unwrap().invoice_hash;
+        assert_ok!(Ledger::grant_access(RuntimeOrigin::signed(1), old_hash, 4, [7; 32]));
+        let grant = Ledger::access_grant(old_hash, 4).unwrap();
+
+        let post_info =
+            Ledger::update_invoice_metadata(RuntimeOrigin::signed(1), 2, 0, b"Amended".to_vec())
+                .unwrap();
+        assert_eq!(
+            post_info.actual_weight,
+            Some(Ledger::update_invoice_metadata_weight(7, 1))
+        );
+
+        // The grantee keeps access under the new hash; nothing is left under the old one
+        let new_hash = Ledger::get_invoice(&2, 0).unwrap().invoice_hash;
+        assert!(Ledger::has_access(new_hash, &4));
+        assert_eq!(Ledger::access_grant(new_hash, 4), Some(grant));
+        assert_eq!(Ledger::access_grant_count(new_hash), 1);
+        assert_eq!(Ledger::access_grant(old_hash, 4), None);
+        assert_eq!(Ledger::access_grant_count(old_hash), 0);
+
+        // Purging clears them under the hash they moved to
+        assert_ok!(Ledger::purge_invoice(RuntimeOrigin::root(), 2, 0, false));
+        assert_eq!(Ledger::access_grant(new_hash, 4), None);
+        assert_eq!(Ledger::access_grant_count(new_hash), 0);
+    });
+}
+
+#[test]
+fn access_grants_are_bounded_per_invoice() {
+    new_test_ext().execute_with(|| {
+        create_invoices(2, 1);
+        let hash = Ledger::get_invoice(&2, 0).unwrap().invoice_hash;
+
+        // The mock allows three grants per invoice
+        for grantee in 3..=5 {
+            assert_ok!(Ledger::grant_access(RuntimeOrigin::signed(1), hash, grantee, [7; 32]));
+        }
+        assert_eq!(Ledger::access_grant_count(hash), 3);
+        assert_noop!(
+            Ledger::grant_access(RuntimeOrigin::signed(1), hash, 6, [7; 32]),
+            Error::<Test>::TooManyGrants
+        );
+        // Rotating an existing grant's key takes no new slot
+        assert_ok!(Ledger::grant_access(RuntimeOrigin::signed(2), hash, 5, [8; 32]));
+        assert_eq!(Ledger::access_grant_count(hash), 3);
+
+        // A revocation frees a slot
+        assert_ok!(Ledger::revoke_access(RuntimeOrigin::signed(2), hash, 3));
+        assert_eq!(Ledger::access_grant_count(hash), 2);
+        assert_ok!(Ledger::grant_access(RuntimeOrigin::signed(1), hash, 6, [7; 32]));
+        assert_eq!(Ledger::access_grant_count(hash), 3);
+    });
+}
+
+#[test]
+fn access_grants_require_a_known_invoice_hash() {
+    new_test_ext().execute_with(|| {
+        create_invoices(2, 1);
+
+        assert_noop!(
+            Ledger::grant_access(RuntimeOrigin::signed(1), [0xee; 32], 4, [7; 32]),
+            Error::<Test>::UnknownInvoiceHash
+        );
+        assert_noop!(
+            Ledger::revoke_access(RuntimeOrigin::signed(1), [0xee; 32], 4),
+            Error::<Test>::UnknownInvoiceHash
+        );
+        assert!(!Ledger::has_access([0xee; 32], &1));
+    });
+}
+
+#[test]
+fn purge_invoice_leaves_no_dangling_hash_mapping() {
+    new_test_ext().execute_with(|| {
+        InvoiceDeposit::set(50);
+        create_invoices(2, 3);
+        let purged = Ledger::get_invoice(&2, 1).unwrap();
+        assert_ok!(Ledger::grant_access(RuntimeOrigin::signed(1), purged.invoice_hash, 4, [7; 32]));
+        assert_eq!(Ledger::get_outstanding_balance(&2), 100 + 101 + 102);
+
+        assert_noop!(
+            Ledger::purge_invoice(RuntimeOrigin::signed(1), 2, 1, false),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_ok!(Ledger::purge_invoice(RuntimeOrigin::root(), 2, 1, false));
+        System::assert_has_event(
+            Event::InvoicePurged { invoice_id: 1, client: 2, invoice_hash: purged.invoice_hash }
+                .into(),
+        );
+
+        // Nothing resolves to the purged invoice any more
+        assert_eq!(Ledger::get_invoice(&2, 1), None);
+        assert_eq!(Ledger::get_invoice_by_hash(purged.invoice_hash), None);
+        assert_eq!(Ledger::access_grant(purged.invoice_hash, 4), None);
+        assert_eq!(Ledger::access_grant_count(purged.invoice_hash), 0);
+        assert!(!Ledger::has_access(purged.invoice_hash, &1));
+
+        // Counters, indices, balance and deposit follow
+        assert_eq!(Ledger::client_invoice_count(2), 2);
+        let remaining: Vec<u64> = Ledger::get_client_invoices(&2).iter().map(|i| i.id).collect();
+        assert_eq!(remaining, vec![0, 2]);
+        let by_creator: Vec<u64> = Ledger::get_creator_invoices(&1).iter().map(|i| i.id).collect();
+        assert_eq!(by_creator, vec![0, 2]);
+        assert_eq!(Ledger::get_outstanding_balance(&2), 100 + 102);
+        assert_eq!(Ledger::invoice_deposit(1), None);
+        assert_eq!(Balances::reserved_balance(1), 100);
+
+        assert_noop!(
+            Ledger::purge_invoice(RuntimeOrigin::root(), 2, 1, false),
+            Error::<Test>::InvoiceNotFound
+        );
+    });
+}
+
+#[test]
+fn purging_a_settled_invoice_requires_force() {
+    new_test_ext().execute_with(|| {
+        create_invoices(2, 1);
+        let hash = Ledger::get_invoice(&2, 0).unwrap().invoice_hash;
+        assert_ok!(Ledger::pay_invoice(RuntimeOrigin::signed(2), 1, 0));
+
+        assert_noop!(
+            Ledger::purge_invoice(RuntimeOrigin::root(), 2, 0, false),
+            Error::<Test>::PaidInvoiceRequiresForce
+        );
+
+        assert_ok!(Ledger::purge_invoice(RuntimeOrigin::root(), 2, 0, true));
+        assert_eq!(Ledger::get_invoice_by_hash(hash), None);
+        assert_eq!(Ledger::client_invoice_count(2), 0);
+        assert!(!Ledger::verify_invoice_hash(&2, 0));
+    });
+}
+
+#[test]
+fn disputed_invoice_is_only_purged_once_the_dispute_is_resolved() {
+    use crate::DisputeResolution;
+
+    new_test_ext().execute_with(|| {
+        create_invoices(2, 1);
+        assert_ok!(Ledger::dispute_invoice(RuntimeOrigin::signed(2), 1, 0, b"Wrong".to_vec()));
+
+        // Purging would drop the dispute without telling the handler
+        assert_noop!(
+            Ledger::purge_invoice(RuntimeOrigin::root(), 2, 0, true),
+            Error::<Test>::InvoiceDisputed
+        );
+        assert_eq!(DisputeNotifications::get(), vec![(0, true)]);
+
+        assert_ok!(Ledger::resolve_dispute(
+            RuntimeOrigin::root(),
+            2,
+            0,
+            DisputeResolution::Cancelled
+        ));
+        assert_eq!(DisputeNotifications::get(), vec![(0, true), (0, false)]);
+        assert_ok!(Ledger::purge_invoice(RuntimeOrigin::root(), 2, 0, false));
+        assert_eq!(Ledger::get_invoice(&2, 0), None);
+    });
+}
+
+#[test]
+fn challenged_invoice_cannot_be_paid_and_concession_cancels_it() {
+    use crate::ChallengeOutcome;
+
+    new_test_ext().execute_with(|| {
+        InvoiceDeposit::set(50);
+        create_invoices(2, 1);
+
+        assert_noop!(
+            Ledger::challenge_invoice(RuntimeOrigin::signed(1), 2, 0, [7; 32]),
+            Error::<Test>::CannotChallengeOwnInvoice
+        );
+        assert_ok!(Ledger::challenge_invoice(RuntimeOrigin::signed(3), 2, 0, [7; 32]));
+        System::assert_has_event(
+            Event::InvoiceChallenged {
+                invoice_id: 0,
+                client: 2,
+                challenger: 3,
+                evidence_hash: [7; 32],
+                bond: 30,
+                timeout_at: 21,
+            }
+            .into(),
+        );
+        assert_eq!(Ledger::get_invoice(&2, 0).unwrap().status, InvoiceStatus::Challenged);
+        assert_eq!(Balances::reserved_balance(3), 30);
+
+        // Neither payment nor the creator's own cancellation gets past the challenge
+        assert_noop!(
+            Ledger::pay_invoice(RuntimeOrigin::signed(2), 1, 0),
+            Error::<Test>::InvoiceChallenged
+        );
+        assert_noop!(
+            Ledger::cancel_invoice(RuntimeOrigin::signed(1), 2, 0),
+            Error::<Test>::InvoiceChallenged
+        );
+        assert_noop!(
+            Ledger::request_cancellation(RuntimeOrigin::signed(1), 2, 0, [1; 32]),
+            Error::<Test>::InvoiceChallenged
+        );
+        assert_noop!(
+            Ledger::challenge_invoice(RuntimeOrigin::signed(4), 2, 0, [8; 32]),
+            Error::<Test>::InvoiceChallenged
+        );
+
+        assert_noop!(
+            Ledger::concede_challenge(RuntimeOrigin::signed(3), 2, 0),
+            Error::<Test>::NotInvoiceCreator
+        );
+        assert_ok!(Ledger::concede_challenge(RuntimeOrigin::signed(1), 2, 0));
+        System::assert_has_event(
+            Event::ChallengeClosed {
+                invoice_id: 0,
+                client: 2,
+                challenger: 3,
+                outcome: ChallengeOutcome::Conceded,
+            }
+            .into(),
+        );
+        System::assert_has_event(Event::InvoiceCancelled { invoice_id: 0, client: 2 }.into());
+
+        // The bond is returned and the conceding creator keeps the deposit
+        assert_eq!(Ledger::get_invoice(&2, 0).unwrap().status, InvoiceStatus::Cancelled);
+        assert_eq!(Ledger::invoice_challenge(2, 0), None);
+        assert_eq!(Balances::free_balance(3), INITIAL_BALANCE);
+        assert_eq!(Balances::free_balance(1), INITIAL_BALANCE);
+        assert_eq!(Ledger::get_outstanding_balance(&2), 0);
+    });
+}
+
+#[test]
+fn upheld_challenge_awards_the_creation_deposit_to_the_challenger() {
+    new_test_ext().execute_with(|| {
+        InvoiceDeposit::set(50);
+        create_invoices(2, 1);
+        assert_ok!(Ledger::challenge_invoice(RuntimeOrigin::signed(3), 2, 0, [7; 32]));
+
+        assert_noop!(
+            Ledger::resolve_challenge(RuntimeOrigin::signed(2), 2, 0, true),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_ok!(Ledger::resolve_challenge(RuntimeOrigin::root(), 2, 0, true));
+        System::assert_has_event(
+            Event::InvoiceDepositAwarded { invoice_id: 0, creator: 1, challenger: 3, amount: 50 }
+                .into(),
+        );
+        System::assert_has_event(Event::InvoiceCancelled { invoice_id: 0, client: 2 }.into());
+
+        assert_eq!(Ledger::get_invoice(&2, 0).unwrap().status, InvoiceStatus::Cancelled);
+        assert_eq!(Balances::reserved_balance(3), 0);
+        assert_eq!(Balances::free_balance(3), INITIAL_BALANCE + 50);
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(Balances::free_balance(1), INITIAL_BALANCE - 50);
+        assert_eq!(Ledger::invoice_deposit(0), None);
+
+        assert_noop!(
+            Ledger::resolve_challenge(RuntimeOrigin::root(), 2, 0, true),
+            Error::<Test>::InvoiceNotChallenged
+        );
+    });
+}
+
+#[test]
+fn dismissed_challenge_pays_the_bond_to_the_creator_and_reinstates_the_invoice() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Ledger::create_invoice(
+            RuntimeOrigin::signed(1),
+            2,
+            1000u128,
+            0,
+            0,
+            b"Net 30".to_vec(),
+            Some(10),
+            None,
+            vec![],
+            None
+        ));
+        assert_ok!(Ledger::challenge_invoice(RuntimeOrigin::signed(3), 2, 0, [7; 32]));
+
+        // The due sweep leaves the challenged invoice alone
+        System::set_block_number(10);
+        Ledger::on_initialize(10);
+        assert_eq!(Ledger::get_invoice(&2, 0).unwrap().status, InvoiceStatus::Challenged);
+
+        assert_ok!(Ledger::resolve_challenge(RuntimeOrigin::root(), 2, 0, false));
+        System::assert_has_event(
+            Event::ChallengeBondSlashed { invoice_id: 0, challenger: 3, creator: 1, amount: 30 }
+                .into(),
+        );
+        System::assert_has_event(Event::InvoiceOverdue { invoice_id: 0, client: 2 }.into());
+        assert_eq!(Balances::free_balance(3), INITIAL_BALANCE - 30);
+        assert_eq!(Balances::free_balance(1), INITIAL_BALANCE + 30);
+
+        // Past its due block, the reinstated invoice is overdue and payable again
+        assert_eq!(Ledger::get_invoice(&2, 0).unwrap().status, InvoiceStatus::Overdue);
+        assert_ok!(Ledger::pay_invoice(RuntimeOrigin::signed(2), 1, 0));
+
+        // Its timeout entry finds nothing left to uphold
+        System::set_block_number(21);
+        Ledger::on_initialize(21);
+        assert_eq!(Ledger::get_invoice(&2, 0).unwrap().status, InvoiceStatus::Paid);
+        assert!(Ledger::challenge_timeouts(21).is_empty());
+    });
+}
+
+#[test]
+fn unanswered_challenge_is_upheld_at_its_timeout() {
+    use crate::ChallengeOutcome;
+
+    new_test_ext().execute_with(|| {
+        InvoiceDeposit::set(50);
+        create_invoices(2, 3);
+        assert_ok!(Ledger::challenge_invoice(RuntimeOrigin::signed(3), 2, 0, [7; 32]));
+        assert_ok!(Ledger::challenge_invoice(RuntimeOrigin::signed(4), 2, 1, [8; 32]));
+        // `MaxChallengesPerBlock` is 2 in the mock
+        assert_noop!(
+            Ledger::challenge_invoice(RuntimeOrigin::signed(5), 2, 2, [9; 32]),
+            Error::<Test>::TooManyChallengesTimingOut
+        );
+        assert_eq!(Balances::reserved_balance(5), 0);
+        assert_eq!(Ledger::get_invoice(&2, 2).unwrap().status, InvoiceStatus::Pending);
+
+        System::set_block_number(20);
+        Ledger::on_initialize(20);
+        assert_eq!(Ledger::get_invoice(&2, 0).unwrap().status, InvoiceStatus::Challenged);
+
+        System::set_block_number(21);
+        Ledger::on_initialize(21);
+        for (invoice_id, challenger) in [(0, 3), (1, 4)] {
+            System::assert_has_event(
+                Event::ChallengeClosed {
+                    invoice_id,
+                    client: 2,
+                    challenger,
+                    outcome: ChallengeOutcome::TimedOut,
+                }
+                .into(),
+            );
+            assert_eq!(
+                Ledger::get_invoice(&2, invoice_id).unwrap().status,
+                InvoiceStatus::Cancelled
+            );
+            assert_eq!(Balances::free_balance(challenger), INITIAL_BALANCE + 50);
+        }
+        assert!(Ledger::challenge_timeouts(21).is_empty());
+        assert_eq!(Ledger::invoice_challenge(2, 0), None);
+    });
+}
+
+#[test]
+fn failed_challenge_timeout_is_rolled_back_and_reported() {
+    new_test_ext().execute_with(|| {
+        InvoiceDeposit::set(50);
+        create_invoices(2, 1);
+        assert_ok!(Ledger::challenge_invoice(RuntimeOrigin::signed(3), 2, 0, [7; 32]));
+        let bond = Balances::reserved_balance(3);
+
+        // An invoice that can no longer be cancelled fails the close after the bond and
+        // deposit were already settled
+        crate::Invoices::<Test>::mutate(2, 0, |invoice| {
+            invoice.as_mut().unwrap().status = InvoiceStatus::Paid
+        });
+
+        System::set_block_number(21);
+        Ledger::on_initialize(21);
+        System::assert_last_event(
+            Event::ChallengeTimeoutFailed {
+                invoice_id: 0,
+                client: 2,
+                error: Error::<Test>::InvoiceAlreadyPaid.into(),
+            }
+            .into(),
+        );
+        assert!(!System::events().iter().any(|record| matches!(
+            record.event,
+            RuntimeEvent::Ledger(Event::ChallengeClosed { .. })
+        )));
+
+        // Nothing of the attempt is kept
+        assert!(Ledger::invoice_challenge(2, 0).is_some());
+        assert_eq!(Balances::reserved_balance(3), bond);
+        assert_eq!(Balances::reserved_balance(1), 50);
+        assert_eq!(Ledger::invoice_deposit(0), Some(50));
+    });
+}
+
+#[test]
+fn offchain_worker_posts_invoices_created_in_the_block() {
+    use crate::webhook::{PendingNotification, WEBHOOK_QUEUE_KEY, WEBHOOK_URL_KEY};
+    use codec::Decode;
+    use sp_core::offchain::{
+        testing::{PendingRequest, TestOffchainExt},
+        OffchainDbExt, OffchainWorkerExt, StorageKind,
+    };
+
+    let mut ext = new_test_ext();
+    let (offchain, state) = TestOffchainExt::new();
+    ext.register_extension(OffchainWorkerExt::new(offchain.clone()));
+    ext.register_extension(OffchainDbExt::new(offchain));
+
+    ext.execute_with(|| {
+        // Invoices created while the webhook is disabled are not indexed
+        create_invoices(2, 1);
+        assert!(crate::NewInvoices::<Test>::get().is_empty());
+
+        InvoiceWebhook::set(true);
+        create_invoices(3, 1);
+        assert_eq!(crate::NewInvoices::<Test>::get().to_vec(), vec![(3, 1)]);
+
+        // Without an endpoint nothing is sent or queued
+        Ledger::offchain_worker(1);
+        assert!(state.read().requests.is_empty());
+
+        sp_io::offchain::local_storage_set(
+            StorageKind::PERSISTENT,
+            WEBHOOK_URL_KEY,
+            b"http://127.0.0.1:8000/chain/invoices",
+        );
+        let invoice = Ledger::get_invoice(&3, 1).unwrap();
+        let body = format!(
+            "{{\"id\":1,\"client\":\"0x0300000000000000\",\"amount\":100,\"hash\":\"0x{}\",\
+             \"block\":1}}",
+            sp_core::hexdisplay::HexDisplay::from(&invoice.invoice_hash)
+        );
+        state.write().expect_request(PendingRequest {
+            method: "POST".into(),
+            uri: "http://127.0.0.1:8000/chain/invoices".into(),
+            headers: vec![("Content-Type".into(), "application/json".into())],
+            body: body.into_bytes(),
+            response: Some(Vec::new()),
+            sent: true,
+            ..Default::default()
+        });
+        Ledger::offchain_worker(1);
+
+        // Delivered, so nothing is left to retry
+        let queue = sp_io::offchain::local_storage_get(StorageKind::PERSISTENT, WEBHOOK_QUEUE_KEY)
+            .unwrap();
+        assert!(Vec::<PendingNotification>::decode(&mut &queue[..]).unwrap().is_empty());
+
+        // The next block starts with an empty index
+        System::set_block_number(2);
+        Ledger::on_initialize(2);
+        assert!(crate::NewInvoices::<Test>::get().is_empty());
+    });
+}
+
+#[test]
+fn failed_webhook_deliveries_are_retried_a_bounded_number_of_times() {
+    use crate::webhook::{deliver_queue, PendingNotification, MAX_DELIVERY_ATTEMPTS};
+    use sp_runtime::offchain::http;
+
+    let pending = |payload: &[u8], attempts| PendingNotification {
+        payload: payload.to_vec(),
+        attempts,
+    };
+    let mut queue = vec![pending(b"ok", 0), pending(b"down", 0)];
+
+    let mut posted = Vec::new();
+    for attempt in 1..MAX_DELIVERY_ATTEMPTS {
+        queue = deliver_queue(queue, |payload| {
+            posted.push(payload.to_vec());
+            if payload == b"ok" {
+                Ok(())
+            } else {
+                Err(http::Error::IoError)
+            }
+        });
+        assert_eq!(queue, vec![pending(b"down", attempt)]);
+    }
+    // Delivered notifications are not posted again
+    assert_eq!(posted.iter().filter(|payload| payload.as_slice() == b"ok").count(), 1);
+
+    // The last allowed attempt fails too and the notification is dropped
+    assert!(deliver_queue(queue, |_| Err(http::Error::DeadlineReached)).is_empty());
+}
+
+#[test]
+fn unsigned_link_confirmation_is_validated() {
+    use frame_support::{pallet_prelude::*, unsigned::ValidateUnsigned};
+
+    new_test_ext().execute_with(|| {
+        create_invoices(2, 2);
+        let hash_of = |invoice_id| Ledger::get_invoice(&2, invoice_id).unwrap().invoice_hash;
+        let validate = |invoice_id, proof_hash| {
+            <Ledger as ValidateUnsigned>::validate_unsigned(
+                TransactionSource::External,
+                &crate::Call::confirm_offchain_link {
+                    invoice_id,
+                    django_record_id: 500,
+                    proof_hash,
+                },
+            )
+        };
+
+        // The proof must be the hash of this very invoice
+        assert_eq!(validate(0, [9; 32]), InvalidTransaction::BadProof.into());
+        assert_eq!(validate(0, hash_of(1)), InvalidTransaction::BadProof.into());
+        assert_eq!(validate(7, hash_of(0)), InvalidTransaction::BadProof.into());
+
+        let valid = validate(0, hash_of(0)).unwrap();
+        assert_eq!(valid.priority, 100);
+        assert_eq!(valid.longevity, 10);
+        // Distinct tags per invoice, so the pool keeps one confirmation for each
+        assert_ne!(valid.provides, validate(1, hash_of(1)).unwrap().provides);
+
+        // Only the link confirmation goes through unsigned
+        assert_eq!(
+            <Ledger as ValidateUnsigned>::validate_unsigned(
+                TransactionSource::External,
+                &crate::Call::cancel_invoice { client: 2, invoice_id: 0 },
+            ),
+            InvalidTransaction::Call.into()
+        );
+
+        // A confirmed invoice is stale for the pool
+        assert_ok!(Ledger::confirm_offchain_link(RuntimeOrigin::none(), 0, 500, hash_of(0)));
+        assert_eq!(validate(0, hash_of(0)), InvalidTransaction::Stale.into());
+    });
+}
+
+#[test]
+fn confirm_offchain_link_records_the_link_once() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(3);
+        create_invoices(2, 1);
+        let hash = Ledger::get_invoice(&2, 0).unwrap().invoice_hash;
+
+        assert_noop!(
+            Ledger::confirm_offchain_link(RuntimeOrigin::signed(1), 0, 500, hash),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_noop!(
+            Ledger::confirm_offchain_link(RuntimeOrigin::none(), 0, 500, [9; 32]),
+            Error::<Test>::LinkProofMismatch
+        );
+
+        assert_ok!(Ledger::confirm_offchain_link(RuntimeOrigin::none(), 0, 500, hash));
+        System::assert_last_event(
+            Event::OffchainLinkConfirmed {
+                invoice_id: 0,
+                django_record_id: 500,
+                invoice_hash: hash,
+            }
+            .into(),
+        );
+        assert_eq!(
+            Ledger::offchain_link(0),
+            Some(crate::OffchainLink { django_record_id: 500, confirmed_at: 3 })
+        );
+
+        // Replays and competing confirmations are rejected
+        assert_noop!(
+            Ledger::confirm_offchain_link(RuntimeOrigin::none(), 0, 501, hash),
+            Error::<Test>::OffchainLinkAlreadyConfirmed
+        );
+
+        // Purging the invoice drops its link
+        assert_ok!(Ledger::purge_invoice(RuntimeOrigin::root(), 2, 0, false));
+        assert_eq!(Ledger::offchain_link(0), None);
+    });
+}
+
+#[test]
+fn creator_and_client_attach_documents() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(4);
+        create_invoices(2, 1);
+        let hash = Ledger::get_invoice(&2, 0).unwrap().invoice_hash;
+
+        assert_ok!(Ledger::attach_document(
+            RuntimeOrigin::signed(1),
+            2,
+            0,
+            [1; 32],
+            b"invoice-pdf".to_vec()
+        ));
+        System::assert_last_event(
+            Event::DocumentAttached { invoice_id: 0, doc_hash: [1; 32] }.into(),
+        );
+        assert_ok!(Ledger::attach_document(
+            RuntimeOrigin::signed(2),
+            2,
+            0,
+            [2; 32],
+            b"delivery-note".to_vec()
+        ));
+
+        let attachments = Ledger::get_attachments(&2, 0);
+        assert_eq!(
+            attachments.iter().map(|a| (a.doc_hash, a.attached_by)).collect::<Vec<_>>(),
+            vec![([1; 32], 1), ([2; 32], 2)]
+        );
+        assert_eq!(attachments[1].label.to_vec(), b"delivery-note".to_vec());
+        assert_eq!(attachments[1].attached_at, 4);
+        // Attachments live beside the invoice and leave its hash alone
+        assert_eq!(Ledger::get_invoice(&2, 0).unwrap().invoice_hash, hash);
+        assert!(Ledger::verify_invoice_hash(&2, 0));
+    });
+}
+
+#[test]
+fn attach_document_is_restricted_and_bounded() {
+    new_test_ext().execute_with(|| {
+        create_invoices(2, 1);
+
+        assert_noop!(
+            Ledger::attach_document(RuntimeOrigin::signed(3), 2, 0, [1; 32], vec![]),
+            Error::<Test>::NotInvoiceParty
+        );
+        assert_noop!(
+            Ledger::attach_document(RuntimeOrigin::signed(1), 2, 7, [1; 32], vec![]),
+            Error::<Test>::InvoiceNotFound
+        );
+        assert_noop!(
+            Ledger::attach_document(RuntimeOrigin::signed(1), 2, 0, [1; 32], vec![b'x'; 33]),
+            Error::<Test>::AttachmentLabelTooLong
+        );
+
+        assert_ok!(Ledger::attach_document(RuntimeOrigin::signed(1), 2, 0, [1; 32], vec![]));
+        assert_noop!(
+            Ledger::attach_document(RuntimeOrigin::signed(2), 2, 0, [1; 32], vec![]),
+            Error::<Test>::DuplicateAttachment
+        );
+        assert_ok!(Ledger::attach_document(RuntimeOrigin::signed(2), 2, 0, [2; 32], vec![]));
+        // MaxAttachmentsPerInvoice is 2 in the mock
+        assert_noop!(
+            Ledger::attach_document(RuntimeOrigin::signed(1), 2, 0, [3; 32], vec![]),
+            Error::<Test>::TooManyAttachments
+        );
+
+        // Purging the invoice drops its attachments
+        assert_ok!(Ledger::purge_invoice(RuntimeOrigin::root(), 2, 0, false));
+        assert!(Ledger::get_attachments(&2, 0).is_empty());
+    });
+}
+
+#[test]
+fn only_the_client_can_accept_an_invoice() {
+    new_test_ext().execute_with(|| {
+        create_invoices(2, 1);
+
+        // Invoices are found under their client, so nobody else can answer them
+        for other in [1, 3] {
+            assert_noop!(
+                Ledger::accept_invoice(RuntimeOrigin::signed(other), 1, 0),
+                Error::<Test>::InvoiceNotFound
+            );
+        }
+        assert_noop!(
+            Ledger::accept_invoice(RuntimeOrigin::signed(2), 3, 0),
+            Error::<Test>::CreatorMismatch
+        );
+
+        assert_ok!(Ledger::accept_invoice(RuntimeOrigin::signed(2), 1, 0));
+        assert_eq!(Ledger::get_invoice(&2, 0).unwrap().status, InvoiceStatus::Accepted);
+        System::assert_last_event(Event::InvoiceAccepted { invoice_id: 0, client: 2 }.into());
+
+        assert_noop!(
+            Ledger::accept_invoice(RuntimeOrigin::signed(2), 1, 0),
+            Error::<Test>::InvoiceAlreadyAccepted
+        );
+        assert_noop!(
+            Ledger::reject_invoice(RuntimeOrigin::signed(2), 1, 0, b"Too late".to_vec()),
+            Error::<Test>::InvoiceAlreadyAccepted
+        );
+    });
+}
+
+#[test]
+fn rejected_invoice_is_written_off() {
+    new_test_ext().execute_with(|| {
+        create_invoices(2, 1);
+        assert_eq!(Ledger::get_outstanding_balance(&2), 100);
+
+        assert_noop!(
+            Ledger::reject_invoice(RuntimeOrigin::signed(3), 1, 0, b"Not mine".to_vec()),
+            Error::<Test>::InvoiceNotFound
+        );
+        // MaxMetadataLength is 1024 in the mock
+        assert_noop!(
+            Ledger::reject_invoice(RuntimeOrigin::signed(2), 1, 0, vec![b'x'; 1025]),
+            Error::<Test>::MetadataTooLong
+        );
+
+        assert_ok!(Ledger::reject_invoice(RuntimeOrigin::signed(2), 1, 0, b"Wrong rate".to_vec()));
+        assert_eq!(Ledger::get_invoice(&2, 0).unwrap().status, InvoiceStatus::Rejected);
+        assert_eq!(Ledger::get_outstanding_balance(&2), 0);
+        System::assert_last_event(
+            Event::InvoiceRejected {
+                invoice_id: 0,
+                client: 2,
+                reason: b"Wrong rate".to_vec().try_into().unwrap(),
+            }
+            .into(),
+        );
+
+        assert_noop!(
+            Ledger::pay_invoice(RuntimeOrigin::signed(2), 1, 0),
+            Error::<Test>::InvoiceRejected
+        );
+        assert_noop!(
+            Ledger::accept_invoice(RuntimeOrigin::signed(2), 1, 0),
+            Error::<Test>::InvoiceRejected
+        );
+    });
+}
+
+#[test]
+fn required_acceptance_blocks_payment_until_accepted() {
+    new_test_ext().execute_with(|| {
+        RequireAcceptance::set(true);
+        create_invoices(2, 1);
+
+        assert_noop!(
+            Ledger::pay_invoice(RuntimeOrigin::signed(2), 1, 0),
+            Error::<Test>::InvoiceNotAccepted
+        );
+        assert_noop!(
+            Ledger::record_payment(RuntimeOrigin::signed(2), 2, 0, 40),
+            Error::<Test>::InvoiceNotAccepted
+        );
+
+        assert_ok!(Ledger::accept_invoice(RuntimeOrigin::signed(2), 1, 0));
+        assert_ok!(Ledger::record_payment(RuntimeOrigin::signed(2), 2, 0, 40));
+        assert_ok!(Ledger::pay_invoice(RuntimeOrigin::signed(2), 1, 0));
+
+        let invoice = Ledger::get_invoice(&2, 0).unwrap();
+        assert_eq!(invoice.status, InvoiceStatus::Paid);
+        assert_eq!(invoice.paid_amount, 100);
+        assert_eq!(Balances::free_balance(2), INITIAL_BALANCE - 100);
+    });
+}
+
+/// `PaymentsDigest` events deposited so far
+fn payment_digests() -> Vec<(u32, u128, [u8; 32])> {
+    System::events()
+        .into_iter()
+        .filter_map(|record| match record.event {
+            RuntimeEvent::Ledger(Event::PaymentsDigest { count, total_amount, digest_hash }) => {
+                Some((count, total_amount, digest_hash))
+            },
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn payments_in_a_block_emit_one_digest() {
+    use codec::Encode;
+    use sp_io::hashing::sha2_256;
+
+    new_test_ext().execute_with(|| {
+        create_invoices(2, 3);
+
+        assert_ok!(Ledger::pay_invoice(RuntimeOrigin::signed(2), 1, 0));
+        assert_ok!(Ledger::record_payment(RuntimeOrigin::signed(3), 2, 1, 40));
+        assert_ok!(Ledger::pay_invoice(RuntimeOrigin::signed(2), 1, 2));
+        assert!(payment_digests().is_empty());
+
+        Ledger::on_finalize(1);
+
+        // The digest chains the payments in the order they were made
+        let digest = [(0u64, 100u128), (1, 40), (2, 102)]
+            .iter()
+            .fold([0u8; 32], |digest, (invoice_id, amount)| {
+                sha2_256(&[&digest[..], &invoice_id.to_le_bytes(), &amount.encode()[..]].concat())
+            });
+        assert_eq!(payment_digests(), vec![(3, 242, digest)]);
+        assert!(!crate::PaymentsThisBlock::<Test>::exists());
+    });
+}
+
+#[test]
+fn block_without_payments_emits_no_digest() {
+    new_test_ext().execute_with(|| {
+        create_invoices(2, 1);
+
+        Ledger::on_finalize(1);
+
+        assert!(payment_digests().is_empty());
+        assert!(!crate::PaymentsThisBlock::<Test>::exists());
+    });
+}
+
+/// Create invoice 0 from creator 1 to client 2, due at block 5
+fn create_due_invoice(amount: u128, discount: u128) {
+    assert_ok!(Ledger::create_invoice(
+        RuntimeOrigin::signed(1),
+        2,
+        amount,
+        0,
+        discount,
+        b"Net 5".to_vec(),
+        Some(5),
+        None,
+        vec![],
+        None
+    ));
+}
+
+#[test]
+fn flat_late_fee_accrues_per_full_period() {
+    new_test_ext().execute_with(|| {
+        LateFeePeriod::set(10);
+        LateFeePerPeriod::set(LateFee::Flat(5));
+        create_due_invoice(100, 0);
+
+        for (block, due) in [(5, 100), (14, 100), (15, 105), (34, 110), (45, 120)] {
+            System::set_block_number(block);
+            assert_eq!(Ledger::get_amount_due(&2, 0), Some(due), "at block {}", block);
+        }
+        // Only queried so far, nothing charged
+        assert_eq!(Ledger::get_invoice(&2, 0).unwrap().accrued_late_fee, 0);
+        assert_eq!(Ledger::get_outstanding_balance(&2), 100);
+
+        // A payment charges the fee first
+        assert_ok!(Ledger::record_payment(RuntimeOrigin::signed(2), 2, 0, 20));
+        System::assert_has_event(
+            Event::LateFeeAccrued { invoice_id: 0, client: 2, accrued_late_fee: 20 }.into(),
+        );
+        assert_eq!(Ledger::get_invoice(&2, 0).unwrap().accrued_late_fee, 20);
+        assert_eq!(Ledger::get_outstanding_balance(&2), 100);
+
+        // Settling pays the rest of the amount and the fee grown since
+        System::set_block_number(55);
+        assert_ok!(Ledger::pay_invoice(RuntimeOrigin::signed(2), 1, 0));
+        System::assert_has_event(
+            Event::InvoicePaid { invoice_id: 0, payer: 2, payee: 1, amount: 125 }.into(),
+        );
+        assert_eq!(Balances::free_balance(2), INITIAL_BALANCE - 125);
+        assert_eq!(Ledger::get_outstanding_balance(&2), 0);
+        assert_eq!(Ledger::get_creator_stats(&1), (125, 125));
+
+        // Paid invoices accrue nothing more
+        System::set_block_number(100);
+        assert_eq!(Ledger::get_amount_due(&2, 0), Some(0));
+    });
+}
+
+#[test]
+fn per_mille_late_fee_is_taken_on_the_net_amount() {
+    new_test_ext().execute_with(|| {
+        LateFeePeriod::set(10);
+        LateFeePerPeriod::set(LateFee::PerMille(15));
+        // Net amount 1000, so 15 per period
+        create_due_invoice(2000, 1000);
+
+        System::set_block_number(35);
+        assert_eq!(Ledger::get_amount_due(&2, 0), Some(1000 + 3 * 15));
+        assert_ok!(Ledger::pay_invoice(RuntimeOrigin::signed(2), 1, 0));
+        assert_eq!(Balances::free_balance(2), INITIAL_BALANCE - 1045);
+    });
+}
+
+#[test]
+fn late_fee_overflow_is_an_error() {
+    new_test_ext().execute_with(|| {
+        LateFeePeriod::set(10);
+        LateFeePerPeriod::set(LateFee::Flat(u128::MAX / 2 + 1));
+        create_due_invoice(100, 0);
+
+        // One period fits, two overflow
+        System::set_block_number(15);
+        assert_eq!(Ledger::get_amount_due(&2, 0), Some(u128::MAX / 2 + 101));
+        System::set_block_number(25);
+        assert_eq!(Ledger::get_amount_due(&2, 0), None);
+        assert_noop!(
+            Ledger::pay_invoice(RuntimeOrigin::signed(2), 1, 0),
+            Error::<Test>::ArithmeticOverflow
+        );
+
+        // Per mille of an amount too large to multiply
+        LateFeePerPeriod::set(LateFee::PerMille(1000));
+        assert_ok!(Ledger::create_invoice(
+            RuntimeOrigin::signed(1),
+            3,
+            u128::MAX,
+            0,
+            0,
+            b"Huge".to_vec(),
+            Some(5),
+            None,
+            vec![],
+            None
+        ));
+        assert_eq!(Ledger::get_amount_due(&3, 1), None);
+    });
+}
+
+#[test]
+fn late_fees_need_a_due_block_and_a_period() {
+    new_test_ext().execute_with(|| {
+        LateFeePerPeriod::set(LateFee::Flat(5));
+        create_due_invoice(100, 0);
+        create_invoices(2, 1);
+
+        // Disabled while LateFeePeriod is zero
+        System::set_block_number(50);
+        assert_eq!(Ledger::get_amount_due(&2, 0), Some(100));
+
+        // Invoices without a due block are never late
+        LateFeePeriod::set(10);
+        assert_eq!(Ledger::get_amount_due(&2, 0), Some(100 + 4 * 5));
+        assert_eq!(Ledger::get_amount_due(&2, 1), Some(100));
+        assert_eq!(Ledger::get_amount_due(&2, 9), None);
+    });
+}
+
+#[test]
+fn feature_bits_follow_the_config_switches() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(Ledger::feature_bits(), FeatureFlags::OPEN_INVOICE_CREATION);
+
+        // A runtime configured the other way round
+        OpenCreation::set(false);
+        RequireAcceptance::set(true);
+        InvoiceWebhook::set(true);
+        InvoiceDeposit::set(100);
+        LateFeePeriod::set(10);
+        assert_eq!(
+            Ledger::feature_bits(),
+            FeatureFlags::INVOICE_ACCEPTANCE |
+                FeatureFlags::INVOICE_WEBHOOK |
+                FeatureFlags::INVOICE_DEPOSITS |
+                FeatureFlags::LATE_FEES
+        );
+    });
+}
+
+#[test]
+fn abandoned_invoice_deposit_goes_to_the_client() {
+    new_test_ext().execute_with(|| {
+        InvoiceDeposit::set(50);
+        AbandonThreshold::set(100);
+        create_invoices(2, 1);
+        assert_eq!(Ledger::get_invoice(&2, 0).unwrap().last_activity, 1);
+
+        System::set_block_number(100);
+        assert_noop!(
+            Ledger::claim_abandoned_invoice(RuntimeOrigin::signed(2), 0),
+            Error::<Test>::InvoiceNotAbandoned
+        );
+
+        System::set_block_number(101);
+        assert_ok!(Ledger::claim_abandoned_invoice(RuntimeOrigin::signed(2), 0));
+        System::assert_has_event(
+            Event::InvoiceAbandoned { invoice_id: 0, client: 2, creator: 1, compensation: 50 }
+                .into(),
+        );
+        System::assert_last_event(Event::InvoiceCancelled { invoice_id: 0, client: 2 }.into());
+
+        // The reserved deposit moved to the client's free balance, nothing was released
+        assert_eq!(Ledger::get_invoice(&2, 0).unwrap().status, InvoiceStatus::Cancelled);
+        assert_eq!(Ledger::invoice_deposit(0), None);
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(Balances::free_balance(1), INITIAL_BALANCE - 50);
+        assert_eq!(Balances::free_balance(2), INITIAL_BALANCE + 50);
+        assert_eq!(Ledger::get_outstanding_balance(&2), 0);
+        assert!(!System::events().iter().any(|record| matches!(
+            record.event,
+            RuntimeEvent::Ledger(Event::InvoiceDepositReleased { .. })
+        )));
+
+        // Closed now
+        assert_noop!(
+            Ledger::claim_abandoned_invoice(RuntimeOrigin::signed(2), 0),
+            Error::<Test>::InvoiceCancelled
+        );
+    });
+}
+
+#[test]
+fn creator_activity_restarts_the_abandonment_clock() {
+    new_test_ext().execute_with(|| {
+        AbandonThreshold::set(100);
+        create_invoices(2, 1);
+
+        System::set_block_number(60);
+        assert_ok!(Ledger::update_invoice_metadata(
+            RuntimeOrigin::signed(1),
+            2,
+            0,
+            b"INV-0 rev".to_vec()
+        ));
+        assert_eq!(Ledger::get_invoice(&2, 0).unwrap().last_activity, 60);
+
+        // Documents attached by the client do not count as the creator's activity
+        System::set_block_number(120);
+        assert_ok!(Ledger::attach_document(
+            RuntimeOrigin::signed(2),
+            2,
+            0,
+            [2; 32],
+            b"complaint".to_vec()
+        ));
+        assert_eq!(Ledger::get_invoice(&2, 0).unwrap().last_activity, 60);
+        assert_ok!(Ledger::attach_document(
+            RuntimeOrigin::signed(1),
+            2,
+            0,
+            [1; 32],
+            b"timesheet".to_vec()
+        ));
+        assert_eq!(Ledger::get_invoice(&2, 0).unwrap().last_activity, 120);
+
+        System::set_block_number(200);
+        assert_ok!(Ledger::request_cancellation(RuntimeOrigin::signed(1), 2, 0, [9; 32]));
+        assert_eq!(Ledger::get_invoice(&2, 0).unwrap().last_activity, 200);
+
+        System::set_block_number(299);
+        assert_noop!(
+            Ledger::claim_abandoned_invoice(RuntimeOrigin::signed(2), 0),
+            Error::<Test>::InvoiceNotAbandoned
+        );
+        // Without a deposit there is nothing to pay out, but the invoice is still closed
+        System::set_block_number(300);
+        assert_ok!(Ledger::claim_abandoned_invoice(RuntimeOrigin::signed(2), 0));
+        System::assert_has_event(
+            Event::InvoiceAbandoned { invoice_id: 0, client: 2, creator: 1, compensation: 0 }
+                .into(),
+        );
+        assert_eq!(Ledger::pending_cancellations(&2, 0), None);
+    });
+}
+
+#[test]
+fn only_invoices_awaiting_their_creator_can_be_claimed() {
+    new_test_ext().execute_with(|| {
+        create_invoices(2, 4);
+
+        // Disabled while AbandonThreshold is zero
+        System::set_block_number(1_000);
+        assert_noop!(
+            Ledger::claim_abandoned_invoice(RuntimeOrigin::signed(2), 0),
+            Error::<Test>::InvoiceNotAbandoned
+        );
+
+        AbandonThreshold::set(100);
+        assert_ok!(Ledger::accept_invoice(RuntimeOrigin::signed(2), 1, 1));
+        assert_ok!(Ledger::pay_invoice(RuntimeOrigin::signed(2), 1, 2));
+        assert_ok!(Ledger::dispute_invoice(RuntimeOrigin::signed(2), 1, 3, b"wrong".to_vec()));
+        assert_noop!(
+            Ledger::claim_abandoned_invoice(RuntimeOrigin::signed(2), 1),
+            Error::<Test>::InvoiceAlreadyAccepted
+        );
+        assert_noop!(
+            Ledger::claim_abandoned_invoice(RuntimeOrigin::signed(2), 2),
+            Error::<Test>::InvoiceAlreadyPaid
+        );
+        assert_noop!(
+            Ledger::claim_abandoned_invoice(RuntimeOrigin::signed(2), 3),
+            Error::<Test>::InvoiceDisputed
+        );
+        // Invoices are looked up under the signer, so only the client can claim
+        assert_noop!(
+            Ledger::claim_abandoned_invoice(RuntimeOrigin::signed(3), 0),
+            Error::<Test>::InvoiceNotFound
+        );
+        assert_ok!(Ledger::claim_abandoned_invoice(RuntimeOrigin::signed(2), 0));
+    });
+}
+
+#[test]
+fn assigned_invoice_is_paid_to_the_new_beneficiary() {
+    new_test_ext().execute_with(|| {
+        create_invoices(2, 1);
+        assert_eq!(Ledger::get_invoice(&2, 0).unwrap().beneficiary, 1);
+
+        System::set_block_number(5);
+        assert_ok!(Ledger::assign_invoice(RuntimeOrigin::signed(1), 2, 0, 3));
+        System::assert_last_event(Event::InvoiceAssigned { invoice_id: 0, from: 1, to: 3 }.into());
+        let invoice = Ledger::get_invoice(&2, 0).unwrap();
+        assert_eq!((invoice.created_by, invoice.beneficiary), (1, 3));
+
+        // The creator still identifies the invoice, the payment goes to the beneficiary
+        assert_ok!(Ledger::pay_invoice(RuntimeOrigin::signed(2), 1, 0));
+        assert_eq!(Balances::free_balance(2), INITIAL_BALANCE - 100);
+        assert_eq!(Balances::free_balance(3), INITIAL_BALANCE + 100);
+        assert_eq!(Balances::free_balance(1), INITIAL_BALANCE);
+        System::assert_has_event(
+            Event::InvoicePaid { invoice_id: 0, payer: 2, payee: 3, amount: 100 }.into(),
+        );
+
+        let history = Ledger::get_assignments(&2, 0);
+        assert_eq!(history.len(), 1);
+        assert_eq!((history[0].from, history[0].to, history[0].assigned_at), (1, 3, 5));
+
+        // Revenue stays with the creator
+        assert_eq!(Ledger::get_creator_stats(&1), (100, 100));
+        assert_eq!(Ledger::get_creator_stats(&3), (0, 0));
+    });
+}
+
+#[test]
+fn installments_of_an_assigned_invoice_go_to_the_beneficiary() {
+    new_test_ext().execute_with(|| {
+        create_invoices(2, 1);
+
+        assert_ok!(Ledger::record_payment(RuntimeOrigin::signed(2), 2, 0, 40));
+        assert_ok!(Ledger::assign_invoice(RuntimeOrigin::signed(1), 2, 0, 3));
+        assert_ok!(Ledger::record_payment(RuntimeOrigin::signed(2), 2, 0, 60));
+
+        assert_eq!(Balances::free_balance(1), INITIAL_BALANCE + 40);
+        assert_eq!(Balances::free_balance(3), INITIAL_BALANCE + 60);
+        assert_eq!(Ledger::get_invoice(&2, 0).unwrap().status, InvoiceStatus::Paid);
+        System::assert_has_event(
+            Event::InvoicePaid { invoice_id: 0, payer: 2, payee: 3, amount: 100 }.into(),
+        );
+    });
+}
+
+#[test]
+fn only_the_beneficiary_can_assign_an_open_invoice() {
+    new_test_ext().execute_with(|| {
+        create_invoices(2, 3);
+
+        assert_noop!(
+            Ledger::assign_invoice(RuntimeOrigin::signed(1), 2, 9, 3),
+            Error::<Test>::InvoiceNotFound
+        );
+        assert_noop!(
+            Ledger::assign_invoice(RuntimeOrigin::signed(2), 2, 0, 3),
+            Error::<Test>::NotInvoiceBeneficiary
+        );
+        assert_noop!(
+            Ledger::assign_invoice(RuntimeOrigin::signed(1), 2, 0, 1),
+            Error::<Test>::AlreadyBeneficiary
+        );
+
+        // Once assigned, the creator no longer controls the right to payment
+        assert_ok!(Ledger::assign_invoice(RuntimeOrigin::signed(1), 2, 0, 3));
+        assert_noop!(
+            Ledger::assign_invoice(RuntimeOrigin::signed(1), 2, 0, 4),
+            Error::<Test>::NotInvoiceBeneficiary
+        );
+        assert_ok!(Ledger::assign_invoice(RuntimeOrigin::signed(3), 2, 0, 4));
+        assert_eq!(Ledger::get_invoice(&2, 0).unwrap().beneficiary, 4);
+
+        assert_ok!(Ledger::pay_invoice(RuntimeOrigin::signed(2), 1, 1));
+        assert_ok!(Ledger::cancel_invoice(RuntimeOrigin::signed(1), 2, 2));
+        assert_noop!(
+            Ledger::assign_invoice(RuntimeOrigin::signed(1), 2, 1, 3),
+            Error::<Test>::InvoiceAlreadyPaid
+        );
+        assert_noop!(
+            Ledger::assign_invoice(RuntimeOrigin::signed(1), 2, 2, 3),
+            Error::<Test>::InvoiceCancelled
+        );
+    });
+}
+
+#[test]
+fn assignment_history_is_bounded() {
+    new_test_ext().execute_with(|| {
+        create_invoices(2, 1);
+
+        // The mock keeps two assignments per invoice
+        assert_ok!(Ledger::assign_invoice(RuntimeOrigin::signed(1), 2, 0, 3));
+        assert_ok!(Ledger::assign_invoice(RuntimeOrigin::signed(3), 2, 0, 4));
+        assert_noop!(
+            Ledger::assign_invoice(RuntimeOrigin::signed(4), 2, 0, 5),
+            Error::<Test>::TooManyAssignments
+        );
+        let history: Vec<_> =
+            Ledger::get_assignments(&2, 0).into_iter().map(|a| (a.from, a.to)).collect();
+        assert_eq!(history, [(1, 3), (3, 4)]);
+    });
+}
@@ -0,0 +1,208 @@
+//! Push notifications of new invoices to the Django backend.
+//!
+//! While `Config::InvoiceWebhook` is enabled, every created invoice is indexed in
+//! `NewInvoices`, which `on_initialize` clears at the start of the next block. The offchain
+//! worker of a block turns the indexed invoices into JSON payloads
+//!
+//! ```json
+//! {"id":7,"client":"0x<SCALE-encoded account>","amount":1000,"hash":"0x<hash>","block":42}
+//! ```
+//!
+//! and POSTs them to the endpoint kept under [`WEBHOOK_URL_KEY`] in persistent offchain local
+//! storage, as raw UTF-8 (e.g. set with the `offchain_localStorageSet` RPC). Nothing is
+//! queued while no endpoint is set.
+//!
+//! Deliveries that fail, or are answered with a non-2xx status, stay queued under
+//! [`WEBHOOK_QUEUE_KEY`] and are retried by the worker of the next block, until
+//! [`MAX_DELIVERY_ATTEMPTS`] attempts failed and the notification is dropped. Failures are
+//! logged under the `runtime::ledger::webhook` target. Offchain workers also run for blocks
+//! that are later retracted, so the backend treats notifications as hints and deduplicates
+//! them by invoice hash.
+
+use crate::{Config, Invoice, Invoices, NewInvoices};
+use codec::{Decode, Encode};
+use frame_support::{traits::Get, weights::Weight};
+use frame_system::pallet_prelude::BlockNumberFor;
+use sp_core::{offchain::StorageKind, ConstU32};
+use sp_runtime::{
+    offchain::{
+        http,
+        storage::StorageValueRef,
+        storage_lock::{StorageLock, Time},
+        Duration, Timestamp,
+    },
+    traits::UniqueSaturatedInto,
+};
+use sp_std::{vec, vec::Vec};
+use tidygen_primitives::writer::{ByteWriter, Overflow};
+
+/// Persistent local storage key of the endpoint URL
+pub const WEBHOOK_URL_KEY: &[u8] = b"tidygen::ledger::webhook-url";
+
+/// Persistent local storage key of the notifications awaiting delivery
+pub const WEBHOOK_QUEUE_KEY: &[u8] = b"tidygen::ledger::webhook-queue";
+
+/// Persistent local storage key of the lock keeping workers from delivering concurrently
+const WEBHOOK_LOCK_KEY: &[u8] = b"tidygen::ledger::webhook-lock";
+
+/// Delivery attempts after which a notification is dropped
+pub const MAX_DELIVERY_ATTEMPTS: u32 = 5;
+
+/// Time one worker may spend delivering, in milliseconds
+const DELIVERY_TIMEOUT_MS: u64 = 10_000;
+
+/// Maximum length of a JSON payload
+pub type MaxPayloadLength = ConstU32<512>;
+
+const LOG_TARGET: &str = "runtime::ledger::webhook";
+
+/// A notification awaiting delivery
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+pub struct PendingNotification {
+    /// JSON body to POST
+    pub payload: Vec<u8>,
+    /// Failed delivery attempts so far
+    pub attempts: u32,
+}
+
+/// Index an invoice created in the current block, if the webhook is enabled.
+///
+/// Invoices beyond `MaxWebhookInvoicesPerBlock` are not indexed and not notified; creating
+/// them still succeeds.
+pub(crate) fn index_invoice<T: Config>(client: &T::AccountId, invoice_id: u64) {
+    if !T::InvoiceWebhook::get() {
+        return;
+    }
+    if NewInvoices::<T>::try_append((client.clone(), invoice_id)).is_err() {
+        log::warn!(
+            target: LOG_TARGET,
+            "invoice {} not notified: MaxWebhookInvoicesPerBlock reached",
+            invoice_id
+        );
+    }
+}
+
+/// Drop the index of the previous block
+pub(crate) fn clear_index<T: Config>() -> Weight {
+    if !T::InvoiceWebhook::get() {
+        return Weight::zero();
+    }
+    NewInvoices::<T>::kill();
+    T::DbWeight::get().writes(1)
+}
+
+/// Queue the invoices created at `now` and deliver everything queued
+pub(crate) fn offchain_worker<T: Config>(now: BlockNumberFor<T>) {
+    if !T::InvoiceWebhook::get() {
+        return;
+    }
+    let Some(url) = sp_io::offchain::local_storage_get(StorageKind::PERSISTENT, WEBHOOK_URL_KEY)
+    else {
+        log::debug!(target: LOG_TARGET, "no webhook endpoint configured");
+        return;
+    };
+    let Ok(url) = core::str::from_utf8(&url) else {
+        log::error!(target: LOG_TARGET, "webhook endpoint is not valid UTF-8");
+        return;
+    };
+
+    let mut lock = StorageLock::<Time>::with_deadline(
+        WEBHOOK_LOCK_KEY,
+        Duration::from_millis(DELIVERY_TIMEOUT_MS),
+    );
+    // The worker of an earlier block is still delivering; its queue is picked up next block
+    let Ok(_guard) = lock.try_lock() else {
+        return;
+    };
+
+    let queue_ref = StorageValueRef::persistent(WEBHOOK_QUEUE_KEY);
+    let mut queue = queue_ref
+        .get::<Vec<PendingNotification>>()
+        .ok()
+        .flatten()
+        .unwrap_or_default();
+    for (client, invoice_id) in NewInvoices::<T>::get() {
+        // Purged in the block that created it
+        let Some(invoice) = Invoices::<T>::get(&client, invoice_id) else {
+            continue;
+        };
+        match payload(&invoice, now) {
+            Ok(payload) => queue.push(PendingNotification { payload, attempts: 0 }),
+            Err(Overflow) => {
+                log::error!(target: LOG_TARGET, "payload of invoice {} too long", invoice_id)
+            },
+        }
+    }
+    if queue.is_empty() {
+        return;
+    }
+
+    let deadline = sp_io::offchain::timestamp().add(Duration::from_millis(DELIVERY_TIMEOUT_MS));
+    let retry = deliver_queue(queue, |payload| post(url, payload, deadline));
+    queue_ref.set(&retry);
+}
+
+/// JSON body announcing `invoice`, created at `block`
+pub fn payload<T: Config>(
+    invoice: &Invoice<T>,
+    block: BlockNumberFor<T>,
+) -> Result<Vec<u8>, Overflow> {
+    let mut json = ByteWriter::<MaxPayloadLength>::new();
+    json.push_str("{\"id\":")?;
+    json.push_u64(invoice.id)?;
+    json.push_str(",\"client\":\"0x")?;
+    json.push_hex(&invoice.client.encode())?;
+    json.push_str("\",\"amount\":")?;
+    json.push_u128(invoice.amount.unique_saturated_into())?;
+    json.push_str(",\"hash\":\"0x")?;
+    json.push_hex(&invoice.invoice_hash)?;
+    json.push_str("\",\"block\":")?;
+    json.push_u64(block.unique_saturated_into())?;
+    json.push_str("}")?;
+    Ok(json.into_inner().into_inner())
+}
+
+/// Try every queued notification once and return those to retry in the next block
+pub(crate) fn deliver_queue(
+    queue: Vec<PendingNotification>,
+    mut deliver: impl FnMut(&[u8]) -> Result<(), http::Error>,
+) -> Vec<PendingNotification> {
+    queue
+        .into_iter()
+        .filter_map(|mut notification| {
+            let error = deliver(&notification.payload).err()?;
+            notification.attempts = notification.attempts.saturating_add(1);
+            if notification.attempts >= MAX_DELIVERY_ATTEMPTS {
+                log::error!(
+                    target: LOG_TARGET,
+                    "dropping invoice notification after {} failed attempts: {:?}",
+                    notification.attempts,
+                    error
+                );
+                return None;
+            }
+            log::warn!(
+                target: LOG_TARGET,
+                "invoice notification failed (attempt {}), retrying next block: {:?}",
+                notification.attempts,
+                error
+            );
+            Some(notification)
+        })
+        .collect()
+}
+
+/// POST `payload` to `url`, expecting a 2xx answer before `deadline`
+fn post(url: &str, payload: &[u8], deadline: Timestamp) -> Result<(), http::Error> {
+    let pending = http::Request::post(url, vec![payload])
+        .add_header("Content-Type", "application/json")
+        .deadline(deadline)
+        .send()
+        .map_err(|_| http::Error::IoError)?;
+    let response = pending.try_wait(deadline).map_err(|_| http::Error::DeadlineReached)??;
+    if !(200..300).contains(&response.code) {
+        log::warn!(target: LOG_TARGET, "webhook endpoint answered {}", response.code);
+        return Err(http::Error::Unknown);
+    }
+    Ok(())
+}
@@ -0,0 +1,208 @@
+//! Weights for the ledger pallet.
+//!
+//! The creation, payment and removal calls take their weight from `WeightInfo`; the others
+//! keep the flat figures on their `#[pallet::weight]` annotations. The `create_invoice` and
+//! `update_invoice_metadata` weights are a base cost plus a slope per byte of metadata,
+//! `create_invoice` also one per line item, `purge_invoice` one per invoice of the client and
+//! per access grant removed, and `archive_invoices` also one per archived invoice. Checking a
+//! creator signature is weighed on its own and an operator signature per key tried; the
+//! pallet adds them to the creation weights. These are the shapes measured by the benchmarks
+//! in `benchmarking.rs`. The figures below are estimates
+//! for reference hardware and are to be replaced by the benchmark output of the production
+//! runtime:
+//!
+//! ```text
+//! benchmark pallet --pallet pallet_ledger --extrinsic '*' --steps 3 --repeat 20
+//! ```
+
+use core::marker::PhantomData;
+use frame_support::{
+    traits::Get,
+    weights::{constants::RocksDbWeight, Weight},
+};
+
+/// Weight functions needed by the ledger pallet
+pub trait WeightInfo {
+    /// `create_invoice` with `m` bytes of metadata and `l` line items, without a signature
+    fn create_invoice(m: u32, l: u32) -> Weight;
+    /// Checking the signature of a `create_invoice` call against the creator's key
+    fn verify_creator_signature() -> Weight;
+    /// Checking an operator signature against `k` operator keys
+    fn verify_operator_signature(k: u32) -> Weight;
+    /// `pay_invoice`
+    fn pay_invoice() -> Weight;
+    /// `record_payment` of the installment that settles the invoice
+    fn record_payment() -> Weight;
+    /// `update_invoice_metadata` with `m` bytes of new metadata
+    fn update_invoice_metadata(m: u32) -> Weight;
+    /// `purge_invoice` of a client with `n` invoices, the invoice having `g` access grants
+    fn purge_invoice(n: u32, g: u32) -> Weight;
+    /// `archive_invoices` of a client with `n` invoices, archiving `a` of them with `g`
+    /// access grants in total
+    fn archive_invoices(n: u32, a: u32, g: u32) -> Weight;
+}
+
+/// Weights for the ledger pallet using the runtime's database weights
+pub struct SubstrateWeight<T>(PhantomData<T>);
+
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+    /// Reads: `AuthorizedCreators`, `ClientInvoiceCount`, `NormalizeText`, `InvoiceCount`,
+    /// `InvoiceByHash`, `DueInvoices`, `NewInvoices`, deposit balance.
+    /// Writes: `Invoices`, `InvoicesByCreator`, `ClientInvoiceIds`, `ClientInvoiceCount`,
+    /// `InvoiceCount`, `InvoiceByHash`, `OutstandingBalance`, `InvoiceDeposits`, `NewInvoices`.
+    fn create_invoice(m: u32, l: u32) -> Weight {
+        Weight::from_parts(48_000_000, 3_900)
+            // Hashing and storing the metadata
+            .saturating_add(Weight::from_parts(1_250, 1).saturating_mul(m.into()))
+            // Checking, hashing and storing each line item
+            .saturating_add(Weight::from_parts(950_000, 300).saturating_mul(l.into()))
+            .saturating_add(T::DbWeight::get().reads(8))
+            .saturating_add(T::DbWeight::get().writes(9))
+    }
+
+    /// Reads: none; an sr25519 and an ed25519 verification.
+    fn verify_creator_signature() -> Weight {
+        Weight::from_parts(96_000_000, 0)
+    }
+
+    /// Reads: `OperatorKeys`; an sr25519 verification per key.
+    fn verify_operator_signature(k: u32) -> Weight {
+        Weight::from_parts(3_000_000, 1_700)
+            .saturating_add(Weight::from_parts(48_000_000, 0).saturating_mul(k.into()))
+            .saturating_add(T::DbWeight::get().reads(1))
+    }
+
+    /// Reads: `Invoices`, `InvoiceDisputes`, `OutstandingBalance`, `CreatorStats`,
+    /// `InvoiceDeposits`, `PaymentsThisBlock`, both balances.
+    /// Writes: `Invoices`, `OutstandingBalance`, `CreatorStats`, `InvoiceDeposits`,
+    /// `PaymentsThisBlock`, both balances, deposit balance.
+    fn pay_invoice() -> Weight {
+        Weight::from_parts(64_000_000, 6_200)
+            .saturating_add(T::DbWeight::get().reads(8))
+            .saturating_add(T::DbWeight::get().writes(8))
+    }
+
+    /// Reads and writes: as `pay_invoice`.
+    fn record_payment() -> Weight {
+        Weight::from_parts(66_000_000, 6_200)
+            .saturating_add(T::DbWeight::get().reads(8))
+            .saturating_add(T::DbWeight::get().writes(8))
+    }
+
+    /// Reads: `NormalizeText`, `InvoiceOperator`, `Invoices`, `InvoiceDisputes`,
+    /// `InvoiceByHash`.
+    /// Writes: `Invoices`, `InvoiceByHash` (old and new hash).
+    fn update_invoice_metadata(m: u32) -> Weight {
+        Weight::from_parts(31_000_000, 3_900)
+            // Hashing and storing the metadata
+            .saturating_add(Weight::from_parts(1_150, 1).saturating_mul(m.into()))
+            .saturating_add(T::DbWeight::get().reads(5))
+            .saturating_add(T::DbWeight::get().writes(3))
+    }
+
+    /// Reads: `Invoices`, `InvoiceDisputes`, `ClientInvoiceCount`, `AccessGrantCount`,
+    /// `OutstandingBalance`, `CreatorStats`, `InvoiceDeposits`, `InvoiceChallenges`,
+    /// `InvoiceByHash`, deposit balance; `ClientInvoiceIds` per invoice of the client.
+    /// Writes: the invoice and every record keyed by it, `OutstandingBalance`,
+    /// `CreatorStats`, `InvoiceDeposits`, deposit balance, `ClientInvoiceCount`;
+    /// `ClientInvoiceIds` twice per invoice of the client, `AccessGrants` per grant.
+    fn purge_invoice(n: u32, g: u32) -> Weight {
+        Weight::from_parts(62_000_000, 4_200)
+            // Re-packing the client's invoice index
+            .saturating_add(Weight::from_parts(3_100_000, 2_500).saturating_mul(n.into()))
+            // Removing each access grant
+            .saturating_add(Weight::from_parts(1_600_000, 0).saturating_mul(g.into()))
+            .saturating_add(T::DbWeight::get().reads(10))
+            .saturating_add(T::DbWeight::get().writes(19))
+            .saturating_add(T::DbWeight::get().reads(1).saturating_mul(n.into()))
+            .saturating_add(T::DbWeight::get().writes(2).saturating_mul(n.into()))
+            .saturating_add(T::DbWeight::get().writes(1).saturating_mul(g.into()))
+    }
+
+    /// Reads: `ClientInvoiceCount`; `ClientInvoiceIds`, `Invoices` and `InvoiceDisputes` per
+    /// invoice of the client, `AccessGrantCount` per archived invoice.
+    /// Writes: `ClientInvoiceCount`; `ClientInvoiceIds` twice per invoice of the client, the
+    /// invoice, every record keyed by it and `ArchivedInvoiceHashes` per archived invoice,
+    /// `AccessGrants` per grant.
+    fn archive_invoices(n: u32, a: u32, g: u32) -> Weight {
+        Weight::from_parts(24_000_000, 3_600)
+            // Scanning and re-packing the client's invoice index
+            .saturating_add(Weight::from_parts(3_400_000, 2_600).saturating_mul(n.into()))
+            // Removing each archived invoice
+            .saturating_add(Weight::from_parts(9_800_000, 0).saturating_mul(a.into()))
+            // Removing each access grant
+            .saturating_add(Weight::from_parts(1_600_000, 0).saturating_mul(g.into()))
+            .saturating_add(T::DbWeight::get().reads(1))
+            .saturating_add(T::DbWeight::get().writes(1))
+            .saturating_add(T::DbWeight::get().reads(3).saturating_mul(n.into()))
+            .saturating_add(T::DbWeight::get().writes(2).saturating_mul(n.into()))
+            .saturating_add(T::DbWeight::get().reads(1).saturating_mul(a.into()))
+            .saturating_add(T::DbWeight::get().writes(14).saturating_mul(a.into()))
+            .saturating_add(T::DbWeight::get().writes(1).saturating_mul(g.into()))
+    }
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+    fn create_invoice(m: u32, l: u32) -> Weight {
+        Weight::from_parts(48_000_000, 3_900)
+            .saturating_add(Weight::from_parts(1_250, 1).saturating_mul(m.into()))
+            .saturating_add(Weight::from_parts(950_000, 300).saturating_mul(l.into()))
+            .saturating_add(RocksDbWeight::get().reads(8))
+            .saturating_add(RocksDbWeight::get().writes(9))
+    }
+
+    fn verify_creator_signature() -> Weight {
+        Weight::from_parts(96_000_000, 0)
+    }
+
+    fn verify_operator_signature(k: u32) -> Weight {
+        Weight::from_parts(3_000_000, 1_700)
+            .saturating_add(Weight::from_parts(48_000_000, 0).saturating_mul(k.into()))
+            .saturating_add(RocksDbWeight::get().reads(1))
+    }
+
+    fn pay_invoice() -> Weight {
+        Weight::from_parts(64_000_000, 6_200)
+            .saturating_add(RocksDbWeight::get().reads(8))
+            .saturating_add(RocksDbWeight::get().writes(8))
+    }
+
+    fn record_payment() -> Weight {
+        Weight::from_parts(66_000_000, 6_200)
+            .saturating_add(RocksDbWeight::get().reads(8))
+            .saturating_add(RocksDbWeight::get().writes(8))
+    }
+
+    fn update_invoice_metadata(m: u32) -> Weight {
+        Weight::from_parts(31_000_000, 3_900)
+            .saturating_add(Weight::from_parts(1_150, 1).saturating_mul(m.into()))
+            .saturating_add(RocksDbWeight::get().reads(5))
+            .saturating_add(RocksDbWeight::get().writes(3))
+    }
+
+    fn purge_invoice(n: u32, g: u32) -> Weight {
+        Weight::from_parts(62_000_000, 4_200)
+            .saturating_add(Weight::from_parts(3_100_000, 2_500).saturating_mul(n.into()))
+            .saturating_add(Weight::from_parts(1_600_000, 0).saturating_mul(g.into()))
+            .saturating_add(RocksDbWeight::get().reads(10))
+            .saturating_add(RocksDbWeight::get().writes(19))
+            .saturating_add(RocksDbWeight::get().reads(1).saturating_mul(n.into()))
+            .saturating_add(RocksDbWeight::get().writes(2).saturating_mul(n.into()))
+            .saturating_add(RocksDbWeight::get().writes(1).saturating_mul(g.into()))
+    }
+
+    fn archive_invoices(n: u32, a: u32, g: u32) -> Weight {
+        Weight::from_parts(24_000_000, 3_600)
+            .saturating_add(Weight::from_parts(3_400_000, 2_600).saturating_mul(n.into()))
+            .saturating_add(Weight::from_parts(9_800_000, 0).saturating_mul(a.into()))
+            .saturating_add(Weight::from_parts(1_600_000, 0).saturating_mul(g.into()))
+            .saturating_add(RocksDbWeight::get().reads(1))
+            .saturating_add(RocksDbWeight::get().writes(1))
+            .saturating_add(RocksDbWeight::get().reads(3).saturating_mul(n.into()))
+            .saturating_add(RocksDbWeight::get().writes(2).saturating_mul(n.into()))
+            .saturating_add(RocksDbWeight::get().reads(1).saturating_mul(a.into()))
+            .saturating_add(RocksDbWeight::get().writes(14).saturating_mul(a.into()))
+            .saturating_add(RocksDbWeight::get().writes(1).saturating_mul(g.into()))
+    }
+}
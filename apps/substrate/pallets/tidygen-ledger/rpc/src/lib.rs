@@ -0,0 +1,312 @@
+//! RPC interface for the TidyGen Ledger pallet
+
+use codec::Codec;
+use futures::{FutureExt, StreamExt};
+use jsonrpsee::{
+    core::{async_trait, RpcResult},
+    proc_macros::rpc,
+    types::{
+        error::{CallError, ErrorObject},
+        SubscriptionResult,
+    },
+    SubscriptionSink,
+};
+use sc_client_api::{BlockchainEvents, ProofProvider};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::{traits::SpawnNamed, Bytes, H256};
+use sp_runtime::traits::Block as BlockT;
+use std::sync::Arc;
+use tidygen_primitives::subscription::{self, SubscriptionItem};
+
+pub use pallet_tidygen_ledger_runtime_api::{
+    AnchoredTransaction, TidygenLedgerApi as TidygenLedgerRuntimeApi,
+    TidygenLedgerEventsApi as TidygenLedgerEventsRuntimeApi,
+};
+
+/// Error code for failed runtime API calls
+pub const RUNTIME_ERROR: i32 = 1;
+
+/// Error code for a read proof the node could not build
+pub const PROOF_ERROR: i32 = 2;
+
+/// Notifications buffered per subscriber before it is considered lagging
+pub const SUBSCRIPTION_BUFFER: usize = 256;
+
+/// A transaction anchored in a finalized block
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnchorNotification<BlockHash, AccountId, BlockNumber> {
+    /// Finalized block the transaction was anchored in
+    pub block_hash: BlockHash,
+    /// Position of the `TransactionAnchored` event among the block's events
+    pub event_index: u32,
+    /// Anchored transaction hash
+    pub tx_hash: H256,
+    /// Account that anchored the transaction
+    pub anchored_by: AccountId,
+    /// Number of that block
+    pub block_number: BlockNumber,
+}
+
+/// An anchor with everything needed to verify it against a trusted block
+///
+/// Verify by reading `storage_key` from the trie built out of `proof` at the state root of
+/// `block_hash`, then SCALE-decoding the value and comparing it with `anchor`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnchorWithProof<BlockHash, BlockNumber, Anchor> {
+    /// Block the anchor was read at and the proof was built against
+    pub block_hash: BlockHash,
+    /// Number of that block
+    pub at_block: BlockNumber,
+    /// The stored anchor
+    pub anchor: Anchor,
+    /// Storage key of the anchor
+    pub storage_key: Bytes,
+    /// Trie nodes proving the value stored under `storage_key`
+    pub proof: Vec<Bytes>,
+}
+
+#[rpc(client, server)]
+pub trait TidygenLedgerApi<BlockHash, BlockNumber, Anchor> {
+    /// Get the anchor of a transaction hash with a storage read proof at the given block
+    #[method(name = "tidygenLedger_getAnchorWithProof")]
+    fn get_anchor_with_proof(
+        &self,
+        tx_hash: H256,
+        at: Option<BlockHash>,
+    ) -> RpcResult<Option<AnchorWithProof<BlockHash, BlockNumber, Anchor>>>;
+}
+
+/// A struct that implements the `TidygenLedgerApi`.
+pub struct TidygenLedger<C, Block> {
+    client: Arc<C>,
+    _marker: std::marker::PhantomData<Block>,
+}
+
+impl<C, Block> TidygenLedger<C, Block> {
+    /// Create new `TidygenLedger` instance with the given reference to the client.
+    pub fn new(client: Arc<C>) -> Self {
+        Self {
+            client,
+            _marker: Default::default(),
+        }
+    }
+}
+
+#[async_trait]
+impl<C, Block, BlockNumber, Anchor>
+    TidygenLedgerApiServer<<Block as BlockT>::Hash, BlockNumber, Anchor>
+    for TidygenLedger<C, Block>
+where
+    Block: BlockT,
+    C: Send
+        + Sync
+        + 'static
+        + ProvideRuntimeApi<Block>
+        + HeaderBackend<Block>
+        + ProofProvider<Block>,
+    C::Api: TidygenLedgerRuntimeApi<Block, Anchor, BlockNumber>,
+    BlockNumber: Codec,
+    Anchor: Codec,
+{
+    fn get_anchor_with_proof(
+        &self,
+        tx_hash: H256,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> RpcResult<Option<AnchorWithProof<<Block as BlockT>::Hash, BlockNumber, Anchor>>> {
+        let api = self.client.runtime_api();
+        let at = at.unwrap_or_else(|| self.client.info().best_hash);
+
+        let Some(found) = api
+            .anchor_proof(at, tx_hash.into())
+            .map_err(runtime_error_into_rpc_err)?
+        else {
+            return Ok(None);
+        };
+
+        // Prove the key at the same block the runtime API read it from
+        let proof = self
+            .client
+            .read_proof(at, &mut std::iter::once(found.storage_key.as_slice()))
+            .map_err(proof_error_into_rpc_err)?;
+
+        Ok(Some(AnchorWithProof {
+            block_hash: at,
+            at_block: found.at_block,
+            anchor: found.anchor,
+            storage_key: found.storage_key.into(),
+            proof: proof.into_iter_nodes().map(Bytes).collect(),
+        }))
+    }
+}
+
+#[rpc(client, server)]
+pub trait TidygenLedgerEventsApi<BlockHash, AccountId, BlockNumber> {
+    /// Subscribe to transactions anchored in finalized blocks, only those anchored by
+    /// `anchored_by` if given
+    #[subscription(
+        name = "tidygenLedger_subscribeAnchors" => "tidygenLedger_anchor",
+        unsubscribe = "tidygenLedger_unsubscribeAnchors",
+        item = SubscriptionItem<AnchorNotification<BlockHash, AccountId, BlockNumber>>
+    )]
+    fn subscribe_anchors(&self, anchored_by: Option<AccountId>);
+}
+
+/// A struct that implements the `TidygenLedgerEventsApi`.
+pub struct TidygenLedgerEvents<C, Block> {
+    client: Arc<C>,
+    executor: Arc<dyn SpawnNamed>,
+    _marker: std::marker::PhantomData<Block>,
+}
+
+impl<C, Block> TidygenLedgerEvents<C, Block> {
+    /// Create new `TidygenLedgerEvents` instance running subscriptions on `executor`.
+    pub fn new(client: Arc<C>, executor: Arc<dyn SpawnNamed>) -> Self {
+        Self {
+            client,
+            executor,
+            _marker: Default::default(),
+        }
+    }
+}
+
+impl<C, Block, AccountId, BlockNumber>
+    TidygenLedgerEventsApiServer<<Block as BlockT>::Hash, AccountId, BlockNumber>
+    for TidygenLedgerEvents<C, Block>
+where
+    Block: BlockT,
+    C: Send + Sync + 'static + ProvideRuntimeApi<Block> + BlockchainEvents<Block>,
+    C::Api: TidygenLedgerEventsRuntimeApi<Block, AccountId, BlockNumber>,
+    AccountId: Codec + PartialEq + Serialize + DeserializeOwned + Send + Sync + 'static,
+    BlockNumber: Codec + Serialize + Send + Sync + 'static,
+{
+    fn subscribe_anchors(
+        &self,
+        sink: SubscriptionSink,
+        anchored_by: Option<AccountId>,
+    ) -> SubscriptionResult {
+        let (sender, receiver) = subscription::lag_channel(SUBSCRIPTION_BUFFER);
+        let finalized = self.client.finality_notification_stream().map(|n| n.hash);
+        let runtime = self.client.clone();
+        let fetch = move |hash| {
+            let anchored = runtime.runtime_api().anchored_transactions(hash)?;
+            Ok::<_, sp_api::ApiError>(anchor_notifications(hash, anchored, anchored_by.as_ref()))
+        };
+
+        self.executor.spawn(
+            "tidygen-ledger-anchor-events",
+            Some("rpc"),
+            subscription::relay(finalized, fetch, sender).boxed(),
+        );
+        self.executor.spawn(
+            "tidygen-ledger-anchor-subscription",
+            Some("rpc"),
+            async move {
+                sink.pipe_from_stream(receiver).await;
+            }
+            .boxed(),
+        );
+
+        Ok(())
+    }
+}
+
+/// Notifications for the transactions anchored in block `hash`, only those anchored by
+/// `anchored_by` if given
+fn anchor_notifications<Hash: Copy, AccountId: PartialEq, BlockNumber>(
+    hash: Hash,
+    anchored: Vec<AnchoredTransaction<AccountId, BlockNumber>>,
+    anchored_by: Option<&AccountId>,
+) -> Vec<AnchorNotification<Hash, AccountId, BlockNumber>> {
+    anchored
+        .into_iter()
+        .filter(|anchor| anchored_by.map_or(true, |who| who == &anchor.anchored_by))
+        .map(|anchor| AnchorNotification {
+            block_hash: hash,
+            event_index: anchor.event_index,
+            tx_hash: anchor.tx_hash.into(),
+            anchored_by: anchor.anchored_by,
+            block_number: anchor.block_number,
+        })
+        .collect()
+}
+
+/// Converts a runtime trap into an RPC error.
+fn runtime_error_into_rpc_err(err: impl std::fmt::Debug) -> jsonrpsee::core::Error {
+    CallError::Custom(ErrorObject::owned(
+        RUNTIME_ERROR,
+        "Runtime error",
+        Some(format!("{:?}", err)),
+    ))
+    .into()
+}
+
+/// Converts a failure to build a read proof into an RPC error.
+fn proof_error_into_rpc_err(err: impl std::fmt::Debug) -> jsonrpsee::core::Error {
+    CallError::Custom(ErrorObject::owned(
+        PROOF_ERROR,
+        "Read proof error",
+        Some(format!("{:?}", err)),
+    ))
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn anchored(event_index: u32, anchored_by: u64) -> AnchoredTransaction<u64, u32> {
+        AnchoredTransaction {
+            event_index,
+            tx_hash: [event_index as u8; 32],
+            anchored_by,
+            block_number: 12,
+        }
+    }
+
+    #[test]
+    fn notifications_keep_only_the_requested_account() {
+        let block = H256::repeat_byte(7);
+        let events = vec![anchored(0, 1), anchored(3, 2), anchored(5, 1)];
+
+        assert_eq!(anchor_notifications(block, events.clone(), None).len(), 3);
+        assert_eq!(
+            anchor_notifications(block, events, Some(&1)),
+            vec![
+                AnchorNotification {
+                    block_hash: block,
+                    event_index: 0,
+                    tx_hash: H256::repeat_byte(0),
+                    anchored_by: 1,
+                    block_number: 12,
+                },
+                AnchorNotification {
+                    block_hash: block,
+                    event_index: 5,
+                    tx_hash: H256::repeat_byte(5),
+                    anchored_by: 1,
+                    block_number: 12,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn notifications_serialize_as_camel_case() {
+        let item = SubscriptionItem::Event(AnchorNotification {
+            block_hash: H256::zero(),
+            event_index: 2,
+            tx_hash: H256::repeat_byte(0xab),
+            anchored_by: 1u64,
+            block_number: 12u32,
+        });
+        let json = serde_json::to_value(&item).unwrap();
+        assert_eq!(json["type"], "event");
+        assert_eq!(json["eventIndex"], 2);
+        assert_eq!(json["anchoredBy"], 1);
+        assert_eq!(json["txHash"], format!("0x{}", "ab".repeat(32)));
+    }
+}
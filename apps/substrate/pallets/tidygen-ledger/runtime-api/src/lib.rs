@@ -0,0 +1,31 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Runtime API definition for the TidyGen Ledger pallet
+
+use codec::Codec;
+use sp_std::vec::Vec;
+
+pub use pallet_tidygen_ledger::{AnchorProof, AnchoredTransaction};
+
+sp_api::decl_runtime_apis! {
+    /// The API to interact with TidyGen Ledger pallet
+    pub trait TidygenLedgerApi<Anchor, BlockNumber>
+    where
+        Anchor: Codec,
+        BlockNumber: Codec,
+    {
+        /// Get the anchor of a transaction hash with its storage key and the block number
+        /// of the state it was read from
+        fn anchor_proof(tx_hash: [u8; 32]) -> Option<AnchorProof<Anchor, BlockNumber>>;
+    }
+
+    /// TidyGen Ledger events of a block, read back for RPC subscriptions
+    pub trait TidygenLedgerEventsApi<AccountId, BlockNumber>
+    where
+        AccountId: Codec,
+        BlockNumber: Codec,
+    {
+        /// Get the `TransactionAnchored` events deposited in the block
+        fn anchored_transactions() -> Vec<AnchoredTransaction<AccountId, BlockNumber>>;
+    }
+}
@@ -19,6 +19,47 @@
 //! * `create_ledger_entry` - Create a new ledger entry with transaction data
 //! * `update_ledger_status` - Update the status of an existing ledger entry
 //! * `anchor_transaction` - Anchor a transaction hash on-chain
+//! * `anchor_transaction_idempotent` - Anchor a transaction hash, treating a retry of the
+//!   same anchor as a successful no-op
+//! * `anchor_transactions` - Anchor several transaction hashes, skipping the ones that fail
+//!
+//! Existing anchors can be inspected with `get_anchor`.
+//!
+//! ### Status Transitions
+//!
+//! Every status change made by `update_ledger_status` is reported to `Config::OnTransition`,
+//! e.g. a compliance log archiving them.
+//!
+//! ### Batch Anchoring
+//!
+//! `anchor_transactions` anchors up to `MaxBatchSize` hashes like `anchor_transaction`, each
+//! in its own storage transaction. An entry that fails (already anchored, metadata too
+//! long) is rolled back alone and reported in `AnchorBatchItemSkipped` with its
+//! `SkipReason`. The call ends with `BatchCompleted`, whose `BatchOutcome` is also stored in
+//! `LastBatchOutcome` for the caller.
+//!
+//! ### Anchor Proofs
+//!
+//! `anchor_proof` returns an anchor together with its `TransactionAnchors` storage key and
+//! the block number of the state it was read from. The `tidygenLedger_getAnchorWithProof`
+//! RPC bundles this with a storage read proof for that key, so a third party can check
+//! "hash H was anchored at block B by account A" against a block hash they trust:
+//!
+//! 1. Fetch the header for the trusted block hash and take its `state_root`.
+//! 2. Build a trie database from the proof nodes and read `storage_key` at `state_root`
+//!    (e.g. `sp_trie::read_trie_value` with `LayoutV1<BlakeTwo256>`). The read must
+//!    succeed, which proves the value is part of that block's state.
+//! 3. SCALE-decode the value as a `TransactionAnchor` and compare it with the returned
+//!    anchor: `tx_hash` is H, `block_number` is B and `anchored_by` is A.
+//!
+//! The same proof can be obtained without the convenience RPC by passing `storage_key` to
+//! the standard `state_getReadProof` at the trusted block.
+//!
+//! ### Status Lifecycle
+//!
+//! Entries start as `Pending` and may move to `Confirmed`, `Failed` or `Cancelled`. A
+//! `Failed` entry can be resubmitted (`Pending`) or `Cancelled`. `Confirmed` and `Cancelled`
+//! are final. The table lives in `LEDGER_STATUS`.
 
 pub use pallet::*;
 
@@ -26,10 +67,18 @@ pub use pallet::*;
 pub mod pallet {
     use frame_support::{
         pallet_prelude::*,
-        traits::{Currency, ExistenceRequirement, Get},
+        storage::{with_transaction, TransactionOutcome},
+        traits::{Currency, ExistenceRequirement, Get, PalletInfoAccess},
     };
     use frame_system::pallet_prelude::*;
+    #[cfg(feature = "serde")]
+    use serde::{Deserialize, Serialize};
+    use sp_runtime::ModuleError;
     use sp_std::vec::Vec;
+    use tidygen_primitives::{
+        batch::{BatchOutcome, SkipReason},
+        transitions::{status_index, OnStatusTransition, StateMachine, TransitionSubject},
+    };
 
     type BalanceOf<T> =
         <<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
@@ -52,6 +101,15 @@ pub mod pallet {
         }
     }
 
+    /// Allowed ledger entry status transitions; `Confirmed` and `Cancelled` are terminal
+    pub const LEDGER_STATUS: StateMachine<LedgerStatus> = StateMachine::new(&[
+        (LedgerStatus::Pending, LedgerStatus::Confirmed),
+        (LedgerStatus::Pending, LedgerStatus::Failed),
+        (LedgerStatus::Pending, LedgerStatus::Cancelled),
+        (LedgerStatus::Failed, LedgerStatus::Pending),
+        (LedgerStatus::Failed, LedgerStatus::Cancelled),
+    ]);
+
     /// Ledger entry data structure
     #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
     #[scale_info(skip_type_params(T))]
@@ -75,17 +133,58 @@ pub mod pallet {
     /// Transaction anchor data structure
     #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
     #[scale_info(skip_type_params(T))]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            rename_all = "camelCase",
+            bound(
+                serialize = "T::AccountId: Serialize, BlockNumberFor<T>: Serialize",
+                deserialize = "T::AccountId: Deserialize<'de>, \
+                               BlockNumberFor<T>: Deserialize<'de>"
+            )
+        )
+    )]
     pub struct TransactionAnchor<T: Config> {
         /// Account that anchored the transaction
         pub anchored_by: T::AccountId,
         /// Transaction hash
+        #[cfg_attr(feature = "serde", serde(with = "tidygen_primitives::hex"))]
         pub tx_hash: [u8; 32],
         /// Block number when anchored
         pub block_number: BlockNumberFor<T>,
         /// Additional metadata
+        #[cfg_attr(feature = "serde", serde(with = "tidygen_primitives::hex"))]
         pub metadata: BoundedVec<u8, T::MaxMetadataLength>,
     }
 
+    /// An anchor with the storage location needed to prove it against a block's state root
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    #[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+    pub struct AnchorProof<Anchor, BlockNumber> {
+        /// The stored anchor
+        pub anchor: Anchor,
+        /// Full storage key of the anchor in `TransactionAnchors`
+        #[cfg_attr(feature = "serde", serde(with = "tidygen_primitives::hex"))]
+        pub storage_key: Vec<u8>,
+        /// Block number of the state the anchor was read from
+        pub at_block: BlockNumber,
+    }
+
+    /// A `TransactionAnchored` event read back from the block that deposited it
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+    pub struct AnchoredTransaction<AccountId, BlockNumber> {
+        /// Position of the event among all events of the block
+        pub event_index: u32,
+        /// Anchored transaction hash
+        pub tx_hash: [u8; 32],
+        /// Account that anchored the transaction
+        pub anchored_by: AccountId,
+        /// Block number when anchored
+        pub block_number: BlockNumber,
+    }
+
     #[pallet::config]
     pub trait Config: frame_system::Config {
         /// The overarching event type.
@@ -101,6 +200,13 @@ pub mod pallet {
         /// Maximum length of metadata
         #[pallet::constant]
         type MaxMetadataLength: Get<u32>;
+
+        /// Maximum number of anchors in one `anchor_transactions` call
+        #[pallet::constant]
+        type MaxBatchSize: Get<u32>;
+
+        /// Notified of every ledger entry status transition
+        type OnTransition: OnStatusTransition<Self::AccountId>;
     }
 
     /// Storage for ledger entries
@@ -120,6 +226,12 @@ pub mod pallet {
     #[pallet::getter(fn entry_count)]
     pub type EntryCount<T> = StorageValue<_, u64, ValueQuery>;
 
+    /// Outcome of the latest `anchor_transactions` call of each account
+    #[pallet::storage]
+    #[pallet::getter(fn last_batch_outcome)]
+    pub type LastBatchOutcome<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, BatchOutcome, OptionQuery>;
+
     #[pallet::event]
     #[pallet::generate_deposit(pub(super) fn deposit_event)]
     pub enum Event<T: Config> {
@@ -141,6 +253,21 @@ pub mod pallet {
             anchored_by: T::AccountId,
             block_number: BlockNumberFor<T>,
         },
+        /// Idempotent anchor retry found the identical anchor [tx_hash, original_block]
+        AnchorAlreadyPresent {
+            tx_hash: [u8; 32],
+            original_block: BlockNumberFor<T>,
+        },
+        /// Batch anchor rolled back and skipped [index, reason]
+        AnchorBatchItemSkipped {
+            index: u32,
+            reason: SkipReason,
+        },
+        /// Batch call finished [who, outcome]
+        BatchCompleted {
+            who: T::AccountId,
+            outcome: BatchOutcome,
+        },
     }
 
     #[pallet::error]
@@ -149,6 +276,10 @@ pub mod pallet {
         EntryNotFound,
         /// Transaction already anchored
         TransactionAlreadyAnchored,
+        /// Transaction already anchored by another account (see `get_anchor`)
+        AnchoredByOtherAccount,
+        /// Transaction already anchored by the caller with different metadata (see `get_anchor`)
+        AnchorMetadataMismatch,
         /// Invalid status transition
         InvalidStatusTransition,
         /// Unauthorized operation
@@ -157,6 +288,10 @@ pub mod pallet {
         TransactionTypeTooLong,
         /// Metadata too long
         MetadataTooLong,
+        /// A batch call needs at least one entry
+        EmptyBatch,
+        /// More than `MaxBatchSize` entries in one batch call
+        BatchTooLarge,
     }
 
     #[pallet::call]
@@ -213,6 +348,11 @@ pub mod pallet {
         /// * `origin` - Transaction origin
         /// * `entry_id` - ID of the ledger entry to update
         /// * `new_status` - New status to set
+        ///
+        /// # Errors
+        /// * `EntryNotFound` - Ledger entry doesn't exist
+        /// * `Unauthorized` - Origin is not the entry creator
+        /// * `InvalidStatusTransition` - `LEDGER_STATUS` does not allow the change
         #[pallet::call_index(1)]
         #[pallet::weight(10_000)]
         pub fn update_ledger_status(
@@ -228,9 +368,15 @@ pub mod pallet {
                 // Only creator can update status
                 ensure!(entry.creator == who, Error::<T>::Unauthorized);
 
-                let old_status = entry.status.clone();
-                entry.status = new_status.clone();
+                let old_status = LEDGER_STATUS
+                    .transition(&mut entry.status, new_status.clone())
+                    .map_err(|_| Error::<T>::InvalidStatusTransition)?;
                 entry.updated_at = frame_system::Pallet::<T>::block_number();
+                T::OnTransition::on_transition(
+                    TransitionSubject::LedgerEntry(entry_id),
+                    status_index(&old_status),
+                    status_index(&new_status),
+                );
 
                 Self::deposit_event(Event::LedgerStatusUpdated {
                     entry_id,
@@ -256,7 +402,125 @@ pub mod pallet {
             metadata: Vec<u8>,
         ) -> DispatchResult {
             let who = ensure_signed(origin)?;
+            Self::try_anchor(who, tx_hash, metadata)
+        }
+
+        /// Anchor a transaction hash on-chain, safe to retry
+        ///
+        /// If the caller already anchored `tx_hash` with the same metadata, the call succeeds
+        /// without changes and only a fraction of its weight is charged.
+        ///
+        /// # Arguments
+        /// * `origin` - Transaction origin
+        /// * `tx_hash` - Transaction hash to anchor
+        /// * `metadata` - Optional metadata about the transaction
+        ///
+        /// # Events
+        /// * `TransactionAnchored` - Emitted when the hash is anchored
+        /// * `AnchorAlreadyPresent` - Emitted when the identical anchor already exists
+        ///
+        /// # Errors
+        /// * `AnchoredByOtherAccount` - Another account anchored `tx_hash`
+        /// * `AnchorMetadataMismatch` - The caller anchored `tx_hash` with other metadata
+        ///
+        /// Use `get_anchor` to inspect the conflicting anchor.
+        #[pallet::call_index(3)]
+        #[pallet::weight(10_000)]
+        pub fn anchor_transaction_idempotent(
+            origin: OriginFor<T>,
+            tx_hash: [u8; 32],
+            metadata: Vec<u8>,
+        ) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+
+            let bounded_metadata: BoundedVec<u8, T::MaxMetadataLength> = metadata
+                .try_into()
+                .map_err(|_| Error::<T>::MetadataTooLong)?;
+
+            if let Some(existing) = TransactionAnchors::<T>::get(tx_hash) {
+                ensure!(existing.anchored_by == who, Error::<T>::AnchoredByOtherAccount);
+                ensure!(
+                    existing.metadata == bounded_metadata,
+                    Error::<T>::AnchorMetadataMismatch
+                );
+
+                Self::deposit_event(Event::AnchorAlreadyPresent {
+                    tx_hash,
+                    original_block: existing.block_number,
+                });
+
+                // A single read was needed; refund the rest
+                return Ok(Some(T::DbWeight::get().reads(1)).into());
+            }
+
+            Self::do_anchor(who, tx_hash, bounded_metadata);
 
+            Ok(().into())
+        }
+
+        /// Anchor several transaction hashes, skipping the ones that fail
+        ///
+        /// Every entry is anchored like `anchor_transaction`, in order and in its own storage
+        /// transaction. A failing entry is rolled back and skipped; the others are kept. The
+        /// outcome is stored in `LastBatchOutcome`.
+        ///
+        /// # Arguments
+        /// * `origin` - Transaction origin (recorded as anchoring account of every entry)
+        /// * `anchors` - Entries of (tx_hash, metadata)
+        ///
+        /// # Events
+        /// * `TransactionAnchored` - Emitted for every hash anchored
+        /// * `AnchorBatchItemSkipped` - Emitted for every entry that failed
+        /// * `BatchCompleted` - Emitted last with the `BatchOutcome`
+        ///
+        /// # Errors
+        /// * `EmptyBatch` - No entries were given
+        /// * `BatchTooLarge` - More than `MaxBatchSize` entries were given
+        #[pallet::call_index(4)]
+        #[pallet::weight(Weight::from_parts(10_000, 0).saturating_mul(anchors.len() as u64))]
+        pub fn anchor_transactions(
+            origin: OriginFor<T>,
+            anchors: Vec<([u8; 32], Vec<u8>)>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            ensure!(!anchors.is_empty(), Error::<T>::EmptyBatch);
+            ensure!(
+                anchors.len() <= T::MaxBatchSize::get() as usize,
+                Error::<T>::BatchTooLarge
+            );
+
+            let mut outcome = BatchOutcome::default();
+            for (index, (tx_hash, metadata)) in anchors.into_iter().enumerate() {
+                let index = index as u32;
+                // Only this entry's changes, events included, are rolled back on error
+                let result = with_transaction(|| {
+                    let result = Self::try_anchor(who.clone(), tx_hash, metadata);
+                    match result {
+                        Ok(()) => TransactionOutcome::Commit(result),
+                        Err(_) => TransactionOutcome::Rollback(result),
+                    }
+                });
+                if let Err(error) = &result {
+                    Self::deposit_event(Event::AnchorBatchItemSkipped {
+                        index,
+                        reason: Self::skip_reason(*error),
+                    });
+                }
+                outcome.record(index, &result);
+            }
+
+            LastBatchOutcome::<T>::insert(&who, outcome);
+            Self::deposit_event(Event::BatchCompleted { who, outcome });
+
+            Ok(())
+        }
+    }
+
+    // Helper functions
+    impl<T: Config> Pallet<T> {
+        /// Anchor `tx_hash` unless it is already anchored
+        fn try_anchor(who: T::AccountId, tx_hash: [u8; 32], metadata: Vec<u8>) -> DispatchResult {
             // Ensure transaction not already anchored
             ensure!(
                 !TransactionAnchors::<T>::contains_key(tx_hash),
@@ -267,13 +531,24 @@ pub mod pallet {
                 .try_into()
                 .map_err(|_| Error::<T>::MetadataTooLong)?;
 
+            Self::do_anchor(who, tx_hash, bounded_metadata);
+
+            Ok(())
+        }
+
+        /// Store a new anchor and emit `TransactionAnchored`
+        fn do_anchor(
+            who: T::AccountId,
+            tx_hash: [u8; 32],
+            metadata: BoundedVec<u8, T::MaxMetadataLength>,
+        ) {
             let current_block = frame_system::Pallet::<T>::block_number();
 
             let anchor = TransactionAnchor {
                 anchored_by: who.clone(),
                 tx_hash,
                 block_number: current_block,
-                metadata: bounded_metadata,
+                metadata,
             };
 
             TransactionAnchors::<T>::insert(tx_hash, anchor);
@@ -283,8 +558,78 @@ pub mod pallet {
                 anchored_by: who,
                 block_number: current_block,
             });
+        }
 
-            Ok(())
+        /// Get the anchor stored for a transaction hash
+        pub fn get_anchor(tx_hash: [u8; 32]) -> Option<TransactionAnchor<T>> {
+            TransactionAnchors::<T>::get(tx_hash)
+        }
+
+        /// Get the anchor stored for a transaction hash with its storage key, for building
+        /// a read proof at the current block
+        pub fn anchor_proof(
+            tx_hash: [u8; 32],
+        ) -> Option<AnchorProof<TransactionAnchor<T>, BlockNumberFor<T>>> {
+            TransactionAnchors::<T>::get(tx_hash).map(|anchor| AnchorProof {
+                anchor,
+                storage_key: TransactionAnchors::<T>::hashed_key_for(tx_hash),
+                at_block: frame_system::Pallet::<T>::block_number(),
+            })
+        }
+
+        /// `TransactionAnchored` events deposited so far in the current block, for RPC
+        /// subscriptions
+        pub fn anchored_transactions() -> Vec<AnchoredTransaction<T::AccountId, BlockNumberFor<T>>>
+        where
+            <T as Config>::RuntimeEvent: TryInto<Event<T>>,
+        {
+            frame_system::Pallet::<T>::read_events_no_consensus()
+                .enumerate()
+                .filter_map(|(index, record)| {
+                    match <T as Config>::RuntimeEvent::from(record.event).try_into().ok()? {
+                        Event::TransactionAnchored { tx_hash, anchored_by, block_number } => {
+                            Some(AnchoredTransaction {
+                                event_index: index as u32,
+                                tx_hash,
+                                anchored_by,
+                                block_number,
+                            })
+                        }
+                        _ => None,
+                    }
+                })
+                .collect()
+        }
+
+        /// Classify the failure of a batch item for its skip event
+        pub fn skip_reason(error: DispatchError) -> SkipReason {
+            match error {
+                DispatchError::Module(ModuleError { index, error, .. })
+                    if usize::from(index) == <Self as PalletInfoAccess>::index() =>
+                {
+                    Error::<T>::decode(&mut &error[..])
+                        .map(Into::into)
+                        .unwrap_or(SkipReason::Other(error[0]))
+                }
+                DispatchError::BadOrigin => SkipReason::Unauthorized,
+                _ => SkipReason::Other(SkipReason::FOREIGN_ERROR),
+            }
+        }
+    }
+
+    impl<T: Config> From<Error<T>> for SkipReason {
+        fn from(error: Error<T>) -> Self {
+            match error {
+                Error::<T>::TransactionTypeTooLong | Error::<T>::MetadataTooLong => Self::TooLong,
+                Error::<T>::TransactionAlreadyAnchored
+                | Error::<T>::AnchoredByOtherAccount
+                | Error::<T>::AnchorMetadataMismatch => Self::Duplicate,
+                Error::<T>::EntryNotFound => Self::NotFound,
+                Error::<T>::Unauthorized => Self::Unauthorized,
+                Error::<T>::BatchTooLarge => Self::BoundExceeded,
+                // Field-less pallet errors encode as their variant index
+                other => Self::Other(other.encode()[0]),
+            }
         }
     }
 }
@@ -292,12 +637,15 @@ pub mod pallet {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use codec::Decode;
     use frame_support::{assert_noop, assert_ok};
     use sp_core::H256;
     use sp_runtime::{
         traits::{BlakeTwo256, IdentityLookup},
         BuildStorage,
     };
+    use tidygen_primitives::batch::{BatchOutcome, SkipReason};
+    use tidygen_test_utils::NoopCurrency;
 
     type Block = frame_system::mocking::MockBlock<Test>;
 
@@ -336,9 +684,11 @@ mod tests {
 
     impl pallet::Config for Test {
         type RuntimeEvent = RuntimeEvent;
-        type Currency = ();
+        type Currency = NoopCurrency;
         type MaxTransactionTypeLength = frame_support::traits::ConstU32<32>;
         type MaxMetadataLength = frame_support::traits::ConstU32<256>;
+        type MaxBatchSize = frame_support::traits::ConstU32<4>;
+        type OnTransition = ();
     }
 
     fn new_test_ext() -> sp_io::TestExternalities {
@@ -365,5 +715,458 @@ mod tests {
             assert_eq!(TidygenLedger::entry_count(), 1);
         });
     }
-}
+    #[test]
+    fn update_ledger_status_follows_lifecycle() {
+        new_test_ext().execute_with(|| {
+            assert_ok!(TidygenLedger::create_ledger_entry(
+                RuntimeOrigin::signed(1),
+                b"invoice".to_vec(),
+                [1u8; 32],
+                None
+            ));
+
+            // Failed entries can be resubmitted
+            assert_ok!(TidygenLedger::update_ledger_status(
+                RuntimeOrigin::signed(1),
+                0,
+                LedgerStatus::Failed
+            ));
+            assert_ok!(TidygenLedger::update_ledger_status(
+                RuntimeOrigin::signed(1),
+                0,
+                LedgerStatus::Pending
+            ));
+            assert_ok!(TidygenLedger::update_ledger_status(
+                RuntimeOrigin::signed(1),
+                0,
+                LedgerStatus::Confirmed
+            ));
+
+            // Confirmed is final
+            assert_noop!(
+                TidygenLedger::update_ledger_status(
+                    RuntimeOrigin::signed(1),
+                    0,
+                    LedgerStatus::Pending
+                ),
+                Error::<Test>::InvalidStatusTransition
+            );
+            assert_eq!(
+                TidygenLedger::ledger_entries(0).unwrap().status,
+                LedgerStatus::Confirmed
+            );
 
+            // Only the creator may update
+            assert_ok!(TidygenLedger::create_ledger_entry(
+                RuntimeOrigin::signed(1),
+                b"invoice".to_vec(),
+                [2u8; 32],
+                None
+            ));
+            assert_noop!(
+                TidygenLedger::update_ledger_status(
+                    RuntimeOrigin::signed(2),
+                    1,
+                    LedgerStatus::Confirmed
+                ),
+                Error::<Test>::Unauthorized
+            );
+        });
+    }
+
+    #[test]
+    fn ledger_status_table_terminal_states_have_no_exits() {
+        let all = [
+            LedgerStatus::Pending,
+            LedgerStatus::Confirmed,
+            LedgerStatus::Failed,
+            LedgerStatus::Cancelled,
+        ];
+        for terminal in [LedgerStatus::Confirmed, LedgerStatus::Cancelled] {
+            assert!(LEDGER_STATUS.is_terminal(&terminal));
+            for to in &all {
+                assert!(!LEDGER_STATUS.can_transition(&terminal, to));
+            }
+        }
+        for state in &all {
+            assert!(!LEDGER_STATUS.can_transition(state, state));
+        }
+    }
+
+    #[test]
+    fn idempotent_anchor_retry_by_same_account_is_noop() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(5);
+            let tx_hash = [7u8; 32];
+
+            assert_ok!(TidygenLedger::anchor_transaction_idempotent(
+                RuntimeOrigin::signed(1),
+                tx_hash,
+                b"batch-1".to_vec()
+            ));
+
+            // Retry after a timeout in a later block
+            System::set_block_number(9);
+            let post_info = TidygenLedger::anchor_transaction_idempotent(
+                RuntimeOrigin::signed(1),
+                tx_hash,
+                b"batch-1".to_vec(),
+            )
+            .unwrap();
+            assert!(post_info.actual_weight.is_some());
+            System::assert_last_event(
+                Event::AnchorAlreadyPresent { tx_hash, original_block: 5 }.into(),
+            );
+            assert_eq!(TidygenLedger::get_anchor(tx_hash).unwrap().block_number, 5);
+
+            // The plain call still rejects duplicates
+            assert_noop!(
+                TidygenLedger::anchor_transaction(
+                    RuntimeOrigin::signed(1),
+                    tx_hash,
+                    b"batch-1".to_vec()
+                ),
+                Error::<Test>::TransactionAlreadyAnchored
+            );
+        });
+    }
+
+    #[test]
+    fn idempotent_anchor_conflicts_are_reported() {
+        new_test_ext().execute_with(|| {
+            let tx_hash = [7u8; 32];
+            assert_ok!(TidygenLedger::anchor_transaction(
+                RuntimeOrigin::signed(1),
+                tx_hash,
+                b"batch-1".to_vec()
+            ));
+
+            assert_noop!(
+                TidygenLedger::anchor_transaction_idempotent(
+                    RuntimeOrigin::signed(2),
+                    tx_hash,
+                    b"batch-1".to_vec()
+                ),
+                Error::<Test>::AnchoredByOtherAccount
+            );
+            assert_noop!(
+                TidygenLedger::anchor_transaction_idempotent(
+                    RuntimeOrigin::signed(1),
+                    tx_hash,
+                    b"batch-2".to_vec()
+                ),
+                Error::<Test>::AnchorMetadataMismatch
+            );
+            assert_eq!(TidygenLedger::get_anchor(tx_hash).unwrap().anchored_by, 1);
+        });
+    }
+
+    #[test]
+    fn anchor_proof_verifies_against_state_root() {
+        let tx_hash = [7u8; 32];
+        let mut ext = new_test_ext();
+        ext.execute_with(|| {
+            System::set_block_number(3);
+            assert_ok!(TidygenLedger::anchor_transaction(
+                RuntimeOrigin::signed(1),
+                tx_hash,
+                b"batch-1".to_vec()
+            ));
+            assert!(TidygenLedger::anchor_proof([8u8; 32]).is_none());
+        });
+        ext.commit_all().unwrap();
+
+        let bundle = ext.execute_with(|| TidygenLedger::anchor_proof(tx_hash)).unwrap();
+        assert_eq!(bundle.at_block, 3);
+        assert_eq!(bundle.anchor.block_number, 3);
+        assert_eq!(bundle.anchor.anchored_by, 1);
+
+        // Prove the key as a node would for `state_getReadProof`
+        let backend = ext.as_backend();
+        let state_root = *backend.root();
+        let proof = sp_state_machine::prove_read(backend, [&bundle.storage_key]).unwrap();
+
+        // Verify with nothing but the proof nodes and the trusted state root
+        let db = proof.into_memory_db::<BlakeTwo256>();
+        let value = sp_trie::read_trie_value::<sp_trie::LayoutV1<BlakeTwo256>, _>(
+            &db,
+            &state_root,
+            &bundle.storage_key,
+            None,
+            None,
+        )
+        .unwrap()
+        .expect("anchor is in the proven state");
+        assert_eq!(TransactionAnchor::<Test>::decode(&mut &value[..]).unwrap(), bundle.anchor);
+
+        // The proof does not vouch for other anchors
+        let other_key = TransactionAnchors::<Test>::hashed_key_for([8u8; 32]);
+        assert!(!matches!(
+            sp_trie::read_trie_value::<sp_trie::LayoutV1<BlakeTwo256>, _>(
+                &db,
+                &state_root,
+                &other_key,
+                None,
+                None,
+            ),
+            Ok(Some(_))
+        ));
+    }
+
+    #[test]
+    fn anchor_errors_map_to_skip_reasons() {
+        assert_eq!(SkipReason::from(Error::<Test>::MetadataTooLong), SkipReason::TooLong);
+        assert_eq!(
+            SkipReason::from(Error::<Test>::TransactionAlreadyAnchored),
+            SkipReason::Duplicate
+        );
+        assert_eq!(SkipReason::from(Error::<Test>::EntryNotFound), SkipReason::NotFound);
+        assert_eq!(SkipReason::from(Error::<Test>::Unauthorized), SkipReason::Unauthorized);
+        // `InvalidStatusTransition` is the 5th error
+        assert_eq!(
+            SkipReason::from(Error::<Test>::InvalidStatusTransition),
+            SkipReason::Other(4)
+        );
+
+        new_test_ext().execute_with(|| {
+            let tx_hash = [7u8; 32];
+            assert_ok!(TidygenLedger::anchor_transaction(
+                RuntimeOrigin::signed(1),
+                tx_hash,
+                b"batch-1".to_vec()
+            ));
+            let error = TidygenLedger::anchor_transaction(
+                RuntimeOrigin::signed(1),
+                tx_hash,
+                b"batch-1".to_vec(),
+            )
+            .unwrap_err();
+            assert_eq!(TidygenLedger::skip_reason(error), SkipReason::Duplicate);
+        });
+    }
+
+    #[test]
+    fn anchor_proof_json_field_names_are_stable() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(2);
+            assert_ok!(TidygenLedger::anchor_transaction(
+                RuntimeOrigin::signed(1),
+                [0xab; 32],
+                b"batch-1".to_vec()
+            ));
+            let bundle = TidygenLedger::anchor_proof([0xab; 32]).unwrap();
+            let json = serde_json::to_value(&bundle).unwrap();
+
+            // Frontend types are generated from these names; renaming one breaks them
+            assert_eq!(json.as_object().unwrap().len(), 3);
+            assert_eq!(
+                json["anchor"],
+                serde_json::json!({
+                    "anchoredBy": 1,
+                    "txHash": format!("0x{}", "ab".repeat(32)),
+                    "blockNumber": 2,
+                    "metadata": "0x62617463682d31",
+                })
+            );
+            assert_eq!(json["atBlock"], 2);
+            assert!(json["storageKey"].as_str().unwrap().starts_with("0x"));
+
+            assert_eq!(
+                serde_json::from_value::<AnchorProof<TransactionAnchor<Test>, u64>>(json).unwrap(),
+                bundle
+            );
+        });
+    }
+
+    #[test]
+    fn anchored_transactions_reads_back_the_block_events() {
+        new_test_ext().execute_with(|| {
+            // Events are not recorded at genesis
+            System::set_block_number(1);
+            assert_ok!(TidygenLedger::anchor_transaction(
+                RuntimeOrigin::signed(1),
+                [1; 32],
+                vec![]
+            ));
+            assert_ok!(TidygenLedger::create_ledger_entry(
+                RuntimeOrigin::signed(2),
+                b"invoice".to_vec(),
+                [9; 32],
+                None
+            ));
+            assert_ok!(TidygenLedger::anchor_transaction(
+                RuntimeOrigin::signed(2),
+                [2; 32],
+                vec![]
+            ));
+            // An identical retry anchors nothing new
+            assert_ok!(TidygenLedger::anchor_transaction_idempotent(
+                RuntimeOrigin::signed(2),
+                [2; 32],
+                vec![]
+            ));
+
+            let anchored = TidygenLedger::anchored_transactions();
+            assert_eq!(
+                anchored,
+                vec![
+                    AnchoredTransaction {
+                        event_index: 0,
+                        tx_hash: [1; 32],
+                        anchored_by: 1,
+                        block_number: 1
+                    },
+                    AnchoredTransaction {
+                        event_index: 2,
+                        tx_hash: [2; 32],
+                        anchored_by: 2,
+                        block_number: 1
+                    },
+                ]
+            );
+
+            System::reset_events();
+            assert!(TidygenLedger::anchored_transactions().is_empty());
+        });
+    }
+
+    #[test]
+    fn anchor_transactions_skips_failing_entries_and_reports_the_outcome() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            assert_ok!(TidygenLedger::anchor_transaction(
+                RuntimeOrigin::signed(1),
+                [1; 32],
+                vec![]
+            ));
+            System::reset_events();
+
+            // Entry 1 is already anchored and entry 2 has too much metadata
+            assert_ok!(TidygenLedger::anchor_transactions(
+                RuntimeOrigin::signed(2),
+                vec![
+                    ([2; 32], vec![]),
+                    ([1; 32], vec![]),
+                    ([3; 32], vec![0; 257]),
+                    ([4; 32], vec![]),
+                ]
+            ));
+
+            let events: Vec<_> = System::events()
+                .into_iter()
+                .filter_map(|record| match record.event {
+                    RuntimeEvent::TidygenLedger(event) => Some(event),
+                    _ => None,
+                })
+                .collect();
+            let anchored =
+                events.iter().filter(|e| matches!(e, Event::TransactionAnchored { .. })).count();
+            let skipped: Vec<_> = events
+                .iter()
+                .filter_map(|e| match e {
+                    Event::AnchorBatchItemSkipped { index, reason } => Some((*index, *reason)),
+                    _ => None,
+                })
+                .collect();
+            assert_eq!(skipped, vec![(1, SkipReason::Duplicate), (2, SkipReason::TooLong)]);
+
+            // The summary agrees with the per-item events and comes last
+            let outcome = BatchOutcome { succeeded: 2, skipped: 2, first_error_index: Some(1) };
+            assert_eq!(anchored as u32, outcome.succeeded);
+            assert_eq!(skipped.len() as u32, outcome.skipped);
+            assert_eq!(events.last(), Some(&Event::BatchCompleted { who: 2, outcome }));
+            assert_eq!(TidygenLedger::last_batch_outcome(2), Some(outcome));
+
+            // Skipped entries leave nothing behind
+            assert_eq!(TidygenLedger::get_anchor([1; 32]).unwrap().anchored_by, 1);
+            assert!(TidygenLedger::get_anchor([3; 32]).is_none());
+            assert!(TidygenLedger::get_anchor([4; 32]).is_some());
+
+            assert_noop!(
+                TidygenLedger::anchor_transactions(RuntimeOrigin::signed(2), vec![]),
+                Error::<Test>::EmptyBatch
+            );
+            assert_noop!(
+                TidygenLedger::anchor_transactions(
+                    RuntimeOrigin::signed(2),
+                    (0..5u8).map(|i| ([i + 10; 32], vec![])).collect()
+                ),
+                Error::<Test>::BatchTooLarge
+            );
+        });
+    }
+
+    /// Pinned call indices and storage prefixes of pallet-tidygen-ledger.
+    ///
+    /// Downstream tooling decodes extrinsics and storage by these values. See
+    /// `tidygen_primitives::compat` for how an intended change to them is made.
+    mod compatibility {
+        use super::*;
+        use codec::Encode;
+        use frame_support::traits::{GetCallIndex, GetCallName, StorageInfoTrait};
+        use tidygen_primitives::compat::{assert_call_indices, assert_storage_prefixes};
+
+        /// Version of the tables below, bumped when an existing entry moves or goes away
+        const COMPAT_VERSION: u32 = 1;
+
+        /// Every dispatchable with its call index
+        const CALL_INDICES: &[(&str, u8)] = &[
+            ("create_ledger_entry", 0),
+            ("update_ledger_status", 1),
+            ("anchor_transaction", 2),
+            ("anchor_transaction_idempotent", 3),
+            ("anchor_transactions", 4),
+        ];
+
+        /// Every storage item with the `twox_128` of its name
+        const STORAGE_PREFIXES: &[(&str, &str)] = &[
+            ("LedgerEntries", "53be5b6d2502c0876933aff21ff990b6"),
+            ("TransactionAnchors", "f260f4c300408e9516dedb91f07c675d"),
+            ("EntryCount", "3a91370312d2084e43ce7f10bf343152"),
+            ("LastBatchOutcome", "16cfc87158735655605b9f9003b1f661"),
+        ];
+
+        #[test]
+        fn call_indices_are_stable() {
+            assert_call_indices(
+                "pallet-tidygen-ledger",
+                COMPAT_VERSION,
+                CALL_INDICES,
+                pallet::Call::<Test>::get_call_names(),
+                pallet::Call::<Test>::get_call_indices(),
+            );
+        }
+
+        #[test]
+        fn encoded_calls_lead_with_the_pinned_index() {
+            let call = pallet::Call::<Test>::update_ledger_status {
+                entry_id: 3,
+                new_status: LedgerStatus::Confirmed,
+            };
+            assert_eq!(call.encode(), [&[1u8][..], &3u64.encode(), &[1u8]].concat());
+
+            // In a runtime call the pallet index comes first, TidygenLedger is the second
+            // pallet of the mock
+            let call = RuntimeCall::TidygenLedger(pallet::Call::anchor_transaction {
+                tx_hash: [7u8; 32],
+                metadata: vec![],
+            });
+            assert_eq!(call.encode(), [&[1u8, 2][..], &[7u8; 32], &[0u8]].concat());
+        }
+
+        #[test]
+        fn storage_prefixes_are_stable() {
+            let actual: Vec<_> = pallet::Pallet::<Test>::storage_info()
+                .into_iter()
+                .map(|info| (info.storage_name, info.prefix))
+                .collect();
+
+            assert_storage_prefixes(
+                "pallet-tidygen-ledger",
+                COMPAT_VERSION,
+                STORAGE_PREFIXES,
+                &actual,
+            );
+        }
+    }
+}
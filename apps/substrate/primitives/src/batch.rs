@@ -0,0 +1,90 @@
+//! Shared vocabulary for batch extrinsics.
+//!
+//! Batch calls skip individual items instead of failing as a whole and report every skip in
+//! an event. All pallets use [`SkipReason`] in those events, so an indexer decodes a single
+//! type regardless of which pallet emitted it. Each pallet converts its own `Error<T>`
+//! into a `SkipReason` with a `From` impl.
+//!
+//! Each item runs in its own storage transaction, so a failing item leaves no trace besides
+//! its skip event. The call then summarizes itself in a [`BatchOutcome`], which pallets
+//! store per caller and repeat in a trailing event, so a submitter can learn what happened
+//! without scanning every item event.
+
+use codec::{Decode, Encode, MaxEncodedLen};
+use scale_info::TypeInfo;
+
+/// Why a batch item was skipped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Encode, Decode, TypeInfo, MaxEncodedLen)]
+pub enum SkipReason {
+    /// A field exceeded its length bound.
+    TooLong,
+    /// The item already exists (same hash, key or payload).
+    Duplicate,
+    /// A referenced item does not exist.
+    NotFound,
+    /// The caller may not act on the item.
+    Unauthorized,
+    /// A per-account or per-block capacity, or an amount limit, would be exceeded.
+    BoundExceeded,
+    /// Any other failure, carrying the emitting pallet's error index, or
+    /// [`SkipReason::FOREIGN_ERROR`] for errors raised outside that pallet.
+    Other(u8),
+}
+
+impl SkipReason {
+    /// `Other` code for errors that did not originate in the batch's own pallet.
+    pub const FOREIGN_ERROR: u8 = u8::MAX;
+}
+
+/// Summary of a batch call.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Encode, Decode, TypeInfo, MaxEncodedLen)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct BatchOutcome {
+    /// Items that were applied
+    pub succeeded: u32,
+    /// Items that were skipped
+    pub skipped: u32,
+    /// Position of the first skipped item in the batch
+    pub first_error_index: Option<u32>,
+}
+
+impl BatchOutcome {
+    /// Count the item at `index` as applied, or as skipped if `result` is an error.
+    pub fn record<T, E>(&mut self, index: u32, result: &Result<T, E>) {
+        match result {
+            Ok(_) => self.succeeded = self.succeeded.saturating_add(1),
+            Err(_) => {
+                self.skipped = self.skipped.saturating_add(1);
+                self.first_error_index.get_or_insert(index);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skip_reasons_have_stable_encoding() {
+        // Indexers decode these bytes; reordering variants is a breaking change
+        assert_eq!(SkipReason::TooLong.encode(), vec![0]);
+        assert_eq!(SkipReason::BoundExceeded.encode(), vec![4]);
+        assert_eq!(SkipReason::Other(7).encode(), vec![5, 7]);
+        assert_eq!(SkipReason::max_encoded_len(), 2);
+        assert_eq!(SkipReason::decode(&mut &[2u8][..]), Ok(SkipReason::NotFound));
+    }
+
+    #[test]
+    fn outcome_counts_items_and_keeps_the_first_error() {
+        let mut outcome = BatchOutcome::default();
+        let results: [Result<(), SkipReason>; 4] =
+            [Ok(()), Err(SkipReason::TooLong), Ok(()), Err(SkipReason::Duplicate)];
+        for (index, result) in results.iter().enumerate() {
+            outcome.record(index as u32, result);
+        }
+
+        assert_eq!(outcome, BatchOutcome { succeeded: 2, skipped: 2, first_error_index: Some(1) });
+    }
+}
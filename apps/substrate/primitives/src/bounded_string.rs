@@ -0,0 +1,380 @@
+//! Length- and charset-checked strings for titles, labels and identifiers.
+//!
+//! A [`BoundedString`] holds at most `S::get()` bytes that all belong to its [`Charset`]:
+//! [`Ascii`], [`Utf8`] or [`LowerAsciiAlnum`]. Both checks happen once, in
+//! `TryFrom<Vec<u8>>`, which reports the first one that fails as a [`BoundedStringError`].
+//! Code holding a `BoundedString` can therefore rely on its contents without re-validating
+//! them.
+//!
+//! The SCALE encoding is that of the wrapped `BoundedVec<u8, S>`, so a `BoundedVec` field
+//! can become a `BoundedString` without moving stored bytes. Decoding checks the length
+//! only: values stored before a charset applied keep decoding, and a migration brings them
+//! in line with [`BoundedString::from_lossy`]. With the `serde` feature a string serializes
+//! as a JSON string and is checked again when deserialized.
+
+use codec::{Decode, Encode, EncodeLike, Input, MaxEncodedLen};
+use core::{fmt, marker::PhantomData, ops::Deref};
+use scale_info::TypeInfo;
+use sp_core::{bounded::BoundedVec, Get};
+use sp_std::vec::Vec;
+
+/// The bytes a [`BoundedString`] may hold
+pub trait Charset {
+    /// Whether every byte of `bytes` belongs to the charset
+    fn is_valid(bytes: &[u8]) -> bool;
+
+    /// Drop whatever does not belong to the charset, keeping the rest in order
+    fn retain_valid(bytes: Vec<u8>) -> Vec<u8>;
+}
+
+/// 7-bit ASCII, control characters included
+#[derive(Clone, Copy, Debug, PartialEq, Eq, TypeInfo)]
+pub struct Ascii;
+
+impl Charset for Ascii {
+    fn is_valid(bytes: &[u8]) -> bool {
+        bytes.is_ascii()
+    }
+
+    fn retain_valid(mut bytes: Vec<u8>) -> Vec<u8> {
+        bytes.retain(u8::is_ascii);
+        bytes
+    }
+}
+
+/// Any valid UTF-8
+#[derive(Clone, Copy, Debug, PartialEq, Eq, TypeInfo)]
+pub struct Utf8;
+
+impl Charset for Utf8 {
+    fn is_valid(bytes: &[u8]) -> bool {
+        core::str::from_utf8(bytes).is_ok()
+    }
+
+    /// Drops each invalid sequence, so what is left is never longer than the input
+    fn retain_valid(bytes: Vec<u8>) -> Vec<u8> {
+        if Self::is_valid(&bytes) {
+            return bytes;
+        }
+
+        let mut out = Vec::with_capacity(bytes.len());
+        let mut rest = &bytes[..];
+        loop {
+            match core::str::from_utf8(rest) {
+                Ok(valid) => {
+                    out.extend_from_slice(valid.as_bytes());
+                    return out;
+                }
+                Err(error) => {
+                    let (valid, invalid) = rest.split_at(error.valid_up_to());
+                    out.extend_from_slice(valid);
+                    // A sequence cut short at the end has no error length
+                    rest = &invalid[error.error_len().unwrap_or(invalid.len())..];
+                }
+            }
+        }
+    }
+}
+
+/// Lowercase ASCII letters and digits, e.g. for slugs and category keys
+#[derive(Clone, Copy, Debug, PartialEq, Eq, TypeInfo)]
+pub struct LowerAsciiAlnum;
+
+impl LowerAsciiAlnum {
+    fn allows(byte: &u8) -> bool {
+        byte.is_ascii_lowercase() || byte.is_ascii_digit()
+    }
+}
+
+impl Charset for LowerAsciiAlnum {
+    fn is_valid(bytes: &[u8]) -> bool {
+        bytes.iter().all(Self::allows)
+    }
+
+    fn retain_valid(mut bytes: Vec<u8>) -> Vec<u8> {
+        bytes.retain(Self::allows);
+        bytes
+    }
+}
+
+/// Why bytes were refused as a [`BoundedString`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Encode, Decode, TypeInfo, MaxEncodedLen)]
+pub enum BoundedStringError {
+    /// Longer than the bound
+    TooLong,
+    /// Holds bytes outside the charset
+    InvalidCharset,
+}
+
+/// At most `S::get()` bytes, all in charset `C`.
+#[derive(TypeInfo)]
+#[scale_info(skip_type_params(S, C))]
+pub struct BoundedString<S, C>(BoundedVec<u8, S>, PhantomData<C>);
+
+impl<S: Get<u32>, C: Charset> BoundedString<S, C> {
+    /// Keep what `C` allows of `bytes`, cut to the bound. For migrating data stored
+    /// before the charset was enforced, never for new input.
+    pub fn from_lossy(bytes: Vec<u8>) -> Self {
+        let mut kept = C::retain_valid(bytes);
+        // Cutting may split a multi-byte character, drop its remains
+        while kept.len() > S::get() as usize {
+            kept.truncate(S::get() as usize);
+            kept = C::retain_valid(kept);
+        }
+        Self(BoundedVec::truncate_from(kept), PhantomData)
+    }
+}
+
+impl<S, C: Charset> BoundedString<S, C> {
+    /// Whether the contents belong to the charset. Only false for decoded values stored
+    /// before the charset was enforced.
+    pub fn is_valid(&self) -> bool {
+        C::is_valid(&self.0)
+    }
+
+    /// The raw bytes
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// The wrapped bounded bytes
+    pub fn into_inner(self) -> BoundedVec<u8, S> {
+        self.0
+    }
+}
+
+impl<S: Get<u32>, C: Charset> TryFrom<Vec<u8>> for BoundedString<S, C> {
+    type Error = BoundedStringError;
+
+    fn try_from(bytes: Vec<u8>) -> Result<Self, BoundedStringError> {
+        let bytes = BoundedVec::try_from(bytes).map_err(|_| BoundedStringError::TooLong)?;
+        if !C::is_valid(&bytes) {
+            return Err(BoundedStringError::InvalidCharset);
+        }
+        Ok(Self(bytes, PhantomData))
+    }
+}
+
+impl<S, C> Deref for BoundedString<S, C> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl<S: Get<u32>, C> Default for BoundedString<S, C> {
+    fn default() -> Self {
+        Self(BoundedVec::default(), PhantomData)
+    }
+}
+
+impl<S, C> Clone for BoundedString<S, C> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone(), PhantomData)
+    }
+}
+
+impl<S, C> PartialEq for BoundedString<S, C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<S, C> Eq for BoundedString<S, C> {}
+
+impl<S, C> fmt::Debug for BoundedString<S, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match core::str::from_utf8(&self.0) {
+            Ok(text) => fmt::Debug::fmt(text, f),
+            Err(_) => fmt::Debug::fmt(&self.0[..], f),
+        }
+    }
+}
+
+impl<S, C> Encode for BoundedString<S, C> {
+    fn size_hint(&self) -> usize {
+        self.0.size_hint()
+    }
+
+    fn encode_to<O: codec::Output + ?Sized>(&self, dest: &mut O) {
+        self.0.encode_to(dest)
+    }
+}
+
+impl<S, C> EncodeLike for BoundedString<S, C> {}
+
+impl<S: Get<u32>, C> Decode for BoundedString<S, C> {
+    fn decode<I: Input>(input: &mut I) -> Result<Self, codec::Error> {
+        BoundedVec::decode(input).map(|bytes| Self(bytes, PhantomData))
+    }
+}
+
+impl<S: Get<u32>, C> MaxEncodedLen for BoundedString<S, C> {
+    fn max_encoded_len() -> usize {
+        BoundedVec::<u8, S>::max_encoded_len()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<S, C> serde::Serialize for BoundedString<S, C> {
+    fn serialize<Ser: serde::Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+        let text =
+            core::str::from_utf8(&self.0).map_err(<Ser::Error as serde::ser::Error>::custom)?;
+        serializer.serialize_str(text)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, S: Get<u32>, C: Charset> serde::Deserialize<'de> for BoundedString<S, C> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor<S, C>(PhantomData<(S, C)>);
+
+        impl<'de, S: Get<u32>, C: Charset> serde::de::Visitor<'de> for Visitor<S, C> {
+            type Value = BoundedString<S, C>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a bounded string")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, text: &str) -> Result<Self::Value, E> {
+                BoundedString::try_from(text.as_bytes().to_vec()).map_err(|error| match error {
+                    BoundedStringError::TooLong => E::custom("string too long"),
+                    BoundedStringError::InvalidCharset => {
+                        E::custom("string has characters outside its charset")
+                    }
+                })
+            }
+        }
+
+        deserializer.deserialize_str(Visitor(PhantomData))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sp_core::ConstU32;
+
+    type Title = BoundedString<ConstU32<8>, Utf8>;
+
+    /// Deterministic xorshift byte strings of up to 12 bytes, so a failing input reproduces.
+    /// Bytes are drawn from a narrow alphabet half the time, to reach the valid cases often.
+    fn samples(count: usize) -> Vec<Vec<u8>> {
+        let mut seed = 0x9e37_79b9_7f4a_7c15u64;
+        let mut next = move || {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            seed
+        };
+        const NARROW: &[u8] = b"az09AZ -\xc3\xa9";
+        (0..count)
+            .map(|_| {
+                let len = (next() % 13) as usize;
+                let narrow = next() % 2 == 0;
+                (0..len)
+                    .map(|_| {
+                        let byte = next();
+                        if narrow {
+                            NARROW[(byte % NARROW.len() as u64) as usize]
+                        } else {
+                            byte as u8
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// `try_from` accepts exactly the bytes `allowed` accepts that fit the bound, and
+    /// `from_lossy` always yields a valid string made of input bytes kept in order
+    fn check_policy<C: Charset>(allowed: impl Fn(&[u8]) -> bool) {
+        let mut accepted = 0;
+        for bytes in samples(2000) {
+            let result = BoundedString::<ConstU32<8>, C>::try_from(bytes.clone());
+            let expected = if bytes.len() > 8 {
+                Err(BoundedStringError::TooLong)
+            } else if !allowed(&bytes) {
+                Err(BoundedStringError::InvalidCharset)
+            } else {
+                accepted += 1;
+                Ok(bytes.clone())
+            };
+            assert_eq!(result.map(|s| s.to_vec()), expected, "{:?}", bytes);
+
+            let lossy = BoundedString::<ConstU32<8>, C>::from_lossy(bytes.clone());
+            assert!(lossy.is_valid() && allowed(&lossy), "{:?}", bytes);
+            let mut rest = bytes.iter();
+            assert!(lossy.iter().all(|kept| rest.any(|byte| byte == kept)), "{:?}", bytes);
+            if bytes.len() <= 8 && allowed(&bytes) {
+                assert_eq!(lossy.to_vec(), bytes);
+            }
+        }
+        assert!(accepted > 100, "only {} valid samples", accepted);
+    }
+
+    #[test]
+    fn ascii_policy_accepts_ascii_only() {
+        check_policy::<Ascii>(|bytes| bytes.iter().all(|byte| *byte < 0x80));
+    }
+
+    #[test]
+    fn utf8_policy_accepts_valid_utf8_only() {
+        check_policy::<Utf8>(|bytes| core::str::from_utf8(bytes).is_ok());
+    }
+
+    #[test]
+    fn lower_alnum_policy_accepts_lowercase_letters_and_digits_only() {
+        check_policy::<LowerAsciiAlnum>(|bytes| {
+            bytes.iter().all(|byte| matches!(byte, b'a'..=b'z' | b'0'..=b'9'))
+        });
+    }
+
+    #[test]
+    fn length_is_checked_before_the_charset() {
+        assert_eq!(Title::try_from(vec![0xff; 9]), Err(BoundedStringError::TooLong));
+        assert_eq!(Title::try_from(vec![0xff; 8]), Err(BoundedStringError::InvalidCharset));
+        assert!(Title::try_from(Vec::new()).is_ok());
+    }
+
+    #[test]
+    fn lossy_cut_never_splits_a_character() {
+        // "caf\u{e9}" repeated: the cut at 8 bytes falls inside the second "\u{e9}"
+        let lossy = Title::from_lossy("caf\u{e9}caf\u{e9}".as_bytes().to_vec());
+        assert_eq!(lossy.as_bytes(), "caf\u{e9}caf".as_bytes());
+        assert_eq!(Title::from_lossy(vec![b'a', 0xc3, 0x28, b'b']).as_bytes(), b"a(b");
+    }
+
+    #[test]
+    fn encodes_like_the_wrapped_bounded_vec() {
+        for bytes in samples(200).into_iter().filter(|bytes| bytes.len() <= 8) {
+            let raw: BoundedVec<u8, ConstU32<8>> = bytes.clone().try_into().unwrap();
+            let string = BoundedString::<ConstU32<8>, Ascii>::from_lossy(bytes);
+            let lossy_raw: BoundedVec<u8, ConstU32<8>> = string.to_vec().try_into().unwrap();
+
+            assert_eq!(string.encode(), lossy_raw.encode());
+            let decoded = BoundedString::decode(&mut &string.encode()[..]).unwrap();
+            assert_eq!(decoded, string);
+            // Stored raw bytes decode whatever their charset, but not past the bound
+            let decoded = BoundedString::<ConstU32<8>, Ascii>::decode(&mut &raw.encode()[..]);
+            assert_eq!(decoded.unwrap().as_bytes(), &raw[..]);
+        }
+
+        let too_long: BoundedVec<u8, ConstU32<9>> = vec![b'a'; 9].try_into().unwrap();
+        assert!(Title::decode(&mut &too_long.encode()[..]).is_err());
+        assert_eq!(Title::max_encoded_len(), BoundedVec::<u8, ConstU32<8>>::max_encoded_len());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serializes_as_a_checked_json_string() {
+        let title = Title::try_from("Budget".as_bytes().to_vec()).unwrap();
+        assert_eq!(serde_json::to_string(&title).unwrap(), r#""Budget""#);
+        assert_eq!(serde_json::from_str::<Title>(r#""Budget""#).unwrap(), title);
+
+        assert!(serde_json::from_str::<Title>(r#""Budget Q4 2025""#).is_err());
+        assert!(serde_json::from_str::<BoundedString<ConstU32<8>, LowerAsciiAlnum>>(r#""Q4""#)
+            .is_err());
+    }
+}
@@ -0,0 +1,159 @@
+//! Regression checks for the identifiers downstream tooling keys on.
+//!
+//! Explorers, indexers and offline signers address extrinsics by their call index and read
+//! state by hashed storage prefix. Reordering dispatchables or renaming a storage item
+//! changes both without any compile error. Every pallet keeps a `compatibility` test module
+//! with the call indices and storage prefixes it has shipped and a `COMPAT_VERSION`, and
+//! compares the pallet against them with the helpers here.
+//!
+//! New calls and storage items only extend the tables. When an existing entry moves or goes
+//! away on purpose, the same change updates the tables and bumps `COMPAT_VERSION`, so the
+//! break is visible in review and can be announced downstream.
+//! The helpers report every difference at once rather than stopping at the first one.
+
+use sp_core::hashing::twox_128;
+use std::{collections::BTreeMap, string::String, vec::Vec};
+
+/// Assert that a pallet's dispatchables carry exactly the expected call indices.
+///
+/// `names` and `indices` are the pallet's `GetCallName::get_call_names()` and
+/// `GetCallIndex::get_call_indices()`, which list the calls in the same order.
+/// `expected` pairs every dispatchable name with the index it has shipped with.
+pub fn assert_call_indices(
+    pallet: &str,
+    compat_version: u32,
+    expected: &[(&str, u8)],
+    names: &[&str],
+    indices: &[u8],
+) {
+    assert_eq!(names.len(), indices.len(), "{pallet}: call names and indices do not line up");
+
+    let actual: BTreeMap<&str, u8> = names.iter().copied().zip(indices.iter().copied()).collect();
+    let expected: BTreeMap<&str, u8> = expected.iter().copied().collect();
+    let mut problems = Vec::new();
+
+    for (name, index) in &expected {
+        match actual.get(name) {
+            Some(found) if found == index => {},
+            Some(found) => problems.push(format!("{name}: expected index {index}, found {found}")),
+            None => problems.push(format!("{name}: expected at index {index}, call is gone")),
+        }
+    }
+    for (name, found) in &actual {
+        if !expected.contains_key(name) {
+            problems.push(format!("{name}: new call at index {found} is not in the table"));
+        }
+    }
+
+    report(pallet, compat_version, "call indices", problems);
+}
+
+/// Assert that a pallet's storage items live under exactly the expected prefixes.
+///
+/// `actual` holds `(storage_name, prefix)` for every item, as reported by the pallet's
+/// `StorageInfoTrait::storage_info()`. `expected` pairs every storage name with the hex
+/// `twox_128` of the name, the storage half of the 32 byte prefix. The pallet half depends
+/// on the name given in `construct_runtime!` and is only required to be shared.
+pub fn assert_storage_prefixes(
+    pallet: &str,
+    compat_version: u32,
+    expected: &[(&str, &str)],
+    actual: &[(Vec<u8>, Vec<u8>)],
+) {
+    let mut problems = Vec::new();
+    let mut pallet_halves = Vec::new();
+    let mut found = BTreeMap::new();
+
+    for (name, prefix) in actual {
+        let name = String::from_utf8_lossy(name).into_owned();
+        if prefix.len() != 32 {
+            problems.push(format!("{name}: prefix is {} bytes, expected 32", prefix.len()));
+            continue;
+        }
+        let (pallet_half, storage_half) = prefix.split_at(16);
+        if storage_half != twox_128(name.as_bytes()) {
+            problems.push(format!("{name}: prefix does not derive from the storage name"));
+        }
+        if !pallet_halves.contains(&pallet_half) {
+            pallet_halves.push(pallet_half);
+        }
+        found.insert(name, to_hex(storage_half));
+    }
+    if pallet_halves.len() > 1 {
+        problems.push(format!("storage is spread over {} pallet prefixes", pallet_halves.len()));
+    }
+
+    for (name, hex) in expected {
+        match found.remove(*name) {
+            Some(ref prefix) if prefix == hex => {},
+            Some(prefix) => problems.push(format!("{name}: expected prefix {hex}, found {prefix}")),
+            None => problems.push(format!("{name}: expected prefix {hex}, storage is gone")),
+        }
+    }
+    for (name, prefix) in found {
+        problems.push(format!("{name}: new storage with prefix {prefix} is not in the table"));
+    }
+
+    report(pallet, compat_version, "storage prefixes", problems);
+}
+
+fn report(pallet: &str, compat_version: u32, what: &str, problems: Vec<String>) {
+    if problems.is_empty() {
+        return;
+    }
+    panic!(
+        "{pallet} {what} differ from the COMPAT_VERSION {compat_version} tables:\n  {}\n\
+         If this is intended, update the tables in the pallet's `compatibility` module, \
+         and bump its COMPAT_VERSION when an existing entry moved or went away.",
+        problems.join("\n  "),
+    );
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prefix(pallet: &str, storage: &str) -> (Vec<u8>, Vec<u8>) {
+        let mut prefix = twox_128(pallet.as_bytes()).to_vec();
+        prefix.extend_from_slice(&twox_128(storage.as_bytes()));
+        (storage.as_bytes().to_vec(), prefix)
+    }
+
+    #[test]
+    fn matching_tables_pass() {
+        assert_call_indices("pallet-test", 1, &[("a", 0), ("b", 2)], &["a", "b"], &[0, 2]);
+        assert_storage_prefixes(
+            "pallet-test",
+            1,
+            &[("Number", "02a5c1b19ab7a04f536c519aca4983ac")],
+            &[prefix("System", "Number")],
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "b: expected index 1, found 2")]
+    fn shifted_call_index_fails() {
+        assert_call_indices("pallet-test", 1, &[("a", 0), ("b", 1)], &["a", "b"], &[0, 2]);
+    }
+
+    #[test]
+    #[should_panic(expected = "c: new call at index 3 is not in the table")]
+    fn unlisted_call_fails() {
+        assert_call_indices("pallet-test", 1, &[("a", 0)], &["a", "c"], &[0, 3]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Number: expected prefix 02a5c1b19ab7a04f536c519aca4983ac")]
+    fn renamed_storage_fails() {
+        assert_storage_prefixes(
+            "pallet-test",
+            1,
+            &[("Number", "02a5c1b19ab7a04f536c519aca4983ac")],
+            &[prefix("System", "BlockNumber")],
+        );
+    }
+}
@@ -0,0 +1,173 @@
+//! Feature flags a runtime reports to client applications.
+//!
+//! Several pallet behaviours are switched by `Config` items, so two runtimes built from this
+//! repository can differ in what a frontend should offer. Each pallet turns its switches into
+//! [`FeatureFlags`] with a `feature_bits()` helper; the runtime ORs those together and serves
+//! the result from the aggregate runtime API (RPC `tidygen_getFeatures`).
+//!
+//! Flags are a bitset, SCALE-encoded as a `u64`, and serialize to JSON as an object with one
+//! snake_case boolean per flag. Both are append-only: a new flag takes the next free bit and
+//! a new name, and no bit or name is ever moved or reused. Bits a decoder does not know are
+//! kept, and unknown JSON fields are ignored, so an older client reads a newer runtime.
+
+use codec::{Decode, Encode, MaxEncodedLen};
+use core::ops::BitOr;
+use scale_info::TypeInfo;
+
+/// Optional features enabled in a runtime, one bit each.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Encode, Decode, TypeInfo, MaxEncodedLen)]
+pub struct FeatureFlags(u64);
+
+impl FeatureFlags {
+    /// Anyone may create ledger invoices, not only authorized creators (`OpenCreation`)
+    pub const OPEN_INVOICE_CREATION: Self = Self(1 << 0);
+    /// Clients accept ledger invoices before paying them (`RequireAcceptance`)
+    pub const INVOICE_ACCEPTANCE: Self = Self(1 << 1);
+    /// New ledger invoices are pushed to the Django webhook (`InvoiceWebhook`)
+    pub const INVOICE_WEBHOOK: Self = Self(1 << 2);
+    /// Creating a ledger invoice reserves a deposit (non-zero `InvoiceDeposit`)
+    pub const INVOICE_DEPOSITS: Self = Self(1 << 3);
+    /// Overdue ledger invoices accrue late fees (non-zero `LateFeePeriod`)
+    pub const LATE_FEES: Self = Self(1 << 4);
+    /// The offchain worker closes expired DAO proposals (`OffchainAutoClose`)
+    pub const PROPOSAL_AUTO_CLOSE: Self = Self(1 << 5);
+    /// Rejected DAO proposals with little approval lose their deposit
+    /// (`SlashRejectedDeposits`)
+    pub const SPAM_DEPOSIT_SLASHING: Self = Self(1 << 6);
+    /// Only DAO members may vote (`MembersOnly`)
+    pub const MEMBERS_ONLY_VOTING: Self = Self(1 << 7);
+
+    /// Every flag with its JSON field name, in bit order. Append only.
+    pub const NAMED: &'static [(Self, &'static str)] = &[
+        (Self::OPEN_INVOICE_CREATION, "open_invoice_creation"),
+        (Self::INVOICE_ACCEPTANCE, "invoice_acceptance"),
+        (Self::INVOICE_WEBHOOK, "invoice_webhook"),
+        (Self::INVOICE_DEPOSITS, "invoice_deposits"),
+        (Self::LATE_FEES, "late_fees"),
+        (Self::PROPOSAL_AUTO_CLOSE, "proposal_auto_close"),
+        (Self::SPAM_DEPOSIT_SLASHING, "spam_deposit_slashing"),
+        (Self::MEMBERS_ONLY_VOTING, "members_only_voting"),
+    ];
+
+    /// No feature enabled
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    /// The raw bits
+    pub const fn bits(self) -> u64 {
+        self.0
+    }
+
+    /// Whether every flag of `other` is set
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// These flags with `flag` added if `enabled`, for building from `Config` switches
+    pub const fn with(self, flag: Self, enabled: bool) -> Self {
+        if enabled {
+            Self(self.0 | flag.0)
+        } else {
+            self
+        }
+    }
+}
+
+impl BitOr for FeatureFlags {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use super::FeatureFlags;
+    use core::fmt;
+    use serde::{
+        de::{IgnoredAny, MapAccess, Visitor},
+        ser::SerializeMap,
+        Deserialize, Deserializer, Serialize, Serializer,
+    };
+    use sp_std::borrow::Cow;
+
+    impl Serialize for FeatureFlags {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut map = serializer.serialize_map(Some(Self::NAMED.len()))?;
+            for (flag, name) in Self::NAMED {
+                map.serialize_entry(name, &self.contains(*flag))?;
+            }
+            map.end()
+        }
+    }
+
+    impl<'de> Deserialize<'de> for FeatureFlags {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            deserializer.deserialize_map(FlagsVisitor)
+        }
+    }
+
+    struct FlagsVisitor;
+
+    impl<'de> Visitor<'de> for FlagsVisitor {
+        type Value = FeatureFlags;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("an object of feature flags")
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<FeatureFlags, A::Error> {
+            let mut flags = FeatureFlags::empty();
+            while let Some(name) = map.next_key::<Cow<'de, str>>()? {
+                match FeatureFlags::NAMED.iter().find(|(_, known)| *known == name) {
+                    Some((flag, _)) => flags = flags.with(*flag, map.next_value()?),
+                    // A flag added by a newer runtime
+                    None => {
+                        map.next_value::<IgnoredAny>()?;
+                    }
+                }
+            }
+            Ok(flags)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags_have_stable_bits() {
+        // Clients decode these bits; moving a flag is a breaking change
+        assert_eq!(FeatureFlags::OPEN_INVOICE_CREATION.bits(), 1);
+        assert_eq!(FeatureFlags::PROPOSAL_AUTO_CLOSE.bits(), 1 << 5);
+        for (bit, (flag, _)) in FeatureFlags::NAMED.iter().enumerate() {
+            assert_eq!(flag.bits(), 1 << bit);
+        }
+
+        let flags = FeatureFlags::empty()
+            .with(FeatureFlags::INVOICE_WEBHOOK, true)
+            .with(FeatureFlags::LATE_FEES, false);
+        assert_eq!(flags.encode(), 4u64.encode());
+        assert!(flags.contains(FeatureFlags::INVOICE_WEBHOOK));
+        assert!(!flags.contains(FeatureFlags::LATE_FEES | FeatureFlags::INVOICE_WEBHOOK));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn flags_serialize_as_snake_case_booleans() {
+        let flags = FeatureFlags::INVOICE_ACCEPTANCE | FeatureFlags::PROPOSAL_AUTO_CLOSE;
+        let json = serde_json::to_value(flags).unwrap();
+        assert_eq!(json.as_object().unwrap().len(), FeatureFlags::NAMED.len());
+        assert_eq!(json["invoice_acceptance"], true);
+        assert_eq!(json["proposal_auto_close"], true);
+        assert_eq!(json["open_invoice_creation"], false);
+        assert_eq!(serde_json::from_value::<FeatureFlags>(json).unwrap(), flags);
+
+        // Flags of a newer runtime are ignored, missing ones are off
+        let newer = r#"{"late_fees":true,"flag_from_the_future":true}"#;
+        assert_eq!(serde_json::from_str::<FeatureFlags>(newer).unwrap(), FeatureFlags::LATE_FEES);
+    }
+}
@@ -0,0 +1,119 @@
+//! Typed runtime parameter changes decided by governance, and the categories proposals are
+//! filed under.
+//!
+//! A DAO proposal may carry a [`ParameterChange`] instead of an opaque call, so members vote
+//! on what they can read. On execution the DAO hands the change to its `ParameterHandler`:
+//! every pallet owning governable parameters implements [`ParameterHandler`] for the
+//! variants it applies, and the runtime chains them in a tuple, e.g. `(Ledger, Dao)`.
+//!
+//! Variants are append-only, as they are stored on proposals: a parameter that goes away
+//! keeps its variant, and the runtime simply stops handling it. Applying such a change
+//! reports [`ParameterNoLongerExists`] instead of failing to decode the proposal.
+
+use codec::{Decode, Encode, MaxEncodedLen};
+use scale_info::TypeInfo;
+
+/// A governable parameter with the value to set it to.
+///
+/// Serialized as tagged JSON, e.g. `{"kind": "ledgerTextNormalization", "value": true}`.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode, TypeInfo, MaxEncodedLen)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(tag = "kind", content = "value", rename_all = "camelCase")
+)]
+pub enum ParameterChange {
+    /// UTF-8 validation and line ending normalization of ledger text fields
+    LedgerTextNormalization(bool),
+    /// Line ending normalization of DAO proposal text
+    DaoTextNormalization(bool),
+}
+
+/// Kind of decision a proposal asks for, so UIs can filter proposals without parsing titles.
+///
+/// Fixed at creation. Variants are append-only, as they are stored on proposals and key the
+/// category index. Serialized in camelCase, e.g. `"budget"`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Encode, Decode, TypeInfo, MaxEncodedLen)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub enum ProposalCategory {
+    /// Anything else; proposals created without a category
+    #[default]
+    General,
+    /// Spending and budget allocation
+    Budget,
+    /// Hiring and staffing
+    Hiring,
+    /// Technical changes, e.g. runtime parameters and upgrades
+    Technical,
+}
+
+/// No handler of the runtime governs the parameter of a change
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParameterNoLongerExists;
+
+/// Applies the parameter changes of the variants a pallet owns
+pub trait ParameterHandler {
+    /// Apply `change`, or report that this handler does not govern its parameter
+    fn apply(change: &ParameterChange) -> Result<(), ParameterNoLongerExists>;
+}
+
+/// Governs no parameter
+impl ParameterHandler for () {
+    fn apply(_change: &ParameterChange) -> Result<(), ParameterNoLongerExists> {
+        Err(ParameterNoLongerExists)
+    }
+}
+
+/// Offers the change to `A`, then to `B`. Nest pairs to chain more handlers.
+impl<A: ParameterHandler, B: ParameterHandler> ParameterHandler for (A, B) {
+    fn apply(change: &ParameterChange) -> Result<(), ParameterNoLongerExists> {
+        A::apply(change).or_else(|_| B::apply(change))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Governs only the DAO switch
+    struct DaoOnly;
+
+    impl ParameterHandler for DaoOnly {
+        fn apply(change: &ParameterChange) -> Result<(), ParameterNoLongerExists> {
+            match change {
+                ParameterChange::DaoTextNormalization(_) => Ok(()),
+                _ => Err(ParameterNoLongerExists),
+            }
+        }
+    }
+
+    #[test]
+    fn pairs_offer_the_change_to_each_handler() {
+        let ledger = ParameterChange::LedgerTextNormalization(true);
+        let dao = ParameterChange::DaoTextNormalization(false);
+
+        assert_eq!(<()>::apply(&dao), Err(ParameterNoLongerExists));
+        assert_eq!(<((), DaoOnly)>::apply(&dao), Ok(()));
+        assert_eq!(<(DaoOnly, ())>::apply(&dao), Ok(()));
+        assert_eq!(<(DaoOnly, ())>::apply(&ledger), Err(ParameterNoLongerExists));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn categories_serialize_in_camel_case() {
+        let json = serde_json::to_string(&ProposalCategory::Technical).unwrap();
+        assert_eq!(json, r#""technical""#);
+        let category: ProposalCategory = serde_json::from_str(&json).unwrap();
+        assert_eq!(category, ProposalCategory::Technical);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn changes_serialize_as_tagged_json() {
+        let change = ParameterChange::LedgerTextNormalization(true);
+        let json = r#"{"kind":"ledgerTextNormalization","value":true}"#;
+        assert_eq!(serde_json::to_string(&change).unwrap(), json);
+        assert_eq!(serde_json::from_str::<ParameterChange>(json).unwrap(), change);
+    }
+}
@@ -0,0 +1,166 @@
+//! Serde helpers rendering byte fields as `0x`-prefixed hex strings.
+//!
+//! Runtime API types use these through `#[serde(with = "tidygen_primitives::hex")]`, so the
+//! RPC layer, the client crate and generated frontend types see `"0x1234"` instead of an
+//! array of numbers, the same way Substrate renders `Bytes`. Output is lowercase; input may
+//! use either case and the `0x` prefix is optional.
+//!
+//! Deserialization targets anything built from a `Vec<u8>` (`Vec<u8>`, `BoundedVec<u8, _>`,
+//! `[u8; N]`), so a value that does not fit the field's bound is rejected.
+
+use core::fmt;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use sp_std::vec::Vec;
+
+const HEX_CHARS: &[u8; 16] = b"0123456789abcdef";
+
+/// Serialize bytes as a `0x`-prefixed lowercase hex string.
+pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    let mut hex = Vec::with_capacity(2 + bytes.len() * 2);
+    hex.extend_from_slice(b"0x");
+    for byte in bytes {
+        hex.push(HEX_CHARS[usize::from(byte >> 4)]);
+        hex.push(HEX_CHARS[usize::from(byte & 0xf)]);
+    }
+    // Only ASCII was pushed
+    serializer.serialize_str(core::str::from_utf8(&hex).unwrap_or_default())
+}
+
+/// Deserialize a hex string into any byte container.
+pub fn deserialize<'de, D, B>(deserializer: D) -> Result<B, D::Error>
+where
+    D: Deserializer<'de>,
+    B: TryFrom<Vec<u8>>,
+{
+    HexBytes::deserialize(deserializer)?.into_bounded::<B, D::Error>()
+}
+
+/// The same encoding for optional byte fields; `None` is rendered as `null`.
+pub mod option {
+    use super::*;
+
+    /// Serialize optional bytes as a hex string or `null`.
+    pub fn serialize<S, B>(bytes: &Option<B>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        B: AsRef<[u8]>,
+    {
+        match bytes {
+            Some(bytes) => serializer.serialize_some(&HexRef(bytes.as_ref())),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    /// Deserialize a hex string or `null` into an optional byte container.
+    pub fn deserialize<'de, D, B>(deserializer: D) -> Result<Option<B>, D::Error>
+    where
+        D: Deserializer<'de>,
+        B: TryFrom<Vec<u8>>,
+    {
+        Option::<HexBytes>::deserialize(deserializer)?
+            .map(HexBytes::into_bounded::<B, D::Error>)
+            .transpose()
+    }
+}
+
+/// Borrowed bytes serialized as hex
+struct HexRef<'a>(&'a [u8]);
+
+impl Serialize for HexRef<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize(self.0, serializer)
+    }
+}
+
+/// Bytes decoded from a hex string, before being fitted into the target type
+struct HexBytes(Vec<u8>);
+
+impl HexBytes {
+    fn into_bounded<B: TryFrom<Vec<u8>>, E: de::Error>(self) -> Result<B, E> {
+        let len = self.0.len();
+        B::try_from(self.0).map_err(|_| E::invalid_length(len, &"bytes within the field bound"))
+    }
+}
+
+impl<'de> Deserialize<'de> for HexBytes {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_str(HexVisitor).map(HexBytes)
+    }
+}
+
+struct HexVisitor;
+
+impl<'de> de::Visitor<'de> for HexVisitor {
+    type Value = Vec<u8>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a hex string")
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Vec<u8>, E> {
+        decode(value).ok_or_else(|| E::invalid_value(de::Unexpected::Str(value), &self))
+    }
+}
+
+/// Decode a hex string, with or without `0x` prefix
+fn decode(value: &str) -> Option<Vec<u8>> {
+    let digits = value.strip_prefix("0x").unwrap_or(value).as_bytes();
+    if digits.len() % 2 != 0 {
+        return None;
+    }
+    digits.chunks(2).map(|pair| Some((nibble(pair[0])? << 4) | nibble(pair[1])?)).collect()
+}
+
+fn nibble(digit: u8) -> Option<u8> {
+    match digit {
+        b'0'..=b'9' => Some(digit - b'0'),
+        b'a'..=b'f' => Some(digit - b'a' + 10),
+        b'A'..=b'F' => Some(digit - b'A' + 10),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Fields {
+        #[serde(with = "super")]
+        hash: [u8; 4],
+        #[serde(with = "super")]
+        data: Vec<u8>,
+        #[serde(with = "super::option")]
+        digest: Option<[u8; 2]>,
+    }
+
+    #[test]
+    fn bytes_round_trip_as_hex() {
+        let fields = Fields { hash: [0xde, 0xad, 0xbe, 0xef], data: vec![], digest: Some([1, 2]) };
+        let json = serde_json::to_string(&fields).unwrap();
+        assert_eq!(json, r#"{"hash":"0xdeadbeef","data":"0x","digest":"0x0102"}"#);
+        assert_eq!(serde_json::from_str::<Fields>(&json).unwrap(), fields);
+
+        let none = Fields { digest: None, ..fields };
+        let json = serde_json::to_string(&none).unwrap();
+        assert_eq!(json, r#"{"hash":"0xdeadbeef","data":"0x","digest":null}"#);
+        assert_eq!(serde_json::from_str::<Fields>(&json).unwrap(), none);
+    }
+
+    #[test]
+    fn prefix_and_case_are_optional_on_input() {
+        let fields: Fields =
+            serde_json::from_str(r#"{"hash":"DEADbeef","data":"0x00ff","digest":null}"#).unwrap();
+        assert_eq!(fields.hash, [0xde, 0xad, 0xbe, 0xef]);
+        assert_eq!(fields.data, vec![0x00, 0xff]);
+    }
+
+    #[test]
+    fn malformed_or_oversized_hex_is_rejected() {
+        // Odd length, non-hex digit, and five bytes for a four byte field
+        for hash in ["0xabc", "0xzz00aabb", "0x0011223344"] {
+            let json = format!(r#"{{"hash":"{}","data":"0x","digest":null}}"#, hash);
+            assert!(serde_json::from_str::<Fields>(&json).is_err(), "{}", hash);
+        }
+    }
+}
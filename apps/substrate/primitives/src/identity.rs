@@ -0,0 +1,68 @@
+//! Identity checks shared between pallets.
+//!
+//! Pallets that gate actions on a registered identity take a [`DidProvider`] in their config
+//! instead of depending on the DID pallet, which implements it. The `()` implementation
+//! treats every account as identified, for chains without a DID pallet.
+//!
+//! Chains of the same group reference each other's DIDs. A DID identifier therefore
+//! resolves to a [`Resolution`]: an account on this chain, the network the DID lives on, or
+//! nothing known.
+
+use codec::{Decode, Encode, MaxEncodedLen};
+use scale_info::TypeInfo;
+
+/// Identifier of a network of the chain group, e.g. `*b"finance\0"`
+pub type NetworkId = [u8; 8];
+
+/// Source of the DID status of accounts
+pub trait DidProvider<AccountId> {
+    /// Whether `who` has an active DID
+    fn is_active(who: &AccountId) -> bool;
+}
+
+/// Every account counts as having an active DID
+impl<AccountId> DidProvider<AccountId> for () {
+    fn is_active(_who: &AccountId) -> bool {
+        true
+    }
+}
+
+/// Where a DID identifier resolves to.
+///
+/// Serialized as tagged JSON: `{"kind": "local", "value": <account>}`,
+/// `{"kind": "external", "value": "0x<network id>"}` or `{"kind": "unknown"}`.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode, TypeInfo, MaxEncodedLen)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(tag = "kind", content = "value", rename_all = "camelCase")
+)]
+pub enum Resolution<AccountId> {
+    /// Registered on this chain by the account
+    Local(AccountId),
+    /// Registered on another network of the group
+    External(#[cfg_attr(feature = "serde", serde(with = "crate::hex"))] NetworkId),
+    /// Neither registered on this chain nor known to live elsewhere
+    Unknown,
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolutions_serialize_as_tagged_json() {
+        let cases = [
+            (Resolution::Local(7u64), r#"{"kind":"local","value":7}"#),
+            (
+                Resolution::External(*b"finance\0"),
+                r#"{"kind":"external","value":"0x66696e616e636500"}"#,
+            ),
+            (Resolution::Unknown, r#"{"kind":"unknown"}"#),
+        ];
+        for (resolution, json) in cases {
+            assert_eq!(serde_json::to_string(&resolution).unwrap(), json);
+            assert_eq!(serde_json::from_str::<Resolution<u64>>(json).unwrap(), resolution);
+        }
+    }
+}
@@ -0,0 +1,39 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! # TidyGen Primitives
+//!
+//! Shared `no_std` building blocks used by the TidyGen pallets.
+//!
+//! ## Modules
+//!
+//! * `batch` - `SkipReason`, the per-item skip reason reported by batch extrinsics
+//! * `bounded_string` - `BoundedString`, length- and charset-checked text such as titles
+//! * `compat` - Call index and storage prefix regression checks for pallet tests (`std` only)
+//! * `features` - `FeatureFlags`, the optional features a runtime reports to clients
+//! * `governance` - `ParameterChange`, the typed parameter changes DAO proposals carry, and
+//!   the `ParameterHandler` pallets apply them with
+//! * `hex` - Serde helpers rendering byte fields as hex strings (`serde` feature)
+//! * `identity` - `DidProvider`, the DID status check pallets gate actions on, and the
+//!   `Resolution` of DID identifiers across networks
+//! * `pagination` - `PageCursor`, the opaque cursor taken and returned by paginated APIs
+//! * `subscription` - Lag-aware relaying of block events to RPC subscribers (`std` only)
+//! * `text` - UTF-8 validation and line ending normalization for user supplied text
+//! * `transitions` - Declarative status state machines shared by the pallets' lifecycles,
+//!   and the `OnStatusTransition` handler notified of every transition
+//! * `writer` - `ByteWriter`, bounded formatting of identifiers without `format!`/`String`
+
+pub mod batch;
+pub mod bounded_string;
+#[cfg(feature = "std")]
+pub mod compat;
+pub mod features;
+pub mod governance;
+#[cfg(feature = "serde")]
+pub mod hex;
+pub mod identity;
+pub mod pagination;
+#[cfg(feature = "std")]
+pub mod subscription;
+pub mod text;
+pub mod transitions;
+pub mod writer;
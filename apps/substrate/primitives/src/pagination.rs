@@ -0,0 +1,129 @@
+//! Opaque cursors for paginated runtime APIs.
+//!
+//! A paginated API returns a [`PageCursor`] with every page but the last, and takes it back
+//! to continue where that page ended. Callers treat the bytes as opaque; only the pallet that
+//! issued a cursor interprets it.
+//!
+//! Inside, a cursor is a version byte followed by the SCALE encoding of the API's position
+//! (for example the ID of the next invoice). A cursor with an unknown version, a payload that
+//! does not decode, or trailing bytes is rejected with [`InvalidCursor`] rather than being
+//! guessed at, so the position type can change in a later version without old cursors being
+//! misread. Cursors are bounded to [`MAX_CURSOR_LEN`] bytes.
+
+use codec::{Decode, DecodeAll, Encode, MaxEncodedLen};
+use scale_info::TypeInfo;
+use sp_core::{bounded::BoundedVec, ConstU32};
+use sp_std::vec::Vec;
+
+/// Maximum length of an encoded cursor, version byte included
+pub const MAX_CURSOR_LEN: u32 = 64;
+
+/// Opaque position in a paginated listing.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode, TypeInfo, MaxEncodedLen)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct PageCursor(
+    #[cfg_attr(feature = "serde", serde(with = "crate::hex"))]
+    BoundedVec<u8, ConstU32<MAX_CURSOR_LEN>>,
+);
+
+/// A cursor that was not issued by the API it was passed to, or was corrupted on the way.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Encode, Decode, TypeInfo, MaxEncodedLen)]
+pub struct InvalidCursor;
+
+impl PageCursor {
+    /// Version byte of the cursors issued by this code
+    pub const VERSION: u8 = 1;
+
+    /// Cursor resuming at `position`.
+    ///
+    /// Positions must encode to less than [`MAX_CURSOR_LEN`] bytes. A longer one is cut
+    /// short, so the cursor is rejected when it comes back instead of being misread.
+    pub fn new<P: Encode + MaxEncodedLen>(position: &P) -> Self {
+        debug_assert!(P::max_encoded_len() < MAX_CURSOR_LEN as usize);
+        let mut bytes = Vec::with_capacity(1 + position.encoded_size());
+        bytes.push(Self::VERSION);
+        position.encode_to(&mut bytes);
+        Self(BoundedVec::truncate_from(bytes))
+    }
+
+    /// Decode the position this cursor resumes at.
+    pub fn position<P: Decode>(&self) -> Result<P, InvalidCursor> {
+        match self.0.split_first() {
+            Some((&Self::VERSION, mut payload)) => {
+                P::decode_all(&mut payload).map_err(|_| InvalidCursor)
+            }
+            _ => Err(InvalidCursor),
+        }
+    }
+
+    /// Decode the position of an optional cursor, `None` meaning the first page.
+    pub fn position_or<P: Decode>(cursor: Option<&Self>, first: P) -> Result<P, InvalidCursor> {
+        cursor.map_or(Ok(first), Self::position)
+    }
+
+    /// The raw cursor bytes
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl TryFrom<Vec<u8>> for PageCursor {
+    type Error = InvalidCursor;
+
+    /// Wrap raw cursor bytes, e.g. received over the wire; only the length is checked here.
+    fn try_from(bytes: Vec<u8>) -> Result<Self, InvalidCursor> {
+        BoundedVec::try_from(bytes).map(Self).map_err(|_| InvalidCursor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cursors_round_trip_their_position() {
+        let cursor = PageCursor::new(&42u64);
+        assert_eq!(cursor.as_bytes()[0], PageCursor::VERSION);
+        assert_eq!(cursor.position::<u64>(), Ok(42));
+        assert_eq!(PageCursor::position_or(Some(&cursor), 0u64), Ok(42));
+        assert_eq!(PageCursor::position_or(None, 7u64), Ok(7));
+
+        let tuple = PageCursor::new(&(3u32, [9u8; 32]));
+        assert_eq!(tuple.position::<(u32, [u8; 32])>(), Ok((3, [9u8; 32])));
+    }
+
+    #[test]
+    fn corrupted_cursors_are_rejected() {
+        let valid = PageCursor::new(&42u64).as_bytes().to_vec();
+        let mut corrupted = vec![
+            vec![],
+            vec![PageCursor::VERSION],
+            // Unknown version
+            [&[2u8][..], &valid[1..]].concat(),
+            // Truncated and extended payloads
+            valid[..valid.len() - 1].to_vec(),
+            [&valid[..], &[0u8][..]].concat(),
+        ];
+        // Every single bit flip in the version byte
+        corrupted.extend((0..8).map(|bit| [&[valid[0] ^ (1 << bit)][..], &valid[1..]].concat()));
+
+        for bytes in corrupted {
+            let cursor = PageCursor::try_from(bytes.clone()).unwrap();
+            assert_eq!(cursor.position::<u64>(), Err(InvalidCursor), "{:?}", bytes);
+        }
+
+        // Longer than any cursor can be
+        assert_eq!(PageCursor::try_from(vec![1u8; 65]), Err(InvalidCursor));
+        assert!(PageCursor::decode(&mut &vec![1u8; 65].encode()[..]).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn cursors_serialize_as_hex() {
+        let cursor = PageCursor::new(&1u64);
+        let json = serde_json::to_string(&cursor).unwrap();
+        assert_eq!(json, r#""0x010100000000000000""#);
+        assert_eq!(serde_json::from_str::<PageCursor>(&json).unwrap(), cursor);
+    }
+}
@@ -0,0 +1,149 @@
+//! Lag-aware relaying of block events to RPC subscribers (`std` only).
+//!
+//! Subscription tasks read the events of every finalized block and push the matching ones to
+//! the subscriber. A subscriber that reads slower than blocks are finalized must not make the
+//! node queue notifications without limit, so [`lag_channel`] puts a bounded buffer between
+//! the two. Once it is full, new items are dropped and counted, and the subscriber receives
+//! [`SubscriptionItem::Lagged`] with the number it missed ahead of the next item that fits.
+//! A subscriber seeing it should re-read the state it tracks instead of relying on deltas.
+
+use futures::{channel::mpsc, Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use sp_std::vec::Vec;
+
+/// Notification sent to a subscriber
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum SubscriptionItem<T> {
+    /// The next matching event
+    Event(T),
+    /// `missed` events were dropped because the subscriber fell behind
+    Lagged { missed: u64 },
+}
+
+/// Sending half of a [`lag_channel`]
+pub struct LagSender<T> {
+    tx: mpsc::Sender<SubscriptionItem<T>>,
+    missed: u64,
+}
+
+impl<T> LagSender<T> {
+    /// Queue `item`, or drop it when the buffer is full.
+    ///
+    /// Returns `false` once the subscriber is gone, so the caller can stop producing.
+    pub fn send(&mut self, item: T) -> bool {
+        if self.missed > 0 {
+            match self.tx.try_send(SubscriptionItem::Lagged { missed: self.missed }) {
+                Ok(()) => self.missed = 0,
+                Err(e) if e.is_disconnected() => return false,
+                Err(_) => {
+                    self.missed = self.missed.saturating_add(1);
+                    return true;
+                }
+            }
+        }
+
+        match self.tx.try_send(SubscriptionItem::Event(item)) {
+            Ok(()) => true,
+            Err(e) if e.is_disconnected() => false,
+            Err(_) => {
+                self.missed = self.missed.saturating_add(1);
+                true
+            }
+        }
+    }
+}
+
+/// Channel holding up to `capacity` notifications (at least one) for a subscriber.
+pub fn lag_channel<T>(capacity: usize) -> (LagSender<T>, mpsc::Receiver<SubscriptionItem<T>>) {
+    // The only sender gets a slot of its own on top of the shared buffer
+    let (tx, rx) = mpsc::channel(capacity.saturating_sub(1));
+    (LagSender { tx, missed: 0 }, rx)
+}
+
+/// Send the items `fetch` returns for each of `blocks` until the subscriber goes away.
+///
+/// Blocks whose items cannot be read, e.g. because their state was pruned, are skipped.
+pub async fn relay<Hash, Item, E>(
+    blocks: impl Stream<Item = Hash>,
+    mut fetch: impl FnMut(Hash) -> Result<Vec<Item>, E>,
+    mut sender: LagSender<Item>,
+) {
+    futures::pin_mut!(blocks);
+    while let Some(hash) = blocks.next().await {
+        for item in fetch(hash).unwrap_or_default() {
+            if !sender.send(item) {
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::{executor::LocalPool, task::LocalSpawnExt};
+
+    #[test]
+    fn full_buffer_drops_and_reports_the_gap() {
+        let (mut sender, mut receiver) = lag_channel(2);
+        for item in 0..5 {
+            assert!(sender.send(item));
+        }
+        assert_eq!(receiver.try_next().unwrap(), Some(SubscriptionItem::Event(0)));
+        assert_eq!(receiver.try_next().unwrap(), Some(SubscriptionItem::Event(1)));
+        assert!(receiver.try_next().is_err());
+
+        // The gap is reported right before the next item that fits
+        assert!(sender.send(5));
+        assert_eq!(receiver.try_next().unwrap(), Some(SubscriptionItem::Lagged { missed: 3 }));
+        assert_eq!(receiver.try_next().unwrap(), Some(SubscriptionItem::Event(5)));
+
+        drop(receiver);
+        assert!(!sender.send(6));
+    }
+
+    #[test]
+    fn relay_forwards_items_of_each_block() {
+        let mut pool = LocalPool::new();
+        let (blocks, finalized) = futures::channel::mpsc::unbounded::<u32>();
+        let (sender, mut receiver) = lag_channel(2);
+        // Block `n` holds `n` items; block 9 cannot be read
+        let fetch = |n: u32| if n == 9 { Err(()) } else { Ok((0..n).map(|i| (n, i)).collect()) };
+        pool.spawner().spawn_local(relay(finalized, fetch, sender)).unwrap();
+
+        let next_block = |pool: &mut LocalPool, n: u32| {
+            blocks.unbounded_send(n).unwrap();
+            pool.run_until_stalled();
+        };
+        let mut drain = || -> Vec<_> {
+            std::iter::from_fn(|| receiver.try_next().ok().flatten()).collect()
+        };
+
+        next_block(&mut pool, 1);
+        next_block(&mut pool, 9);
+        assert_eq!(drain(), vec![SubscriptionItem::Event((1, 0))]);
+
+        // Two of the four items fit
+        next_block(&mut pool, 4);
+        assert_eq!(drain(), vec![SubscriptionItem::Event((4, 0)), SubscriptionItem::Event((4, 1))]);
+        next_block(&mut pool, 1);
+        assert_eq!(
+            drain(),
+            vec![SubscriptionItem::Lagged { missed: 2 }, SubscriptionItem::Event((1, 0))]
+        );
+    }
+
+    #[test]
+    fn items_serialize_with_a_type_tag() {
+        #[derive(Serialize)]
+        struct Anchored {
+            id: u32,
+        }
+
+        let event = serde_json::to_string(&SubscriptionItem::Event(Anchored { id: 7 })).unwrap();
+        assert_eq!(event, r#"{"type":"event","id":7}"#);
+        let lagged = serde_json::to_string(&SubscriptionItem::<Anchored>::Lagged { missed: 3 });
+        assert_eq!(lagged.unwrap(), r#"{"type":"lagged","missed":3}"#);
+    }
+}
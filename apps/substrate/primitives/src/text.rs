@@ -0,0 +1,85 @@
+//! Text handling for titles, descriptions and metadata.
+//!
+//! Text fields are stored as raw bytes and some of them are hashed on-chain. Documents
+//! produced on Windows use CRLF line endings while Linux produces LF, so the "same"
+//! document would otherwise hash differently depending on where it was written.
+//!
+//! Normalization rules:
+//! * `\r\n` (CRLF) becomes `\n`
+//! * a lone `\r` (old Mac style) becomes `\n`
+//! * every other byte is kept as is
+//!
+//! The functions here only rely on `core`/`sp_std` and are safe to use in the runtime.
+
+use sp_std::vec::Vec;
+
+/// Returns `true` if `bytes` is valid UTF-8.
+pub fn is_valid_utf8(bytes: &[u8]) -> bool {
+    core::str::from_utf8(bytes).is_ok()
+}
+
+/// Rewrite CRLF and lone CR line endings to LF.
+///
+/// Only ASCII bytes are touched, so valid UTF-8 input stays valid UTF-8.
+pub fn normalize_line_endings(bytes: Vec<u8>) -> Vec<u8> {
+    if !bytes.contains(&b'\r') {
+        return bytes;
+    }
+
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut iter = bytes.iter().peekable();
+    while let Some(&byte) = iter.next() {
+        if byte == b'\r' {
+            // Swallow the LF of a CRLF pair, the CR itself becomes LF
+            if iter.peek() == Some(&&b'\n') {
+                iter.next();
+            }
+            out.push(b'\n');
+        } else {
+            out.push(byte);
+        }
+    }
+    out
+}
+
+/// Validate `bytes` as UTF-8 and normalize its line endings.
+///
+/// Returns `None` if the input is not valid UTF-8.
+pub fn sanitize(bytes: Vec<u8>) -> Option<Vec<u8>> {
+    if !is_valid_utf8(&bytes) {
+        return None;
+    }
+    Some(normalize_line_endings(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crlf_and_lf_normalize_to_same_bytes() {
+        let crlf = b"INV-2025-001\r\nLine 2\r\n".to_vec();
+        let lf = b"INV-2025-001\nLine 2\n".to_vec();
+        assert_eq!(normalize_line_endings(crlf), lf);
+        assert_eq!(normalize_line_endings(lf.clone()), lf);
+    }
+
+    #[test]
+    fn lone_cr_becomes_lf() {
+        assert_eq!(normalize_line_endings(b"a\rb\r".to_vec()), b"a\nb\n".to_vec());
+        assert_eq!(normalize_line_endings(b"\r\r\n".to_vec()), b"\n\n".to_vec());
+    }
+
+    #[test]
+    fn utf8_is_preserved() {
+        let text = "Facture n\u{b0}1\r\nMontant: 100 \u{20ac}".as_bytes().to_vec();
+        let expected = "Facture n\u{b0}1\nMontant: 100 \u{20ac}".as_bytes().to_vec();
+        assert_eq!(sanitize(text), Some(expected));
+    }
+
+    #[test]
+    fn invalid_utf8_is_rejected() {
+        assert!(!is_valid_utf8(&[0xff, 0xfe]));
+        assert_eq!(sanitize(vec![b'a', 0xc3]), None);
+    }
+}
@@ -0,0 +1,194 @@
+//! Declarative status state machines.
+//!
+//! Each pallet describes the lifecycle of a status enum as a const table of allowed
+//! `(from, to)` edges instead of hand-written `ensure!` chains:
+//!
+//! ```ignore
+//! pub const INVOICE_STATUS: StateMachine<InvoiceStatus> = StateMachine::new(&[
+//!     (InvoiceStatus::Pending, InvoiceStatus::Paid),
+//!     (InvoiceStatus::Pending, InvoiceStatus::Cancelled),
+//! ]);
+//!
+//! let old = INVOICE_STATUS.transition(&mut invoice.status, InvoiceStatus::Paid)?;
+//! ```
+//!
+//! A state without outgoing edges is terminal. Self-transitions are only allowed if the
+//! table lists them explicitly.
+//!
+//! Pallets report every transition they apply to an [`OnStatusTransition`] handler, e.g. a
+//! compliance log archiving them, naming the entity with a [`TransitionSubject`] and the
+//! statuses with their [`status_index`].
+
+use codec::{Decode, Encode, MaxEncodedLen};
+use scale_info::TypeInfo;
+
+/// The requested transition is not in the table.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InvalidTransition;
+
+/// A state machine over `S` defined by a static transition table.
+pub struct StateMachine<S: 'static> {
+    edges: &'static [(S, S)],
+}
+
+impl<S: 'static> StateMachine<S> {
+    /// Build a state machine from its allowed `(from, to)` edges.
+    pub const fn new(edges: &'static [(S, S)]) -> Self {
+        Self { edges }
+    }
+
+    /// The transition table.
+    pub fn edges(&self) -> &'static [(S, S)] {
+        self.edges
+    }
+}
+
+impl<S: PartialEq + 'static> StateMachine<S> {
+    /// Returns `true` if `from -> to` is an allowed transition.
+    pub fn can_transition(&self, from: &S, to: &S) -> bool {
+        self.edges.iter().any(|(f, t)| f == from && t == to)
+    }
+
+    /// Returns `true` if no transition leaves `state`.
+    pub fn is_terminal(&self, state: &S) -> bool {
+        !self.edges.iter().any(|(f, _)| f == state)
+    }
+
+    /// Move `state` to `to` if the table allows it, returning the previous state.
+    ///
+    /// `state` is left untouched on error.
+    pub fn transition(&self, state: &mut S, to: S) -> Result<S, InvalidTransition> {
+        if !self.can_transition(state, &to) {
+            return Err(InvalidTransition);
+        }
+        Ok(core::mem::replace(state, to))
+    }
+}
+
+/// Entity whose status changed, which also names the pallet owning it.
+///
+/// Variants are append-only, as handlers may store them.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode, TypeInfo, MaxEncodedLen)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(tag = "kind", content = "value", rename_all = "camelCase")
+)]
+pub enum TransitionSubject<AccountId> {
+    /// Invoice in pallet-ledger: (client, invoice_id)
+    Invoice(AccountId, u64),
+    /// Ledger entry in pallet-tidygen-ledger: (entry_id)
+    LedgerEntry(u64),
+    /// Proposal in pallet-dao: (proposal_id)
+    Proposal(u64),
+    /// DID document in pallet-did: (account)
+    Did(AccountId),
+}
+
+/// SCALE index of a fieldless status variant, as reported to [`OnStatusTransition`]
+pub fn status_index<S: Encode>(status: &S) -> u8 {
+    status.using_encoded(|bytes| bytes.first().copied().unwrap_or_default())
+}
+
+/// Notified of every status transition a pallet applies
+pub trait OnStatusTransition<AccountId> {
+    /// `subject` moved from status `old` to status `new`, both given by [`status_index`]
+    fn on_transition(subject: TransitionSubject<AccountId>, old: u8, new: u8);
+}
+
+/// Nobody is notified
+impl<AccountId> OnStatusTransition<AccountId> for () {
+    fn on_transition(_subject: TransitionSubject<AccountId>, _old: u8, _new: u8) {}
+}
+
+/// Notifies `A`, then `B`. Nest pairs to chain more handlers.
+impl<AccountId: Clone, A, B> OnStatusTransition<AccountId> for (A, B)
+where
+    A: OnStatusTransition<AccountId>,
+    B: OnStatusTransition<AccountId>,
+{
+    fn on_transition(subject: TransitionSubject<AccountId>, old: u8, new: u8) {
+        A::on_transition(subject.clone(), old, new);
+        B::on_transition(subject, old, new);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    enum Light {
+        Red,
+        Green,
+        Yellow,
+        Off,
+    }
+
+    const ALL: [Light; 4] = [Light::Red, Light::Green, Light::Yellow, Light::Off];
+
+    const LIGHT: StateMachine<Light> = StateMachine::new(&[
+        (Light::Red, Light::Green),
+        (Light::Green, Light::Yellow),
+        (Light::Yellow, Light::Red),
+        (Light::Red, Light::Off),
+        (Light::Green, Light::Off),
+        (Light::Yellow, Light::Off),
+    ]);
+
+    #[test]
+    fn can_transition_matches_table_for_every_pair() {
+        for from in ALL {
+            for to in ALL {
+                let listed = LIGHT.edges().contains(&(from, to));
+                assert_eq!(LIGHT.can_transition(&from, &to), listed, "{:?} -> {:?}", from, to);
+            }
+        }
+    }
+
+    #[test]
+    fn transition_applies_only_allowed_edges() {
+        for from in ALL {
+            for to in ALL {
+                let mut state = from;
+                match LIGHT.transition(&mut state, to) {
+                    Ok(old) => {
+                        assert_eq!(old, from);
+                        assert_eq!(state, to);
+                    }
+                    Err(InvalidTransition) => assert_eq!(state, from),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn terminal_states_have_no_outgoing_edges() {
+        assert!(LIGHT.is_terminal(&Light::Off));
+        for to in ALL {
+            assert!(!LIGHT.can_transition(&Light::Off, &to));
+        }
+        assert!(!LIGHT.is_terminal(&Light::Red));
+    }
+
+    #[test]
+    fn self_transition_requires_explicit_edge() {
+        let mut state = Light::Red;
+        assert_eq!(LIGHT.transition(&mut state, Light::Red), Err(InvalidTransition));
+    }
+
+    #[derive(Encode)]
+    enum Status {
+        First,
+        Second,
+        #[codec(index = 7)]
+        Pinned,
+    }
+
+    #[test]
+    fn status_index_is_the_variant_index() {
+        assert_eq!(status_index(&Status::First), 0);
+        assert_eq!(status_index(&Status::Second), 1);
+        assert_eq!(status_index(&Status::Pinned), 7);
+    }
+}
@@ -0,0 +1,170 @@
+//! Formatting of identifiers and documents into bounded byte buffers.
+//!
+//! Runtime code builds strings such as DID identifiers byte by byte instead of going
+//! through `format!` and `String`, which pull the `core::fmt` machinery into the wasm blob
+//! and only produce a bound-checked value at the very end. A [`ByteWriter`] knows its
+//! bound from the start: a push that does not fit fails with [`Overflow`] and leaves the
+//! buffer as it was, so the caller decides what an oversized value means.
+//!
+//! The output only depends on the pushed values, never on the platform, so native and wasm
+//! builds produce the same bytes.
+
+use codec::{Decode, Encode, MaxEncodedLen};
+use core::marker::PhantomData;
+use scale_info::TypeInfo;
+use sp_core::{bounded::BoundedVec, Get};
+use sp_std::vec::Vec;
+
+const HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+/// A push did not fit in the writer's bound.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Encode, Decode, TypeInfo, MaxEncodedLen)]
+pub struct Overflow;
+
+/// Append-only byte buffer holding at most `S::get()` bytes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ByteWriter<S: Get<u32>> {
+    buf: Vec<u8>,
+    _bound: PhantomData<S>,
+}
+
+impl<S: Get<u32>> Default for ByteWriter<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S: Get<u32>> ByteWriter<S> {
+    /// Empty writer
+    pub fn new() -> Self {
+        Self { buf: Vec::new(), _bound: PhantomData }
+    }
+
+    /// Bytes written so far
+    pub fn len(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Whether nothing has been written yet
+    pub fn is_empty(&self) -> bool {
+        self.buf.is_empty()
+    }
+
+    /// Bytes still available before the bound is reached
+    pub fn remaining(&self) -> usize {
+        (S::get() as usize).saturating_sub(self.buf.len())
+    }
+
+    /// Append raw bytes
+    pub fn push_bytes(&mut self, bytes: &[u8]) -> Result<(), Overflow> {
+        self.reserve(bytes.len())?;
+        self.buf.extend_from_slice(bytes);
+        Ok(())
+    }
+
+    /// Append a string as its UTF-8 bytes
+    pub fn push_str(&mut self, s: &str) -> Result<(), Overflow> {
+        self.push_bytes(s.as_bytes())
+    }
+
+    /// Append `bytes` as lowercase hex, two digits per byte and without a `0x` prefix
+    pub fn push_hex(&mut self, bytes: &[u8]) -> Result<(), Overflow> {
+        self.reserve(bytes.len().saturating_mul(2))?;
+        for &byte in bytes {
+            self.buf.push(HEX_DIGITS[(byte >> 4) as usize]);
+            self.buf.push(HEX_DIGITS[(byte & 0xf) as usize]);
+        }
+        Ok(())
+    }
+
+    /// Append `value` in decimal, without leading zeros
+    pub fn push_u64(&mut self, value: u64) -> Result<(), Overflow> {
+        self.push_u128(value.into())
+    }
+
+    /// Append `value` in decimal, without leading zeros
+    pub fn push_u128(&mut self, mut value: u128) -> Result<(), Overflow> {
+        // u128::MAX has 39 digits
+        let mut digits = [0u8; 39];
+        let mut start = digits.len();
+        loop {
+            start -= 1;
+            digits[start] = b'0' + (value % 10) as u8;
+            value /= 10;
+            if value == 0 {
+                break;
+            }
+        }
+        self.push_bytes(&digits[start..])
+    }
+
+    /// The bytes written, within the bound
+    pub fn into_inner(self) -> BoundedVec<u8, S> {
+        // Every push checked the bound, so nothing is cut off
+        BoundedVec::truncate_from(self.buf)
+    }
+
+    fn reserve(&mut self, additional: usize) -> Result<(), Overflow> {
+        if additional > self.remaining() {
+            return Err(Overflow);
+        }
+        self.buf.reserve(additional);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sp_core::ConstU32;
+
+    #[test]
+    fn writes_strings_hex_and_numbers() {
+        let mut writer = ByteWriter::<ConstU32<128>>::new();
+        writer.push_str("did:").unwrap();
+        writer.push_hex(&[0x00, 0xab, 0x0f]).unwrap();
+        writer.push_bytes(b":").unwrap();
+        writer.push_u64(0).unwrap();
+        writer.push_bytes(b",").unwrap();
+        writer.push_u64(u64::MAX).unwrap();
+        writer.push_bytes(b",").unwrap();
+        writer.push_u128(u128::MAX).unwrap();
+
+        assert_eq!(
+            writer.into_inner().to_vec(),
+            b"did:00ab0f:0,18446744073709551615,340282366920938463463374607431768211455".to_vec()
+        );
+    }
+
+    #[test]
+    fn fills_up_to_the_bound_exactly() {
+        let mut writer = ByteWriter::<ConstU32<4>>::new();
+        writer.push_hex(&[0xde, 0xad]).unwrap();
+        assert_eq!(writer.remaining(), 0);
+        assert_eq!(writer.into_inner().to_vec(), b"dead".to_vec());
+    }
+
+    #[test]
+    fn overflowing_push_leaves_buffer_untouched() {
+        let mut writer = ByteWriter::<ConstU32<5>>::new();
+        writer.push_str("abc").unwrap();
+
+        assert_eq!(writer.push_str("def"), Err(Overflow));
+        assert_eq!(writer.push_hex(&[0x01, 0x02]), Err(Overflow));
+        assert_eq!(writer.push_u64(100), Err(Overflow));
+        assert_eq!(writer.len(), 3);
+
+        // A push that fits still succeeds afterwards
+        writer.push_u64(42).unwrap();
+        assert_eq!(writer.into_inner().to_vec(), b"abc42".to_vec());
+    }
+
+    #[test]
+    fn zero_bound_rejects_everything_but_empty_pushes() {
+        let mut writer = ByteWriter::<ConstU32<0>>::new();
+        assert_eq!(writer.push_str(""), Ok(()));
+        assert_eq!(writer.push_hex(&[]), Ok(()));
+        assert_eq!(writer.push_u64(0), Err(Overflow));
+        assert!(writer.is_empty());
+    }
+}
@@ -0,0 +1,64 @@
+//! RPC interface describing a TidyGen runtime as a whole
+
+use jsonrpsee::{
+    core::{async_trait, RpcResult},
+    proc_macros::rpc,
+    types::error::{CallError, ErrorObject},
+};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::traits::Block as BlockT;
+use std::sync::Arc;
+
+pub use tidygen_runtime_api::{FeatureFlags, TidygenApi as TidygenRuntimeApi};
+
+/// Error code for failed runtime API calls
+pub const RUNTIME_ERROR: i32 = 1;
+
+#[rpc(client, server)]
+pub trait TidygenApi<BlockHash> {
+    /// Get the optional features the runtime enables, as one snake_case boolean per flag
+    #[method(name = "tidygen_getFeatures")]
+    fn get_features(&self, at: Option<BlockHash>) -> RpcResult<FeatureFlags>;
+}
+
+/// A struct that implements the `TidygenApi`.
+pub struct Tidygen<C, Block> {
+    client: Arc<C>,
+    _marker: std::marker::PhantomData<Block>,
+}
+
+impl<C, Block> Tidygen<C, Block> {
+    /// Create new `Tidygen` instance with the given reference to the client.
+    pub fn new(client: Arc<C>) -> Self {
+        Self {
+            client,
+            _marker: Default::default(),
+        }
+    }
+}
+
+#[async_trait]
+impl<C, Block> TidygenApiServer<<Block as BlockT>::Hash> for Tidygen<C, Block>
+where
+    Block: BlockT,
+    C: Send + Sync + 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block>,
+    C::Api: TidygenRuntimeApi<Block>,
+{
+    fn get_features(&self, at: Option<<Block as BlockT>::Hash>) -> RpcResult<FeatureFlags> {
+        let api = self.client.runtime_api();
+        let at = at.unwrap_or_else(|| self.client.info().best_hash);
+
+        api.features(at).map_err(runtime_error_into_rpc_err)
+    }
+}
+
+/// Converts a runtime trap into an RPC error.
+fn runtime_error_into_rpc_err(err: impl std::fmt::Debug) -> jsonrpsee::core::Error {
+    CallError::Custom(ErrorObject::owned(
+        RUNTIME_ERROR,
+        "Runtime error",
+        Some(format!("{:?}", err)),
+    ))
+    .into()
+}
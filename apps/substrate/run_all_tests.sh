@@ -53,6 +53,7 @@ echo ""
 run_pallet_tests "pallet-ledger" "pallet-ledger"
 run_pallet_tests "pallet-did" "pallet-did"
 run_pallet_tests "pallet-dao" "pallet-dao"
+run_pallet_tests "runtime wiring" "tidygen-runtime-smoke"
 
 # Summary
 echo "================================================"
@@ -0,0 +1,14 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Runtime API describing a TidyGen runtime as a whole, rather than a single pallet
+
+pub use tidygen_primitives::features::FeatureFlags;
+
+sp_api::decl_runtime_apis! {
+    /// The API to learn what a TidyGen runtime offers
+    pub trait TidygenApi {
+        /// Get the optional features this runtime enables, the `feature_bits()` of its
+        /// pallets combined
+        fn features() -> FeatureFlags;
+    }
+}
@@ -0,0 +1,67 @@
+//! Chain extension letting ink contracts query `pallet-did`
+//!
+//! Contracts cannot read pallet storage, so checks such as "does the caller have an active
+//! DID" go through this extension. Each function takes a SCALE-encoded account and writes
+//! back a SCALE-encoded answer:
+//!
+//! | Function ID | Function             | Answer            |
+//! |-------------|----------------------|-------------------|
+//! | 1           | `is_did_active`      | `bool`            |
+//! | 2           | `get_did_identifier` | `Option<Vec<u8>>` |
+//!
+//! The extension ID in the upper 16 bits of the ID a contract calls with is ignored, as this
+//! is the only extension of the runtime. The contract side declares the same IDs in its
+//! `#[ink::chain_extension]` trait.
+
+use codec::Encode;
+use frame_support::traits::Get;
+use pallet_contracts::chain_extension::{ChainExtension, Environment, Ext, InitState, RetVal};
+use sp_runtime::DispatchError;
+
+/// Function ID of `is_did_active(account) -> bool`
+pub const IS_DID_ACTIVE: u16 = 1;
+
+/// Function ID of `get_did_identifier(account) -> Option<Vec<u8>>`
+pub const GET_DID_IDENTIFIER: u16 = 2;
+
+/// `ChainExtension` answering DID queries of contracts from `pallet-did`
+#[derive(Default)]
+pub struct DidExtension;
+
+impl DidExtension {
+    /// SCALE-encoded answer of function `func_id` about `account`
+    ///
+    /// Kept apart from `call` so the answers can be checked without deploying a contract.
+    pub fn answer<T: pallet_did::Config>(
+        func_id: u16,
+        account: &T::AccountId,
+    ) -> Result<Vec<u8>, DispatchError> {
+        match func_id {
+            IS_DID_ACTIVE => Ok(pallet_did::Pallet::<T>::is_did_active(account).encode()),
+            GET_DID_IDENTIFIER => Ok(pallet_did::Pallet::<T>::get_did(account)
+                .map(|did| did.did_identifier.into_inner())
+                .encode()),
+            _ => Err(DispatchError::Other("Unknown DID extension function")),
+        }
+    }
+}
+
+impl<T> ChainExtension<T> for DidExtension
+where
+    T: pallet_contracts::Config + pallet_did::Config,
+{
+    fn call<E: Ext<T = T>>(
+        &mut self,
+        env: Environment<E, InitState>,
+    ) -> Result<RetVal, DispatchError> {
+        let func_id = env.func_id();
+        let mut env = env.buf_in_buf_out();
+        // Both functions read the account's DID document once
+        env.charge_weight(T::DbWeight::get().reads(1))?;
+        let account: T::AccountId = env.read_as()?;
+
+        let output = Self::answer::<T>(func_id, &account)?;
+        env.write(&output, false, None)?;
+        Ok(RetVal::Converging(0))
+    }
+}
@@ -0,0 +1,369 @@
+//! # TidyGen Runtime Smoke Test
+//!
+//! A `construct_runtime!` with every pallet of this repository, plus `pallet-balances`,
+//! `pallet-timestamp` and `pallet-contracts`, configured the way the node runtime is expected
+//! to be.
+//!
+//! ## Overview
+//!
+//! Wiring mistakes such as a missing `Currency` or a `RuntimeEvent` bound that does not hold
+//! used to surface only when assembling the node. This crate is a workspace member and is
+//! compiled with every `cargo build --workspace`, so:
+//! - A new `Config` item fails to compile here before it reaches the downstream node
+//! - The integration tests under `tests/` dispatch one extrinsic per pallet and check that
+//!   its event round-trips through the `RuntimeEvent` encoding, and exercise couplings
+//!   between pallets such as the ledger requiring an active DID for invoice clients
+//!
+//! Pallet indices are fixed, since they are part of every encoded call and event. New pallets
+//! are appended with the next free index; this is the canonical wiring example.
+//!
+//! `pallet-contracts` is registered with `DidExtension`, the chain extension through which ink
+//! contracts check the DIDs of their callers (see `chain_extension`).
+
+pub mod chain_extension;
+
+pub use chain_extension::DidExtension;
+
+use frame_support::{
+    parameter_types,
+    traits::{ConstBool, ConstU128, ConstU32, ConstU64, Nothing, Randomness},
+    weights::Weight,
+    PalletId,
+};
+use pallet_access_log::{Capability, CapabilityCheck};
+use sp_core::H256;
+use sp_runtime::{
+    testing::TestXt,
+    traits::{BlakeTwo256, Convert, Hash, IdentityLookup},
+    BuildStorage, Perbill,
+};
+use tidygen_primitives::features::FeatureFlags;
+
+pub type AccountId = u64;
+pub type Balance = u128;
+pub type BlockNumber = u64;
+
+type Block = frame_system::mocking::MockBlock<Runtime>;
+
+pub type Extrinsic = TestXt<RuntimeCall, ()>;
+
+frame_support::construct_runtime!(
+    pub enum Runtime {
+        System: frame_system = 0,
+        Timestamp: pallet_timestamp = 1,
+        Balances: pallet_balances = 2,
+        Ledger: pallet_ledger = 10,
+        TidygenLedger: pallet_tidygen_ledger = 11,
+        Did: pallet_did = 12,
+        Dao: pallet_dao = 13,
+        AccessLog: pallet_access_log = 14,
+        ComplianceLog: pallet_compliance_log = 15,
+        Contracts: pallet_contracts = 16,
+    }
+);
+
+parameter_types! {
+    pub const BlockHashCount: BlockNumber = 250;
+    pub const SS58Prefix: u8 = 42;
+}
+
+impl frame_system::Config for Runtime {
+    type BaseCallFilter = frame_support::traits::Everything;
+    type BlockWeights = ();
+    type BlockLength = ();
+    type DbWeight = ();
+    type RuntimeOrigin = RuntimeOrigin;
+    type RuntimeCall = RuntimeCall;
+    type Nonce = u64;
+    type Hash = H256;
+    type Hashing = BlakeTwo256;
+    type AccountId = AccountId;
+    type Lookup = IdentityLookup<Self::AccountId>;
+    type Block = Block;
+    type RuntimeEvent = RuntimeEvent;
+    type BlockHashCount = BlockHashCount;
+    type Version = ();
+    type PalletInfo = PalletInfo;
+    type AccountData = pallet_balances::AccountData<Balance>;
+    type OnNewAccount = ();
+    type OnKilledAccount = ();
+    type SystemWeightInfo = ();
+    type SS58Prefix = SS58Prefix;
+    type OnSetCode = ();
+    type MaxConsumers = ConstU32<16>;
+}
+
+impl<C> frame_system::offchain::SendTransactionTypes<C> for Runtime
+where
+    RuntimeCall: From<C>,
+{
+    type OverarchingCall = RuntimeCall;
+    type Extrinsic = Extrinsic;
+}
+
+impl pallet_timestamp::Config for Runtime {
+    type Moment = u64;
+    type OnTimestampSet = ();
+    type MinimumPeriod = ConstU64<3_000>;
+    type WeightInfo = ();
+}
+
+impl pallet_balances::Config for Runtime {
+    type MaxLocks = ConstU32<50>;
+    type MaxReserves = ();
+    type ReserveIdentifier = [u8; 8];
+    type Balance = Balance;
+    type RuntimeEvent = RuntimeEvent;
+    type DustRemoval = ();
+    type ExistentialDeposit = ConstU128<1>;
+    type AccountStore = System;
+    type WeightInfo = ();
+    type FreezeIdentifier = ();
+    type MaxFreezes = ();
+    type RuntimeHoldReason = RuntimeHoldReason;
+    type RuntimeFreezeReason = ();
+    // Storage deposits of `pallet-contracts`
+    type MaxHolds = ConstU32<1>;
+}
+
+parameter_types! {
+    pub const OverdueCheckWeight: Weight = Weight::from_parts(1_000_000_000, 0);
+    pub const LedgerHashAlgorithm: pallet_ledger::HashAlgorithm =
+        pallet_ledger::HashAlgorithm::Sha256;
+    // 1 per mille of the net amount for every day (14_400 blocks) past due
+    pub const LedgerLateFee: pallet_ledger::LateFee<Balance> = pallet_ledger::LateFee::PerMille(1);
+}
+
+impl pallet_ledger::Config for Runtime {
+    type RuntimeEvent = RuntimeEvent;
+    type Currency = Balances;
+    type MaxMetadataLength = ConstU32<1024>;
+    type MaxInvoicesPerClient = ConstU32<1000>;
+    type MaxDueInvoicesPerBlock = ConstU32<16>;
+    type MaxExpiringInvoicesPerBlock = ConstU32<16>;
+    type OverdueCheckWeight = OverdueCheckWeight;
+    type AdminOrigin = frame_system::EnsureRoot<AccountId>;
+    type MaxOperatorKeys = ConstU32<4>;
+    type MaxExternalRefLength = ConstU32<64>;
+    type CancellationTimeout = ConstU64<100>;
+    type CancellationCooldown = ConstU64<100>;
+    type DisputeResolutionOrigin = frame_system::EnsureRoot<AccountId>;
+    type MaxDisputeReasonLength = ConstU32<256>;
+    type DisputeHandler = ();
+    type OnTransition = ComplianceLog;
+    type MaxBatchSize = ConstU32<200>;
+    type OpenCreation = ConstBool<true>;
+    type RequireAcceptance = ConstBool<false>;
+    type MaxLineItems = ConstU32<64>;
+    type MaxLineItemDescriptionLength = ConstU32<256>;
+    type MaxSignatureLength = ConstU32<64>;
+    type CreatorKeys = pallet_ledger::AccountIdAsPublicKey;
+    type InvoiceHashAlgorithm = LedgerHashAlgorithm;
+    type DidProvider = Did;
+    type InvoiceDeposit = ConstU128<100>;
+    // No treasury in this runtime, so slashed deposits are burned
+    type Slashed = ();
+    type ChallengeBond = ConstU128<500>;
+    type ChallengeTimeout = ConstU64<14_400>;
+    type MaxChallengesPerBlock = ConstU32<16>;
+    type ChallengeJudgeOrigin = frame_system::EnsureRoot<AccountId>;
+    type InvoiceWebhook = ConstBool<true>;
+    type MaxWebhookInvoicesPerBlock = ConstU32<64>;
+    type UnsignedPriority = ConstU64<100>;
+    type MaxAttachmentsPerInvoice = ConstU32<16>;
+    type MaxAttachmentLabelLength = ConstU32<128>;
+    type LateFeePeriod = ConstU64<14_400>;
+    type LateFeePerPeriod = LedgerLateFee;
+    // 30 days of 6 second blocks
+    type AbandonThreshold = ConstU64<432_000>;
+    type MaxAssignmentsPerInvoice = ConstU32<16>;
+    type MaxGrantsPerInvoice = ConstU32<32>;
+    type MaxArchivedPerCall = ConstU32<50>;
+    type WeightInfo = pallet_ledger::weights::SubstrateWeight<Runtime>;
+}
+
+impl pallet_tidygen_ledger::Config for Runtime {
+    type RuntimeEvent = RuntimeEvent;
+    type Currency = Balances;
+    type MaxTransactionTypeLength = ConstU32<32>;
+    type MaxMetadataLength = ConstU32<256>;
+    type MaxBatchSize = ConstU32<200>;
+    type OnTransition = ComplianceLog;
+}
+
+impl pallet_did::Config for Runtime {
+    type RuntimeEvent = RuntimeEvent;
+    type MaxPublicKeyLength = ConstU32<256>;
+    type MaxMetadataLength = ConstU32<1024>;
+    type MaxDidLength = ConstU32<256>;
+    type FreezeOrigin = frame_system::EnsureRoot<AccountId>;
+    type SelfSuspendCooldown = ConstU64<10>;
+    type MaxAttestations = ConstU32<16>;
+    type BaseTrustScore = ConstU32<100>;
+    type MaxTrustScore = ConstU32<1_000>;
+    type AttestationWeight = AttestationWeight;
+    type AttestationHalfLife = ConstU64<100_800>;
+    type TrustScoreCacheTtl = ConstU64<600>;
+    type RegistrarOrigin = frame_system::EnsureRoot<AccountId>;
+    type OnTransition = ComplianceLog;
+}
+
+parameter_types! {
+    pub const AttestationWeight: Perbill = Perbill::from_percent(10);
+}
+
+parameter_types! {
+    pub const DaoPalletId: PalletId = PalletId(*b"py/tgdao");
+    pub const DaoMaxCallWeight: Weight = Weight::from_parts(1_000_000_000_000, 1024 * 1024);
+    pub const DaoAutoCloseWeight: Weight = Weight::from_parts(1_000_000_000, 64 * 1024);
+    pub const DaoMemberQuorum: Perbill = Perbill::from_percent(50);
+    pub const DaoVoteUnit: Balance = 1_000;
+}
+
+impl pallet_dao::Config for Runtime {
+    type RuntimeEvent = RuntimeEvent;
+    type Currency = Balances;
+    type MaxTitleLength = ConstU32<256>;
+    type MaxDescriptionLength = ConstU32<2048>;
+    type MinVotingPeriod = ConstU64<10>;
+    type MaxVotingPeriod = ConstU64<1000>;
+    type ProposalDeposit = ConstU128<1000>;
+    type MaxProposalsPerBlock = ConstU32<16>;
+    type MinQuorum = ConstU64<2>;
+    type OffchainAutoClose = ConstBool<true>;
+    type UnsignedPriority = ConstU64<100>;
+    type MaxReceiptsPerAccount = ConstU32<64>;
+    type AdminOrigin = frame_system::EnsureRoot<AccountId>;
+    type MaxVouchersPerProposal = ConstU32<256>;
+    type Parameters = (Ledger, Dao);
+    type MaxDelegationDepth = ConstU32<8>;
+    type MaxDelegators = ConstU32<64>;
+    type OnTransition = ComplianceLog;
+    type SlashRejectedDeposits = ConstBool<true>;
+    type MinRefundApproval = ConstU32<20>;
+    // No treasury in this runtime, so slashed deposits are burned
+    type Slashed = ();
+    type RuntimeCall = RuntimeCall;
+    type ProposalOrigin = pallet_dao::PotOrigin<Runtime>;
+    type PalletId = DaoPalletId;
+    type MaxCallSize = ConstU32<1024>;
+    type MaxCallWeight = DaoMaxCallWeight;
+    type AutoCloseWeight = DaoAutoCloseWeight;
+    type MembershipOrigin = frame_system::EnsureRoot<AccountId>;
+    // Open governance: any account votes
+    type MembersOnly = ConstBool<false>;
+    type MemberQuorum = DaoMemberQuorum;
+    type VoteUnit = DaoVoteUnit;
+    type WeightInfo = pallet_dao::weights::SubstrateWeight<Runtime>;
+}
+
+/// Account granted `AuditRead` in the smoke runtime
+pub const AUDITOR: AccountId = 1;
+
+/// Capability source of the smoke runtime: only `AUDITOR` may read for audits
+pub struct Auditors;
+
+impl CapabilityCheck<AccountId> for Auditors {
+    fn has_capability(who: &AccountId, capability: Capability) -> bool {
+        *who == AUDITOR && capability == Capability::AuditRead
+    }
+}
+
+impl pallet_access_log::Config for Runtime {
+    type RuntimeEvent = RuntimeEvent;
+    type Capabilities = Auditors;
+    type MaxLogEntries = ConstU32<100>;
+}
+
+impl pallet_compliance_log::Config for Runtime {
+    type RuntimeEvent = RuntimeEvent;
+    // 6 second blocks
+    type BlocksPerDay = ConstU64<14_400>;
+    type MaxRecordsPerDay = ConstU32<1024>;
+    // Seven years
+    type RetentionDays = ConstU32<2_557>;
+    type WeightInfo = pallet_compliance_log::weights::SubstrateWeight<Runtime>;
+}
+
+/// Randomness of contracts in the smoke runtime: the parent hash mixed with the subject.
+/// Predictable, so never to be used by a production runtime.
+pub struct ParentHashRandomness;
+
+impl Randomness<H256, BlockNumber> for ParentHashRandomness {
+    fn random(subject: &[u8]) -> (H256, BlockNumber) {
+        let seed = BlakeTwo256::hash_of(&(subject, System::parent_hash()));
+        (seed, System::block_number())
+    }
+}
+
+/// Price of contract weight in the smoke runtime: one unit of balance per unit of ref time
+pub struct RefTimePrice;
+
+impl Convert<Weight, Balance> for RefTimePrice {
+    fn convert(weight: Weight) -> Balance {
+        weight.ref_time().into()
+    }
+}
+
+parameter_types! {
+    pub ContractsSchedule: pallet_contracts::Schedule<Runtime> = Default::default();
+    pub const ContractsDepositPerItem: Balance = 100;
+    pub const ContractsDepositPerByte: Balance = 1;
+    pub const ContractsDefaultDepositLimit: Balance = 10_000;
+    pub const ContractsCodeHashLockupDepositPercent: Perbill = Perbill::from_percent(30);
+}
+
+impl pallet_contracts::Config for Runtime {
+    type Time = Timestamp;
+    type Randomness = ParentHashRandomness;
+    type Currency = Balances;
+    type RuntimeEvent = RuntimeEvent;
+    type RuntimeCall = RuntimeCall;
+    // Contracts dispatch no runtime calls
+    type CallFilter = Nothing;
+    type WeightPrice = RefTimePrice;
+    type WeightInfo = pallet_contracts::weights::SubstrateWeight<Runtime>;
+    type ChainExtension = DidExtension;
+    type Schedule = ContractsSchedule;
+    type CallStack = [pallet_contracts::Frame<Self>; 5];
+    type DepositPerByte = ContractsDepositPerByte;
+    type DefaultDepositLimit = ContractsDefaultDepositLimit;
+    type DepositPerItem = ContractsDepositPerItem;
+    type CodeHashLockupDepositPercent = ContractsCodeHashLockupDepositPercent;
+    type AddressGenerator = pallet_contracts::DefaultAddressGenerator;
+    type MaxCodeLen = ConstU32<{ 123 * 1024 }>;
+    type MaxStorageKeyLen = ConstU32<128>;
+    type UnsafeUnstableInterface = ConstBool<false>;
+    type MaxDebugBufferLen = ConstU32<{ 2 * 1024 * 1024 }>;
+    type RuntimeHoldReason = RuntimeHoldReason;
+    type Migrations = ();
+    type MaxDelegateDependencies = ConstU32<32>;
+    type Debug = ();
+    type Environment = ();
+    type Xcm = ();
+}
+
+/// Features of this runtime, as its `TidygenApi::features` would return them: the
+/// `feature_bits()` of every pallet with `Config` switches
+pub fn features() -> FeatureFlags {
+    Ledger::feature_bits() | Dao::feature_bits()
+}
+
+/// Initial balance of every endowed account
+pub const INITIAL_BALANCE: Balance = 1_000_000;
+
+/// Externalities with accounts 1 to 5 endowed, at block 1 so events are recorded
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    let storage = RuntimeGenesisConfig {
+        balances: pallet_balances::GenesisConfig {
+            balances: (1..=5).map(|who| (who, INITIAL_BALANCE)).collect(),
+        },
+        ..Default::default()
+    }
+    .build_storage()
+    .unwrap();
+
+    let mut ext: sp_io::TestExternalities = storage.into();
+    ext.execute_with(|| System::set_block_number(1));
+    ext
+}
@@ -0,0 +1,97 @@
+//! Status transitions of the ledger, DAO and DID pallets reach the compliance log through
+//! their `OnTransition` handlers.
+
+use frame_support::assert_ok;
+use pallet_dao::ProposalStatus;
+use pallet_did::DidStatus;
+use pallet_ledger::InvoiceStatus;
+use tidygen_primitives::transitions::{status_index, TransitionSubject};
+use tidygen_runtime_smoke::*;
+
+const CREATOR: AccountId = 1;
+const CLIENT: AccountId = 2;
+
+/// Records of `day` as (subject, old, new)
+fn day(day: u32) -> Vec<(TransitionSubject<AccountId>, u8, u8)> {
+    ComplianceLog::get_day(day, 0, u32::MAX)
+        .into_iter()
+        .map(|record| (record.subject, record.old, record.new))
+        .collect()
+}
+
+/// Digest of `day` recomputed from its records, as an archive would
+fn recomputed_digest(day: u32) -> [u8; 32] {
+    ComplianceLog::get_day(day, 0, u32::MAX)
+        .iter()
+        .fold([0u8; 32], |digest, record| ComplianceLog::next_digest(&digest, record))
+}
+
+#[test]
+fn transitions_of_three_pallets_share_a_day() {
+    new_test_ext().execute_with(|| {
+        // Invoice cancelled by its creator
+        assert_ok!(Did::register_did(
+            RuntimeOrigin::signed(CLIENT),
+            CLIENT,
+            vec![7u8; 32],
+            b"{}".to_vec()
+        ));
+        assert_ok!(Ledger::create_invoice(
+            RuntimeOrigin::signed(CREATOR),
+            CLIENT,
+            500,
+            0,
+            0,
+            b"INV-1".to_vec(),
+            None,
+            None,
+            vec![],
+            None
+        ));
+        assert_ok!(Ledger::cancel_invoice(RuntimeOrigin::signed(CREATOR), CLIENT, 0));
+
+        // Proposal cancelled by its proposer
+        assert_ok!(Dao::create_proposal(
+            RuntimeOrigin::signed(CREATOR),
+            b"Proposal".to_vec(),
+            b"Description".to_vec(),
+            None
+        ));
+        assert_ok!(Dao::cancel_proposal(RuntimeOrigin::signed(CREATOR), 0));
+
+        // DID suspended by its owner, a few hundred blocks later the same day
+        System::set_block_number(500);
+        assert_ok!(Did::self_suspend(RuntimeOrigin::signed(CLIENT)));
+
+        assert_eq!(ComplianceLog::current_day(), 0);
+        assert_eq!(
+            day(0),
+            vec![
+                (
+                    TransitionSubject::Invoice(CLIENT, 0),
+                    status_index(&InvoiceStatus::Pending),
+                    status_index(&InvoiceStatus::Cancelled),
+                ),
+                (
+                    TransitionSubject::Proposal(0),
+                    status_index(&ProposalStatus::Active),
+                    status_index(&ProposalStatus::Cancelled),
+                ),
+                (
+                    TransitionSubject::Did(CLIENT),
+                    status_index(&DidStatus::Active),
+                    status_index(&DidStatus::Suspended),
+                ),
+            ]
+        );
+        let digest = ComplianceLog::day_digest(0).expect("day 0 has records");
+        assert_eq!(digest, recomputed_digest(0));
+
+        // A transition on the next day leaves the digest of day 0 as archived
+        System::set_block_number(14_400);
+        assert_ok!(Did::revoke_did(RuntimeOrigin::signed(CLIENT), CLIENT));
+        assert_eq!(day(1).len(), 1);
+        assert_eq!(ComplianceLog::day_digest(0), Some(digest));
+        assert_eq!(day(0).len(), 3);
+    });
+}
@@ -0,0 +1,58 @@
+//! The chain extension answers the DID queries of contracts from the DID pallet.
+
+use codec::Decode;
+use frame_support::assert_ok;
+use tidygen_runtime_smoke::{
+    chain_extension::{GET_DID_IDENTIFIER, IS_DID_ACTIVE},
+    *,
+};
+
+const HOLDER: AccountId = 2;
+
+/// Answer of the extension's function `func_id` about `account`, decoded as `A`
+fn answer<A: Decode>(func_id: u16, account: AccountId) -> A {
+    let output = DidExtension::answer::<Runtime>(func_id, &account).expect("known function");
+    A::decode(&mut &output[..]).expect("answer decodes")
+}
+
+#[test]
+fn extension_reports_whether_a_did_is_active() {
+    new_test_ext().execute_with(|| {
+        assert!(!answer::<bool>(IS_DID_ACTIVE, HOLDER));
+
+        assert_ok!(Did::register_did(
+            RuntimeOrigin::signed(HOLDER),
+            HOLDER,
+            vec![7u8; 32],
+            b"{}".to_vec()
+        ));
+        assert!(answer::<bool>(IS_DID_ACTIVE, HOLDER));
+
+        assert_ok!(Did::self_suspend(RuntimeOrigin::signed(HOLDER)));
+        assert!(!answer::<bool>(IS_DID_ACTIVE, HOLDER));
+    });
+}
+
+#[test]
+fn extension_returns_the_did_identifier() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(answer::<Option<Vec<u8>>>(GET_DID_IDENTIFIER, HOLDER), None);
+
+        assert_ok!(Did::register_did(
+            RuntimeOrigin::signed(HOLDER),
+            HOLDER,
+            vec![7u8; 32],
+            b"{}".to_vec()
+        ));
+        let identifier = Did::get_did(&HOLDER).unwrap().did_identifier.into_inner();
+        assert!(identifier.starts_with(b"did:"));
+        assert_eq!(answer::<Option<Vec<u8>>>(GET_DID_IDENTIFIER, HOLDER), Some(identifier));
+    });
+}
+
+#[test]
+fn extension_rejects_unknown_functions() {
+    new_test_ext().execute_with(|| {
+        assert!(DidExtension::answer::<Runtime>(3, &HOLDER).is_err());
+    });
+}
@@ -0,0 +1,93 @@
+//! The ledger only invoices clients with an active DID, as reported by the DID pallet.
+
+use frame_support::{assert_noop, assert_ok, dispatch::DispatchResultWithPostInfo};
+use pallet_ledger::Error;
+use tidygen_runtime_smoke::*;
+
+const CREATOR: AccountId = 1;
+const CLIENT: AccountId = 2;
+
+fn register_did(who: AccountId) {
+    assert_ok!(Did::register_did(
+        RuntimeOrigin::signed(who),
+        who,
+        vec![7u8; 32],
+        b"{}".to_vec()
+    ));
+}
+
+fn invoice(client: AccountId, metadata: &[u8]) -> DispatchResultWithPostInfo {
+    Ledger::create_invoice(
+        RuntimeOrigin::signed(CREATOR),
+        client,
+        500,
+        0,
+        0,
+        metadata.to_vec(),
+        None,
+        None,
+        vec![],
+        None,
+    )
+}
+
+#[test]
+fn clients_without_a_did_cannot_be_invoiced() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(invoice(CLIENT, b"INV-1"), Error::<Runtime>::ClientHasNoActiveDid);
+        assert_noop!(
+            Ledger::create_invoice_with_ref(
+                RuntimeOrigin::signed(CREATOR),
+                CLIENT,
+                500,
+                b"INV-1".to_vec(),
+                None,
+                b"REF-1".to_vec()
+            ),
+            Error::<Runtime>::ClientHasNoActiveDid
+        );
+
+        register_did(CLIENT);
+        assert_ok!(invoice(CLIENT, b"INV-1"));
+        assert_eq!(Ledger::get_client_invoices(&CLIENT).len(), 1);
+
+        // The creator needs no DID of its own, but every client in a batch does
+        assert_noop!(
+            Ledger::batch_create_invoices(
+                RuntimeOrigin::signed(CREATOR),
+                vec![(CLIENT, 100, b"INV-2".to_vec()), (3, 100, b"INV-3".to_vec())]
+            ),
+            Error::<Runtime>::ClientHasNoActiveDid
+        );
+    });
+}
+
+#[test]
+fn revoked_did_blocks_new_invoices_but_not_payments() {
+    new_test_ext().execute_with(|| {
+        register_did(CLIENT);
+        assert_ok!(invoice(CLIENT, b"INV-1"));
+
+        assert_ok!(Did::revoke_did(RuntimeOrigin::signed(CLIENT), CLIENT));
+        assert_noop!(invoice(CLIENT, b"INV-2"), Error::<Runtime>::ClientHasNoActiveDid);
+
+        // The invoice issued before the revocation can still be settled
+        assert_ok!(Ledger::record_payment(RuntimeOrigin::signed(CLIENT), CLIENT, 0, 500));
+        assert_eq!(
+            Ledger::get_invoice(&CLIENT, 0).unwrap().status,
+            pallet_ledger::InvoiceStatus::Paid
+        );
+    });
+}
+
+#[test]
+fn suspended_did_blocks_new_invoices_until_lifted() {
+    new_test_ext().execute_with(|| {
+        register_did(CLIENT);
+        assert_ok!(Did::self_suspend(RuntimeOrigin::signed(CLIENT)));
+        assert_noop!(invoice(CLIENT, b"INV-1"), Error::<Runtime>::ClientHasNoActiveDid);
+
+        assert_ok!(Did::unsuspend_did(RuntimeOrigin::signed(CLIENT), CLIENT));
+        assert_ok!(invoice(CLIENT, b"INV-1"));
+    });
+}
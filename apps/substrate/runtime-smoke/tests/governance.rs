@@ -0,0 +1,97 @@
+//! DAO proposals change the parameters of other pallets through the runtime's
+//! `ParameterHandler` chain, and dispatch runtime calls as the DAO's own account.
+
+use frame_support::assert_ok;
+use pallet_dao::VoteKind;
+use tidygen_primitives::governance::ParameterChange;
+use tidygen_runtime_smoke::*;
+
+const PROPOSER: AccountId = 1;
+
+/// Propose `change`, vote it through and execute it once voting ended
+fn enact(change: ParameterChange) {
+    assert_ok!(Dao::create_parameter_proposal(
+        RuntimeOrigin::signed(PROPOSER),
+        b"Change a parameter".to_vec(),
+        b"Description".to_vec(),
+        None,
+        change
+    ));
+    let proposal_id = Dao::proposal_count() - 1;
+    assert_ok!(Dao::vote(RuntimeOrigin::signed(2), proposal_id, VoteKind::Aye));
+    assert_ok!(Dao::vote(RuntimeOrigin::signed(3), proposal_id, VoteKind::Aye));
+
+    System::set_block_number(System::block_number() + 10);
+    assert_ok!(Dao::execute_proposal(RuntimeOrigin::signed(PROPOSER), proposal_id));
+}
+
+#[test]
+fn governance_changes_a_ledger_parameter() {
+    new_test_ext().execute_with(|| {
+        assert!(Ledger::normalize_text());
+
+        let change = ParameterChange::LedgerTextNormalization(false);
+        enact(change.clone());
+
+        assert!(!Ledger::normalize_text());
+        System::assert_has_event(
+            pallet_ledger::Event::<Runtime>::TextNormalizationSet { enabled: false }.into(),
+        );
+        System::assert_last_event(
+            pallet_dao::Event::<Runtime>::ParameterChanged { proposal_id: 0, change }.into(),
+        );
+        // The DAO's own switch is untouched
+        assert!(Dao::normalize_text());
+    });
+}
+
+#[test]
+fn each_change_reaches_the_pallet_owning_it() {
+    new_test_ext().execute_with(|| {
+        enact(ParameterChange::DaoTextNormalization(false));
+        assert!(!Dao::normalize_text());
+        assert!(Ledger::normalize_text());
+
+        enact(ParameterChange::LedgerTextNormalization(false));
+        assert!(!Ledger::normalize_text());
+        System::assert_last_event(
+            pallet_dao::Event::<Runtime>::ParameterChanged {
+                proposal_id: 1,
+                change: ParameterChange::LedgerTextNormalization(false),
+            }
+            .into(),
+        );
+    });
+}
+
+#[test]
+fn call_proposal_pays_from_the_dao_pot() {
+    new_test_ext().execute_with(|| {
+        let pot = Dao::account_id();
+        assert_ok!(Balances::transfer_allow_death(RuntimeOrigin::signed(4), pot, 5_000));
+        let call = RuntimeCall::Balances(pallet_balances::Call::transfer_allow_death {
+            dest: 5,
+            value: 2_000,
+        });
+        let before = Balances::free_balance(5);
+
+        assert_ok!(Dao::create_call_proposal(
+            RuntimeOrigin::signed(PROPOSER),
+            b"Pay a contributor".to_vec(),
+            b"Description".to_vec(),
+            None,
+            Box::new(call)
+        ));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(2), 0, VoteKind::Aye));
+        assert_ok!(Dao::vote(RuntimeOrigin::signed(3), 0, VoteKind::Aye));
+        System::set_block_number(System::block_number() + 10);
+        assert_ok!(Dao::execute_proposal(RuntimeOrigin::signed(PROPOSER), 0));
+
+        assert_eq!(Balances::free_balance(pot), 3_000);
+        assert_eq!(Balances::free_balance(5), before + 2_000);
+        assert_eq!(
+            Dao::get_proposal_details(0).unwrap().status,
+            pallet_dao::ProposalStatus::Executed
+        );
+    });
+}
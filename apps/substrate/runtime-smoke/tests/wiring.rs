@@ -0,0 +1,211 @@
+//! Dispatches one extrinsic per pallet of the smoke runtime and checks that the resulting
+//! event survives the `RuntimeEvent` encoding.
+
+use codec::{Decode, Encode};
+use frame_support::{assert_ok, traits::PalletInfoAccess};
+use pallet_access_log::ResourceRef;
+use tidygen_primitives::features::FeatureFlags;
+use tidygen_runtime_smoke::*;
+
+/// Register an active DID for invoice `client`, as the ledger requires
+fn register_client_did(client: AccountId) {
+    assert_ok!(Did::register_did(
+        RuntimeOrigin::signed(client),
+        client,
+        vec![7u8; 32],
+        b"{}".to_vec()
+    ));
+}
+
+/// Last deposited event, after checking that it decodes back from its SCALE encoding
+fn last_event_round_trip() -> RuntimeEvent {
+    let event = System::events().pop().expect("an event was deposited").event;
+    let encoded = event.encode();
+    let decoded = RuntimeEvent::decode(&mut &encoded[..]).expect("event decodes");
+    assert_eq!(decoded, event);
+    decoded
+}
+
+#[test]
+fn pallet_indices_are_fixed() {
+    assert_eq!(<System as PalletInfoAccess>::index(), 0);
+    assert_eq!(<Timestamp as PalletInfoAccess>::index(), 1);
+    assert_eq!(<Balances as PalletInfoAccess>::index(), 2);
+    assert_eq!(<Ledger as PalletInfoAccess>::index(), 10);
+    assert_eq!(<TidygenLedger as PalletInfoAccess>::index(), 11);
+    assert_eq!(<Did as PalletInfoAccess>::index(), 12);
+    assert_eq!(<Dao as PalletInfoAccess>::index(), 13);
+    assert_eq!(<AccessLog as PalletInfoAccess>::index(), 14);
+    assert_eq!(<ComplianceLog as PalletInfoAccess>::index(), 15);
+    assert_eq!(<Contracts as PalletInfoAccess>::index(), 16);
+}
+
+#[test]
+fn event_encoding_starts_with_pallet_index() {
+    new_test_ext().execute_with(|| {
+        register_client_did(2);
+        assert_ok!(Ledger::create_invoice(
+            RuntimeOrigin::signed(1),
+            2,
+            500,
+            0,
+            0,
+            b"INV-1".to_vec(),
+            None,
+            None,
+            vec![],
+            None
+        ));
+        let encoded = last_event_round_trip().encode();
+        assert_eq!(usize::from(encoded[0]), <Ledger as PalletInfoAccess>::index());
+    });
+}
+
+#[test]
+fn timestamp_and_balances_are_wired() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Timestamp::set(RuntimeOrigin::none(), 6_000));
+        assert_eq!(Timestamp::get(), 6_000);
+
+        assert_ok!(Balances::transfer_keep_alive(RuntimeOrigin::signed(1), 2, 100));
+        assert!(matches!(
+            last_event_round_trip(),
+            RuntimeEvent::Balances(pallet_balances::Event::Transfer { from: 1, to: 2, amount: 100 })
+        ));
+    });
+}
+
+#[test]
+fn ledger_is_wired() {
+    new_test_ext().execute_with(|| {
+        register_client_did(2);
+        assert_ok!(Ledger::create_invoice(
+            RuntimeOrigin::signed(1),
+            2,
+            500,
+            0,
+            0,
+            b"INV-1".to_vec(),
+            None,
+            None,
+            vec![],
+            None
+        ));
+        assert!(matches!(
+            last_event_round_trip(),
+            RuntimeEvent::Ledger(pallet_ledger::Event::InvoiceHashStored { invoice_id: 0, .. })
+        ));
+    });
+}
+
+#[test]
+fn tidygen_ledger_is_wired() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(TidygenLedger::create_ledger_entry(
+            RuntimeOrigin::signed(1),
+            b"invoice".to_vec(),
+            [1u8; 32],
+            None
+        ));
+        assert!(matches!(
+            last_event_round_trip(),
+            RuntimeEvent::TidygenLedger(pallet_tidygen_ledger::Event::LedgerEntryCreated {
+                entry_id: 0,
+                creator: 1,
+                ..
+            })
+        ));
+    });
+}
+
+#[test]
+fn did_is_wired() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Did::register_did(
+            RuntimeOrigin::signed(1),
+            1,
+            vec![7u8; 32],
+            b"{}".to_vec()
+        ));
+        assert!(matches!(
+            last_event_round_trip(),
+            RuntimeEvent::Did(pallet_did::Event::DidRegistered { account: 1, .. })
+        ));
+    });
+}
+
+#[test]
+fn dao_is_wired() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Dao::create_proposal(
+            RuntimeOrigin::signed(1),
+            b"Budget".to_vec(),
+            b"Approve the quarterly budget".to_vec(),
+            None
+        ));
+        assert!(matches!(
+            last_event_round_trip(),
+            RuntimeEvent::Dao(pallet_dao::Event::ProposalCreated {
+                proposal_id: 0,
+                proposer: 1,
+                ..
+            })
+        ));
+    });
+}
+
+#[test]
+fn access_log_is_wired() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(AccessLog::log_access(
+            RuntimeOrigin::signed(AUDITOR),
+            ResourceRef::Invoice(2, 0)
+        ));
+        assert!(matches!(
+            last_event_round_trip(),
+            RuntimeEvent::AccessLog(pallet_access_log::Event::ResourceAccessed {
+                accessor: AUDITOR,
+                resource: ResourceRef::Invoice(2, 0),
+                ..
+            })
+        ));
+    });
+}
+
+#[test]
+fn compliance_log_is_wired() {
+    new_test_ext().execute_with(|| {
+        register_client_did(2);
+        assert_ok!(Did::revoke_did(RuntimeOrigin::signed(2), 2));
+
+        // Day 0 falls out of the seven year retention
+        System::set_block_number(14_400 * 2_557);
+        assert_ok!(ComplianceLog::prune_day(RuntimeOrigin::signed(3), 0));
+        assert!(matches!(
+            last_event_round_trip(),
+            RuntimeEvent::ComplianceLog(pallet_compliance_log::Event::DayPruned {
+                day: 0,
+                records: 1,
+                pruned_by: 3,
+            })
+        ));
+    });
+}
+
+#[test]
+fn features_combine_the_pallet_switches() {
+    new_test_ext().execute_with(|| {
+        // Open creation, webhook, deposits and late fees in the ledger, auto-close and deposit
+        // slashing in the DAO; client acceptance is off
+        assert_eq!(
+            features(),
+            FeatureFlags::OPEN_INVOICE_CREATION |
+                FeatureFlags::INVOICE_WEBHOOK |
+                FeatureFlags::INVOICE_DEPOSITS |
+                FeatureFlags::LATE_FEES |
+                FeatureFlags::PROPOSAL_AUTO_CLOSE |
+                FeatureFlags::SPAM_DEPOSIT_SLASHING
+        );
+        assert!(!features().contains(FeatureFlags::INVOICE_ACCEPTANCE));
+    });
+}
@@ -0,0 +1,187 @@
+//! A `Currency` for mocks of pallets whose tests never move balances.
+//!
+//! A pallet's `Config` often asks for a `Currency` that its tests never touch, e.g. only to
+//! name the `Balance` type of an optional amount. Configuring `type Currency = ()` for it
+//! fails with trait-bound errors far from the cause as soon as the pallet requires more of
+//! the currency. [`NoopCurrency`] fills the slot without a `pallet_balances` instance in the
+//! mock. Every operation, reads included, panics with [`NOT_CONFIGURED`], so a test reaching
+//! a balance path fails with the fix instead of running against made-up balances.
+
+use core::marker::PhantomData;
+use frame_support::traits::{
+    tokens::Balance as BalanceT, BalanceStatus, Currency, ExistenceRequirement,
+    ReservableCurrency, SignedImbalance, WithdrawReasons,
+};
+use sp_runtime::{
+    traits::MaybeSerializeDeserialize, DispatchError, DispatchResult, FixedPointOperand,
+};
+
+/// What every [`NoopCurrency`] panic asks for
+pub const NOT_CONFIGURED: &str = "configure pallet_balances in this mock to test balance paths";
+
+/// `Currency` and `ReservableCurrency` that panic on use, with `u128` balances unless
+/// another `Balance` is given.
+///
+/// ```ignore
+/// impl pallet::Config for Test {
+///     type Currency = NoopCurrency;
+/// }
+/// ```
+pub struct NoopCurrency<Balance = u128>(PhantomData<Balance>);
+
+/// Fail the test that reached `operation`
+#[track_caller]
+fn unconfigured(operation: &str) -> ! {
+    panic!("NoopCurrency::{operation} called: {NOT_CONFIGURED}")
+}
+
+impl<AccountId, Balance> Currency<AccountId> for NoopCurrency<Balance>
+where
+    Balance: BalanceT + MaybeSerializeDeserialize + FixedPointOperand,
+{
+    type Balance = Balance;
+    type PositiveImbalance = ();
+    type NegativeImbalance = ();
+
+    fn total_balance(_who: &AccountId) -> Balance {
+        unconfigured("total_balance")
+    }
+
+    fn can_slash(_who: &AccountId, _value: Balance) -> bool {
+        unconfigured("can_slash")
+    }
+
+    fn total_issuance() -> Balance {
+        unconfigured("total_issuance")
+    }
+
+    fn minimum_balance() -> Balance {
+        unconfigured("minimum_balance")
+    }
+
+    fn burn(_amount: Balance) -> Self::PositiveImbalance {
+        unconfigured("burn")
+    }
+
+    fn issue(_amount: Balance) -> Self::NegativeImbalance {
+        unconfigured("issue")
+    }
+
+    fn free_balance(_who: &AccountId) -> Balance {
+        unconfigured("free_balance")
+    }
+
+    fn ensure_can_withdraw(
+        _who: &AccountId,
+        _amount: Balance,
+        _reasons: WithdrawReasons,
+        _new_balance: Balance,
+    ) -> DispatchResult {
+        unconfigured("ensure_can_withdraw")
+    }
+
+    fn transfer(
+        _source: &AccountId,
+        _dest: &AccountId,
+        _value: Balance,
+        _existence_requirement: ExistenceRequirement,
+    ) -> DispatchResult {
+        unconfigured("transfer")
+    }
+
+    fn slash(_who: &AccountId, _value: Balance) -> (Self::NegativeImbalance, Balance) {
+        unconfigured("slash")
+    }
+
+    fn deposit_into_existing(
+        _who: &AccountId,
+        _value: Balance,
+    ) -> Result<Self::PositiveImbalance, DispatchError> {
+        unconfigured("deposit_into_existing")
+    }
+
+    fn deposit_creating(_who: &AccountId, _value: Balance) -> Self::PositiveImbalance {
+        unconfigured("deposit_creating")
+    }
+
+    fn withdraw(
+        _who: &AccountId,
+        _value: Balance,
+        _reasons: WithdrawReasons,
+        _liveness: ExistenceRequirement,
+    ) -> Result<Self::NegativeImbalance, DispatchError> {
+        unconfigured("withdraw")
+    }
+
+    fn make_free_balance_be(
+        _who: &AccountId,
+        _balance: Balance,
+    ) -> SignedImbalance<Balance, Self::PositiveImbalance> {
+        unconfigured("make_free_balance_be")
+    }
+}
+
+impl<AccountId, Balance> ReservableCurrency<AccountId> for NoopCurrency<Balance>
+where
+    Balance: BalanceT + MaybeSerializeDeserialize + FixedPointOperand,
+{
+    fn can_reserve(_who: &AccountId, _value: Balance) -> bool {
+        unconfigured("can_reserve")
+    }
+
+    fn slash_reserved(_who: &AccountId, _value: Balance) -> (Self::NegativeImbalance, Balance) {
+        unconfigured("slash_reserved")
+    }
+
+    fn reserved_balance(_who: &AccountId) -> Balance {
+        unconfigured("reserved_balance")
+    }
+
+    fn reserve(_who: &AccountId, _value: Balance) -> DispatchResult {
+        unconfigured("reserve")
+    }
+
+    fn unreserve(_who: &AccountId, _value: Balance) -> Balance {
+        unconfigured("unreserve")
+    }
+
+    fn repatriate_reserved(
+        _slashed: &AccountId,
+        _beneficiary: &AccountId,
+        _value: Balance,
+        _status: BalanceStatus,
+    ) -> Result<Balance, DispatchError> {
+        unconfigured("repatriate_reserved")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[should_panic(
+        expected = "NoopCurrency::transfer called: \
+                    configure pallet_balances in this mock to test balance paths"
+    )]
+    fn balance_use_fails_with_the_fix() {
+        let _ = <NoopCurrency as Currency<u64>>::transfer(
+            &1,
+            &2,
+            10,
+            ExistenceRequirement::KeepAlive,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "NoopCurrency::reserve called")]
+    fn reserving_fails_too() {
+        let _ = <NoopCurrency<u64> as ReservableCurrency<u64>>::reserve(&1, 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "NoopCurrency::free_balance called")]
+    fn reads_fail_rather_than_answer_zero() {
+        <NoopCurrency as Currency<u64>>::free_balance(&1);
+    }
+}
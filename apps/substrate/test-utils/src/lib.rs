@@ -0,0 +1,12 @@
+//! # TidyGen Test Utilities
+//!
+//! Helpers shared by the pallet test mocks. `std` only; pallets take this crate as a
+//! dev-dependency.
+//!
+//! ## Modules
+//!
+//! * `currency` - `NoopCurrency`, the `Currency` of mocks whose tests never move balances
+
+pub mod currency;
+
+pub use currency::NoopCurrency;